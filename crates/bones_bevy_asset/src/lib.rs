@@ -151,6 +151,7 @@ impl<'a, T: bevy_asset::Asset> std::ops::Deref for BevyAssets<'a, T> {
 impl<'a, T: bevy_asset::Asset> bones_lib::ecs::system::SystemParam for BevyAssets<'a, T> {
     type State = bones::AtomicResource<BevyWorld>;
     type Param<'s> = BevyAssets<'s, T>;
+    type Local = ();
 
     fn initialize(_world: &mut bones::World) {}
 
@@ -158,7 +159,7 @@ impl<'a, T: bevy_asset::Asset> bones_lib::ecs::system::SystemParam for BevyAsset
         world.resources.get::<BevyWorld>()
     }
 
-    fn borrow(state: &mut Self::State) -> Self::Param<'_> {
+    fn borrow<'s>(state: &'s mut Self::State, _local: &'s mut Self::Local) -> Self::Param<'s> {
         BevyAssets {
             cell: state.borrow(),
             _phantom: PhantomData,
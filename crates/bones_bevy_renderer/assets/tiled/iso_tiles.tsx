@@ -0,0 +1,4 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.9" tiledversion="1.9.2" name="iso_tiles" tilewidth="32" tileheight="32" tilecount="2" columns="2">
+ <image source="iso_tiles.png" width="64" height="32"/>
+</tileset>
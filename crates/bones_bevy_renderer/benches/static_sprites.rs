@@ -0,0 +1,95 @@
+//! Compares syncing 10 000 static and 100 dynamic sprites to syncing 10 100 dynamic sprites, to
+//! show that the cost of syncing a level is dominated by its dynamic sprites.
+
+use bevy::{asset::AssetPlugin, prelude::*, render::render_resource::Shader, window::WindowPlugin};
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[derive(Resource)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Create an app with 10 100 sprites, where all but the first 100 are static if `with_static` is
+/// `true`.
+fn setup_app(with_static: bool) -> App {
+    let mut world = bones::World::new();
+    world
+        .run_system(
+            move |mut entities: bones::ResMut<bones::Entities>,
+                  mut sprites: bones::CompMut<bones::Sprite>,
+                  mut transforms: bones::CompMut<bones::Transform>,
+                  mut statics: bones::CompMut<bones::Static>| {
+                for i in 0..10_100 {
+                    let entity = entities.create();
+                    sprites.insert(entity, bones::Sprite::default());
+                    transforms.insert(
+                        entity,
+                        bones::Transform::from_translation(Vec3::new(i as f32, 0.0, 0.0)),
+                    );
+                    if with_static && i >= 100 {
+                        statics.insert(entity, bones::Static);
+                    }
+                }
+            },
+        )
+        .unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin::default())
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<Shader>()
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .init_resource::<ClearColor>()
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .insert_resource(BonesWorld(world));
+
+    // Spawn the bevy sprites.
+    app.update();
+    app.update();
+    app
+}
+
+/// Move the dynamic sprites, like a game would every frame.
+fn move_dynamic_sprites(app: &mut App) {
+    let world = &mut app.world.resource_mut::<BonesWorld>().0;
+    world
+        .run_system(
+            |entities: bones::Res<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             statics: bones::Comp<bones::Static>| {
+                for (_, (mut transform, ())) in
+                    entities.iter_with((&mut transforms, bones::without(&statics)))
+                {
+                    transform.translation.y += 1.0;
+                }
+            },
+        )
+        .unwrap();
+}
+
+fn static_sprites(c: &mut Criterion) {
+    let mut group = c.benchmark_group("static_sprites");
+
+    for (name, with_static) in [("static", true), ("dynamic", false)] {
+        let mut app = setup_app(with_static);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                move_dynamic_sprites(&mut app);
+                app.update();
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, static_sprites);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+//! Shakes a bones camera that looks at a row of sprites.
+//!
+//! Press space to add trauma to the shake. The shake decays over time, and adding trauma again
+//! before it has settled makes it stronger, up to the maximum trauma.
+//!
+//! Run it with `cargo run --example camera_shake`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the image loaded, since the bones sprites only hold weak handles to it.
+#[derive(Resource)]
+struct TilesImage(#[allow(dead_code)] Handle<Image>);
+
+/// The trauma that is added to the shake every time space is pressed.
+const TRAUMA_PER_PRESS: f32 = 0.4;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .add_system(shake_camera)
+        .run();
+}
+
+/// Spawn the sprites, and a camera with a [`bones::CameraShake`].
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(TilesImage(asset_server.load("tiled/tiles.png")));
+
+    world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut sprites: bones::CompMut<bones::Sprite>,
+             mut cameras: bones::CompMut<bones::Camera>,
+             mut shakes: bones::CompMut<bones::CameraShake>| {
+                for i in 0..4 {
+                    let sprite = entities.create();
+                    sprites.insert(
+                        sprite,
+                        bones::Sprite {
+                            image: bones::Handle::new("tiled/tiles.png", None),
+                            rect: Some(bones::Rect::new(
+                                i as f32 * 16.0,
+                                0.0,
+                                (i + 1) as f32 * 16.0,
+                                16.0,
+                            )),
+                            ..default()
+                        },
+                    );
+                    transforms.insert(
+                        sprite,
+                        bones::Transform::from_translation(Vec3::new(
+                            (i as f32 - 1.5) * 24.0,
+                            0.0,
+                            0.0,
+                        )),
+                    );
+                }
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(100.0),
+                        ..default()
+                    },
+                );
+                shakes.insert(camera, bones::CameraShake::default());
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+            },
+        )
+        .unwrap();
+}
+
+/// Add trauma to every camera shake when space is pressed, then advance the bones [`bones::Time`]
+/// by the frame time of bevy and update the shakes.
+fn shake_camera(keys: Res<Input<KeyCode>>, time: Res<Time>, mut world: ResMut<BonesWorld>) {
+    let world = &mut world.0;
+
+    if keys.just_pressed(KeyCode::Space) {
+        world
+            .run_system(|mut shakes: bones::CompMut<bones::CameraShake>| {
+                for mut shake in shakes.iter_mut() {
+                    shake.add_trauma(TRAUMA_PER_PRESS);
+                    info!("Trauma: {:.2}", shake.trauma());
+                }
+            })
+            .unwrap();
+    }
+
+    let delta = time.delta();
+    world
+        .run_system(move |mut time: bones::ResMut<bones::Time>| {
+            time.set_next_delta(delta);
+            time.update();
+        })
+        .unwrap();
+    world.run_system(bones::camera_shake_system).unwrap();
+}
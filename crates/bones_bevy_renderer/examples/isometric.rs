@@ -0,0 +1,131 @@
+//! Loads an isometric Tiled map, with blocks that are taller than the tiles of its grid, and
+//! spawns it into a bones world.
+//!
+//! The blocks stand on the bottom corners of their cells, and the ones in front are drawn over the
+//! ones behind them. Click on a tile to log its position in the grid.
+//!
+//! Run it with `cargo run --example isometric --features tiled`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{tiled::TiledMap, BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+#[derive(Resource)]
+struct MapHandle(Handle<TiledMap>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(|mut commands: Commands, asset_server: Res<AssetServer>| {
+            commands.insert_resource(MapHandle(asset_server.load("tiled/iso.tmx")));
+        })
+        .add_system(spawn_map)
+        .add_system(pick_tile)
+        .run();
+}
+
+/// Spawn the map and a camera that looks at it, once the map is loaded.
+fn spawn_map(
+    mut done: Local<bool>,
+    map_handle: Res<MapHandle>,
+    maps: Res<Assets<TiledMap>>,
+    mut world: ResMut<BonesWorld>,
+) {
+    if *done {
+        return;
+    }
+    let Some(map) = maps.get(&map_handle.0) else {
+        return;
+    };
+    *done = true;
+
+    let map = map.clone();
+    world
+        .0
+        .run_system(
+            move |mut entities: bones::ResMut<bones::Entities>,
+                  mut transforms: bones::CompMut<bones::Transform>,
+                  mut tiles: bones::CompMut<bones::Tile>,
+                  mut tile_layers: bones::CompMut<bones::TileLayer>,
+                  mut cameras: bones::CompMut<bones::Camera>| {
+                map.spawn(
+                    bones::Transform::default(),
+                    &mut entities,
+                    &mut transforms,
+                    &mut tiles,
+                    &mut tile_layers,
+                );
+
+                // The diamond of the map is as wide and as tall as both of its sides together.
+                let map_size = (map.size.x + map.size.y) as f32 * map.tile_size / 2.0;
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(map_size.y * 1.5),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation((map_size / 2.0).extend(100.0)),
+                );
+            },
+        )
+        .unwrap();
+}
+
+/// Log the position of the tile of the floor under the cursor when the left mouse button is
+/// clicked.
+fn pick_tile(
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world: ResMut<BonesWorld>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    // Unproject the cursor from the normalized device coordinates of the camera.
+    let window_size = Vec2::new(window.width(), window.height());
+    let ndc = cursor / window_size * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+    let point = ndc_to_world.project_point3(ndc.extend(-1.0)).truncate();
+
+    world
+        .0
+        .run_system(
+            move |entities: bones::Res<bones::Entities>,
+                  transforms: bones::Comp<bones::Transform>,
+                  tile_layers: bones::Comp<bones::TileLayer>| {
+                let floor = entities.iter_with((&tile_layers, &transforms)).next();
+                if let Some((_, (layer, transform))) = floor {
+                    match layer.world_to_tile(transform, point) {
+                        Some(pos) => info!("Clicked on tile {pos}"),
+                        None => info!("Clicked outside of the map"),
+                    }
+                }
+            },
+        )
+        .unwrap();
+}
@@ -0,0 +1,116 @@
+//! Draws a dialog panel with a bones [`bones::NinePatchSprite`], which keeps the corners of its
+//! image sharp at any size.
+//!
+//! Hold the arrow keys to resize the panel, and press `T` to switch between stretching and tiling
+//! the edges and center of the image.
+//!
+//! Run it with `cargo run --example nine_patch`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the image loaded, since the bones nine-patches only hold weak handles to it.
+#[derive(Resource)]
+struct PanelImage(#[allow(dead_code)] Handle<Image>);
+
+/// How fast the panel is resized, in in-game pixels per second.
+const RESIZE_SPEED: f32 = 80.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .add_system(resize_panel)
+        .run();
+}
+
+/// Spawn the panel, and a camera that looks at it.
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(PanelImage(asset_server.load("ui/panel.png")));
+
+    world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut panels: bones::CompMut<bones::NinePatchSprite>,
+             mut cameras: bones::CompMut<bones::Camera>| {
+                let panel = entities.create();
+                panels.insert(
+                    panel,
+                    bones::NinePatchSprite {
+                        image: bones::Handle::new("ui/panel.png", None),
+                        borders: bones::NinePatchBorders::all(8.0),
+                        size: Vec2::new(96.0, 48.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(panel, bones::Transform::default());
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(200.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+            },
+        )
+        .unwrap();
+}
+
+/// Resize the panel with the arrow keys, and switch its mode with `T`.
+fn resize_panel(keys: Res<Input<KeyCode>>, time: Res<Time>, mut world: ResMut<BonesWorld>) {
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        direction.x += 1.0;
+    }
+    if keys.pressed(KeyCode::Down) {
+        direction.y -= 1.0;
+    }
+    if keys.pressed(KeyCode::Up) {
+        direction.y += 1.0;
+    }
+    let resize = direction * RESIZE_SPEED * time.delta_seconds();
+    let toggle_mode = keys.just_pressed(KeyCode::T);
+    if resize == Vec2::ZERO && !toggle_mode {
+        return;
+    }
+
+    world
+        .0
+        .run_system(move |mut panels: bones::CompMut<bones::NinePatchSprite>| {
+            for mut panel in panels.iter_mut() {
+                // Keep the panel at least as big as its borders, which are shrunk to fit otherwise.
+                panel.size = (panel.size + resize).max(Vec2::splat(16.0));
+                if toggle_mode {
+                    panel.mode = match panel.mode {
+                        bones::NinePatchMode::Stretch => bones::NinePatchMode::Tile,
+                        bones::NinePatchMode::Tile => bones::NinePatchMode::Stretch,
+                    };
+                    info!("Nine-patch mode: {:?}", panel.mode);
+                }
+            }
+        })
+        .unwrap();
+}
@@ -0,0 +1,138 @@
+//! Scrolls a bones camera over three repeated background layers with a [`bones::Parallax`], that
+//! move by 20%, 50%, and 80% of the camera's movement.
+//!
+//! Hold the left and right arrow keys to move the camera. The layers that follow the camera more
+//! look further away, and wrap around so that they never run out.
+//!
+//! Run it with `cargo run --example parallax`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the image loaded, since the bones sprites only hold weak handles to it.
+#[derive(Resource)]
+struct TilesImage(#[allow(dead_code)] Handle<Image>);
+
+/// The bones camera that is moved with the arrow keys.
+#[derive(Resource, Clone, Copy)]
+struct MainCamera(bones::Entity);
+
+/// How fast the camera moves, in in-game pixels per second.
+const CAMERA_SPEED: f32 = 120.0;
+
+/// The number of tiles that each layer is repeated by, which is enough to cover the screen while
+/// the camera scrolls.
+const LAYER_TILES: f32 = 24.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .add_system(move_camera)
+        .run();
+}
+
+/// Spawn the background layers and the camera.
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(TilesImage(asset_server.load("tiled/tiles.png")));
+
+    let camera = world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut sprites: bones::CompMut<bones::Sprite>,
+             mut parallaxes: bones::CompMut<bones::Parallax>,
+             mut cameras: bones::CompMut<bones::Camera>| {
+                // The closest layer is at the bottom of the screen, and follows the camera the
+                // least.
+                for (i, factor) in [0.2, 0.5, 0.8].into_iter().enumerate() {
+                    let x = i as f32 * 16.0;
+                    let layer = entities.create();
+                    sprites.insert(
+                        layer,
+                        bones::Sprite {
+                            image: bones::Handle::new("tiled/tiles.png", None),
+                            rect: Some(bones::Rect::new(x, 0.0, x + 16.0, 16.0)),
+                            custom_size: Some(Vec2::new(LAYER_TILES * 16.0, 16.0)),
+                            repeat: Some(Vec2::new(LAYER_TILES, 1.0)),
+                            ..default()
+                        },
+                    );
+                    transforms.insert(
+                        layer,
+                        bones::Transform::from_translation(Vec3::new(
+                            0.0,
+                            i as f32 * 20.0 - 20.0,
+                            -(i as f32),
+                        )),
+                    );
+                    parallaxes.insert(
+                        layer,
+                        bones::Parallax {
+                            repeat_x: true,
+                            ..bones::Parallax::new(Vec2::new(factor, 0.0))
+                        },
+                    );
+                }
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(100.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+
+                MainCamera(camera)
+            },
+        )
+        .unwrap();
+    commands.insert_resource(camera);
+}
+
+/// Move the camera with the arrow keys, then update the parallax of the layers.
+fn move_camera(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    camera: Res<MainCamera>,
+    mut world: ResMut<BonesWorld>,
+) {
+    let world = &mut world.0;
+
+    let mut direction = 0.0;
+    if keys.pressed(KeyCode::Left) {
+        direction -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        direction += 1.0;
+    }
+    let movement = direction * CAMERA_SPEED * time.delta_seconds();
+    let MainCamera(camera) = *camera;
+
+    world
+        .run_system(move |mut transforms: bones::CompMut<bones::Transform>| {
+            if let Some(mut transform) = transforms.get_mut(camera) {
+                transform.translation.x += movement;
+            }
+        })
+        .unwrap();
+    world.run_system(bones::parallax_system).unwrap();
+}
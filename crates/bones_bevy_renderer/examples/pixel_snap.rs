@@ -0,0 +1,140 @@
+//! Moves a zoomed in bones camera by fractions of a pixel over a row of static sprites, and a
+//! sprite that moves by fractions of a pixel too.
+//!
+//! Press `P` to toggle the [`bones::PixelSnap`] of the cameras and sprites. Without snapping, the
+//! pixel art shimmers as the sprites land between the pixels of the screen.
+//!
+//! Run it with `cargo run --example pixel_snap`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the image loaded, since the bones sprites only hold weak handles to it.
+#[derive(Resource)]
+struct TilesImage(#[allow(dead_code)] Handle<Image>);
+
+/// The bones entities that are moved every frame.
+#[derive(Resource, Clone, Copy)]
+struct Moving {
+    camera: bones::Entity,
+    sprite: bones::Entity,
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .add_system(toggle_pixel_snap)
+        .add_system(move_entities)
+        .run();
+}
+
+/// Create a bones sprite for one of the tiles in the tileset image.
+fn tile_sprite(tile: usize) -> bones::Sprite {
+    let x = tile as f32 * 16.0;
+    bones::Sprite {
+        image: bones::Handle::new("tiled/tiles.png", None),
+        rect: Some(bones::Rect::new(x, 0.0, x + 16.0, 16.0)),
+        ..default()
+    }
+}
+
+/// Spawn the sprites and the camera.
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(TilesImage(asset_server.load("tiled/tiles.png")));
+
+    let moving = world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut sprites: bones::CompMut<bones::Sprite>,
+             mut statics: bones::CompMut<bones::Static>,
+             mut cameras: bones::CompMut<bones::Camera>| {
+                // The ground never moves, so it is only synced again when the snapping changes.
+                for i in -8..8i32 {
+                    let ground = entities.create();
+                    sprites.insert(ground, tile_sprite(i.rem_euclid(4) as usize));
+                    statics.insert(ground, bones::Static);
+                    transforms.insert(
+                        ground,
+                        bones::Transform::from_translation(Vec3::new(
+                            i as f32 * 16.0 + 0.3,
+                            -16.0,
+                            0.0,
+                        )),
+                    );
+                }
+
+                let sprite = entities.create();
+                sprites.insert(sprite, tile_sprite(1));
+                transforms.insert(
+                    sprite,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+                );
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(64.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+
+                Moving { camera, sprite }
+            },
+        )
+        .unwrap();
+    commands.insert_resource(moving);
+}
+
+/// Toggle the snapping of the cameras and sprites when `P` is pressed.
+fn toggle_pixel_snap(keys: Res<Input<KeyCode>>, mut world: ResMut<BonesWorld>) {
+    if !keys.just_pressed(KeyCode::P) {
+        return;
+    }
+
+    world
+        .0
+        .run_system(|mut pixel_snap: bones::ResMut<bones::PixelSnap>| {
+            pixel_snap.cameras = !pixel_snap.cameras;
+            pixel_snap.sprites = !pixel_snap.sprites;
+            info!("Pixel snapping enabled: {}", pixel_snap.sprites);
+        })
+        .unwrap();
+}
+
+/// Sway the camera and the moving sprite back and forth by fractions of a pixel every frame.
+fn move_entities(time: Res<Time>, moving: Res<Moving>, mut world: ResMut<BonesWorld>) {
+    let elapsed = time.elapsed_seconds();
+    let Moving { camera, sprite } = *moving;
+
+    world
+        .0
+        .run_system(move |mut transforms: bones::CompMut<bones::Transform>| {
+            if let Some(mut transform) = transforms.get_mut(camera) {
+                transform.translation.x = (elapsed * 0.3).sin() * 40.0;
+            }
+            if let Some(mut transform) = transforms.get_mut(sprite) {
+                transform.translation.x = (elapsed * 0.5).cos() * 24.0;
+            }
+        })
+        .unwrap();
+}
@@ -0,0 +1,126 @@
+//! Draws a brick wall background that is 50 units wide and two bricks high, by repeating a 16x16
+//! brick image with [`bones::Sprite::repeat`].
+//!
+//! Hold the left and right arrow keys to change the width of the wall. The last repetition of the
+//! bricks is cut off when the width isn't a multiple of the image width.
+//!
+//! Run it with `cargo run --example repeat`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the image loaded, since the bones sprite only holds a weak handle to it.
+#[derive(Resource)]
+struct BricksImage(#[allow(dead_code)] Handle<Image>);
+
+/// The bones entity with the brick wall sprite.
+#[derive(Resource, Clone, Copy)]
+struct Background(bones::Entity);
+
+/// The size of the brick image, in pixels.
+const BRICK_SIZE: f32 = 16.0;
+
+/// The width that the background starts at, in in-game units.
+const BACKGROUND_WIDTH: f32 = 50.0;
+
+/// How fast the width of the background changes, in in-game units per second.
+const RESIZE_SPEED: f32 = 20.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .add_system(resize_background)
+        .run();
+}
+
+/// Spawn the background and the camera.
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(BricksImage(asset_server.load("bricks.png")));
+
+    let background = world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut sprites: bones::CompMut<bones::Sprite>,
+             mut cameras: bones::CompMut<bones::Camera>| {
+                // The background is anchored at its bottom left corner, so it grows to the right.
+                let background = entities.create();
+                sprites.insert(
+                    background,
+                    bones::Sprite {
+                        image: bones::Handle::new("bricks.png", None),
+                        repeat: Some(Vec2::new(BACKGROUND_WIDTH / BRICK_SIZE, 2.0)),
+                        anchor: bones::Anchor::BottomLeft,
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    background,
+                    bones::Transform::from_translation(Vec3::new(-25.0, -16.0, 0.0)),
+                );
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(64.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+
+                Background(background)
+            },
+        )
+        .unwrap();
+    commands.insert_resource(background);
+}
+
+/// Change the width of the background with the arrow keys.
+fn resize_background(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    background: Res<Background>,
+    mut world: ResMut<BonesWorld>,
+) {
+    let mut direction = 0.0;
+    if keys.pressed(KeyCode::Left) {
+        direction -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        direction += 1.0;
+    }
+    if direction == 0.0 {
+        return;
+    }
+    let change = direction * RESIZE_SPEED * time.delta_seconds() / BRICK_SIZE;
+    let Background(background) = *background;
+
+    world
+        .0
+        .run_system(move |mut sprites: bones::CompMut<bones::Sprite>| {
+            if let Some(mut sprite) = sprites.get_mut(background) {
+                if let Some(repeat) = &mut sprite.repeat {
+                    repeat.x = (repeat.x + change).max(0.0);
+                }
+            }
+        })
+        .unwrap();
+}
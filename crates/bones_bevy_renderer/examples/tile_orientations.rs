@@ -0,0 +1,99 @@
+//! Draws all eight orientations of a tile in one [`bones::TileLayer`], with a letter "F" tile that
+//! looks different in every one of them.
+//!
+//! The top row is the tile rotated clockwise by 0, 90, 180, and 270 degrees, and the bottom row is
+//! the same rotations of the tile flipped horizontally. The tile is flipped before it is rotated.
+//!
+//! Run it with `cargo run --example tile_orientations`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+/// Keeps the atlas loaded, since the bones tile layer only holds a weak handle to it.
+#[derive(Resource)]
+struct LetterAtlas(#[allow(dead_code)] Handle<TextureAtlas>);
+
+/// The path of the atlas with the letter tile, in the assets folder.
+const ATLAS_PATH: &str = "orientations/letter.atlas.yaml";
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(setup)
+        .run();
+}
+
+/// Spawn the tile layer and the camera.
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut world: ResMut<BonesWorld>) {
+    commands.insert_resource(LetterAtlas(asset_server.load(ATLAS_PATH)));
+
+    world
+        .0
+        .run_system(
+            |mut entities: bones::ResMut<bones::Entities>,
+             mut transforms: bones::CompMut<bones::Transform>,
+             mut tiles: bones::CompMut<bones::Tile>,
+             mut tile_layers: bones::CompMut<bones::TileLayer>,
+             mut cameras: bones::CompMut<bones::Camera>| {
+                let mut layer = bones::TileLayer::new(
+                    UVec2::new(4, 2),
+                    Vec2::splat(16.0),
+                    bones::Handle::new(ATLAS_PATH, None),
+                );
+                let rotations = [
+                    bones::TileRotation::None,
+                    bones::TileRotation::Clockwise90,
+                    bones::TileRotation::Clockwise180,
+                    bones::TileRotation::Clockwise270,
+                ];
+                // The first row of the layer is drawn at the bottom.
+                for (y, flip_x) in [true, false].into_iter().enumerate() {
+                    for (x, rotation) in rotations.into_iter().enumerate() {
+                        let tile = entities.create();
+                        tiles.insert(
+                            tile,
+                            bones::Tile {
+                                idx: 0,
+                                flip_x,
+                                rotation,
+                                ..default()
+                            },
+                        );
+                        layer.set(UVec2::new(x as u32, y as u32), Some(tile));
+                    }
+                }
+                let layer_entity = entities.create();
+                tile_layers.insert(layer_entity, layer);
+                transforms.insert(
+                    layer_entity,
+                    bones::Transform::from_translation(Vec3::new(-32.0, -16.0, 0.0)),
+                );
+
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(64.0),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                );
+            },
+        )
+        .unwrap();
+}
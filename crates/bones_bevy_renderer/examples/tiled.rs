@@ -0,0 +1,94 @@
+//! Loads a Tiled map with the asset server, and spawns its tile layers into a bones world.
+//!
+//! Run it with `cargo run --example tiled --features tiled`.
+
+use bevy::prelude::*;
+use bones_bevy_renderer::{tiled::TiledMap, BonesRendererPlugin, HasBonesWorld};
+use bones_lib::prelude as bones;
+
+#[derive(Resource, Default)]
+struct BonesWorld(bones::World);
+
+impl HasBonesWorld for BonesWorld {
+    fn world(&mut self) -> &mut bones::World {
+        &mut self.0
+    }
+}
+
+#[derive(Resource)]
+struct MapHandle(Handle<TiledMap>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(BonesRendererPlugin::<BonesWorld>::new())
+        .init_resource::<BonesWorld>()
+        .add_startup_system(|mut commands: Commands, asset_server: Res<AssetServer>| {
+            commands.insert_resource(MapHandle(asset_server.load("tiled/level.tmx")));
+        })
+        .add_system(spawn_map)
+        .run();
+}
+
+/// Spawn the map and a camera that looks at it, once the map is loaded.
+fn spawn_map(
+    mut done: Local<bool>,
+    map_handle: Res<MapHandle>,
+    maps: Res<Assets<TiledMap>>,
+    mut world: ResMut<BonesWorld>,
+) {
+    if *done {
+        return;
+    }
+    let Some(map) = maps.get(&map_handle.0) else {
+        return;
+    };
+    *done = true;
+
+    for layer in &map.object_layers {
+        for object in &layer.objects {
+            info!(
+                "{} {:?} at {:?}, covering {:?}, with {:?}",
+                object.class,
+                object.name,
+                object.position,
+                object.rect(),
+                object.properties
+            );
+        }
+    }
+
+    let map = map.clone();
+    world
+        .0
+        .run_system(
+            move |mut entities: bones::ResMut<bones::Entities>,
+                  mut transforms: bones::CompMut<bones::Transform>,
+                  mut tiles: bones::CompMut<bones::Tile>,
+                  mut tile_layers: bones::CompMut<bones::TileLayer>,
+                  mut cameras: bones::CompMut<bones::Camera>| {
+                map.spawn(
+                    bones::Transform::default(),
+                    &mut entities,
+                    &mut transforms,
+                    &mut tiles,
+                    &mut tile_layers,
+                );
+
+                let map_size = map.size.as_vec2() * map.tile_size;
+                let camera = entities.create();
+                cameras.insert(
+                    camera,
+                    bones::Camera {
+                        size: bones::CameraSize::FixedHeight(map_size.y * 1.5),
+                        ..default()
+                    },
+                );
+                transforms.insert(
+                    camera,
+                    bones::Transform::from_translation((map_size / 2.0).extend(100.0)),
+                );
+            },
+        )
+        .unwrap();
+}
@@ -1,23 +1,275 @@
-use std::ffi::OsStr;
+use std::{ffi::OsStr, fmt};
 
-use bevy::{asset::LoadedAsset, sprite::TextureAtlas};
+use bevy::{
+    asset::{AssetPath, LoadedAsset},
+    math::Rect,
+    prelude::{Deref, Handle},
+    reflect::TypeUuid,
+    sprite::TextureAtlas,
+};
 use bones_bevy_asset::BonesBevyAssetLoad;
 use glam::Vec2;
+use serde::de::{MapAccess, Visitor};
+
+/// The label of the [`BonesAtlas`] that the [`TextureAtlasLoader`] loads along with each
+/// [`TextureAtlas`].
+pub const BONES_ATLAS_LABEL: &str = "bones_atlas";
+
+/// The bones [`Atlas`][bones_lib::render::sprite::Atlas] that a [`TextureAtlas`] was loaded from,
+/// which has the information that the bevy atlas doesn't, such as the
+/// [trims][bones_lib::render::sprite::Atlas::trims] of the frames.
+///
+/// It is loaded as a labeled asset, with the [`BONES_ATLAS_LABEL`], of each atlas file.
+#[derive(TypeUuid, Deref)]
+#[uuid = "6f1c2a0e-8d43-4b7a-9e15-3c5b7d92a4f1"]
+pub struct BonesAtlas(pub bones_lib::render::sprite::Atlas);
+
+impl BonesAtlas {
+    /// Get the handle of the bones atlas that was loaded along with the bevy atlas at the given
+    /// path.
+    pub fn weak_handle(atlas_path: &bones_lib::asset::AssetPath) -> Handle<BonesAtlas> {
+        let path = AssetPath::new(
+            atlas_path.path.to_path_buf(),
+            Some(BONES_ATLAS_LABEL.to_owned()),
+        );
+        Handle::weak(path.into())
+    }
+}
 
 /// The YAML/JSON metadata format for texture atlases
 #[derive(serde::Deserialize)]
 pub struct AtlasMeta {
     pub image: bones_lib::asset::Handle<bones_lib::render::sprite::Image>,
-    pub tile_size: Vec2,
-    pub columns: usize,
-    pub rows: usize,
+    #[serde(flatten)]
+    pub layout: AtlasLayoutMeta,
+}
+
+/// The layout of the frames of a texture atlas, in [`AtlasMeta`].
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub enum AtlasLayoutMeta {
+    /// A grid of frames of the same size, optionally with padding between the frames and an offset
+    /// from the top-left corner of the image.
+    Grid {
+        tile_size: Vec2,
+        columns: usize,
+        rows: usize,
+        #[serde(default)]
+        padding: Option<Vec2>,
+        #[serde(default)]
+        offset: Option<Vec2>,
+    },
+    /// A list of frames of any size, such as the frames of a packed sprite sheet, and how they were
+    /// trimmed, if they were.
+    Frames {
+        frames: Vec<bones_lib::render::datatypes::Rect>,
+        #[serde(default)]
+        trims: Vec<Option<bones_lib::render::sprite::AtlasTrim>>,
+    },
+}
+
+impl AtlasMeta {
+    /// Create the bones atlas that the metadata describes.
+    pub fn atlas(&self) -> bones_lib::render::sprite::Atlas {
+        use bones_lib::render::sprite::Atlas;
+        let image = self.image.clone();
+        match &self.layout {
+            AtlasLayoutMeta::Grid {
+                tile_size,
+                columns,
+                rows,
+                padding,
+                offset,
+            } => Atlas::from_grid(
+                image,
+                *tile_size,
+                *columns,
+                *rows,
+                padding.unwrap_or_default(),
+                offset.unwrap_or_default(),
+            ),
+            AtlasLayoutMeta::Frames { frames, trims } => {
+                Atlas::from_frames(image, frames.clone()).with_trims(trims.clone())
+            }
+        }
+    }
+}
+
+/// The JSON hash metadata format for texture atlases, that is exported by TexturePacker, Aseprite,
+/// and other sprite packers.
+///
+/// The frames are kept in the order that they are listed in, so that they are numbered the way
+/// that the packer exported them. Rotated frames aren't supported.
+#[derive(serde::Deserialize)]
+pub struct JsonHashAtlasMeta {
+    pub frames: JsonHashFrames,
+    pub meta: JsonHashMeta,
+}
+
+/// The `meta` of a [`JsonHashAtlasMeta`].
+#[derive(serde::Deserialize)]
+pub struct JsonHashMeta {
+    pub image: bones_lib::asset::Handle<bones_lib::render::sprite::Image>,
+}
+
+/// The frames of a [`JsonHashAtlasMeta`], by name, in the order that they are listed in.
+pub struct JsonHashFrames(pub Vec<(String, JsonHashFrame)>);
+
+impl<'de> serde::Deserialize<'de> for JsonHashFrames {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = JsonHashFrames;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of frames by name")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(JsonHashFrames(frames))
+            }
+        }
+        deserializer.deserialize_map(FramesVisitor)
+    }
+}
+
+/// A frame of a [`JsonHashAtlasMeta`].
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonHashFrame {
+    /// The rectangle of the trimmed frame in the image.
+    pub frame: JsonHashRect,
     #[serde(default)]
-    pub padding: Option<Vec2>,
+    pub rotated: bool,
     #[serde(default)]
-    pub offset: Option<Vec2>,
+    pub trimmed: bool,
+    /// The rectangle of the trimmed frame in the original image.
+    pub sprite_source_size: Option<JsonHashRect>,
+    /// The size of the original image.
+    pub source_size: Option<JsonHashSize>,
+}
+
+/// A rectangle in a [`JsonHashAtlasMeta`], in pixels from the top-left corner.
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct JsonHashRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// A size in a [`JsonHashAtlasMeta`], in pixels.
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct JsonHashSize {
+    pub w: f32,
+    pub h: f32,
+}
+
+impl JsonHashAtlasMeta {
+    /// Create the bones atlas that the metadata describes, or return an error if one of the frames
+    /// is rotated.
+    pub fn atlas(&self) -> Result<bones_lib::render::sprite::Atlas, bevy::asset::Error> {
+        use bones_lib::render::{
+            datatypes::Rect,
+            sprite::{Atlas, AtlasTrim},
+        };
+        let mut frames = Vec::with_capacity(self.frames.0.len());
+        let mut trims = Vec::with_capacity(self.frames.0.len());
+        for (name, frame) in &self.frames.0 {
+            if frame.rotated {
+                return Err(bevy::asset::Error::msg(format!(
+                    "Atlas frame {name:?} is rotated, which isn't supported"
+                )));
+            }
+            let JsonHashRect { x, y, w, h } = frame.frame;
+            frames.push(Rect::new(x, y, x + w, y + h));
+            trims.push(
+                match (frame.trimmed, frame.sprite_source_size, frame.source_size) {
+                    (true, Some(sprite_source_size), Some(source_size)) => Some(AtlasTrim {
+                        source_size: Vec2::new(source_size.w, source_size.h),
+                        offset: Vec2::new(sprite_source_size.x, sprite_source_size.y),
+                    }),
+                    _ => None,
+                },
+            );
+        }
+        Ok(Atlas::from_frames(self.meta.image.clone(), frames).with_trims(trims))
+    }
+}
+
+/// Returns `true` if the JSON atlas metadata is in the [`JsonHashAtlasMeta`] format, because it
+/// has a `meta` field.
+fn is_json_hash(bytes: &[u8]) -> Result<bool, serde_json::Error> {
+    #[derive(serde::Deserialize)]
+    struct Probe {
+        meta: Option<serde::de::IgnoredAny>,
+    }
+    let probe: Probe = serde_json::from_slice(bytes)?;
+    Ok(probe.meta.is_some())
+}
+
+/// Create the bevy [`TextureAtlas`] for a bones atlas, with the bones atlas image as its texture.
+pub(crate) fn texture_atlas(atlas: &bones_lib::render::sprite::Atlas) -> TextureAtlas {
+    TextureAtlas {
+        texture: atlas.image.get_bevy_handle_untyped().typed(),
+        size: atlas.size,
+        textures: atlas
+            .frames
+            .iter()
+            .map(|frame| Rect {
+                min: frame.min,
+                max: frame.max,
+            })
+            .collect(),
+        texture_handles: None,
+    }
+}
+
+/// Get the path of an asset relative to the file at `base`, from the root of the assets.
+#[cfg(any(feature = "tiled", feature = "ldtk"))]
+pub(crate) fn relative_path(path: &str, base: &std::path::Path) -> std::path::PathBuf {
+    let mut path = bones_lib::asset::AssetPath::new(path, None);
+    path.normalize_relative_to(base);
+    path.path.to_path_buf()
+}
+
+/// Fill a bones tile layer with the tiles of a layer of a map file, and create the entities of the
+/// tiles, which are shared by the slots that have the same tile.
+///
+/// The `tiles` are in the same order as the [`tiles`][bones_lib::render::tilemap::TileLayer::tiles]
+/// of the layer.
+#[cfg(any(feature = "tiled", feature = "ldtk"))]
+pub(crate) fn fill_tile_layer(
+    layer: &mut bones_lib::render::tilemap::TileLayer,
+    tiles: &[Option<bones_lib::render::tilemap::Tile>],
+    entities: &mut bones_lib::prelude::Entities,
+    tile_components: &mut bones_lib::prelude::CompMut<bones_lib::render::tilemap::Tile>,
+) {
+    let mut tile_entities = std::collections::HashMap::new();
+    let grid_width = layer.grid_size.x;
+    for (slot, tile) in tiles.iter().enumerate() {
+        let Some(tile) = tile else {
+            continue;
+        };
+        let key = (tile.idx, tile.flip_x, tile.flip_y, tile.rotation);
+        let entity = *tile_entities.entry(key).or_insert_with(|| {
+            let entity = entities.create();
+            tile_components.insert(entity, tile.clone());
+            entity
+        });
+        let pos = glam::UVec2::new(slot as u32 % grid_width, slot as u32 / grid_width);
+        layer.set(pos, Some(entity));
+    }
 }
 
 /// An asset loader for [`TextureAtlas`]s from JSON or YAML.
+///
+/// The JSON may be in the [`AtlasMeta`] or the [`JsonHashAtlasMeta`] format. The bones atlas is
+/// loaded along with the bevy atlas, as a [`BonesAtlas`].
 pub struct TextureAtlasLoader;
 
 impl bevy::asset::AssetLoader for TextureAtlasLoader {
@@ -30,25 +282,25 @@ impl bevy::asset::AssetLoader for TextureAtlasLoader {
             let self_path = &load_context.path().to_owned();
             let mut dependencies = Vec::with_capacity(1);
 
-            let mut meta: AtlasMeta = if self_path.extension() == Some(OsStr::new("json")) {
-                serde_json::from_slice(bytes)?
+            let is_json = self_path.extension() == Some(OsStr::new("json"));
+            let mut atlas = if is_json && is_json_hash(bytes)? {
+                let mut meta: JsonHashAtlasMeta = serde_json::from_slice(bytes)?;
+                meta.meta.image.load(load_context, &mut dependencies);
+                meta.atlas()?
             } else {
-                serde_yaml::from_slice(bytes)?
+                let mut meta: AtlasMeta = if is_json {
+                    serde_json::from_slice(bytes)?
+                } else {
+                    serde_yaml::from_slice(bytes)?
+                };
+                meta.image.load(load_context, &mut dependencies);
+                meta.atlas()
             };
 
-            meta.image.load(load_context, &mut dependencies);
-
             load_context.set_default_asset(
-                LoadedAsset::new(TextureAtlas::from_grid(
-                    meta.image.get_bevy_handle_untyped().typed(),
-                    meta.tile_size,
-                    meta.columns,
-                    meta.rows,
-                    meta.padding,
-                    meta.offset,
-                ))
-                .with_dependencies(dependencies),
+                LoadedAsset::new(texture_atlas(&atlas)).with_dependencies(dependencies),
             );
+            load_context.set_labeled_asset(BONES_ATLAS_LABEL, LoadedAsset::new(BonesAtlas(atlas)));
 
             Ok(())
         })
@@ -58,3 +310,35 @@ impl bevy::asset::AssetLoader for TextureAtlasLoader {
         &["atlas.json", "atlas.yml", "atlas.yaml"]
     }
 }
+
+/// The bones [`AutotileRules`][bones_lib::render::autotile::AutotileRules], loaded from a YAML or
+/// JSON file, so that artists can tweak them without recompiling the game.
+#[derive(TypeUuid, Deref)]
+#[uuid = "4a7d2c91-3e0b-4f86-a5d1-6b9e8c2f07d3"]
+pub struct BonesAutotileRules(pub bones_lib::render::autotile::AutotileRules);
+
+/// An asset loader for [`BonesAutotileRules`] from `.autotile.yaml` and `.autotile.json` files.
+pub struct AutotileRulesLoader;
+
+impl bevy::asset::AssetLoader for AutotileRulesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let is_json = load_context.path().extension() == Some(OsStr::new("json"));
+            let rules = if is_json {
+                serde_json::from_slice(bytes)?
+            } else {
+                serde_yaml::from_slice(bytes)?
+            };
+            load_context.set_default_asset(LoadedAsset::new(BonesAutotileRules(rules)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["autotile.json", "autotile.yml", "autotile.yaml"]
+    }
+}
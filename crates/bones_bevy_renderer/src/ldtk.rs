@@ -0,0 +1,652 @@
+//! Loader for [LDtk](https://ldtk.io/) projects.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bones_bevy_asset::BonesBevyAssetLoad;
+use bones_lib::prelude as bones;
+use glam::{UVec2, Vec2};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::asset::{fill_tile_layer, relative_path, texture_atlas};
+
+/// An LDtk project, loaded from a `.ldtk` file along with its external levels.
+///
+/// Each level is also loaded as a labeled asset of the project, named after its identifier, so a
+/// single level can be loaded with the asset server, such as with `"world.ldtk#Level_0"`.
+///
+/// LDtk measures positions from the top-left corner with y pointing down, and they are converted
+/// to the bones coordinate space, where y points up:
+///
+/// - The [`position`][LdtkLevel::position] of a level is its bottom-left corner in the world, so
+///   that the levels are laid out like in LDtk.
+/// - Everything in a level is positioned relative to the bottom-left corner of the level.
+/// - The rows of the tile layers are flipped, so that the bottom row of the level is row `0` of
+///   the [`TileLayer`][bones::TileLayer]s.
+#[derive(TypeUuid, Clone, Debug)]
+#[uuid = "8b3f1d27-64ce-4a0e-9c5a-2e7d41f09b63"]
+pub struct LdtkProject {
+    /// The levels of the project.
+    pub levels: Vec<LdtkLevel>,
+    /// The tilesets of the project, by their unique IDs.
+    pub tilesets: HashMap<i64, LdtkTileset>,
+}
+
+impl LdtkProject {
+    /// Get the level with the given identifier.
+    pub fn level(&self, identifier: &str) -> Option<&LdtkLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.identifier == identifier)
+    }
+}
+
+/// A tileset of an [`LdtkProject`].
+#[derive(Clone, Debug)]
+pub struct LdtkTileset {
+    /// The identifier of the tileset.
+    pub identifier: String,
+    /// The size of the tiles in the tileset image.
+    pub tile_size: Vec2,
+    /// The tileset image.
+    pub image: bones::Handle<bones::Image>,
+    /// The atlas of the tiles in the tileset image.
+    ///
+    /// It's loaded as a labeled asset of the project, so it can be used by the tile layers.
+    pub atlas: bones::Handle<bones::Atlas>,
+}
+
+/// A level of an [`LdtkProject`].
+#[derive(TypeUuid, Clone, Debug)]
+#[uuid = "c52e9a4b-0f3d-47e1-8b6c-91d5a73e2f08"]
+pub struct LdtkLevel {
+    /// The identifier of the level.
+    pub identifier: String,
+    /// The unique instance ID of the level.
+    pub iid: String,
+    /// The position of the bottom-left corner of the level in the world.
+    pub position: Vec2,
+    /// The size of the level in pixels.
+    pub size: Vec2,
+    /// The visible tile and IntGrid layers of the level, from the bottom to the top.
+    pub tile_layers: Vec<LdtkTileLayer>,
+    /// The entity layers of the level, from the bottom to the top, including the hidden ones.
+    pub entity_layers: Vec<LdtkEntityLayer>,
+    /// The values of the custom fields of the level.
+    pub fields: LdtkFields,
+}
+
+/// A tile layer of an [`LdtkLevel`], made from a tiles, auto-layer, or IntGrid layer.
+///
+/// The values of an IntGrid layer are in the [`meta`][Self::meta] of the layer, along with its
+/// auto-layer tiles, if it has any. An IntGrid layer without auto-layer tiles still becomes a tile
+/// layer, with no tiles, so that the game can read its values.
+///
+/// LDtk can stack several tiles in the same cell of a layer, but a bones
+/// [`TileLayer`][bones::TileLayer] only has one tile per slot, so the tiles that are stacked on
+/// other tiles are moved to more layers, with the same identifier, right above it. Only the first
+/// of those layers has the IntGrid values.
+#[derive(Clone, Debug)]
+pub struct LdtkTileLayer {
+    /// The identifier of the layer.
+    pub identifier: String,
+    /// The z offset of the layer, which is the index of the layer from the bottom of the level,
+    /// counting the layers that stacked tiles are moved to.
+    pub z: f32,
+    /// The offset of the layer from the bottom-left corner of the level.
+    pub offset: Vec2,
+    /// The size of the layer in cells.
+    pub grid_size: UVec2,
+    /// The size of the cells of the layer's grid.
+    pub tile_size: Vec2,
+    /// The atlas of the tileset of the layer, or the default handle if it has no tileset.
+    pub atlas: bones::Handle<bones::Atlas>,
+    /// The tiles of the layer, in the same order as the [`tiles`][bones::TileLayer::tiles] of a
+    /// bones tile layer.
+    pub tiles: Vec<Option<bones::Tile>>,
+    /// The IntGrid values of the layer, in the same order as the tiles, or an empty list if it
+    /// isn't an IntGrid layer.
+    pub meta: Vec<u16>,
+}
+
+/// An entity layer of an [`LdtkLevel`].
+#[derive(Clone, Debug)]
+pub struct LdtkEntityLayer {
+    /// The identifier of the layer.
+    pub identifier: String,
+    /// The z offset of the layer, like the [`z`][LdtkTileLayer::z] of a tile layer.
+    pub z: f32,
+    /// The offset of the layer from the bottom-left corner of the level.
+    pub offset: Vec2,
+    /// Whether the layer is visible.
+    pub visible: bool,
+    /// The entity instances of the layer.
+    pub entities: Vec<LdtkEntity>,
+}
+
+/// An entity instance of an [`LdtkEntityLayer`].
+#[derive(Clone, Debug)]
+pub struct LdtkEntity {
+    /// The identifier of the entity's definition, such as `Player`.
+    pub identifier: String,
+    /// The unique instance ID of the entity.
+    pub iid: String,
+    /// The position of the pivot of the entity, in pixels, relative to its layer.
+    pub position: Vec2,
+    /// The size of the entity in pixels.
+    pub size: Vec2,
+    /// The pivot of the entity, from `(0, 0)` at its bottom-left corner to `(1, 1)` at its
+    /// top-right corner.
+    pub pivot: Vec2,
+    /// The tags of the entity's definition.
+    pub tags: Vec<String>,
+    /// The values of the custom fields of the entity.
+    pub fields: LdtkFields,
+}
+
+impl LdtkEntity {
+    /// Get the rectangle that the entity covers, relative to its layer.
+    pub fn rect(&self) -> bones::Rect {
+        let min = self.position - self.pivot * self.size;
+        bones::Rect::from_corners(min, min + self.size)
+    }
+}
+
+/// The values of the custom fields of an LDtk level or entity, by their identifiers.
+pub type LdtkFields = HashMap<String, LdtkFieldValue>;
+
+/// The value of a custom field of an LDtk level or entity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LdtkFieldValue {
+    /// A field that has no value.
+    Null,
+    /// A `Bool` field.
+    Bool(bool),
+    /// An `Int` field.
+    Int(i64),
+    /// A `Float` field.
+    Float(f64),
+    /// A `String`, `Multilines`, `Color`, `FilePath`, or enum field.
+    ///
+    /// Colors are in the `#RRGGBB` format, and the values of enums are their identifiers. The
+    /// values of the other types of fields are kept as JSON.
+    String(String),
+    /// A `Point` field, with the cell that it points to, with y pointing up like the rows of the
+    /// tile layers.
+    Point(UVec2),
+    /// An `EntityRef` field.
+    EntityRef {
+        /// The unique instance ID of the entity.
+        entity_iid: String,
+        /// The unique instance ID of the level that the entity is in.
+        level_iid: String,
+    },
+    /// An array field.
+    Array(Vec<LdtkFieldValue>),
+}
+
+impl LdtkLevel {
+    /// Spawn the [`tile_layers`][Self::tile_layers] of the level, and get their entities, from the
+    /// bottom to the top.
+    ///
+    /// Each layer is spawned with a transform that is offset from the given `transform` by the
+    /// layer's offset and z offset, so the level's [`position`][Self::position] has to be added to
+    /// the transform to spawn it where it is in the world. The tiles of each layer are shared by
+    /// the slots that have the same tile.
+    pub fn spawn(
+        &self,
+        transform: bones::Transform,
+        entities: &mut bones::Entities,
+        transforms: &mut bones::CompMut<bones::Transform>,
+        tiles: &mut bones::CompMut<bones::Tile>,
+        tile_layers: &mut bones::CompMut<bones::TileLayer>,
+    ) -> Vec<bones::Entity> {
+        self.tile_layers
+            .iter()
+            .map(|layer| {
+                let mut layer_transform = transform;
+                layer_transform.translation += layer.offset.extend(layer.z);
+                let layer_entity = entities.create();
+                transforms.insert(layer_entity, layer_transform);
+                tile_layers.insert(layer_entity, layer.tile_layer(entities, tiles));
+                layer_entity
+            })
+            .collect()
+    }
+}
+
+impl LdtkTileLayer {
+    /// Create the tile entities of the layer, and get the bones tile layer that uses them, with
+    /// the IntGrid values as its metadata.
+    pub fn tile_layer(
+        &self,
+        entities: &mut bones::Entities,
+        tiles: &mut bones::CompMut<bones::Tile>,
+    ) -> bones::TileLayer {
+        let mut layer = bones::TileLayer::new(self.grid_size, self.tile_size, self.atlas.clone());
+        if self.meta.len() == layer.meta.len() {
+            layer.meta.copy_from_slice(&self.meta);
+        }
+        fill_tile_layer(&mut layer, &self.tiles, entities, tiles);
+        layer
+    }
+}
+
+/// An asset loader for [`LdtkProject`]s from `.ldtk` files.
+///
+/// The external levels of the project are read from their `.ldtkl` files. The atlas of each
+/// tileset is loaded as a labeled asset of the project, named `tileset` followed by the unique ID
+/// of the tileset, and each level is loaded as a labeled [`LdtkLevel`], named after its
+/// identifier.
+pub struct LdtkLoader;
+
+impl AssetLoader for LdtkLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let project_path = load_context.path().to_owned();
+            let json: JsonProject = serde_json::from_slice(bytes)?;
+
+            let mut dependencies = Vec::new();
+            let mut tilesets = HashMap::new();
+            for json in &json.defs.tilesets {
+                // Tilesets without an image are the internal icons of LDtk.
+                let Some(rel_path) = &json.rel_path else {
+                    continue;
+                };
+                let image_path = relative_path(rel_path, &project_path);
+                // Reading the image here reports a missing image along with the project, instead
+                // of only when the renderer fails to load it.
+                load_context
+                    .read_asset_bytes(&image_path)
+                    .await
+                    .map_err(|err| {
+                        bevy::asset::Error::msg(format!(
+                            "Image {image_path:?} of tileset {:?} couldn't be read: {err}",
+                            json.identifier
+                        ))
+                    })?;
+
+                let mut image = bones::Handle::new(rel_path.clone(), None);
+                image.load(load_context, &mut dependencies);
+                let tile_size = Vec2::splat(json.tile_grid_size);
+                let mut atlas = bones::Atlas::from_grid(
+                    image.clone(),
+                    tile_size,
+                    json.c_wid as usize,
+                    json.c_hei as usize,
+                    Vec2::splat(json.spacing),
+                    Vec2::splat(json.padding),
+                );
+                atlas.size = Vec2::new(json.px_wid, json.px_hei);
+                let label = format!("tileset{}", json.uid);
+                load_context.set_labeled_asset(&label, LoadedAsset::new(texture_atlas(&atlas)));
+                tilesets.insert(
+                    json.uid,
+                    LdtkTileset {
+                        identifier: json.identifier.clone(),
+                        tile_size,
+                        image,
+                        atlas: bones::Handle::new(project_path.clone(), Some(label)),
+                    },
+                );
+            }
+
+            let mut levels = Vec::with_capacity(json.levels.len());
+            for level in json.levels {
+                let level = match level.external_rel_path.clone() {
+                    Some(external_rel_path) => {
+                        let path = relative_path(&external_rel_path, &project_path);
+                        let bytes = load_context.read_asset_bytes(&path).await.map_err(|err| {
+                            bevy::asset::Error::msg(format!(
+                                "Level {:?} couldn't be read from {path:?}: {err}",
+                                level.identifier
+                            ))
+                        })?;
+                        serde_json::from_slice(&bytes)?
+                    }
+                    None => level,
+                };
+                let level = load_level(level, &tilesets, json.default_grid_size)?;
+                load_context
+                    .set_labeled_asset(&level.identifier.clone(), LoadedAsset::new(level.clone()));
+                levels.push(level);
+            }
+
+            load_context.set_default_asset(
+                LoadedAsset::new(LdtkProject { levels, tilesets }).with_dependencies(dependencies),
+            );
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// Convert a level of the project.
+fn load_level(
+    json: JsonLevel,
+    tilesets: &HashMap<i64, LdtkTileset>,
+    default_grid_size: f32,
+) -> Result<LdtkLevel, bevy::asset::Error> {
+    let size = Vec2::new(json.px_wid, json.px_hei);
+    let level_grid_height = (json.px_hei / default_grid_size).ceil() as u32;
+    let mut level = LdtkLevel {
+        identifier: json.identifier,
+        iid: json.iid,
+        position: Vec2::new(json.world_x, -json.world_y - json.px_hei),
+        size,
+        tile_layers: Vec::new(),
+        entity_layers: Vec::new(),
+        fields: fields(&json.field_instances, level_grid_height)?,
+    };
+
+    // The layers are listed from the top to the bottom.
+    let mut z = 0.0;
+    for layer in json.layer_instances.unwrap_or_default().into_iter().rev() {
+        let offset = Vec2::new(layer.px_total_offset_x, -layer.px_total_offset_y);
+        if layer.r#type == "Entities" {
+            let entities = layer
+                .entity_instances
+                .iter()
+                .map(|entity| {
+                    Ok(LdtkEntity {
+                        identifier: entity.identifier.clone(),
+                        iid: entity.iid.clone(),
+                        position: Vec2::new(entity.px[0], size.y - entity.px[1]),
+                        size: Vec2::new(entity.width, entity.height),
+                        pivot: Vec2::new(entity.pivot[0], 1.0 - entity.pivot[1]),
+                        tags: entity.tags.clone(),
+                        fields: fields(&entity.field_instances, layer.c_hei)?,
+                    })
+                })
+                .collect::<Result<_, bevy::asset::Error>>()?;
+            level.entity_layers.push(LdtkEntityLayer {
+                identifier: layer.identifier,
+                z,
+                offset,
+                visible: layer.visible,
+                entities,
+            });
+            z += 1.0;
+        } else if layer.visible {
+            for mut tile_layer in tile_layers(&layer, tilesets, offset)? {
+                tile_layer.z = z;
+                level.tile_layers.push(tile_layer);
+                z += 1.0;
+            }
+        }
+    }
+
+    Ok(level)
+}
+
+/// Convert a tiles, auto-layer, or IntGrid layer to tile layers, one for each level of stacked
+/// tiles.
+fn tile_layers(
+    json: &JsonLayer,
+    tilesets: &HashMap<i64, LdtkTileset>,
+    offset: Vec2,
+) -> Result<Vec<LdtkTileLayer>, bevy::asset::Error> {
+    let grid_size = UVec2::new(json.c_wid, json.c_hei);
+    let slot_count = (json.c_wid * json.c_hei) as usize;
+    // LDtk rows go down from the top of the level, and bones rows go up from the bottom.
+    let slot = |cell: UVec2| ((grid_size.y - 1 - cell.y) * grid_size.x + cell.x) as usize;
+
+    let mut meta = Vec::new();
+    if !json.int_grid_csv.is_empty() {
+        if json.int_grid_csv.len() != slot_count {
+            return Err(bevy::asset::Error::msg(format!(
+                "IntGrid layer {:?} has {} values instead of {slot_count}",
+                json.identifier,
+                json.int_grid_csv.len()
+            )));
+        }
+        meta = vec![0; slot_count];
+        for (i, value) in json.int_grid_csv.iter().enumerate() {
+            let cell = UVec2::new(i as u32 % grid_size.x, i as u32 / grid_size.x);
+            meta[slot(cell)] = u16::try_from(*value).map_err(|_| {
+                bevy::asset::Error::msg(format!(
+                    "IntGrid value {value} of layer {:?} doesn't fit in the tile metadata",
+                    json.identifier
+                ))
+            })?;
+        }
+    }
+
+    let atlas = match json.tileset_def_uid {
+        Some(uid) => tilesets
+            .get(&uid)
+            .ok_or_else(|| {
+                bevy::asset::Error::msg(format!(
+                    "Tileset {uid} of layer {:?} doesn't have an image",
+                    json.identifier
+                ))
+            })?
+            .atlas
+            .clone(),
+        None => bones::Handle::default(),
+    };
+    let new_layer = |meta: Vec<u16>| LdtkTileLayer {
+        identifier: json.identifier.clone(),
+        z: 0.0,
+        offset,
+        grid_size,
+        tile_size: Vec2::splat(json.grid_size),
+        atlas: atlas.clone(),
+        tiles: vec![None; slot_count],
+        meta,
+    };
+
+    let mut layers = vec![new_layer(meta)];
+    // The tiles are listed from the bottom to the top of the stacks.
+    for tile in json.grid_tiles.iter().chain(&json.auto_layer_tiles) {
+        let cell = UVec2::new(
+            (tile.px[0] / json.grid_size) as u32,
+            (tile.px[1] / json.grid_size) as u32,
+        );
+        if cell.x >= grid_size.x || cell.y >= grid_size.y {
+            continue;
+        }
+        let slot = slot(cell);
+        let stack = match layers.iter().position(|layer| layer.tiles[slot].is_none()) {
+            Some(stack) => stack,
+            None => {
+                layers.push(new_layer(Vec::new()));
+                layers.len() - 1
+            }
+        };
+        layers[stack].tiles[slot] = Some(bones::Tile {
+            idx: tile.t as usize,
+            flip_x: tile.f & 1 != 0,
+            flip_y: tile.f & 2 != 0,
+            ..Default::default()
+        });
+    }
+    Ok(layers)
+}
+
+/// Convert the custom fields of a level or an entity, whose points are on a grid with the given
+/// number of rows.
+fn fields(json: &[JsonField], grid_height: u32) -> Result<LdtkFields, bevy::asset::Error> {
+    json.iter()
+        .map(|field| {
+            let value = field_value(&field.r#type, &field.value, grid_height).ok_or_else(|| {
+                bevy::asset::Error::msg(format!(
+                    "Field {:?} has an invalid {} value: {}",
+                    field.identifier, field.r#type, field.value
+                ))
+            })?;
+            Ok((field.identifier.clone(), value))
+        })
+        .collect()
+}
+
+/// Convert the value of a custom field with the given type, or return `None` if it isn't a value
+/// of that type.
+fn field_value(r#type: &str, value: &Value, grid_height: u32) -> Option<LdtkFieldValue> {
+    if let Some(item_type) = r#type
+        .strip_prefix("Array<")
+        .and_then(|x| x.strip_suffix('>'))
+    {
+        return value
+            .as_array()?
+            .iter()
+            .map(|item| field_value(item_type, item, grid_height))
+            .collect::<Option<_>>()
+            .map(LdtkFieldValue::Array);
+    }
+    if value.is_null() {
+        return Some(LdtkFieldValue::Null);
+    }
+    Some(match r#type {
+        "Bool" => LdtkFieldValue::Bool(value.as_bool()?),
+        "Int" => LdtkFieldValue::Int(value.as_i64()?),
+        "Float" => LdtkFieldValue::Float(value.as_f64()?),
+        "Point" => {
+            let cx = value.get("cx")?.as_u64()? as u32;
+            let cy = value.get("cy")?.as_u64()? as u32;
+            LdtkFieldValue::Point(UVec2::new(cx, grid_height.checked_sub(cy + 1)?))
+        }
+        "EntityRef" => LdtkFieldValue::EntityRef {
+            entity_iid: value.get("entityIid")?.as_str()?.to_owned(),
+            level_iid: value.get("levelIid")?.as_str()?.to_owned(),
+        },
+        _ => LdtkFieldValue::String(match value.as_str() {
+            Some(value) => value.to_owned(),
+            None => value.to_string(),
+        }),
+    })
+}
+
+/// The root of a `.ldtk` file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonProject {
+    defs: JsonDefs,
+    levels: Vec<JsonLevel>,
+    default_grid_size: f32,
+}
+
+/// The definitions of a project.
+#[derive(Deserialize)]
+struct JsonDefs {
+    tilesets: Vec<JsonTileset>,
+}
+
+/// The definition of a tileset.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTileset {
+    uid: i64,
+    identifier: String,
+    rel_path: Option<String>,
+    px_wid: f32,
+    px_hei: f32,
+    tile_grid_size: f32,
+    spacing: f32,
+    padding: f32,
+    #[serde(rename = "__cWid")]
+    c_wid: u32,
+    #[serde(rename = "__cHei")]
+    c_hei: u32,
+}
+
+/// A level, in a `.ldtk` file or in its own `.ldtkl` file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLevel {
+    identifier: String,
+    iid: String,
+    world_x: f32,
+    world_y: f32,
+    px_wid: f32,
+    px_hei: f32,
+    #[serde(default)]
+    field_instances: Vec<JsonField>,
+    /// The layers of the level, which are only missing when the level is in its own file.
+    layer_instances: Option<Vec<JsonLayer>>,
+    external_rel_path: Option<String>,
+}
+
+/// A layer of a level.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLayer {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    r#type: String,
+    #[serde(rename = "__cWid")]
+    c_wid: u32,
+    #[serde(rename = "__cHei")]
+    c_hei: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    #[serde(rename = "__tilesetDefUid")]
+    tileset_def_uid: Option<i64>,
+    #[serde(rename = "__pxTotalOffsetX")]
+    px_total_offset_x: f32,
+    #[serde(rename = "__pxTotalOffsetY")]
+    px_total_offset_y: f32,
+    visible: bool,
+    #[serde(default)]
+    int_grid_csv: Vec<i64>,
+    #[serde(default)]
+    grid_tiles: Vec<JsonTile>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<JsonTile>,
+    #[serde(default)]
+    entity_instances: Vec<JsonEntity>,
+}
+
+/// A tile of a tiles or auto-layer layer.
+#[derive(Deserialize)]
+struct JsonTile {
+    /// The position of the top-left corner of the tile in the layer, in pixels.
+    px: [f32; 2],
+    /// The flips of the tile, `1` for x and `2` for y.
+    f: u8,
+    /// The ID of the tile in the tileset.
+    t: u32,
+}
+
+/// An entity instance.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    iid: String,
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    #[serde(rename = "__tags", default)]
+    tags: Vec<String>,
+    px: [f32; 2],
+    width: f32,
+    height: f32,
+    #[serde(default)]
+    field_instances: Vec<JsonField>,
+}
+
+/// A custom field of a level or an entity.
+#[derive(Deserialize)]
+struct JsonField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    r#type: String,
+    #[serde(rename = "__value")]
+    value: Value,
+}
@@ -7,7 +7,16 @@
 
 use std::marker::PhantomData;
 
-use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::{ScalingMode, Viewport},
+        view::RenderLayers,
+    },
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
 use bevy_simple_tilemap::{prelude::TileMapBundle, Tile, TileFlags, TileMap};
 use bones_lib::prelude::{self as bones, BitSet, IntoBevy};
 
@@ -17,6 +26,11 @@ pub mod prelude {
 }
 
 mod asset;
+pub use asset::BonesAutotileRules;
+#[cfg(feature = "ldtk")]
+pub mod ldtk;
+#[cfg(feature = "tiled")]
+pub mod tiled;
 
 /// This is a trait that must be implemented for your Bevy resource containing the bones
 /// [`World`][bones::World].
@@ -54,17 +68,193 @@ impl<W: HasBonesWorld> BonesRendererPlugin<W> {
 #[derive(Component)]
 pub struct BevyBonesEntity;
 
+/// Marker component for the entities that bones [`Static`][bones::Static] sprites are rendered
+/// with.
+#[derive(Component)]
+pub struct BevyBonesStaticSprite;
+
+/// Marker component for the entities that bones
+/// [`ChunkedTileLayer`][bones::ChunkedTileLayer]s are rendered with.
+#[derive(Component)]
+pub struct BevyBonesChunkedTileLayer;
+
+/// Marker component for the sprites that the tiles of bones [`TileLayer`][bones::TileLayer]s
+/// with an isometric or hexagonal [`TileProjection`][bones::TileProjection] are rendered with.
+#[derive(Component)]
+pub struct BevyBonesProjectedTile;
+
+/// Marker component for the entities that bones nine-patch sprites are rendered with.
+///
+/// The slices of the nine-patch are rendered by its [`BevyBonesNinePatchSlice`] children.
+#[derive(Component)]
+pub struct BevyBonesNinePatch;
+
+/// Marker component for the sprites that the slices of a [`BevyBonesNinePatch`] are rendered with.
+#[derive(Component)]
+pub struct BevyBonesNinePatchSlice;
+
 impl<W: HasBonesWorld> Plugin for BonesRendererPlugin<W> {
     fn build(&self, app: &mut App) {
         app.add_plugin(bevy_simple_tilemap::plugin::SimpleTileMapPlugin)
             // Install the asset loader for .atlas.yaml files.
             .add_asset_loader(asset::TextureAtlasLoader)
+            .add_asset::<asset::BonesAtlas>()
+            // Install the asset loader for .autotile.yaml files.
+            .add_asset_loader(asset::AutotileRulesLoader)
+            .add_asset::<asset::BonesAutotileRules>()
             // Add the world sync systems
+            .add_system_to_stage(CoreStage::First, sync_window::<W>)
             .add_system_to_stage(CoreStage::Last, sync_sprites::<W>)
             .add_system_to_stage(CoreStage::Last, sync_atlas_sprites::<W>)
+            .add_system_to_stage(CoreStage::Last, sync_nine_patches::<W>)
             .add_system_to_stage(CoreStage::Last, sync_cameras::<W>)
             .add_system_to_stage(CoreStage::Last, sync_clear_color::<W>)
-            .add_system_to_stage(CoreStage::Last, sync_tilemaps::<W>);
+            .add_system_to_stage(CoreStage::Last, sync_tilemaps::<W>)
+            .add_system_to_stage(CoreStage::Last, sync_projected_tilemaps::<W>)
+            .add_system_to_stage(CoreStage::Last, sync_chunked_tilemaps::<W>);
+
+        // Install the asset loader for .tmx files.
+        #[cfg(feature = "tiled")]
+        app.add_asset::<tiled::TiledMap>()
+            .add_asset_loader(tiled::TiledMapLoader);
+
+        // Install the asset loader for .ldtk files.
+        #[cfg(feature = "ldtk")]
+        app.add_asset::<ldtk::LdtkProject>()
+            .add_asset::<ldtk::LdtkLevel>()
+            .add_asset_loader(ldtk::LdtkLoader);
+    }
+}
+
+/// Convert the bones render layers of an entity to bevy render layers.
+fn bevy_render_layers(layers: Option<&bones::RenderLayers>) -> RenderLayers {
+    let layers = layers.copied().unwrap_or_default();
+    RenderLayers::from_layers(&layers.iter().collect::<Vec<_>>())
+}
+
+/// Convert the bones anchor of a sprite to a bevy anchor.
+///
+/// Bevy flips the image without moving the anchor, so the anchor is mirrored to flip the sprite
+/// around it.
+fn bevy_anchor(anchor: &bones::Anchor, flip_x: bool, flip_y: bool) -> Anchor {
+    Anchor::Custom(anchor.flipped(flip_x, flip_y))
+}
+
+/// Convert the bones rectangle of a sprite to a bevy rectangle, clamped to the image if it has
+/// been loaded.
+fn bevy_sprite_rect(rect: Option<bones::Rect>, image: Option<&Image>) -> Option<Rect> {
+    let mut rect = rect?;
+    if let Some(image) = image {
+        let image_rect = bones::Rect::from_corners(Vec2::ZERO, image.size());
+        let clamped = rect.intersect(&image_rect);
+        if clamped != rect {
+            debug!("Sprite rect {rect:?} is outside of its image, clamping it to {clamped:?}");
+            rect = clamped;
+        }
+    }
+    Some(Rect {
+        min: rect.min,
+        max: rect.max,
+    })
+}
+
+/// Get the index of the frame of a bones atlas sprite, clamped to the frames of its atlas if it
+/// has been loaded.
+///
+/// In debug builds, a warning is logged when the index of an entity goes out of range. It isn't
+/// logged again until the index changes, so it doesn't spam the log every frame.
+fn bevy_atlas_index(
+    bones_ent: bones::Entity,
+    bones_atlas: &bones::AtlasSprite,
+    atlas: Option<&TextureAtlas>,
+    warned_indices: &mut HashMap<bones::Entity, usize>,
+) -> usize {
+    let index = bones_atlas.index;
+    let Some(frame_count) = atlas.map(|x| x.textures.len()) else {
+        return index;
+    };
+    if index < frame_count {
+        warned_indices.remove(&bones_ent);
+        return index;
+    }
+    if cfg!(debug_assertions) && warned_indices.insert(bones_ent, index) != Some(index) {
+        warn!(
+            "Atlas sprite index {index} of entity {bones_ent:?} is out of range for the atlas {:?} \
+            with {frame_count} frames, clamping it",
+            bones_atlas.atlas.path.path,
+        );
+    }
+    index.min(frame_count.saturating_sub(1))
+}
+
+/// Get the custom size and the anchor of a bones atlas sprite that shows the frame at the given
+/// index, so that the frame is drawn where it was before it was [trimmed][bones::Atlas::trims].
+///
+/// The frame is drawn as if it wasn't trimmed until the bones atlas has been loaded.
+fn bevy_atlas_quad(
+    bones_atlas: &bones::AtlasSprite,
+    index: usize,
+    atlas: Option<&asset::BonesAtlas>,
+) -> (Option<Vec2>, Anchor) {
+    let quad = atlas.and_then(|atlas| {
+        if index == bones_atlas.index {
+            bones_atlas.quad(atlas)
+        } else {
+            bones::AtlasSprite {
+                index,
+                ..bones_atlas.clone()
+            }
+            .quad(atlas)
+        }
+    });
+    match quad {
+        Some(quad) => (Some(quad.size), Anchor::Custom(quad.anchor)),
+        None => (
+            bones_atlas.custom_size,
+            bevy_anchor(&bones_atlas.anchor, bones_atlas.flip_x, bones_atlas.flip_y),
+        ),
+    }
+}
+
+/// Get the bevy tile flags that flip and rotate a tile like a bones tile.
+///
+/// The bevy tiles are transposed by their diagonal flag before they are flipped, like the tiles of
+/// a Tiled map.
+fn bevy_tile_flags(tile: &bones::Tile) -> TileFlags {
+    let flips = tile.tiled_flips();
+    let mut flags = TileFlags::empty();
+    flags.set(TileFlags::FLIP_X, flips.horizontal);
+    flags.set(TileFlags::FLIP_Y, flips.vertical);
+    flags.set(TileFlags::FLIP_D, flips.diagonal);
+    flags
+}
+
+/// Get the bevy color that the tile in the slot with the given index of a bones tile layer is
+/// tinted with.
+fn bevy_tile_color(layer: &bones::TileLayer, idx: usize) -> Color {
+    layer
+        .tints
+        .get(idx)
+        .map(|tint| Color::from(<[f32; 4]>::from(*tint)))
+        .unwrap_or(Color::WHITE)
+}
+
+/// Get the transform that a bones entity is rendered at, with the offset of its
+/// [`Parallax`][bones::Parallax] if it has one.
+fn bones_render_transform(
+    transform: &bones::Transform,
+    parallax: Option<&bones::Parallax>,
+) -> bones::Transform {
+    match parallax {
+        Some(parallax) => parallax.apply(*transform),
+        None => *transform,
+    }
+}
+
+/// Convert the bones visibility of an entity to a bevy visibility.
+fn bevy_visibility(visibility: Option<&bones::Visibility>) -> Visibility {
+    Visibility {
+        is_visible: bones::Visibility::is_visible(visibility),
     }
 }
 
@@ -88,14 +278,64 @@ fn sync_clear_color<W: HasBonesWorld>(
     clear_color.0 = Color::from(bones_clear_color.0);
 }
 
-/// The system that renders the bones world.
+fn sync_window<W: HasBonesWorld>(windows: Res<Windows>, world_resource: Option<ResMut<W>>) {
+    let Some(mut world_resource) = world_resource else {
+        return;
+    };
+    let world = world_resource.world();
+
+    let bones_window = match windows.get_primary() {
+        Some(window) => {
+            let size = UVec2::new(window.physical_width(), window.physical_height());
+            // Bevy's cursor position is in logical pixels from the bottom-left of the window.
+            let cursor_position = window.cursor_position().map(|position| {
+                let position = position * window.scale_factor() as f32;
+                Vec2::new(position.x, size.y as f32 - position.y)
+            });
+            bones::Window {
+                size,
+                cursor_position,
+            }
+        }
+        None => default(),
+    };
+    world.resources.init::<bones::Window>();
+    *world.resources.get::<bones::Window>().borrow_mut() = bones_window;
+}
+
+/// The system that renders the bones sprites.
+///
+/// Dynamic sprites are synced every frame. [`Static`][bones::Static] sprites are rendered with
+/// their own bevy entities, which are only synced again when the components of the sprite change,
+/// so a level with thousands of static decorations only costs as much as its dynamic sprites to
+/// sync. Bevy still extracts every sprite to its render world each frame.
 fn sync_sprites<W: HasBonesWorld>(
     mut has_init: Local<bool>,
+    mut static_sprites: Local<HashMap<bones::Entity, Entity>>,
+    mut last_static_sync: Local<Option<bones::Tick>>,
     mut commands: Commands,
     world_resource: Option<ResMut<W>>,
+    images: Res<Assets<Image>>,
     mut bevy_bones_sprites: Query<
-        (Entity, &mut Handle<Image>, &mut Sprite, &mut Transform),
-        With<BevyBonesEntity>,
+        (
+            Entity,
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
+        ),
+        (With<BevyBonesEntity>, Without<BevyBonesStaticSprite>),
+    >,
+    mut bevy_bones_static_sprites: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
+        ),
+        (With<BevyBonesStaticSprite>, Without<BevyBonesEntity>),
     >,
 ) {
     let Some(mut world_resource) = world_resource else {
@@ -107,43 +347,170 @@ fn sync_sprites<W: HasBonesWorld>(
     if !*has_init {
         world.components.init::<bones::Sprite>();
         world.components.init::<bones::Transform>();
+        world.components.init::<bones::Parallax>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.components.init::<bones::Static>();
+        world.resources.init::<bones::PixelSnap>();
         *has_init = true;
     }
 
+    let this_static_sync = world.change_tick();
     let entities = world.resources.get::<bones::Entities>();
     let entities = entities.borrow();
     let sprites = world.components.get::<bones::Sprite>();
     let sprites = sprites.borrow();
     let transforms = world.components.get::<bones::Transform>();
     let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
+    let statics = world.components.get::<bones::Static>();
+    let statics = statics.borrow();
+    let pixel_snap = world.resources.get::<bones::PixelSnap>();
+    // Static sprites are snapped when they are synced, so they have to be synced again when the
+    // snapping is changed.
+    let pixel_snap_ticks = pixel_snap.ticks();
+    let pixel_snap = pixel_snap.borrow();
+
+    let bevy_sprite = |bones_ent: bones::Entity| {
+        let bones_sprite = sprites.get(bones_ent).unwrap();
+        let mut bones_transform = pixel_snap.snap_sprite(bones_render_transform(
+            transforms.get(bones_ent).unwrap(),
+            parallaxes.get(bones_ent),
+        ));
+        bones_transform.translation.z = bones_sprite.draw_z(&bones_transform);
+        let image: Handle<Image> = bones_sprite.image.get_bevy_handle_untyped().typed();
+        let sprite = Sprite {
+            rect: bevy_sprite_rect(bones_sprite.rect, images.get(&image)),
+            custom_size: bones_sprite.custom_size,
+            color: Color::from(<[f32; 4]>::from(bones_sprite.color)),
+            flip_x: bones_sprite.flip_x,
+            flip_y: bones_sprite.flip_y,
+            anchor: bevy_anchor(
+                &bones_sprite.anchor,
+                bones_sprite.flip_x,
+                bones_sprite.flip_y,
+            ),
+        };
+        (
+            image,
+            sprite,
+            bones_transform.into_bevy(),
+            bevy_render_layers(bones_render_layers.get(bones_ent)),
+            bevy_visibility(visibilities.get(bones_ent)),
+        )
+    };
 
-    // Sync sprites
     let mut sprites_bitset = sprites.bitset().clone();
     sprites_bitset.bit_and(transforms.bitset());
+    // Repeating sprites are rendered with the nine-patches.
+    for (bones_ent, bones_sprite) in entities.iter_with(&sprites) {
+        if bones_sprite.repeat.is_some() {
+            sprites_bitset.bit_reset(bones_ent.index() as usize);
+        }
+    }
+    let mut static_bitset = sprites_bitset.clone();
+    static_bitset.bit_and(statics.bitset());
+    sprites_bitset.bit_andnot(statics.bitset());
+
+    // Sync static sprites
+    //
+    // The renderer doesn't advance the change tick of the world, so the tick may not have changed
+    // since the last sync. The changes made in the tick of the last sync may have been made after
+    // it, so they are synced again.
+    let last_sync = last_static_sync.map(|tick| bones::Tick::new(tick.get().wrapping_sub(1)));
+    // Removing an optional component doesn't change the ticks of the other components, so the
+    // sprites that lost one are synced again too.
+    let removed: HashSet<bones::Entity> = parallaxes
+        .removed()
+        .iter()
+        .chain(bones_render_layers.removed())
+        .chain(visibilities.removed())
+        .copied()
+        .collect();
+    let mut synced_static_sprites = HashMap::with_capacity(static_sprites.len());
+    for bones_ent in entities.iter_with_bitset(&static_bitset) {
+        let bevy_ent = static_sprites.remove(&bones_ent);
+        let needs_sync = removed.contains(&bones_ent)
+            || bones::Static::needs_sync(
+                [
+                    sprites.ticks(bones_ent),
+                    transforms.ticks(bones_ent),
+                    parallaxes.ticks(bones_ent),
+                    bones_render_layers.ticks(bones_ent),
+                    visibilities.ticks(bones_ent),
+                    statics.ticks(bones_ent),
+                    Some(pixel_snap_ticks),
+                ],
+                last_sync,
+                this_static_sync,
+            );
+        // The rect of the sprite can only be clamped once its image has been loaded.
+        let bones_sprite = sprites.get(bones_ent).unwrap();
+        let image_loading = bones_sprite.rect.is_some()
+            && images
+                .get(&bones_sprite.image.get_bevy_handle_untyped().typed())
+                .is_none();
+
+        let bevy_ent = match bevy_ent.map(|x| (x, bevy_bones_static_sprites.get_mut(x))) {
+            Some((bevy_ent, Ok((mut image, mut sprite, mut transform, mut layers, mut vis)))) => {
+                if needs_sync || image_loading {
+                    (*image, *sprite, *transform, *layers, *vis) = bevy_sprite(bones_ent);
+                }
+                bevy_ent
+            }
+            _ => {
+                let (texture, sprite, transform, render_layers, visibility) =
+                    bevy_sprite(bones_ent);
+                commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite,
+                            texture,
+                            transform,
+                            visibility,
+                            ..default()
+                        },
+                        render_layers,
+                        BevyBonesStaticSprite,
+                    ))
+                    .id()
+            }
+        };
+        synced_static_sprites.insert(bones_ent, bevy_ent);
+    }
+    // Despawn the sprites that aren't static anymore, or have been removed.
+    for (_, bevy_ent) in std::mem::replace(&mut *static_sprites, synced_static_sprites) {
+        commands.entity(bevy_ent).despawn();
+    }
+    *last_static_sync = Some(this_static_sync);
+
+    // Sync dynamic sprites
     let mut bones_sprite_entity_iter = entities.iter_with_bitset(&sprites_bitset);
-    for (bevy_ent, mut image, mut sprite, mut transform) in &mut bevy_bones_sprites {
+    for (bevy_ent, mut image, mut sprite, mut transform, mut render_layers, mut visibility) in
+        &mut bevy_bones_sprites
+    {
         if let Some(bones_ent) = bones_sprite_entity_iter.next() {
-            let bones_sprite = sprites.get(bones_ent).unwrap();
-            let bones_transform = transforms.get(bones_ent).unwrap();
-
-            sprite.flip_x = bones_sprite.flip_x;
-            sprite.flip_y = bones_sprite.flip_y;
-            *image = bones_sprite.image.get_bevy_handle_untyped().typed();
-            *transform = bones_transform.into_bevy();
+            (*image, *sprite, *transform, *render_layers, *visibility) = bevy_sprite(bones_ent);
         } else {
             commands.entity(bevy_ent).despawn();
         }
     }
     for bones_ent in bones_sprite_entity_iter {
-        let bones_sprite = sprites.get(bones_ent).unwrap();
-        let bones_transform = transforms.get(bones_ent).unwrap();
-
+        let (texture, sprite, transform, render_layers, visibility) = bevy_sprite(bones_ent);
         commands.spawn((
             SpriteBundle {
-                texture: bones_sprite.image.get_bevy_handle_untyped().typed(),
-                transform: bones_transform.into_bevy(),
+                sprite,
+                texture,
+                transform,
+                visibility,
                 ..default()
             },
+            render_layers,
             BevyBonesEntity,
         ));
     }
@@ -154,12 +521,17 @@ fn sync_atlas_sprites<W: HasBonesWorld>(
     mut has_init: Local<bool>,
     mut commands: Commands,
     world_resource: Option<ResMut<W>>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    bones_atlases: Res<Assets<asset::BonesAtlas>>,
+    mut warned_indices: Local<HashMap<bones::Entity, usize>>,
     mut bevy_bones_atlases: Query<
         (
             Entity,
             &mut Handle<TextureAtlas>,
             &mut TextureAtlasSprite,
             &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
         ),
         With<BevyBonesEntity>,
     >,
@@ -173,6 +545,10 @@ fn sync_atlas_sprites<W: HasBonesWorld>(
     if !*has_init {
         world.components.init::<bones::AtlasSprite>();
         world.components.init::<bones::Transform>();
+        world.components.init::<bones::Parallax>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.resources.init::<bones::PixelSnap>();
         *has_init = true;
     }
 
@@ -182,52 +558,310 @@ fn sync_atlas_sprites<W: HasBonesWorld>(
     let atlas_sprites = atlas_sprites.borrow();
     let transforms = world.components.get::<bones::Transform>();
     let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
+    let pixel_snap = world.resources.get::<bones::PixelSnap>();
+    let pixel_snap = pixel_snap.borrow();
 
     // Sync atlas sprites
     let mut atlas_bitset = atlas_sprites.bitset().clone();
     atlas_bitset.bit_and(transforms.bitset());
     let mut bones_atlas_sprite_entity_iter = entities.iter_with_bitset(&atlas_bitset);
-    for (bevy_ent, mut image, mut atlas_sprite, mut transform) in &mut bevy_bones_atlases {
+    for (bevy_ent, mut image, mut atlas_sprite, mut transform, mut render_layers, mut visibility) in
+        &mut bevy_bones_atlases
+    {
         if let Some(bones_ent) = bones_atlas_sprite_entity_iter.next() {
+            *render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+            *visibility = bevy_visibility(visibilities.get(bones_ent));
             let bones_atlas = atlas_sprites.get(bones_ent).unwrap();
-            let bones_transform = transforms.get(bones_ent).unwrap();
+            let mut bones_transform = pixel_snap.snap_sprite(bones_render_transform(
+                transforms.get(bones_ent).unwrap(),
+                parallaxes.get(bones_ent),
+            ));
+            bones_transform.translation.z = bones_atlas.draw_z(&bones_transform);
 
             *image = bones_atlas.atlas.get_bevy_handle_untyped().typed();
             *transform = bones_transform.into_bevy();
 
-            atlas_sprite.index = bones_atlas.index;
+            atlas_sprite.index = bevy_atlas_index(
+                bones_ent,
+                bones_atlas,
+                texture_atlases.get(&*image),
+                &mut warned_indices,
+            );
+            (atlas_sprite.custom_size, atlas_sprite.anchor) = bevy_atlas_quad(
+                bones_atlas,
+                atlas_sprite.index,
+                bones_atlases.get(&asset::BonesAtlas::weak_handle(&bones_atlas.atlas.path)),
+            );
             atlas_sprite.flip_x = bones_atlas.flip_x;
             atlas_sprite.flip_y = bones_atlas.flip_y;
+            atlas_sprite.color = Color::from(<[f32; 4]>::from(bones_atlas.color));
         } else {
             commands.entity(bevy_ent).despawn();
         }
     }
     for bones_ent in bones_atlas_sprite_entity_iter {
         let bones_atlas = atlas_sprites.get(bones_ent).unwrap();
-        let bones_transform = transforms.get(bones_ent).unwrap();
+        let mut bones_transform = pixel_snap.snap_sprite(bones_render_transform(
+            transforms.get(bones_ent).unwrap(),
+            parallaxes.get(bones_ent),
+        ));
+        bones_transform.translation.z = bones_atlas.draw_z(&bones_transform);
+
+        let texture_atlas: Handle<TextureAtlas> =
+            bones_atlas.atlas.get_bevy_handle_untyped().typed();
+
+        let index = bevy_atlas_index(
+            bones_ent,
+            bones_atlas,
+            texture_atlases.get(&texture_atlas),
+            &mut warned_indices,
+        );
+        let (custom_size, anchor) = bevy_atlas_quad(
+            bones_atlas,
+            index,
+            bones_atlases.get(&asset::BonesAtlas::weak_handle(&bones_atlas.atlas.path)),
+        );
 
         commands.spawn((
             SpriteSheetBundle {
-                texture_atlas: bones_atlas.atlas.get_bevy_handle_untyped().typed(),
+                sprite: TextureAtlasSprite {
+                    index,
+                    custom_size,
+                    color: Color::from(<[f32; 4]>::from(bones_atlas.color)),
+                    flip_x: bones_atlas.flip_x,
+                    flip_y: bones_atlas.flip_y,
+                    anchor,
+                    ..default()
+                },
+                texture_atlas,
+                visibility: bevy_visibility(visibilities.get(bones_ent)),
                 transform: bones_transform.into_bevy(),
                 ..default()
             },
+            bevy_render_layers(bones_render_layers.get(bones_ent)),
             BevyBonesEntity,
         ));
     }
 }
 
+/// The system that renders the bones nine-patch sprites, and the bones sprites that are
+/// [repeated][bones::Sprite::repeat].
+///
+/// Every nine-patch is rendered with a parent entity that has its transform, and a child sprite
+/// for each of its slices. The children are reused when the slices change, so resizing a
+/// nine-patch doesn't spawn new entities.
+///
+/// Bevy sprites can't wrap their texture, so repeating sprites are rendered the same way, with a
+/// child sprite for each repetition.
+fn sync_nine_patches<W: HasBonesWorld>(
+    mut has_init: Local<bool>,
+    mut commands: Commands,
+    world_resource: Option<ResMut<W>>,
+    images: Res<Assets<Image>>,
+    mut bevy_bones_nine_patches: Query<
+        (Entity, &mut Transform, &mut Visibility, Option<&Children>),
+        (With<BevyBonesNinePatch>, Without<BevyBonesNinePatchSlice>),
+    >,
+    mut bevy_bones_slices: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut RenderLayers,
+        ),
+        (With<BevyBonesNinePatchSlice>, Without<BevyBonesNinePatch>),
+    >,
+) {
+    let Some(mut world_resource) = world_resource else {
+        return;
+    };
+
+    let world = world_resource.world();
+
+    if !*has_init {
+        world.components.init::<bones::NinePatchSprite>();
+        world.components.init::<bones::Sprite>();
+        world.components.init::<bones::Transform>();
+        world.components.init::<bones::Parallax>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.resources.init::<bones::PixelSnap>();
+        *has_init = true;
+    }
+
+    let entities = world.resources.get::<bones::Entities>();
+    let entities = entities.borrow();
+    let nine_patches = world.components.get::<bones::NinePatchSprite>();
+    let nine_patches = nine_patches.borrow();
+    let sprites = world.components.get::<bones::Sprite>();
+    let sprites = sprites.borrow();
+    let transforms = world.components.get::<bones::Transform>();
+    let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
+    let pixel_snap = world.resources.get::<bones::PixelSnap>();
+    let pixel_snap = pixel_snap.borrow();
+
+    // Repeating sprites are drawn at their z index.
+    let nine_patch_transform = |bones_ent: bones::Entity| {
+        let mut transform = pixel_snap.snap_sprite(bones_render_transform(
+            transforms.get(bones_ent).unwrap(),
+            parallaxes.get(bones_ent),
+        ));
+        if nine_patches.get(bones_ent).is_none() {
+            let bones_sprite = sprites.get(bones_ent).unwrap();
+            transform.translation.z = bones_sprite.draw_z(&transform);
+        }
+        transform
+    };
+    // The slices can only be computed once the image has been loaded.
+    let slices = |bones_ent: bones::Entity| {
+        if let Some(bones_nine_patch) = nine_patches.get(bones_ent) {
+            let image: Handle<Image> = bones_nine_patch.image.get_bevy_handle_untyped().typed();
+            let slices = images
+                .get(&image)
+                .map(|x| bones_nine_patch.slices(x.size()))
+                .unwrap_or_default();
+            let sprite = Sprite {
+                color: Color::from(<[f32; 4]>::from(bones_nine_patch.color)),
+                ..default()
+            };
+            (image, slices, sprite)
+        } else {
+            let bones_sprite = sprites.get(bones_ent).unwrap();
+            let image: Handle<Image> = bones_sprite.image.get_bevy_handle_untyped().typed();
+            let tiles = images
+                .get(&image)
+                .map(|x| bones_sprite.tiles(x.size()))
+                .unwrap_or_default();
+            let sprite = Sprite {
+                color: Color::from(<[f32; 4]>::from(bones_sprite.color)),
+                flip_x: bones_sprite.flip_x,
+                flip_y: bones_sprite.flip_y,
+                ..default()
+            };
+            (image, tiles, sprite)
+        }
+    };
+    let slice_sprite = |sprite: &Sprite, slice: &bones::NinePatchSlice| Sprite {
+        custom_size: Some(slice.size),
+        rect: Some(Rect {
+            min: slice.rect_min,
+            max: slice.rect_max,
+        }),
+        ..sprite.clone()
+    };
+
+    // Sync nine-patches
+    let mut nine_patch_bitset = nine_patches.bitset().clone();
+    for (bones_ent, bones_sprite) in entities.iter_with(&sprites) {
+        if bones_sprite.repeat.is_some() {
+            nine_patch_bitset.bit_set(bones_ent.index() as usize);
+        }
+    }
+    nine_patch_bitset.bit_and(transforms.bitset());
+    let mut bones_nine_patch_entity_iter = entities.iter_with_bitset(&nine_patch_bitset);
+    for (bevy_ent, mut transform, mut visibility, children) in &mut bevy_bones_nine_patches {
+        let Some(bones_ent) = bones_nine_patch_entity_iter.next() else {
+            commands.entity(bevy_ent).despawn_recursive();
+            continue;
+        };
+        let bones_transform = nine_patch_transform(bones_ent);
+        let render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+        *transform = bones_transform.into_bevy();
+        *visibility = bevy_visibility(visibilities.get(bones_ent));
+
+        let (image, slices, base_sprite) = slices(bones_ent);
+        let children = children.map(|x| &**x).unwrap_or_default();
+        for (i, slice) in slices.iter().enumerate() {
+            let sprite = slice_sprite(&base_sprite, slice);
+            let slice_transform = Transform::from_translation(slice.position.extend(0.0));
+            if let Some(Ok((mut bevy_image, mut bevy_sprite, mut bevy_transform, mut layers))) =
+                children.get(i).map(|&x| bevy_bones_slices.get_mut(x))
+            {
+                *bevy_image = image.clone();
+                *bevy_sprite = sprite;
+                *bevy_transform = slice_transform;
+                *layers = render_layers;
+            } else {
+                let child = commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite,
+                            texture: image.clone(),
+                            transform: slice_transform,
+                            ..default()
+                        },
+                        render_layers,
+                        BevyBonesNinePatchSlice,
+                    ))
+                    .id();
+                commands.entity(bevy_ent).add_child(child);
+            }
+        }
+        if children.len() > slices.len() {
+            let extra = &children[slices.len()..];
+            commands.entity(bevy_ent).remove_children(extra);
+            for &child in extra {
+                commands.entity(child).despawn();
+            }
+        }
+    }
+    for bones_ent in bones_nine_patch_entity_iter {
+        let bones_transform = nine_patch_transform(bones_ent);
+        let render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+
+        let (image, slices, base_sprite) = slices(bones_ent);
+        commands
+            .spawn((
+                SpatialBundle {
+                    visibility: bevy_visibility(visibilities.get(bones_ent)),
+                    transform: bones_transform.into_bevy(),
+                    ..default()
+                },
+                BevyBonesNinePatch,
+            ))
+            .with_children(|parent| {
+                for slice in &slices {
+                    parent.spawn((
+                        SpriteBundle {
+                            sprite: slice_sprite(&base_sprite, slice),
+                            texture: image.clone(),
+                            transform: Transform::from_translation(slice.position.extend(0.0)),
+                            ..default()
+                        },
+                        render_layers,
+                        BevyBonesNinePatchSlice,
+                    ));
+                }
+            });
+    }
+}
+
 /// The system that renders the bones world.
 fn sync_cameras<W: HasBonesWorld>(
     mut has_init: Local<bool>,
     mut commands: Commands,
     world_resource: Option<ResMut<W>>,
+    windows: Res<Windows>,
     mut bevy_bones_cameras: Query<
         (
             Entity,
             &mut Camera,
+            &mut Camera2d,
             &mut OrthographicProjection,
             &mut Transform,
+            &mut RenderLayers,
         ),
         With<BevyBonesEntity>,
     >,
@@ -241,6 +875,10 @@ fn sync_cameras<W: HasBonesWorld>(
     if !*has_init {
         world.components.init::<bones::Transform>();
         world.components.init::<bones::Camera>();
+        world.components.init::<bones::CameraShake>();
+        world.components.init::<bones::CameraBounds>();
+        world.components.init::<bones::RenderLayers>();
+        world.resources.init::<bones::PixelSnap>();
         *has_init = true;
     }
 
@@ -248,48 +886,119 @@ fn sync_cameras<W: HasBonesWorld>(
     let entities = entities.borrow();
     let transforms = world.components.get::<bones::Transform>();
     let transforms = transforms.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let pixel_snap = world.resources.get::<bones::PixelSnap>();
+    let pixel_snap = pixel_snap.borrow();
     let cameras = world.components.get::<bones::Camera>();
     let cameras = cameras.borrow();
+    let camera_shakes = world.components.get::<bones::CameraShake>();
+    let camera_shakes = camera_shakes.borrow();
+    let camera_bounds = world.components.get::<bones::CameraBounds>();
+    let camera_bounds = camera_bounds.borrow();
+
+    let window_size = windows
+        .get_primary()
+        .map(|window| UVec2::new(window.physical_width(), window.physical_height()))
+        .unwrap_or_default();
+    // The shake is applied to the bevy transform only, so it never moves the bones camera. The
+    // bounds are applied again after it, so the shake can't show anything outside of them, and the
+    // camera is snapped to the pixel grid last.
+    let camera_transform = |bones_ent: bones::Entity| {
+        let bones_camera = cameras.get(bones_ent).unwrap();
+        let mut bones_transform = *transforms.get(bones_ent).unwrap();
+        if let Some(shake) = camera_shakes.get(bones_ent) {
+            bones_transform = shake.apply(bones_transform);
+        }
+        if let Some(bounds) = camera_bounds.get(bones_ent) {
+            bones_transform = bounds.clamp_transform(bones_camera, window_size, bones_transform);
+        }
+        pixel_snap.snap_camera(bones_camera, window_size, bones_transform)
+    };
+    // Viewports that don't fit in the window after it was resized are clamped, because bevy
+    // panics when rendering to a viewport outside of the window.
+    let viewport = |bones_camera: &bones::Camera| {
+        let viewport = bones_camera.viewport?.clamp_to_window(window_size)?;
+        Some(Viewport {
+            physical_position: viewport.position,
+            physical_size: viewport.size,
+            depth: viewport.depth_min..viewport.depth_max,
+        })
+    };
+    // The visible size is computed by bones, so that the bevy camera shows exactly the area that
+    // bones converts screen positions with.
+    let visible_height =
+        |bones_camera: &bones::Camera| Some(bones_camera.visible_size(window_size)?.y);
 
     // Sync cameras
     let mut cameras_bitset = cameras.bitset().clone();
     cameras_bitset.bit_and(transforms.bitset());
-    let mut bones_camera_entity_iter = entities.iter_with_bitset(&cameras_bitset);
-    for (bevy_ent, mut camera, mut projection, mut transform) in &mut bevy_bones_cameras {
-        if let Some(bones_ent) = bones_camera_entity_iter.next() {
+    // Cameras are rendered in order of priority, and then in order of their bones entities, so
+    // that cameras with the same priority are still rendered in a consistent order.
+    let mut bones_cameras = entities
+        .iter_with_bitset(&cameras_bitset)
+        .collect::<Vec<_>>();
+    bones_cameras.sort_by_key(|&bones_ent| cameras.get(bones_ent).unwrap().priority);
+    // Only the first active camera clears the window with the clear color, so that the cameras
+    // after it render over it.
+    let first_active = bones_cameras
+        .iter()
+        .position(|&bones_ent| cameras.get(bones_ent).unwrap().active);
+    let clear_color = |order: usize, bones_camera: &bones::Camera| match bones_camera.clear {
+        bones::CameraClear::Inherit if first_active == Some(order) => ClearColorConfig::Default,
+        bones::CameraClear::Inherit => ClearColorConfig::None,
+        bones::CameraClear::Color(color) => ClearColorConfig::Custom(Color::from(color)),
+        bones::CameraClear::None => ClearColorConfig::None,
+    };
+
+    let mut bones_camera_entity_iter = bones_cameras.iter().copied().enumerate();
+    for (bevy_ent, mut camera, mut camera_2d, mut projection, mut transform, mut render_layers) in
+        &mut bevy_bones_cameras
+    {
+        if let Some((order, bones_ent)) = bones_camera_entity_iter.next() {
+            *render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
             let bones_camera = cameras.get(bones_ent).unwrap();
-            let bones_transform = transforms.get(bones_ent).unwrap();
 
             camera.is_active = bones_camera.active;
-            match projection.scaling_mode {
-                ScalingMode::FixedVertical(height) if height != bones_camera.height => {
-                    projection.scaling_mode = ScalingMode::FixedVertical(bones_camera.height)
+            camera.priority = order as isize;
+            camera.viewport = viewport(bones_camera);
+            camera_2d.clear_color = clear_color(order, bones_camera);
+            // Nothing is visible if the window has no area, so the projection is kept.
+            if let Some(visible_height) = visible_height(bones_camera) {
+                match projection.scaling_mode {
+                    ScalingMode::FixedVertical(height) if height == visible_height => (),
+                    _ => projection.scaling_mode = ScalingMode::FixedVertical(visible_height),
                 }
-                _ => (),
             }
 
-            *transform = bones_transform.into_bevy();
+            *transform = camera_transform(bones_ent).into_bevy();
         } else {
             commands.entity(bevy_ent).despawn();
         }
     }
-    for bones_ent in bones_camera_entity_iter {
+    for (order, bones_ent) in bones_camera_entity_iter {
         let bones_camera = cameras.get(bones_ent).unwrap();
-        let bones_transform = transforms.get(bones_ent).unwrap();
+        let mut projection = OrthographicProjection::default();
+        if let Some(visible_height) = visible_height(bones_camera) {
+            projection.scaling_mode = ScalingMode::FixedVertical(visible_height);
+        }
 
         commands.spawn((
             Camera2dBundle {
                 camera: Camera {
                     is_active: bones_camera.active,
+                    priority: order as isize,
+                    viewport: viewport(bones_camera),
                     ..default()
                 },
-                projection: OrthographicProjection {
-                    scaling_mode: ScalingMode::FixedVertical(bones_camera.height),
-                    ..default()
+                camera_2d: Camera2d {
+                    clear_color: clear_color(order, bones_camera),
                 },
-                transform: bones_transform.into_bevy(),
+                projection,
+                transform: camera_transform(bones_ent).into_bevy(),
                 ..default()
             },
+            bevy_render_layers(bones_render_layers.get(bones_ent)),
             BevyBonesEntity,
         ));
     }
@@ -305,6 +1014,8 @@ fn sync_tilemaps<W: HasBonesWorld>(
             &mut TileMap,
             &mut Handle<TextureAtlas>,
             &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
         ),
         With<BevyBonesEntity>,
     >,
@@ -318,6 +1029,9 @@ fn sync_tilemaps<W: HasBonesWorld>(
     if !*has_init {
         world.components.init::<bones::Tile>();
         world.components.init::<bones::TileLayer>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.components.init::<bones::Parallax>();
         *has_init = true;
     }
 
@@ -329,16 +1043,35 @@ fn sync_tilemaps<W: HasBonesWorld>(
     let tile_layers = tile_layers.borrow();
     let transforms = world.components.get::<bones::Transform>();
     let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
 
     // Sync tile layers
     let mut tile_layers_bitset = tile_layers.bitset().clone();
     tile_layers_bitset.bit_and(transforms.bitset());
 
-    let mut bones_tile_layer_entity_iter = entities.iter_with_bitset(&tile_layers_bitset);
-    for (bevy_ent, mut tile_map, mut atlas, mut transform) in &mut bevy_bones_tile_layers {
+    // The layers with other projections are rendered by `sync_projected_tilemaps`.
+    let mut bones_tile_layer_entity_iter =
+        entities
+            .iter_with_bitset(&tile_layers_bitset)
+            .filter(|ent| {
+                tile_layers.get(*ent).unwrap().projection == bones::TileProjection::Orthogonal
+            });
+    for (bevy_ent, mut tile_map, mut atlas, mut transform, mut render_layers, mut visibility) in
+        &mut bevy_bones_tile_layers
+    {
         if let Some(bones_ent) = bones_tile_layer_entity_iter.next() {
+            *render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+            *visibility = bevy_visibility(visibilities.get(bones_ent));
             let bones_tile_layer = tile_layers.get(bones_ent).unwrap();
-            let bones_transform = transforms.get(bones_ent).unwrap();
+            let bones_transform = bones_render_transform(
+                transforms.get(bones_ent).unwrap(),
+                parallaxes.get(bones_ent),
+            );
 
             *atlas = bones_tile_layer.atlas.get_bevy_handle_untyped().typed();
             *transform = bones_transform.into_bevy();
@@ -356,17 +1089,9 @@ fn sync_tilemaps<W: HasBonesWorld>(
                         .map(|e| {
                             let tile = tiles.get(e)?;
                             Some(Tile {
-                                sprite_index: tile.idx as _,
-                                color: default(),
-                                flags: if tile.flip_x {
-                                    TileFlags::FLIP_X
-                                } else {
-                                    TileFlags::empty()
-                                } | if tile.flip_y {
-                                    TileFlags::FLIP_Y
-                                } else {
-                                    TileFlags::empty()
-                                },
+                                sprite_index: bones_tile_layer.animated_idx(tile.idx) as _,
+                                color: bevy_tile_color(bones_tile_layer, idx),
+                                flags: bevy_tile_flags(tile),
                             })
                         })
                         .flatten();
@@ -381,7 +1106,10 @@ fn sync_tilemaps<W: HasBonesWorld>(
     }
     for bones_ent in bones_tile_layer_entity_iter {
         let bones_tile_layer = tile_layers.get(bones_ent).unwrap();
-        let bones_transform = transforms.get(bones_ent).unwrap();
+        let bones_transform = bones_render_transform(
+            transforms.get(bones_ent).unwrap(),
+            parallaxes.get(bones_ent),
+        );
 
         let mut tile_map = TileMap::default();
 
@@ -397,17 +1125,9 @@ fn sync_tilemaps<W: HasBonesWorld>(
                     .map(|e| {
                         let tile = tiles.get(e)?;
                         Some(Tile {
-                            sprite_index: tile.idx as _,
-                            color: default(),
-                            flags: if tile.flip_x {
-                                TileFlags::FLIP_X
-                            } else {
-                                TileFlags::empty()
-                            } | if tile.flip_y {
-                                TileFlags::FLIP_Y
-                            } else {
-                                TileFlags::empty()
-                            },
+                            sprite_index: bones_tile_layer.animated_idx(tile.idx) as _,
+                            color: bevy_tile_color(bones_tile_layer, idx),
+                            flags: bevy_tile_flags(tile),
                         })
                     })
                     .flatten();
@@ -421,10 +1141,353 @@ fn sync_tilemaps<W: HasBonesWorld>(
         commands.spawn((
             TileMapBundle {
                 tilemap: tile_map,
+                visibility: bevy_visibility(visibilities.get(bones_ent)),
                 transform,
                 ..default()
             },
+            bevy_render_layers(bones_render_layers.get(bones_ent)),
             BevyBonesEntity,
         ));
     }
 }
+
+/// The system that renders the bones tile layers with an isometric or hexagonal projection.
+///
+/// Isometric and hexagonal tiles overlap each other, so they can't be batched into a bevy tilemap,
+/// and each tile is rendered by its own sprite instead. The sprites are drawn from the bottom of
+/// the diamond or hex of their cell up, so that tiles that are taller than the grid stand on their cell, and
+/// are put in front of each other by the [depth][bones::TileLayer::tile_depth] of their cell.
+fn sync_projected_tilemaps<W: HasBonesWorld>(
+    mut has_init: Local<bool>,
+    mut commands: Commands,
+    world_resource: Option<ResMut<W>>,
+    mut bevy_bones_tiles: Query<
+        (
+            Entity,
+            &mut Handle<TextureAtlas>,
+            &mut TextureAtlasSprite,
+            &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
+        ),
+        With<BevyBonesProjectedTile>,
+    >,
+) {
+    let Some(mut world_resource) = world_resource else {
+        return;
+    };
+
+    let world = world_resource.world();
+
+    if !*has_init {
+        world.components.init::<bones::Tile>();
+        world.components.init::<bones::TileLayer>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.components.init::<bones::Parallax>();
+        *has_init = true;
+    }
+
+    let entities = world.resources.get::<bones::Entities>();
+    let entities = entities.borrow();
+    let tiles = world.components.get::<bones::Tile>();
+    let tiles = tiles.borrow();
+    let tile_layers = world.components.get::<bones::TileLayer>();
+    let tile_layers = tile_layers.borrow();
+    let transforms = world.components.get::<bones::Transform>();
+    let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
+
+    let mut tile_layers_bitset = tile_layers.bitset().clone();
+    tile_layers_bitset.bit_and(transforms.bitset());
+
+    // The sprites of the tiles of every projected layer, in order.
+    let mut bones_tile_iter = entities
+        .iter_with_bitset(&tile_layers_bitset)
+        .filter(|ent| {
+            tile_layers.get(*ent).unwrap().projection != bones::TileProjection::Orthogonal
+        })
+        .flat_map(|bones_ent| {
+            let layer = tile_layers.get(bones_ent).unwrap();
+            let layer_transform = bones_render_transform(
+                transforms.get(bones_ent).unwrap(),
+                parallaxes.get(bones_ent),
+            );
+            let render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+            let visibility = bevy_visibility(visibilities.get(bones_ent));
+            let tiles = &tiles;
+            let width = layer.grid_size.x;
+            layer
+                .tiles
+                .iter()
+                .enumerate()
+                .filter_map(move |(idx, entity)| {
+                    let pos = UVec2::new(idx as u32 % width, idx as u32 / width);
+                    let tile = tiles.get((*entity)?)?;
+                    let position = layer.tile_to_world(&layer_transform, pos)
+                        - Vec2::new(0.0, layer.tile_size.y / 2.0 * layer_transform.scale.y);
+                    let mut transform = layer_transform.into_bevy();
+                    transform.translation =
+                        position.extend(layer_transform.translation.z + layer.tile_depth(pos));
+                    transform.rotation *=
+                        Quat::from_rotation_z(-(tile.rotation.degrees() as f32).to_radians());
+                    let sprite = TextureAtlasSprite {
+                        index: layer.animated_idx(tile.idx),
+                        flip_x: tile.flip_x,
+                        flip_y: tile.flip_y,
+                        color: bevy_tile_color(layer, idx),
+                        anchor: Anchor::BottomCenter,
+                        ..default()
+                    };
+                    let atlas: Handle<TextureAtlas> = layer.atlas.get_bevy_handle_untyped().typed();
+                    Some((
+                        atlas,
+                        sprite,
+                        transform,
+                        render_layers.clone(),
+                        visibility.clone(),
+                    ))
+                })
+        });
+    for (bevy_ent, mut atlas, mut sprite, mut transform, mut render_layers, mut visibility) in
+        &mut bevy_bones_tiles
+    {
+        if let Some(tile) = bones_tile_iter.next() {
+            (*atlas, *sprite, *transform, *render_layers, *visibility) = tile;
+        } else {
+            commands.entity(bevy_ent).despawn();
+        }
+    }
+    for (texture_atlas, sprite, transform, render_layers, visibility) in bones_tile_iter {
+        commands.spawn((
+            SpriteSheetBundle {
+                sprite,
+                texture_atlas,
+                transform,
+                visibility,
+                ..default()
+            },
+            render_layers,
+            BevyBonesProjectedTile,
+        ));
+    }
+}
+
+/// The system that renders the bones chunked tile layers.
+///
+/// Only the tiles in the chunks that can be seen by an active camera are synced, so the cost of a
+/// layer depends on the size of the screen instead of the size of the map.
+fn sync_chunked_tilemaps<W: HasBonesWorld>(
+    mut has_init: Local<bool>,
+    mut commands: Commands,
+    world_resource: Option<ResMut<W>>,
+    windows: Res<Windows>,
+    mut bevy_bones_tile_layers: Query<
+        (
+            Entity,
+            &mut TileMap,
+            &mut Handle<TextureAtlas>,
+            &mut Transform,
+            &mut RenderLayers,
+            &mut Visibility,
+        ),
+        With<BevyBonesChunkedTileLayer>,
+    >,
+) {
+    let Some(mut world_resource) = world_resource else {
+        return;
+    };
+
+    let world = world_resource.world();
+
+    if !*has_init {
+        world.components.init::<bones::Tile>();
+        world.components.init::<bones::ChunkedTileLayer>();
+        world.components.init::<bones::Camera>();
+        world.components.init::<bones::RenderLayers>();
+        world.components.init::<bones::Visibility>();
+        world.components.init::<bones::Parallax>();
+        *has_init = true;
+    }
+
+    let entities = world.resources.get::<bones::Entities>();
+    let entities = entities.borrow();
+    let tiles = world.components.get::<bones::Tile>();
+    let tiles = tiles.borrow();
+    let tile_layers = world.components.get::<bones::ChunkedTileLayer>();
+    let tile_layers = tile_layers.borrow();
+    let transforms = world.components.get::<bones::Transform>();
+    let transforms = transforms.borrow();
+    let parallaxes = world.components.get::<bones::Parallax>();
+    let parallaxes = parallaxes.borrow();
+    let cameras = world.components.get::<bones::Camera>();
+    let cameras = cameras.borrow();
+    let bones_render_layers = world.components.get::<bones::RenderLayers>();
+    let bones_render_layers = bones_render_layers.borrow();
+    let visibilities = world.components.get::<bones::Visibility>();
+    let visibilities = visibilities.borrow();
+
+    // The rectangles of the world that the active cameras show.
+    let window_size = windows
+        .get_primary()
+        .map(|window| UVec2::new(window.physical_width(), window.physical_height()))
+        .unwrap_or_default();
+    let views = entities
+        .iter_with((&cameras, &transforms))
+        .filter(|(_, (camera, _))| camera.active)
+        .filter_map(|(_, (camera, transform))| camera.visible_rect(transform, window_size))
+        .collect::<Vec<_>>();
+
+    // Get the tiles of the layer that can be seen, with a margin of one tile, so that the tiles
+    // at the edges of the screen don't pop in when the camera shakes.
+    let visible_tiles = |layer: &bones::ChunkedTileLayer, transform: &bones::Transform| {
+        let views = views.iter().map(|view| {
+            let (min, max) = layer.tile_rect(transform, *view);
+            let min = (min.as_ivec2() - IVec2::ONE).max(IVec2::ZERO).as_uvec2();
+            (min, max + UVec2::ONE)
+        });
+        views
+            .flat_map(|(min, max)| layer.tinted_tiles_in_rect(min, max))
+            .filter_map(|(pos, entity, tint)| {
+                let tile = tiles.get(entity)?;
+                let tile = Tile {
+                    sprite_index: layer.animated_idx(tile.idx) as _,
+                    color: Color::from(<[f32; 4]>::from(tint)),
+                    flags: bevy_tile_flags(tile),
+                };
+                Some((IVec3::new(pos.x as i32, pos.y as i32, 0), Some(tile)))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Sync tile layers
+    let mut tile_layers_bitset = tile_layers.bitset().clone();
+    tile_layers_bitset.bit_and(transforms.bitset());
+
+    let mut bones_tile_layer_entity_iter = entities.iter_with_bitset(&tile_layers_bitset);
+    for (bevy_ent, mut tile_map, mut atlas, mut transform, mut render_layers, mut visibility) in
+        &mut bevy_bones_tile_layers
+    {
+        if let Some(bones_ent) = bones_tile_layer_entity_iter.next() {
+            *render_layers = bevy_render_layers(bones_render_layers.get(bones_ent));
+            *visibility = bevy_visibility(visibilities.get(bones_ent));
+            let bones_tile_layer = tile_layers.get(bones_ent).unwrap();
+            let bones_transform = bones_render_transform(
+                transforms.get(bones_ent).unwrap(),
+                parallaxes.get(bones_ent),
+            );
+
+            *atlas = bones_tile_layer.atlas.get_bevy_handle_untyped().typed();
+            *transform = bones_transform.into_bevy();
+            transform.translation += bones_tile_layer.tile_size.extend(0.0) / 2.0;
+
+            tile_map.clear();
+            tile_map.set_tiles(visible_tiles(bones_tile_layer, &bones_transform));
+        } else {
+            commands.entity(bevy_ent).despawn();
+        }
+    }
+    for bones_ent in bones_tile_layer_entity_iter {
+        let bones_tile_layer = tile_layers.get(bones_ent).unwrap();
+        let bones_transform = bones_render_transform(
+            transforms.get(bones_ent).unwrap(),
+            parallaxes.get(bones_ent),
+        );
+
+        let mut tile_map = TileMap::default();
+        tile_map.set_tiles(visible_tiles(bones_tile_layer, &bones_transform));
+
+        let mut transform = bones_transform.into_bevy();
+        transform.translation += bones_tile_layer.tile_size.extend(0.0) / 2.0;
+        commands.spawn((
+            TileMapBundle {
+                tilemap: tile_map,
+                visibility: bevy_visibility(visibilities.get(bones_ent)),
+                transform,
+                ..default()
+            },
+            bevy_render_layers(bones_render_layers.get(bones_ent)),
+            BevyBonesChunkedTileLayer,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+
+    #[derive(Resource)]
+    struct BonesWorld(bones::World);
+
+    impl HasBonesWorld for BonesWorld {
+        fn world(&mut self) -> &mut bones::World {
+            &mut self.0
+        }
+    }
+
+    /// Advance the change tick of the bones world, like a game step would, and sync the sprites.
+    fn step(app: &mut App) {
+        app.world.resource::<BonesWorld>().0.increment_change_tick();
+        app.update();
+    }
+
+    fn static_sprite_visible(app: &mut App) -> bool {
+        app.world
+            .query_filtered::<&Visibility, With<BevyBonesStaticSprite>>()
+            .single(&app.world)
+            .is_visible
+    }
+
+    #[test]
+    fn static_sprite_component_removed() {
+        let mut world = bones::World::new();
+        let entity = world
+            .run_system(
+                |mut entities: bones::ResMut<bones::Entities>,
+                 mut sprites: bones::CompMut<bones::Sprite>,
+                 mut transforms: bones::CompMut<bones::Transform>,
+                 mut visibilities: bones::CompMut<bones::Visibility>,
+                 mut statics: bones::CompMut<bones::Static>| {
+                    let entity = entities.create();
+                    sprites.insert(entity, bones::Sprite::default());
+                    transforms.insert(entity, bones::Transform::default());
+                    visibilities.insert(entity, bones::Visibility { visible: false });
+                    statics.insert(entity, bones::Static);
+                    entity
+                },
+            )
+            .unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_system(sync_sprites::<BonesWorld>)
+            .insert_resource(BonesWorld(world));
+
+        // Let the sprite be synced a few times, so that it isn't synced again just because it
+        // was spawned recently.
+        for _ in 0..3 {
+            step(&mut app);
+        }
+        assert!(!static_sprite_visible(&mut app));
+
+        // Sprites without a visibility are visible.
+        app.world
+            .resource_mut::<BonesWorld>()
+            .0
+            .run_system(move |mut visibilities: bones::CompMut<bones::Visibility>| {
+                visibilities.remove(entity);
+            })
+            .unwrap();
+        step(&mut app);
+        assert!(static_sprite_visible(&mut app));
+    }
+}
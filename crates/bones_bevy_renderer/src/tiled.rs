@@ -0,0 +1,935 @@
+//! Loader for [Tiled](https://www.mapeditor.org/) maps.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use base64::Engine;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bones_bevy_asset::BonesBevyAssetLoad;
+use bones_lib::prelude as bones;
+use glam::{UVec2, Vec2};
+use serde::{de::IgnoredAny, Deserialize};
+
+use crate::asset::{fill_tile_layer, relative_path, texture_atlas};
+
+/// A Tiled map, loaded from a `.tmx` file along with its tilesets.
+///
+/// The map is converted to the bones coordinate space, where y points up and the bottom-left
+/// corner of the map is at `(0, 0)`. The rows of the tile layers are flipped, so that the bottom
+/// row of the map is row `0` of the [`TileLayer`][bones::TileLayer]s, and the positions of the
+/// objects are measured from the bottom of the map.
+///
+/// The [`tile_layers`][Self::tile_layers] can be [spawned][Self::spawn] into a bones world, and the
+/// [`object_layers`][Self::object_layers] are left to the game, which knows what its objects mean.
+///
+/// Orthogonal, isometric, and isometric staggered maps are supported, as long as they're finite and
+/// their tilesets are made from a single image. The tile layers of isometric maps get the
+/// matching [`TileProjection`][bones::TileProjection]. Tiled measures the positions of the objects
+/// of an isometric map along the axes of its grid, with [`tile_size.y`][Self::tile_size] per tile,
+/// and they're kept that way, so they can be projected with
+/// [`TileLayer::tile_to_world()`][bones::TileLayer::tile_to_world] after they're divided by it.
+/// The staggered maps must be staggered along the y axis.
+///
+/// The tile layer data may be encoded as CSV or as uncompressed base64.
+#[derive(TypeUuid, Clone, Debug)]
+#[uuid = "2d8e4f60-1a7b-4c93-b5e2-7f0c9a3d6e18"]
+pub struct TiledMap {
+    /// The size of the map in tiles.
+    pub size: UVec2,
+    /// The size of the tiles of the map's grid.
+    pub tile_size: Vec2,
+    /// How the map's grid is laid out, from its orientation.
+    pub projection: bones::TileProjection,
+    /// The tilesets of the map, in the order of their first global tile IDs.
+    pub tilesets: Vec<TiledTileset>,
+    /// The visible tile layers of the map, from the bottom to the top.
+    pub tile_layers: Vec<TiledTileLayer>,
+    /// The object layers of the map, from the bottom to the top, including the hidden ones.
+    pub object_layers: Vec<TiledObjectLayer>,
+    /// The custom properties of the map.
+    pub properties: TiledProperties,
+}
+
+/// A tileset of a [`TiledMap`].
+#[derive(Clone, Debug)]
+pub struct TiledTileset {
+    /// The name of the tileset.
+    pub name: String,
+    /// The global tile ID of the first tile of the tileset.
+    pub first_gid: u32,
+    /// The size of the tiles in the tileset image.
+    pub tile_size: Vec2,
+    /// The number of tiles in the tileset.
+    pub tile_count: u32,
+    /// The tileset image.
+    pub image: bones::Handle<bones::Image>,
+    /// The atlas of the tiles in the tileset image.
+    ///
+    /// It's loaded as a labeled asset of the map, so it can be used by the tile layers.
+    pub atlas: bones::Handle<bones::Atlas>,
+    /// The custom properties of the tileset.
+    pub properties: TiledProperties,
+}
+
+/// A tile layer of a [`TiledMap`].
+///
+/// A bones [`TileLayer`][bones::TileLayer] only has one atlas, so a Tiled layer that uses tiles
+/// from more than one tileset is split into one [`TiledTileLayer`] per tileset, with the same name
+/// and z offset.
+#[derive(Clone, Debug)]
+pub struct TiledTileLayer {
+    /// The name of the layer.
+    pub name: String,
+    /// The z offset of the layer.
+    ///
+    /// It's the index of the layer in the drawing order of all of the layers of the map, so that
+    /// the layers are drawn in the same order as in Tiled, unless the layer has a `z` property.
+    pub z: f32,
+    /// The offset of the layer from the bottom-left corner of the map, including the offsets of
+    /// the groups that it is in.
+    pub offset: Vec2,
+    /// The size of the layer in tiles.
+    pub grid_size: UVec2,
+    /// The size of the tiles of the layer's grid.
+    pub tile_size: Vec2,
+    /// How the layer's grid is laid out, like the [`projection`][TiledMap::projection] of the map.
+    pub projection: bones::TileProjection,
+    /// The index of the tileset in the [`tilesets`][TiledMap::tilesets] of the map.
+    pub tileset: usize,
+    /// The atlas of the tileset.
+    pub atlas: bones::Handle<bones::Atlas>,
+    /// The tiles of the layer, in the same order as the [`tiles`][bones::TileLayer::tiles] of a
+    /// bones tile layer.
+    pub tiles: Vec<Option<bones::Tile>>,
+    /// The custom properties of the layer.
+    pub properties: TiledProperties,
+}
+
+/// An object layer of a [`TiledMap`].
+#[derive(Clone, Debug)]
+pub struct TiledObjectLayer {
+    /// The name of the layer.
+    pub name: String,
+    /// The z offset of the layer, like the [`z`][TiledTileLayer::z] of a tile layer.
+    pub z: f32,
+    /// The offset of the layer from the bottom-left corner of the map, including the offsets of
+    /// the groups that it is in.
+    pub offset: Vec2,
+    /// Whether the layer and the groups that it is in are visible.
+    ///
+    /// Hidden object layers are often used for spawn points and triggers, so they're loaded too.
+    pub visible: bool,
+    /// The objects of the layer.
+    pub objects: Vec<TiledObject>,
+    /// The custom properties of the layer.
+    pub properties: TiledProperties,
+}
+
+/// An object of a [`TiledObjectLayer`].
+#[derive(Clone, Debug)]
+pub struct TiledObject {
+    /// The unique ID of the object in the map.
+    pub id: u32,
+    /// The name of the object.
+    pub name: String,
+    /// The class of the object, which is its `type` in maps made with Tiled before 1.9.
+    pub class: String,
+    /// The position of the object, relative to its layer.
+    ///
+    /// It's the top-left corner of rectangles and ellipses, the bottom-left corner of tiles, and
+    /// the origin of the points of polygons and polylines.
+    pub position: Vec2,
+    /// The width and the height of the object.
+    pub size: Vec2,
+    /// The clockwise rotation of the object around its position, in degrees.
+    pub rotation: f32,
+    /// Whether the object is visible.
+    pub visible: bool,
+    /// The shape of the object.
+    pub shape: TiledShape,
+    /// The custom properties of the object.
+    pub properties: TiledProperties,
+}
+
+/// The shape of a [`TiledObject`].
+#[derive(Clone, Debug)]
+pub enum TiledShape {
+    /// A rectangle with the size of the object.
+    Rect,
+    /// An ellipse that fits in the rectangle of the object.
+    Ellipse,
+    /// A point at the position of the object.
+    Point,
+    /// A closed polygon, with points relative to the position of the object.
+    Polygon(Vec<Vec2>),
+    /// An open line, with points relative to the position of the object.
+    Polyline(Vec<Vec2>),
+    /// A tile with the size of the object.
+    Tile {
+        /// The index of the tileset in the [`tilesets`][TiledMap::tilesets] of the map.
+        tileset: usize,
+        /// The tile, with its flips.
+        tile: bones::Tile,
+    },
+}
+
+impl TiledObject {
+    /// Get the rectangle that the object covers, relative to its layer, ignoring its rotation.
+    ///
+    /// Points cover an empty rectangle at their position.
+    pub fn rect(&self) -> bones::Rect {
+        let Vec2 { x, y } = self.position;
+        let Vec2 { x: w, y: h } = self.size;
+        match &self.shape {
+            TiledShape::Rect | TiledShape::Ellipse => bones::Rect::new(x, y - h, x + w, y),
+            TiledShape::Tile { .. } => bones::Rect::new(x, y, x + w, y + h),
+            TiledShape::Point => bones::Rect::new(x, y, x, y),
+            TiledShape::Polygon(points) | TiledShape::Polyline(points) => {
+                let min = points
+                    .iter()
+                    .copied()
+                    .fold(Vec2::splat(f32::MAX), Vec2::min);
+                let max = points
+                    .iter()
+                    .copied()
+                    .fold(Vec2::splat(f32::MIN), Vec2::max);
+                bones::Rect::from_corners(self.position + min, self.position + max)
+            }
+        }
+    }
+}
+
+/// The custom properties of a Tiled map, layer, tileset, or object, by their names.
+pub type TiledProperties = HashMap<String, TiledProperty>;
+
+/// The value of a custom property of a Tiled map.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledProperty {
+    /// A `bool` property.
+    Bool(bool),
+    /// An `int` property.
+    Int(i64),
+    /// A `float` property.
+    Float(f64),
+    /// A `string`, `color`, or `file` property.
+    ///
+    /// Colors are in the `#AARRGGBB` format, and files are relative to the file that the property
+    /// is in.
+    String(String),
+    /// An `object` property, with the ID of the object, or `0` for no object.
+    Object(u32),
+    /// A `class` property, with its members.
+    Class(TiledProperties),
+}
+
+impl TiledMap {
+    /// Spawn the [`tile_layers`][Self::tile_layers] of the map, and get their entities, from the
+    /// bottom to the top.
+    ///
+    /// Each layer is spawned with a transform that is offset from the given `transform` by the
+    /// layer's offset and z offset. The tiles of each layer are shared by the slots that have the
+    /// same tile.
+    pub fn spawn(
+        &self,
+        transform: bones::Transform,
+        entities: &mut bones::Entities,
+        transforms: &mut bones::CompMut<bones::Transform>,
+        tiles: &mut bones::CompMut<bones::Tile>,
+        tile_layers: &mut bones::CompMut<bones::TileLayer>,
+    ) -> Vec<bones::Entity> {
+        self.tile_layers
+            .iter()
+            .map(|layer| {
+                let mut layer_transform = transform;
+                layer_transform.translation += layer.offset.extend(layer.z);
+                let layer_entity = entities.create();
+                transforms.insert(layer_entity, layer_transform);
+                tile_layers.insert(layer_entity, layer.tile_layer(entities, tiles));
+                layer_entity
+            })
+            .collect()
+    }
+}
+
+impl TiledTileLayer {
+    /// Create the tile entities of the layer, and get the bones tile layer that uses them.
+    pub fn tile_layer(
+        &self,
+        entities: &mut bones::Entities,
+        tiles: &mut bones::CompMut<bones::Tile>,
+    ) -> bones::TileLayer {
+        let mut layer = bones::TileLayer::new(self.grid_size, self.tile_size, self.atlas.clone());
+        layer.projection = self.projection;
+        fill_tile_layer(&mut layer, &self.tiles, entities, tiles);
+        layer
+    }
+}
+
+/// An asset loader for [`TiledMap`]s from `.tmx` files.
+///
+/// The external tilesets of the map are read from their `.tsx` files, and the atlas of each
+/// tileset is loaded as a labeled asset of the map, named `tileset0`, `tileset1`, and so on.
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let map_path = load_context.path().to_owned();
+            let xml: XmlMap = quick_xml::de::from_reader(bytes)?;
+            let projection = xml.projection().ok_or_else(|| {
+                bevy::asset::Error::msg(format!(
+                    "Map {map_path:?} is {}, which isn't supported",
+                    match xml.staggeraxis.as_deref() {
+                        Some(axis) if xml.orientation == "staggered" => {
+                            format!("staggered along the {axis} axis")
+                        }
+                        _ => xml.orientation.clone(),
+                    }
+                ))
+            })?;
+            if xml.infinite != 0 {
+                return Err(bevy::asset::Error::msg(format!(
+                    "Map {map_path:?} is infinite, which isn't supported"
+                )));
+            }
+
+            let mut dependencies = Vec::new();
+            let mut tilesets = Vec::new();
+            for child in &xml.children {
+                let XmlChild::Tileset(tileset) = child else {
+                    continue;
+                };
+                let first_gid = tileset.firstgid.unwrap_or(1);
+                let (tileset, tileset_path) = match &tileset.source {
+                    Some(source) => {
+                        let path = relative_path(source, &map_path);
+                        let bytes = load_context.read_asset_bytes(&path).await.map_err(|err| {
+                            bevy::asset::Error::msg(format!(
+                                "Tileset {path:?} of map {map_path:?} couldn't be read: {err}"
+                            ))
+                        })?;
+                        let tileset: XmlTileset = quick_xml::de::from_reader(&bytes[..])?;
+                        (tileset, path)
+                    }
+                    None => (tileset.clone(), map_path.clone()),
+                };
+                let index = tilesets.len();
+                let (mut tileset, mut atlas) =
+                    load_tileset(tileset, first_gid, index, &tileset_path, load_context).await?;
+                tileset.image.load(load_context, &mut dependencies);
+                atlas.image = tileset.image.clone();
+                load_context.set_labeled_asset(
+                    &tileset_label(index),
+                    LoadedAsset::new(texture_atlas(&atlas)),
+                );
+                tilesets.push(tileset);
+            }
+
+            let mut map = TiledMap {
+                size: UVec2::new(xml.width, xml.height),
+                tile_size: Vec2::new(xml.tilewidth, xml.tileheight),
+                projection,
+                tilesets,
+                tile_layers: Vec::new(),
+                object_layers: Vec::new(),
+                properties: properties(xml.children.iter().find_map(|child| match child {
+                    XmlChild::Properties(properties) => Some(properties),
+                    _ => None,
+                }))?,
+            };
+            let mut z = 0;
+            map.load_layers(&xml.children, Vec2::ZERO, true, &mut z)?;
+
+            load_context.set_default_asset(LoadedAsset::new(map).with_dependencies(dependencies));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+/// Get the label of the atlas of the tileset with the given index.
+fn tileset_label(index: usize) -> String {
+    format!("tileset{index}")
+}
+
+/// Create a tileset and its atlas, and make sure that its image exists.
+async fn load_tileset(
+    xml: XmlTileset,
+    first_gid: u32,
+    index: usize,
+    tileset_path: &Path,
+    load_context: &LoadContext<'_>,
+) -> Result<(TiledTileset, bones::Atlas), bevy::asset::Error> {
+    let Some(image) = &xml.image else {
+        return Err(bevy::asset::Error::msg(format!(
+            "Tileset {:?} in {tileset_path:?} isn't made from a single image, which isn't supported",
+            xml.name
+        )));
+    };
+    let image_path = relative_path(&image.source, tileset_path);
+    // Reading the image here reports a missing image along with the map, instead of only when the
+    // renderer fails to load it.
+    load_context
+        .read_asset_bytes(&image_path)
+        .await
+        .map_err(|err| {
+            bevy::asset::Error::msg(format!(
+                "Image {image_path:?} of tileset {:?} in {tileset_path:?} couldn't be read: {err}",
+                xml.name
+            ))
+        })?;
+
+    // The path is already relative to the root of the assets, which the leading `/` tells the
+    // handle, so that it isn't made relative to the map again when it is loaded.
+    let image_handle = bones::Handle::new(Path::new("/").join(&image_path), None);
+    let tile_size = Vec2::new(xml.tilewidth, xml.tileheight);
+    let columns = xml.columns.max(1);
+    let rows = (xml.tilecount + columns - 1) / columns;
+    let mut atlas = bones::Atlas::from_grid(
+        image_handle,
+        tile_size,
+        columns as usize,
+        rows as usize,
+        Vec2::splat(xml.spacing),
+        Vec2::splat(xml.margin),
+    );
+    if let (Some(width), Some(height)) = (image.width, image.height) {
+        atlas.size = Vec2::new(width, height);
+    }
+    let map_path = load_context.path().to_owned();
+    let tileset = TiledTileset {
+        name: xml.name,
+        first_gid,
+        tile_size,
+        tile_count: xml.tilecount,
+        image: atlas.image.clone(),
+        atlas: bones::Handle::new(map_path, Some(tileset_label(index))),
+        properties: properties(xml.properties.as_ref())?,
+    };
+    Ok((tileset, atlas))
+}
+
+impl TiledMap {
+    /// Add the layers in the children of a map or a group, which have the given offset and
+    /// visibility, and count their z offsets.
+    fn load_layers(
+        &mut self,
+        children: &[XmlChild],
+        offset: Vec2,
+        visible: bool,
+        z: &mut usize,
+    ) -> Result<(), bevy::asset::Error> {
+        for child in children {
+            match child {
+                XmlChild::Layer(layer) => {
+                    let layer_z = layer_z(*z, layer.properties.as_ref())?;
+                    *z += 1;
+                    if visible && layer.visible != 0 {
+                        self.load_tile_layer(layer, offset + layer.offset(), layer_z)?;
+                    }
+                }
+                XmlChild::Objectgroup(group) => {
+                    let layer_z = layer_z(*z, group.properties.as_ref())?;
+                    *z += 1;
+                    self.load_object_layer(
+                        group,
+                        offset + group.offset(),
+                        visible && group.visible != 0,
+                        layer_z,
+                    )?;
+                }
+                XmlChild::Group(group) => self.load_layers(
+                    &group.children,
+                    offset + group.offset(),
+                    visible && group.visible != 0,
+                    z,
+                )?,
+                XmlChild::Imagelayer(_) => *z += 1,
+                XmlChild::Properties(_)
+                | XmlChild::Tileset(_)
+                | XmlChild::Editorsettings(_)
+                | XmlChild::Other => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the tile layers for a Tiled tile layer, one for each tileset that it uses.
+    fn load_tile_layer(
+        &mut self,
+        xml: &XmlTileLayer,
+        offset: Vec2,
+        z: f32,
+    ) -> Result<(), bevy::asset::Error> {
+        let grid_size = UVec2::new(xml.width, xml.height);
+        let gids = xml.data.gids()?;
+        if gids.len() != (grid_size.x * grid_size.y) as usize {
+            return Err(bevy::asset::Error::msg(format!(
+                "Layer {:?} has {} tiles instead of {}",
+                xml.name,
+                gids.len(),
+                grid_size.x * grid_size.y
+            )));
+        }
+
+        let properties = properties(xml.properties.as_ref())?;
+        let mut layers = BTreeMap::new();
+        for (i, gid) in gids.into_iter().enumerate() {
+            let (tile, tileset) = match self.tile(gid)? {
+                Some(tile) => tile,
+                None => continue,
+            };
+            // Tiled rows go down from the top of the map, and bones rows go up from the bottom.
+            let x = i as u32 % grid_size.x;
+            let y = grid_size.y - 1 - i as u32 / grid_size.x;
+            let layer = layers.entry(tileset).or_insert_with(|| TiledTileLayer {
+                name: xml.name.clone(),
+                z,
+                offset,
+                grid_size,
+                tile_size: self.tile_size,
+                projection: self.projection,
+                tileset,
+                atlas: self.tilesets[tileset].atlas.clone(),
+                tiles: vec![None; (grid_size.x * grid_size.y) as usize],
+                properties: properties.clone(),
+            });
+            layer.tiles[(y * grid_size.x + x) as usize] = Some(tile);
+        }
+        self.tile_layers.extend(layers.into_values());
+        Ok(())
+    }
+
+    /// Add an object layer.
+    fn load_object_layer(
+        &mut self,
+        xml: &XmlObjectGroup,
+        offset: Vec2,
+        visible: bool,
+        z: f32,
+    ) -> Result<(), bevy::asset::Error> {
+        // The rows of a staggered map overlap by half a tile.
+        let map_height = match self.projection {
+            bones::TileProjection::IsometricStaggered(_) => {
+                (self.size.y + 1) as f32 * self.tile_size.y / 2.0
+            }
+            _ => self.size.y as f32 * self.tile_size.y,
+        };
+        let objects = xml
+            .object
+            .iter()
+            .map(|object| {
+                let shape = if let Some(gid) = object.gid {
+                    match self.tile(gid)? {
+                        Some((tile, tileset)) => TiledShape::Tile { tileset, tile },
+                        None => TiledShape::Rect,
+                    }
+                } else if object.ellipse.is_some() {
+                    TiledShape::Ellipse
+                } else if object.point.is_some() {
+                    TiledShape::Point
+                } else if let Some(polygon) = &object.polygon {
+                    TiledShape::Polygon(polygon.points()?)
+                } else if let Some(polyline) = &object.polyline {
+                    TiledShape::Polyline(polyline.points()?)
+                } else {
+                    TiledShape::Rect
+                };
+                Ok(TiledObject {
+                    id: object.id,
+                    name: object.name.clone(),
+                    class: object
+                        .class
+                        .clone()
+                        .or_else(|| object.r#type.clone())
+                        .unwrap_or_default(),
+                    position: Vec2::new(object.x, map_height - object.y),
+                    size: Vec2::new(object.width, object.height),
+                    rotation: object.rotation,
+                    visible: object.visible != 0,
+                    shape,
+                    properties: properties(object.properties.as_ref())?,
+                })
+            })
+            .collect::<Result<_, bevy::asset::Error>>()?;
+        self.object_layers.push(TiledObjectLayer {
+            name: xml.name.clone(),
+            z,
+            offset,
+            visible,
+            objects,
+            properties: properties(xml.properties.as_ref())?,
+        });
+        Ok(())
+    }
+
+    /// Get the tile with the given global tile ID and the index of its tileset, or `None` for an
+    /// empty tile.
+    fn tile(&self, gid: u32) -> Result<Option<(bones::Tile, usize)>, bevy::asset::Error> {
+        let (id, flips) = bones::TiledFlips::from_gid(gid);
+        if id == 0 {
+            return Ok(None);
+        }
+        let tileset = self
+            .tilesets
+            .iter()
+            .rposition(|tileset| tileset.first_gid <= id)
+            .ok_or_else(|| {
+                bevy::asset::Error::msg(format!("Tile {id} isn't in any of the tilesets"))
+            })?;
+        let mut tile = bones::Tile {
+            idx: (id - self.tilesets[tileset].first_gid) as usize,
+            ..Default::default()
+        };
+        tile.set_tiled_flips(flips);
+        Ok(Some((tile, tileset)))
+    }
+}
+
+/// Get the z offset of the layer with the given index in the drawing order, which is overridden
+/// by its `z` property, if it has one.
+fn layer_z(index: usize, xml: Option<&XmlProperties>) -> Result<f32, bevy::asset::Error> {
+    Ok(match properties(xml)?.get("z") {
+        Some(TiledProperty::Float(z)) => *z as f32,
+        Some(TiledProperty::Int(z)) => *z as f32,
+        _ => index as f32,
+    })
+}
+
+/// Convert the custom properties of a Tiled map, if it has any.
+fn properties(xml: Option<&XmlProperties>) -> Result<TiledProperties, bevy::asset::Error> {
+    let Some(xml) = xml else {
+        return Ok(TiledProperties::new());
+    };
+    xml.property
+        .iter()
+        .map(|property| {
+            let value = property
+                .value
+                .clone()
+                .or_else(|| property.text.clone())
+                .unwrap_or_default();
+            let name = &property.name;
+            let value = match property.r#type.as_deref().unwrap_or("string") {
+                "bool" => TiledProperty::Bool(parse_property(name, &value)?),
+                "int" => TiledProperty::Int(parse_property(name, &value)?),
+                "float" => TiledProperty::Float(parse_property(name, &value)?),
+                "object" => TiledProperty::Object(parse_property(name, &value)?),
+                "class" => TiledProperty::Class(properties(property.properties.as_ref())?),
+                _ => TiledProperty::String(value),
+            };
+            Ok((property.name.clone(), value))
+        })
+        .collect()
+}
+
+/// Parse the value of a custom property.
+fn parse_property<T>(name: &str, value: &str) -> Result<T, bevy::asset::Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|err| {
+        bevy::asset::Error::msg(format!(
+            "Property {name:?} has an invalid value {value:?}: {err}"
+        ))
+    })
+}
+
+/// The `<map>` element of a `.tmx` file.
+#[derive(Deserialize)]
+struct XmlMap {
+    #[serde(rename = "@orientation")]
+    orientation: String,
+    #[serde(rename = "@width")]
+    width: u32,
+    #[serde(rename = "@height")]
+    height: u32,
+    #[serde(rename = "@tilewidth")]
+    tilewidth: f32,
+    #[serde(rename = "@tileheight")]
+    tileheight: f32,
+    #[serde(rename = "@infinite", default)]
+    infinite: u8,
+    #[serde(rename = "@staggeraxis", default)]
+    staggeraxis: Option<String>,
+    #[serde(rename = "@staggerindex", default)]
+    staggerindex: Option<String>,
+    #[serde(rename = "$value", default)]
+    children: Vec<XmlChild>,
+}
+
+impl XmlMap {
+    /// Get the projection of the map's orientation, or `None` if it isn't supported.
+    fn projection(&self) -> Option<bones::TileProjection> {
+        match self.orientation.as_str() {
+            "orthogonal" => Some(bones::TileProjection::Orthogonal),
+            "isometric" => Some(bones::TileProjection::IsometricDiamond),
+            "staggered" if self.staggeraxis.as_deref().unwrap_or("y") == "y" => {
+                // Tiled rows go down from the top of the map, so whether the shifted bones rows are
+                // odd or even depends on the number of rows.
+                let odd = self.staggerindex.as_deref().unwrap_or("odd") == "odd";
+                let stagger = if (self.height + 1 + odd as u32) % 2 == 1 {
+                    bones::TileStagger::Odd
+                } else {
+                    bones::TileStagger::Even
+                };
+                Some(bones::TileProjection::IsometricStaggered(stagger))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An element in a `<map>` or a `<group>`, in the order that they're drawn in.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum XmlChild {
+    Properties(XmlProperties),
+    Tileset(XmlTileset),
+    Layer(XmlTileLayer),
+    Objectgroup(XmlObjectGroup),
+    Group(XmlGroup),
+    Imagelayer(IgnoredAny),
+    Editorsettings(IgnoredAny),
+    #[serde(other)]
+    Other,
+}
+
+/// A `<tileset>` element, in a `.tmx` or a `.tsx` file.
+#[derive(Deserialize, Clone)]
+struct XmlTileset {
+    #[serde(rename = "@firstgid")]
+    firstgid: Option<u32>,
+    #[serde(rename = "@source")]
+    source: Option<String>,
+    #[serde(rename = "@name", default)]
+    name: String,
+    #[serde(rename = "@tilewidth", default)]
+    tilewidth: f32,
+    #[serde(rename = "@tileheight", default)]
+    tileheight: f32,
+    #[serde(rename = "@spacing", default)]
+    spacing: f32,
+    #[serde(rename = "@margin", default)]
+    margin: f32,
+    #[serde(rename = "@tilecount", default)]
+    tilecount: u32,
+    #[serde(rename = "@columns", default)]
+    columns: u32,
+    image: Option<XmlImage>,
+    properties: Option<XmlProperties>,
+}
+
+/// An `<image>` element.
+#[derive(Deserialize, Clone)]
+struct XmlImage {
+    #[serde(rename = "@source")]
+    source: String,
+    #[serde(rename = "@width")]
+    width: Option<f32>,
+    #[serde(rename = "@height")]
+    height: Option<f32>,
+}
+
+/// A `<layer>` element.
+#[derive(Deserialize)]
+struct XmlTileLayer {
+    #[serde(rename = "@name", default)]
+    name: String,
+    #[serde(rename = "@width")]
+    width: u32,
+    #[serde(rename = "@height")]
+    height: u32,
+    #[serde(rename = "@offsetx", default)]
+    offsetx: f32,
+    #[serde(rename = "@offsety", default)]
+    offsety: f32,
+    #[serde(rename = "@visible", default = "visible")]
+    visible: u8,
+    properties: Option<XmlProperties>,
+    data: XmlData,
+}
+
+/// An `<objectgroup>` element.
+#[derive(Deserialize)]
+struct XmlObjectGroup {
+    #[serde(rename = "@name", default)]
+    name: String,
+    #[serde(rename = "@offsetx", default)]
+    offsetx: f32,
+    #[serde(rename = "@offsety", default)]
+    offsety: f32,
+    #[serde(rename = "@visible", default = "visible")]
+    visible: u8,
+    properties: Option<XmlProperties>,
+    #[serde(default)]
+    object: Vec<XmlObject>,
+}
+
+/// A `<group>` element.
+#[derive(Deserialize)]
+struct XmlGroup {
+    #[serde(rename = "@offsetx", default)]
+    offsetx: f32,
+    #[serde(rename = "@offsety", default)]
+    offsety: f32,
+    #[serde(rename = "@visible", default = "visible")]
+    visible: u8,
+    #[serde(rename = "$value", default)]
+    children: Vec<XmlChild>,
+}
+
+/// Layers and groups are visible when they don't say otherwise.
+fn visible() -> u8 {
+    1
+}
+
+/// Implement getting the offset of a layer in the bones coordinate space.
+macro_rules! impl_offset {
+    ( $($type:ty),* $(,)? ) => {
+        $(
+            impl $type {
+                /// Get the offset of the layer, with y pointing up.
+                fn offset(&self) -> Vec2 {
+                    Vec2::new(self.offsetx, -self.offsety)
+                }
+            }
+        )*
+    };
+}
+
+impl_offset!(XmlTileLayer, XmlObjectGroup, XmlGroup);
+
+/// The `<data>` element of a tile layer.
+#[derive(Deserialize)]
+struct XmlData {
+    #[serde(rename = "@encoding")]
+    encoding: Option<String>,
+    #[serde(rename = "@compression")]
+    compression: Option<String>,
+    #[serde(rename = "$text", default)]
+    text: String,
+}
+
+impl XmlData {
+    /// Decode the global tile IDs of the layer.
+    fn gids(&self) -> Result<Vec<u32>, bevy::asset::Error> {
+        if let Some(compression) = &self.compression {
+            return Err(bevy::asset::Error::msg(format!(
+                "Tile layer data compressed with {compression} isn't supported"
+            )));
+        }
+        match self.encoding.as_deref() {
+            Some("csv") => self
+                .text
+                .split(',')
+                .map(str::trim)
+                .filter(|gid| !gid.is_empty())
+                .map(|gid| Ok(gid.parse()?))
+                .collect(),
+            Some("base64") => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(self.text.trim())?;
+                Ok(bytes
+                    .chunks_exact(4)
+                    .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                    .collect())
+            }
+            encoding => Err(bevy::asset::Error::msg(format!(
+                "Tile layer data encoded as {}isn't supported",
+                encoding.map(|x| format!("{x} ")).unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// An `<object>` element.
+#[derive(Deserialize)]
+struct XmlObject {
+    #[serde(rename = "@id", default)]
+    id: u32,
+    #[serde(rename = "@name", default)]
+    name: String,
+    #[serde(rename = "@class")]
+    class: Option<String>,
+    #[serde(rename = "@type")]
+    r#type: Option<String>,
+    #[serde(rename = "@x", default)]
+    x: f32,
+    #[serde(rename = "@y", default)]
+    y: f32,
+    #[serde(rename = "@width", default)]
+    width: f32,
+    #[serde(rename = "@height", default)]
+    height: f32,
+    #[serde(rename = "@rotation", default)]
+    rotation: f32,
+    #[serde(rename = "@gid")]
+    gid: Option<u32>,
+    #[serde(rename = "@visible", default = "visible")]
+    visible: u8,
+    ellipse: Option<IgnoredAny>,
+    point: Option<IgnoredAny>,
+    polygon: Option<XmlPoints>,
+    polyline: Option<XmlPoints>,
+    properties: Option<XmlProperties>,
+}
+
+/// A `<polygon>` or `<polyline>` element.
+#[derive(Deserialize)]
+struct XmlPoints {
+    #[serde(rename = "@points")]
+    points: String,
+}
+
+impl XmlPoints {
+    /// Parse the points, with y pointing up.
+    fn points(&self) -> Result<Vec<Vec2>, bevy::asset::Error> {
+        self.points
+            .split_whitespace()
+            .map(|point| {
+                let (x, y) = point
+                    .split_once(',')
+                    .ok_or_else(|| bevy::asset::Error::msg(format!("Invalid point {point:?}")))?;
+                Ok(Vec2::new(x.parse()?, -y.parse::<f32>()?))
+            })
+            .collect()
+    }
+}
+
+/// A `<properties>` element.
+#[derive(Deserialize, Clone)]
+struct XmlProperties {
+    #[serde(default)]
+    property: Vec<XmlProperty>,
+}
+
+/// A `<property>` element.
+#[derive(Deserialize, Clone)]
+struct XmlProperty {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@type")]
+    r#type: Option<String>,
+    #[serde(rename = "@value")]
+    value: Option<String>,
+    /// The value of a multi-line string property.
+    #[serde(rename = "$text")]
+    text: Option<String>,
+    /// The members of a class property.
+    properties: Option<XmlProperties>,
+}
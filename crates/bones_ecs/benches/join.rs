@@ -0,0 +1,74 @@
+//! Measures joined iteration over two component stores, for dense, sparse, and clustered component
+//! distributions.
+
+use bones_ecs::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQCWB2K8N5T0R3XJ7Z9VMD4F"]
+struct Transform(f32);
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQCWBAQ1H6W4E2YC8S0NP5GT"]
+struct Sprite(u32);
+
+/// Create a world with 50 000 entities with a transform, where `has_sprite` decides which entities
+/// also have a sprite.
+fn setup_world(has_sprite: fn(usize) -> bool) -> World {
+    let mut world = World::new();
+    world
+        .run_system(
+            move |mut entities: ResMut<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut sprites: CompMut<Sprite>| {
+                for i in 0..50_000 {
+                    let entity = entities.create();
+                    transforms.insert(entity, Transform(0.0));
+                    if has_sprite(i) {
+                        sprites.insert(entity, Sprite(i as u32));
+                    }
+                }
+            },
+        )
+        .unwrap();
+
+    world
+}
+
+fn join(c: &mut Criterion) {
+    let mut group = c.benchmark_group("join");
+
+    let distributions: [(&str, fn(usize) -> bool); 3] = [
+        // Every entity has both components.
+        ("dense", |_| true),
+        // One entity in a hundred has a sprite.
+        ("sparse", |i| i % 100 == 0),
+        // The sprites are grouped in runs of 500 entities, with long gaps between them.
+        ("clustered", |i| (i / 500) % 10 == 0),
+    ];
+
+    for (name, has_sprite) in distributions {
+        let world = setup_world(has_sprite);
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let transforms = world.components.get::<Transform>();
+        let mut transforms = transforms.borrow_mut();
+        let sprites = world.components.get::<Sprite>();
+        let sprites = sprites.borrow();
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for (_, (mut transform, sprite)) in entities.iter_with((&mut transforms, &sprites))
+                {
+                    transform.0 += sprite.0 as f32;
+                }
+                black_box(&transforms);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, join);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+//! Compares serial and parallel iteration over a query with a small amount of work per entity.
+
+use bones_ecs::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQDK0A4T8M2X6R1ZC9VN3HBE"]
+struct AtlasSprite {
+    index: usize,
+    frames: usize,
+    timer: f32,
+}
+
+/// The number of entities to iterate over.
+///
+/// The default `keysize16` feature limits worlds to 65 536 entities, so this has to stay below
+/// that unless a larger key size is enabled.
+const ENTITY_COUNT: usize = 60_000;
+
+fn setup_world() -> World {
+    let mut world = World::new();
+    world
+        .run_system(
+            |mut entities: ResMut<Entities>, mut sprites: CompMut<AtlasSprite>| {
+                for i in 0..ENTITY_COUNT {
+                    let entity = entities.create();
+                    sprites.insert(
+                        entity,
+                        AtlasSprite {
+                            index: 0,
+                            frames: 1 + i % 16,
+                            timer: 0.0,
+                        },
+                    );
+                }
+            },
+        )
+        .unwrap();
+
+    world
+}
+
+/// The work done for every entity: advance the animation timer, and the frame when it runs out.
+fn animate(sprite: &mut AtlasSprite) {
+    for _ in 0..8 {
+        sprite.timer += 0.1;
+        if sprite.timer >= 1.0 {
+            sprite.timer -= 1.0;
+            sprite.index = (sprite.index + 1) % sprite.frames;
+        }
+    }
+}
+
+fn par_iter(c: &mut Criterion) {
+    let world = setup_world();
+    let entities = world.resources.get::<Entities>();
+    let entities = entities.borrow();
+    let sprites = world.components.get::<AtlasSprite>();
+    let mut sprites = sprites.borrow_mut();
+
+    let mut group = c.benchmark_group("par_iter");
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            for (_, mut sprite) in entities.iter_with(&mut sprites) {
+                animate(&mut sprite);
+            }
+            black_box(&sprites);
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            entities.par_iter_with(&mut sprites, |_, mut sprite| animate(&mut sprite));
+            black_box(&sprites);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, par_iter);
+criterion_main!(benches);
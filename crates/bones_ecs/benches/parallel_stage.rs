@@ -0,0 +1,82 @@
+//! Compares the [`SimpleSystemStage`] and [`ParallelSystemStage`] on a stage with many independent
+//! systems.
+
+use bones_ecs::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// The amount of work each system does every time it is run.
+const WORKLOAD: u64 = 20_000;
+
+/// Trait for the resources that our systems write their results to.
+trait Output: TypedEcsData + Default {
+    fn set(&mut self, value: u64);
+}
+
+// Define a different output resource for each system, so that the systems don't conflict
+macro_rules! outputs {
+    ($($name:ident = $ulid:literal),* $(,)?) => {
+        $(
+            #[derive(Clone, Default, TypeUlid)]
+            #[ulid = $ulid]
+            struct $name(u64);
+
+            impl Output for $name {
+                fn set(&mut self, value: u64) {
+                    self.0 = value;
+                }
+            }
+        )*
+    };
+}
+
+outputs!(
+    Out0 = "01GQFB0J2N7D4C1VX8W3KZ5T6A",
+    Out1 = "01GQFB0PQ3M8E2R6Y9H1JB4S7C",
+    Out2 = "01GQFB0V5K1T9F3W7N2DX6A8QE",
+    Out3 = "01GQFB10BX4H6J8M2P5RZ1C3VG",
+    Out4 = "01GQFB15R9C2N4Q7T1V8YE6D3J",
+    Out5 = "01GQFB1B7F5S3X9A2K6W4H8M1N",
+    Out6 = "01GQFB1GMV8D1Z4B7E3Q6T2P5R",
+    Out7 = "01GQFB1N3A6Y2C5H9J1X7F4K8T",
+);
+
+/// A system that does a fixed amount of busy work and writes the result to it's output.
+fn work_system<T: Output>(mut out: ResMut<T>) {
+    let mut value = 0u64;
+    for i in 0..WORKLOAD {
+        value = value.wrapping_mul(31).wrapping_add(i);
+    }
+    out.set(value);
+}
+
+/// Add the systems to the stage, and initialize it with a new world.
+fn setup<S: SystemStage>(mut stage: S) -> (S, World) {
+    stage.add_system(work_system::<Out0>.system());
+    stage.add_system(work_system::<Out1>.system());
+    stage.add_system(work_system::<Out2>.system());
+    stage.add_system(work_system::<Out3>.system());
+    stage.add_system(work_system::<Out4>.system());
+    stage.add_system(work_system::<Out5>.system());
+    stage.add_system(work_system::<Out6>.system());
+    stage.add_system(work_system::<Out7>.system());
+
+    let mut world = World::new();
+    stage.initialize(&mut world).unwrap();
+    (stage, world)
+}
+
+fn parallel_stage(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_stage");
+    group.bench_function("simple", |b| {
+        let (mut stage, mut world) = setup(SimpleSystemStage::new(CoreStage::Update));
+        b.iter(|| stage.run(&mut world).unwrap())
+    });
+    group.bench_function("parallel", |b| {
+        let (mut stage, mut world) = setup(ParallelSystemStage::new(CoreStage::Update));
+        b.iter(|| stage.run(&mut world).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parallel_stage);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+//! Compares filtering a query with [`without()`] to checking the filtered store for every entity.
+
+use bones_ecs::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQ2ZA0V3J8XK5N9Q1M7CRT4E"]
+struct Transform(f32);
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQ2ZA8FD2WB6H0ZP3S9Y5KNV"]
+struct Frozen;
+
+/// Create a world with 50 000 entities with a transform, where every other entity is frozen.
+fn setup_world() -> World {
+    let mut world = World::new();
+    world
+        .run_system(
+            |mut entities: ResMut<Entities>,
+             mut transforms: CompMut<Transform>,
+             mut frozen: CompMut<Frozen>| {
+                for i in 0..50_000 {
+                    let entity = entities.create();
+                    transforms.insert(entity, Transform(0.0));
+                    if i % 2 == 0 {
+                        frozen.insert(entity, Frozen);
+                    }
+                }
+            },
+        )
+        .unwrap();
+
+    world
+}
+
+fn query_filter(c: &mut Criterion) {
+    let world = setup_world();
+    let entities = world.resources.get::<Entities>();
+    let entities = entities.borrow();
+    let transforms = world.components.get::<Transform>();
+    let mut transforms = transforms.borrow_mut();
+    let frozen = world.components.get::<Frozen>();
+    let frozen = frozen.borrow();
+
+    let mut group = c.benchmark_group("query_filter");
+    group.bench_function("without", |b| {
+        b.iter(|| {
+            for (_, (mut transform, ())) in entities.iter_with((&mut transforms, without(&frozen)))
+            {
+                transform.0 += 1.0;
+            }
+            black_box(&transforms);
+        })
+    });
+    group.bench_function("manual_contains", |b| {
+        b.iter(|| {
+            for (entity, mut transform) in entities.iter_with(&mut transforms) {
+                if frozen.contains(entity) {
+                    continue;
+                }
+                transform.0 += 1.0;
+            }
+            black_box(&transforms);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, query_filter);
+criterion_main!(benches);
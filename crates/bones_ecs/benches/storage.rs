@@ -0,0 +1,89 @@
+//! Compares iteration and random access between the sparse and dense [`StorageStrategy`]s, for a
+//! component that nearly every entity has.
+
+use bones_ecs::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQPA5D8K3XW0N7C2RTF6QJ9M"]
+struct Transform([f32; 4]);
+
+/// Create a world with 50 000 entities, where all but one entity in twenty has a transform stored
+/// with the given strategy.
+///
+/// Returns the world along with the entities that have a transform, in a scrambled order.
+fn setup_world(strategy: StorageStrategy) -> (World, Vec<Entity>) {
+    let mut world = World::new();
+    world.components.init_with_strategy::<Transform>(strategy);
+    let mut with_transform = world
+        .run_system(
+            |mut entities: ResMut<Entities>,
+             mut transforms: CompMut<Transform>|
+             -> anyhow::Result<Vec<Entity>> {
+                let mut with_transform = Vec::new();
+                for i in 0..50_000 {
+                    let entity = entities.create();
+                    if i % 20 != 0 {
+                        transforms.insert(entity, Transform([i as f32; 4]));
+                        with_transform.push(entity);
+                    }
+                }
+                Ok(with_transform)
+            },
+        )
+        .unwrap();
+
+    // Visit the entities in a fixed, cache-unfriendly order.
+    let len = with_transform.len();
+    with_transform = (0..len).map(|i| with_transform[i * 7_919 % len]).collect();
+
+    (world, with_transform)
+}
+
+fn storage(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage");
+
+    for (name, strategy) in [
+        ("sparse", StorageStrategy::Sparse),
+        ("dense", StorageStrategy::Dense),
+    ] {
+        let (world, scrambled) = setup_world(strategy);
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let transforms = world.components.get::<Transform>();
+        let mut transforms = transforms.borrow_mut();
+
+        group.bench_function(format!("iter/{name}"), |b| {
+            b.iter(|| {
+                for mut transform in transforms.iter_mut() {
+                    transform.0[0] += 1.0;
+                }
+                black_box(&transforms);
+            })
+        });
+
+        group.bench_function(format!("join/{name}"), |b| {
+            b.iter(|| {
+                for (_, mut transform) in entities.iter_with(&mut transforms) {
+                    transform.0[0] += 1.0;
+                }
+                black_box(&transforms);
+            })
+        });
+
+        group.bench_function(format!("random_access/{name}"), |b| {
+            b.iter(|| {
+                let mut sum = 0.0;
+                for &entity in &scrambled {
+                    sum += transforms.get(entity).unwrap().0[0];
+                }
+                black_box(sum)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, storage);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+use bones_ecs::prelude::*;
+
+// A marker component for entities that should be despawned.
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPT8JX4JSX7ETZ8HH9DN6WBV"]
+pub struct Doomed;
+
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPT8K4TJ1C2RMN0WZ7Y63DTS"]
+pub struct Health(pub u32);
+
+fn main() {
+    let mut world = World::new();
+
+    let mut stages = SystemStages::with_core_stages();
+    stages
+        .add_startup_system(setup_system)
+        .unwrap()
+        // Exclusive systems take `&mut World` and run at their place in the stage like any other
+        // system, but never at the same time as other systems.
+        .add_system_to_stage(CoreStage::Update, despawn_doomed_system)
+        .add_system_to_stage(CoreStage::PostUpdate, print_system)
+        .initialize_systems(&mut world)
+        .unwrap();
+
+    stages.run(&mut world).unwrap();
+}
+
+/// Setup system that spawns a few entities, some of which are doomed.
+fn setup_system(
+    mut entities: ResMut<Entities>,
+    mut healths: CompMut<Health>,
+    mut doomed: CompMut<Doomed>,
+) {
+    for i in 0..5 {
+        let entity = entities.create();
+        healths.insert(entity, Health(i * 10));
+        if i % 2 == 0 {
+            doomed.insert(entity, Doomed);
+        }
+    }
+}
+
+/// Exclusive system that despawns all of the doomed entities, and then cleans up their
+/// components immediately with [`World::maintain()`].
+fn despawn_doomed_system(world: &mut World) {
+    {
+        let entities = world.resources.get::<Entities>();
+        let mut entities = entities.borrow_mut();
+        let doomed = world.components.get::<Doomed>();
+        let doomed = doomed.borrow();
+
+        let to_despawn = entities
+            .iter_with(&doomed)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in to_despawn {
+            entities.kill(entity);
+        }
+    }
+
+    world.maintain();
+}
+
+/// System that prints the remaining entities.
+fn print_system(entities: Res<Entities>, healths: Comp<Health>) {
+    for (entity, health) in entities.iter_with(&healths) {
+        println!("{entity:?}: {health:?}");
+    }
+}
@@ -0,0 +1,44 @@
+use bones_ecs::prelude::*;
+
+/// The label for our nested simulation schedule.
+#[derive(StageLabel)]
+pub struct Simulation;
+
+/// Resource counting the number of simulated frames.
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[ulid = "01GPXCBW4WZ3Q2D8F4Z9QK0S4N"]
+pub struct SimulatedFrames(pub u32);
+
+fn main() {
+    let mut world = World::new();
+
+    // Create the inner schedule, containing the game simulation.
+    let mut simulation = SystemStages::with_core_stages();
+    simulation.add_system_to_stage(CoreStage::Update, simulate_system);
+
+    // Create the outer frame schedule, which runs the simulation three times every frame, like a
+    // rollback netcode might when it needs to re-simulate the last few frames.
+    let mut stages = SystemStages::with_core_stages();
+    stages
+        .insert_stage_after(
+            CoreStage::Update,
+            Box::new(NestedStages::new(Simulation, simulation).with_repeat(3)),
+        )
+        .unwrap()
+        .add_system_to_stage(CoreStage::PostUpdate, print_system)
+        .initialize_systems(&mut world)
+        .unwrap();
+
+    stages.run(&mut world).unwrap();
+}
+
+/// System that simulates a single frame.
+fn simulate_system(mut frames: ResMut<SimulatedFrames>) {
+    frames.0 += 1;
+    println!("Simulating frame {}", frames.0);
+}
+
+/// System that prints how many frames have been simulated.
+fn print_system(frames: Res<SimulatedFrames>) {
+    println!("Simulated {} frames in one outer frame", frames.0);
+}
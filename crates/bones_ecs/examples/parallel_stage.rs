@@ -0,0 +1,100 @@
+//! A simple benchmark comparing the [`SimpleSystemStage`] and [`ParallelSystemStage`] on a stage
+//! with many independent systems.
+//!
+//! Run it in release mode to get meaningful timings:
+//!
+//! ```sh
+//! cargo run --release --example parallel_stage
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bones_ecs::prelude::*;
+
+/// Resource containing the amount of work each system should do.
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GPT2R1RDMCMW0WX0TFGG8YB4"]
+pub struct Workload(u64);
+
+impl Default for Workload {
+    fn default() -> Self {
+        Self(200_000)
+    }
+}
+
+/// Trait for the resources that our systems write their results to.
+pub trait Output: TypedEcsData + Default {
+    fn set(&mut self, value: u64);
+}
+
+// Define a different output resource for each system, so that the systems don't conflict
+macro_rules! outputs {
+    ($($name:ident = $ulid:literal),* $(,)?) => {
+        $(
+            #[derive(Clone, Default, TypeUlid)]
+            #[ulid = $ulid]
+            pub struct $name(u64);
+
+            impl Output for $name {
+                fn set(&mut self, value: u64) {
+                    self.0 = value;
+                }
+            }
+        )*
+    };
+}
+
+outputs!(
+    Out0 = "01GPT2S3J6V0J1X3J5B6E5T2BD",
+    Out1 = "01GPT2S9B2QX5CPMRB4Y9SZ8AN",
+    Out2 = "01GPT2SF2YWCMW3T7S1D0A3E8X",
+    Out3 = "01GPT2SMPJD2T6FY1G5B2XHCQ7",
+    Out4 = "01GPT2SV4Z4FJ5V6X8CA0F1N3M",
+    Out5 = "01GPT2T1F60YE4KQ2H5S2R8CDW",
+    Out6 = "01GPT2T7M1R3JHWK2B4N6A0XPS",
+    Out7 = "01GPT2TDW5GZ0Q8C3TJ1V7KMEB",
+);
+
+/// A system that does a fixed amount of busy work and writes the result to it's output.
+fn work_system<T: Output>(workload: Res<Workload>, mut out: ResMut<T>) {
+    let mut value = 0u64;
+    for i in 0..workload.0 {
+        value = value.wrapping_mul(31).wrapping_add(i);
+    }
+    out.set(value);
+}
+
+fn add_systems<S: SystemStage>(mut stage: S) -> S {
+    stage.add_system(work_system::<Out0>.system());
+    stage.add_system(work_system::<Out1>.system());
+    stage.add_system(work_system::<Out2>.system());
+    stage.add_system(work_system::<Out3>.system());
+    stage.add_system(work_system::<Out4>.system());
+    stage.add_system(work_system::<Out5>.system());
+    stage.add_system(work_system::<Out6>.system());
+    stage.add_system(work_system::<Out7>.system());
+    stage
+}
+
+/// Run the stage for a number of frames and return the average time per frame.
+fn bench(mut stage: impl SystemStage) -> Duration {
+    const FRAMES: u32 = 100;
+
+    let mut world = World::new();
+    stage.initialize(&mut world).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        stage.run(&mut world).unwrap();
+    }
+
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    let serial = bench(add_systems(SimpleSystemStage::new(CoreStage::Update)));
+    let parallel = bench(add_systems(ParallelSystemStage::new(CoreStage::Update)));
+
+    println!("SimpleSystemStage:   {serial:?} per frame");
+    println!("ParallelSystemStage: {parallel:?} per frame");
+}
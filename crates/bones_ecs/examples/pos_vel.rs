@@ -28,11 +28,12 @@ fn main() {
         .add_system_to_stage(CoreStage::Update, pos_vel_system)
         .add_system_to_stage(CoreStage::PostUpdate, print_system)
         // This must be called once, after adding our systems
-        .initialize_systems(&mut world);
+        .initialize_systems(&mut world)
+        .unwrap();
 
     // Run our game loop for 10 frames
     for _ in 0..10 {
-        dispatcher.run(&world).unwrap();
+        dispatcher.run(&mut world).unwrap();
     }
 }
 
@@ -53,7 +54,7 @@ fn setup_system(
 
 /// Update the Pos of all entities with both a Pos and a Vel
 fn pos_vel_system(entities: Res<Entities>, mut pos: CompMut<Pos>, vel: Comp<Vel>) {
-    for (_, (pos, vel)) in entities.iter_with((&mut pos, &vel)) {
+    for (_, (mut pos, vel)) in entities.iter_with((&mut pos, &vel)) {
         **pos += **vel;
     }
 }
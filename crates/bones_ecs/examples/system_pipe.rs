@@ -0,0 +1,65 @@
+use bones_ecs::prelude::*;
+
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPWF3MJ9V8R7Y0Q0M0WQZ4JT"]
+pub struct Pos(pub i32);
+
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPWF3W9PGCY1T0AGA3S1D4BN"]
+pub struct Health(pub u32);
+
+fn main() {
+    let mut world = World::new();
+
+    let mut stages = SystemStages::with_core_stages();
+    stages
+        .add_startup_system(setup_system)
+        .unwrap()
+        // The list of hit entities is passed straight from one system to the next, without having
+        // to store it in a resource.
+        .add_system_to_stage(CoreStage::Update, detect_hits.pipe(apply_damage))
+        .add_system_to_stage(CoreStage::PostUpdate, print_system)
+        .initialize_systems(&mut world)
+        .unwrap();
+
+    stages.run(&mut world).unwrap();
+}
+
+/// Setup system that spawns a few entities with a position and some health.
+fn setup_system(
+    mut entities: ResMut<Entities>,
+    mut positions: CompMut<Pos>,
+    mut healths: CompMut<Health>,
+) {
+    for i in 0..5 {
+        let entity = entities.create();
+        positions.insert(entity, Pos(i * 10));
+        healths.insert(entity, Health(100));
+    }
+}
+
+/// System that returns the entities that are within range of an explosion.
+fn detect_hits(entities: Res<Entities>, positions: Comp<Pos>) -> anyhow::Result<Vec<Entity>> {
+    let explosion = 15;
+    Ok(entities
+        .iter_with(&positions)
+        .filter(|(_, pos)| (pos.0 - explosion).abs() <= 10)
+        .map(|(entity, _)| entity)
+        .collect())
+}
+
+/// System that damages the entities that were hit, taking them as it's input.
+fn apply_damage(hits: In<Vec<Entity>>, mut healths: CompMut<Health>) {
+    for &entity in hits.iter() {
+        if let Some(mut health) = healths.get_mut(entity) {
+            health.0 = health.0.saturating_sub(30);
+        }
+    }
+}
+
+/// System that prints the health of every entity.
+fn print_system(entities: Res<Entities>, healths: Comp<Health>) {
+    for (entity, health) in entities.iter_with(&healths) {
+        println!("{entity:?}: {health:?}");
+    }
+}
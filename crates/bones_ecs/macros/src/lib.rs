@@ -0,0 +1,245 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+/// Derive macro for the `StageLabel` trait.
+///
+/// May be derived for unit structs and for enums without data in their variants. The `name()` of
+/// the label is the name of the struct or enum variant, and the `id()` is a hash of the full path
+/// to the label. See `bones_ecs::stage::stage_label_id()` for details.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(StageLabel)]
+/// enum MyStage {
+///     Physics,
+///     Network,
+/// }
+/// ```
+#[proc_macro_derive(StageLabel)]
+pub fn stage_label(input: TokenStream) -> TokenStream {
+    let input = syn::parse(input).unwrap();
+
+    impl_stage_label(&input).into()
+}
+
+/// Derive macro for the `SystemParam` trait.
+///
+/// May be derived for structs with at most one lifetime parameter, where every field is a
+/// `SystemParam`. The struct can then be used as a single system parameter, that borrows all of
+/// it's fields.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(SystemParam)]
+/// struct PhysicsCtx<'a> {
+///     bodies: CompMut<'a, Body>,
+///     time: Res<'a, Time>,
+/// }
+/// ```
+#[proc_macro_derive(SystemParam)]
+pub fn system_param(input: TokenStream) -> TokenStream {
+    let input = syn::parse(input).unwrap();
+
+    impl_system_param(&input).into()
+}
+
+fn impl_stage_label(input: &syn::DeriveInput) -> TokenStream2 {
+    let item_ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Collect the pattern and the name of every value of the label
+    let labels = match &input.data {
+        syn::Data::Struct(data) => {
+            if !matches!(data.fields, syn::Fields::Unit) {
+                return quote_spanned! { data.fields.span() =>
+                    compile_error!("`StageLabel` can only be derived for unit structs");
+                };
+            }
+
+            vec![(
+                quote! { Self },
+                item_ident.to_string(),
+                item_ident.to_string(),
+            )]
+        }
+        syn::Data::Enum(data) => {
+            let mut labels = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, syn::Fields::Unit) {
+                    return quote_spanned! { variant.span() =>
+                        compile_error!(
+                            "`StageLabel` can only be derived for enums without data in their \
+                            variants"
+                        );
+                    };
+                }
+
+                let variant_ident = &variant.ident;
+                labels.push((
+                    quote! { Self::#variant_ident },
+                    variant_ident.to_string(),
+                    format!("{item_ident}::{variant_ident}"),
+                ));
+            }
+
+            labels
+        }
+        syn::Data::Union(data) => {
+            return quote_spanned! { data.union_token.span() =>
+                compile_error!("`StageLabel` can't be derived for unions");
+            };
+        }
+    };
+
+    // Enums without variants can't be constructed, and can't be matched on without the rest of the
+    // function becoming unreachable.
+    if labels.is_empty() {
+        return quote! {
+            impl #impl_generics ::bones_ecs::stage::StageLabel for #item_ident #ty_generics #where_clause {
+                fn name(&self) -> ::std::string::String {
+                    match *self {}
+                }
+
+                fn id(&self) -> ::bones_ecs::ulid::Ulid {
+                    match *self {}
+                }
+            }
+        };
+    }
+
+    let name_arms = labels.iter().map(|(pattern, name, _)| {
+        quote! { #pattern => #name, }
+    });
+    let id_arms = labels.iter().map(|(pattern, _, path)| {
+        quote! {
+            #pattern => {
+                const ID: ::bones_ecs::ulid::Ulid =
+                    ::bones_ecs::stage::stage_label_id(concat!(module_path!(), "::", #path));
+                ID
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::bones_ecs::stage::StageLabel for #item_ident #ty_generics #where_clause {
+            fn name(&self) -> ::std::string::String {
+                let name: &str = match *self {
+                    #(#name_arms)*
+                };
+                name.into()
+            }
+
+            fn id(&self) -> ::bones_ecs::ulid::Ulid {
+                match *self {
+                    #(#id_arms)*
+                }
+            }
+        }
+    }
+}
+
+fn impl_system_param(input: &syn::DeriveInput) -> TokenStream2 {
+    let item_ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        syn::Data::Struct(data) => &data.fields,
+        _ => {
+            return quote_spanned! { input.ident.span() =>
+                compile_error!("`SystemParam` can only be derived for structs");
+            };
+        }
+    };
+
+    let mut lifetimes = input.generics.lifetimes();
+    if let (Some(_), Some(extra)) = (lifetimes.next(), lifetimes.next()) {
+        return quote_spanned! { extra.span() =>
+            compile_error!("`SystemParam` can only be derived for structs with at most one lifetime");
+        };
+    }
+
+    // The type of the struct, with it's lifetime replaced by the lifetime of the borrowed state.
+    let param_generics = input.generics.params.iter().map(|param| match param {
+        syn::GenericParam::Lifetime(_) => quote! { '__s },
+        syn::GenericParam::Type(ty) => {
+            let ident = &ty.ident;
+            quote! { #ident }
+        }
+        syn::GenericParam::Const(constant) => {
+            let ident = &constant.ident;
+            quote! { #ident }
+        }
+    });
+
+    let param_type = if input.generics.params.is_empty() {
+        quote! { #item_ident }
+    } else {
+        quote! { #item_ident<#(#param_generics),*> }
+    };
+
+    let field_types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(i.into()),
+        })
+        .collect::<Vec<_>>();
+    let indexes = (0..fields.len()).map(syn::Index::from).collect::<Vec<_>>();
+
+    // The locals are stored in nested pairs, so that they implement `Default` for any number of
+    // fields.
+    let local_type = field_types.iter().rev().fold(quote! { () }, |rest, ty| {
+        quote! { (<#ty as ::bones_ecs::system::SystemParam>::Local, #rest) }
+    });
+    let local_paths = (0..fields.len()).map(|i| {
+        let rest = std::iter::repeat(quote! { .1 }).take(i);
+        quote! { local #(#rest)* .0 }
+    });
+
+    quote! {
+        impl #impl_generics ::bones_ecs::system::SystemParam for #item_ident #ty_generics #where_clause {
+            type State = (#(<#field_types as ::bones_ecs::system::SystemParam>::State,)*);
+            type Param<'__s> = #param_type;
+            type Local = #local_type;
+
+            fn initialize(world: &mut ::bones_ecs::World) {
+                #(<#field_types as ::bones_ecs::system::SystemParam>::initialize(world);)*
+            }
+
+            fn access(access: &mut ::bones_ecs::system::SystemAccess) {
+                access.extend(&::bones_ecs::system::SystemAccess::from_params(&[
+                    #((
+                        ::std::any::type_name::<#field_types>(),
+                        <#field_types as ::bones_ecs::system::SystemParam>::access
+                            as fn(&mut ::bones_ecs::system::SystemAccess),
+                    ),)*
+                ]));
+            }
+
+            fn get_state(world: &::bones_ecs::World) -> Self::State {
+                (#(<#field_types as ::bones_ecs::system::SystemParam>::get_state(world),)*)
+            }
+
+            #[allow(unused_variables, clippy::needless_lifetimes)]
+            fn borrow<'__s>(
+                state: &'__s mut Self::State,
+                local: &'__s mut Self::Local,
+            ) -> Self::Param<'__s> {
+                #item_ident {
+                    #(
+                        #members: <#field_types as ::bones_ecs::system::SystemParam>::borrow(
+                            &mut state.#indexes,
+                            &mut #local_paths,
+                        ),
+                    )*
+                }
+            }
+        }
+    }
+}
@@ -43,6 +43,73 @@ impl BitSetVec {
     pub fn contains(&self, entity: Entity) -> bool {
         self.bit_test(entity.index() as usize)
     }
+
+    /// Get the index of the first set bit in the range `start..end`.
+    ///
+    /// The bitset is scanned a word at a time, so empty words are skipped without testing each
+    /// of their bits.
+    #[inline]
+    pub fn next_set_bit(&self, start: usize, end: usize) -> Option<usize> {
+        let end = end.min(self.bit_len());
+        next_set_bit_by(start, end, |word| self.word(word))
+    }
+
+    /// Get the index of the first bit in the range `start..end` that is set in both this bitset
+    /// and `other`.
+    ///
+    /// This is the same as calling [`next_set_bit()`][Self::next_set_bit] on the intersection of
+    /// the two bitsets, without having to create it.
+    #[inline]
+    pub fn next_set_bit_and(&self, other: &BitSetVec, start: usize, end: usize) -> Option<usize> {
+        let end = end.min(self.bit_len()).min(other.bit_len());
+        next_set_bit_by(start, end, |word| self.word(word) & other.word(word))
+    }
+
+    /// The number of bits in the bitset.
+    #[inline]
+    fn bit_len(&self) -> usize {
+        self.0.len() * BITS_PER_SLICE
+    }
+
+    /// Get the 32-bit word with the given index.
+    #[inline]
+    pub(crate) fn word(&self, word: usize) -> u32 {
+        self.0[word / WORDS_PER_SLICE][word % WORDS_PER_SLICE]
+    }
+
+    /// Iterate mutably over the 32-bit words of the bitset, along with their indices.
+    #[inline]
+    pub(crate) fn words_mut(&mut self) -> impl Iterator<Item = (usize, &mut u32)> {
+        self.0.iter_mut().flatten().enumerate()
+    }
+}
+
+const WORDS_PER_SLICE: usize = 8;
+const BITS_PER_SLICE: usize = 32 * WORDS_PER_SLICE;
+
+/// Find the first set bit in the range `start..end` of the bitset made of the words returned by
+/// `word`. `end` must not be larger than the number of bits in the words.
+#[inline]
+fn next_set_bit_by(start: usize, end: usize, word: impl Fn(usize) -> u32) -> Option<usize> {
+    if start >= end {
+        return None;
+    }
+
+    let mut index = start / 32;
+    // Mask out the bits before `start` in the first word.
+    let mut bits = word(index) & (u32::MAX << (start % 32));
+    loop {
+        if bits != 0 {
+            let bit = index * 32 + bits.trailing_zeros() as usize;
+            return (bit < end).then_some(bit);
+        }
+
+        index += 1;
+        if index * 32 >= end {
+            return None;
+        }
+        bits = word(index);
+    }
 }
 
 /// Creates a bitset big enough to contain the index of each entity.
@@ -50,3 +117,77 @@ impl BitSetVec {
 pub fn create_bitset() -> BitSetVec {
     BitSetVec(vec![[0u32; 8]; BITSET_SLICE_COUNT])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift random number generator, so the tests are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn new(seed: u64) -> Self {
+            Self(seed.max(1))
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Returns true with the given probability.
+        fn chance(&mut self, probability: f64) -> bool {
+            (self.next_u64() % 1_000_000) as f64 / 1_000_000.0 < probability
+        }
+    }
+
+    fn random_bitset(rng: &mut Rng, density: f64) -> BitSetVec {
+        let mut bitset = create_bitset();
+        for i in 0..BITSET_SIZE {
+            if rng.chance(density) {
+                bitset.bit_set(i);
+            }
+        }
+        bitset
+    }
+
+    #[test]
+    fn next_set_bit_matches_naive_scan() {
+        let mut rng = Rng::new(34);
+        for density in [0.0, 0.001, 0.05, 0.5, 1.0] {
+            let a = random_bitset(&mut rng, density);
+            let b = random_bitset(&mut rng, 0.5);
+
+            for _ in 0..50 {
+                let start = rng.next_u64() as usize % (BITSET_SIZE + 10);
+                let end = rng.next_u64() as usize % (BITSET_SIZE + 10);
+
+                let naive = (start..end.min(BITSET_SIZE)).find(|&i| a.bit_test(i));
+                assert_eq!(a.next_set_bit(start, end), naive);
+
+                let naive = (start..end.min(BITSET_SIZE)).find(|&i| a.bit_test(i) && b.bit_test(i));
+                assert_eq!(a.next_set_bit_and(&b, start, end), naive);
+            }
+        }
+    }
+
+    #[test]
+    fn next_set_bit_word_boundaries() {
+        let mut bitset = create_bitset();
+        for i in [0, 31, 32, 255, 256, BITSET_SIZE - 1] {
+            bitset.bit_set(i);
+        }
+
+        let mut found = vec![];
+        let mut start = 0;
+        while let Some(i) = bitset.next_set_bit(start, BITSET_SIZE) {
+            found.push(i);
+            start = i + 1;
+        }
+        assert_eq!(found, [0, 31, 32, 255, 256, BITSET_SIZE - 1]);
+        assert_eq!(bitset.next_set_bit(1, 31), None);
+        assert_eq!(bitset.next_set_bit(33, 256), Some(255));
+    }
+}
@@ -0,0 +1,458 @@
+//! Change detection for components and resources.
+//!
+//! The [`World`] has a change [`Tick`] counter that is advanced at the start of every frame run by
+//! [`SystemStages::run()`], and by the stages after every system that they run, so that every
+//! system run gets it's own tick. System parameters only read the current tick, so systems that
+//! are run outside of a stage share a tick until it is advanced with
+//! [`World::increment_change_tick()`].
+//!
+//! Every component and resource records the tick that it was added in, and the tick that it was
+//! last mutably accessed in, which systems compare to the tick of their previous run to find out
+//! what changed since then. A system doesn't detect the changes that it made itself.
+//!
+//! Reading a component or resource never touches its ticks. Mutable access through [`Mut`] or
+//! [`ResMut`] only marks the value as changed when it is mutably dereferenced.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use crate::prelude::*;
+
+/// The number of ticks after which [`World::check_change_ticks()`] clamps the ticks of all of the
+/// components and resources, so that old ticks don't appear to be new after the tick counter wraps
+/// around.
+pub const CHECK_TICK_THRESHOLD: u32 = 518_400_000;
+
+/// The maximum age of a tick, compared to the current tick of the [`World`].
+///
+/// Ticks that are older than this are clamped to this age by
+/// [`World::check_change_ticks()`].
+pub const MAX_CHANGE_AGE: u32 = u32::MAX - (2 * CHECK_TICK_THRESHOLD - 1);
+
+/// A point in time used for change detection.
+///
+/// Ticks wrap around when they overflow, so they are always compared by their age relative to the
+/// current tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tick(u32);
+
+impl Tick {
+    /// Create a tick with the given value.
+    pub const fn new(tick: u32) -> Self {
+        Self(tick)
+    }
+
+    /// Get the value of the tick.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Get the tick that follows this one, wrapping around on overflow.
+    pub fn next(self) -> Self {
+        Self(self.0.wrapping_add(1))
+    }
+
+    /// Whether this tick is later than `last_run`, as seen from `this_run`.
+    ///
+    /// If `last_run` is `None` every tick counts as newer. The ticks are compared by their age
+    /// relative to `this_run`, so this handles the ticks wrapping around, as long as the ticks are
+    /// no older than [`MAX_CHANGE_AGE`].
+    pub fn is_newer_than(self, last_run: Option<Tick>, this_run: Tick) -> bool {
+        let Some(last_run) = last_run else {
+            return true;
+        };
+        let last_run_age = this_run.0.wrapping_sub(last_run.0).min(MAX_CHANGE_AGE);
+        let age = this_run.0.wrapping_sub(self.0);
+
+        age < last_run_age
+    }
+
+    /// Clamp the tick so that it is no older than [`MAX_CHANGE_AGE`] compared to `this_run`.
+    pub fn check(&mut self, this_run: Tick) {
+        if this_run.0.wrapping_sub(self.0) > MAX_CHANGE_AGE {
+            self.0 = this_run.0.wrapping_sub(MAX_CHANGE_AGE);
+        }
+    }
+}
+
+/// Atomic tick counter, shared by a [`World`] and all of it's component and resource stores, so
+/// that changes made outside of systems are recorded with the current tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TickCounter(Arc<AtomicU32>);
+
+impl TickCounter {
+    /// Create a counter starting at the given tick.
+    pub fn new(tick: Tick) -> Self {
+        Self(Arc::new(AtomicU32::new(tick.0)))
+    }
+
+    /// Get the current tick.
+    pub fn get(&self) -> Tick {
+        Tick(self.0.load(Ordering::Acquire))
+    }
+
+    /// Advance the counter, returning the tick from before it was advanced.
+    ///
+    /// The returned tick is reserved for the caller: every change recorded afterwards, with
+    /// [`get()`][Self::get] or another reserved tick, has a newer tick.
+    pub fn increment(&self) -> Tick {
+        Tick(self.0.fetch_add(1, Ordering::AcqRel))
+    }
+}
+
+/// The change ticks of a single component or resource.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentTicks {
+    /// The tick that the value was inserted in.
+    pub added: Tick,
+    /// The tick that the value was last mutably accessed in.
+    pub changed: Tick,
+}
+
+impl ComponentTicks {
+    /// Create ticks for a value that was inserted in the given tick.
+    pub fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// Whether the value was added after `last_run`.
+    pub fn is_added(&self, last_run: Option<Tick>, this_run: Tick) -> bool {
+        self.added.is_newer_than(last_run, this_run)
+    }
+
+    /// Whether the value was added or changed after `last_run`.
+    pub fn is_changed(&self, last_run: Option<Tick>, this_run: Tick) -> bool {
+        self.changed.is_newer_than(last_run, this_run)
+    }
+
+    /// Clamp the ticks so that they are no older than [`MAX_CHANGE_AGE`].
+    pub fn check(&mut self, this_run: Tick) {
+        self.added.check(this_run);
+        self.changed.check(this_run);
+    }
+}
+
+/// Mutable access to a component, that marks the component as changed when it is mutably
+/// dereferenced.
+///
+/// This is returned by the mutable accessors of [`ComponentStore`] and when joining over
+/// `&mut CompMut<T>` with [`Entities::iter_with()`].
+pub struct Mut<'a, T> {
+    value: &'a mut T,
+    ticks: &'a mut ComponentTicks,
+    change_tick: Tick,
+}
+
+impl<'a, T> Mut<'a, T> {
+    pub(crate) fn new(value: &'a mut T, ticks: &'a mut ComponentTicks, change_tick: Tick) -> Self {
+        Self {
+            value,
+            ticks,
+            change_tick,
+        }
+    }
+
+    /// Get the change ticks of the component.
+    pub fn ticks(&self) -> ComponentTicks {
+        *self.ticks
+    }
+
+    /// Mark the component as changed, without modifying it.
+    pub fn set_changed(&mut self) {
+        self.ticks.changed = self.change_tick;
+    }
+
+    /// Get mutable access to the component without marking it as changed.
+    pub fn bypass_change_detection(&mut self) -> &mut T {
+        self.value
+    }
+
+    /// Convert into a mutable reference to the component, marking it as changed.
+    pub fn into_inner(self) -> &'a mut T {
+        self.ticks.changed = self.change_tick;
+        self.value
+    }
+}
+
+impl<'a, T> Deref for Mut<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for Mut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.set_changed();
+        self.value
+    }
+}
+
+impl<'a, T: std::fmt::Debug> std::fmt::Debug for Mut<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn tick_wraparound() {
+        let last_run = Tick::new(u32::MAX - 1);
+        let this_run = Tick::new(3);
+
+        assert!(Tick::new(u32::MAX).is_newer_than(Some(last_run), this_run));
+        assert!(Tick::new(1).is_newer_than(Some(last_run), this_run));
+        assert!(!last_run.is_newer_than(Some(last_run), this_run));
+        assert!(!Tick::new(u32::MAX - 2).is_newer_than(Some(last_run), this_run));
+        assert!(Tick::new(10).is_newer_than(None, this_run));
+    }
+
+    #[test]
+    fn tick_check_clamps_old_ticks() {
+        let this_run = Tick::new(5);
+        let mut old = this_run;
+        for _ in 0..7 {
+            old = Tick::new(old.get().wrapping_sub(CHECK_TICK_THRESHOLD));
+        }
+        assert!(this_run.get().wrapping_sub(old.get()) > MAX_CHANGE_AGE);
+
+        old.check(this_run);
+        assert_eq!(this_run.get().wrapping_sub(old.get()), MAX_CHANGE_AGE);
+        assert!(!old.is_newer_than(Some(Tick::new(4)), this_run));
+    }
+
+    #[derive(Clone, TypeUlid, Debug, PartialEq, Eq)]
+    #[ulid = "01GQ4B8M2W7ZK3N5TQ0VJ6XR9D"]
+    struct Pos(u32);
+
+    fn changed_positions(entities: Res<Entities>, pos: Comp<Pos>) -> anyhow::Result<Vec<u32>> {
+        Ok(entities
+            .iter_with((&pos, changed(&pos)))
+            .map(|(_, (pos, ()))| pos.0)
+            .collect())
+    }
+
+    fn added_positions(entities: Res<Entities>, pos: Comp<Pos>) -> anyhow::Result<Vec<u32>> {
+        Ok(entities
+            .iter_with((&pos, added(&pos)))
+            .map(|(_, (pos, ()))| pos.0)
+            .collect())
+    }
+
+    fn spawn_positions(world: &mut World, positions: &[u32]) -> Vec<Entity> {
+        world.components.init::<Pos>();
+        let entities = world.resources.get::<Entities>();
+        let mut entities = entities.borrow_mut();
+        let pos = world.components.get::<Pos>();
+        let mut pos = pos.borrow_mut();
+
+        positions
+            .iter()
+            .map(|&x| {
+                let entity = entities.create();
+                pos.insert(entity, Pos(x));
+                entity
+            })
+            .collect()
+    }
+
+    #[test]
+    fn changes_are_reported_once() {
+        let mut world = World::new();
+        let spawned = spawn_positions(&mut world, &[1, 2, 3]);
+
+        let mut changed = changed_positions.system();
+        changed.initialize(&mut world);
+        let mut added = added_positions.system();
+        added.initialize(&mut world);
+
+        // Everything is new the first time a system runs.
+        assert_eq!(changed.run(&world).unwrap(), vec![1, 2, 3]);
+        assert_eq!(added.run(&world).unwrap(), vec![1, 2, 3]);
+        assert!(changed.run(&world).unwrap().is_empty());
+        assert!(added.run(&world).unwrap().is_empty());
+
+        // Systems that aren't run in a stage share a tick until it is advanced.
+        world.increment_change_tick();
+        let entity = spawned[1];
+        world
+            .run_system(move |mut pos: CompMut<Pos>| {
+                pos.get_mut(entity).unwrap().0 = 20;
+            })
+            .unwrap();
+        assert_eq!(changed.run(&world).unwrap(), vec![20]);
+        assert!(added.run(&world).unwrap().is_empty());
+        assert!(changed.run(&world).unwrap().is_empty());
+
+        world.increment_change_tick();
+        spawn_positions(&mut world, &[4]);
+        assert_eq!(changed.run(&world).unwrap(), vec![4]);
+        assert_eq!(added.run(&world).unwrap(), vec![4]);
+        assert!(changed.run(&world).unwrap().is_empty());
+        assert!(added.run(&world).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reads_are_not_changes() {
+        #[derive(Clone, TypeUlid, Default)]
+        #[ulid = "01GQ4B9C5HV1R8E2YJ7KX0WM3T"]
+        struct Shared(std::sync::Arc<std::sync::Mutex<u32>>);
+
+        let mut world = World::new();
+        let spawned = spawn_positions(&mut world, &[1, 2]);
+        world.components.init::<Shared>();
+        world
+            .components
+            .get::<Shared>()
+            .borrow_mut()
+            .insert(spawned[0], Shared::default());
+
+        let mut changed = changed_positions.system();
+        changed.initialize(&mut world);
+        changed.run(&world).unwrap();
+
+        let mut shared_changed =
+            (|entities: Res<Entities>, shared: Comp<Shared>| -> anyhow::Result<usize> {
+                Ok(entities.iter_with(changed(&shared)).count())
+            })
+            .system();
+        shared_changed.initialize(&mut world);
+        assert_eq!(shared_changed.run(&world).unwrap(), 1);
+
+        world.increment_change_tick();
+        world
+            .run_system(
+                |entities: Res<Entities>, mut pos: CompMut<Pos>, shared: Comp<Shared>| {
+                    // Reading through a mutable store, or a `Mut`, doesn't mark anything as changed.
+                    let mut sum = 0;
+                    for (_, pos) in entities.iter_with(&pos) {
+                        sum += pos.0;
+                    }
+                    for (_, pos) in entities.iter_with(&mut pos) {
+                        sum += pos.0;
+                    }
+                    assert_eq!(sum, 6);
+
+                    // Mutation through a shared reference isn't detected either.
+                    for (_, shared) in entities.iter_with(&shared) {
+                        *shared.0.lock().unwrap() += 1;
+                    }
+                },
+            )
+            .unwrap();
+
+        assert!(changed.run(&world).unwrap().is_empty());
+        assert_eq!(shared_changed.run(&world).unwrap(), 0);
+    }
+
+    #[test]
+    fn system_does_not_detect_own_changes() {
+        let mut world = World::new();
+        spawn_positions(&mut world, &[1]);
+
+        let mut bump =
+            (|entities: Res<Entities>, mut pos: CompMut<Pos>| -> anyhow::Result<usize> {
+                let count = entities.iter_with(changed(&pos)).count();
+                for (_, mut pos) in entities.iter_with(&mut pos) {
+                    pos.0 += 1;
+                }
+                Ok(count)
+            })
+            .system();
+        bump.initialize(&mut world);
+
+        assert_eq!(bump.run(&world).unwrap(), 1);
+        world.increment_change_tick();
+        assert_eq!(bump.run(&world).unwrap(), 0);
+        world.increment_change_tick();
+        assert_eq!(bump.run(&world).unwrap(), 0);
+    }
+
+    #[test]
+    fn stages_detect_changes_made_later_in_the_frame() {
+        #[derive(Clone, TypeUlid, Default)]
+        #[ulid = "01GQ9T4K7B2N5X8DJ3RW6VZ1MC"]
+        struct Seen(Vec<usize>);
+
+        let mut world = World::new();
+        spawn_positions(&mut world, &[1, 2]);
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                |entities: Res<Entities>, pos: Comp<Pos>, mut seen: ResMut<Seen>| {
+                    seen.0.push(entities.iter_with(changed(&pos)).count());
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::Update,
+                |entities: Res<Entities>, mut pos: CompMut<Pos>| {
+                    for (_, mut pos) in entities.iter_with(&mut pos).take(1) {
+                        pos.0 += 1;
+                    }
+                },
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        for _ in 0..3 {
+            stages.run(&mut world).unwrap();
+        }
+        assert_eq!(world.resources.get::<Seen>().borrow().0, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn resource_changes() {
+        #[derive(Clone, TypeUlid, Default)]
+        #[ulid = "01GQ4BA0QF6T9D3MZ5XW8N2KCE"]
+        struct Score(u32);
+
+        let mut world = World::new();
+
+        let mut is_changed = (|score: Res<Score>| score.is_changed()).system();
+        is_changed.initialize(&mut world);
+        let mut is_added = (|score: Res<Score>| score.is_added()).system();
+        is_added.initialize(&mut world);
+
+        assert!(is_changed.run(&world).unwrap());
+        assert!(is_added.run(&world).unwrap());
+        assert!(!is_changed.run(&world).unwrap());
+        assert!(!is_added.run(&world).unwrap());
+
+        // Reading through a `ResMut` doesn't mark the resource as changed.
+        world.increment_change_tick();
+        world
+            .run_system(|score: ResMut<Score>| {
+                assert_eq!(score.0, 0);
+            })
+            .unwrap();
+        assert!(!is_changed.run(&world).unwrap());
+
+        world.increment_change_tick();
+        world
+            .run_system(|mut score: ResMut<Score>| {
+                score.0 += 1;
+            })
+            .unwrap();
+        assert!(is_changed.run(&world).unwrap());
+        assert!(!is_added.run(&world).unwrap());
+        assert!(!is_changed.run(&world).unwrap());
+
+        world.increment_change_tick();
+        world.resources.insert(Score(10));
+        assert!(is_changed.run(&world).unwrap());
+        assert!(is_added.run(&world).unwrap());
+    }
+}
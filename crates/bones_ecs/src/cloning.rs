@@ -0,0 +1,250 @@
+//! Cloning entities along with all of their components.
+
+use crate::prelude::*;
+
+/// Resource containing the component types that can't be cloned by [`World::clone_entity()`].
+///
+/// Every component has a clone function, but cloning some components would break invariants that
+/// the rest of the game relies on. For example, cloning the [`Children`] of an entity would list
+/// the same children under two parents. Mark such components with [`World::forbid_clone()`], so
+/// that cloning an entity that has one fails with a [`CloneEntityError`] naming the component.
+#[derive(Clone, Default, TypeUlid)]
+#[ulid = "01GQJ4R7B3WZ8D5KT0N2XF6MHE"]
+pub struct NonClonableComponents {
+    type_names: UlidMap<&'static str>,
+}
+
+impl NonClonableComponents {
+    /// Mark the component of type `T` as not clonable.
+    pub fn forbid<T: TypeUlid>(&mut self) {
+        self.type_names.insert(T::ULID, std::any::type_name::<T>());
+    }
+
+    /// Get the type name of the component with the given ULID, if it is not clonable.
+    pub fn get(&self, id: Ulid) -> Option<&'static str> {
+        self.type_names.get(&id).copied()
+    }
+}
+
+impl World {
+    /// Mark the component of type `T` as not clonable by [`World::clone_entity()`].
+    ///
+    /// See [`NonClonableComponents`].
+    pub fn forbid_clone<T: TypeUlid>(&mut self) {
+        self.resources.init::<NonClonableComponents>();
+        self.resources
+            .get::<NonClonableComponents>()
+            .borrow_mut()
+            .forbid::<T>();
+    }
+
+    /// Spawn a new entity with a clone of every component of `src`.
+    ///
+    /// The components are cloned with the clone functions that their stores were registered with.
+    /// Nothing is spawned if the entity isn't alive, or has a component that was marked with
+    /// [`World::forbid_clone()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Entities`] or any of the component stores are borrowed.
+    pub fn clone_entity(&mut self, src: Entity) -> Result<Entity, CloneEntityError> {
+        self.check_clonable(src)?;
+        let dst = self.resources.get::<Entities>().borrow_mut().create();
+        self.clone_components(src, dst);
+        Ok(dst)
+    }
+
+    /// Make sure that `src` is alive, and doesn't have any components that can't be cloned.
+    fn check_clonable(&self, src: Entity) -> Result<(), CloneEntityError> {
+        if !self.resources.get::<Entities>().borrow().is_alive(src) {
+            return Err(CloneEntityError::NotAlive(src));
+        }
+
+        let Some(non_clonable) = self.resources.try_get::<NonClonableComponents>() else {
+            return Ok(());
+        };
+        let non_clonable = non_clonable.borrow();
+        for (&id, components) in &self.components.components {
+            if let Some(type_name) = non_clonable.get(id) {
+                if components.borrow().bitset().bit_test(src.index() as usize) {
+                    return Err(CloneEntityError::NotClonable {
+                        entity: src,
+                        type_name,
+                        id,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clone every component of `src` to `dst`.
+    fn clone_components(&mut self, src: Entity, dst: Entity) {
+        for components in self.components.components.values() {
+            components.borrow_mut().clone_component(src, dst);
+        }
+    }
+}
+
+impl<'a> Commands<'a> {
+    /// Clone an entity, along with all of its components.
+    ///
+    /// The new entity is reserved immediately, but the components are cloned when the commands are
+    /// applied. See [`World::clone_entity()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when the commands are applied, if `src` can't be cloned. See [`CloneEntityError`].
+    pub fn clone_entity(&mut self, src: Entity) -> Entity {
+        let dst = self.spawn();
+        self.add(move |world| {
+            if let Err(error) = world.check_clonable(src) {
+                panic!("{error}");
+            }
+            world.clone_components(src, dst);
+        });
+        dst
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQJ4RF6M0T9YB2C8E5HVKQ7N"]
+    struct Transform {
+        translation: glam::Vec3,
+        scale: glam::Vec3,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQJ4RPX8N3F1QH6D9ZJW4A2S"]
+    struct Sprite {
+        image: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQJ4RY2C7K5V0XG3BMT8PE6D"]
+    struct Inventory(Vec<u32>);
+
+    fn spawn_player(world: &mut World) -> Entity {
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut transforms: CompMut<Transform>,
+                 mut sprites: CompMut<Sprite>,
+                 mut inventories: CompMut<Inventory>|
+                 -> anyhow::Result<_> {
+                    // Spawn another entity first, so that the player's components aren't at index
+                    // zero.
+                    let other = entities.create();
+                    sprites.insert(
+                        other,
+                        Sprite {
+                            image: "tree.png".into(),
+                        },
+                    );
+
+                    let player = entities.create();
+                    transforms.insert(
+                        player,
+                        Transform {
+                            translation: glam::Vec3::new(1.0, 2.0, 3.0),
+                            scale: glam::Vec3::ONE,
+                        },
+                    );
+                    sprites.insert(
+                        player,
+                        Sprite {
+                            image: "player.png".into(),
+                        },
+                    );
+                    inventories.insert(player, Inventory(vec![1, 2, 3]));
+                    Ok(player)
+                },
+            )
+            .unwrap()
+    }
+
+    fn assert_clone(world: &mut World, player: Entity, clone: Entity) {
+        world
+            .run_system(
+                move |transforms: Comp<Transform>,
+                      sprites: Comp<Sprite>,
+                      mut inventories: CompMut<Inventory>| {
+                    assert_ne!(player, clone);
+                    assert_eq!(transforms.get(clone), transforms.get(player));
+                    assert_eq!(sprites.get(clone).unwrap().image, "player.png");
+
+                    // The clone doesn't share data with the original.
+                    inventories.get_mut(clone).unwrap().0.push(4);
+                    assert_eq!(inventories.get(player).unwrap().0, [1, 2, 3]);
+                    assert_eq!(inventories.get(clone).unwrap().0, [1, 2, 3, 4]);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn clone_entity() {
+        let mut world = World::new();
+        let player = spawn_player(&mut world);
+
+        let clone = world.clone_entity(player).unwrap();
+        assert_clone(&mut world, player, clone);
+    }
+
+    #[test]
+    fn clone_entity_with_commands() {
+        let mut world = World::new();
+        let player = spawn_player(&mut world);
+
+        let clone = world
+            .run_system(move |mut commands: Commands| -> anyhow::Result<_> {
+                Ok(commands.clone_entity(player))
+            })
+            .unwrap();
+        world.apply_commands();
+        assert_clone(&mut world, player, clone);
+    }
+
+    #[test]
+    fn non_clonable_component() {
+        let mut world = World::new();
+        let player = spawn_player(&mut world);
+        world.forbid_clone::<Inventory>();
+
+        let error = world.clone_entity(player).unwrap_err();
+        assert_eq!(
+            error,
+            CloneEntityError::NotClonable {
+                entity: player,
+                type_name: std::any::type_name::<Inventory>(),
+                id: Inventory::ULID,
+            }
+        );
+        assert!(error.to_string().contains("Inventory"));
+
+        // Entities without the component can still be cloned.
+        world
+            .run_system(move |mut inventories: CompMut<Inventory>| {
+                inventories.remove(player);
+            })
+            .unwrap();
+        assert!(world.clone_entity(player).is_ok());
+    }
+
+    #[test]
+    fn clone_dead_entity() {
+        let mut world = World::new();
+        let player = spawn_player(&mut world);
+        world.resources.get::<Entities>().borrow_mut().kill(player);
+
+        assert_eq!(
+            world.clone_entity(player),
+            Err(CloneEntityError::NotAlive(player))
+        );
+    }
+}
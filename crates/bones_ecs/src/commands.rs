@@ -0,0 +1,282 @@
+//! Deferred world modifications, that are applied at the end of each stage.
+
+use crate::prelude::*;
+
+/// A command queued by [`Commands`], to be applied to the [`World`] later.
+pub type Command = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// Resource containing the queue of [`Command`]s that haven't been applied to the [`World`] yet.
+///
+/// The queue is applied by [`SystemStages`] at the end of every stage, or manually with
+/// [`World::apply_commands()`].
+///
+/// > **Note:** Queued commands can't be cloned, so cloning the queue creates an empty queue.
+#[derive(Default, TypeUlid)]
+#[ulid = "01GPT9W1TNCYGB1R7P3S3JSFMB"]
+pub struct CommandQueue {
+    commands: Vec<Command>,
+}
+
+impl Clone for CommandQueue {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl CommandQueue {
+    /// Add a command to the queue.
+    pub fn push<F: FnOnce(&mut World) + Send + Sync + 'static>(&mut self, command: F) {
+        self.commands.push(Box::new(command));
+    }
+
+    /// Returns the number of commands in the queue.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if there are no commands in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Remove all of the commands from the queue and return them.
+    pub fn take(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+}
+
+/// [`SystemParam`] for queuing changes to the [`World`], that are applied at the end of the
+/// stage.
+///
+/// This can be used to spawn and despawn entities, or add components and resources, from systems
+/// that are iterating over [`Entities`] and wouldn't be able to borrow them mutably.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GPTA1C6S3ZN2SVDGP3GEGHHR"]
+/// # struct Health(u32);
+/// fn despawn_dead(mut commands: Commands, entities: Res<Entities>, healths: Comp<Health>) {
+///     for (entity, health) in entities.iter_with(&healths) {
+///         if health.0 == 0 {
+///             commands.despawn(entity);
+///         }
+///     }
+/// }
+/// ```
+pub struct Commands<'a> {
+    queue: AtomicRefMut<'a, CommandQueue>,
+    entities: &'a AtomicResource<Entities>,
+}
+
+impl<'a> Commands<'a> {
+    /// Spawn a new entity.
+    ///
+    /// The entity is reserved immediately, so it's id may be used right away, but it won't be alive
+    /// until the commands are applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Entities`] is mutably borrowed, such as by a [`ResMut<Entities>`] parameter on
+    /// the same system. Use [`Entities::create()`] directly in that case.
+    pub fn spawn(&mut self) -> Entity {
+        let entity = self.entities.borrow().reserve();
+        // Reserved entities are created when the commands are applied, we just need to make sure
+        // the commands are applied even if nothing else is queued.
+        self.queue.push(|_| ());
+        entity
+    }
+
+    /// Despawn an entity.
+    ///
+    /// This kills the entity when the commands are applied. Like [`Entities::kill()`], the
+    /// entity's components are removed by the next [`World::maintain()`].
+    pub fn despawn(&mut self, entity: Entity) {
+        self.queue.push(move |world| {
+            world.resources.get::<Entities>().borrow_mut().kill(entity);
+        });
+    }
+
+    /// Insert a component for an entity.
+    pub fn insert<T: TypedEcsData>(&mut self, entity: Entity, component: T) {
+        self.queue.push(move |world| {
+            world.components.init::<T>();
+            world
+                .components
+                .get::<T>()
+                .borrow_mut()
+                .insert(entity, component);
+        });
+    }
+
+    /// Remove a component from an entity.
+    pub fn remove<T: TypedEcsData>(&mut self, entity: Entity) {
+        self.queue.push(move |world| {
+            if let Ok(components) = world.components.try_get::<T>() {
+                components.borrow_mut().remove(entity);
+            }
+        });
+    }
+
+    /// Insert a resource, replacing it if it already exists.
+    pub fn insert_resource<T: TypedEcsData>(&mut self, resource: T) {
+        self.queue
+            .push(move |world| world.resources.insert(resource));
+    }
+
+    /// Queue a custom command that will be run with mutable access to the [`World`].
+    pub fn add<F: FnOnce(&mut World) + Send + Sync + 'static>(&mut self, command: F) {
+        self.queue.push(command);
+    }
+}
+
+impl<'a> SystemParam for Commands<'a> {
+    type State = (AtomicResource<CommandQueue>, AtomicResource<Entities>);
+    type Param<'p> = Commands<'p>;
+    type Local = ();
+
+    fn initialize(world: &mut World) {
+        world.resources.init::<CommandQueue>();
+    }
+    fn access(access: &mut SystemAccess) {
+        access.resource_writes.insert(CommandQueue::ULID);
+        access.resource_reads.insert(Entities::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        (
+            world.resources.get::<CommandQueue>(),
+            world.resources.get::<Entities>(),
+        )
+    }
+    fn borrow<'s>(state: &'s mut Self::State, _local: &'s mut Self::Local) -> Self::Param<'s> {
+        Commands {
+            queue: state.0.borrow_mut(),
+            entities: &state.1,
+        }
+    }
+}
+
+impl World {
+    /// Apply all of the commands that have been queued by [`Commands`].
+    ///
+    /// This also creates any entities that have been [reserved][Entities::reserve].
+    pub fn apply_commands(&mut self) {
+        self.resources
+            .get::<Entities>()
+            .borrow_mut()
+            .flush_reserved();
+
+        let Some(queue) = self.resources.try_get::<CommandQueue>() else {
+            return;
+        };
+        let commands = queue.borrow_mut().take();
+        for command in commands {
+            command(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GPTA6H1J2J3B0B6N1T8FWY4G"]
+    struct Pos(i32);
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GPTA6RNGBKMRQ3AN8S0QNF6B"]
+    struct Child(Entity);
+
+    #[test]
+    fn spawn_while_iterating() {
+        let mut world = World::new();
+        world
+            .run_system(|mut entities: ResMut<Entities>, mut pos: CompMut<Pos>| {
+                for i in 0..3 {
+                    let entity = entities.create();
+                    pos.insert(entity, Pos(i));
+                }
+            })
+            .unwrap();
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                |mut commands: Commands, entities: Res<Entities>, pos: Comp<Pos>| {
+                    for (entity, pos) in entities.iter_with(&pos) {
+                        let child = commands.spawn();
+                        commands.insert(child, Pos(pos.0 + 10));
+                        commands.insert(entity, Child(child));
+                    }
+                },
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+        stages.run(&mut world).unwrap();
+
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let pos = world.components.get::<Pos>();
+        let pos = pos.borrow();
+        let children = world.components.get::<Child>();
+        let children = children.borrow();
+
+        assert_eq!(entities.iter_with(&pos).count(), 6);
+        for (_, (pos_a, child)) in entities.iter_with((&pos, &children)) {
+            assert!(entities.is_alive(child.0));
+            assert_eq!(pos.get(child.0).unwrap().0, pos_a.0 + 10);
+        }
+    }
+
+    #[test]
+    fn commands_applied_at_end_of_stage() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::Update, |mut commands: Commands| {
+                commands.insert_resource(7u32);
+            })
+            .add_system_to_stage(CoreStage::Update, |value: Res<u32>| {
+                // Not applied yet
+                assert_eq!(*value, 0);
+            })
+            .add_system_to_stage(CoreStage::PostUpdate, |value: Res<u32>| {
+                assert_eq!(*value, 7);
+            })
+            .initialize_systems(&mut world)
+            .unwrap();
+        stages.run(&mut world).unwrap();
+    }
+
+    #[test]
+    fn despawn_and_remove() {
+        let mut world = World::new();
+        let (e1, e2) = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            (entities.create(), entities.create())
+        };
+        world.components.init::<Pos>();
+        world
+            .components
+            .get::<Pos>()
+            .borrow_mut()
+            .insert(e2, Pos(2));
+
+        world
+            .run_system(move |mut commands: Commands| {
+                commands.despawn(e1);
+                commands.remove::<Pos>(e2);
+            })
+            .unwrap();
+        assert!(world.resources.get::<Entities>().borrow().is_alive(e1));
+
+        world.apply_commands();
+        assert!(!world.resources.get::<Entities>().borrow().is_alive(e1));
+        assert!(world.components.get::<Pos>().borrow().get(e2).is_none());
+    }
+}
@@ -1,6 +1,8 @@
 //! ECS component storage.
 
-use std::{any::TypeId, sync::Arc};
+use std::sync::Arc;
+
+use bytemuck::NoUninit;
 
 use crate::prelude::*;
 
@@ -15,12 +17,13 @@ pub use untyped::*;
 /// Makes sure that the component type `T` matches the component type previously registered with
 /// the same UUID.
 fn validate_type_uuid_match<T: TypeUlid + 'static>(
-    type_ids: &UlidMap<TypeId>,
+    type_ids: &UlidMap<RegisteredType>,
 ) -> Result<(), EcsError> {
-    if type_ids.get(&T::ULID).ok_or(EcsError::NotInitialized)? != &TypeId::of::<T>() {
-        Err(EcsError::TypeUlidCollision)
-    } else {
+    let registered = type_ids.get(&T::ULID).ok_or(EcsError::NotInitialized)?;
+    if registered.is::<T>() {
         Ok(())
+    } else {
+        registered.check(T::ULID, &RegisteredType::of::<T>())
     }
 }
 
@@ -31,7 +34,8 @@ fn validate_type_uuid_match<T: TypeUlid + 'static>(
 #[derive(Default)]
 pub struct ComponentStores {
     pub(crate) components: UlidMap<Arc<AtomicRefCell<UntypedComponentStore>>>,
-    type_ids: UlidMap<TypeId>,
+    pub(crate) type_ids: UlidMap<RegisteredType>,
+    change_tick: TickCounter,
 }
 
 impl Clone for ComponentStores {
@@ -45,6 +49,7 @@ impl Clone for ComponentStores {
                 .map(|(&k, v)| (k, Arc::new((**v).clone())))
                 .collect(),
             type_ids: self.type_ids.clone(),
+            change_tick: self.change_tick.clone(),
         }
     }
 }
@@ -64,16 +69,119 @@ impl ComponentStores {
                 validate_type_uuid_match::<T>(&self.type_ids)
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
-                entry.insert(Arc::new(AtomicRefCell::new(
-                    UntypedComponentStore::for_type::<T>(),
-                )));
-                self.type_ids.insert(T::ULID, TypeId::of::<T>());
+                let mut store = UntypedComponentStore::for_type::<T>();
+                store.set_tick_counter(self.change_tick.clone());
+                entry.insert(Arc::new(AtomicRefCell::new(store)));
+                self.type_ids.insert(T::ULID, RegisteredType::of::<T>());
 
                 Ok(())
             }
         }
     }
 
+    /// Initialize component storage for type `T`, laid out with the given [`StorageStrategy`].
+    ///
+    /// If the store was already initialized, its components are moved to the new layout.
+    pub fn init_with_strategy<T: Clone + TypeUlid + Send + Sync + 'static>(
+        &mut self,
+        strategy: StorageStrategy,
+    ) {
+        self.try_init_with_strategy::<T>(strategy).unwrap();
+    }
+
+    /// Initialize component storage for type `T`, laid out with the given [`StorageStrategy`].
+    ///
+    /// If the store was already initialized, its components are moved to the new layout.
+    pub fn try_init_with_strategy<T: Clone + TypeUlid + Send + Sync + 'static>(
+        &mut self,
+        strategy: StorageStrategy,
+    ) -> Result<(), EcsError> {
+        self.try_init::<T>()?;
+        self.components[&T::ULID]
+            .borrow_mut()
+            .set_storage_strategy(strategy);
+
+        Ok(())
+    }
+
+    /// Initialize component storage for type `T`, registering its [`PartialEq`] implementation to
+    /// find the components that changed in [`WorldSnapshot::diff()`].
+    ///
+    /// Without it, the components can't be compared, so every component of the type is in every
+    /// diff, unless the type was registered as [padding-free][Self::init_padding_free].
+    pub fn init_with_eq<T: Clone + PartialEq + TypeUlid + Send + Sync + 'static>(&mut self) {
+        self.try_init_with_eq::<T>().unwrap();
+    }
+
+    /// Initialize component storage for type `T`, registering its [`PartialEq`] implementation to
+    /// find the components that changed in [`WorldSnapshot::diff()`].
+    pub fn try_init_with_eq<T: Clone + PartialEq + TypeUlid + Send + Sync + 'static>(
+        &mut self,
+    ) -> Result<(), EcsError> {
+        self.try_init::<T>()?;
+        // Safe: The store was initialized for `T`, which the function compares.
+        unsafe {
+            self.components[&T::ULID].borrow_mut().set_eq_fn(T::raw_eq);
+        }
+
+        Ok(())
+    }
+
+    /// Initialize component storage for type `T`, registering it as having no padding, so that
+    /// its components are compared byte-for-byte in [`WorldSnapshot::diff()`].
+    ///
+    /// This is cheaper than comparing them with [`init_with_eq()`][Self::init_with_eq], but
+    /// components that own heap data, such as a [`Vec`], can't be padding-free.
+    pub fn init_padding_free<T: Clone + NoUninit + TypeUlid + Send + Sync + 'static>(&mut self) {
+        self.try_init_padding_free::<T>().unwrap();
+    }
+
+    /// Initialize component storage for type `T`, registering it as having no padding, so that
+    /// its components are compared byte-for-byte in [`WorldSnapshot::diff()`].
+    pub fn try_init_padding_free<T: Clone + NoUninit + TypeUlid + Send + Sync + 'static>(
+        &mut self,
+    ) -> Result<(), EcsError> {
+        self.try_init::<T>()?;
+        // Safe: `T` is `NoUninit`, so all of its bytes are initialized.
+        unsafe {
+            self.components[&T::ULID].borrow_mut().set_padding_free();
+        }
+
+        Ok(())
+    }
+
+    /// Initialize component storage for the type with the given ULID and [`RegisteredType`],
+    /// creating an empty store with the same layout and functions as `template` if it doesn't
+    /// exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a different type was already initialized with the same ULID.
+    ///
+    /// # Safety
+    ///
+    /// The `template` store must store components of the given `ty`.
+    pub(crate) unsafe fn init_untyped(
+        &mut self,
+        id: Ulid,
+        ty: RegisteredType,
+        template: &UntypedComponentStore,
+    ) -> Result<Arc<AtomicRefCell<UntypedComponentStore>>, EcsError> {
+        if let Some(existing) = self.type_ids.get(&id) {
+            existing.check(id, &ty)?;
+        }
+
+        let change_tick = &self.change_tick;
+        let components = self.components.entry(id).or_insert_with(|| {
+            let mut store = template.empty_copy();
+            store.set_tick_counter(change_tick.clone());
+            Arc::new(AtomicRefCell::new(store))
+        });
+        self.type_ids.insert(id, ty);
+
+        Ok(components.clone())
+    }
+
     /// Get the components of a certain type
     ///
     /// # Panics
@@ -113,4 +221,206 @@ impl ComponentStores {
             .cloned()
             .ok_or(EcsError::NotInitialized)
     }
+    /// Get the tick that components are marked as added or changed in, outside of systems.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Share the tick counter of the [`World`] that the components belong to, with all of the
+    /// component stores.
+    pub(crate) fn set_tick_counter(&mut self, counter: TickCounter) {
+        for components in self.components.values() {
+            components.borrow_mut().set_tick_counter(counter.clone());
+        }
+        self.change_tick = counter;
+    }
+
+    /// Clamp the change ticks of all of the components so that they are no older than
+    /// [`MAX_CHANGE_AGE`] compared to `this_run`.
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        for components in self.components.values() {
+            components.borrow_mut().check_change_ticks(this_run);
+        }
+    }
+
+    /// Replace the contents of the component stores with copies of the stores in `snapshot`.
+    ///
+    /// Stores that aren't in the `snapshot` are emptied, instead of being removed, so that systems
+    /// that have already been initialized can still access them.
+    pub(crate) fn restore(&mut self, snapshot: &ComponentStores) {
+        for (id, components) in &self.components {
+            let mut components = components.borrow_mut();
+            *components = match snapshot.components.get(id) {
+                Some(saved) => saved.borrow().clone(),
+                None => components.empty_copy(),
+            };
+            components.set_tick_counter(self.change_tick.clone());
+        }
+
+        for (&id, saved) in &snapshot.components {
+            if let std::collections::hash_map::Entry::Vacant(entry) = self.components.entry(id) {
+                let mut components = saved.borrow().clone();
+                components.set_tick_counter(self.change_tick.clone());
+                entry.insert(Arc::new(AtomicRefCell::new(components)));
+            }
+        }
+        for (&id, &ty) in &snapshot.type_ids {
+            self.type_ids.entry(id).or_insert(ty);
+        }
+    }
+
+    /// Clear the lists of [removed][UntypedComponentStore::removed] components of all of the
+    /// component stores.
+    pub fn clear_removed(&self) {
+        for components in self.components.values() {
+            components.borrow_mut().clear_removed();
+        }
+    }
+}
+
+/// System that clears the lists of removed components read by [`RemovedComponents`].
+///
+/// This is added to [`CoreStage::Last`] by [`SystemStages::with_core_stages()`], so that the
+/// components removed during a frame can be detected until the end of that frame. Components
+/// removed by systems that run after it, including systems added to [`CoreStage::Last`] later,
+/// are detected during the next frame instead. When using custom stages, it should be added to the
+/// end of a stage that runs once every frame.
+pub fn removed_components_update_system(world: &World) {
+    world.components.clear_removed();
+}
+
+/// Resource containing the component types that only live for a single frame.
+///
+/// Component types are registered with [`World::register_frame_component()`].
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[ulid = "01GQSA7M3K9D2XW5BV8JZC4NQF"]
+pub struct FrameComponents {
+    ids: UlidSet,
+}
+
+impl FrameComponents {
+    /// Register the component type `T` to be cleared by the [`frame_components_clear_system`].
+    pub fn register<T: TypeUlid>(&mut self) {
+        self.ids.insert(T::ULID);
+    }
+
+    /// Returns `true` if the component type `T` is cleared every frame.
+    pub fn contains<T: TypeUlid>(&self) -> bool {
+        self.ids.contains(&T::ULID)
+    }
+}
+
+impl World {
+    /// Register the component type `T` as a frame component, that is removed from every entity at
+    /// the end of each frame by the [`frame_components_clear_system`].
+    ///
+    /// This is useful for marker components such as `JustSpawned` or `Hit`, that must only be seen
+    /// during the frame that they were inserted in.
+    pub fn register_frame_component<T: TypedEcsData>(&mut self) {
+        self.components.init::<T>();
+        self.resources.init::<FrameComponents>();
+        self.resources
+            .get::<FrameComponents>()
+            .borrow_mut()
+            .register::<T>();
+    }
+}
+
+/// System that [clears][UntypedComponentStore::clear] the stores of the component types registered
+/// in the [`FrameComponents`] resource.
+///
+/// This is added to [`CoreStage::Last`] by [`SystemStages::with_core_stages()`], so frame
+/// components inserted during a frame can be seen by all of the systems that run after they are
+/// inserted, until the end of that frame. When using custom stages, it should be added to the end
+/// of a stage that runs once every frame.
+pub fn frame_components_clear_system(world: &World) {
+    let Some(frame_components) = world.resources.try_get::<FrameComponents>() else {
+        return;
+    };
+    for id in &frame_components.borrow().ids {
+        if let Some(components) = world.components.components.get(id) {
+            components.borrow_mut().clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQSA8E6P1R4T7W0Y3B5D8G2J"]
+    struct Hit(u32);
+
+    /// Component that needs to be dropped.
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQSA9X4N7Q0S3U6W9Y2A5C8E"]
+    struct Label(String);
+
+    /// Resource recording the hits seen by the systems after the hits are inserted.
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GQSA8P9S2V5X8Z1C4F7H0K3M"]
+    struct Seen(Vec<u32>);
+
+    #[test]
+    fn clear_store() {
+        let mut store = ComponentStore::<Label>::default();
+        for i in 0..10 {
+            store.insert(Entity::new(i * 3, 0), Label(i.to_string()));
+        }
+        store.clear();
+        assert_eq!(store.iter().count(), 0);
+        assert!(store.get(Entity::new(3, 0)).is_none());
+        assert!(store.removed().is_empty());
+
+        store.insert(Entity::new(4, 0), Label("again".into()));
+        assert_eq!(store.iter().collect::<Vec<_>>(), [&Label("again".into())]);
+
+        let mut dense = ComponentStore::<Label>::with_storage_strategy(StorageStrategy::Dense);
+        dense.insert(Entity::new(7, 0), Label("a".into()));
+        dense.insert(Entity::new(2, 0), Label("b".into()));
+        dense.clear();
+        assert_eq!(dense.iter().count(), 0);
+        dense.insert(Entity::new(2, 0), Label("c".into()));
+        assert_eq!(dense.iter().collect::<Vec<_>>(), [&Label("c".into())]);
+    }
+
+    #[test]
+    fn frame_components() {
+        let mut world = World::new();
+        world.register_frame_component::<Hit>();
+        assert!(world
+            .resources
+            .get::<FrameComponents>()
+            .borrow()
+            .contains::<Hit>());
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                |mut entities: ResMut<Entities>, mut hits: CompMut<Hit>, mut frame: ResMut<u32>| {
+                    *frame += 1;
+                    // Only hit something every other frame.
+                    if *frame % 2 == 1 {
+                        hits.insert(entities.create(), Hit(*frame));
+                    }
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                |entities: Res<Entities>, hits: Comp<Hit>, mut seen: ResMut<Seen>| {
+                    seen.0
+                        .extend(entities.iter_with(&hits).map(|(_, hit)| hit.0));
+                },
+            );
+        stages.initialize_systems(&mut world).unwrap();
+
+        for _ in 0..4 {
+            stages.run(&mut world).unwrap();
+            world.maintain();
+        }
+        assert_eq!(world.resources.get::<Seen>().borrow().0, [1, 3]);
+        assert_eq!(world.components.get::<Hit>().borrow().iter().count(), 0);
+    }
 }
@@ -34,34 +34,128 @@ impl<'a, T: 'static> Iterator for ComponentBitsetIterator<'a, T> {
 /// Mutable iterator over components matching a given bitset
 pub struct ComponentBitsetIteratorMut<'a, T> {
     iter: UntypedComponentBitsetIteratorMut<'a>,
+    /// The tick that the components are marked as changed with.
+    change_tick: Tick,
     _phantom: PhantomData<T>,
 }
 
 impl<'a, T> ComponentBitsetIteratorMut<'a, T> {
     /// # Safety
     /// The untyped iterator must be valid for type T.
-    pub(crate) unsafe fn new(iter: UntypedComponentBitsetIteratorMut<'a>) -> Self {
+    pub(crate) unsafe fn new(
+        iter: UntypedComponentBitsetIteratorMut<'a>,
+        change_tick: Tick,
+    ) -> Self {
         Self {
             iter,
+            change_tick,
             _phantom: PhantomData,
         }
     }
 }
 
 impl<'a, T: 'static> Iterator for ComponentBitsetIteratorMut<'a, T> {
-    type Item = &'a mut T;
+    type Item = Mut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ptr = self.iter.next()?;
+        // The untyped iterator has moved past the index of the component it returned.
+        let index = self.iter.current_id - 1;
+        let ticks = self.iter.components.ticks.as_mut_ptr();
+
+        // SAFE: It is unsafe to construct this iterator, and user affirms that untyped iterator
+        // is valid for type T. Every index is only returned once, and the entities with a
+        // component have both data and ticks.
+        unsafe {
+            Some(Mut::new(
+                &mut *(ptr as *mut T),
+                &mut *ticks.add(index),
+                self.change_tick,
+            ))
+        }
+    }
+}
+
+/// Read-only iterator over the entities in a given bitset, that yields the component of each
+/// entity, if it has one.
+pub struct OptionalComponentBitsetIterator<'a, T> {
+    iter: UntypedOptionalComponentBitsetIterator<'a>,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T> OptionalComponentBitsetIterator<'a, T> {
+    /// # Safety
+    /// The untyped iterator must be valid for type T.
+    pub(crate) unsafe fn new(iter: UntypedOptionalComponentBitsetIterator<'a>) -> Self {
+        Self {
+            iter,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'static> Iterator for OptionalComponentBitsetIterator<'a, T> {
+    type Item = Option<&'a T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.iter
             .next()
             // SAFE: It is unsafe to construct this iterator, and user affirms that untyped iterator
             // is valid for type T.
-            .map(|x| unsafe { &mut *(x as *mut T) })
+            .map(|x| x.map(|x| unsafe { &*(x as *const T) }))
+    }
+}
+
+/// Mutable iterator over the entities in a given bitset, that yields the component of each entity,
+/// if it has one.
+pub struct OptionalComponentBitsetIteratorMut<'a, T> {
+    iter: UntypedOptionalComponentBitsetIteratorMut<'a>,
+    /// The tick that the components are marked as changed with.
+    change_tick: Tick,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T> OptionalComponentBitsetIteratorMut<'a, T> {
+    /// # Safety
+    /// The untyped iterator must be valid for type T.
+    pub(crate) unsafe fn new(
+        iter: UntypedOptionalComponentBitsetIteratorMut<'a>,
+        change_tick: Tick,
+    ) -> Self {
+        Self {
+            iter,
+            change_tick,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'static> Iterator for OptionalComponentBitsetIteratorMut<'a, T> {
+    type Item = Option<Mut<'a, T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(ptr) = self.iter.next()? else {
+            return Some(None);
+        };
+        // The untyped iterator has moved past the index of the component it returned.
+        let index = self.iter.current_id - 1;
+        let ticks = self.iter.components.ticks.as_mut_ptr();
+
+        // SAFE: It is unsafe to construct this iterator, and user affirms that untyped iterator
+        // is valid for type T. Every index is only returned once, and the entities with a
+        // component have both data and ticks.
+        unsafe {
+            Some(Some(Mut::new(
+                &mut *(ptr as *mut T),
+                &mut *ticks.add(index),
+                self.change_tick,
+            )))
+        }
     }
 }
 
 /// Iterates over components using a provided bitset. Each time the bitset has a 1 in index i, the
-/// iterator will fetch data from the storage at index i and return it.
+/// iterator will fetch the component of entity index i from the storage and return it.
 pub struct UntypedComponentBitsetIterator<'a> {
     pub(crate) current_id: usize,
     pub(crate) components: &'a UntypedComponentStore,
@@ -71,28 +165,19 @@ pub struct UntypedComponentBitsetIterator<'a> {
 impl<'a> Iterator for UntypedComponentBitsetIterator<'a> {
     type Item = *const u8;
     fn next(&mut self) -> Option<Self::Item> {
-        let max_id = self.components.max_id;
-        let size = self.components.layout.size();
-        while !(self.bitset.bit_test(self.current_id)
-            && self.components.bitset.bit_test(self.current_id))
-            && self.current_id <= max_id
-        {
-            self.current_id += 1;
-        }
-        let ret = if self.current_id <= max_id {
-            let offset = self.current_id * size;
-            // SAFE: Here we are just getting a pointer, not doing anything unsafe with it.
-            Some(unsafe { self.components.storage.as_ptr().add(offset) })
-        } else {
-            None
-        };
-        self.current_id += 1;
-        ret
+        let id = self.bitset.next_set_bit_and(
+            &self.components.bitset,
+            self.current_id,
+            self.components.max_id,
+        )?;
+        self.current_id = id + 1;
+
+        Some(self.components.ptr(id))
     }
 }
 
 /// Iterates over components using a provided bitset. Each time the bitset has a 1 in index i, the
-/// iterator will fetch data from the storage at index i.
+/// iterator will fetch the component of entity index i from the storage.
 pub struct UntypedComponentBitsetIteratorMut<'a> {
     pub(crate) current_id: usize,
     pub(crate) components: &'a mut UntypedComponentStore,
@@ -102,23 +187,64 @@ pub struct UntypedComponentBitsetIteratorMut<'a> {
 impl<'a> Iterator for UntypedComponentBitsetIteratorMut<'a> {
     type Item = *mut u8;
     fn next(&mut self) -> Option<Self::Item> {
-        let max_id = self.components.max_id;
-        let size = self.components.layout.size();
-        while !(self.bitset.bit_test(self.current_id)
-            && self.components.bitset.bit_test(self.current_id))
-            && self.current_id <= max_id
-        {
-            self.current_id += 1;
+        let id = self.bitset.next_set_bit_and(
+            &self.components.bitset,
+            self.current_id,
+            self.components.max_id,
+        )?;
+        self.current_id = id + 1;
+
+        Some(self.components.ptr_mut(id))
+    }
+}
+
+/// Iterates over the entities in a provided bitset. Each time the bitset has a 1 in index i, the
+/// iterator returns the component of entity index i if there is a component at that index,
+/// and `None` otherwise.
+///
+/// Unlike [`UntypedComponentBitsetIterator`], this doesn't skip the entities that don't have a
+/// component.
+pub struct UntypedOptionalComponentBitsetIterator<'a> {
+    pub(crate) current_id: usize,
+    pub(crate) components: &'a UntypedComponentStore,
+    pub(crate) bitset: Rc<BitSetVec>,
+}
+
+impl<'a> Iterator for UntypedOptionalComponentBitsetIterator<'a> {
+    type Item = Option<*const u8>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.bitset.next_set_bit(self.current_id, BITSET_SIZE)?;
+        self.current_id = id + 1;
+        if self.components.bitset.bit_test(id) {
+            Some(Some(self.components.ptr(id)))
+        } else {
+            Some(None)
         }
-        let ret = if self.current_id <= max_id {
-            let offset = self.current_id * size;
-            // SAFE: Here we are just getting a pointer, not doing anything unsafe with it.
-            Some(unsafe { self.components.storage.as_mut_ptr().add(offset) })
+    }
+}
+
+/// Iterates over the entities in a provided bitset. Each time the bitset has a 1 in index i, the
+/// iterator returns the component of entity index i if there is a component at that index,
+/// and `None` otherwise.
+///
+/// Unlike [`UntypedComponentBitsetIteratorMut`], this doesn't skip the entities that don't have a
+/// component.
+pub struct UntypedOptionalComponentBitsetIteratorMut<'a> {
+    pub(crate) current_id: usize,
+    pub(crate) components: &'a mut UntypedComponentStore,
+    pub(crate) bitset: Rc<BitSetVec>,
+}
+
+impl<'a> Iterator for UntypedOptionalComponentBitsetIteratorMut<'a> {
+    type Item = Option<*mut u8>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.bitset.next_set_bit(self.current_id, BITSET_SIZE)?;
+        self.current_id = id + 1;
+        if self.components.bitset.bit_test(id) {
+            Some(Some(self.components.ptr_mut(id)))
         } else {
-            None
-        };
-        self.current_id += 1;
-        ret
+            Some(None)
+        }
     }
 }
 
@@ -142,4 +268,31 @@ mod test {
         assert_eq!(components.iter_with_bitset(bitset.clone()).count(), 0);
         assert_eq!(components.iter_mut_with_bitset(bitset).count(), 0);
     }
+
+    #[test]
+    fn iter_optional_with_bitset() {
+        let mut entities = Entities::default();
+        let e1 = entities.create();
+        let e2 = entities.create();
+        let e3 = entities.create();
+        let mut components = ComponentStore::<A>::default();
+        components.insert(e2, A);
+
+        let mut bitset = BitSetVec::default();
+        bitset.bit_set(e1.index() as usize);
+        bitset.bit_set(e2.index() as usize);
+        let bitset = Rc::new(bitset);
+
+        let found = components
+            .iter_with_bitset_optional(bitset.clone())
+            .map(|x| x.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(found, [false, true]);
+        let found = components
+            .iter_mut_with_bitset_optional(bitset)
+            .map(|x| x.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(found, [false, true]);
+        assert!(!components.contains(e3));
+    }
 }
@@ -83,10 +83,24 @@ impl<T: TypedEcsData> ComponentStore<T> {
         self.components
     }
 
+    /// Create an empty [`ComponentStore<T>`], laid out with the given [`StorageStrategy`].
+    pub fn with_storage_strategy(strategy: StorageStrategy) -> Self {
+        let mut store = Self::default();
+        store.components.set_storage_strategy(strategy);
+        store
+    }
+
+    /// Get the [`StorageStrategy`] that the components are laid out with.
+    pub fn storage_strategy(&self) -> StorageStrategy {
+        self.components.storage_strategy()
+    }
+
     /// Inserts a component for the given `Entity` index.
     /// Returns the previous component, if any.
     pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
-        self.ops.insert(&mut self.components, entity, component)
+        let change_tick = self.components.change_tick();
+        self.ops
+            .insert(&mut self.components, entity, component, change_tick)
     }
 
     /// Gets an immutable reference to the component of `Entity`.
@@ -95,8 +109,20 @@ impl<T: TypedEcsData> ComponentStore<T> {
     }
 
     /// Gets a mutable reference to the component of `Entity`.
-    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
-        self.ops.get_mut(&mut self.components, entity)
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Mut<T>> {
+        let change_tick = self.components.change_tick();
+        self.ops.get_mut(&mut self.components, entity, change_tick)
+    }
+
+    /// Gets mutable references to the components of multiple entities at once.
+    /// Errors if an entity doesn't have the component, or if the same entity is passed twice.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        entities: [Entity; N],
+    ) -> Result<[Mut<T>; N], GetManyMutError> {
+        let change_tick = self.components.change_tick();
+        self.ops
+            .get_many_mut(&mut self.components, entities, change_tick)
     }
 
     /// Removes the component of `Entity`.
@@ -106,16 +132,26 @@ impl<T: TypedEcsData> ComponentStore<T> {
         self.ops.remove(&mut self.components, entity)
     }
 
+    /// Remove all of the components, keeping the memory allocated for them.
+    ///
+    /// See [`UntypedComponentStore::clear()`].
+    pub fn clear(&mut self) {
+        self.components.clear();
+    }
+
     /// Iterates immutably over all components of this type.
     /// Very fast but doesn't allow joining with other component types.
+    ///
+    /// See [`UntypedComponentStore::iter()`] for the order of the components.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.ops.iter(&self.components)
     }
 
     /// Iterates mutably over all components of this type.
     /// Very fast but doesn't allow joining with other component types.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.ops.iter_mut(&mut self.components)
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = Mut<T>> {
+        let change_tick = self.components.change_tick();
+        self.ops.iter_mut(&mut self.components, change_tick)
     }
 
     /// Iterates immutably over the components of this type where `bitset`
@@ -129,7 +165,31 @@ impl<T: TypedEcsData> ComponentStore<T> {
     /// indicates the indices of entities.
     /// Slower than `iter()` but allows joining between multiple component types.
     pub fn iter_mut_with_bitset(&mut self, bitset: Rc<BitSetVec>) -> ComponentBitsetIteratorMut<T> {
-        self.ops.iter_mut_with_bitset(&mut self.components, bitset)
+        let change_tick = self.components.change_tick();
+        self.ops
+            .iter_mut_with_bitset(&mut self.components, bitset, change_tick)
+    }
+
+    /// Iterates immutably over the entities in `bitset`, returning the component of each entity
+    /// if it has one.
+    /// Unlike `iter_with_bitset()`, entities without a component are not skipped.
+    pub fn iter_with_bitset_optional(
+        &self,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIterator<T> {
+        self.ops.iter_with_bitset_optional(&self.components, bitset)
+    }
+
+    /// Iterates mutably over the entities in `bitset`, returning the component of each entity if
+    /// it has one.
+    /// Unlike `iter_mut_with_bitset()`, entities without a component are not skipped.
+    pub fn iter_mut_with_bitset_optional(
+        &mut self,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIteratorMut<T> {
+        let change_tick = self.components.change_tick();
+        self.ops
+            .iter_mut_with_bitset_optional(&mut self.components, bitset, change_tick)
     }
 
     /// Read the bitset containing the list of entites with this component type on it.
@@ -137,11 +197,45 @@ impl<T: TypedEcsData> ComponentStore<T> {
         self.components.bitset()
     }
 
+    /// Returns the number of components in the store.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns `true` if there are no components in the store.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns the number of components that the store has room for without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.components.capacity()
+    }
+
+    /// Free the memory that isn't needed for the components in the store.
+    ///
+    /// See [`UntypedComponentStore::shrink_to_fit()`].
+    pub fn shrink_to_fit(&mut self) {
+        self.components.shrink_to_fit();
+    }
+
     /// Check whether or not this component store has data for the given entity.
     #[inline]
     pub fn contains(&self, entity: Entity) -> bool {
         self.bitset().contains(entity)
     }
+
+    /// Get the entities whose components have been removed since the list was last cleared.
+    ///
+    /// See [`UntypedComponentStore::removed()`].
+    pub fn removed(&self) -> &[Entity] {
+        self.components.removed()
+    }
+
+    /// Get the change ticks of the component of `Entity`, if it has one.
+    pub fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.components.ticks(entity)
+    }
 }
 
 /// A typed, wrapper handle around [`UntypedComponentStore`] that is runtime borrow checked and can
@@ -175,21 +269,51 @@ impl<T: TypedEcsData> AtomicComponentStore<T> {
     }
 
     /// Borrow the component store.
+    ///
+    /// Every component counts as added and changed for the [`is_added()`] and [`is_changed()`]
+    /// checks of the returned borrow.
+    ///
+    /// [`is_added()`]: AtomicComponentStoreRef::is_added
+    /// [`is_changed()`]: AtomicComponentStoreRef::is_changed
     pub fn borrow(&self) -> AtomicComponentStoreRef<T> {
+        #[cfg(feature = "debug")]
+        let components = crate::debug::borrow(&self.components, || {
+            format!("Comp<{}>", std::any::type_name::<T>())
+        });
+        #[cfg(not(feature = "debug"))]
+        let components = self.components.borrow();
+        let this_run = components.change_tick();
         AtomicComponentStoreRef {
-            components: self.components.borrow(),
+            components,
             // Safe: The component type T is the same as the one we already have
             ops: unsafe { TypedComponentOps::<T>::new() },
+            last_run: None,
+            this_run,
         }
     }
 
     /// Mutably borrow the component store.
+    ///
+    /// Every component counts as added and changed for the [`is_added()`] and [`is_changed()`]
+    /// checks of the returned borrow.
+    ///
+    /// [`is_added()`]: AtomicComponentStoreRefMut::is_added
+    /// [`is_changed()`]: AtomicComponentStoreRefMut::is_changed
     pub fn borrow_mut(&self) -> AtomicComponentStoreRefMut<T> {
+        #[cfg(feature = "debug")]
+        let components = crate::debug::borrow_mut(&self.components, || {
+            format!("CompMut<{}>", std::any::type_name::<T>())
+        });
+        #[cfg(not(feature = "debug"))]
+        let components = self.components.borrow_mut();
+        let this_run = components.change_tick();
         AtomicComponentStoreRefMut {
-            components: self.components.borrow_mut(),
+            components,
             // Safe: The construction of an [`AtomicComponents`] is unsafe, and this has the same
             // invariants.
             ops: unsafe { TypedComponentOps::<T>::new() },
+            last_run: None,
+            this_run,
         }
     }
 }
@@ -198,6 +322,10 @@ impl<T: TypedEcsData> AtomicComponentStore<T> {
 pub struct AtomicComponentStoreRef<'a, T: TypedEcsData> {
     components: AtomicRef<'a, UntypedComponentStore>,
     ops: TypedComponentOps<T>,
+    /// The tick that the system borrowing the store last ran in, used for change detection.
+    last_run: Option<Tick>,
+    /// The tick that changes are detected from, and recorded with.
+    this_run: Tick,
 }
 
 impl<'a, T: TypedEcsData> AtomicComponentStoreRef<'a, T> {
@@ -208,6 +336,8 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRef<'a, T> {
 
     /// Iterates immutably over all components of this type.
     /// Very fast but doesn't allow joining with other component types.
+    ///
+    /// See [`UntypedComponentStore::iter()`] for the order of the components.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.ops.iter(&self.components)
     }
@@ -219,6 +349,17 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRef<'a, T> {
         self.ops.iter_with_bitset(&self.components, bitset)
     }
 
+    /// Iterates immutably over the entities in `bitset`, returning the component of each entity
+    /// if it has one.
+    ///
+    /// Unlike `iter_with_bitset()`, entities without a component are not skipped.
+    pub fn iter_with_bitset_optional(
+        &self,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIterator<T> {
+        self.ops.iter_with_bitset_optional(&self.components, bitset)
+    }
+
     /// Read the bitset containing the list of entites with this component type on it.
     pub fn bitset(&self) -> &BitSetVec {
         self.components.bitset()
@@ -229,12 +370,85 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRef<'a, T> {
     pub fn contains(&self, entity: Entity) -> bool {
         self.bitset().contains(entity)
     }
+
+    /// Get the entities whose components have been removed since the list was last cleared.
+    ///
+    /// See [`UntypedComponentStore::removed()`].
+    pub fn removed(&self) -> &[Entity] {
+        self.components.removed()
+    }
+
+    /// Get the component of the single alive entity that has one.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no alive entity, or more than one alive entity, has this component.
+    pub fn single(&self, entities: &Entities) -> Result<&T, SingleError> {
+        entities
+            .single_with(self)
+            .map(|(_, component)| component)
+            .map_err(|e| e.with_name(std::any::type_name::<T>()))
+    }
+
+    /// Get the change ticks of the component of `Entity`, if it has one.
+    pub fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.components.ticks(entity)
+    }
+
+    /// Whether the component of the given [`Entity`] was added since the system borrowing the
+    /// store last ran.
+    pub fn is_added(&self, entity: Entity) -> bool {
+        self.components
+            .ticks(entity)
+            .map_or(false, |ticks| ticks.is_added(self.last_run, self.this_run))
+    }
+
+    /// Whether the component of the given [`Entity`] was added or mutably accessed since the
+    /// system borrowing the store last ran.
+    pub fn is_changed(&self, entity: Entity) -> bool {
+        self.components.ticks(entity).map_or(false, |ticks| {
+            ticks.is_changed(self.last_run, self.this_run)
+        })
+    }
+
+    /// Get the untyped store that this borrow is for.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn untyped(&self) -> &UntypedComponentStore {
+        &self.components
+    }
+
+    /// Detect the changes made since `last_run`, and record changes with `this_run`, replacing
+    /// `last_run` with `this_run` so that the next borrow detects the changes made from now on.
+    pub(crate) fn track_last_run(mut self, this_run: Tick, last_run: &mut Option<Tick>) -> Self {
+        self.this_run = this_run;
+        self.last_run = last_run.replace(this_run);
+        self
+    }
+
+    /// Remove the entities from `bitset` that don't have a component that was added since the
+    /// system borrowing the store last ran.
+    pub(crate) fn retain_added(&self, bitset: &mut BitSetVec) {
+        self.components
+            .retain_ticks(bitset, |ticks| ticks.is_added(self.last_run, self.this_run));
+    }
+
+    /// Remove the entities from `bitset` that don't have a component that was changed since the
+    /// system borrowing the store last ran.
+    pub(crate) fn retain_changed(&self, bitset: &mut BitSetVec) {
+        self.components.retain_ticks(bitset, |ticks| {
+            ticks.is_changed(self.last_run, self.this_run)
+        });
+    }
 }
 
 /// A mutable borrow of [`AtomicComponentStore`].
 pub struct AtomicComponentStoreRefMut<'a, T: TypedEcsData> {
     components: AtomicRefMut<'a, UntypedComponentStore>,
     ops: TypedComponentOps<T>,
+    /// The tick that the system borrowing the store last ran in, used for change detection.
+    last_run: Option<Tick>,
+    /// The tick that changes are detected from, and recorded with.
+    this_run: Tick,
 }
 
 impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
@@ -242,7 +456,8 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
     ///
     /// Returns the previous component, if any.
     pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
-        self.ops.insert(&mut self.components, entity, component)
+        self.ops
+            .insert(&mut self.components, entity, component, self.this_run)
     }
 
     /// Gets an immutable reference to the component of [`Entity`].
@@ -251,8 +466,39 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
     }
 
     /// Gets a mutable reference to the component of [`Entity`].
-    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
-        self.ops.get_mut(&mut self.components, entity)
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Mut<T>> {
+        self.ops
+            .get_mut(&mut self.components, entity, self.this_run)
+    }
+
+    /// Gets mutable references to the components of multiple [`Entity`]s at once.
+    ///
+    /// # Errors
+    ///
+    /// Errors if an entity doesn't have the component, or if the same entity is passed more than
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// # #[derive(Clone, TypeUlid)]
+    /// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+    /// # struct Health(u32);
+    /// fn attack(attacker: Entity, target: Entity, mut healths: CompMut<Health>) -> SystemResult {
+    ///     let [mut attacker, mut target] = healths.get_many_mut([attacker, target])?;
+    ///     target.0 = target.0.saturating_sub(10);
+    ///     attacker.0 += 1;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        entities: [Entity; N],
+    ) -> Result<[Mut<T>; N], GetManyMutError> {
+        self.ops
+            .get_many_mut(&mut self.components, entities, self.this_run)
     }
 
     /// Removes the component of [`Entity`].
@@ -262,9 +508,18 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
         self.ops.remove(&mut self.components, entity)
     }
 
+    /// Remove all of the components, keeping the memory allocated for them.
+    ///
+    /// See [`UntypedComponentStore::clear()`].
+    pub fn clear(&mut self) {
+        self.components.clear();
+    }
+
     /// Iterates immutably over all components of this type.
     ///
     /// Very fast but doesn't allow joining with other component types.
+    ///
+    /// See [`UntypedComponentStore::iter()`] for the order of the components.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.ops.iter(&self.components)
     }
@@ -272,8 +527,8 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
     /// Iterates mutably over all components of this type.
     ///
     /// Very fast but doesn't allow joining with other component types.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.ops.iter_mut(&mut self.components)
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = Mut<T>> {
+        self.ops.iter_mut(&mut self.components, self.this_run)
     }
 
     /// Iterates immutably over the components of this type where `bitset` indicates the indices of
@@ -289,7 +544,31 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
     ///
     /// Slower than `iter()` but allows joining between multiple component types.
     pub fn iter_mut_with_bitset(&mut self, bitset: Rc<BitSetVec>) -> ComponentBitsetIteratorMut<T> {
-        self.ops.iter_mut_with_bitset(&mut self.components, bitset)
+        self.ops
+            .iter_mut_with_bitset(&mut self.components, bitset, self.this_run)
+    }
+
+    /// Iterates immutably over the entities in `bitset`, returning the component of each entity
+    /// if it has one.
+    ///
+    /// Unlike `iter_with_bitset()`, entities without a component are not skipped.
+    pub fn iter_with_bitset_optional(
+        &self,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIterator<T> {
+        self.ops.iter_with_bitset_optional(&self.components, bitset)
+    }
+
+    /// Iterates mutably over the entities in `bitset`, returning the component of each entity if
+    /// it has one.
+    ///
+    /// Unlike `iter_mut_with_bitset()`, entities without a component are not skipped.
+    pub fn iter_mut_with_bitset_optional(
+        &mut self,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIteratorMut<T> {
+        self.ops
+            .iter_mut_with_bitset_optional(&mut self.components, bitset, self.this_run)
     }
 
     /// Get the bitset representing which entities have this component on it.
@@ -297,11 +576,116 @@ impl<'a, T: TypedEcsData> AtomicComponentStoreRefMut<'a, T> {
         self.components.bitset()
     }
 
+    /// Returns the number of components in the store.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns `true` if there are no components in the store.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns the number of components that the store has room for without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.components.capacity()
+    }
+
+    /// Free the memory that isn't needed for the components in the store.
+    ///
+    /// See [`UntypedComponentStore::shrink_to_fit()`].
+    pub fn shrink_to_fit(&mut self) {
+        self.components.shrink_to_fit();
+    }
+
     /// Check whether or not this component store has data for the given entity.
     #[inline]
     pub fn contains(&self, entity: Entity) -> bool {
         self.bitset().contains(entity)
     }
+
+    /// Get the entities whose components have been removed since the list was last cleared.
+    ///
+    /// See [`UntypedComponentStore::removed()`].
+    pub fn removed(&self) -> &[Entity] {
+        self.components.removed()
+    }
+
+    /// Get the component of the single alive entity that has one.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no alive entity, or more than one alive entity, has this component.
+    pub fn single(&self, entities: &Entities) -> Result<&T, SingleError> {
+        entities
+            .single_with(self)
+            .map(|(_, component)| component)
+            .map_err(|e| e.with_name(std::any::type_name::<T>()))
+    }
+
+    /// Get mutable access to the component of the single alive entity that has one.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no alive entity, or more than one alive entity, has this component.
+    pub fn single_mut(&mut self, entities: &Entities) -> Result<Mut<T>, SingleError> {
+        entities
+            .single_with(self)
+            .map(|(_, component)| component)
+            .map_err(|e| e.with_name(std::any::type_name::<T>()))
+    }
+
+    /// Whether the component of the given [`Entity`] was added since the system borrowing the
+    /// store last ran.
+    pub fn is_added(&self, entity: Entity) -> bool {
+        self.components
+            .ticks(entity)
+            .map_or(false, |ticks| ticks.is_added(self.last_run, self.this_run))
+    }
+
+    /// Whether the component of the given [`Entity`] was added or mutably accessed since the
+    /// system borrowing the store last ran.
+    pub fn is_changed(&self, entity: Entity) -> bool {
+        self.components.ticks(entity).map_or(false, |ticks| {
+            ticks.is_changed(self.last_run, self.this_run)
+        })
+    }
+
+    /// Get the untyped store that this borrow is for.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn untyped(&self) -> &UntypedComponentStore {
+        &self.components
+    }
+
+    /// Get mutable access to the untyped store that this borrow is for, along with the tick that
+    /// changes are recorded with.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn untyped_mut(&mut self) -> (&mut UntypedComponentStore, Tick) {
+        (&mut self.components, self.this_run)
+    }
+
+    /// Detect the changes made since `last_run`, and record changes with `this_run`, replacing
+    /// `last_run` with `this_run` so that the next borrow detects the changes made from now on.
+    pub(crate) fn track_last_run(mut self, this_run: Tick, last_run: &mut Option<Tick>) -> Self {
+        self.this_run = this_run;
+        self.last_run = last_run.replace(this_run);
+        self
+    }
+
+    /// Remove the entities from `bitset` that don't have a component that was added since the
+    /// system borrowing the store last ran.
+    pub(crate) fn retain_added(&self, bitset: &mut BitSetVec) {
+        self.components
+            .retain_ticks(bitset, |ticks| ticks.is_added(self.last_run, self.this_run));
+    }
+
+    /// Remove the entities from `bitset` that don't have a component that was changed since the
+    /// system borrowing the store last ran.
+    pub(crate) fn retain_changed(&self, bitset: &mut BitSetVec) {
+        self.components.retain_ticks(bitset, |ticks| {
+            ticks.is_changed(self.last_run, self.this_run)
+        });
+    }
 }
 
 #[cfg(test)]
@@ -329,4 +713,286 @@ mod tests {
             vec![A("world".into())]
         )
     }
+
+    #[test]
+    fn get_many_mut() {
+        #[derive(Debug, Clone, PartialEq, Eq, TypeUlid)]
+        #[ulid = "01GQ9C0V5XK2E7M4RZ8T1HJ3NB"]
+        struct Health(u32);
+
+        let mut entities = Entities::default();
+        let e1 = entities.create();
+        let e2 = entities.create();
+        let e3 = entities.create();
+
+        let mut storage = ComponentStore::<Health>::default();
+        storage.insert(e1, Health(10));
+        storage.insert(e2, Health(20));
+
+        let [mut h1, mut h2] = storage.get_many_mut([e1, e2]).unwrap();
+        std::mem::swap(&mut h1.0, &mut h2.0);
+        assert_eq!(storage.get(e1), Some(&Health(20)));
+        assert_eq!(storage.get(e2), Some(&Health(10)));
+
+        assert_eq!(
+            storage.get_many_mut([e1, e3]).err(),
+            Some(GetManyMutError::Missing(e3))
+        );
+        assert_eq!(
+            storage.get_many_mut([e2, e1, e2]).err(),
+            Some(GetManyMutError::Duplicate(e2))
+        );
+
+        // An entity that reuses the index of a killed one shares it's component slot.
+        entities.kill(e2);
+        entities.clear_killed();
+        let e4 = entities.create();
+        assert_eq!(e4.index(), e2.index());
+        assert_eq!(
+            storage.get_many_mut([e2, e4]).err(),
+            Some(GetManyMutError::Duplicate(e4))
+        );
+
+        let [h1] = storage.get_many_mut([e1]).unwrap();
+        assert_eq!(*h1, Health(20));
+        assert!(storage.get_many_mut([]).is_ok());
+
+        let untyped = &mut storage.components;
+        assert!(untyped.get_many_mut([e1, e1]).is_err());
+        let [p1, p2] = untyped.get_many_mut([e1, e2]).unwrap();
+        assert_ne!(p1, p2);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQPA3M7ZC0K5R8VT2XD9HE4W"]
+    struct Label(String);
+
+    #[test]
+    fn dense_storage() {
+        let mut entities = Entities::default();
+        let [e1, e2, e3] = [entities.create(), entities.create(), entities.create()];
+
+        let mut storage = ComponentStore::<Label>::with_storage_strategy(StorageStrategy::Dense);
+        storage.insert(e3, Label("c".into()));
+        storage.insert(e1, Label("a".into()));
+        storage.insert(e2, Label("b".into()));
+        assert_eq!(
+            storage.insert(e1, Label("d".into())),
+            Some(Label("a".into()))
+        );
+
+        // Removing a component moves the last one into its slot.
+        assert_eq!(storage.remove(e3), Some(Label("c".into())));
+        assert_eq!(storage.remove(e3), None);
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get(e1), Some(&Label("d".into())));
+        assert_eq!(storage.get(e2), Some(&Label("b".into())));
+        assert!(!storage.contains(e3));
+
+        for mut label in storage.iter_mut() {
+            label.0.push('!');
+        }
+        let mut labels = storage.iter().cloned().collect::<Vec<_>>();
+        labels.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(labels, [Label("b!".into()), Label("d!".into())]);
+
+        // Clones have their own copy of the components.
+        let mut clone = ComponentStore::<Label>::default();
+        clone.components = storage.components.clone();
+        clone.get_mut(e1).unwrap().0.clear();
+        assert_eq!(storage.get(e1), Some(&Label("d!".into())));
+        assert_eq!(
+            storage
+                .iter_with_bitset(storage.bitset().clone().into())
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn change_storage_strategy() {
+        let mut entities = Entities::default();
+        let spawned = (0..10).map(|_| entities.create()).collect::<Vec<_>>();
+
+        let mut storage = ComponentStore::<Label>::default();
+        for (i, &entity) in spawned.iter().enumerate().filter(|(i, _)| i % 3 == 0) {
+            storage.insert(entity, Label(i.to_string()));
+        }
+
+        for strategy in [StorageStrategy::Dense, StorageStrategy::Sparse] {
+            storage.components.set_storage_strategy(strategy);
+            assert_eq!(storage.storage_strategy(), strategy);
+            assert_eq!(storage.len(), 4);
+            for (i, &entity) in spawned.iter().enumerate() {
+                let expected = (i % 3 == 0).then(|| Label(i.to_string()));
+                assert_eq!(storage.get(entity), expected.as_ref());
+            }
+        }
+    }
+
+    #[test]
+    fn join_dense_and_sparse() {
+        #[derive(Debug, Clone, Copy, PartialEq, TypeUlid)]
+        #[ulid = "01GQPA3VB2N6F1T4KX8QG5ZR0J"]
+        struct Position(f32);
+
+        #[derive(Debug, Clone, Copy, PartialEq, TypeUlid)]
+        #[ulid = "01GQPA42HE7W3D0MY9S6CJ1VPB"]
+        struct Velocity(f32);
+
+        let mut world = World::new();
+        world
+            .components
+            .init_with_strategy::<Position>(StorageStrategy::Dense);
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut positions: CompMut<Position>,
+                 mut velocities: CompMut<Velocity>| {
+                    for i in 0..100 {
+                        let entity = entities.create();
+                        positions.insert(entity, Position(i as f32));
+                        if i % 10 == 0 {
+                            velocities.insert(entity, Velocity(1.0));
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |entities: Res<Entities>,
+                 mut positions: CompMut<Position>,
+                 velocities: Comp<Velocity>| {
+                    for (_, (mut position, velocity)) in
+                        entities.iter_with((&mut positions, &velocities))
+                    {
+                        position.0 += velocity.0;
+                    }
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(|entities: Res<Entities>, positions: Comp<Position>| {
+                for (i, (_, position)) in entities.iter_with(&positions).enumerate() {
+                    let moved = if i % 10 == 0 { 1.0 } else { 0.0 };
+                    assert_eq!(*position, Position(i as f32 + moved));
+                }
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn raw_access() {
+        #[derive(Debug, Clone, Copy, PartialEq, TypeUlid)]
+        #[ulid = "01GQSN3A4B5C6D7E8F9G0H1J2K"]
+        #[repr(C)]
+        struct Transform {
+            position: [f32; 2],
+            layer: u32,
+        }
+
+        // SAFE: The fields fill the size of the transform without padding.
+        unsafe impl bytemuck::NoUninit for Transform {}
+        /// Get the bytes of a transform, which are all initialized, because it has no padding.
+        fn init_bytes(bytes: &[std::mem::MaybeUninit<u8>]) -> Vec<u8> {
+            // SAFE: The bytes are of a `Transform`, which has no padding.
+            bytes
+                .iter()
+                .map(|byte| unsafe { byte.assume_init() })
+                .collect()
+        }
+
+        let mut entities = Entities::default();
+        let e1 = entities.create();
+        let e2 = entities.create();
+
+        let mut store = UntypedComponentStore::for_type::<Transform>();
+        // SAFE: `Transform` is `NoUninit`.
+        unsafe { store.set_padding_free() };
+        assert!(store.is_padding_free());
+        let transform = Transform {
+            position: [1.0, 2.0],
+            layer: 3,
+        };
+        let mut value = transform;
+        // SAFE: The pointer is to a `Transform`, which the store is for.
+        unsafe { store.insert(e1, &mut value as *mut Transform as *mut u8) };
+
+        // Round-trip the component through an unaligned buffer, as a scripting layer would.
+        let mut buffer = vec![0u8];
+        buffer.extend(init_bytes(store.get_raw(e1).unwrap()));
+        // SAFE: The bytes are a `Transform`, which doesn't need drop.
+        assert_eq!(unsafe { store.insert_raw(e2, &buffer[1..]) }, Ok(false));
+        assert!(store.contains(e2));
+        // SAFE: The store is for `Transform`.
+        let get = |store: &UntypedComponentStore, entity| unsafe {
+            *store.get(entity).unwrap().cast::<Transform>()
+        };
+        assert_eq!(get(&store, e2), transform);
+
+        let layer = std::mem::size_of::<[f32; 2]>();
+        // SAFE: Any `u32` is a valid layer.
+        let bytes = unsafe { store.get_raw_mut(e2) }.unwrap();
+        for (byte, value) in bytes[layer..].iter_mut().zip(7u32.to_ne_bytes()) {
+            byte.write(value);
+        }
+        assert_eq!(get(&store, e2).layer, 7);
+
+        // SAFE: The bytes are a `Transform`.
+        let bytes = init_bytes(store.get_raw(e1).unwrap());
+        assert_eq!(unsafe { store.insert_raw(e2, &bytes) }, Ok(true));
+        assert_eq!(get(&store, e2), transform);
+
+        // SAFE: Nothing is inserted when the size is wrong.
+        let error = unsafe { store.insert_raw(e2, &bytes[1..]) }.unwrap_err();
+        assert_eq!(error.expected, std::mem::size_of::<Transform>());
+        assert_eq!(error.found, std::mem::size_of::<Transform>() - 1);
+
+        assert!(store.remove_raw(e1));
+        assert!(!store.remove_raw(e1));
+        assert!(store.get_raw(e1).is_none());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn raw_access_drops_components() {
+        #[derive(Debug, Clone, TypeUlid)]
+        #[ulid = "01GQSN3B5C6D7E8F9G0H1J2K3M"]
+        #[repr(C)]
+        struct Counted(std::rc::Rc<()>);
+
+        let mut entities = Entities::default();
+        let e1 = entities.create();
+        let e2 = entities.create();
+        let counter = std::rc::Rc::new(());
+
+        let mut store = UntypedComponentStore::for_type::<Counted>();
+        assert!(store.needs_drop());
+        let insert = |store: &mut UntypedComponentStore, entity| {
+            let component = std::mem::ManuallyDrop::new(Counted(counter.clone()));
+            // SAFE: The bytes are a `Counted`, and ownership of it is moved into the store.
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    &*component as *const Counted as *const u8,
+                    std::mem::size_of::<Counted>(),
+                )
+            };
+            unsafe { store.insert_raw(entity, bytes) }.unwrap()
+        };
+        insert(&mut store, e1);
+        insert(&mut store, e2);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 3);
+
+        // The replaced component is dropped.
+        insert(&mut store, e1);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 3);
+
+        store.remove_raw(e1);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 2);
+        drop(store);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+    }
 }
@@ -29,12 +29,13 @@ impl<T: Clone + 'static> TypedComponentOps<T> {
         Self(PhantomData)
     }
 
-    /// Insert a component into the store.
+    /// Insert a component into the store, recording the change with the given tick.
     pub fn insert(
         &self,
         components: &mut UntypedComponentStore,
         entity: Entity,
         component: T,
+        change_tick: Tick,
     ) -> Option<T> {
         let mut component = ManuallyDrop::new(component);
         let ptr = component.deref_mut() as *mut T as *mut u8;
@@ -42,7 +43,7 @@ impl<T: Clone + 'static> TypedComponentOps<T> {
         // SAFE: constructing TypedComponentOps is unsafe, and user asserts that component storage
         // is valid for type T.
         unsafe {
-            let already_existed = components.insert(entity, ptr);
+            let already_existed = components.insert_with_tick(entity, ptr, change_tick);
 
             if already_existed {
                 let previous_component = ManuallyDrop::take(&mut component);
@@ -61,16 +62,48 @@ impl<T: Clone + 'static> TypedComponentOps<T> {
     }
 
     /// Mutably borrow a component in the store, if it exists for the given entity.
-    pub fn get_mut(
+    ///
+    /// The component is marked as changed with the given tick when the returned [`Mut`] is mutably
+    /// dereferenced.
+    pub fn get_mut<'a>(
         &self,
-        components: &mut UntypedComponentStore,
+        components: &'a mut UntypedComponentStore,
         entity: Entity,
-    ) -> Option<&mut T> {
-        components
-            .get_mut(entity)
-            // SAFE: constructing TypedComponentOps is unsafe, and user asserts that component storage
-            // is valid for type T.
-            .map(|x| unsafe { &mut *(x as *mut T) })
+        change_tick: Tick,
+    ) -> Option<Mut<'a, T>> {
+        let index = entity.index() as usize;
+        if !components.bitset.bit_test(index) {
+            return None;
+        }
+
+        let ptr = components.ptr_mut(index);
+        // SAFE: constructing TypedComponentOps is unsafe, and user asserts that component storage
+        // is valid for type T. The entity has a component, so its slot is in bounds.
+        let value = unsafe { &mut *(ptr as *mut T) };
+        Some(Mut::new(value, &mut components.ticks[index], change_tick))
+    }
+
+    /// Mutably borrow the components of multiple entities at once.
+    ///
+    /// The components are marked as changed with the given tick when the returned [`Mut`]s are
+    /// mutably dereferenced.
+    pub fn get_many_mut<'a, const N: usize>(
+        &self,
+        components: &'a mut UntypedComponentStore,
+        entities: [Entity; N],
+        change_tick: Tick,
+    ) -> Result<[Mut<'a, T>; N], GetManyMutError> {
+        components.validate_many(&entities)?;
+
+        let ticks = components.ticks.as_mut_ptr();
+        Ok(std::array::from_fn(|i| {
+            let index = entities[i].index() as usize;
+            let ptr = components.ptr_mut(index) as *mut T;
+            // SAFE: constructing TypedComponentOps is unsafe, and user asserts that component
+            // storage is valid for type T. Every entity has a component, and no index is repeated,
+            // so the references don't alias.
+            unsafe { Mut::new(&mut *ptr, &mut *ticks.add(index), change_tick) }
+        }))
     }
 
     /// Remove a component from an entity, returning the previous component if one existed.
@@ -105,15 +138,47 @@ impl<T: Clone + 'static> TypedComponentOps<T> {
     }
 
     /// Mutably iterate over all components in the store.
+    ///
+    /// Each component is marked as changed with the given tick when it's [`Mut`] is mutably
+    /// dereferenced.
     pub fn iter_mut<'a>(
         &'a self,
         components: &'a mut UntypedComponentStore,
-    ) -> impl Iterator<Item = &'a mut T> {
-        components
-            .iter_mut()
+        change_tick: Tick,
+    ) -> impl Iterator<Item = Mut<'a, T>> {
+        let UntypedComponentStore {
+            bitset,
+            storage,
+            ticks,
+            max_id,
+            dense,
+            ..
+        } = components;
+        let storage = storage.as_mut_ptr() as *mut T;
+        let ticks = ticks.as_mut_ptr();
+
+        // Pairs of storage slots and entity indices.
+        let slots = match dense {
+            Some(dense) => either::Left(
+                dense
+                    .indices
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, &i)| (slot, i as usize)),
+            ),
+            None => either::Right(
+                (0..*max_id)
+                    .filter(move |&i| bitset.bit_test(i))
+                    .map(|i| (i, i)),
+            ),
+        };
+        slots
             // SAFE: constructing TypedComponentOps is unsafe, and user asserts that component
-            // storage is valid for type T.
-            .map(|x| unsafe { &mut *(x.as_mut_ptr() as *mut T) })
+            // storage is valid for type T. Every slot and index is only yielded once, and the
+            // entities with a component have both data and ticks.
+            .map(move |(slot, i)| unsafe {
+                Mut::new(&mut *storage.add(slot), &mut *ticks.add(i), change_tick)
+            })
     }
 
     /// Iterate over all the components in the store that match the entities in the given bitset.
@@ -129,13 +194,54 @@ impl<T: Clone + 'static> TypedComponentOps<T> {
 
     /// Mutably iterate over all the components in the store that match the entities in the given
     /// bitset.
+    ///
+    /// Each component is marked as changed with the given tick when it's [`Mut`] is mutably
+    /// dereferenced.
     pub fn iter_mut_with_bitset<'a>(
         &'a self,
         components: &'a mut UntypedComponentStore,
         bitset: Rc<BitSetVec>,
+        change_tick: Tick,
     ) -> ComponentBitsetIteratorMut<T> {
         // SAFE: Constructing `TypedComponentOps` is unsafe and user affirms the type T is valid for
         // the underlying, untyped data.
-        unsafe { ComponentBitsetIteratorMut::new(components.iter_mut_with_bitset(bitset)) }
+        unsafe {
+            ComponentBitsetIteratorMut::new(components.iter_mut_with_bitset(bitset), change_tick)
+        }
+    }
+
+    /// Iterate over all the entities in the given bitset, returning the component of each entity
+    /// if it has one.
+    pub fn iter_with_bitset_optional<'a>(
+        &'a self,
+        components: &'a UntypedComponentStore,
+        bitset: Rc<BitSetVec>,
+    ) -> OptionalComponentBitsetIterator<'a, T> {
+        // SAFE: Constructing `TypedComponentOps` is unsafe and user affirms the type T is valid for
+        // the underlying, untyped data.
+        unsafe {
+            OptionalComponentBitsetIterator::new(components.iter_with_bitset_optional(bitset))
+        }
+    }
+
+    /// Mutably iterate over all the entities in the given bitset, returning the component of each
+    /// entity if it has one.
+    ///
+    /// Each component is marked as changed with the given tick when it's [`Mut`] is mutably
+    /// dereferenced.
+    pub fn iter_mut_with_bitset_optional<'a>(
+        &'a self,
+        components: &'a mut UntypedComponentStore,
+        bitset: Rc<BitSetVec>,
+        change_tick: Tick,
+    ) -> OptionalComponentBitsetIteratorMut<T> {
+        // SAFE: Constructing `TypedComponentOps` is unsafe and user affirms the type T is valid for
+        // the underlying, untyped data.
+        unsafe {
+            OptionalComponentBitsetIteratorMut::new(
+                components.iter_mut_with_bitset_optional(bitset),
+                change_tick,
+            )
+        }
     }
 }
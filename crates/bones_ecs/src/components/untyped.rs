@@ -3,10 +3,59 @@ use crate::prelude::*;
 use aligned_vec::AVec;
 use std::{
     alloc::Layout,
+    mem::MaybeUninit,
     ptr::{self},
     rc::Rc,
 };
 
+/// The type name of the components in stores that were created without one.
+const UNKNOWN_TYPE_NAME: &str = "<unknown>";
+
+/// How the components in an [`UntypedComponentStore`] are laid out in memory.
+///
+/// Both layouts track the entities that have a component with a bitset, so stores with different
+/// layouts are joined in the same way, and systems don't need to know which layout a store uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StorageStrategy {
+    /// The component of each entity is stored at the entity's index.
+    ///
+    /// Lookups by entity are direct, but the storage has room for every entity up to the highest
+    /// entity index, and iteration skips over the entities without a component. This suits
+    /// components that only some entities have.
+    #[default]
+    Sparse,
+    /// The components are packed together, with maps between entity indices and storage slots.
+    ///
+    /// Iteration over all of the components is contiguous, but lookups by entity go through the
+    /// map, and removing a component moves the last component into its slot. This suits
+    /// components that nearly every entity has, such as transforms and sprites.
+    Dense,
+}
+
+/// The maps between entity indices and storage slots of a [`StorageStrategy::Dense`] store.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DenseIndex {
+    /// The storage slot of the component of each entity index.
+    ///
+    /// Only valid for the entity indices that are set in the bitset of the store.
+    pub(crate) slots: Vec<u32>,
+    /// The entity index of the component in each storage slot.
+    pub(crate) indices: Vec<u32>,
+}
+
+impl DenseIndex {
+    /// Add a slot at the end of the storage for the given entity index, returning the slot.
+    fn push(&mut self, index: usize) -> usize {
+        let slot = self.indices.len();
+        if self.slots.len() <= index {
+            self.slots.resize(index + 1, 0);
+        }
+        self.slots[index] = slot as u32;
+        self.indices.push(index as u32);
+        slot
+    }
+}
+
 /// Holds components of a given type indexed by `Entity`.
 ///
 /// We do not check if the given entity is alive here, this should be done using `Entities`.
@@ -17,6 +66,23 @@ pub struct UntypedComponentStore {
     pub(crate) max_id: usize,
     pub(crate) drop_fn: Option<unsafe extern "C" fn(*mut u8)>,
     pub(crate) clone_fn: unsafe extern "C" fn(*const u8, *mut u8),
+    /// The function that compares two components, if one was registered with
+    /// [`set_eq_fn()`][Self::set_eq_fn].
+    pub(crate) eq_fn: Option<unsafe extern "C" fn(*const u8, *const u8) -> bool>,
+    /// Whether every byte of the components is initialized, because they have no padding, which
+    /// is registered with [`set_padding_free()`][Self::set_padding_free].
+    pub(crate) padding_free: bool,
+    /// The change ticks of the components, indexed by entity index.
+    pub(crate) ticks: Vec<ComponentTicks>,
+    /// The counter of the tick that changes made outside of systems are recorded with.
+    pub(crate) change_tick: TickCounter,
+    /// The entities whose components have been removed since the last call to
+    /// [`clear_removed()`][Self::clear_removed].
+    pub(crate) removed: Vec<Entity>,
+    /// The maps between entity indices and storage slots, if the components are stored densely.
+    pub(crate) dense: Option<DenseIndex>,
+    /// The name of the component type, for display in tools.
+    pub(crate) type_name: &'static str,
 }
 
 impl Clone for UntypedComponentStore {
@@ -32,8 +98,9 @@ impl Clone for UntypedComponentStore {
                 // - And our previous pointer is a valid pointer to component data
                 // - And our new pointer is a writable pointer with the same layout
                 unsafe {
-                    let prev_ptr = self.storage.as_ptr().add(i * size);
-                    let new_ptr = new_storage.as_mut_ptr().add(i * size);
+                    let offset = self.slot(i) * size;
+                    let prev_ptr = self.storage.as_ptr().add(offset);
+                    let new_ptr = new_storage.as_mut_ptr().add(offset);
                     (self.clone_fn)(prev_ptr, new_ptr);
                 }
             }
@@ -46,6 +113,13 @@ impl Clone for UntypedComponentStore {
             max_id: self.max_id,
             drop_fn: self.drop_fn,
             clone_fn: self.clone_fn,
+            eq_fn: self.eq_fn,
+            padding_free: self.padding_free,
+            ticks: self.ticks.clone(),
+            change_tick: self.change_tick.clone(),
+            removed: self.removed.clone(),
+            dense: self.dense.clone(),
+            type_name: self.type_name,
         }
     }
 }
@@ -57,14 +131,14 @@ impl Drop for UntypedComponentStore {
             if size < 1 {
                 return;
             }
-            for i in 0..(self.storage.len() / size) {
+            for i in 0..self.max_id {
                 if self.bitset.bit_test(i) {
+                    let ptr = self.ptr_mut(i);
                     // SAFE: constructing an UntypedComponent store is unsafe, and the user affirms
                     // that clone_fn will not do anything unsound.
                     //
                     // And our pointer is valid.
                     unsafe {
-                        let ptr = self.storage.as_mut_ptr().add(i * size);
                         drop_fn(ptr);
                     }
                 }
@@ -96,6 +170,13 @@ impl UntypedComponentStore {
             max_id: 0,
             clone_fn,
             drop_fn,
+            eq_fn: None,
+            padding_free: false,
+            ticks: Vec::new(),
+            change_tick: TickCounter::default(),
+            removed: Vec::new(),
+            dense: None,
+            type_name: UNKNOWN_TYPE_NAME,
         }
     }
 
@@ -110,6 +191,13 @@ impl UntypedComponentStore {
             max_id: 0,
             clone_fn: T::raw_clone,
             drop_fn: Some(T::raw_drop),
+            eq_fn: None,
+            padding_free: false,
+            ticks: Vec::new(),
+            change_tick: TickCounter::default(),
+            removed: Vec::new(),
+            dense: None,
+            type_name: std::any::type_name::<T>(),
         }
     }
 
@@ -118,39 +206,432 @@ impl UntypedComponentStore {
         self.layout
     }
 
+    /// Get the name of the component type.
+    ///
+    /// This is the Rust type name for stores created with [`for_type()`][Self::for_type], and
+    /// `"<unknown>"` for stores created with [`new()`][Self::new], unless it was set with
+    /// [`set_type_name()`][Self::set_type_name].
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Set the name of the component type, that tools display for the components in the store.
+    pub fn set_type_name(&mut self, type_name: &'static str) {
+        self.type_name = type_name;
+    }
+
+    /// Set the function that compares two components, which [`WorldSnapshot::diff()`] uses to
+    /// find the components that changed.
+    ///
+    /// Use [`RawEq::raw_eq`] to compare the components with their [`PartialEq`] implementation.
+    ///
+    /// # Safety
+    ///
+    /// The `eq_fn` must not do anything unsound when given two valid pointers to components of
+    /// the store.
+    pub unsafe fn set_eq_fn(&mut self, eq_fn: unsafe extern "C" fn(*const u8, *const u8) -> bool) {
+        self.eq_fn = Some(eq_fn);
+    }
+
+    /// Returns `true` if the components have been registered as having no padding, so that every
+    /// one of their bytes is initialized.
+    pub fn is_padding_free(&self) -> bool {
+        self.padding_free
+    }
+
+    /// Register the components as having no padding, so that they can be compared byte-for-byte
+    /// when no [comparison function][Self::set_eq_fn] was set.
+    ///
+    /// # Safety
+    ///
+    /// Every byte of every value of the component type must be initialized, like for types that
+    /// implement [`bytemuck::NoUninit`].
+    pub unsafe fn set_padding_free(&mut self) {
+        self.padding_free = true;
+    }
+
+    /// Returns `true` if the components of both stores at the given entity index are equal.
+    ///
+    /// The components are compared with the [comparison function][Self::set_eq_fn] of the store,
+    /// or byte-for-byte if the components are [padding-free][Self::is_padding_free]. Otherwise
+    /// they can't be compared, and are never equal.
+    ///
+    /// Both stores must be for the same component type, and have a component at the index.
+    pub(crate) fn components_eq(&self, other: &Self, index: usize) -> bool {
+        let (ptr, other_ptr) = (self.ptr(index), other.ptr(index));
+        if let Some(eq_fn) = self.eq_fn.or(other.eq_fn) {
+            // SAFE: The store was created with a sound comparison function, and both pointers
+            // are to valid components of the same type.
+            unsafe { eq_fn(ptr, other_ptr) }
+        } else if self.padding_free || other.padding_free {
+            let size = self.layout.size();
+            // SAFE: The components are padding-free, so all of their bytes are initialized.
+            unsafe {
+                std::slice::from_raw_parts(ptr, size) == std::slice::from_raw_parts(other_ptr, size)
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Get the [`StorageStrategy`] that the components are laid out with.
+    pub fn storage_strategy(&self) -> StorageStrategy {
+        match self.dense {
+            Some(_) => StorageStrategy::Dense,
+            None => StorageStrategy::Sparse,
+        }
+    }
+
+    /// Move the components to the layout of the given [`StorageStrategy`].
+    ///
+    /// This is cheap for an empty store, and needs to move every component otherwise.
+    pub fn set_storage_strategy(&mut self, strategy: StorageStrategy) {
+        if self.storage_strategy() == strategy {
+            return;
+        }
+
+        let size = self.layout.size();
+        let mut dense = match strategy {
+            StorageStrategy::Sparse => None,
+            StorageStrategy::Dense => Some(DenseIndex::default()),
+        };
+        let mut storage = AVec::with_capacity(self.layout.align(), self.storage.capacity());
+        for index in (0..self.max_id).filter(|&i| self.bitset.bit_test(i)) {
+            let slot = match &mut dense {
+                Some(dense) => dense.push(index),
+                None => index,
+            };
+            while storage.len() < (slot + 1) * size {
+                storage.push(0);
+            }
+            // The components are moved byte-for-byte, and the old storage is dropped without
+            // dropping the components.
+            let offset = self.slot(index) * size;
+            storage[slot * size..(slot + 1) * size]
+                .copy_from_slice(&self.storage[offset..offset + size]);
+        }
+        self.storage = storage;
+        self.dense = dense;
+    }
+
+    /// Get the storage slot of the component at the given entity index.
+    ///
+    /// The entity index must have a component in the store.
+    #[inline]
+    pub(crate) fn slot(&self, index: usize) -> usize {
+        match &self.dense {
+            Some(dense) => dense.slots[index] as usize,
+            None => index,
+        }
+    }
+
+    /// Get a pointer to the component at the given entity index.
+    ///
+    /// The entity index must have a component in the store.
+    #[inline]
+    pub(crate) fn ptr(&self, index: usize) -> *const u8 {
+        // SAFE: The slot of a component is always inside of the storage.
+        unsafe {
+            self.storage
+                .as_ptr()
+                .add(self.slot(index) * self.layout.size())
+        }
+    }
+
+    /// Get a mutable pointer to the component at the given entity index.
+    ///
+    /// The entity index must have a component in the store.
+    #[inline]
+    pub(crate) fn ptr_mut(&mut self, index: usize) -> *mut u8 {
+        let offset = self.slot(index) * self.layout.size();
+        // SAFE: The slot of a component is always inside of the storage.
+        unsafe { self.storage.as_mut_ptr().add(offset) }
+    }
+
+    /// Make room in the storage for a new component at the given entity index.
+    fn allocate_slot(&mut self, index: usize) {
+        let slot = match &mut self.dense {
+            Some(dense) => dense.push(index),
+            None => index,
+        };
+        self.allocate_enough(slot * self.layout.size());
+    }
+
+    /// Free the storage slot of the component at the given entity index, after the component was
+    /// moved out or dropped.
+    ///
+    /// Dense stores move the component in the last slot into the freed slot, so that the
+    /// components stay packed.
+    fn free_slot(&mut self, index: usize) {
+        let size = self.layout.size();
+        let Some(dense) = &mut self.dense else {
+            return;
+        };
+        let slot = dense.slots[index] as usize;
+        let last = dense.indices.len() - 1;
+        if slot != last {
+            let moved = dense.indices[last];
+            dense.indices[slot] = moved;
+            dense.slots[moved as usize] = slot as u32;
+            // SAFE: Both slots are inside of the storage, and they are different slots.
+            unsafe {
+                let ptr = self.storage.as_mut_ptr();
+                ptr::copy_nonoverlapping(ptr.add(last * size), ptr.add(slot * size), size);
+            }
+        }
+        dense.indices.pop();
+    }
+
     /// Returns true if the entity already had a component of this type.
     ///
     /// If true is returned, the previous value of the pointer will be written to `data`.
     ///
+    /// The component is marked as changed, and as added if the entity didn't already have one.
+    ///
     /// # Safety
     ///
     /// The data pointer must be valid for reading and writing objects with the layout that the
     /// [`UntypedComponentStore`] was created with.
     pub unsafe fn insert(&mut self, entity: Entity, data: *mut u8) -> bool {
-        let size = self.layout.size();
+        self.insert_with_tick(entity, data, self.change_tick.get())
+    }
 
+    /// Same as [`insert()`][Self::insert], but records the change with the given tick.
+    ///
+    /// # Safety
+    ///
+    /// See [`insert()`][Self::insert].
+    pub(crate) unsafe fn insert_with_tick(
+        &mut self,
+        entity: Entity,
+        data: *mut u8,
+        change_tick: Tick,
+    ) -> bool {
+        let size = self.layout.size();
         let index = entity.index() as usize;
-        self.allocate_enough(index * size);
-        let ptr = self.storage.as_mut_ptr().add(index * size);
 
         // If the component already exists on the entity
-        if self.bitset.bit_test(entity.index() as usize) {
+        if self.bitset.bit_test(index) {
             // Swap the data with the data already there
+            let ptr = self.ptr_mut(index);
             ptr::swap_nonoverlapping(ptr, data, size);
+            self.ticks[index].changed = change_tick;
 
             // There was already a component of this type
             true
         } else {
             self.max_id = self.max_id.max(index + 1);
-            self.allocate_enough(index * size);
+            self.allocate_slot(index);
             self.bitset.bit_set(index);
+            let ptr = self.ptr_mut(index);
             ptr::swap_nonoverlapping(ptr, data, size);
+            if self.ticks.len() <= index {
+                self.ticks.resize(index + 1, ComponentTicks::default());
+            }
+            self.ticks[index] = ComponentTicks::new(change_tick);
 
             // There was not already a component of this type
             false
         }
     }
 
+    /// Insert a clone of the component that `src` has in `other`, if it has one, for the entity
+    /// `dst`, replacing the component that `dst` already had in this store.
+    ///
+    /// # Safety
+    ///
+    /// `other` must store the same type of component as this store.
+    pub(crate) unsafe fn insert_clone_from(
+        &mut self,
+        other: &UntypedComponentStore,
+        src: Entity,
+        dst: Entity,
+    ) {
+        let Some(src) = other.get(src) else {
+            return;
+        };
+        let ptr = self.prepare_clone_target(dst.index() as usize);
+
+        // SAFE: The source is a valid component of the same type, and the storage is aligned for
+        // the component's layout.
+        (self.clone_fn)(src, ptr);
+    }
+
+    /// Insert a clone of the component that `src` has, if it has one, for the entity `dst`,
+    /// replacing the component that `dst` already had.
+    ///
+    /// Returns `true` if `src` had a component to clone.
+    pub(crate) fn clone_component(&mut self, src: Entity, dst: Entity) -> bool {
+        let src_index = src.index() as usize;
+        if !self.bitset.bit_test(src_index) {
+            return false;
+        }
+        if src_index == dst.index() as usize {
+            return true;
+        }
+
+        let ptr = self.prepare_clone_target(dst.index() as usize);
+        // SAFE: The source and target slots are different, and both are inside of the storage,
+        // which was already extended to fit the target. The source slot holds a valid component,
+        // and the clone function is the one this store was created with.
+        unsafe {
+            (self.clone_fn)(self.ptr(src_index), ptr);
+        }
+        true
+    }
+
+    /// Make room for a cloned component at `index`, dropping the component that was already
+    /// there, and updating the bitset and change ticks.
+    ///
+    /// Returns a pointer to the slot that the clone must be written to.
+    fn prepare_clone_target(&mut self, index: usize) -> *mut u8 {
+        if self.bitset.bit_test(index) {
+            if let Some(drop_fn) = self.drop_fn {
+                // SAFE: The slot holds a valid component, that is about to be overwritten.
+                unsafe { drop_fn(self.ptr_mut(index)) };
+            }
+            self.ticks[index].changed = self.change_tick.get();
+        } else {
+            self.max_id = self.max_id.max(index + 1);
+            self.allocate_slot(index);
+            self.bitset.bit_set(index);
+            if self.ticks.len() <= index {
+                self.ticks.resize(index + 1, ComponentTicks::default());
+            }
+            self.ticks[index] = ComponentTicks::new(self.change_tick.get());
+        }
+
+        self.ptr_mut(index)
+    }
+
+    /// Returns the number of components in the store.
+    pub fn len(&self) -> usize {
+        match &self.dense {
+            Some(dense) => dense.indices.len(),
+            None => (0..self.max_id)
+                .filter(|&i| self.bitset.bit_test(i))
+                .count(),
+        }
+    }
+
+    /// Returns `true` if there are no components in the store.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of components that the store has room for without reallocating.
+    ///
+    /// Zero-sized components never need room, so this is [`usize::MAX`] for them.
+    pub fn capacity(&self) -> usize {
+        match self.layout.size() {
+            0 => usize::MAX,
+            size => self.storage.capacity() / size,
+        }
+    }
+
+    /// Returns the number of bytes allocated by the store.
+    ///
+    /// This includes the component data, change ticks, and the maps of dense stores, but not heap
+    /// memory owned by the components themselves, or the bitset, which has a fixed size.
+    pub fn allocated_bytes(&self) -> usize {
+        let dense = self.dense.as_ref().map_or(0, |dense| {
+            (dense.slots.capacity() + dense.indices.capacity()) * std::mem::size_of::<u32>()
+        });
+        self.storage.capacity()
+            + self.ticks.capacity() * std::mem::size_of::<ComponentTicks>()
+            + self.removed.capacity() * std::mem::size_of::<Entity>()
+            + dense
+    }
+
+    /// Free the memory that isn't needed for the components in the store.
+    ///
+    /// [`Sparse`][StorageStrategy::Sparse] storage is indexed by entity index, so the slots of
+    /// entities with lower indices than the highest one with a component are still allocated,
+    /// even if they are empty.
+    pub fn shrink_to_fit(&mut self) {
+        let len = (0..self.max_id)
+            .rev()
+            .find(|&i| self.bitset.bit_test(i))
+            .map_or(0, |i| i + 1);
+        let slots = match &mut self.dense {
+            Some(dense) => {
+                dense.slots.truncate(len);
+                dense.slots.shrink_to_fit();
+                dense.indices.shrink_to_fit();
+                dense.indices.len()
+            }
+            None => len,
+        };
+        let size = self.layout.size();
+        let bytes = (slots * size).min(self.storage.len());
+
+        // Copy the components into an allocation of the exact size. The components are moved
+        // byte-for-byte, and the old storage is dropped without dropping the components.
+        let mut storage = AVec::with_capacity(self.layout.align(), bytes);
+        for &byte in &self.storage[..bytes] {
+            storage.push(byte);
+        }
+        self.storage = storage;
+        self.max_id = len;
+        self.ticks.truncate(len);
+        self.ticks.shrink_to_fit();
+        self.removed.shrink_to_fit();
+    }
+
+    /// Remove all of the components, keeping the memory allocated for them.
+    ///
+    /// The bitset of the store is scanned a word at a time, so this takes time proportional to the
+    /// number of components, not to the capacity of the store. The components aren't recorded as
+    /// [removed][Self::removed].
+    pub fn clear(&mut self) {
+        let mut start = 0;
+        while let Some(index) = self.bitset.next_set_bit(start, self.max_id) {
+            if let Some(drop_fn) = self.drop_fn {
+                // SAFE: construcing `UntypedComponentStore` asserts the soundess of the drop_fn,
+                // and the entity has a component, so the pointer is valid for the component type.
+                unsafe { drop_fn(self.ptr_mut(index)) };
+            }
+            self.bitset.bit_reset(index);
+            start = index + 1;
+        }
+        if let Some(dense) = &mut self.dense {
+            dense.indices.clear();
+        }
+        self.max_id = 0;
+    }
+
+    /// Remove all of the components and free their memory, without recording them as
+    /// [removed][Self::removed].
+    pub(crate) fn reset(&mut self) {
+        let change_tick = self.change_tick.clone();
+        *self = self.empty_copy();
+        self.change_tick = change_tick;
+    }
+
+    /// Create an empty store for the same type of components.
+    pub(crate) fn empty_copy(&self) -> Self {
+        // Safe: The functions are the ones that this store was already created with.
+        let mut store = unsafe { Self::new(self.layout, self.clone_fn, self.drop_fn) };
+        store.set_storage_strategy(self.storage_strategy());
+        store.type_name = self.type_name;
+        store.eq_fn = self.eq_fn;
+        store.padding_free = self.padding_free;
+        store
+    }
+
+    /// Get the raw bytes of the component at the given entity index, if there is one.
+    ///
+    /// The padding bytes of the component may be uninitialized, so the bytes are only
+    /// [`MaybeUninit`].
+    pub(crate) fn bytes(&self, index: usize) -> Option<&[MaybeUninit<u8>]> {
+        let size = self.layout.size();
+        self.bitset.bit_test(index).then(|| {
+            // SAFE: The slot of the component is `size` bytes long, inside of the storage, and
+            // `MaybeUninit<u8>` is valid for any byte, initialized or not.
+            unsafe { std::slice::from_raw_parts(self.ptr(index).cast(), size) }
+        })
+    }
+
     /// Ensures that we have the vec filled at least until the `until` variable.
     ///
     /// Usually, set this to `entity.index`.
@@ -169,33 +650,167 @@ impl UntypedComponentStore {
         let index = entity.index() as usize;
 
         if self.bitset.bit_test(index) {
-            let size = self.layout.size();
-            // SAFE: we've already validated that the contents of storage is valid for type T.
-            unsafe {
-                let ptr = self.storage.as_ptr().add(index * size);
-                Some(ptr)
-            }
+            Some(self.ptr(index))
         } else {
             None
         }
     }
 
     /// Get a mutable pointer to the component for the given [`Entity`]
+    ///
+    /// The component is marked as changed, because writes through the pointer can't be tracked.
     pub fn get_mut(&mut self, entity: Entity) -> Option<*mut u8> {
         let index = entity.index() as usize;
 
         if self.bitset.bit_test(index) {
-            let size = self.layout.size();
-            // SAFE: we've already validated that the contents of storage is valid for type T.
-            unsafe {
-                let ptr = self.storage.as_mut_ptr().add(index * size);
-                Some(ptr)
-            }
+            self.ticks[index].changed = self.change_tick.get();
+            Some(self.ptr_mut(index))
         } else {
             None
         }
     }
 
+    /// Get mutable pointers to the components of multiple [`Entity`]s at once.
+    ///
+    /// The components are marked as changed, because writes through the pointers can't be tracked.
+    ///
+    /// # Errors
+    ///
+    /// Errors if one of the entities doesn't have a component, or if the same entity is passed
+    /// more than once, so that the returned pointers never alias.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        entities: [Entity; N],
+    ) -> Result<[*mut u8; N], GetManyMutError> {
+        self.validate_many(&entities)?;
+
+        let change_tick = self.change_tick.get();
+        Ok(std::array::from_fn(|i| {
+            let index = entities[i].index() as usize;
+            self.ticks[index].changed = change_tick;
+            // We've validated that every entity has a component, so the slot is in bounds.
+            self.ptr_mut(index)
+        }))
+    }
+
+    /// Make sure that all of the entities have a component, and that no entity index is repeated.
+    pub(crate) fn validate_many(&self, entities: &[Entity]) -> Result<(), GetManyMutError> {
+        for (i, &entity) in entities.iter().enumerate() {
+            if !self.bitset.bit_test(entity.index() as usize) {
+                return Err(GetManyMutError::Missing(entity));
+            }
+            // Entities with different generations still share the same component slot.
+            if entities[..i].iter().any(|x| x.index() == entity.index()) {
+                return Err(GetManyMutError::Duplicate(entity));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the [`Entity`] has a component in this store.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.bitset.bit_test(entity.index() as usize)
+    }
+
+    /// Returns `true` if the components have a drop function, which is called when they are
+    /// removed or replaced.
+    ///
+    /// The bytes of components with a drop function own resources, such as heap allocations, so
+    /// they must never be duplicated with [`insert_raw()`][Self::insert_raw].
+    pub fn needs_drop(&self) -> bool {
+        self.drop_fn.is_some()
+    }
+
+    /// Insert a component for the given [`Entity`] by copying its bytes, dropping the component
+    /// that the entity already had in the store, if any.
+    ///
+    /// This is meant for scripting and other layers that don't know the Rust type of the
+    /// component. The bytes don't have to be aligned, they are copied into aligned storage.
+    ///
+    /// The component is marked as changed, and as added if the entity didn't already have one.
+    /// Returns `true` if the entity already had a component.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the length of `data` isn't the size of the [layout][Self::layout] of the store,
+    /// in which case nothing is inserted.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be a valid value of the component type. If the store
+    /// [needs drop][Self::needs_drop], the store takes ownership of the value, so the bytes must
+    /// not be dropped, or inserted again, anywhere else.
+    pub unsafe fn insert_raw(
+        &mut self,
+        entity: Entity,
+        data: &[u8],
+    ) -> Result<bool, ComponentSizeError> {
+        let size = self.layout.size();
+        if data.len() != size {
+            return Err(ComponentSizeError {
+                type_name: self.type_name,
+                expected: size,
+                found: data.len(),
+            });
+        }
+        let index = entity.index() as usize;
+
+        if self.bitset.bit_test(index) {
+            let ptr = self.ptr_mut(index);
+            if let Some(drop_fn) = self.drop_fn {
+                // SAFE: The pointer is to a valid component, which is overwritten right away.
+                drop_fn(ptr);
+            }
+            // SAFE: The data has the size of a component, and can't overlap the storage, which is
+            // mutably borrowed.
+            ptr::copy_nonoverlapping(data.as_ptr(), ptr, size);
+            self.ticks[index].changed = self.change_tick.get();
+            Ok(true)
+        } else {
+            // The previous contents of the slot are swapped into the copy, and discarded.
+            let mut data = data.to_vec();
+            self.insert(entity, data.as_mut_ptr());
+            Ok(false)
+        }
+    }
+
+    /// Get the bytes of the component for the given [`Entity`], if it has one.
+    ///
+    /// The bytes are aligned for the [layout][Self::layout] of the store, so they may be cast to
+    /// the component type. Padding bytes in the component may be uninitialized, so the bytes are
+    /// only [`MaybeUninit`]. They are all initialized if the store is
+    /// [padding-free][Self::is_padding_free].
+    pub fn get_raw(&self, entity: Entity) -> Option<&[MaybeUninit<u8>]> {
+        self.bytes(entity.index() as usize)
+    }
+
+    /// Get the bytes of the component for the given [`Entity`] mutably, if it has one.
+    ///
+    /// The component is marked as changed, because writes to the bytes can't be tracked. The
+    /// bytes may be uninitialized, like for [`get_raw()`][Self::get_raw].
+    ///
+    /// # Safety
+    ///
+    /// Like with [`insert_raw()`][Self::insert_raw], the bytes must be left as a valid value of
+    /// the component type.
+    pub unsafe fn get_raw_mut(&mut self, entity: Entity) -> Option<&mut [MaybeUninit<u8>]> {
+        let size = self.layout.size();
+        let ptr = self.get_mut(entity)?;
+        // SAFE: The pointer is to the component's slot, which is `size` bytes long, and borrowed
+        // mutably along with the store. `MaybeUninit<u8>` is valid for any byte.
+        Some(std::slice::from_raw_parts_mut(ptr.cast(), size))
+    }
+
+    /// Remove the component of the given [`Entity`], dropping it with the drop function of the
+    /// store.
+    ///
+    /// Returns `true` if the entity had a component.
+    pub fn remove_raw(&mut self, entity: Entity) -> bool {
+        // SAFE: No output pointer is given, so the component is dropped in place.
+        unsafe { self.remove(entity, None) }
+    }
+
     /// If there is a previous value, `true` will be returned.
     ///
     /// If `out` is set, the previous value will be written to it.
@@ -208,10 +823,9 @@ impl UntypedComponentStore {
         let size = self.layout.size();
 
         if self.bitset.bit_test(index) {
+            let ptr = self.ptr_mut(index);
             self.bitset.bit_reset(index);
 
-            let ptr = self.storage.as_mut_ptr().add(index * size);
-
             if let Some(out) = out {
                 // SAFE: user asserts `out` is non-overlapping
                 ptr::copy_nonoverlapping(ptr, out, size);
@@ -221,6 +835,8 @@ impl UntypedComponentStore {
                 // And ptr is a valid pointer to the component type.
                 drop_fn(ptr);
             }
+            self.free_slot(index);
+            self.removed.push(entity);
 
             // Found previous component
             true
@@ -233,20 +849,30 @@ impl UntypedComponentStore {
     /// Iterates immutably over all components of this type.
     ///
     /// Very fast but doesn't allow joining with other component types.
+    ///
+    /// Sparse stores yield the components in ascending order of entity index. Dense stores yield
+    /// them in storage order, which depends on the order that the components were inserted and
+    /// removed in. That order is kept by [snapshots][World::snapshot], but may be different after
+    /// [`World::apply_diff()`], so join over the store with [`Entities::iter_with()`] when the
+    /// order must be the same everywhere.
     pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
         let Self {
             storage: components,
             bitset,
             layout,
+            dense,
             ..
         } = self;
 
         if layout.size() > 0 {
+            // Dense storage only contains components, so it doesn't need to be filtered.
+            let dense_len = dense.as_ref().map(|dense| dense.indices.len());
             either::Left(
                 components
                     .chunks(layout.size())
+                    .take(dense_len.unwrap_or(usize::MAX))
                     .enumerate()
-                    .filter(move |(i, _)| bitset.bit_test(*i))
+                    .filter(move |(i, _)| dense_len.is_some() || bitset.bit_test(*i))
                     .map(|(_i, x)| x),
             )
         } else {
@@ -274,20 +900,27 @@ impl UntypedComponentStore {
     /// Iterates mutably over all components of this type.
     ///
     /// Very fast but doesn't allow joining with other component types.
+    ///
+    /// The components are not marked as changed, use [`set_changed()`][Self::set_changed] for the
+    /// components that are modified.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
         let Self {
             storage,
             bitset,
             layout,
+            dense,
             ..
         } = self;
 
         if layout.size() > 0 {
+            // Dense storage only contains components, so it doesn't need to be filtered.
+            let dense_len = dense.as_ref().map(|dense| dense.indices.len());
             either::Left(
                 storage
                     .chunks_mut(layout.size())
+                    .take(dense_len.unwrap_or(usize::MAX))
                     .enumerate()
-                    .filter(move |(i, _)| bitset.bit_test(*i))
+                    .filter(move |(i, _)| dense_len.is_some() || bitset.bit_test(*i))
                     .map(|(_i, x)| x),
             )
         } else {
@@ -327,6 +960,9 @@ impl UntypedComponentStore {
     /// entities.
     ///
     /// Slower than `iter()` but allows joining between multiple component types.
+    ///
+    /// The components are not marked as changed, use [`set_changed()`][Self::set_changed] for the
+    /// components that are modified.
     pub fn iter_mut_with_bitset(
         &mut self,
         bitset: Rc<BitSetVec>,
@@ -338,6 +974,38 @@ impl UntypedComponentStore {
         }
     }
 
+    /// Iterates immutably over the entities in `bitset`, returning the component of each entity if
+    /// it has one, and `None` otherwise.
+    ///
+    /// Unlike [`iter_with_bitset()`][Self::iter_with_bitset], this yields an item for every entity
+    /// in the bitset, so it can be joined with other component types without constraining them.
+    pub fn iter_with_bitset_optional(
+        &self,
+        bitset: Rc<BitSetVec>,
+    ) -> UntypedOptionalComponentBitsetIterator {
+        UntypedOptionalComponentBitsetIterator {
+            current_id: 0,
+            components: self,
+            bitset,
+        }
+    }
+
+    /// Iterates mutably over the entities in `bitset`, returning the component of each entity if
+    /// it has one, and `None` otherwise.
+    ///
+    /// The components are not marked as changed, use [`set_changed()`][Self::set_changed] for the
+    /// components that are modified.
+    pub fn iter_mut_with_bitset_optional(
+        &mut self,
+        bitset: Rc<BitSetVec>,
+    ) -> UntypedOptionalComponentBitsetIteratorMut {
+        UntypedOptionalComponentBitsetIteratorMut {
+            current_id: 0,
+            components: self,
+            bitset,
+        }
+    }
+
     /// Returns the bitset indicating which entity indices have a component associated to them.
     ///
     /// Useful to build conditions between multiple `Components`' bitsets.
@@ -349,4 +1017,64 @@ impl UntypedComponentStore {
     pub fn bitset(&self) -> &BitSetVec {
         &self.bitset
     }
+    /// Get the change ticks of the component for the given [`Entity`], if it has one.
+    pub fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        let index = entity.index() as usize;
+        self.bitset.bit_test(index).then(|| self.ticks[index])
+    }
+
+    /// Get the entities whose components have been removed since the last call to
+    /// [`clear_removed()`][Self::clear_removed].
+    ///
+    /// An entity is listed once for every time that its component was removed, including when
+    /// the entity was killed and its components were removed by [`World::maintain()`].
+    pub fn removed(&self) -> &[Entity] {
+        &self.removed
+    }
+
+    /// Clear the list of [removed][Self::removed] components.
+    pub fn clear_removed(&mut self) {
+        self.removed.clear();
+    }
+
+    /// Mark the component of the given [`Entity`] as changed, if it has one.
+    pub fn set_changed(&mut self, entity: Entity) {
+        let index = entity.index() as usize;
+        if self.bitset.bit_test(index) {
+            self.ticks[index].changed = self.change_tick.get();
+        }
+    }
+
+    /// Get the tick that changes made outside of systems are currently recorded with.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Share the tick counter of the [`World`] that the store is in.
+    pub(crate) fn set_tick_counter(&mut self, counter: TickCounter) {
+        self.change_tick = counter;
+    }
+
+    /// Clamp the ticks of all of the components so that they are no older than
+    /// [`MAX_CHANGE_AGE`] compared to `this_run`.
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        for ticks in &mut self.ticks {
+            ticks.check(this_run);
+        }
+    }
+
+    /// Remove the entities from `bitset` that don't have a component, or whose component's ticks
+    /// don't match the `filter`.
+    pub(crate) fn retain_ticks(
+        &self,
+        bitset: &mut BitSetVec,
+        filter: impl Fn(&ComponentTicks) -> bool,
+    ) {
+        bitset.bit_and(&self.bitset);
+        for (i, ticks) in self.ticks.iter().enumerate().take(self.max_id) {
+            if bitset.bit_test(i) && !filter(ticks) {
+                bitset.bit_reset(i);
+            }
+        }
+    }
 }
@@ -0,0 +1,154 @@
+//! Borrow tracking, for explaining borrow conflicts between systems.
+//!
+//! When the `debug` feature is enabled, every borrow of a resource or component store records the
+//! system that made it, so that a conflicting borrow panics with the names of both systems,
+//! instead of the generic `atomic_refcell` error.
+
+use std::{borrow::Cow, cell::RefCell, panic::Location, sync::Mutex};
+
+use fxhash::FxHashMap;
+
+use crate::prelude::*;
+
+/// A system that is running, and where it was created.
+#[derive(Clone)]
+struct SystemInfo {
+    name: Cow<'static, str>,
+    location: &'static Location<'static>,
+}
+
+/// The most recent borrow of a cell.
+#[derive(Clone)]
+struct BorrowInfo {
+    /// The system that borrowed the cell, or [`None`] if it wasn't borrowed from a system.
+    system: Option<SystemInfo>,
+    mutable: bool,
+}
+
+thread_local! {
+    /// The system that is currently running on this thread.
+    static RUNNING_SYSTEM: RefCell<Option<SystemInfo>> = RefCell::new(None);
+}
+
+/// The most recent borrow of every cell, by the address of the cell.
+static BORROWS: Mutex<Option<FxHashMap<usize, BorrowInfo>>> = Mutex::new(None);
+
+/// Guard that marks a system as running on the current thread, until it is dropped.
+pub(crate) struct RunningSystem {
+    previous: Option<SystemInfo>,
+}
+
+impl RunningSystem {
+    /// Mark the system as running on the current thread.
+    pub(crate) fn enter(name: &Cow<'static, str>, location: &'static Location<'static>) -> Self {
+        let name = name.clone();
+        let previous =
+            RUNNING_SYSTEM.with(|running| running.replace(Some(SystemInfo { name, location })));
+        Self { previous }
+    }
+}
+
+impl Drop for RunningSystem {
+    fn drop(&mut self) {
+        RUNNING_SYSTEM.with(|running| *running.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Borrow the cell, panicking with the name of the system that holds the conflicting borrow if it
+/// is already mutably borrowed.
+///
+/// `requested` is the name of the parameter that the borrow is made for, such as `Res<Thing>`.
+pub(crate) fn borrow<'a, T>(
+    cell: &'a AtomicRefCell<T>,
+    requested: impl FnOnce() -> String,
+) -> AtomicRef<'a, T> {
+    match cell.try_borrow() {
+        Ok(borrow) => {
+            record(address(cell), false);
+            borrow
+        }
+        Err(_) => borrow_failed(address(cell), requested()),
+    }
+}
+
+/// Mutably borrow the cell, panicking with the name of the system that holds the conflicting
+/// borrow if it is already borrowed.
+///
+/// `requested` is the name of the parameter that the borrow is made for, such as `ResMut<Thing>`.
+pub(crate) fn borrow_mut<'a, T>(
+    cell: &'a AtomicRefCell<T>,
+    requested: impl FnOnce() -> String,
+) -> AtomicRefMut<'a, T> {
+    match cell.try_borrow_mut() {
+        Ok(borrow) => {
+            record(address(cell), true);
+            borrow
+        }
+        Err(_) => borrow_failed(address(cell), requested()),
+    }
+}
+
+fn address<T>(cell: &AtomicRefCell<T>) -> usize {
+    cell as *const AtomicRefCell<T> as usize
+}
+
+fn record(cell: usize, mutable: bool) {
+    let system = RUNNING_SYSTEM.with(|running| running.borrow().clone());
+    let mut borrows = BORROWS.lock().unwrap_or_else(|e| e.into_inner());
+    borrows
+        .get_or_insert_with(default)
+        .insert(cell, BorrowInfo { system, mutable });
+}
+
+fn borrow_failed(cell: usize, requested: String) -> ! {
+    let requester = match RUNNING_SYSTEM.with(|running| running.borrow().clone()) {
+        Some(system) => format!("`{}`", system.name),
+        None => "code outside of a system".into(),
+    };
+    let holder = BORROWS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|borrows| borrows.get(&cell).cloned());
+
+    match holder {
+        Some(BorrowInfo { system, mutable }) => {
+            let holder = match system {
+                Some(system) => format!("`{}` (at {})", system.name, system.location),
+                None => "code outside of a system".into(),
+            };
+            let mutably = if mutable { "mutably " } else { "" };
+            panic!(
+                "`{requested}` requested by {requester} but already {mutably}borrowed by {holder}"
+            )
+        }
+        None => panic!("`{requested}` requested by {requester} but already borrowed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GQN3C8V0M6RJ2YW5KD9HBE7T"]
+    struct Thing;
+
+    #[test]
+    #[should_panic(
+        expected = "requested by `apply_velocity` but already mutably borrowed by `physics_step` (at"
+    )]
+    fn conflicting_borrow_names_systems() {
+        let mut world = World::new();
+        world.resources.insert(Thing);
+
+        let mut apply_velocity = (|_thing: ResMut<Thing>| ()).named("apply_velocity");
+        let physics_step = (move |world: &World| {
+            let thing = world.resources.get::<Thing>();
+            let _thing = thing.borrow_mut();
+            apply_velocity.run(world).unwrap();
+        })
+        .named("physics_step");
+        world.run_system(physics_step).unwrap();
+    }
+}
@@ -1,9 +1,23 @@
 //! [`Entity`] implementation, storage, and interation.
 
-use std::rc::Rc;
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::prelude::*;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod query;
+pub use query::*;
+
+#[cfg(feature = "parallel")]
+mod par_iter;
+#[cfg(feature = "parallel")]
+pub use par_iter::*;
+
 /// An entity index.
 ///
 /// They are created using the `Entities` struct. They are used as indices with `Components`
@@ -53,6 +67,83 @@ pub struct Entities {
     /// helps to know if we should directly append after next_id or if we should look through the
     /// bitset.
     has_deleted: bool,
+    /// The number of entities that have been reserved after next_id, but not created yet.
+    reserved: ReservedCount,
+}
+
+/// The serialized form of [`Entities`].
+///
+/// Only the generations of the entity indices that have been used are saved.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct EntitiesData {
+    generations: Vec<u32>,
+    alive: Vec<u32>,
+    killed: Vec<Entity>,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Entities {
+    /// Serialize the entities, including the generations of dead entities, so that entity ids line
+    /// up after they are deserialized.
+    ///
+    /// Entities that have been [reserved][Entities::reserve] but not created yet are not saved.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EntitiesData {
+            generations: self.generation[..self.next_id].to_vec(),
+            alive: self
+                .iter_with_bitset(&self.alive)
+                .map(|entity| entity.index())
+                .collect(),
+            killed: self.killed.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Entities {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let data = EntitiesData::deserialize(deserializer)?;
+        if data.generations.len() > BITSET_SIZE {
+            return Err(D::Error::custom(format!(
+                "Can't load {} entities, the maximum is {}",
+                data.generations.len(),
+                BITSET_SIZE
+            )));
+        }
+
+        let mut entities = Entities::default();
+        entities.next_id = data.generations.len();
+        entities.generation[..entities.next_id].copy_from_slice(&data.generations);
+        for index in data.alive {
+            let index = index as usize;
+            if index >= entities.next_id {
+                return Err(D::Error::custom(format!(
+                    "Alive entity index {index} is out of bounds"
+                )));
+            }
+            entities.alive.bit_set(index);
+        }
+        entities.has_deleted =
+            entities.iter_with_bitset(&entities.alive).count() < entities.next_id;
+        entities.killed = data.killed;
+
+        Ok(entities)
+    }
+}
+
+/// Atomic counter for reserved entities, so that entities can be reserved without mutable access
+/// to [`Entities`].
+#[derive(Default)]
+struct ReservedCount(AtomicUsize);
+
+impl Clone for ReservedCount {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.0.load(Ordering::Acquire)))
+    }
 }
 
 impl Default for Entities {
@@ -63,6 +154,7 @@ impl Default for Entities {
             killed: vec![],
             next_id: 0,
             has_deleted: false,
+            reserved: default(),
         }
     }
 }
@@ -77,8 +169,6 @@ pub trait QueryItem {
     fn iter_with_bitset(self, bitset: Rc<BitSetVec>) -> Self::Iter;
 }
 
-// TODO: Implement optional component query iterators.
-
 impl<'a, 'q, T: TypedEcsData> QueryItem for &'a Comp<'q, T> {
     type Iter = ComponentBitsetIterator<'a, T>;
     fn apply_bitset(&self, bitset: &mut BitSetVec) {
@@ -110,6 +200,225 @@ impl<'a, 'q, T: TypedEcsData> QueryItem for &'a mut CompMut<'q, T> {
     }
 }
 
+/// A [`QueryItem`] that yields the component of each entity in the query if it has one, created
+/// with [`opt()`].
+///
+/// Optional stores don't modify the query bitset, so they don't constrain which entities are
+/// iterated over.
+pub struct Optional<S>(S);
+
+/// Create a query item that yields the component in the given store if the entity has one, and
+/// `None` otherwise.
+///
+/// Passing `&Comp<T>` or `&CompMut<T>` yields an `Option<&T>`, and passing `&mut CompMut<T>`
+/// yields an `Option<Mut<T>>`.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+/// # struct Transform { x: f32, y: f32 };
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GP1SW3HYWEB2TY4S40ARMB1R"]
+/// # struct AtlasSprite { index: usize };
+///
+/// fn render_system(
+///     entities: Res<Entities>,
+///     transforms: Comp<Transform>,
+///     atlas_sprites: Comp<AtlasSprite>,
+/// ) {
+///     for (entity, (transform, atlas_sprite)) in
+///         entities.iter_with((&transforms, opt(&atlas_sprites)))
+///     {
+///         if let Some(atlas_sprite) = atlas_sprite {
+///             // Render the atlas sprite.
+///         } else {
+///             // Render a plain sprite.
+///         }
+///     }
+/// }
+/// ```
+pub fn opt<S>(store: S) -> Optional<S>
+where
+    Optional<S>: QueryItem,
+{
+    Optional(store)
+}
+
+impl<'a, 'q, T: TypedEcsData> QueryItem for Optional<&'a Comp<'q, T>> {
+    type Iter = OptionalComponentBitsetIterator<'a, T>;
+    fn apply_bitset(&self, _bitset: &mut BitSetVec) {}
+
+    fn iter_with_bitset(self, bitset: Rc<BitSetVec>) -> Self::Iter {
+        self.0.iter_with_bitset_optional(bitset)
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Optional<&'a CompMut<'q, T>> {
+    type Iter = OptionalComponentBitsetIterator<'a, T>;
+    fn apply_bitset(&self, _bitset: &mut BitSetVec) {}
+
+    fn iter_with_bitset(self, bitset: Rc<BitSetVec>) -> Self::Iter {
+        self.0.iter_with_bitset_optional(bitset)
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Optional<&'a mut CompMut<'q, T>> {
+    type Iter = OptionalComponentBitsetIteratorMut<'a, T>;
+    fn apply_bitset(&self, _bitset: &mut BitSetVec) {}
+
+    fn iter_with_bitset(self, bitset: Rc<BitSetVec>) -> Self::Iter {
+        self.0.iter_mut_with_bitset_optional(bitset)
+    }
+}
+
+/// A [`QueryItem`] that skips the entities that have a component, created with [`without()`].
+///
+/// The filtered entities are removed from the query bitset, so they are skipped without checking
+/// the component store for every entity. The filter yields `()` for every entity in the query.
+pub struct Without<S>(S);
+
+/// Create a query filter that skips the entities that have a component in the given store.
+///
+/// This can be combined with other [`QueryItem`]s and other filters in a tuple passed to
+/// [`Entities::iter_with()`].
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+/// # struct Pos { x: f32, y: f32 };
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GP1SW3HYWEB2TY4S40ARMB1R"]
+/// # struct Frozen;
+///
+/// fn fall_system(entities: Res<Entities>, mut pos: CompMut<Pos>, frozen: Comp<Frozen>) {
+///     for (entity, (mut pos, ())) in entities.iter_with((&mut pos, without(&frozen))) {
+///         pos.y -= 1.0;
+///     }
+/// }
+/// ```
+pub fn without<S>(store: S) -> Without<S>
+where
+    Without<S>: QueryItem,
+{
+    Without(store)
+}
+
+impl<'a, 'q, T: TypedEcsData> QueryItem for Without<&'a Comp<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        bitset.bit_andnot(self.0.bitset());
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Without<&'a CompMut<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        bitset.bit_andnot(self.0.bitset());
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+
+/// A [`QueryItem`] that skips the entities whose component wasn't added since the system last ran,
+/// created with [`added()`].
+///
+/// The filter yields `()` for every entity in the query.
+pub struct Added<S>(S);
+
+/// Create a query filter that skips the entities whose component in the given store wasn't added
+/// since the system last ran.
+///
+/// Every component is reported once, the next time the system runs after the component was
+/// inserted. A system doesn't detect the components that it inserted itself.
+pub fn added<S>(store: S) -> Added<S>
+where
+    Added<S>: QueryItem,
+{
+    Added(store)
+}
+
+/// A [`QueryItem`] that skips the entities whose component wasn't changed since the system last
+/// ran, created with [`changed()`].
+///
+/// The filter yields `()` for every entity in the query.
+pub struct Changed<S>(S);
+
+/// Create a query filter that skips the entities whose component in the given store wasn't added
+/// or mutably accessed since the system last ran.
+///
+/// Only mutably dereferencing a [`Mut`] marks a component as changed, reading it doesn't. Like
+/// [`added()`], every change is reported once, and a system doesn't detect it's own changes.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+/// # struct Transform { x: f32, y: f32 };
+/// fn sync_system(entities: Res<Entities>, transforms: Comp<Transform>) {
+///     for (entity, (transform, ())) in entities.iter_with((&transforms, changed(&transforms))) {
+///         // Only the transforms that changed since the last run get here.
+///     }
+/// }
+/// ```
+pub fn changed<S>(store: S) -> Changed<S>
+where
+    Changed<S>: QueryItem,
+{
+    Changed(store)
+}
+
+impl<'a, 'q, T: TypedEcsData> QueryItem for Added<&'a Comp<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        self.0.retain_added(bitset);
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Added<&'a CompMut<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        self.0.retain_added(bitset);
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Changed<&'a Comp<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        self.0.retain_changed(bitset);
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryItem for Changed<&'a CompMut<'q, T>> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        self.0.retain_changed(bitset);
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+
 #[doc(hidden)]
 pub struct MultiQueryIter<T> {
     data: T,
@@ -233,17 +542,10 @@ impl<'a, I: Iterator> Iterator for EntitiesIterWith<'a, I> {
     type Item = (Entity, I::Item);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while !self.bitset.bit_test(self.current_id) && self.current_id < self.next_id {
-            self.current_id += 1;
-        }
+        let id = self.bitset.next_set_bit(self.current_id, self.next_id)?;
+        let entity = Entity::new(id as u32, self.generations[id]);
 
-        if self.current_id >= self.next_id {
-            return None;
-        }
-
-        let entity = Entity::new(self.current_id as u32, self.generations[self.current_id]);
-
-        self.current_id += 1;
+        self.current_id = id + 1;
         self.query.next().map(|item| (entity, item))
     }
 }
@@ -255,8 +557,22 @@ impl Entities {
     /// [`CompMut`] and for tuples of up to 26 items, so you can join over your mutable or immutable
     /// component borrows in your systems.
     ///
+    /// Every item is yielded along with its [`Entity`], which is found in the same walk over the
+    /// query bitset as the components, so there is no need to look up the entities separately.
+    ///
+    /// Items are always yielded in ascending order of entity index, whatever the
+    /// [`StorageStrategy`] of the stores, so iterating the same world gives the same order on
+    /// every machine, and after a [snapshot][World::snapshot] is restored. Systems that accumulate
+    /// floating point values, or consume random numbers, while iterating can rely on this for
+    /// deterministic results.
+    ///
     /// You can also pass a single component, to iterate only over the components that have alive
-    /// entities.
+    /// entities, use [`opt()`] to get a component only for the entities that have one, and use
+    /// [`without()`] to skip the entities that have a component. The [`added()`] and [`changed()`]
+    /// filters skip the entities whose component wasn't added or changed since the system last ran.
+    ///
+    /// Joining over `&mut CompMut<T>` yields a [`Mut<T>`][Mut], which marks the component as
+    /// changed when it is mutably dereferenced.
     ///
     /// # Example
     ///
@@ -270,7 +586,7 @@ impl Entities {
     /// # struct Vel { x: f32, y: f32 };
     ///
     /// fn my_system(entities: Res<Entities>, mut pos: CompMut<Pos>, vel: Comp<Vel>) {
-    ///     for (entity, (pos, vel)) in entities.iter_with((&mut pos, &vel)) {
+    ///     for (entity, (mut pos, vel)) in entities.iter_with((&mut pos, &vel)) {
     ///         pos.x += vel.x;
     ///         pos.y += vel.y;
     ///     }
@@ -290,10 +606,64 @@ impl Entities {
         }
     }
 
+    /// Get the single entity matching the given query, along with its components.
+    ///
+    /// This takes the same queries as [`iter_with()`][Self::iter_with], and is useful for the
+    /// components that only one entity is supposed to have, like the active camera.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no entity, or more than one entity, matches the query.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// # #[derive(Clone, TypeUlid)]
+    /// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+    /// # struct Transform { x: f32, y: f32 };
+    /// # #[derive(Clone, TypeUlid)]
+    /// # #[ulid = "01GP1SW3HYWEB2TY4S40ARMB1R"]
+    /// # struct Camera;
+    ///
+    /// fn follow_system(
+    ///     entities: Res<Entities>,
+    ///     cameras: Comp<Camera>,
+    ///     mut transforms: CompMut<Transform>,
+    /// ) -> SystemResult {
+    ///     let (_camera_ent, (_, mut transform)) =
+    ///         entities.single_with((&cameras, &mut transforms))?;
+    ///     transform.x += 1.0;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn single_with<Q: QueryItem>(
+        &self,
+        query: Q,
+    ) -> Result<(Entity, <Q::Iter as Iterator>::Item), SingleError> {
+        let name = std::any::type_name::<<Q::Iter as Iterator>::Item>();
+        let mut iter = self.iter_with(query);
+        let single = iter.next().ok_or(SingleError::NoEntities(name))?;
+
+        if iter.next().is_some() {
+            return Err(SingleError::MultipleEntities(name));
+        }
+
+        Ok(single)
+    }
+
     /// Creates a new `Entity` and returns it.
     ///
     /// This function will not reuse the index of an entity that is still in the killed entities.
+    ///
+    /// The lowest index that isn't alive or in the killed entities is always picked, so the
+    /// entities that are created only depend on the entities that were created and killed before,
+    /// which makes the allocation deterministic.
     pub fn create(&mut self) -> Entity {
+        // Make sure we don't hand out the index of a reserved entity.
+        self.flush_reserved();
+
         if !self.has_deleted {
             let i = self.next_id;
             if i >= BITSET_SIZE {
@@ -324,6 +694,36 @@ impl Entities {
         }
     }
 
+    /// Reserve an [`Entity`] that will be created by the next call to
+    /// [`flush_reserved()`][Self::flush_reserved], without needing mutable access.
+    ///
+    /// The reserved entity is not alive until it is flushed, but it's id may already be used, for
+    /// example to queue component insertions. This is how [`Commands::spawn()`] creates entities.
+    ///
+    /// Reserving is atomic, so entities may be reserved from multiple threads at once, for
+    /// example while iterating in parallel, or while loading a level in the background. Reserved
+    /// entities are flushed by [`World::maintain()`], when the commands of a stage are applied,
+    /// and before [`create()`][Self::create] picks an index, so a reserved id is never handed out
+    /// twice. Killing a reserved entity before it is flushed has no effect.
+    pub fn reserve(&self) -> Entity {
+        // Indices from `next_id` onwards have never been used, so their generations aren't held by
+        // any other entity, and `next_id` can't change while the entities are shared.
+        let i = self.next_id + self.reserved.0.fetch_add(1, Ordering::AcqRel);
+        if i >= BITSET_SIZE {
+            panic!("Exceeded maximum amount of concurrent entities.");
+        }
+        Entity::new(i as u32, self.generation[i])
+    }
+
+    /// Create all of the entities that have been [reserved][Self::reserve].
+    pub fn flush_reserved(&mut self) {
+        let reserved = std::mem::take(self.reserved.0.get_mut());
+        for i in self.next_id..(self.next_id + reserved) {
+            self.alive.bit_set(i);
+        }
+        self.next_id += reserved;
+    }
+
     /// Checks if the `Entity` is still alive.
     ///
     /// Returns true if it is alive. Returns false if it has been killed.
@@ -342,6 +742,11 @@ impl Entities {
         }
     }
 
+    /// Get the entity with the given index, with the generation that the index currently has.
+    pub(crate) fn entity_at(&self, index: usize) -> Entity {
+        Entity::new(index as u32, self.generation[index])
+    }
+
     /// Returns entities in the killed list.
     pub fn killed(&self) -> &Vec<Entity> {
         &self.killed
@@ -360,7 +765,7 @@ impl Entities {
         &self.alive
     }
 
-    /// Iterates over entities using the provided bitset.
+    /// Iterates over entities using the provided bitset, in ascending order of entity index.
     pub fn iter_with_bitset<'a>(&'a self, bitset: &'a BitSetVec) -> EntityIterator {
         EntityIterator {
             current_id: 0,
@@ -385,21 +790,12 @@ pub struct EntityIterator<'a> {
 impl<'a> Iterator for EntityIterator<'a> {
     type Item = Entity;
     fn next(&mut self) -> Option<Self::Item> {
-        while !(self.bitset.bit_test(self.current_id) && self.entities.bit_test(self.current_id))
-            && self.current_id < self.next_id
-        {
-            self.current_id += 1;
-        }
-        let ret = if self.current_id < self.next_id {
-            Some(Entity::new(
-                self.current_id as u32,
-                self.generations[self.current_id],
-            ))
-        } else {
-            None
-        };
-        self.current_id += 1;
-        ret
+        let id = self
+            .bitset
+            .next_set_bit_and(self.entities, self.current_id, self.next_id)?;
+        self.current_id = id + 1;
+
+        Some(Entity::new(id as u32, self.generations[id]))
     }
 }
 
@@ -460,6 +856,82 @@ mod tests {
         assert!(!entities.is_alive(e4));
     }
 
+    #[test]
+    fn reserve_entities() {
+        let mut entities = Entities::default();
+        let e1 = entities.create();
+        entities.kill(e1);
+        entities.clear_killed();
+
+        let e2 = entities.reserve();
+        let e3 = entities.reserve();
+        assert_eq!(e2.index(), 1);
+        assert_eq!(e3.index(), 2);
+        assert!(!entities.is_alive(e2));
+
+        // Creating an entity must not re-use the reserved indexes
+        let e4 = entities.create();
+        assert!(entities.is_alive(e2));
+        assert!(entities.is_alive(e3));
+        assert_eq!(e4.index(), 0);
+
+        let e5 = entities.reserve();
+        entities.flush_reserved();
+        assert!(entities.is_alive(e5));
+        assert_eq!(e5.index(), 3);
+    }
+
+    #[test]
+    fn reserve_from_many_threads() {
+        let mut entities = Entities::default();
+        let spawned = (0..10).map(|_| entities.create()).collect::<Vec<_>>();
+        for &entity in &spawned[..5] {
+            entities.kill(entity);
+        }
+        entities.clear_killed();
+
+        let reserved = std::thread::scope(|scope| {
+            let threads = (0..8)
+                .map(|_| {
+                    let entities = &entities;
+                    scope.spawn(move || (0..1000).map(|_| entities.reserve()).collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let unique = reserved.iter().map(|e| e.index()).collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 8000);
+        // The killed indices aren't re-used before the reserved entities are flushed.
+        assert!(reserved.iter().all(|e| e.index() >= 10));
+        assert!(reserved.iter().all(|&e| !entities.is_alive(e)));
+
+        entities.flush_reserved();
+        assert!(reserved.iter().all(|&e| entities.is_alive(e)));
+        let created = entities.create();
+        assert!(created.index() < 5);
+    }
+
+    #[test]
+    fn maintain_flushes_reserved() {
+        let mut world = World::new();
+        let reserved = {
+            let entities = world.resources.get::<Entities>();
+            let entities = entities.borrow();
+            let entities = &*entities;
+            std::thread::scope(|scope| scope.spawn(|| entities.reserve()).join().unwrap())
+        };
+        world.maintain();
+        assert!(world
+            .resources
+            .get::<Entities>()
+            .borrow()
+            .is_alive(reserved));
+    }
+
     #[test]
     /// Exercise basic operations on entities to increase code coverage
     fn clone_debug_hash() {
@@ -528,4 +1000,415 @@ mod tests {
         let bitset = BitSetVec::default();
         assert_eq!(entities.iter_with_bitset(&bitset).count(), 0);
     }
+
+    #[test]
+    fn iter_with_without() {
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ2Z3F4N1V0QW8H7XJ5RKM6T"]
+        struct Pos(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ2Z3PAMB8T2SZ0XC6E4W9QH"]
+        struct Frozen;
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ2Z3XK5D7NJ3R1YF8V0GTBA"]
+        struct Hidden;
+
+        let mut world = World::new();
+        world.components.init::<Pos>();
+        world.components.init::<Frozen>();
+        world.components.init::<Hidden>();
+
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut pos: CompMut<Pos>,
+                 mut frozen: CompMut<Frozen>,
+                 mut hidden: CompMut<Hidden>| {
+                    for i in 0..6 {
+                        let entity = entities.create();
+                        pos.insert(entity, Pos(i));
+                        if i % 2 == 0 {
+                            frozen.insert(entity, Frozen);
+                        }
+                        if i == 3 {
+                            hidden.insert(entity, Hidden);
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |entities: Res<Entities>,
+                 mut pos: CompMut<Pos>,
+                 frozen: Comp<Frozen>,
+                 hidden: Comp<Hidden>| {
+                    let moved = entities
+                        .iter_with((&mut pos, without(&frozen)))
+                        .map(|(_, (mut pos, ()))| {
+                            pos.0 += 10;
+                            pos.0
+                        })
+                        .collect::<Vec<_>>();
+                    assert_eq!(moved, [11, 13, 15]);
+
+                    let visible = entities
+                        .iter_with((&pos, without(&frozen), without(&hidden)))
+                        .map(|(_, (pos, (), ()))| pos.0)
+                        .collect::<Vec<_>>();
+                    assert_eq!(visible, [11, 15]);
+
+                    // A filter on its own iterates the alive entities without the component.
+                    assert_eq!(entities.iter_with(without(&frozen)).count(), 3);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn iter_with_large_tuple() {
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBAEHYBMYWJ4KTKZ3VHMF73"]
+        struct A(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBMJD4Q7MFHM0PKSRB72Q74"]
+        struct B(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQB957M03THPCCRXYPXKZ40Y"]
+        struct C(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBRRQ7F0WMAZY25JXHVTGYY"]
+        struct D(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBR1SMMA6BMJBPVVNQ20EY5"]
+        struct E(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBH57CCC440VQ09T158X21D"]
+        struct F(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBE7NQ5ZK110ZJNM8N6NRZW"]
+        struct G(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQBHC5C8G6BT88YXXMF01159"]
+        struct H(u32);
+
+        let mut world = World::new();
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut a: CompMut<A>,
+                 mut b: CompMut<B>,
+                 mut c: CompMut<C>,
+                 mut d: CompMut<D>,
+                 mut e: CompMut<E>,
+                 mut f: CompMut<F>,
+                 mut g: CompMut<G>,
+                 mut h: CompMut<H>| {
+                    for i in 0..10 {
+                        let entity = entities.create();
+                        a.insert(entity, A(i));
+                        b.insert(entity, B(i));
+                        c.insert(entity, C(i));
+                        d.insert(entity, D(i));
+                        e.insert(entity, E(i));
+                        f.insert(entity, F(i));
+                        g.insert(entity, G(i));
+                        // Only every third entity has all of the components.
+                        if i % 3 == 0 {
+                            h.insert(entity, H(i));
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |entities: Res<Entities>,
+                 a: Comp<A>,
+                 b: Comp<B>,
+                 c: Comp<C>,
+                 d: Comp<D>,
+                 e: Comp<E>,
+                 f: Comp<F>,
+                 g: Comp<G>,
+                 mut h: CompMut<H>| {
+                    let joined = entities
+                        .iter_with((&a, &b, &c, &d, &e, &f, &g, &mut h))
+                        .map(|(entity, (a, b, c, d, e, f, g, mut h))| {
+                            h.0 += a.0 + b.0 + c.0 + d.0 + e.0 + f.0 + g.0;
+                            (entity, h.0)
+                        })
+                        .collect::<Vec<_>>();
+
+                    // The entities are yielded from the same bitset walk as the components.
+                    assert_eq!(
+                        joined
+                            .iter()
+                            .map(|(entity, h)| (entity.index(), *h))
+                            .collect::<Vec<_>>(),
+                        [(0, 0), (3, 24), (6, 48), (9, 72)]
+                    );
+                    for (entity, _) in joined {
+                        assert!(entities.is_alive(entity));
+                        assert_eq!(a.get(entity).unwrap().0, entity.index());
+                    }
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn iter_with_matches_naive_join() {
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQCW3H5N0R8T2XZ6KA4MJ9VE"]
+        struct A(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQCW3RB7F1Y9D3QG5WN8PX2S"]
+        struct B(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQCW40MV6K2E8HJ0T4RC1ZBD"]
+        struct C(u32);
+
+        // A small xorshift random number generator, so the test is reproducible.
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        // Dense, sparse, and clustered component distributions.
+        let distributions: [fn(usize, u64) -> bool; 3] = [
+            |_, r| r % 10 < 9,
+            |_, r| r % 100 == 0,
+            |i, r| (i / 500) % 4 == 0 && r % 2 == 0,
+        ];
+
+        for has_component in distributions {
+            let mut world = World::new();
+            world.components.init::<A>();
+            world.components.init::<B>();
+            world.components.init::<C>();
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            let a = world.components.get::<A>();
+            let mut a = a.borrow_mut();
+            let b = world.components.get::<B>();
+            let mut b = b.borrow_mut();
+            let c = world.components.get::<C>();
+            let mut c = c.borrow_mut();
+
+            let mut spawned = Vec::new();
+            for i in 0..5000 {
+                let entity = entities.create();
+                if has_component(i, random()) {
+                    a.insert(entity, A(i as u32));
+                }
+                if has_component(i, random()) {
+                    b.insert(entity, B(i as u32));
+                }
+                if has_component(i, random()) {
+                    c.insert(entity, C(i as u32));
+                }
+                spawned.push(entity);
+            }
+            // Kill some of the entities, so that the dead ones have to be skipped too.
+            for &entity in &spawned {
+                if random() % 7 == 0 {
+                    entities.kill(entity);
+                }
+            }
+            let alive = spawned
+                .iter()
+                .copied()
+                .filter(|&entity| entities.is_alive(entity))
+                .collect::<Vec<_>>();
+
+            let joined = entities
+                .iter_with((&a, &b, without(&c)))
+                .map(|(entity, (a, b, ()))| (entity, a.0, b.0))
+                .collect::<Vec<_>>();
+            let naive = alive
+                .iter()
+                .filter(|&&e| a.contains(e) && b.contains(e) && !c.contains(e))
+                .map(|&e| (e, a.get(e).unwrap().0, b.get(e).unwrap().0))
+                .collect::<Vec<_>>();
+            assert_eq!(joined, naive);
+
+            let joined = entities
+                .iter_with((&mut a, opt(&c)))
+                .map(|(entity, (a, c))| (entity, a.0, c.map(|c| c.0)))
+                .collect::<Vec<_>>();
+            let naive = alive
+                .iter()
+                .filter(|&&e| a.contains(e))
+                .map(|&e| (e, a.get(e).unwrap().0, c.get(e).map(|c| c.0)))
+                .collect::<Vec<_>>();
+            assert_eq!(joined, naive);
+
+            let joined = entities.iter_with_bitset(b.bitset()).collect::<Vec<_>>();
+            let naive = alive
+                .iter()
+                .copied()
+                .filter(|&e| b.contains(e))
+                .collect::<Vec<_>>();
+            assert_eq!(joined, naive);
+        }
+    }
+
+    #[test]
+    fn iter_with_opt() {
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ6M2D8RZ4K0W7V3QX1NH5TB"]
+        struct Pos(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ6M2MJ9E5A1C8T6YB3PZ0RD"]
+        struct Vel(u32);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQ6M2VXQ3H7F2N9K0DG5WM8S"]
+        struct Unused;
+
+        let mut world = World::new();
+        world.components.init::<Pos>();
+        world.components.init::<Vel>();
+        world.components.init::<Unused>();
+
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut pos: CompMut<Pos>, mut vel: CompMut<Vel>| {
+                    for i in 0..4 {
+                        let entity = entities.create();
+                        pos.insert(entity, Pos(i));
+                        if i % 2 == 1 {
+                            vel.insert(entity, Vel(i * 10));
+                        }
+                    }
+                    // An entity with only the optional component isn't part of the query.
+                    let entity = entities.create();
+                    vel.insert(entity, Vel(100));
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |entities: Res<Entities>,
+                 mut pos: CompMut<Pos>,
+                 mut vel: CompMut<Vel>,
+                 unused: Comp<Unused>| {
+                    let joined = entities
+                        .iter_with((&pos, opt(&vel)))
+                        .map(|(_, (pos, vel))| (pos.0, vel.map(|vel| vel.0)))
+                        .collect::<Vec<_>>();
+                    assert_eq!(joined, [(0, None), (1, Some(10)), (2, None), (3, Some(30))]);
+
+                    for (_, (mut pos, vel)) in entities.iter_with((&mut pos, opt(&mut vel))) {
+                        if let Some(mut vel) = vel {
+                            vel.0 += 1;
+                            pos.0 += vel.0;
+                        }
+                    }
+                    let joined = entities
+                        .iter_with((&pos, opt(&vel), opt(&unused)))
+                        .map(|(_, (pos, vel, unused))| {
+                            (pos.0, vel.map(|vel| vel.0), unused.is_some())
+                        })
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        joined,
+                        [
+                            (0, None, false),
+                            (1 + 11, Some(11), false),
+                            (2, None, false),
+                            (3 + 31, Some(31), false)
+                        ]
+                    );
+
+                    // On its own, an optional store iterates over all of the alive entities.
+                    assert_eq!(entities.iter_with(opt(&unused)).count(), 5);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn single() {
+        #[derive(Clone, TypeUlid, Debug, PartialEq, Eq)]
+        #[ulid = "01GQ8K4T0B2W6N9R5YJX3MD7HC"]
+        struct Camera;
+        #[derive(Clone, TypeUlid, Debug, PartialEq, Eq)]
+        #[ulid = "01GQ8K52QE7V1C4Z8HSA0GX6NF"]
+        struct Player(u32);
+
+        let mut world = World::new();
+        world.components.init::<Camera>();
+        world.components.init::<Player>();
+
+        world
+            .run_system(
+                |entities: Res<Entities>, cameras: Comp<Camera>, mut players: CompMut<Player>| {
+                    assert_eq!(
+                        cameras.single(&entities),
+                        Err(SingleError::NoEntities(std::any::type_name::<Camera>()))
+                    );
+                    assert!(players.single_mut(&entities).is_err());
+                    assert!(entities.single_with((&cameras, &players)).is_err());
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut cameras: CompMut<Camera>,
+                 mut players: CompMut<Player>| {
+                    let camera = entities.create();
+                    cameras.insert(camera, Camera);
+                    players.insert(camera, Player(0));
+                    let player = entities.create();
+                    players.insert(player, Player(1));
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                |entities: Res<Entities>, cameras: Comp<Camera>, mut players: CompMut<Player>| {
+                    assert_eq!(cameras.single(&entities), Ok(&Camera));
+                    assert_eq!(
+                        players.single_mut(&entities).map(|x| x.0),
+                        Err(SingleError::MultipleEntities(
+                            std::any::type_name::<Player>()
+                        ))
+                    );
+
+                    let (_, (_, mut player)) =
+                        entities.single_with((&cameras, &mut players)).unwrap();
+                    player.0 = 10;
+                    assert_eq!(
+                        entities
+                            .iter_with(&players)
+                            .map(|(_, player)| player.0)
+                            .collect::<Vec<_>>(),
+                        [10, 1]
+                    );
+                },
+            )
+            .unwrap();
+
+        // Killed entities don't count.
+        world
+            .run_system(|mut entities: ResMut<Entities>, players: Comp<Player>| {
+                let (camera_ent, _) = entities
+                    .iter_with(&players)
+                    .find(|(_, player)| player.0 == 10)
+                    .unwrap();
+                entities.kill(camera_ent);
+                assert_eq!(players.single(&entities), Ok(&Player(1)));
+            })
+            .unwrap();
+    }
 }
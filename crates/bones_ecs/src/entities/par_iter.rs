@@ -0,0 +1,453 @@
+//! Parallel iteration over entity queries, with [`Entities::par_iter_with()`].
+
+use std::marker::PhantomData;
+
+use crate::prelude::*;
+
+/// A [`QueryItem`] that can be iterated over in parallel with [`Entities::par_iter_with()`].
+///
+/// This is implemented for the same component borrows and filters as [`QueryItem`], and for
+/// tuples of them.
+pub trait ParQueryItem: QueryItem {
+    /// The fetcher used to get the item for every entity in the query.
+    type Fetch: QueryFetch + Send + Sync;
+    /// Create the fetcher for this query item.
+    fn into_fetch(self) -> Self::Fetch;
+}
+
+/// Fetches the item of a [`ParQueryItem`] by entity index.
+pub trait QueryFetch {
+    /// The item fetched for every entity.
+    type Item;
+
+    /// Fetch the item for the entity with the given index.
+    ///
+    /// # Safety
+    ///
+    /// The index must be in the bitset of the query that created the fetcher, and every index must
+    /// be fetched at most once, so that mutable fetches never alias.
+    unsafe fn fetch(&self, index: usize) -> Self::Item;
+}
+
+/// Get the storage slot of the component at the given entity index, from the slots of a dense
+/// store.
+///
+/// # Safety
+///
+/// The entity index must have a component in the store that the slots were taken from.
+unsafe fn slot(slots: Option<*const u32>, index: usize) -> usize {
+    match slots {
+        Some(slots) => *slots.add(index) as usize,
+        None => index,
+    }
+}
+
+/// Get a pointer to the storage slots of a component store, if it is stored densely.
+fn slots(components: &UntypedComponentStore) -> Option<*const u32> {
+    components.dense.as_ref().map(|dense| dense.slots.as_ptr())
+}
+
+/// Fetches a reference to a component for every entity in the query.
+pub struct ComponentFetch<'a, T> {
+    storage: *const T,
+    slots: Option<*const u32>,
+    _phantom: PhantomData<&'a T>,
+}
+
+// SAFE: The fetcher only hands out shared references to the components, which are `Sync`.
+unsafe impl<'a, T: Sync> Send for ComponentFetch<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for ComponentFetch<'a, T> {}
+
+impl<'a, T> ComponentFetch<'a, T> {
+    /// # Safety
+    /// The component store must be valid for type T.
+    unsafe fn new(components: &'a UntypedComponentStore) -> Self {
+        Self {
+            storage: components.storage.as_ptr() as *const T,
+            slots: slots(components),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'a> QueryFetch for ComponentFetch<'a, T> {
+    type Item = &'a T;
+    unsafe fn fetch(&self, index: usize) -> Self::Item {
+        &*self.storage.add(slot(self.slots, index))
+    }
+}
+
+/// Fetches mutable access to a component for every entity in the query.
+pub struct ComponentFetchMut<'a, T> {
+    storage: *mut T,
+    slots: Option<*const u32>,
+    ticks: *mut ComponentTicks,
+    change_tick: Tick,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+// SAFE: Every index is fetched at most once, so the mutable references handed out to different
+// threads never alias.
+unsafe impl<'a, T: Send> Send for ComponentFetchMut<'a, T> {}
+unsafe impl<'a, T: Send> Sync for ComponentFetchMut<'a, T> {}
+
+impl<'a, T> ComponentFetchMut<'a, T> {
+    /// # Safety
+    /// The component store must be valid for type T.
+    unsafe fn new(components: &mut UntypedComponentStore, change_tick: Tick) -> Self {
+        Self {
+            storage: components.storage.as_mut_ptr() as *mut T,
+            slots: slots(components),
+            ticks: components.ticks.as_mut_ptr(),
+            change_tick,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'a> QueryFetch for ComponentFetchMut<'a, T> {
+    type Item = Mut<'a, T>;
+    unsafe fn fetch(&self, index: usize) -> Self::Item {
+        Mut::new(
+            &mut *self.storage.add(slot(self.slots, index)),
+            &mut *self.ticks.add(index),
+            self.change_tick,
+        )
+    }
+}
+
+/// Fetches a reference to a component for every entity in the query that has one.
+pub struct OptionalComponentFetch<'a, T> {
+    bitset: &'a BitSetVec,
+    fetch: ComponentFetch<'a, T>,
+}
+
+impl<'a, T: 'a> QueryFetch for OptionalComponentFetch<'a, T> {
+    type Item = Option<&'a T>;
+    unsafe fn fetch(&self, index: usize) -> Self::Item {
+        self.bitset.bit_test(index).then(|| self.fetch.fetch(index))
+    }
+}
+
+/// Fetches mutable access to a component for every entity in the query that has one.
+pub struct OptionalComponentFetchMut<'a, T> {
+    bitset: &'a BitSetVec,
+    fetch: ComponentFetchMut<'a, T>,
+}
+
+impl<'a, T: 'a> QueryFetch for OptionalComponentFetchMut<'a, T> {
+    type Item = Option<Mut<'a, T>>;
+    unsafe fn fetch(&self, index: usize) -> Self::Item {
+        self.bitset.bit_test(index).then(|| self.fetch.fetch(index))
+    }
+}
+
+/// Query filters don't fetch anything, and yield `()` for every entity.
+impl QueryFetch for () {
+    type Item = ();
+    unsafe fn fetch(&self, _index: usize) -> Self::Item {}
+}
+
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for &'a Comp<'q, T> {
+    type Fetch = ComponentFetch<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        // SAFE: The typed store is valid for type T.
+        unsafe { ComponentFetch::new(self.untyped()) }
+    }
+}
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for &'a CompMut<'q, T> {
+    type Fetch = ComponentFetch<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        // SAFE: The typed store is valid for type T.
+        unsafe { ComponentFetch::new(self.untyped()) }
+    }
+}
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for &'a mut CompMut<'q, T> {
+    type Fetch = ComponentFetchMut<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        let (components, change_tick) = self.untyped_mut();
+        // SAFE: The typed store is valid for type T.
+        unsafe { ComponentFetchMut::new(components, change_tick) }
+    }
+}
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for Optional<&'a Comp<'q, T>> {
+    type Fetch = OptionalComponentFetch<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        let Optional(store) = self;
+        OptionalComponentFetch {
+            bitset: store.bitset(),
+            fetch: store.into_fetch(),
+        }
+    }
+}
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for Optional<&'a CompMut<'q, T>> {
+    type Fetch = OptionalComponentFetch<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        let Optional(store) = self;
+        OptionalComponentFetch {
+            bitset: store.bitset(),
+            fetch: store.into_fetch(),
+        }
+    }
+}
+impl<'a, 'q, T: TypedEcsData> ParQueryItem for Optional<&'a mut CompMut<'q, T>> {
+    type Fetch = OptionalComponentFetchMut<'a, T>;
+    fn into_fetch(self) -> Self::Fetch {
+        let Optional(store) = self;
+        let (components, change_tick) = store.untyped_mut();
+        // SAFE: The typed store is valid for type T.
+        let fetch = unsafe { ComponentFetchMut::new(components, change_tick) };
+        // The fetcher only holds pointers to the component data, slots, and ticks, so the bitset
+        // can still be borrowed.
+        OptionalComponentFetchMut {
+            bitset: components.bitset(),
+            fetch,
+        }
+    }
+}
+
+macro_rules! impl_filter_par_query {
+    ($($filter:ident),*) => {
+        $(
+            impl<'a, 'q, T: TypedEcsData> ParQueryItem for $filter<&'a Comp<'q, T>> {
+                type Fetch = ();
+                fn into_fetch(self) -> Self::Fetch {}
+            }
+            impl<'a, 'q, T: TypedEcsData> ParQueryItem for $filter<&'a CompMut<'q, T>> {
+                type Fetch = ();
+                fn into_fetch(self) -> Self::Fetch {}
+            }
+        )*
+    };
+}
+
+impl_filter_par_query!(Without, Added, Changed);
+
+macro_rules! impl_par_query {
+    ( $( $args:ident, )* ) => {
+        impl<
+            $(
+                $args: QueryFetch,
+            )*
+        > QueryFetch for (
+            $(
+                $args,
+            )*
+        ) {
+            type Item = (
+                $(
+                    $args::Item,
+                )*
+            );
+
+            #[allow(non_snake_case)]
+            unsafe fn fetch(&self, index: usize) -> Self::Item {
+                let (
+                    $(
+                        $args,
+                    )*
+                ) = self;
+                (
+                    $(
+                        $args.fetch(index),
+                    )*
+                )
+            }
+        }
+
+        impl<
+            $(
+                $args: ParQueryItem,
+            )*
+        > ParQueryItem for (
+            $(
+                $args,
+            )*
+        ) {
+            type Fetch = (
+                $(
+                    <$args as ParQueryItem>::Fetch,
+                )*
+            );
+
+            #[allow(non_snake_case)]
+            fn into_fetch(self) -> Self::Fetch {
+                let (
+                    $(
+                        $args,
+                    )*
+                ) = self;
+                (
+                    $(
+                        $args.into_fetch(),
+                    )*
+                )
+            }
+        }
+    };
+}
+
+macro_rules! impl_par_queries {
+    // base case
+    () => {};
+    (
+        $head:ident,
+        $(
+            $tail:ident,
+        )*
+    ) => {
+        // recursive call
+        impl_par_query!($head, $( $tail, )* );
+        impl_par_queries!($( $tail, )* );
+    }
+}
+
+impl_par_queries!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,);
+
+/// The number of entities in each of the blocks that the query bitset is split into.
+const BLOCK_SIZE: usize = 32 * 8;
+
+impl Entities {
+    /// Run a function for every entity and it's components in the given query, in parallel.
+    ///
+    /// This takes the same queries as [`iter_with()`][Self::iter_with]. The query bitset is split
+    /// into blocks of entities that are processed on the [`rayon`] thread pool, so the order that
+    /// the entities are processed in is unspecified.
+    ///
+    /// On `wasm32` the entities are processed serially on the current thread instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// # #[derive(Clone, TypeUlid)]
+    /// # #[ulid = "01GP1SVTTSR91P40B2W0XPQ1SN"]
+    /// # struct AtlasSprite { index: usize, frames: usize };
+    ///
+    /// fn animate_system(entities: Res<Entities>, mut sprites: CompMut<AtlasSprite>) {
+    ///     entities.par_iter_with(&mut sprites, |_entity, mut sprite| {
+    ///         sprite.index = (sprite.index + 1) % sprite.frames;
+    ///     });
+    /// }
+    /// ```
+    pub fn par_iter_with<Q, F>(&self, query: Q, f: F)
+    where
+        Q: ParQueryItem,
+        F: Fn(Entity, <Q::Fetch as QueryFetch>::Item) + Send + Sync,
+    {
+        let mut bitset = self.bitset().clone();
+        query.apply_bitset(&mut bitset);
+        let fetch = query.into_fetch();
+
+        let block_count = (self.next_id + BLOCK_SIZE - 1) / BLOCK_SIZE;
+        let blocks = &bitset.0[..block_count.min(bitset.0.len())];
+        let run_block = |(block, words): (usize, &[u32; 8])| {
+            for (word_index, &word) in words.iter().enumerate() {
+                let mut bits = word;
+                while bits != 0 {
+                    let index =
+                        block * BLOCK_SIZE + word_index * 32 + bits.trailing_zeros() as usize;
+                    // Clear the lowest set bit.
+                    bits &= bits - 1;
+
+                    let entity = Entity::new(index as u32, self.generation[index]);
+                    // SAFE: The index is in the query bitset, and every index is only visited once.
+                    f(entity, unsafe { fetch.fetch(index) });
+                }
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use rayon::prelude::*;
+            blocks.par_iter().enumerate().for_each(run_block);
+        }
+        #[cfg(target_arch = "wasm32")]
+        blocks.iter().enumerate().for_each(run_block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn par_iter_with_matches_serial() {
+        #[derive(Clone, TypeUlid, Debug, PartialEq)]
+        #[ulid = "01GQDJ1S2E7A0V6K4WTXN8RB3H"]
+        struct Pos(u64);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQDJ20ZH3M9Q1C5YDG7PF4KT"]
+        struct Vel(u64);
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GQDJ27T6N2B8X0RJ4EV9WQ5M"]
+        struct Frozen;
+
+        let mut world = World::new();
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut pos: CompMut<Pos>,
+                 mut vel: CompMut<Vel>,
+                 mut frozen: CompMut<Frozen>| {
+                    for i in 0..20_000 {
+                        let entity = entities.create();
+                        pos.insert(entity, Pos(i));
+                        if i % 3 != 0 {
+                            vel.insert(entity, Vel(i * 7 % 13));
+                        }
+                        if i % 11 == 0 {
+                            frozen.insert(entity, Frozen);
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        let update = |pos: &mut Pos, vel: Option<&Vel>| {
+            pos.0 = pos.0 * 3 + vel.map_or(1, |vel| vel.0);
+        };
+
+        let mut serial = world.clone();
+        serial
+            .run_system(
+                move |entities: Res<Entities>,
+                      mut pos: CompMut<Pos>,
+                      vel: Comp<Vel>,
+                      frozen: Comp<Frozen>| {
+                    for (_, (mut pos, vel, ())) in
+                        entities.iter_with((&mut pos, opt(&vel), without(&frozen)))
+                    {
+                        update(&mut pos, vel);
+                    }
+                },
+            )
+            .unwrap();
+
+        world
+            .run_system(
+                move |entities: Res<Entities>,
+                      mut pos: CompMut<Pos>,
+                      vel: Comp<Vel>,
+                      frozen: Comp<Frozen>| {
+                    entities.par_iter_with(
+                        (&mut pos, opt(&vel), without(&frozen)),
+                        |_, (mut pos, vel, ())| update(&mut pos, vel),
+                    );
+                },
+            )
+            .unwrap();
+
+        let positions = |world: &World| {
+            let entities = world.resources.get::<Entities>();
+            let entities = entities.borrow();
+            let pos = world.components.get::<Pos>();
+            let pos = pos.borrow();
+            entities
+                .iter_with(&pos)
+                .map(|(entity, pos)| (entity, pos.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&world), positions(&serial));
+    }
+}
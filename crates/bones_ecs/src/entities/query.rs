@@ -0,0 +1,385 @@
+//! Query combinators, for selecting entities with boolean expressions over component bitsets.
+
+use std::rc::Rc;
+
+use crate::prelude::*;
+
+/// A [`QueryItem`] that selects entities with a boolean expression over the bitsets of component
+/// stores, created with [`with()`] or [`Query::new()`].
+///
+/// Each method combines the expression so far with its argument, so expressions are read from left
+/// to right: `with(&a).with(&b).or(with(&c)).without(&d)` selects the entities that have (`a` and
+/// `b`) or `c`, but not `d`. Expressions are negated with the `!` operator.
+///
+/// The expression is evaluated one bitset word at a time, when it is applied to the query bitset,
+/// so no intermediate bitsets or entity lists are created, and the words that the rest of the
+/// query already ruled out are skipped. The query yields `()` for every entity that it selects.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQSK1A2B3C4D5E6F7G8H9J0K"]
+/// # struct Health(u32);
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQSK1B2C3D4E5F6G7H8J9K0M"]
+/// # struct Enemy;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQSK1C2D3E4F5G6H7J8K9M0N"]
+/// # struct Burning;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQSK1D2E3F4G5H6J7K8M9N0P"]
+/// # struct Cursed;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQSK1E2F3G4H5J6K7M8N9P0Q"]
+/// # struct Invulnerable;
+///
+/// fn damage_system(
+///     entities: Res<Entities>,
+///     mut healths: CompMut<Health>,
+///     enemies: Comp<Enemy>,
+///     burning: Comp<Burning>,
+///     cursed: Comp<Cursed>,
+///     invulnerable: Comp<Invulnerable>,
+/// ) {
+///     let query = with(&enemies)
+///         .with(&burning)
+///         .or(with(&cursed))
+///         .without(&invulnerable);
+///     for (_entity, (mut health, ())) in entities.iter_with((&mut healths, query)) {
+///         health.0 = health.0.saturating_sub(1);
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Query<'a> {
+    expr: Expr<'a>,
+}
+
+/// An expression over bitsets, in a [`Query`].
+#[derive(Clone)]
+enum Expr<'a> {
+    /// Every entity.
+    All,
+    /// The entities in a bitset.
+    Bitset(&'a BitSetVec),
+    /// The entities that aren't selected by the expression.
+    Not(Box<Expr<'a>>),
+    /// The entities selected by both expressions.
+    And(Box<Expr<'a>>, Box<Expr<'a>>),
+    /// The entities selected by either expression.
+    Or(Box<Expr<'a>>, Box<Expr<'a>>),
+}
+
+impl<'a> Expr<'a> {
+    /// Evaluate the expression for the bitset word with the given index.
+    fn word(&self, word: usize) -> u32 {
+        match self {
+            Expr::All => u32::MAX,
+            Expr::Bitset(bitset) => bitset.word(word),
+            Expr::Not(expr) => !expr.word(word),
+            Expr::And(a, b) => match a.word(word) {
+                0 => 0,
+                a => a & b.word(word),
+            },
+            Expr::Or(a, b) => match a.word(word) {
+                u32::MAX => u32::MAX,
+                a => a | b.word(word),
+            },
+        }
+    }
+}
+
+/// Types with a bitset of entities that can be used in a [`Query`], such as `&Comp<T>`,
+/// `&CompMut<T>`, and `&BitSetVec`.
+pub trait QueryBitset<'a> {
+    /// Get the bitset of the entities to select.
+    fn query_bitset(self) -> &'a BitSetVec;
+}
+
+impl<'a> QueryBitset<'a> for &'a BitSetVec {
+    fn query_bitset(self) -> &'a BitSetVec {
+        self
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryBitset<'a> for &'a Comp<'q, T> {
+    fn query_bitset(self) -> &'a BitSetVec {
+        self.bitset()
+    }
+}
+impl<'a, 'q, T: TypedEcsData> QueryBitset<'a> for &'a CompMut<'q, T> {
+    fn query_bitset(self) -> &'a BitSetVec {
+        self.bitset()
+    }
+}
+impl<'a, T: TypedEcsData> QueryBitset<'a> for &'a ComponentStore<T> {
+    fn query_bitset(self) -> &'a BitSetVec {
+        self.bitset()
+    }
+}
+
+/// Create a [`Query`] that selects the entities that have a component in the given store.
+pub fn with<'a>(store: impl QueryBitset<'a>) -> Query<'a> {
+    Query::new().with(store)
+}
+
+impl<'a> Default for Query<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Create a [`Query`] that selects every entity.
+    pub fn new() -> Self {
+        Self { expr: Expr::All }
+    }
+
+    /// Only select the entities that also have a component in the given store.
+    pub fn with(self, store: impl QueryBitset<'a>) -> Self {
+        self.and(Self {
+            expr: Expr::Bitset(store.query_bitset()),
+        })
+    }
+
+    /// Only select the entities that don't have a component in the given store.
+    pub fn without(self, store: impl QueryBitset<'a>) -> Self {
+        self.and(!with(store))
+    }
+
+    /// Only select the entities that are also selected by `other`.
+    pub fn and(self, other: Query<'a>) -> Self {
+        let expr = match (self.expr, other.expr) {
+            (Expr::All, expr) | (expr, Expr::All) => expr,
+            (a, b) => Expr::And(Box::new(a), Box::new(b)),
+        };
+        Self { expr }
+    }
+
+    /// Also select the entities that are selected by `other`.
+    pub fn or(self, other: Query<'a>) -> Self {
+        let expr = match (self.expr, other.expr) {
+            (Expr::All, _) | (_, Expr::All) => Expr::All,
+            (a, b) => Expr::Or(Box::new(a), Box::new(b)),
+        };
+        Self { expr }
+    }
+
+    /// Evaluate the query into a bitset of the selected entity indices.
+    ///
+    /// Negated expressions select indices that don't belong to any entity, so the result should
+    /// be combined with the [bitset of the alive entities][Entities::bitset] before it is used
+    /// on its own. Passing the query to [`Entities::iter_with()`] does this automatically.
+    pub fn to_bitset(&self) -> BitSetVec {
+        let mut bitset = create_bitset();
+        for (i, word) in bitset.words_mut() {
+            *word = self.expr.word(i);
+        }
+        bitset
+    }
+}
+
+impl<'a> std::ops::Not for Query<'a> {
+    type Output = Self;
+
+    /// Select the entities that aren't selected by the query.
+    fn not(self) -> Self {
+        let expr = match self.expr {
+            Expr::Not(expr) => *expr,
+            expr => Expr::Not(Box::new(expr)),
+        };
+        Self { expr }
+    }
+}
+
+impl<'a> QueryItem for Query<'a> {
+    type Iter = std::iter::Repeat<()>;
+    fn apply_bitset(&self, bitset: &mut BitSetVec) {
+        for (i, word) in bitset.words_mut() {
+            if *word != 0 {
+                *word &= self.expr.word(i);
+            }
+        }
+    }
+
+    fn iter_with_bitset(self, _bitset: Rc<BitSetVec>) -> Self::Iter {
+        std::iter::repeat(())
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<'a> ParQueryItem for Query<'a> {
+    type Fetch = ();
+    fn into_fetch(self) -> Self::Fetch {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    /// A small xorshift random number generator, so the tests are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, max: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % max
+        }
+    }
+
+    /// A query expression that is evaluated one entity at a time, to check the combinators.
+    enum Predicate {
+        All,
+        Set(usize),
+        Not(Box<Predicate>),
+        And(Box<Predicate>, Box<Predicate>),
+        Or(Box<Predicate>, Box<Predicate>),
+        Without(Box<Predicate>, usize),
+    }
+
+    impl Predicate {
+        fn random(rng: &mut Rng, sets: usize, depth: u32) -> Self {
+            let choice = if depth == 0 { 0 } else { rng.below(6) };
+            let sub = |rng: &mut Rng| Box::new(Self::random(rng, sets, depth - 1));
+            match choice {
+                0 if rng.below(8) == 0 => Self::All,
+                0 => Self::Set(rng.below(sets as u64) as usize),
+                1 => Self::Not(sub(rng)),
+                2 | 3 => Self::And(sub(rng), sub(rng)),
+                4 => Self::Or(sub(rng), sub(rng)),
+                _ => Self::Without(sub(rng), rng.below(sets as u64) as usize),
+            }
+        }
+
+        fn test(&self, sets: &[BitSetVec], index: usize) -> bool {
+            match self {
+                Self::All => true,
+                Self::Set(set) => sets[*set].bit_test(index),
+                Self::Not(a) => !a.test(sets, index),
+                Self::And(a, b) => a.test(sets, index) && b.test(sets, index),
+                Self::Or(a, b) => a.test(sets, index) || b.test(sets, index),
+                Self::Without(a, set) => a.test(sets, index) && !sets[*set].bit_test(index),
+            }
+        }
+
+        fn query<'a>(&self, sets: &'a [BitSetVec]) -> Query<'a> {
+            match self {
+                Self::All => Query::new(),
+                Self::Set(set) => with(&sets[*set]),
+                Self::Not(a) => !a.query(sets),
+                Self::And(a, b) => a.query(sets).and(b.query(sets)),
+                Self::Or(a, b) => a.query(sets).or(b.query(sets)),
+                Self::Without(a, set) => a.query(sets).without(&sets[*set]),
+            }
+        }
+    }
+
+    #[test]
+    fn combinators_match_predicates() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        // The sets are sparse, dense, and in between, and only use the first few words, so most
+        // words are empty, like in a real world.
+        let sets = [2, 8, 64, 1024]
+            .iter()
+            .map(|&every| {
+                let mut set = create_bitset();
+                for i in 0..4096 {
+                    if rng.below(every) == 0 {
+                        set.bit_set(i);
+                    }
+                }
+                set
+            })
+            .collect::<Vec<_>>();
+        let mut alive = create_bitset();
+        for i in (0..5000).filter(|i| i % 7 != 0) {
+            alive.bit_set(i);
+        }
+
+        for _ in 0..200 {
+            let predicate = Predicate::random(&mut rng, sets.len(), 4);
+            let query = predicate.query(&sets);
+
+            let bitset = query.to_bitset();
+            let mut joined = alive.clone();
+            query.apply_bitset(&mut joined);
+            for index in 0..BITSET_SIZE {
+                let expected = predicate.test(&sets, index);
+                assert_eq!(bitset.bit_test(index), expected);
+                assert_eq!(joined.bit_test(index), expected && alive.bit_test(index));
+            }
+        }
+    }
+
+    #[derive(Clone, TypeUlid)]
+    #[ulid = "01GQSK2F3G4H5J6K7M8N9P0Q1R"]
+    struct Enemy;
+
+    #[derive(Clone, TypeUlid)]
+    #[ulid = "01GQSK2G3H4J5K6M7N8P9Q0R1S"]
+    struct Burning;
+
+    #[derive(Clone, TypeUlid)]
+    #[ulid = "01GQSK2H3J4K5M6N7P8Q9R0S1T"]
+    struct Cursed;
+
+    #[derive(Clone, TypeUlid)]
+    #[ulid = "01GQSK2J3K4M5N6P7Q8R9S0T1V"]
+    struct Invulnerable;
+
+    #[test]
+    fn iter_with_query() {
+        let mut world = World::new();
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut enemies: CompMut<Enemy>,
+                 mut burning: CompMut<Burning>,
+                 mut cursed: CompMut<Cursed>,
+                 mut invulnerable: CompMut<Invulnerable>| {
+                    for i in 0..64 {
+                        let entity = entities.create();
+                        if i % 2 == 0 {
+                            enemies.insert(entity, Enemy);
+                        }
+                        if i % 3 == 0 {
+                            burning.insert(entity, Burning);
+                        }
+                        if i % 5 == 0 {
+                            cursed.insert(entity, Cursed);
+                        }
+                        if i % 7 == 0 {
+                            invulnerable.insert(entity, Invulnerable);
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        let selected = world
+            .run_system(
+                |entities: Res<Entities>,
+                 enemies: Comp<Enemy>,
+                 burning: Comp<Burning>,
+                 cursed: Comp<Cursed>,
+                 invulnerable: Comp<Invulnerable>| {
+                    let query = with(&enemies)
+                        .with(&burning)
+                        .or(with(&cursed))
+                        .without(&invulnerable);
+                    entities
+                        .iter_with(query)
+                        .map(|(entity, ())| entity.index())
+                        .collect::<Vec<_>>()
+                },
+            )
+            .unwrap();
+        let expected = (0..64)
+            .filter(|i| ((i % 2 == 0 && i % 3 == 0) || i % 5 == 0) && i % 7 != 0)
+            .collect::<Vec<_>>();
+        assert_eq!(selected, expected);
+    }
+}
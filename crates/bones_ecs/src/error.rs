@@ -27,11 +27,169 @@ pub enum EcsError {
     SystemError(Box<dyn Error + Send>),
     /// This happens when two Rust types have the same [`TypeUlid`][crate::ulid::TypeUlid], which
     /// must not happen in the same [`World`][crate::world::World].
-    #[error(
-        "Attempted to initialize resource/component with the same TypeUlid as another type \
-        that has already been initialized."
-    )]
-    TypeUlidCollision,
+    #[error("{0}")]
+    TypeUlidCollision(TypeUlidCollisionError),
+    /// A [`SystemStage`][crate::stage::SystemStage] with the given label could not be found.
+    #[error("{0}")]
+    StageNotFound(StageNotFoundError),
+    /// A [`SystemStage`][crate::stage::SystemStage] with the same [`Ulid`][crate::ulid::Ulid] as
+    /// another stage was added to the same [`SystemStages`][crate::stage::SystemStages].
+    #[error("Stage with label `{0}` ( {1} ) already exists.")]
+    StageAlreadyExists(String, crate::ulid::Ulid),
+    /// The ordering constraints of the systems in a stage form a cycle, so there is no valid order
+    /// to run them in.
+    ///
+    /// Contains the name of the stage, and the names of the systems that could not be ordered.
+    #[error("Systems in stage `{0}` have cyclic ordering constraints: {1:?}")]
+    SystemOrderCycle(String, Vec<String>),
+    /// The ordering constraints of the stages in a [`SystemStages`][crate::stage::SystemStages]
+    /// form a cycle.
+    ///
+    /// Contains the names of the stages in the cycle, in the order that they would have to run,
+    /// with the first stage repeated at the end.
+    #[error("Stages have cyclic ordering constraints: {}", .0.join(" -> "))]
+    StageOrderCycle(Vec<String>),
+    /// A startup system was added to a [`SystemStages`][crate::stage::SystemStages] after the
+    /// startup systems had already been run.
+    #[error("Startup systems have already been run, new startup systems cannot be added.")]
+    StartupAlreadyRan,
+    /// Two parameters of a system access the same resource or component, and at least one of
+    /// them mutably, so the system would fail to borrow them when it is run.
+    #[error("{0}")]
+    ConflictingParams(SystemParamConflictError),
+}
+
+/// A [`SystemStage`][crate::stage::SystemStage] with the given label could not be found in a
+/// [`SystemStages`][crate::stage::SystemStages] collection.
+#[derive(Debug, thiserror::Error)]
+#[error("Stage with label `{name}` ( {id} ) doesn't exist. Existing stages: {known_stages:?}")]
+pub struct StageNotFoundError {
+    /// The name of the label.
+    pub name: String,
+    /// The id of the label.
+    pub id: crate::ulid::Ulid,
+    /// The names of the stages that do exist.
+    pub known_stages: Vec<String>,
+}
+
+/// The parameters of a system [conflict][crate::system::AccessConflict] with each other.
+#[derive(Debug, thiserror::Error)]
+#[error("System `{system}` has conflicting parameters: {conflict}")]
+pub struct SystemParamConflictError {
+    /// The name of the system.
+    pub system: String,
+    /// The conflict between its parameters.
+    pub conflict: crate::system::AccessConflict,
+}
+
+/// A resource or component type was registered with the same [`TypeUlid`][crate::ulid::TypeUlid]
+/// as a different Rust type.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Attempted to initialize resource/component `{new}` with the same TypeUlid ( {id} ) as \
+    `{existing}`, which has already been initialized."
+)]
+pub struct TypeUlidCollisionError {
+    /// The ULID that both types have.
+    pub id: crate::ulid::Ulid,
+    /// The name of the type that was registered first.
+    pub existing: &'static str,
+    /// The name of the type that was registered with the same ULID afterwards.
+    pub new: &'static str,
+}
+
+/// An error returned by a system, along with the names of the system and the stage it was run in.
+#[derive(Debug, thiserror::Error)]
+#[error("System `{system}` in stage `{stage}` failed: {error}")]
+pub struct SystemError {
+    /// The name of the stage that the system was run in.
+    pub stage: String,
+    /// The name of the system that failed.
+    pub system: String,
+    /// The error returned by the system.
+    pub error: anyhow::Error,
+}
+
+/// The error returned when looking for the single entity matching a query, and there isn't exactly
+/// one.
+///
+/// Contains the type name of the component or query that was looked for.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SingleError {
+    /// No entity matched the query.
+    #[error("Expected a single entity with `{0}`, but none were found.")]
+    NoEntities(&'static str),
+    /// More than one entity matched the query.
+    #[error("Expected a single entity with `{0}`, but multiple were found.")]
+    MultipleEntities(&'static str),
+}
+
+impl SingleError {
+    /// Replace the type name in the error.
+    pub(crate) fn with_name(self, name: &'static str) -> Self {
+        match self {
+            SingleError::NoEntities(_) => SingleError::NoEntities(name),
+            SingleError::MultipleEntities(_) => SingleError::MultipleEntities(name),
+        }
+    }
+}
+
+/// The error returned by `get_many_mut()` on the component stores, when the components can't all
+/// be borrowed at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum GetManyMutError {
+    /// The entity doesn't have a component in the store.
+    #[error("Entity {0:?} doesn't have the component")]
+    Missing(crate::entities::Entity),
+    /// The entity was passed more than once, so it's component would be borrowed mutably more than
+    /// once.
+    #[error("Entity {0:?} was requested more than once")]
+    Duplicate(crate::entities::Entity),
+}
+
+/// The error returned by
+/// [`UntypedComponentStore::insert_raw()`][crate::components::UntypedComponentStore::insert_raw]
+/// when the data doesn't have the size of the components in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Can't insert {found} bytes into the `{type_name}` store, which holds components of {expected} bytes")]
+pub struct ComponentSizeError {
+    /// The type name of the components in the store.
+    pub type_name: &'static str,
+    /// The size of the components in the store.
+    pub expected: usize,
+    /// The size of the data that was inserted.
+    pub found: usize,
+}
+
+/// The error returned by [`World::clone_entity()`][crate::World::clone_entity].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CloneEntityError {
+    /// The entity to clone is not alive.
+    #[error("Can't clone entity {0:?} because it isn't alive")]
+    NotAlive(crate::entities::Entity),
+    /// The entity has a component that was marked as not clonable with
+    /// [`World::forbid_clone()`][crate::World::forbid_clone].
+    #[error("Can't clone entity {entity:?} because its `{type_name}` ( {id} ) component can't be cloned")]
+    NotClonable {
+        /// The entity that was being cloned.
+        entity: crate::entities::Entity,
+        /// The type name of the component.
+        type_name: &'static str,
+        /// The [`TypeUlid`][crate::ulid::TypeUlid] of the component.
+        id: crate::ulid::Ulid,
+    },
+}
+
+/// The error returned when looking for the single entity with a [`Name`][crate::names::Name],
+/// and there isn't exactly one.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NameLookupError {
+    /// No entity has the name.
+    #[error("No entity is named `{0}`")]
+    NotFound(String),
+    /// More than one entity has the name.
+    #[error("Multiple entities are named `{0}`")]
+    Ambiguous(String),
 }
 
 /// The result of a `System`'s execution.
@@ -0,0 +1,409 @@
+//! Double-buffered event channels, for sending messages between systems.
+
+use crate::prelude::*;
+
+/// Resource containing the events of type `T` that have been sent in the current and previous
+/// frames.
+///
+/// Events are sent with an [`EventWriter`], and read with an [`EventReader`]. Every reader keeps
+/// track of it's own position in the queue, so that each reader sees every event exactly once.
+///
+/// Events are double-buffered: when the [`Events`] are [updated][Self::update], the events sent
+/// since the last update are kept around until the next update. When using
+/// [`SystemStages::with_core_stages()`], the update happens at the start of
+/// [`CoreStage::First`], so events survive until the end of the frame after the one they were
+/// sent in. That way systems in earlier stages don't miss events sent by systems in later stages.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// #[derive(Clone, TypeUlid)]
+/// #[ulid = "01GPVB1N3CX2E9HZ9Z3ZXAM3BN"]
+/// struct PlayerDied {
+///     player: usize,
+/// }
+///
+/// fn kill_player(mut died: EventWriter<PlayerDied>) {
+///     died.send(PlayerDied { player: 1 });
+/// }
+///
+/// fn print_deaths(mut died: EventReader<PlayerDied>) {
+///     for event in died.iter() {
+///         println!("Player {} died", event.player);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Events<T> {
+    /// The events that were sent before the most recent update.
+    previous: Vec<T>,
+    /// The events that were sent since the most recent update.
+    current: Vec<T>,
+    /// The number of events sent before the first event in `previous`.
+    previous_start: usize,
+    /// The number of events sent before the first event in `current`.
+    current_start: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+            current_start: 0,
+        }
+    }
+}
+
+/// The [`TypeUlid`] of [`Events<T>`] is derived from the [`TypeUlid`] of `T`, so that every
+/// event type gets it's own resource.
+impl<T: TypeUlid> TypeUlid for Events<T> {
+    const ULID: Ulid = Ulid(2022217004496468707346213413460779946 ^ T::ULID.0);
+}
+
+impl<T> Events<T> {
+    /// Send an event.
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+    }
+
+    /// Send every event in the iterator.
+    pub fn send_batch<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        self.current.extend(events);
+    }
+
+    /// Swap the event buffers, dropping the events that were sent before the previous update.
+    ///
+    /// This is called once every frame by the [`event_update_system`].
+    pub fn update(&mut self) {
+        self.previous_start = self.current_start;
+        self.current_start += self.current.len();
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Remove all of the events in both buffers.
+    ///
+    /// Readers will not see any of the removed events.
+    pub fn clear(&mut self) {
+        self.update();
+        self.update();
+    }
+
+    /// Returns the total number of events that have ever been sent.
+    pub fn event_count(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+
+    /// Returns the number of events that are currently stored, in both buffers.
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    /// Returns `true` if there are no events stored in either buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over all of the stored events, from oldest to newest, without affecting any
+    /// readers.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.previous.iter().chain(self.current.iter())
+    }
+}
+
+/// A reader's position in an [`Events`] queue.
+///
+/// This is used by [`EventReader`] to remember which events it has already read, and may also be
+/// used directly to read events from systems that take the [`World`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EventCursor {
+    /// The [`event_count()`][Events::event_count] when the events were last read.
+    last_event_count: usize,
+}
+
+impl EventCursor {
+    /// Iterate over the events that have been sent since the last call to `read()`.
+    ///
+    /// Events that were dropped by two calls to [`Events::update()`] before being read are
+    /// skipped.
+    pub fn read<'e, T>(&mut self, events: &'e Events<T>) -> impl Iterator<Item = &'e T> {
+        let previous = self
+            .last_event_count
+            .saturating_sub(events.previous_start)
+            .min(events.previous.len());
+        let current = self
+            .last_event_count
+            .saturating_sub(events.current_start)
+            .min(events.current.len());
+        self.last_event_count = events.event_count();
+
+        events.previous[previous..]
+            .iter()
+            .chain(events.current[current..].iter())
+    }
+
+    /// Returns the number of events that haven't been read yet.
+    pub fn len<T>(&self, events: &Events<T>) -> usize {
+        let first_stored = events.previous_start;
+        events.event_count() - self.last_event_count.max(first_stored)
+    }
+
+    /// Returns `true` if there are no events that haven't been read yet.
+    pub fn is_empty<T>(&self, events: &Events<T>) -> bool {
+        self.len(events) == 0
+    }
+
+    /// Mark all of the events as read, without reading them.
+    pub fn clear<T>(&mut self, events: &Events<T>) {
+        self.last_event_count = events.event_count();
+    }
+}
+
+/// [`SystemParam`] for reading the [`Events`] of type `T`.
+///
+/// Each [`EventReader`] only sees the events that were sent since the last time it's system was
+/// run, as long as it's system runs at least once every frame.
+pub struct EventReader<'a, T: TypedEcsData> {
+    events: AtomicRef<'a, Events<T>>,
+    cursor: &'a mut EventCursor,
+}
+
+impl<'a, T: TypedEcsData> EventReader<'a, T> {
+    /// Iterate over the events that haven't been read yet.
+    pub fn iter(&mut self) -> impl Iterator<Item = &T> {
+        self.cursor.read(&*self.events)
+    }
+
+    /// Returns the number of events that haven't been read yet.
+    pub fn len(&self) -> usize {
+        self.cursor.len(&*self.events)
+    }
+
+    /// Returns `true` if there are no events that haven't been read yet.
+    pub fn is_empty(&self) -> bool {
+        self.cursor.is_empty(&*self.events)
+    }
+
+    /// Mark all of the events as read, without reading them.
+    pub fn clear(&mut self) {
+        self.cursor.clear(&*self.events)
+    }
+}
+
+/// [`SystemParam`] for sending events of type `T`.
+pub struct EventWriter<'a, T: TypedEcsData> {
+    events: AtomicRefMut<'a, Events<T>>,
+}
+
+impl<'a, T: TypedEcsData> EventWriter<'a, T> {
+    /// Send an event.
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    /// Send every event in the iterator.
+    pub fn send_batch<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        self.events.send_batch(events);
+    }
+}
+
+impl<'a, T: TypedEcsData> SystemParam for EventReader<'a, T> {
+    type State = AtomicResource<Events<T>>;
+    type Param<'p> = EventReader<'p, T>;
+    type Local = EventCursor;
+
+    fn initialize(world: &mut World) {
+        world.init_events::<T>();
+    }
+    fn access(access: &mut SystemAccess) {
+        access.resource_reads.insert(Events::<T>::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        world.resources.get::<Events<T>>()
+    }
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        EventReader {
+            events: state.borrow(),
+            cursor: local,
+        }
+    }
+}
+
+impl<'a, T: TypedEcsData> SystemParam for EventWriter<'a, T> {
+    type State = AtomicResource<Events<T>>;
+    type Param<'p> = EventWriter<'p, T>;
+    type Local = ();
+
+    fn initialize(world: &mut World) {
+        world.init_events::<T>();
+    }
+    fn access(access: &mut SystemAccess) {
+        access.resource_writes.insert(Events::<T>::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        world.resources.get::<Events<T>>()
+    }
+    fn borrow<'s>(state: &'s mut Self::State, _local: &'s mut Self::Local) -> Self::Param<'s> {
+        EventWriter {
+            events: state.borrow_mut(),
+        }
+    }
+}
+
+/// Resource containing the update functions for every type of [`Events`] in the world.
+///
+/// Event types are registered with [`World::init_events()`], which is called automatically by
+/// the [`EventReader`] and [`EventWriter`] parameters.
+#[derive(Clone, Default, TypeUlid)]
+#[ulid = "01GPVB8ZP2T5XKGQ1AMJ7V3DNE"]
+pub struct EventUpdates {
+    updates: UlidMap<fn(&World)>,
+}
+
+impl EventUpdates {
+    /// Register the [`Events`] of type `T` to be updated by the [`event_update_system`].
+    pub fn register<T: TypedEcsData>(&mut self) {
+        self.updates
+            .entry(Events::<T>::ULID)
+            .or_insert(|world| world.resources.get::<Events<T>>().borrow_mut().update());
+    }
+}
+
+/// System that [updates][Events::update] all of the registered [`Events`] resources.
+///
+/// This is added to the start of [`CoreStage::First`] by [`SystemStages::with_core_stages()`].
+/// When using custom stages, it should be added to a stage that runs once every frame.
+pub fn event_update_system(world: &World) {
+    let Some(updates) = world.resources.try_get::<EventUpdates>() else {
+        return;
+    };
+    let updates = updates.borrow();
+    for update in updates.updates.values() {
+        update(world);
+    }
+}
+
+impl World {
+    /// Initialize the [`Events`] resource for events of type `T`, and register it to be updated by
+    /// the [`event_update_system`].
+    pub fn init_events<T: TypedEcsData>(&mut self) {
+        self.resources.init::<Events<T>>();
+        self.resources.init::<EventUpdates>();
+        self.resources
+            .get::<EventUpdates>()
+            .borrow_mut()
+            .register::<T>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GPVBCM0CMN7BTXS1Z4DD9D0C"]
+    struct Hit(u32);
+
+    /// Resource used to record the events seen by the readers.
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPVBD2Z4W1K9K6PTNJ7QXEGC"]
+    struct Seen(Vec<(&'static str, u32)>);
+
+    #[test]
+    fn multiple_readers() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::Update, |mut hits: EventWriter<Hit>| {
+                hits.send_batch([Hit(1), Hit(2)]);
+            })
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                |mut hits: EventReader<Hit>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(hits.iter().map(|hit| ("a", hit.0)));
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                |mut hits: EventReader<Hit>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(hits.iter().map(|hit| ("b", hit.0)));
+                },
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+        stages.run(&mut world).unwrap();
+
+        assert_eq!(
+            world.resources.get::<Seen>().borrow().0,
+            vec![("a", 1), ("a", 2), ("b", 1), ("b", 2)]
+        );
+    }
+
+    #[test]
+    fn events_across_two_frames() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            // The reader runs in an earlier stage than the writer, so it only sees the events on
+            // the next frame.
+            .add_system_to_stage(
+                CoreStage::First,
+                |mut hits: EventReader<Hit>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(hits.iter().map(|hit| ("first", hit.0)));
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::Update,
+                |mut hits: EventWriter<Hit>, mut count: ResMut<u32>| {
+                    *count += 1;
+                    hits.send(Hit(*count));
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::Last,
+                |mut hits: EventReader<Hit>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(hits.iter().map(|hit| ("last", hit.0)));
+                },
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(world.resources.get::<Seen>().borrow().0, vec![("last", 1)]);
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(
+            world.resources.get::<Seen>().borrow().0,
+            vec![("last", 1), ("first", 1), ("last", 2)]
+        );
+    }
+
+    #[test]
+    fn buffers_cleared() {
+        let mut events = Events::default();
+        let mut cursor = EventCursor::default();
+
+        events.send(Hit(1));
+        events.update();
+        events.send(Hit(2));
+        assert_eq!(events.len(), 2);
+        assert_eq!(cursor.len(&events), 2);
+
+        // The first event is dropped after the second update, even though it wasn't read.
+        events.update();
+        assert_eq!(events.len(), 1);
+        assert_eq!(cursor.len(&events), 1);
+        assert_eq!(cursor.read(&events).collect::<Vec<_>>(), vec![&Hit(2)]);
+        assert!(cursor.is_empty(&events));
+
+        events.send(Hit(3));
+        events.clear();
+        assert!(events.is_empty());
+        assert_eq!(cursor.read(&events).count(), 0);
+
+        events.send(Hit(4));
+        assert_eq!(cursor.read(&events).collect::<Vec<_>>(), vec![&Hit(4)]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Parent/child relationships between entities.
+//!
+//! The hierarchy is stored in two components: every child entity has a [`Parent`] component, and
+//! every parent entity has a [`Children`] component listing its children. Use the [`Hierarchy`]
+//! system parameter to modify the hierarchy, so that the two sides stay consistent.
+
+use smallvec::SmallVec;
+
+use crate::prelude::*;
+
+/// Component containing the parent of an entity.
+///
+/// This is set with [`Hierarchy::set_parent()`], which also updates the [`Children`] of the
+/// parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+#[ulid = "01GQE3V8Z5K1N7R0TX4MB6WC2D"]
+pub struct Parent(Entity);
+
+impl Parent {
+    /// Get the parent entity.
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Component containing the children of an entity.
+///
+/// The children are kept in the order that they were added in, so that systems walking down the
+/// hierarchy, such as transform propagation, visit them in a stable order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, TypeUlid)]
+#[ulid = "01GQE3VGQ2D8F6A3YH0PJ9NS5R"]
+pub struct Children(SmallVec<[Entity; 8]>);
+
+impl Children {
+    /// Get the child entities.
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+
+    /// Iterate over the child entities.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns the number of children.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no children.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl MapEntities for Parent {
+    fn map_entities(&mut self, map: &EntityMap) {
+        self.0 = map.map(self.0);
+    }
+}
+
+impl MapEntities for Children {
+    fn map_entities(&mut self, map: &EntityMap) {
+        for child in &mut self.0 {
+            *child = map.map(*child);
+        }
+    }
+}
+
+/// [`SystemParam`] for reading and modifying the entity hierarchy.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// fn spawn_player(mut entities: ResMut<Entities>, mut hierarchy: Hierarchy) {
+///     let player = entities.create();
+///     let weapon = entities.create();
+///     hierarchy.set_parent(weapon, player);
+///
+///     // Later, despawn the player along with its weapon.
+///     hierarchy.despawn_recursive(&mut entities, player);
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct Hierarchy<'a> {
+    /// The [`Parent`] components.
+    pub parents: CompMut<'a, Parent>,
+    /// The [`Children`] components.
+    pub children: CompMut<'a, Children>,
+}
+
+impl<'a> Hierarchy<'a> {
+    /// Get the parent of an entity, if it has one.
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(entity).map(Parent::get)
+    }
+
+    /// Get the children of an entity.
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.children
+            .get(entity)
+            .map(Children::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Make `parent` the parent of `child`, removing `child` from the children of its previous
+    /// parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is `child`, or is one of its descendants, because the hierarchy can't
+    /// contain cycles.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            assert!(
+                entity != child,
+                "Entity {child:?} can't be the parent of itself or one of its ancestors"
+            );
+            ancestor = self.parent(entity);
+        }
+
+        self.remove_parent(child);
+        self.parents.insert(child, Parent(parent));
+        if let Some(mut children) = self.children.get_mut(parent) {
+            children.0.push(child);
+        } else {
+            self.children
+                .insert(parent, Children(SmallVec::from_slice(&[child])));
+        }
+    }
+
+    /// Remove the parent of `child`, removing it from the children of the parent, and returning the
+    /// parent if it had one.
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.parents.remove(child)?.0;
+
+        let mut children = self.children.get_mut(parent)?;
+        children.0.retain(|x| *x != child);
+        if children.is_empty() {
+            self.children.remove(parent);
+        }
+
+        Some(parent)
+    }
+
+    /// Kill an entity, along with all of its descendants.
+    ///
+    /// The entity is removed from the children of its parent. Like [`Entities::kill()`], the rest
+    /// of the components of the killed entities are removed by the next [`World::maintain()`].
+    pub fn despawn_recursive(&mut self, entities: &mut Entities, entity: Entity) {
+        self.remove_parent(entity);
+
+        let mut despawn = vec![entity];
+        while let Some(entity) = despawn.pop() {
+            if let Some(children) = self.children.remove(entity) {
+                despawn.extend(children.0);
+            }
+            self.parents.remove(entity);
+            entities.kill(entity);
+        }
+    }
+}
+
+/// System that removes killed entities from the hierarchy.
+///
+/// Entities whose parent was killed without [`Hierarchy::despawn_recursive()`] lose their
+/// [`Parent`], and killed entities are removed from the [`Children`] of their parent.
+///
+/// This should be added to the [`SystemStages`] of games that kill entities that are part of the
+/// hierarchy with [`Entities::kill()`] or [`Commands::despawn()`].
+pub fn cleanup_hierarchy(entities: Res<Entities>, mut hierarchy: Hierarchy) {
+    let Hierarchy { parents, children } = &mut hierarchy;
+
+    let orphans = entities
+        .iter_with(&*parents)
+        .filter(|(_, parent)| !entities.is_alive(parent.get()))
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    for entity in orphans {
+        parents.remove(entity);
+    }
+
+    let mut childless = Vec::new();
+    for (entity, mut children) in entities.iter_with(&mut *children) {
+        if children.iter().any(|child| !entities.is_alive(child)) {
+            children.0.retain(|child| entities.is_alive(*child));
+        }
+        if children.is_empty() {
+            childless.push(entity);
+        }
+    }
+    for entity in childless {
+        children.remove(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn set_and_remove_parent() {
+        let mut world = World::new();
+        world
+            .run_system(|mut entities: ResMut<Entities>, mut hierarchy: Hierarchy| {
+                let [a, b, c] = [entities.create(), entities.create(), entities.create()];
+
+                hierarchy.set_parent(b, a);
+                hierarchy.set_parent(c, a);
+                assert_eq!(hierarchy.children(a), [b, c]);
+                assert_eq!(hierarchy.parent(b), Some(a));
+
+                // Moving a child removes it from its previous parent.
+                hierarchy.set_parent(c, b);
+                assert_eq!(hierarchy.children(a), [b]);
+                assert_eq!(hierarchy.children(b), [c]);
+
+                assert_eq!(hierarchy.remove_parent(c), Some(b));
+                assert_eq!(hierarchy.remove_parent(c), None);
+                assert!(hierarchy.children(b).is_empty());
+                assert!(!hierarchy.children.contains(b));
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_parent_cycle() {
+        let mut world = World::new();
+        world
+            .run_system(|mut entities: ResMut<Entities>, mut hierarchy: Hierarchy| {
+                let [a, b, c] = [entities.create(), entities.create(), entities.create()];
+                hierarchy.set_parent(b, a);
+                hierarchy.set_parent(c, b);
+                hierarchy.set_parent(a, c);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn despawn_recursive() {
+        let mut world = World::new();
+        world
+            .run_system(|mut entities: ResMut<Entities>, mut hierarchy: Hierarchy| {
+                let root = entities.create();
+                let player = entities.create();
+                let weapon = entities.create();
+                let hitbox = entities.create();
+                let other = entities.create();
+                hierarchy.set_parent(player, root);
+                hierarchy.set_parent(other, root);
+                hierarchy.set_parent(weapon, player);
+                hierarchy.set_parent(hitbox, weapon);
+
+                hierarchy.despawn_recursive(&mut entities, player);
+                for entity in [player, weapon, hitbox] {
+                    assert!(!entities.is_alive(entity));
+                    assert_eq!(hierarchy.parent(entity), None);
+                    assert!(hierarchy.children(entity).is_empty());
+                }
+                assert!(entities.is_alive(root));
+                assert!(entities.is_alive(other));
+                assert_eq!(hierarchy.children(root), [other]);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn cleanup_killed_entities() {
+        let mut world = World::new();
+        let [parent, child, grandchild] = world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut hierarchy: Hierarchy| -> anyhow::Result<_> {
+                    let [a, b, c] = [entities.create(), entities.create(), entities.create()];
+                    hierarchy.set_parent(b, a);
+                    hierarchy.set_parent(c, b);
+
+                    // Kill the middle of the hierarchy, without despawning recursively.
+                    entities.kill(b);
+                    Ok([a, b, c])
+                },
+            )
+            .unwrap();
+
+        world.run_system(cleanup_hierarchy).unwrap();
+        world
+            .run_system(move |hierarchy: Hierarchy| {
+                assert!(hierarchy.children(parent).is_empty());
+                assert_eq!(hierarchy.parent(grandchild), None);
+                // The components of the killed entity itself are removed by `World::maintain()`.
+                assert_eq!(hierarchy.children(child), [grandchild]);
+            })
+            .unwrap();
+    }
+}
@@ -3,6 +3,9 @@
 #![deny(rustdoc::all)]
 #![warn(missing_docs)]
 
+// Allows the derive macros to refer to `::bones_ecs` from inside this crate.
+extern crate self as bones_ecs;
+
 pub mod atomic {
     //! Atomic Refcell implmentation.
     //!
@@ -12,11 +15,26 @@ pub mod atomic {
     pub use atomic_refcell::*;
 }
 pub mod bitset;
+pub mod change_detection;
+pub mod cloning;
+pub mod commands;
 pub mod components;
+#[cfg(feature = "debug")]
+mod debug;
 pub mod entities;
+pub mod events;
+pub mod hierarchy;
+pub mod memory;
+pub mod names;
+pub mod reflect;
 pub mod resources;
+pub mod scene;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod snapshot;
 pub mod stage;
 pub mod system;
+pub mod time;
 pub mod ulid;
 
 mod error;
@@ -35,9 +53,14 @@ pub mod prelude {
     };
 
     pub use crate::{
-        bitset::*, components::*, default, entities::*, error::*, resources::*, stage::*,
-        system::*, ulid::*, EcsData, RawFns, TypedEcsData, World,
+        bitset::*, change_detection::*, cloning::*, commands::*, components::*, default,
+        entities::*, error::*, events::*, hierarchy::*, memory::*, names::*, reflect::*,
+        resources::*, scene::*, snapshot::*, stage::*, system::*, time::*, ulid::*, EcsData, RawEq,
+        RawFns, TypedEcsData, World,
     };
+
+    #[cfg(feature = "serde")]
+    pub use crate::serialize::*;
 }
 
 /// Helper trait that is auto-implemented for anything that may be stored in the ECS's untyped
@@ -137,6 +160,45 @@ impl<T: Clone> RawFns for T {
     }
 }
 
+/// Helper trait that is auto-implemented for all types that implement [`PartialEq`]. Provides a
+/// comparison function for raw pointers.
+///
+/// This is used to register how the components of an
+/// [`UntypedComponentStore`][crate::components::UntypedComponentStore] are compared, with
+/// [`set_eq_fn()`][crate::components::UntypedComponentStore::set_eq_fn], so that
+/// [`WorldSnapshot::diff()`][crate::snapshot::WorldSnapshot::diff] can find the changed
+/// components.
+pub trait RawEq {
+    /// Compare the values at `a` and `b`.
+    ///
+    /// # Safety
+    /// - Both pointers must point to valid instances of the type that this implementation is
+    /// assocated with.
+    unsafe extern "C" fn raw_eq(a: *const u8, b: *const u8) -> bool;
+}
+
+impl<T: PartialEq> RawEq for T {
+    unsafe extern "C" fn raw_eq(a: *const u8, b: *const u8) -> bool {
+        use std::io::{self, Write};
+
+        let result = std::panic::catch_unwind(|| *(a as *const T) == *(b as *const T));
+
+        match result {
+            Ok(eq) => eq,
+            Err(_) => {
+                writeln!(
+                    io::stderr(),
+                    "Rust type {} panicked in equality implementation.\n\
+                    Unable to panic across C ABI: aborting.",
+                    std::any::type_name::<T>()
+                )
+                .ok();
+                std::process::abort();
+            }
+        }
+    }
+}
+
 /// Free-standing, shorter equivalent to [`Default::default()`].
 #[inline]
 pub fn default<T: Default>() -> T {
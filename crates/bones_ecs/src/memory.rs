@@ -0,0 +1,264 @@
+//! Memory usage reports, and reclaiming the memory of component stores.
+
+use crate::prelude::*;
+
+/// A report of the memory used by the component stores and resources of a [`World`].
+///
+/// Created with [`World::memory_usage()`]. Only the memory allocated by the stores themselves is
+/// counted, not heap memory owned by the components or resources, such as the contents of a
+/// `Vec`.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryUsage {
+    /// The memory used by each component store.
+    pub components: Vec<ComponentMemoryUsage>,
+    /// The memory used by each resource.
+    pub resources: Vec<ResourceMemoryUsage>,
+}
+
+impl MemoryUsage {
+    /// Returns the total number of bytes used by the component stores and resources.
+    pub fn total_bytes(&self) -> usize {
+        self.components.iter().map(|x| x.bytes).sum::<usize>()
+            + self.resources.iter().map(|x| x.bytes).sum::<usize>()
+    }
+}
+
+/// The memory used by a component store, in a [`MemoryUsage`] report.
+#[derive(Clone, Debug)]
+pub struct ComponentMemoryUsage {
+    /// The [`TypeUlid`] of the component.
+    pub id: Ulid,
+    /// The number of components in the store.
+    pub len: usize,
+    /// The number of components that the store has room for without reallocating.
+    pub capacity: usize,
+    /// The number of bytes allocated by the store.
+    pub bytes: usize,
+}
+
+/// The memory used by a resource, in a [`MemoryUsage`] report.
+#[derive(Clone, Debug)]
+pub struct ResourceMemoryUsage {
+    /// The [`TypeUlid`] of the resource.
+    pub id: Ulid,
+    /// The size of the resource in bytes.
+    pub bytes: usize,
+}
+
+impl World {
+    /// Create a report of the memory used by the component stores and resources.
+    ///
+    /// The entries are sorted by the number of bytes that they use, largest first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the component stores are mutably borrowed.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut components = self
+            .components
+            .components
+            .iter()
+            .map(|(&id, components)| {
+                let components = components.borrow();
+                ComponentMemoryUsage {
+                    id,
+                    len: components.len(),
+                    capacity: components.capacity(),
+                    bytes: components.allocated_bytes(),
+                }
+            })
+            .collect::<Vec<_>>();
+        components.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        let untyped = self.resources.untyped();
+        let mut resources = untyped
+            .ids()
+            .map(|id| ResourceMemoryUsage {
+                id,
+                bytes: untyped.layout(id).map_or(0, |layout| layout.size()),
+            })
+            .collect::<Vec<_>>();
+        resources.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        MemoryUsage {
+            components,
+            resources,
+        }
+    }
+
+    /// [Shrink][UntypedComponentStore::shrink_to_fit] all of the component stores.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the component stores are borrowed.
+    pub fn shrink_component_stores(&mut self) {
+        for components in self.components.components.values() {
+            components.borrow_mut().shrink_to_fit();
+        }
+    }
+}
+
+/// Resource configuring the [`shrink_component_stores_system`].
+///
+/// A component store is shrunk once less than [`min_occupancy`][Self::min_occupancy] of its
+/// capacity has been used for [`frames`][Self::frames] frames in a row, so that stores that only
+/// shrink for a moment don't have to grow again right away.
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GQNR4X1B7DM5TQ0JZ8HE3KWC"]
+pub struct ShrinkPolicy {
+    /// The fraction of the capacity of a store that must be used for it not to be shrunk.
+    pub min_occupancy: f32,
+    /// The number of frames in a row that a store must have a low occupancy for before it is
+    /// shrunk.
+    pub frames: u32,
+    /// The number of frames in a row that each store has had a low occupancy for.
+    low_occupancy_frames: UlidMap<u32>,
+}
+
+impl Default for ShrinkPolicy {
+    fn default() -> Self {
+        Self {
+            min_occupancy: 0.25,
+            frames: 300,
+            low_occupancy_frames: default(),
+        }
+    }
+}
+
+/// System that shrinks the component stores according to the [`ShrinkPolicy`] resource, which is
+/// initialized with its default value if it doesn't exist.
+///
+/// This isn't added to the [`SystemStages`] by default. Games that spawn and despawn large numbers
+/// of entities may add it to [`CoreStage::Last`], after the entities are
+/// [maintained][World::maintain].
+pub fn shrink_component_stores_system(world: &mut World) {
+    world.resources.init::<ShrinkPolicy>();
+    let policy = world.resources.get::<ShrinkPolicy>();
+    let mut policy = policy.borrow_mut();
+
+    for (&id, components) in &world.components.components {
+        let mut components = components.borrow_mut();
+        let capacity = components.capacity();
+        if capacity == 0 || capacity == usize::MAX {
+            policy.low_occupancy_frames.remove(&id);
+            continue;
+        }
+
+        let occupancy = components.len() as f32 / capacity as f32;
+        if occupancy >= policy.min_occupancy {
+            policy.low_occupancy_frames.remove(&id);
+            continue;
+        }
+
+        let frames = policy.low_occupancy_frames.entry(id).or_default();
+        *frames += 1;
+        if *frames >= policy.frames {
+            components.shrink_to_fit();
+            policy.low_occupancy_frames.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQNR5BM3HZ8Y0QKV2D6T9JRF"]
+    struct Bullet([u64; 4]);
+
+    /// Spawn a wave of bullets, and despawn all but the first few.
+    fn bullet_wave(world: &mut World) {
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut bullets: CompMut<Bullet>| {
+                    let spawned = (0..10_000)
+                        .map(|i| {
+                            let entity = entities.create();
+                            bullets.insert(entity, Bullet([i; 4]));
+                            entity
+                        })
+                        .collect::<Vec<_>>();
+                    for &entity in &spawned[10..] {
+                        entities.kill(entity);
+                    }
+                },
+            )
+            .unwrap();
+        world.maintain();
+    }
+
+    fn bullet_capacity(world: &World) -> usize {
+        world.components.get::<Bullet>().borrow().capacity()
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut world = World::new();
+        bullet_wave(&mut world);
+        let capacity = bullet_capacity(&world);
+        assert!(capacity >= 10_000);
+
+        world.shrink_component_stores();
+        let shrunk = bullet_capacity(&world);
+        assert!(shrunk < capacity);
+        assert!(shrunk >= 10);
+        world
+            .run_system(|entities: Res<Entities>, bullets: Comp<Bullet>| {
+                assert_eq!(bullets.len(), 10);
+                for (i, (_, bullet)) in entities.iter_with(&bullets).enumerate() {
+                    assert_eq!(*bullet, Bullet([i as u64; 4]));
+                }
+            })
+            .unwrap();
+
+        // The store grows again when it's needed.
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut bullets: CompMut<Bullet>| {
+                    for _ in 0..100 {
+                        bullets.insert(entities.create(), Bullet([0; 4]));
+                    }
+                    assert_eq!(bullets.len(), 110);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn memory_usage() {
+        let mut world = World::new();
+        bullet_wave(&mut world);
+
+        let usage = world.memory_usage();
+        let bullets = usage
+            .components
+            .iter()
+            .find(|x| x.id == Bullet::ULID)
+            .unwrap();
+        assert_eq!(bullets.len, 10);
+        assert!(bullets.bytes >= 10_000 * std::mem::size_of::<Bullet>());
+        assert!(usage.resources.iter().any(|x| x.id == Entities::ULID));
+
+        world.shrink_component_stores();
+        assert!(world.memory_usage().total_bytes() < usage.total_bytes());
+    }
+
+    #[test]
+    fn shrink_policy() {
+        let mut world = World::new();
+        world.resources.insert(ShrinkPolicy {
+            frames: 3,
+            ..default()
+        });
+        bullet_wave(&mut world);
+        let capacity = bullet_capacity(&world);
+
+        for _ in 0..2 {
+            world.run_system(shrink_component_stores_system).unwrap();
+            assert_eq!(bullet_capacity(&world), capacity);
+        }
+        world.run_system(shrink_component_stores_system).unwrap();
+        assert!(bullet_capacity(&world) < capacity);
+    }
+}
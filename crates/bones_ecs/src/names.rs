@@ -0,0 +1,248 @@
+//! Human-readable entity names, and an index for finding entities by name.
+
+use fxhash::FxHashMap;
+use smallvec::SmallVec;
+
+use crate::prelude::*;
+
+/// Component containing a human-readable name for an entity.
+///
+/// Names don't have to be unique. Use the [`EntityNames`] resource to find entities by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deref, DerefMut, TypeUlid)]
+#[ulid = "01GQMB2E7KX4T9W0ZC5D3RNVHA"]
+pub struct Name(pub String);
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+/// Resource containing an index of the entities with a [`Name`], for finding entities by name.
+///
+/// The index is kept up to date by the [`update_entity_names`] system, which should be added to
+/// the [`SystemStages`] of games that look entities up by name. Changes made since that system
+/// last ran are not reflected in the index.
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[ulid = "01GQMB2PA0F6H8Q3YV1BE7JKSM"]
+pub struct EntityNames {
+    entities: FxHashMap<String, SmallVec<[Entity; 1]>>,
+    names: FxHashMap<Entity, String>,
+}
+
+impl EntityNames {
+    /// Get the entities with the given name, in the order that they were named.
+    pub fn get(&self, name: &str) -> &[Entity] {
+        self.entities
+            .get(name)
+            .map(|entities| entities.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Get the single entity with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no entity, or more than one entity, has the name.
+    pub fn get_single(&self, name: &str) -> Result<Entity, NameLookupError> {
+        match self.get(name) {
+            [entity] => Ok(*entity),
+            [] => Err(NameLookupError::NotFound(name.into())),
+            _ => Err(NameLookupError::Ambiguous(name.into())),
+        }
+    }
+
+    /// Get the name that the index has for the given entity.
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.names.get(&entity).map(String::as_str)
+    }
+
+    /// Returns the number of named entities.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no entities are named.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Add an entity to the index, replacing its previous name.
+    fn insert(&mut self, entity: Entity, name: &str) {
+        if self.name(entity) == Some(name) {
+            return;
+        }
+        self.remove(entity);
+        self.entities.entry(name.into()).or_default().push(entity);
+        self.names.insert(entity, name.into());
+    }
+
+    /// Remove an entity from the index.
+    fn remove(&mut self, entity: Entity) {
+        let Some(name) = self.names.remove(&entity) else {
+            return;
+        };
+        if let Some(entities) = self.entities.get_mut(&name) {
+            entities.retain(|x| *x != entity);
+            if entities.is_empty() {
+                self.entities.remove(&name);
+            }
+        }
+    }
+
+    /// Rebuild the index from scratch.
+    fn rebuild(&mut self, entities: &Entities, names: &Comp<Name>) {
+        *self = Self::default();
+        for (entity, name) in entities.iter_with(names) {
+            self.insert(entity, name);
+        }
+    }
+}
+
+/// System that updates the [`EntityNames`] index with the [`Name`]s that were inserted, changed,
+/// or removed, and the named entities that were killed, since it last ran.
+pub fn update_entity_names(
+    entities: Res<Entities>,
+    names: Comp<Name>,
+    removed: RemovedComponents<Name>,
+    mut index: ResMut<EntityNames>,
+) {
+    for entity in removed.iter().chain(entities.killed().iter().copied()) {
+        index.remove(entity);
+    }
+    for (entity, name) in entities.iter_with(&names) {
+        if names.is_changed(entity) {
+            index.insert(entity, name);
+        }
+    }
+}
+
+impl World {
+    /// Get the single entity with the given name from the [`EntityNames`] index.
+    ///
+    /// # Errors
+    ///
+    /// Errors if no entity, or more than one entity, has the name.
+    pub fn entity_by_name(&self, name: &str) -> Result<Entity, NameLookupError> {
+        match self.resources.try_get::<EntityNames>() {
+            Some(index) => index.borrow().get_single(name),
+            None => Err(NameLookupError::NotFound(name.into())),
+        }
+    }
+
+    /// Rebuild the [`EntityNames`] index, if the world has one, from the [`Name`] components.
+    ///
+    /// This is used after restoring a snapshot, which may have been taken before the index was
+    /// updated with the latest names.
+    pub(crate) fn rebuild_entity_names(&mut self) {
+        let Some(index) = self.resources.try_get::<EntityNames>() else {
+            return;
+        };
+        let Ok(names) = self.components.try_get::<Name>() else {
+            *index.borrow_mut() = EntityNames::default();
+            return;
+        };
+        let entities = self.resources.get::<Entities>();
+        index
+            .borrow_mut()
+            .rebuild(&entities.borrow(), &names.borrow());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn spawn_named(world: &mut World, names: &'static [&'static str]) -> Vec<Entity> {
+        world
+            .run_system(
+                move |mut entities: ResMut<Entities>,
+                      mut name_comps: CompMut<Name>|
+                      -> anyhow::Result<_> {
+                    Ok(names
+                        .iter()
+                        .map(|&name| {
+                            let entity = entities.create();
+                            name_comps.insert(entity, name.into());
+                            entity
+                        })
+                        .collect())
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn lookup_by_name() {
+        let mut world = World::new();
+        let spawned = spawn_named(&mut world, &["player", "enemy", "enemy"]);
+        world.run_system(update_entity_names).unwrap();
+
+        assert_eq!(world.entity_by_name("player").unwrap(), spawned[0]);
+        assert_eq!(
+            world.entity_by_name("enemy"),
+            Err(NameLookupError::Ambiguous("enemy".into()))
+        );
+        assert_eq!(
+            world.entity_by_name("boss"),
+            Err(NameLookupError::NotFound("boss".into()))
+        );
+        let index = world.resources.get::<EntityNames>();
+        assert_eq!(index.borrow().get("enemy"), &spawned[1..]);
+        assert_eq!(index.borrow().name(spawned[0]), Some("player"));
+    }
+
+    #[test]
+    fn index_follows_changes() {
+        let mut world = World::new();
+        let spawned = spawn_named(&mut world, &["player", "enemy", "enemy"]);
+        world.run_system(update_entity_names).unwrap();
+
+        let [player, enemy1, enemy2] = [spawned[0], spawned[1], spawned[2]];
+        world
+            .run_system(
+                move |mut entities: ResMut<Entities>, mut names: CompMut<Name>| {
+                    names.get_mut(player).unwrap().0 = "hero".into();
+                    names.remove(enemy1);
+                    entities.kill(enemy2);
+                },
+            )
+            .unwrap();
+        world.run_system(update_entity_names).unwrap();
+
+        assert_eq!(world.entity_by_name("hero").unwrap(), player);
+        assert!(world.entity_by_name("player").is_err());
+        assert!(world.entity_by_name("enemy").is_err());
+        assert_eq!(world.resources.get::<EntityNames>().borrow().len(), 1);
+    }
+
+    #[test]
+    fn index_survives_restore() {
+        let mut world = World::new();
+        let spawned = spawn_named(&mut world, &["player"]);
+        world.run_system(update_entity_names).unwrap();
+
+        // Take the snapshot before the index is updated with the new name.
+        let boss = spawn_named(&mut world, &["boss"])[0];
+        let snapshot = world.snapshot();
+        world.run_system(update_entity_names).unwrap();
+
+        world
+            .run_system(move |mut names: CompMut<Name>| {
+                names.remove(spawned[0]);
+            })
+            .unwrap();
+        world.run_system(update_entity_names).unwrap();
+        assert!(world.entity_by_name("player").is_err());
+
+        world.restore(&snapshot);
+        assert_eq!(world.entity_by_name("player").unwrap(), spawned[0]);
+        assert_eq!(world.entity_by_name("boss").unwrap(), boss);
+    }
+}
@@ -0,0 +1,292 @@
+//! Access to the components of entities without knowing their types at compile time.
+//!
+//! This is meant for tools such as inspectors, scripting, and save editors, which need to list and
+//! access the components of an entity without per-component boilerplate.
+
+use std::alloc::Layout;
+
+use crate::prelude::*;
+
+/// Information about a component type, for tools that don't know the type at compile time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentInfo {
+    /// The [`TypeUlid`] of the component.
+    pub id: Ulid,
+    /// The name of the component type.
+    ///
+    /// See [`UntypedComponentStore::type_name()`].
+    pub type_name: &'static str,
+    /// The memory layout of the component.
+    pub layout: Layout,
+}
+
+impl ComponentStores {
+    /// Get the [`ComponentInfo`] of the component type with the given ULID, if its store has been
+    /// initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component store is mutably borrowed.
+    pub fn info(&self, id: Ulid) -> Option<ComponentInfo> {
+        let components = self.components.get(&id)?.borrow();
+        Some(ComponentInfo {
+            id,
+            type_name: components.type_name(),
+            layout: components.layout(),
+        })
+    }
+
+    /// Iterate over the [`ComponentInfo`] of every component type that has been initialized,
+    /// sorted by ULID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the component stores are mutably borrowed.
+    pub fn infos(&self) -> impl Iterator<Item = ComponentInfo> + '_ {
+        self.sorted_ids()
+            .into_iter()
+            .filter_map(move |id| self.info(id))
+    }
+
+    /// Get the ULIDs of the initialized component types, in ascending order.
+    fn sorted_ids(&self) -> Vec<Ulid> {
+        let mut ids = self.components.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+}
+
+/// A borrow of the component of an entity, without knowing its type at compile time.
+///
+/// The component store stays borrowed until this is dropped.
+pub struct UntypedComponentRef<'a> {
+    components: AtomicRef<'a, UntypedComponentStore>,
+    ptr: *const u8,
+}
+
+impl<'a> UntypedComponentRef<'a> {
+    /// Get the name of the component type.
+    pub fn type_name(&self) -> &'static str {
+        self.components.type_name()
+    }
+
+    /// Get the memory layout of the component.
+    pub fn layout(&self) -> Layout {
+        self.components.layout()
+    }
+
+    /// Get a pointer to the component.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Get a reference to the component as the type `T`.
+    ///
+    /// # Safety
+    ///
+    /// The component must be of type `T`.
+    pub unsafe fn cast<T>(&self) -> &T {
+        &*self.ptr.cast::<T>()
+    }
+}
+
+/// A mutable borrow of the component of an entity, without knowing its type at compile time.
+///
+/// The component store stays mutably borrowed until this is dropped.
+pub struct UntypedComponentRefMut<'a> {
+    components: AtomicRefMut<'a, UntypedComponentStore>,
+    ptr: *mut u8,
+}
+
+impl<'a> UntypedComponentRefMut<'a> {
+    /// Get the name of the component type.
+    pub fn type_name(&self) -> &'static str {
+        self.components.type_name()
+    }
+
+    /// Get the memory layout of the component.
+    pub fn layout(&self) -> Layout {
+        self.components.layout()
+    }
+
+    /// Get a pointer to the component.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Get a mutable pointer to the component.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Get a mutable reference to the component as the type `T`.
+    ///
+    /// # Safety
+    ///
+    /// The component must be of type `T`.
+    pub unsafe fn cast_mut<T>(&mut self) -> &mut T {
+        &mut *self.ptr.cast::<T>()
+    }
+}
+
+impl World {
+    /// Iterate over the components of `entity`, along with the [`TypeUlid`]s of their types,
+    /// sorted by ULID.
+    ///
+    /// Nothing is returned if the entity isn't alive. Use
+    /// [`ComponentStores::info()`] to get the name and layout of a component type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Entities`], or any of the component stores, are mutably borrowed.
+    pub fn components_of(
+        &self,
+        entity: Entity,
+    ) -> impl Iterator<Item = (Ulid, UntypedComponentRef<'_>)> {
+        let ids = if self.is_alive(entity) {
+            self.components.sorted_ids()
+        } else {
+            Vec::new()
+        };
+        ids.into_iter()
+            .filter_map(move |id| Some((id, self.borrow_raw(entity, id)?)))
+    }
+
+    /// Borrow the component of `entity` with the given [`TypeUlid`], if it has one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Entities`] or the component store are mutably borrowed.
+    pub fn get_raw(&self, entity: Entity, id: Ulid) -> Option<UntypedComponentRef<'_>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.borrow_raw(entity, id)
+    }
+
+    /// Mutably borrow the component of `entity` with the given [`TypeUlid`], if it has one.
+    ///
+    /// The component is marked as changed, because writes through the pointer can't be tracked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Entities`] are mutably borrowed, or if the component store is borrowed.
+    pub fn get_raw_mut(&self, entity: Entity, id: Ulid) -> Option<UntypedComponentRefMut<'_>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        let components = self.components.components.get(&id)?;
+        #[cfg(feature = "debug")]
+        let mut components = crate::debug::borrow_mut(components, || format!("get_raw_mut({id})"));
+        #[cfg(not(feature = "debug"))]
+        let mut components = components.borrow_mut();
+        let ptr = components.get_mut(entity)?;
+        Some(UntypedComponentRefMut { components, ptr })
+    }
+
+    /// Borrow the component of `entity` with the given ULID, without checking that the entity is
+    /// alive.
+    fn borrow_raw(&self, entity: Entity, id: Ulid) -> Option<UntypedComponentRef<'_>> {
+        let components = self.components.components.get(&id)?;
+        #[cfg(feature = "debug")]
+        let components = crate::debug::borrow(components, || format!("get_raw({id})"));
+        #[cfg(not(feature = "debug"))]
+        let components = components.borrow();
+        let ptr = components.get(entity)?;
+        Some(UntypedComponentRef { components, ptr })
+    }
+
+    /// Returns `true` if the entity is alive.
+    fn is_alive(&self, entity: Entity) -> bool {
+        self.resources.get::<Entities>().borrow().is_alive(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQPF0A3C5E7G9J1K3M5P7R9T"]
+    struct Health(u32);
+
+    #[derive(Clone, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQPF0B4D6F8H0K2M4N6Q8S0V"]
+    struct Nickname(String);
+
+    fn spawn_hero(world: &mut World) -> Entity {
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut healths: CompMut<Health>,
+                 mut names: CompMut<Nickname>|
+                 -> anyhow::Result<_> {
+                    let hero = entities.create();
+                    healths.insert(hero, Health(10));
+                    names.insert(hero, Nickname("hero".into()));
+                    // Another entity, with only one of the components.
+                    healths.insert(entities.create(), Health(5));
+                    Ok(hero)
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn list_components() {
+        let mut world = World::new();
+        let hero = spawn_hero(&mut world);
+
+        let components = world
+            .components_of(hero)
+            .map(|(id, component)| (id, component.type_name(), component.layout()))
+            .collect::<Vec<_>>();
+        let mut expected = vec![
+            (
+                Health::ULID,
+                std::any::type_name::<Health>(),
+                std::alloc::Layout::new::<Health>(),
+            ),
+            (
+                Nickname::ULID,
+                std::any::type_name::<Nickname>(),
+                std::alloc::Layout::new::<Nickname>(),
+            ),
+        ];
+        expected.sort_by_key(|(id, ..)| *id);
+        assert_eq!(components, expected);
+
+        let info = world.components.info(Health::ULID).unwrap();
+        assert_eq!(info.type_name, std::any::type_name::<Health>());
+        assert_eq!(world.components.infos().count(), 2);
+
+        world.resources.get::<Entities>().borrow_mut().kill(hero);
+        assert_eq!(world.components_of(hero).count(), 0);
+    }
+
+    #[test]
+    fn raw_access() {
+        let mut world = World::new();
+        let hero = spawn_hero(&mut world);
+
+        {
+            let mut health = world.get_raw_mut(hero, Health::ULID).unwrap();
+            // Safe: The component has the type of its ULID.
+            unsafe { health.cast_mut::<Health>().0 = 20 };
+        }
+        let health = world.get_raw(hero, Health::ULID).unwrap();
+        // Safe: The component has the type of its ULID.
+        assert_eq!(unsafe { health.cast::<Health>() }, &Health(20));
+        assert!(world.get_raw(hero, Ulid(0)).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn raw_access_is_borrow_checked() {
+        let mut world = World::new();
+        let hero = spawn_hero(&mut world);
+
+        let _health = world.get_raw(hero, Health::ULID).unwrap();
+        world.get_raw_mut(hero, Health::ULID);
+    }
+}
@@ -2,7 +2,6 @@
 
 use std::{
     alloc::{self, Layout},
-    any::TypeId,
     io::Write,
     marker::PhantomData,
     mem,
@@ -41,6 +40,7 @@ pub struct UntypedResourceInfo {
 pub struct UntypedResource {
     layout: Layout,
     cell: Arc<AtomicRefCell<*mut u8>>,
+    ticks: Arc<AtomicRefCell<ComponentTicks>>,
     clone_fn: unsafe extern "C" fn(*const u8, *mut u8),
     drop_fn: Option<unsafe extern "C" fn(*mut u8)>,
 }
@@ -58,6 +58,7 @@ impl UntypedResource {
         UntypedResource {
             layout: info.layout,
             cell: info.cell,
+            ticks: Arc::new(AtomicRefCell::new(ComponentTicks::default())),
             clone_fn: info.clone_fn,
             drop_fn: info.drop_fn,
         }
@@ -73,6 +74,7 @@ impl UntypedResource {
 
         Self {
             cell: Arc::new(AtomicRefCell::new(ptr)),
+            ticks: Arc::new(AtomicRefCell::new(ComponentTicks::default())),
             clone_fn: T::raw_clone,
             drop_fn: Some(T::raw_drop),
             layout,
@@ -112,6 +114,7 @@ impl Clone for UntypedResource {
 
         Self {
             cell: Arc::new(AtomicRefCell::new(new_ptr)),
+            ticks: Arc::new(AtomicRefCell::new(*self.ticks.borrow())),
             clone_fn: self.clone_fn,
             drop_fn: self.drop_fn,
             layout: self.layout,
@@ -146,6 +149,41 @@ impl Drop for UntypedResource {
     }
 }
 
+impl UntypedResource {
+    /// Returns whether or not the data of both resources is equal, byte-for-byte.
+    pub(crate) fn bytes_eq(&self, other: &UntypedResource) -> bool {
+        if self.layout != other.layout {
+            return false;
+        }
+        let size = self.layout.size();
+        let (data, other_data) = (self.cell.borrow(), other.cell.borrow());
+
+        // SAFE: Both pointers are valid for reading data with the same layout.
+        unsafe {
+            std::slice::from_raw_parts(*data as *const u8, size)
+                == std::slice::from_raw_parts(*other_data as *const u8, size)
+        }
+    }
+}
+
+impl UntypedResource {
+    /// Move the resource data out as a `T`, and free the resource without dropping the data.
+    ///
+    /// # Safety
+    ///
+    /// The resource must contain a `T`, and there must be no other handles to its data cell.
+    unsafe fn into_inner<T>(mut self) -> T {
+        let ptr = *self.cell.borrow();
+        let value = ptr.cast::<T>().read();
+        if self.layout.size() != 0 {
+            alloc::dealloc(ptr, self.layout);
+        }
+        // The data has been moved out, so it must not be dropped again.
+        self.drop_fn = None;
+        value
+    }
+}
+
 impl UntypedResources {
     /// Create an empty [`UntypedResources`].
     pub fn new() -> Self {
@@ -162,10 +200,33 @@ impl UntypedResources {
         self.resources.get(&uuid).map(|x| x.cell.clone())
     }
 
+    /// Get a cell containing the change ticks of the resource with the given ID
+    pub fn get_ticks(&self, uuid: Ulid) -> Option<Arc<AtomicRefCell<ComponentTicks>>> {
+        self.resources.get(&uuid).map(|x| x.ticks.clone())
+    }
+
+    /// Get the memory layout of the resource with the given ID.
+    pub fn layout(&self, uuid: Ulid) -> Option<Layout> {
+        self.resources.get(&uuid).map(|x| x.layout)
+    }
+
+    /// Iterate over the ULIDs of the resources.
+    pub fn ids(&self) -> impl Iterator<Item = Ulid> + '_ {
+        self.resources.keys().copied()
+    }
+
     /// Remove a resource
     pub fn remove(&mut self, uuid: Ulid) -> Option<UntypedResource> {
         self.resources.remove(&uuid)
     }
+
+    /// Clamp the change ticks of all of the resources so that they are no older than
+    /// [`MAX_CHANGE_AGE`] compared to `this_run`.
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        for resource in self.resources.values() {
+            resource.ticks.borrow_mut().check(this_run);
+        }
+    }
 }
 
 /// A collection of resources.
@@ -174,7 +235,8 @@ impl UntypedResources {
 #[derive(Clone, Default)]
 pub struct Resources {
     untyped: UntypedResources,
-    type_ids: UlidMap<TypeId>,
+    type_ids: UlidMap<RegisteredType>,
+    change_tick: TickCounter,
 }
 
 impl Resources {
@@ -194,8 +256,8 @@ impl Resources {
     ///
     /// # Panics
     ///
-    /// Panics if you try to insert a Rust type with a different [`TypeId`], but the same
-    /// [`TypeUlid`] as another resource in the store.
+    /// Panics if you try to insert a Rust type with a different [`TypeId`][std::any::TypeId], but
+    /// the same [`TypeUlid`] as another resource in the store. The error names both types.
     #[track_caller]
     pub fn insert<T: TypedEcsData>(&mut self, resource: T) {
         self.try_insert(resource).unwrap();
@@ -203,31 +265,88 @@ impl Resources {
 
     /// Try to insert a resource.
     ///
+    /// The resource is marked as added in the current [change tick][Self::change_tick].
+    ///
     /// # Errors
     ///
-    /// Errors if you try to insert a Rust type with a different [`TypeId`], but the same
-    /// [`TypeUlid`] as another resource in the store.
+    /// Errors if you try to insert a Rust type with a different [`TypeId`][std::any::TypeId], but
+    /// the same [`TypeUlid`] as another resource in the store. The error names both types.
     pub fn try_insert<T: TypedEcsData>(&mut self, resource: T) -> Result<(), EcsError> {
         let uuid = T::ULID;
-        let type_id = TypeId::of::<T>();
+        let ty = RegisteredType::of::<T>();
+        let resource = UntypedResource::new(resource);
+        *resource.ticks.borrow_mut() = ComponentTicks::new(self.change_tick.get());
 
         match self.type_ids.entry(uuid) {
             std::collections::hash_map::Entry::Occupied(entry) => {
-                if entry.get() != &type_id {
-                    return Err(EcsError::TypeUlidCollision);
-                }
-
-                self.untyped.insert(uuid, UntypedResource::new(resource));
+                entry.get().check(uuid, &ty)?;
+                self.untyped.insert(uuid, resource);
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
-                entry.insert(type_id);
-                self.untyped.insert(uuid, UntypedResource::new(resource));
+                entry.insert(ty);
+                self.untyped.insert(uuid, resource);
             }
         }
 
         Ok(())
     }
 
+    /// Remove a resource from the store, returning it if it existed.
+    ///
+    /// Systems that run afterwards see the resource as missing, just as if it was never inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource is borrowed. See [`try_remove()`][Self::try_remove].
+    #[track_caller]
+    pub fn remove<T: TypedEcsData>(&mut self) -> Option<T> {
+        self.try_remove().unwrap()
+    }
+
+    /// Remove a resource from the store, returning it if it existed.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`EcsError::AlreadyBorrowed`], leaving the resource in the store, if the
+    /// resource is borrowed, or if there are any [`AtomicResource`] handles to it.
+    pub fn try_remove<T: TypedEcsData>(&mut self) -> Result<Option<T>, EcsError> {
+        let Some(resource) = self.untyped.resources.get(&T::ULID) else {
+            return Ok(None);
+        };
+        if let Some(existing) = self.type_ids.get(&T::ULID) {
+            existing.check(T::ULID, &RegisteredType::of::<T>())?;
+        }
+        // Handles to the resource may be borrowed at any time, so the resource can't be removed
+        // until they've been dropped.
+        if resource.cell.try_borrow_mut().is_err() || Arc::strong_count(&resource.cell) > 1 {
+            return Err(EcsError::AlreadyBorrowed);
+        }
+
+        self.type_ids.remove(&T::ULID);
+        let resource = self.untyped.resources.remove(&T::ULID).unwrap();
+        // SAFE: We checked that the resource is a `T`, and that there are no other handles to it.
+        Ok(Some(unsafe { resource.into_inner() }))
+    }
+
+    /// Take a resource out of the store, leaving its default value in its place, and marking it
+    /// as changed.
+    ///
+    /// Unlike [`remove()`][Self::remove], the resource stays in the store, so this is useful for
+    /// consuming one-shot requests from systems that take the resource as a parameter.
+    ///
+    /// Returns [`None`] if the resource doesn't exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource is borrowed.
+    #[track_caller]
+    pub fn take<T: TypedEcsData + Default>(&self) -> Option<T> {
+        let resource = self.try_get::<T>()?;
+        let value = std::mem::take(&mut *resource.borrow_mut());
+        resource.set_changed();
+        Some(value)
+    }
+
     /// Get a resource handle from the store.
     ///
     /// This is not the resource itself, but a handle, may be cloned cheaply.
@@ -250,16 +369,88 @@ impl Resources {
         self.untyped.resources.contains_key(&T::ULID)
     }
 
+    /// Get a resource handle from the store, inserting the resource returned by `f` first if it
+    /// doesn't exist.
+    ///
+    /// `f` is only called if the resource doesn't exist. An existing resource is never replaced,
+    /// and its handle is returned even if the resource is currently borrowed, so borrowing the
+    /// handle follows the same rules as any other handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a resource with a different Rust type, but the same [`TypeUlid`], is in the
+    /// store.
+    #[track_caller]
+    pub fn get_or_insert_with<T: TypedEcsData>(
+        &mut self,
+        f: impl FnOnce() -> T,
+    ) -> AtomicResource<T> {
+        self.try_get_or_insert_with(f).unwrap()
+    }
+
+    /// Get a resource handle from the store, inserting the resource returned by `f` first if it
+    /// doesn't exist.
+    ///
+    /// See [`get_or_insert_with()`][Self::get_or_insert_with].
+    ///
+    /// # Errors
+    ///
+    /// Errors if a resource with a different Rust type, but the same [`TypeUlid`], is in the
+    /// store.
+    pub fn try_get_or_insert_with<T: TypedEcsData>(
+        &mut self,
+        f: impl FnOnce() -> T,
+    ) -> Result<AtomicResource<T>, EcsError> {
+        if let Some(existing) = self.type_ids.get(&T::ULID) {
+            existing.check(T::ULID, &RegisteredType::of::<T>())?;
+        }
+        if !self.contains::<T>() {
+            self.try_insert(f())?;
+        }
+
+        Ok(self.try_get().unwrap())
+    }
+
+    /// Returns the number of resources in the store.
+    pub fn len(&self) -> usize {
+        self.untyped.resources.len()
+    }
+
+    /// Returns `true` if there are no resources in the store.
+    pub fn is_empty(&self) -> bool {
+        self.untyped.resources.is_empty()
+    }
+
+    /// Iterate over the [`TypeUlid`]s of the resources in the store, in an arbitrary order.
+    ///
+    /// This is meant for diagnostics, such as listing the resources of a [`World`].
+    pub fn ids(&self) -> impl Iterator<Item = Ulid> + '_ {
+        self.untyped.ids()
+    }
+
     /// Gets a resource handle from the store if it exists.
     pub fn try_get<T: TypedEcsData>(&self) -> Option<AtomicResource<T>> {
         let untyped = self.untyped.get(T::ULID)?;
+        let ticks = self.untyped.get_ticks(T::ULID)?;
 
         Some(AtomicResource {
             untyped,
+            ticks,
+            change_tick: self.change_tick.clone(),
             _phantom: PhantomData,
         })
     }
 
+    /// Get the tick that resources are marked as added or changed in, outside of systems.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Share the tick counter of the [`World`] that the resources belong to.
+    pub(crate) fn set_tick_counter(&mut self, counter: TickCounter) {
+        self.change_tick = counter;
+    }
+
     /// Borrow the underlying [`UntypedResources`] store.
     pub fn untyped(&self) -> &UntypedResources {
         &self.untyped
@@ -272,6 +463,173 @@ impl Resources {
     pub fn into_untyped(self) -> UntypedResources {
         self.untyped
     }
+    /// Replace the resources with copies of the resources in `snapshot`, except for the resources
+    /// that `keep` returns `true` for, which are left unchanged.
+    ///
+    /// Resources that aren't in the `snapshot` are removed, unless they are kept.
+    pub(crate) fn restore(&mut self, snapshot: &Resources, keep: impl Fn(Ulid) -> bool) {
+        self.untyped.resources.retain(|&id, _| keep(id));
+        for (&id, resource) in &snapshot.untyped.resources {
+            if !keep(id) {
+                self.untyped.resources.insert(id, resource.clone());
+            }
+        }
+        for (&id, &ty) in &snapshot.type_ids {
+            self.type_ids.entry(id).or_insert(ty);
+        }
+    }
+
+    /// Copy the resources in `other` into this collection, according to the `policy`.
+    ///
+    /// Resources that `skip` returns `true` for are never copied. Copied resources are marked as
+    /// added in the current [change tick][Self::change_tick].
+    ///
+    /// # Errors
+    ///
+    /// Errors, without copying anything, if a resource in `other` has the same [`TypeUlid`] as a
+    /// resource of a different Rust type in this collection.
+    pub(crate) fn merge(
+        &mut self,
+        other: &Resources,
+        policy: ResourceMergePolicy,
+        skip: impl Fn(Ulid) -> bool,
+    ) -> Result<(), EcsError> {
+        if policy == ResourceMergePolicy::Ignore {
+            return Ok(());
+        }
+        for (&id, ty) in &other.type_ids {
+            if let Some(existing) = self.type_ids.get(&id) {
+                existing.check(id, ty)?;
+            }
+        }
+
+        for (&id, resource) in &other.untyped.resources {
+            let exists = self.untyped.resources.contains_key(&id);
+            if skip(id) || (exists && policy == ResourceMergePolicy::KeepExisting) {
+                continue;
+            }
+            let resource = resource.clone();
+            *resource.ticks.borrow_mut() = ComponentTicks::new(self.change_tick.get());
+            self.untyped.resources.insert(id, resource);
+            if let Some(&ty) = other.type_ids.get(&id) {
+                self.type_ids.insert(id, ty);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get copies of the resources in `newer` that were inserted or changed since `older`, along
+    /// with the ULIDs of the resources that were removed.
+    ///
+    /// Resources are compared byte-for-byte. The [`Entities`] resource is always included, so that
+    /// entity allocations line up after the changes are applied.
+    pub(crate) fn diff(older: &Resources, newer: &Resources) -> (Resources, Vec<Ulid>) {
+        let mut changed = Resources::new();
+        for (&id, resource) in &newer.untyped.resources {
+            let unchanged = id != Entities::ULID
+                && older
+                    .untyped
+                    .resources
+                    .get(&id)
+                    .map_or(false, |old| old.bytes_eq(resource));
+            if !unchanged {
+                changed.untyped.resources.insert(id, resource.clone());
+                if let Some(&ty) = newer.type_ids.get(&id) {
+                    changed.type_ids.insert(id, ty);
+                }
+            }
+        }
+
+        let removed = older
+            .untyped
+            .resources
+            .keys()
+            .filter(|id| !newer.untyped.resources.contains_key(id))
+            .copied()
+            .collect();
+
+        (changed, removed)
+    }
+
+    /// Apply changes created with [`diff()`][Self::diff], except to the resources that `keep`
+    /// returns `true` for.
+    pub(crate) fn apply_diff(
+        &mut self,
+        changed: &Resources,
+        removed: &[Ulid],
+        keep: impl Fn(Ulid) -> bool,
+    ) {
+        for &id in removed {
+            if !keep(id) {
+                self.untyped.remove(id);
+            }
+        }
+        for (&id, resource) in &changed.untyped.resources {
+            if !keep(id) {
+                self.untyped.resources.insert(id, resource.clone());
+            }
+        }
+        for (&id, &ty) in &changed.type_ids {
+            self.type_ids.entry(id).or_insert(ty);
+        }
+    }
+}
+
+/// Trait for resources that can be created from the [`World`], such as resources that are
+/// configured by other resources.
+///
+/// This is implemented for every type that implements [`Default`]. Use
+/// [`World::init_resource()`] to insert the resource if it doesn't exist yet.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// #[derive(Clone, TypeUlid)]
+/// #[ulid = "01GQNF1RW5T0JB9K3XC7M2DH4E"]
+/// struct MapSize(u32);
+///
+/// #[derive(Clone, TypeUlid)]
+/// #[ulid = "01GQNF2A6Y8CQ4V1HM0ZTE5NR3"]
+/// struct SpatialIndex(Vec<Vec<Entity>>);
+///
+/// impl FromWorld for SpatialIndex {
+///     fn from_world(world: &mut World) -> Self {
+///         let size = world.resources.get::<MapSize>().borrow().0;
+///         Self(vec![Vec::new(); size as usize])
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.resources.insert(MapSize(16));
+/// world.init_resource::<SpatialIndex>();
+/// assert_eq!(world.resources.get::<SpatialIndex>().borrow().0.len(), 16);
+/// ```
+pub trait FromWorld {
+    /// Create the value from the world.
+    fn from_world(world: &mut World) -> Self;
+}
+
+impl<T: Default> FromWorld for T {
+    fn from_world(_world: &mut World) -> Self {
+        T::default()
+    }
+}
+
+impl World {
+    /// Insert the resource of type `T`, created with [`FromWorld`], if it doesn't exist yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a different Rust type with the same [`TypeUlid`] has already been inserted.
+    #[track_caller]
+    pub fn init_resource<T: TypedEcsData + FromWorld>(&mut self) {
+        if !self.resources.contains::<T>() {
+            let resource = T::from_world(self);
+            self.resources.insert(resource);
+        }
+    }
 }
 
 /// A handle to a resource from a [`Resources`] collection.
@@ -282,6 +640,9 @@ impl Resources {
 /// [`borrow_mut()`][Self::borrow_mut].
 pub struct AtomicResource<T: TypedEcsData> {
     untyped: Arc<AtomicRefCell<*mut u8>>,
+    ticks: Arc<AtomicRefCell<ComponentTicks>>,
+    /// The tick counter of the [`Resources`] that the handle was created from.
+    change_tick: TickCounter,
     _phantom: PhantomData<T>,
 }
 
@@ -290,6 +651,11 @@ impl<T: TypedEcsData> AtomicResource<T> {
     ///
     /// This returns a read guard, very similar to an [`RwLock`][std::sync::RwLock].
     pub fn borrow(&self) -> AtomicRef<T> {
+        #[cfg(feature = "debug")]
+        let borrow = crate::debug::borrow(&self.untyped, || {
+            format!("Res<{}>", std::any::type_name::<T>())
+        });
+        #[cfg(not(feature = "debug"))]
         let borrow = self.untyped.borrow();
         // SAFE: We know that the data pointer is valid for type T.
         AtomicRef::map(borrow, |data| unsafe { &*data.cast::<T>() })
@@ -298,11 +664,39 @@ impl<T: TypedEcsData> AtomicResource<T> {
     /// Lock the resource for read-writing.
     ///
     /// This returns a write guard, very similar to an [`RwLock`][std::sync::RwLock].
+    ///
+    /// Changes made through the guard are not tracked, use [`ResMut`] or
+    /// [`set_changed()`][Self::set_changed] to mark the resource as changed.
     pub fn borrow_mut(&self) -> AtomicRefMut<T> {
+        #[cfg(feature = "debug")]
+        let borrow = crate::debug::borrow_mut(&self.untyped, || {
+            format!("ResMut<{}>", std::any::type_name::<T>())
+        });
+        #[cfg(not(feature = "debug"))]
         let borrow = self.untyped.borrow_mut();
         // SAFE: We know that the data pointer is valid for type T.
         AtomicRefMut::map(borrow, |data| unsafe { &mut *data.cast::<T>() })
     }
+
+    /// Get the change ticks of the resource.
+    pub fn ticks(&self) -> ComponentTicks {
+        *self.ticks.borrow()
+    }
+
+    /// Mark the resource as changed.
+    pub fn set_changed(&self) {
+        self.ticks.borrow_mut().changed = self.change_tick.get();
+    }
+
+    /// Borrow the change ticks of the resource.
+    pub(crate) fn borrow_ticks(&self) -> AtomicRef<ComponentTicks> {
+        self.ticks.borrow()
+    }
+
+    /// Mutably borrow the change ticks of the resource.
+    pub(crate) fn borrow_ticks_mut(&self) -> AtomicRefMut<ComponentTicks> {
+        self.ticks.borrow_mut()
+    }
 }
 
 #[cfg(test)]
@@ -339,4 +733,146 @@ mod test {
         assert_eq!(resources.get::<B>().borrow().0, 2);
         assert_eq!(resources.get::<A>().borrow().0, vec![7, 8, 9]);
     }
+
+    #[derive(TypeUlid, Clone, Debug, Default, PartialEq, Eq)]
+    #[ulid = "01GQMY7R2D5ZK0B8WC3HTQ6N4F"]
+    struct LevelLoadRequest(Option<String>);
+
+    #[test]
+    fn remove_resource() {
+        let mut resources = Resources::new();
+        assert_eq!(resources.remove::<LevelLoadRequest>(), None);
+
+        resources.insert(LevelLoadRequest(Some("level1".into())));
+        assert_eq!(
+            resources.remove::<LevelLoadRequest>(),
+            Some(LevelLoadRequest(Some("level1".into())))
+        );
+        assert!(!resources.contains::<LevelLoadRequest>());
+        assert!(resources.try_get::<LevelLoadRequest>().is_none());
+    }
+
+    #[test]
+    fn remove_borrowed_resource() {
+        let mut resources = Resources::new();
+        resources.insert(LevelLoadRequest(Some("level1".into())));
+
+        let handle = resources.get::<LevelLoadRequest>();
+        let borrow = handle.borrow();
+        assert!(matches!(
+            resources.try_remove::<LevelLoadRequest>(),
+            Err(EcsError::AlreadyBorrowed)
+        ));
+        drop(borrow);
+        // The handle itself still keeps the resource from being removed.
+        assert!(matches!(
+            resources.try_remove::<LevelLoadRequest>(),
+            Err(EcsError::AlreadyBorrowed)
+        ));
+        drop(handle);
+        assert_eq!(
+            resources.try_remove::<LevelLoadRequest>().unwrap(),
+            Some(LevelLoadRequest(Some("level1".into())))
+        );
+    }
+
+    #[test]
+    fn systems_after_remove() {
+        let mut world = World::new();
+        world
+            .resources
+            .insert(LevelLoadRequest(Some("level1".into())));
+        world
+            .run_system(|request: Option<Res<LevelLoadRequest>>| assert!(request.is_some()))
+            .unwrap();
+
+        world.resources.remove::<LevelLoadRequest>();
+        world
+            .run_system(|request: Option<Res<LevelLoadRequest>>| assert!(request.is_none()))
+            .unwrap();
+    }
+
+    #[derive(TypeUlid, Clone, Debug, PartialEq, Eq)]
+    #[ulid = "01GQNF3H0QD7XR6B2Y9CKW4M5T"]
+    struct MapSize(usize);
+
+    #[derive(TypeUlid, Clone, Debug, PartialEq, Eq)]
+    #[ulid = "01GQNF3TE4K1MN8Z0VFR6J3BAC"]
+    struct SpatialIndex(Vec<Vec<Entity>>);
+
+    impl FromWorld for SpatialIndex {
+        fn from_world(world: &mut World) -> Self {
+            world.resources.get::<MapSize>().borrow_mut().0 += 1;
+            let size = world.resources.get::<MapSize>().borrow().0;
+            Self(vec![Vec::new(); size])
+        }
+    }
+
+    #[test]
+    fn init_resource_from_world() {
+        let mut world = World::new();
+        world.resources.insert(MapSize(7));
+
+        world.init_resource::<SpatialIndex>();
+        assert_eq!(world.resources.get::<SpatialIndex>().borrow().0.len(), 8);
+
+        // The resource is only created once.
+        world.init_resource::<SpatialIndex>();
+        assert_eq!(world.resources.get::<MapSize>().borrow().0, 8);
+
+        // Default resources are created with their default value.
+        world.init_resource::<u32>();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 0);
+    }
+
+    #[test]
+    fn init_resource_with_stages() {
+        let mut world = World::new();
+        world.resources.insert(MapSize(3));
+
+        let mut stages = SystemStages::with_core_stages();
+        stages.init_resource::<SpatialIndex>();
+        stages.initialize_systems(&mut world).unwrap();
+        assert_eq!(world.resources.get::<SpatialIndex>().borrow().0.len(), 4);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut resources = Resources::new();
+        assert!(resources.is_empty());
+
+        let request = resources.get_or_insert_with(|| LevelLoadRequest(Some("level1".into())));
+        assert_eq!(*request.borrow(), LevelLoadRequest(Some("level1".into())));
+        assert!(resources.contains::<LevelLoadRequest>());
+        assert_eq!(resources.len(), 1);
+        assert_eq!(
+            resources.ids().collect::<Vec<_>>(),
+            [LevelLoadRequest::ULID]
+        );
+
+        // An existing resource is returned as-is, even while it's mutably borrowed, and the
+        // replacement is never created.
+        let mut borrow = request.borrow_mut();
+        borrow.0 = Some("level2".into());
+        let existing = resources.get_or_insert_with::<LevelLoadRequest>(|| unreachable!());
+        drop(borrow);
+        assert_eq!(*existing.borrow(), LevelLoadRequest(Some("level2".into())));
+        assert_eq!(resources.len(), 1);
+    }
+
+    #[test]
+    fn take_resource() {
+        let mut resources = Resources::new();
+        assert_eq!(resources.take::<LevelLoadRequest>(), None);
+
+        resources.insert(LevelLoadRequest(Some("level1".into())));
+        assert_eq!(
+            resources.take::<LevelLoadRequest>(),
+            Some(LevelLoadRequest(Some("level1".into())))
+        );
+        assert_eq!(
+            *resources.get::<LevelLoadRequest>().borrow(),
+            LevelLoadRequest(None)
+        );
+    }
 }
@@ -0,0 +1,342 @@
+//! Spawning the entities of one [`World`] into another.
+//!
+//! This allows reusable groups of entities, or scenes, to be authored as a small [`World`], and
+//! then spawned into the game world, as many times as needed, with [`World::spawn_scene()`].
+
+use fxhash::FxHashMap;
+
+use crate::prelude::*;
+
+/// A mapping from the entities of a scene [`World`] to the entities that they were spawned as.
+///
+/// Returned by [`World::spawn_scene()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntityMap {
+    entities: FxHashMap<Entity, Entity>,
+}
+
+impl EntityMap {
+    /// Create an empty [`EntityMap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `from` to the entity `to`, returning the entity that it was previously mapped to.
+    pub fn insert(&mut self, from: Entity, to: Entity) -> Option<Entity> {
+        self.entities.insert(from, to)
+    }
+
+    /// Get the entity that `entity` is mapped to, if it is mapped.
+    pub fn get(&self, entity: Entity) -> Option<Entity> {
+        self.entities.get(&entity).copied()
+    }
+
+    /// Get the entity that `entity` is mapped to, or `entity` itself if it isn't mapped.
+    ///
+    /// This is useful in [`MapEntities`] implementations, so that references to entities outside
+    /// of the scene are left unchanged.
+    pub fn map(&self, entity: Entity) -> Entity {
+        self.get(entity).unwrap_or(entity)
+    }
+
+    /// Iterate over the mapped entities, and the entities that they are mapped to, in an arbitrary
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.entities.iter().map(|(&from, &to)| (from, to))
+    }
+
+    /// Returns the number of mapped entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if no entities are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Trait for components that contain [`Entity`] references, which must be remapped when the
+/// component is spawned from a scene.
+///
+/// The component type must be registered with [`World::register_map_entities()`] on the world
+/// that the scene is spawned into. [`Parent`] and [`Children`] are always registered.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// #[derive(Clone, TypeUlid)]
+/// #[ulid = "01GQKA5F2XW7RN4D1T8BYJ6M0C"]
+/// struct Target(Entity);
+///
+/// impl MapEntities for Target {
+///     fn map_entities(&mut self, map: &EntityMap) {
+///         self.0 = map.map(self.0);
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.register_map_entities::<Target>();
+/// ```
+pub trait MapEntities {
+    /// Replace the entities referenced by `self` with the entities that they are mapped to.
+    fn map_entities(&mut self, map: &EntityMap);
+}
+
+/// A type-erased [`MapEntities::map_entities()`] for a component type.
+type MapEntitiesFn = unsafe fn(*mut u8, &EntityMap);
+
+/// Call [`MapEntities::map_entities()`] on a raw component pointer.
+///
+/// # Safety
+///
+/// The pointer must point to a valid `T` that isn't borrowed anywhere else.
+unsafe fn map_entities_raw<T: MapEntities>(ptr: *mut u8, map: &EntityMap) {
+    (*ptr.cast::<T>()).map_entities(map);
+}
+
+/// Resource containing the [`MapEntities`] hooks of the component types that reference entities.
+///
+/// Use [`World::register_map_entities()`] to register a component type.
+#[derive(Clone, TypeUlid)]
+#[ulid = "01GQKA4W8HC3Y0QZ5N7EBP2TRD"]
+pub struct EntityMapHooks {
+    hooks: UlidMap<MapEntitiesFn>,
+}
+
+impl Default for EntityMapHooks {
+    fn default() -> Self {
+        let mut hooks = Self { hooks: default() };
+        hooks.register::<Parent>();
+        hooks.register::<Children>();
+        hooks
+    }
+}
+
+impl EntityMapHooks {
+    /// Register the [`MapEntities`] hook of the component type `T`.
+    pub fn register<T: TypedEcsData + MapEntities>(&mut self) {
+        self.hooks.insert(T::ULID, map_entities_raw::<T>);
+    }
+}
+
+/// What to do with the resources of a scene when it is spawned with [`World::spawn_scene()`].
+///
+/// The [`Entities`] of the scene are never copied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResourceMergePolicy {
+    /// Don't copy any of the scene's resources.
+    #[default]
+    Ignore,
+    /// Copy the scene's resources that the world doesn't have yet, keeping the world's version of
+    /// the resources that both have.
+    KeepExisting,
+    /// Copy all of the scene's resources, replacing the world's version of the resources that both
+    /// have.
+    Replace,
+}
+
+impl World {
+    /// Register the [`MapEntities`] hook of the component type `T`, so that its entity references
+    /// are remapped when it is spawned from a scene.
+    pub fn register_map_entities<T: TypedEcsData + MapEntities>(&mut self) {
+        self.resources.init::<EntityMapHooks>();
+        self.resources
+            .get::<EntityMapHooks>()
+            .borrow_mut()
+            .register::<T>();
+    }
+
+    /// Spawn a copy of every entity in the `scene`, along with its components.
+    ///
+    /// Each scene entity is spawned as a new entity, and the components that reference entities
+    /// are remapped with the hooks registered with [`World::register_map_entities()`]. The
+    /// scene's resources are copied according to the `resources` policy.
+    ///
+    /// Returns the mapping from the scene's entities to the spawned entities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component or resource in the scene has the same [`TypeUlid`] as a different
+    /// Rust type in this world, or if any of the resources or component stores are borrowed.
+    #[track_caller]
+    pub fn spawn_scene(&mut self, scene: &World, resources: ResourceMergePolicy) -> EntityMap {
+        self.try_spawn_scene(scene, resources).unwrap()
+    }
+
+    /// Spawn a copy of every entity in the `scene`, along with its components.
+    ///
+    /// See [`spawn_scene()`][Self::spawn_scene].
+    ///
+    /// # Errors
+    ///
+    /// Errors, without spawning anything, if a component or resource in the scene has the same
+    /// [`TypeUlid`] as a different Rust type in this world.
+    pub fn try_spawn_scene(
+        &mut self,
+        scene: &World,
+        resources: ResourceMergePolicy,
+    ) -> Result<EntityMap, EcsError> {
+        for (&id, ty) in &scene.components.type_ids {
+            if let Some(existing) = self.components.type_ids.get(&id) {
+                existing.check(id, ty)?;
+            }
+        }
+        self.resources
+            .merge(&scene.resources, resources, |id| id == Entities::ULID)?;
+
+        let mut map = EntityMap::new();
+        // The spawned entities, in ascending order of the scene entities, so that components are
+        // inserted in the same order every time, instead of the arbitrary order of the map.
+        let mut spawned = Vec::new();
+        {
+            let scene_entities = scene.resources.get::<Entities>();
+            let scene_entities = scene_entities.borrow();
+            let entities = self.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            for entity in scene_entities.iter_with_bitset(scene_entities.bitset()) {
+                let to = entities.create();
+                map.insert(entity, to);
+                spawned.push((entity, to));
+            }
+        }
+
+        let hooks = self
+            .resources
+            .try_get::<EntityMapHooks>()
+            .map(|hooks| hooks.borrow().clone())
+            .unwrap_or_default();
+        for (&id, scene_components) in &scene.components.components {
+            let scene_components = scene_components.borrow();
+            let ty = scene.components.type_ids[&id];
+            // Safe: The store is initialized for the same type as the scene's store, and we made
+            // sure that the type doesn't collide with another one above.
+            let components = unsafe { self.components.init_untyped(id, ty, &scene_components)? };
+            let mut components = components.borrow_mut();
+
+            for &(from, to) in &spawned {
+                // Safe: The store was initialized for the same type as the scene's store.
+                unsafe {
+                    components.insert_clone_from(&scene_components, from, to);
+                }
+            }
+
+            if let Some(map_entities) = hooks.hooks.get(&id) {
+                for &(_, to) in &spawned {
+                    if let Some(ptr) = components.get_mut(to) {
+                        // Safe: The hook was registered for the type of the store, and we have
+                        // mutable access to the store.
+                        unsafe { map_entities(ptr, &map) };
+                    }
+                }
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQKA6B9R2M7XE0JD4VHN5QTS"]
+    struct Tile(u32);
+
+    /// A tile layer, referencing the entities of its tiles.
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQKA6KC5T8WN1FZ3YQ0BR7MH"]
+    struct TileLayer {
+        tiles: Vec<Entity>,
+    }
+
+    impl MapEntities for TileLayer {
+        fn map_entities(&mut self, map: &EntityMap) {
+            for tile in &mut self.tiles {
+                *tile = map.map(*tile);
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQKA6VHE0D6Q9AC2XK8SPY4F"]
+    struct Music(&'static str);
+
+    /// Create an arena scene with a tile layer and its tiles, returning the scene along with the
+    /// scene's layer entity.
+    fn arena() -> (World, Entity) {
+        let mut scene = World::new();
+        scene.resources.insert(Music("arena.ogg"));
+        let layer = scene
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut tiles: CompMut<Tile>,
+                 mut layers: CompMut<TileLayer>,
+                 mut hierarchy: Hierarchy|
+                 -> anyhow::Result<_> {
+                    let layer = entities.create();
+                    let mut layer_tiles = Vec::new();
+                    for i in 0..3 {
+                        let tile = entities.create();
+                        tiles.insert(tile, Tile(i));
+                        hierarchy.set_parent(tile, layer);
+                        layer_tiles.push(tile);
+                    }
+                    layers.insert(layer, TileLayer { tiles: layer_tiles });
+                    Ok(layer)
+                },
+            )
+            .unwrap();
+        (scene, layer)
+    }
+
+    #[test]
+    fn spawn_scene_twice() {
+        let (scene, scene_layer) = arena();
+        let mut world = World::new();
+        world.register_map_entities::<TileLayer>();
+        // Make sure that the scene entities don't line up with the world's entities.
+        world.resources.get::<Entities>().borrow_mut().create();
+
+        let first = world.spawn_scene(&scene, ResourceMergePolicy::Ignore);
+        let second = world.spawn_scene(&scene, ResourceMergePolicy::Ignore);
+        assert_eq!(first.len(), 4);
+        assert_eq!(second.len(), 4);
+
+        for map in [first, second] {
+            let layer = map.get(scene_layer).unwrap();
+            world
+                .run_system(
+                    move |tiles: Comp<Tile>, layers: Comp<TileLayer>, hierarchy: Hierarchy| {
+                        let layer_tiles = &layers.get(layer).unwrap().tiles;
+                        assert_eq!(layer_tiles.len(), 3);
+                        assert_eq!(hierarchy.children(layer), layer_tiles.as_slice());
+                        for (i, &tile) in layer_tiles.iter().enumerate() {
+                            assert_eq!(tiles.get(tile), Some(&Tile(i as u32)));
+                            assert_eq!(hierarchy.parent(tile), Some(layer));
+                        }
+                    },
+                )
+                .unwrap();
+        }
+        assert!(!world.resources.contains::<Music>());
+    }
+
+    #[test]
+    fn scene_resource_policy() {
+        let (scene, _) = arena();
+
+        let mut world = World::new();
+        world.resources.insert(Music("menu.ogg"));
+        world.spawn_scene(&scene, ResourceMergePolicy::KeepExisting);
+        assert_eq!(world.resources.get::<Music>().borrow().0, "menu.ogg");
+        world.spawn_scene(&scene, ResourceMergePolicy::Replace);
+        assert_eq!(world.resources.get::<Music>().borrow().0, "arena.ogg");
+
+        let mut world = World::new();
+        world.spawn_scene(&scene, ResourceMergePolicy::KeepExisting);
+        assert_eq!(world.resources.get::<Music>().borrow().0, "arena.ogg");
+    }
+}
@@ -0,0 +1,567 @@
+//! Serialization of the [`World`], for save games.
+//!
+//! Component and resource types are opted-in to serialization by registering them with
+//! [`World::register_serializable_component()`] and
+//! [`World::register_serializable_resource()`]. Types that haven't been registered are skipped,
+//! and reported in the [`SkippedTypes`] returned by [`World::serialize()`] and
+//! [`World::deserialize()`].
+
+use std::fmt;
+
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::SerializeMap,
+    Deserializer, Serialize, Serializer,
+};
+
+use crate::prelude::*;
+
+/// Resource containing the functions used to serialize and deserialize the registered component
+/// and resource types.
+///
+/// Types are registered with [`World::register_serializable_component()`] and
+/// [`World::register_serializable_resource()`].
+#[derive(Clone, Default, TypeUlid)]
+#[ulid = "01GQJ4D7MZ3X8B5RWT2CKNF0HE"]
+pub struct SerializationRegistry {
+    components: UlidMap<SerdeFns>,
+    resources: UlidMap<SerdeFns>,
+}
+
+impl SerializationRegistry {
+    /// Returns whether or not the component type with the given ULID is registered.
+    pub fn has_component(&self, id: Ulid) -> bool {
+        self.components.contains_key(&id)
+    }
+
+    /// Returns whether or not the resource type with the given ULID is registered.
+    pub fn has_resource(&self, id: Ulid) -> bool {
+        self.resources.contains_key(&id)
+    }
+}
+
+/// The serialization functions of a single type.
+#[derive(Clone, Copy)]
+struct SerdeFns {
+    /// Copy the data of the type out of the world, in it's serializable form.
+    serialize: fn(&World) -> Box<dyn erased_serde::Serialize>,
+    /// Deserialize the data of the type into the world.
+    deserialize: DeserializeFn,
+}
+
+type DeserializeFn = for<'de> fn(
+    &mut dyn erased_serde::Deserializer<'de>,
+    &mut World,
+) -> Result<(), erased_serde::Error>;
+
+/// The ULIDs of the component and resource types that were skipped while serializing or
+/// deserializing a [`World`], because they weren't registered.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SkippedTypes {
+    /// The skipped component types.
+    pub components: Vec<Ulid>,
+    /// The skipped resource types.
+    pub resources: Vec<Ulid>,
+}
+
+impl SkippedTypes {
+    /// Returns `true` if no types were skipped.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.resources.is_empty()
+    }
+}
+
+impl World {
+    /// Register the component type `T` to be saved by [`serialize()`][Self::serialize], and
+    /// loaded by [`deserialize()`][Self::deserialize].
+    pub fn register_serializable_component<T: TypedEcsData + Serialize + DeserializeOwned>(
+        &mut self,
+    ) {
+        self.components.init::<T>();
+        self.resources.init::<SerializationRegistry>();
+        self.resources
+            .get::<SerializationRegistry>()
+            .borrow_mut()
+            .components
+            .insert(
+                T::ULID,
+                SerdeFns {
+                    serialize: serialize_components::<T>,
+                    deserialize: deserialize_components::<T>,
+                },
+            );
+    }
+
+    /// Register the resource type `T` to be saved by [`serialize()`][Self::serialize], and loaded
+    /// by [`deserialize()`][Self::deserialize].
+    pub fn register_serializable_resource<T: TypedEcsData + Serialize + DeserializeOwned>(
+        &mut self,
+    ) {
+        self.resources.init::<SerializationRegistry>();
+        self.resources
+            .get::<SerializationRegistry>()
+            .borrow_mut()
+            .resources
+            .insert(
+                T::ULID,
+                SerdeFns {
+                    serialize: serialize_resource::<T>,
+                    deserialize: deserialize_resource::<T>,
+                },
+            );
+    }
+
+    /// Serialize the [`Entities`] and the registered components and resources of the world.
+    ///
+    /// Component stores containing components of unregistered types, and unregistered resources,
+    /// are skipped and returned along with the result of the `serializer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered components or resources are mutably borrowed.
+    pub fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<(S::Ok, SkippedTypes), S::Error> {
+        let registry = self.serialization_registry();
+        let mut skipped = SkippedTypes::default();
+
+        let mut resources = Vec::new();
+        let mut resource_ids = self.resources.untyped().ids().collect::<Vec<_>>();
+        resource_ids.sort();
+        for id in resource_ids {
+            if id == Entities::ULID || id == SerializationRegistry::ULID {
+                continue;
+            }
+            match registry.resources.get(&id) {
+                Some(fns) => resources.push((id, (fns.serialize)(self))),
+                None => skipped.resources.push(id),
+            }
+        }
+
+        let mut components = Vec::new();
+        let mut component_ids = self
+            .components
+            .components
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        component_ids.sort();
+        for id in component_ids {
+            match registry.components.get(&id) {
+                Some(fns) => components.push((id, (fns.serialize)(self))),
+                None => {
+                    let is_empty = self.components.components[&id]
+                        .borrow()
+                        .iter()
+                        .next()
+                        .is_none();
+                    if !is_empty {
+                        skipped.components.push(id);
+                    }
+                }
+            }
+        }
+
+        let entities = self.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let ok = SerializedWorld {
+            entities: &entities,
+            resources: SerializedEntries(resources),
+            components: SerializedEntries(components),
+        }
+        .serialize(serializer)?;
+
+        Ok((ok, skipped))
+    }
+
+    /// Load the [`Entities`], components, and resources serialized by
+    /// [`serialize()`][Self::serialize] into the world.
+    ///
+    /// The [`Entities`] resource is replaced, the registered resources in the data are inserted,
+    /// and the registered component stores in the data are cleared before the saved components are
+    /// inserted. This is usually done with a newly created world, with the same types registered
+    /// as the world that was saved.
+    ///
+    /// Types in the data that aren't registered are skipped and returned. Skipping data requires a
+    /// self-describing format, so with formats such as [postcard] all of the saved types must be
+    /// registered.
+    ///
+    /// [postcard]: https://docs.rs/postcard
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data doesn't match the world's format, or if any of the registered types fail
+    /// to deserialize.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<SkippedTypes, D::Error> {
+        let registry = self.serialization_registry();
+        let mut skipped = SkippedTypes::default();
+        deserializer.deserialize_struct(
+            "World",
+            FIELDS,
+            WorldVisitor {
+                world: self,
+                registry: &registry,
+                skipped: &mut skipped,
+            },
+        )?;
+
+        Ok(skipped)
+    }
+
+    /// Get a copy of the [`SerializationRegistry`] resource.
+    fn serialization_registry(&self) -> SerializationRegistry {
+        self.resources
+            .try_get::<SerializationRegistry>()
+            .map(|registry| registry.borrow().clone())
+            .unwrap_or_default()
+    }
+}
+
+fn serialize_components<T: TypedEcsData + Serialize>(
+    world: &World,
+) -> Box<dyn erased_serde::Serialize> {
+    let entities = world.resources.get::<Entities>();
+    let entities = entities.borrow();
+    let components = world.components.get::<T>();
+    let components = components.borrow();
+
+    Box::new(
+        entities
+            .iter_with(&components)
+            .map(|(entity, component)| (entity, component.clone()))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn deserialize_components<T: TypedEcsData + DeserializeOwned>(
+    deserializer: &mut dyn erased_serde::Deserializer,
+    world: &mut World,
+) -> Result<(), erased_serde::Error> {
+    let components: Vec<(Entity, T)> = erased_serde::deserialize(deserializer)?;
+
+    world.components.init::<T>();
+    world.components.get_by_uuid(T::ULID).borrow_mut().reset();
+    let store = world.components.get::<T>();
+    let mut store = store.borrow_mut();
+    for (entity, component) in components {
+        store.insert(entity, component);
+    }
+
+    Ok(())
+}
+
+fn serialize_resource<T: TypedEcsData + Serialize>(
+    world: &World,
+) -> Box<dyn erased_serde::Serialize> {
+    Box::new(world.resources.get::<T>().borrow().clone())
+}
+
+fn deserialize_resource<T: TypedEcsData + DeserializeOwned>(
+    deserializer: &mut dyn erased_serde::Deserializer,
+    world: &mut World,
+) -> Result<(), erased_serde::Error> {
+    let resource: T = erased_serde::deserialize(deserializer)?;
+    world.resources.insert(resource);
+
+    Ok(())
+}
+
+/// The fields of the serialized world.
+const FIELDS: &[&str] = &["entities", "resources", "components"];
+
+/// The serialized form of the [`World`].
+#[derive(Serialize)]
+struct SerializedWorld<'a> {
+    entities: &'a Entities,
+    resources: SerializedEntries,
+    components: SerializedEntries,
+}
+
+/// Map from type ULIDs to the serialized data of that type.
+struct SerializedEntries(Vec<(Ulid, Box<dyn erased_serde::Serialize>)>);
+
+impl Serialize for SerializedEntries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (id, data) in &self.0 {
+            map.serialize_entry(&id.to_string(), data)?;
+        }
+        map.end()
+    }
+}
+
+/// Deserializes the fields of the [`SerializedWorld`] into a [`World`].
+struct WorldVisitor<'a> {
+    world: &'a mut World,
+    registry: &'a SerializationRegistry,
+    skipped: &'a mut SkippedTypes,
+}
+
+impl<'a> WorldVisitor<'a> {
+    fn resources(&mut self) -> EntriesSeed {
+        EntriesSeed {
+            world: &mut *self.world,
+            fns: &self.registry.resources,
+            skipped: &mut self.skipped.resources,
+        }
+    }
+
+    fn components(&mut self) -> EntriesSeed {
+        EntriesSeed {
+            world: &mut *self.world,
+            fns: &self.registry.components,
+            skipped: &mut self.skipped.components,
+        }
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for WorldVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a serialized World")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        let entities: Entities = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"3 fields"))?;
+        self.world.resources.insert(entities);
+        seq.next_element_seed(self.resources())?
+            .ok_or_else(|| de::Error::invalid_length(1, &"3 fields"))?;
+        seq.next_element_seed(self.components())?
+            .ok_or_else(|| de::Error::invalid_length(2, &"3 fields"))?;
+
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "entities" => {
+                    let entities: Entities = map.next_value()?;
+                    self.world.resources.insert(entities);
+                }
+                "resources" => map.next_value_seed(self.resources())?,
+                "components" => map.next_value_seed(self.components())?,
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserializes a map from type ULIDs to the data of that type into the [`World`].
+struct EntriesSeed<'a> {
+    world: &'a mut World,
+    fns: &'a UlidMap<SerdeFns>,
+    skipped: &'a mut Vec<Ulid>,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for EntriesSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for EntriesSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of type ULIDs to serialized data")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Self {
+            world,
+            fns,
+            skipped,
+        } = self;
+        while let Some(key) = map.next_key::<String>()? {
+            let id = Ulid::from_string(&key).map_err(de::Error::custom)?;
+            match fns.get(&id) {
+                Some(fns) => map.next_value_seed(DataSeed {
+                    world: &mut *world,
+                    deserialize: fns.deserialize,
+                })?,
+                None => {
+                    skipped.push(id);
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserializes the data of a single type into the [`World`].
+struct DataSeed<'a> {
+    world: &'a mut World,
+    deserialize: DeserializeFn,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for DataSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.deserialize)(&mut deserializer, self.world).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, TypeUlid, Serialize, Deserialize)]
+    #[ulid = "01GQJ4DGX8K2N5VB0AZ7T3QMCW"]
+    struct Pos(f32, f32);
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid, Serialize, Deserialize)]
+    #[ulid = "01GQJ4DQ1FRJ6H9YE4S0WD8KXN"]
+    struct Name(String);
+
+    #[derive(Clone, Debug, TypeUlid)]
+    #[ulid = "01GQJ4DY5C0TMQ3X7G2BN9EAVZ"]
+    struct NotSaved;
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq, TypeUlid, Serialize, Deserialize)]
+    #[ulid = "01GQJ4E5JW7DZ1A6PHK3MXR8TF"]
+    struct Score(u32);
+
+    fn register(world: &mut World) {
+        world.register_serializable_component::<Pos>();
+        world.register_serializable_component::<Name>();
+        world.register_serializable_resource::<Score>();
+    }
+
+    fn setup_world() -> World {
+        let mut world = World::new();
+        register(&mut world);
+        world.resources.insert(Score(42));
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut positions: CompMut<Pos>,
+                 mut names: CompMut<Name>,
+                 mut not_saved: CompMut<NotSaved>| {
+                    let player = entities.create();
+                    positions.insert(player, Pos(1.0, 2.5));
+                    names.insert(player, Name("Fishy".into()));
+                    not_saved.insert(player, NotSaved);
+
+                    let dead = entities.create();
+                    let enemy = entities.create();
+                    positions.insert(enemy, Pos(-3.0, 0.0));
+                    entities.kill(dead);
+                },
+            )
+            .unwrap();
+        world.maintain();
+        world
+    }
+
+    type SavedState = (Vec<Entity>, Vec<(Entity, Pos)>, Vec<(Entity, Name)>, u32);
+
+    fn saved_state(world: &World) -> SavedState {
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let positions = world.components.get::<Pos>();
+        let positions = positions.borrow();
+        let names = world.components.get::<Name>();
+        let names = names.borrow();
+        (
+            entities.iter_with_bitset(entities.bitset()).collect(),
+            entities
+                .iter_with(&positions)
+                .map(|(entity, pos)| (entity, pos.clone()))
+                .collect(),
+            entities
+                .iter_with(&names)
+                .map(|(entity, name)| (entity, name.clone()))
+                .collect(),
+            world.resources.get::<Score>().borrow().0,
+        )
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        let world = setup_world();
+
+        let mut yaml = Vec::new();
+        let ((), skipped) = world
+            .serialize(&mut serde_yaml::Serializer::new(&mut yaml))
+            .unwrap();
+        assert_eq!(skipped.components, [NotSaved::ULID]);
+
+        let mut loaded = World::new();
+        register(&mut loaded);
+        let skipped = loaded
+            .deserialize(serde_yaml::Deserializer::from_slice(&yaml))
+            .unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(saved_state(&loaded), saved_state(&world));
+
+        // Entity ids line up after loading.
+        let next = world.resources.get::<Entities>().borrow_mut().create();
+        let loaded_next = loaded.resources.get::<Entities>().borrow_mut().create();
+        assert_eq!(next, loaded_next);
+    }
+
+    #[test]
+    fn postcard_round_trip() {
+        let world = setup_world();
+
+        let bytes = postcard::to_allocvec(&SaveWrapper(&world)).unwrap();
+
+        let mut loaded = World::new();
+        register(&mut loaded);
+        let skipped = loaded
+            .deserialize(&mut postcard::Deserializer::from_bytes(&bytes))
+            .unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(saved_state(&loaded), saved_state(&world));
+    }
+
+    #[test]
+    fn unregistered_types_are_skipped_when_loading() {
+        let world = setup_world();
+        let yaml = serde_yaml::to_string(&SaveWrapper(&world)).unwrap();
+
+        let mut loaded = World::new();
+        loaded.register_serializable_component::<Pos>();
+        let skipped = loaded
+            .deserialize(serde_yaml::Deserializer::from_str(&yaml))
+            .unwrap();
+        assert_eq!(skipped.components, [Name::ULID]);
+        assert_eq!(skipped.resources, [Score::ULID]);
+        assert_eq!(
+            loaded
+                .components
+                .get::<Pos>()
+                .borrow()
+                .iter()
+                .collect::<Vec<_>>(),
+            [&Pos(1.0, 2.5), &Pos(-3.0, 0.0)]
+        );
+    }
+
+    /// Helper for serializing a world with functions that take a [`Serialize`] implementation.
+    struct SaveWrapper<'a>(&'a World);
+
+    impl<'a> Serialize for SaveWrapper<'a> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize(serializer).map(|(ok, _)| ok)
+        }
+    }
+}
@@ -0,0 +1,848 @@
+//! Snapshots of the [`World`] state, for rollback networking.
+
+use crate::prelude::*;
+
+/// A copy of the resources and components of a [`World`], created with [`World::snapshot()`].
+///
+/// The snapshot can be restored with [`World::restore()`] any number of times.
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    pub(crate) resources: Resources,
+    pub(crate) components: ComponentStores,
+}
+
+impl WorldSnapshot {
+    /// Compute the changes that turn the `older` snapshot into the `newer` one.
+    ///
+    /// Components are compared with the [`PartialEq`] implementations registered with
+    /// [`ComponentStores::init_with_eq()`], or byte-for-byte if they were registered as
+    /// padding-free with [`ComponentStores::init_padding_free()`]. The components of other types
+    /// can't be compared, so they are always reported as changed. Resources are compared
+    /// byte-for-byte. The [`Entities`] resource is always included in the diff.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either snapshot doesn't contain the [`Entities`] resource.
+    pub fn diff(older: &WorldSnapshot, newer: &WorldSnapshot) -> WorldDiff {
+        let (resources, removed_resources) = Resources::diff(&older.resources, &newer.resources);
+
+        let older_entities = older.resources.get::<Entities>();
+        let older_entities = older_entities.borrow();
+        let newer_entities = newer.resources.get::<Entities>();
+        let newer_entities = newer_entities.borrow();
+
+        let mut components = UlidMap::default();
+        for (&id, newer_store) in &newer.components.components {
+            let newer_store = newer_store.borrow();
+            let ty = newer.components.type_ids[&id];
+            let diff = match older.components.components.get(&id) {
+                Some(older_store) => ComponentStoreDiff::new(
+                    &older_store.borrow(),
+                    &newer_store,
+                    ty,
+                    &older_entities,
+                    &newer_entities,
+                ),
+                None => ComponentStoreDiff::new(
+                    &newer_store.empty_copy(),
+                    &newer_store,
+                    ty,
+                    &older_entities,
+                    &newer_entities,
+                ),
+            };
+            if !diff.is_empty() {
+                components.insert(id, diff);
+            }
+        }
+        for (&id, older_store) in &older.components.components {
+            if !newer.components.components.contains_key(&id) {
+                let older_store = older_store.borrow();
+                let diff = ComponentStoreDiff::new(
+                    &older_store,
+                    &older_store.empty_copy(),
+                    older.components.type_ids[&id],
+                    &older_entities,
+                    &newer_entities,
+                );
+                if !diff.is_empty() {
+                    components.insert(id, diff);
+                }
+            }
+        }
+
+        WorldDiff {
+            resources,
+            removed_resources,
+            components,
+        }
+    }
+}
+
+/// The changes between two [`WorldSnapshot`]s, created with [`WorldSnapshot::diff()`].
+///
+/// The changes can be applied to a world in the state of the older snapshot with
+/// [`World::apply_diff()`].
+#[derive(Clone)]
+pub struct WorldDiff {
+    /// Copies of the resources that were inserted or changed.
+    pub(crate) resources: Resources,
+    /// The ULIDs of the resources that were removed.
+    pub removed_resources: Vec<Ulid>,
+    /// The changes to the components of every type that changed, by the component's ULID.
+    pub components: UlidMap<ComponentStoreDiff>,
+}
+
+/// The changes to the components of a single type, in a [`WorldDiff`].
+#[derive(Clone)]
+pub struct ComponentStoreDiff {
+    /// The entities that the component was added to.
+    pub added: Vec<Entity>,
+    /// The entities that the component was removed from.
+    pub removed: Vec<Entity>,
+    /// The entities whose component changed.
+    pub changed: Vec<Entity>,
+    /// Copies of the added and changed components.
+    components: UntypedComponentStore,
+    /// The type of the components.
+    ty: RegisteredType,
+}
+
+impl ComponentStoreDiff {
+    /// Compare the components in two stores of the same type.
+    ///
+    /// The generations of the entities in the lists are taken from `newer_entities`, except for
+    /// the entities whose component was removed, which may have been killed since the older
+    /// snapshot.
+    fn new(
+        older: &UntypedComponentStore,
+        newer: &UntypedComponentStore,
+        ty: RegisteredType,
+        older_entities: &Entities,
+        newer_entities: &Entities,
+    ) -> Self {
+        let mut diff = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            components: newer.empty_copy(),
+            ty,
+        };
+
+        for index in 0..older.max_id.max(newer.max_id) {
+            let (in_older, in_newer) = (older.bitset.bit_test(index), newer.bitset.bit_test(index));
+            match (in_older, in_newer) {
+                (false, true) => diff.added.push(newer_entities.entity_at(index)),
+                (true, false) => diff.removed.push(older_entities.entity_at(index)),
+                (true, true) if !older.components_eq(newer, index) => {
+                    diff.changed.push(newer_entities.entity_at(index))
+                }
+                _ => (),
+            }
+        }
+
+        for &entity in diff.added.iter().chain(&diff.changed) {
+            // Safe: The diff store was created as a copy of the newer store.
+            unsafe {
+                diff.components.insert_clone_from(newer, entity, entity);
+            }
+        }
+
+        diff
+    }
+
+    /// Returns `true` if no components were added, removed, or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Resource listing the resources that are left out of [`WorldSnapshot`]s.
+///
+/// Resources are added to the list with [`World::exclude_from_snapshot()`]. Excluded resources
+/// are not copied into snapshots, and are left unchanged when a snapshot is restored. This is
+/// useful for resources that shouldn't be rolled back, such as network sockets, or resources that
+/// are expensive to clone and never change during a match.
+#[derive(Clone, Default, TypeUlid)]
+#[ulid = "01GQG8S6W2Y4C9KJ3T0EFXN7BD"]
+pub struct SnapshotExclusions {
+    ids: UlidSet,
+}
+
+impl SnapshotExclusions {
+    /// Exclude the resource of type `T` from snapshots.
+    pub fn exclude<T: TypeUlid>(&mut self) {
+        self.ids.insert(T::ULID);
+    }
+
+    /// Returns whether or not the resource with the given ULID is excluded from snapshots.
+    ///
+    /// The [`SnapshotExclusions`] resource itself is always excluded.
+    pub fn is_excluded(&self, id: Ulid) -> bool {
+        id == Self::ULID || self.ids.contains(&id)
+    }
+}
+
+impl World {
+    /// Exclude the resource of type `T` from the [`WorldSnapshot`]s of this world.
+    ///
+    /// See [`SnapshotExclusions`].
+    pub fn exclude_from_snapshot<T: TypeUlid>(&mut self) {
+        self.resources.init::<SnapshotExclusions>();
+        self.resources
+            .get::<SnapshotExclusions>()
+            .borrow_mut()
+            .exclude::<T>();
+    }
+
+    /// Create a snapshot of the world.
+    ///
+    /// All of the component stores and resources, except for the resources that are
+    /// [excluded][Self::exclude_from_snapshot], are deep-copied with the clone functions that they
+    /// were registered with. The [`Entities`] resource is copied along with the rest, so entity
+    /// ids and generations are preserved exactly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the resources or component stores are mutably borrowed.
+    pub fn snapshot(&self) -> WorldSnapshot {
+        let exclusions = self.snapshot_exclusions();
+
+        // Copy the resources into an empty collection, so that excluded resources are never cloned.
+        let mut resources = Resources::new();
+        resources.restore(&self.resources, |id| exclusions.is_excluded(id));
+        let mut components = self.components.clone();
+        components.set_tick_counter(TickCounter::default());
+
+        WorldSnapshot {
+            resources,
+            components,
+        }
+    }
+
+    /// Restore the world to the state that it was in when the `snapshot` was taken.
+    ///
+    /// Resources that were [excluded][Self::exclude_from_snapshot] are left unchanged, resources
+    /// that were inserted since the snapshot was taken are removed, and component stores that
+    /// were initialized since the snapshot was taken are emptied.
+    ///
+    /// The change tick of the world is not rolled back, so the components and resources keep the
+    /// change ticks that they had when the snapshot was taken, but systems may not detect changes
+    /// that were made before the snapshot, after they last ran.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the resources or component stores are borrowed.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        let exclusions = self.snapshot_exclusions();
+
+        self.resources
+            .restore(&snapshot.resources, |id| exclusions.is_excluded(id));
+        self.components.restore(&snapshot.components);
+        self.rebuild_entity_names();
+    }
+
+    /// Apply the changes in a [`WorldDiff`] to the world.
+    ///
+    /// When the world is in the state of the older snapshot that the diff was created from, it
+    /// will be in the state of the newer snapshot afterwards, including the entity allocations.
+    /// Resources that were [excluded][Self::exclude_from_snapshot] are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component type in the diff has the same ULID as a different component type in
+    /// the world, or if any of the resources or changed component stores are borrowed.
+    pub fn apply_diff(&mut self, diff: &WorldDiff) {
+        let exclusions = self.snapshot_exclusions();
+        self.resources
+            .apply_diff(&diff.resources, &diff.removed_resources, |id| {
+                exclusions.is_excluded(id)
+            });
+
+        for (&id, changes) in &diff.components {
+            // Safe: The diff's store contains components of the type that it was created with.
+            let components = unsafe {
+                self.components
+                    .init_untyped(id, changes.ty, &changes.components)
+                    .unwrap()
+            };
+            let mut components = components.borrow_mut();
+
+            for &entity in &changes.removed {
+                // Safe: We don't provide an out pointer, so it doesn't overlap the component's
+                // internal storage.
+                unsafe {
+                    components.remove(entity, None);
+                }
+            }
+            for &entity in changes.added.iter().chain(&changes.changed) {
+                // Safe: The store was initialized for the same type as the diff's store.
+                unsafe {
+                    components.insert_clone_from(&changes.components, entity, entity);
+                }
+            }
+        }
+        self.rebuild_entity_names();
+    }
+
+    /// Get a copy of the [`SnapshotExclusions`] resource.
+    fn snapshot_exclusions(&self) -> SnapshotExclusions {
+        self.resources
+            .try_get::<SnapshotExclusions>()
+            .map(|exclusions| exclusions.borrow().clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQG8SF3N5R0AZ7XC2HDK9V1M"]
+    struct Pos(i32, i32);
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQG8SNM4B8JE1QW6Y3TF0PZS"]
+    struct Vel(i32, i32);
+
+    /// Deterministic random number generator resource.
+    #[derive(Clone, Debug, TypeUlid)]
+    #[ulid = "01GQG8SWB7HX2CR5N9E4MJ3KQA"]
+    struct Rng(u64);
+
+    impl Default for Rng {
+        fn default() -> Self {
+            Self(0x2545_f491_4f6c_dd1d)
+        }
+    }
+
+    impl Rng {
+        fn next_i32(&mut self, max: i32) -> i32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % (2 * max as u64 + 1)) as i32 - max
+        }
+    }
+
+    /// Resource standing in for a network socket, which shouldn't be rolled back.
+    #[derive(Clone, Debug, Default, TypeUlid)]
+    #[ulid = "01GQG8T3JQ6DVW0S2G8ZEAC5NT"]
+    struct Socket(u32);
+
+    fn spawn(
+        mut entities: ResMut<Entities>,
+        mut rng: ResMut<Rng>,
+        mut positions: CompMut<Pos>,
+        mut velocities: CompMut<Vel>,
+    ) {
+        for _ in 0..3 {
+            let entity = entities.create();
+            positions.insert(entity, Pos(rng.next_i32(10), rng.next_i32(10)));
+            velocities.insert(entity, Vel(rng.next_i32(3), rng.next_i32(3)));
+        }
+    }
+
+    fn movement(entities: Res<Entities>, mut positions: CompMut<Pos>, velocities: Comp<Vel>) {
+        for (_, (mut pos, vel)) in entities.iter_with((&mut positions, &velocities)) {
+            pos.0 += vel.0;
+            pos.1 += vel.1;
+        }
+    }
+
+    fn despawn(mut entities: ResMut<Entities>, positions: Comp<Pos>) {
+        let out_of_bounds = entities
+            .iter_with(&positions)
+            .filter(|(_, pos)| pos.0.abs() > 20 || pos.1.abs() > 20)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in out_of_bounds {
+            entities.kill(entity);
+        }
+    }
+
+    fn count_packets(mut socket: ResMut<Socket>) {
+        socket.0 += 1;
+    }
+
+    fn stages(world: &mut World) -> SystemStages {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::PreUpdate, spawn)
+            .add_system_to_stage(CoreStage::Update, movement)
+            .add_system_to_stage(CoreStage::PostUpdate, despawn)
+            .add_system_to_stage(CoreStage::PostUpdate, count_packets);
+        stages.initialize_systems(world).unwrap();
+        stages
+    }
+
+    fn run_frames(world: &mut World, stages: &mut SystemStages, frames: usize) {
+        for _ in 0..frames {
+            stages.run(world).unwrap();
+            world.maintain();
+        }
+    }
+
+    /// Get the raw bytes of every component in the world, along with the alive entities.
+    fn world_state(world: &World) -> (Vec<Entity>, Vec<(Ulid, Vec<Vec<u8>>)>) {
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let alive = entities.iter_with_bitset(entities.bitset()).collect();
+
+        let mut components = world
+            .components
+            .components
+            .iter()
+            .map(|(&id, store)| {
+                let store = store.borrow();
+                (id, store.iter().map(|bytes| bytes.to_vec()).collect())
+            })
+            .collect::<Vec<_>>();
+        components.sort_by_key(|(id, _)| *id);
+
+        (alive, components)
+    }
+
+    #[test]
+    fn restored_snapshot_is_deterministic() {
+        let mut world = World::new();
+        world.exclude_from_snapshot::<Socket>();
+        let mut stages = stages(&mut world);
+
+        run_frames(&mut world, &mut stages, 5);
+        let snapshot = world.snapshot();
+        run_frames(&mut world, &mut stages, 10);
+        let expected = world_state(&world);
+        let next_entity = world.resources.get::<Entities>().borrow_mut().create();
+
+        world.restore(&snapshot);
+        assert_ne!(world_state(&world), expected);
+        run_frames(&mut world, &mut stages, 10);
+        assert_eq!(world_state(&world), expected);
+        assert_eq!(
+            world.resources.get::<Entities>().borrow_mut().create(),
+            next_entity
+        );
+
+        // The excluded resource kept counting through the rollback.
+        assert_eq!(world.resources.get::<Socket>().borrow().0, 25);
+    }
+
+    /// A body in the randomized simulation, stored sparsely.
+    #[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQR2KD5W8Z1N4B7E0H3J6M9P"]
+    struct Body {
+        position: [f32; 2],
+        velocity: [f32; 2],
+    }
+
+    /// The charge of a body in the randomized simulation, stored densely.
+    #[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQR2KM0A3C6F9H2K5N8R1T4W"]
+    struct Charge(f32);
+
+    /// Total energy of the simulation, accumulated in iteration order, so that any difference in
+    /// the order changes its bits.
+    #[derive(Clone, Debug, Default, TypeUlid)]
+    #[ulid = "01GQR2KVB6E9G2J5M8Q1S4V7X0"]
+    struct Energy(f32);
+
+    impl Rng {
+        fn next_below(&mut self, max: usize) -> usize {
+            self.next_i32(i32::MAX).unsigned_abs() as usize % max
+        }
+
+        fn next_f32(&mut self) -> f32 {
+            self.next_i32(1 << 20) as f32 / (1 << 20) as f32
+        }
+    }
+
+    fn simulate(
+        mut entities: ResMut<Entities>,
+        mut rng: ResMut<Rng>,
+        mut bodies: CompMut<Body>,
+        mut charges: CompMut<Charge>,
+        mut energy: ResMut<Energy>,
+    ) {
+        for _ in 0..rng.next_below(6) {
+            let entity = entities.create();
+            bodies.insert(
+                entity,
+                Body {
+                    position: [rng.next_f32(), rng.next_f32()],
+                    velocity: [rng.next_f32(), rng.next_f32()],
+                },
+            );
+            if rng.next_below(2) == 0 {
+                charges.insert(entity, Charge(rng.next_f32()));
+            }
+        }
+
+        let alive = entities
+            .iter_with_bitset(entities.bitset())
+            .collect::<Vec<_>>();
+        for _ in 0..rng.next_below(4).min(alive.len()) {
+            let entity = alive[rng.next_below(alive.len())];
+            match rng.next_below(3) {
+                0 => entities.kill(entity),
+                1 => {
+                    charges.remove(entity);
+                }
+                _ => {
+                    charges.insert(entity, Charge(rng.next_f32()));
+                }
+            }
+        }
+
+        for (_, (mut body, charge)) in entities.iter_with((&mut bodies, &charges)) {
+            body.velocity[0] += charge.0 * 0.1;
+            body.velocity[1] -= charge.0 * 0.1;
+            energy.0 += body.velocity[0] * body.velocity[0] + body.velocity[1] * body.velocity[1];
+        }
+        for (_, mut body) in entities.iter_with(&mut bodies) {
+            body.position[0] += body.velocity[0] * 0.016;
+            body.position[1] += body.velocity[1] * 0.016;
+            energy.0 += body.position[0] * 1e-3 - body.position[1] * 1e-4;
+        }
+    }
+
+    fn simulation_world() -> World {
+        let mut world = World::new();
+        world
+            .components
+            .init_with_strategy::<Charge>(StorageStrategy::Dense);
+        world
+    }
+
+    fn run_simulation(world: &mut World, frames: usize) {
+        for _ in 0..frames {
+            world.run_system(simulate).unwrap();
+            world.maintain();
+        }
+    }
+
+    /// The alive entities, the energy, and the bytes of every component by entity index, which
+    /// doesn't depend on the layout of the stores.
+    fn simulation_state(world: &World) -> (Vec<Entity>, u32, Vec<(Ulid, usize, Vec<u8>)>) {
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        let alive = entities.iter_with_bitset(entities.bitset()).collect();
+        let energy = world.resources.get::<Energy>().borrow().0.to_bits();
+
+        let mut ids = world
+            .components
+            .components
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort();
+        let mut components = Vec::new();
+        for id in ids {
+            let store = world.components.components[&id].borrow();
+            for index in 0..store.max_id {
+                if let Some(bytes) = store.bytes(index) {
+                    // SAFE: The simulation components have no padding, so all of their bytes
+                    // are initialized.
+                    let bytes = bytes.iter().map(|byte| unsafe { byte.assume_init() });
+                    components.push((id, index, bytes.collect()));
+                }
+            }
+        }
+
+        (alive, energy, components)
+    }
+
+    #[test]
+    fn randomized_simulation_is_deterministic() {
+        let mut world = simulation_world();
+        run_simulation(&mut world, 100);
+        let snapshot = world.snapshot();
+        let states = (0..100)
+            .map(|_| {
+                run_simulation(&mut world, 1);
+                simulation_state(&world)
+            })
+            .collect::<Vec<_>>();
+        let final_snapshot = world.snapshot();
+        assert!(!states.last().unwrap().2.is_empty());
+
+        // Rolling back and re-simulating gives bit-identical results, frame by frame.
+        world.restore(&snapshot);
+        for state in &states {
+            run_simulation(&mut world, 1);
+            assert_eq!(simulation_state(&world), *state);
+        }
+
+        // So does a peer that simulates from the start.
+        let mut peer = simulation_world();
+        run_simulation(&mut peer, 200);
+        assert_eq!(simulation_state(&peer), *states.last().unwrap());
+
+        // And one that catches up with a diff, even though the layout of the dense store may be
+        // different, because joins iterate in entity order.
+        let mut peer = simulation_world();
+        peer.restore(&snapshot);
+        peer.apply_diff(&WorldSnapshot::diff(&snapshot, &final_snapshot));
+        assert_eq!(simulation_state(&peer), *states.last().unwrap());
+        run_simulation(&mut world, 50);
+        run_simulation(&mut peer, 50);
+        assert_eq!(simulation_state(&peer), simulation_state(&world));
+    }
+
+    #[test]
+    fn restore_removes_new_data() {
+        let mut world = World::new();
+        world.resources.insert(Rng::default());
+        let snapshot = world.snapshot();
+
+        world.resources.insert(Socket(1));
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut positions: CompMut<Pos>| {
+                    positions.insert(entities.create(), Pos(1, 2));
+                },
+            )
+            .unwrap();
+
+        world.restore(&snapshot);
+        assert!(world.resources.contains::<Rng>());
+        assert!(!world.resources.contains::<Socket>());
+        world
+            .run_system(|entities: Res<Entities>, positions: Comp<Pos>| {
+                assert_eq!(entities.iter_with(&positions).count(), 0);
+                assert_eq!(entities.iter_with_bitset(entities.bitset()).count(), 0);
+            })
+            .unwrap();
+
+        // The same snapshot can be restored more than once.
+        world.restore(&snapshot);
+        assert!(world.resources.contains::<Rng>());
+    }
+
+    // The sprites have no padding, so they are compared byte-for-byte, and the other components
+    // are compared with their `PartialEq` implementations.
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQHB2W6T4ZK8M1FDXR3NPC9E"]
+    #[repr(C)]
+    struct Sprite {
+        image: u16,
+        flip_x: bool,
+        flip_y: bool,
+    }
+
+    // SAFE: The fields fill the size of the sprite without padding, and are all `NoUninit`.
+    unsafe impl bytemuck::NoUninit for Sprite {}
+
+    #[derive(Clone, Debug, PartialEq, TypeUlid)]
+    #[ulid = "01GQHB35R0V7QJ2YBN6SAE4GKD"]
+    struct Transform {
+        translation: glam::Vec3,
+        rotation: glam::Quat,
+        scale: f32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GQHB3DC9XE5HW8T0PFZM2RJA"]
+    struct TileLayer {
+        tiles: Vec<Option<Entity>>,
+    }
+
+    /// The alive entities, and the components of every type, along with their entity.
+    type RenderState = (
+        Vec<Entity>,
+        Vec<(Entity, Sprite)>,
+        Vec<(Entity, Transform)>,
+        Vec<(Entity, TileLayer)>,
+    );
+
+    fn render_state(world: &World) -> RenderState {
+        fn components<T: TypedEcsData>(world: &World, entities: &Entities) -> Vec<(Entity, T)> {
+            let components = world.components.get::<T>();
+            let components = components.borrow();
+            entities
+                .iter_with(&components)
+                .map(|(entity, component)| (entity, component.clone()))
+                .collect()
+        }
+
+        let entities = world.resources.get::<Entities>();
+        let entities = entities.borrow();
+        (
+            entities.iter_with_bitset(entities.bitset()).collect(),
+            components(world, &entities),
+            components(world, &entities),
+            components(world, &entities),
+        )
+    }
+
+    fn setup_render_world() -> (World, Vec<Entity>) {
+        let mut world = World::new();
+        world.components.init_padding_free::<Sprite>();
+        world.components.init_with_eq::<Transform>();
+        world.components.init_with_eq::<TileLayer>();
+        let spawned = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut sprites: CompMut<Sprite>,
+                 mut transforms: CompMut<Transform>,
+                 mut layers: CompMut<TileLayer>|
+                 -> anyhow::Result<_> {
+                    let mut spawned = Vec::new();
+                    for i in 0..5 {
+                        let entity = entities.create();
+                        sprites.insert(
+                            entity,
+                            Sprite {
+                                image: i,
+                                flip_x: false,
+                                flip_y: false,
+                            },
+                        );
+                        transforms.insert(
+                            entity,
+                            Transform {
+                                translation: glam::Vec3::new(i as f32, 0.0, 0.0),
+                                rotation: glam::Quat::IDENTITY,
+                                scale: 1.0,
+                            },
+                        );
+                        spawned.push(entity);
+                    }
+
+                    let layer = entities.create();
+                    layers.insert(
+                        layer,
+                        TileLayer {
+                            tiles: vec![Some(spawned[0]), None, Some(spawned[1])],
+                        },
+                    );
+                    spawned.push(layer);
+
+                    Ok(spawned)
+                },
+            )
+            .unwrap();
+
+        (world, spawned)
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let (mut world, spawned) = setup_render_world();
+        let older = world.snapshot();
+
+        let changes = spawned.clone();
+        world
+            .run_system(
+                move |mut entities: ResMut<Entities>,
+                      mut sprites: CompMut<Sprite>,
+                      mut transforms: CompMut<Transform>| {
+                    transforms.get_mut(changes[0]).unwrap().translation.y = 2.0;
+                    sprites.get_mut(changes[1]).unwrap().flip_x = true;
+                    sprites.remove(changes[2]);
+                    entities.kill(changes[3]);
+                },
+            )
+            .unwrap();
+        world.maintain();
+
+        let layer = spawned[5];
+        let new_entity = world
+            .run_system(
+                move |mut entities: ResMut<Entities>,
+                      mut transforms: CompMut<Transform>,
+                      mut layers: CompMut<TileLayer>|
+                      -> anyhow::Result<_> {
+                    // The new entity re-uses the index of the killed entity.
+                    let new_entity = entities.create();
+                    transforms.insert(
+                        new_entity,
+                        Transform {
+                            translation: glam::Vec3::ONE,
+                            rotation: glam::Quat::from_rotation_z(1.0),
+                            scale: 2.0,
+                        },
+                    );
+                    layers.get_mut(layer).unwrap().tiles[1] = Some(new_entity);
+
+                    Ok(new_entity)
+                },
+            )
+            .unwrap();
+        assert_eq!(new_entity.index(), spawned[3].index());
+        let newer = world.snapshot();
+
+        let diff = WorldSnapshot::diff(&older, &newer);
+        let sprites = &diff.components[&Sprite::ULID];
+        assert_eq!(sprites.changed, [spawned[1]]);
+        assert_eq!(sprites.removed, [spawned[2], spawned[3]]);
+        assert!(sprites.added.is_empty());
+        let transforms = &diff.components[&Transform::ULID];
+        assert_eq!(transforms.changed, [spawned[0], new_entity]);
+        assert!(transforms.added.is_empty());
+        assert!(transforms.removed.is_empty());
+        let layers = &diff.components[&TileLayer::ULID];
+        assert_eq!(layers.changed, [spawned[5]]);
+
+        let mut copy = World::new();
+        copy.restore(&older);
+        assert_eq!(render_state(&copy), render_state(&setup_render_world().0));
+        copy.apply_diff(&diff);
+        assert_eq!(render_state(&copy), render_state(&world));
+
+        // The entity allocators line up after the diff is applied.
+        let next = world.resources.get::<Entities>().borrow_mut().create();
+        let copy_next = copy.resources.get::<Entities>().borrow_mut().create();
+        assert_eq!(next, copy_next);
+    }
+
+    #[test]
+    fn diff_of_new_and_removed_stores() {
+        let (world, spawned) = setup_render_world();
+        let empty = World::new().snapshot();
+        let full = world.snapshot();
+
+        // Applying a diff from an empty world adds every component.
+        let diff = WorldSnapshot::diff(&empty, &full);
+        assert_eq!(diff.components[&Sprite::ULID].added, spawned[..5]);
+        let mut copy = World::new();
+        copy.apply_diff(&diff);
+        assert_eq!(render_state(&copy), render_state(&world));
+
+        // And the reverse diff removes them again.
+        copy.apply_diff(&WorldSnapshot::diff(&full, &empty));
+        let state = render_state(&copy);
+        assert!(state.0.is_empty() && state.1.is_empty() && state.2.is_empty());
+
+        // Snapshots without changes only differ by the entities, even though the tile layers in
+        // different snapshots own different copies of their tiles.
+        let diff = WorldSnapshot::diff(&full, &world.snapshot());
+        assert!(diff.components.is_empty());
+        assert!(diff.removed_resources.is_empty());
+    }
+
+    #[test]
+    fn diff_without_comparison() {
+        #[derive(Clone, Debug, TypeUlid)]
+        #[ulid = "01GQHB3NK4D8RW2YF6TXAJ0C5V"]
+        struct Health(u8, u32);
+
+        let mut world = World::new();
+        let entity = world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut healths: CompMut<Health>| {
+                    let entity = entities.create();
+                    healths.insert(entity, Health(1, 10));
+                    entity
+                },
+            )
+            .unwrap();
+
+        // Components that can't be compared are always reported as changed.
+        let snapshot = world.snapshot();
+        let diff = WorldSnapshot::diff(&snapshot, &snapshot);
+        assert_eq!(diff.components[&Health::ULID].changed, [entity]);
+    }
+}
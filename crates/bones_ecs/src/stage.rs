@@ -1,71 +1,498 @@
 //! Implementation of stage abstraction for running collections of systems over a [`World`].
 
+use std::collections::HashMap;
+
 use crate::prelude::*;
 
+mod fixed_timestep;
+pub use fixed_timestep::*;
+mod parallel;
+pub use parallel::*;
+mod nested;
+pub use nested::*;
+mod profile;
+pub use profile::*;
+mod report;
+pub use report::*;
+mod stage_ordering;
+pub use stage_ordering::*;
+mod state;
+pub use state::*;
+mod step;
+pub use step::*;
+mod system_set;
+pub use system_set::*;
+
 /// An ordered collection of [`SystemStage`]s.
 pub struct SystemStages {
     /// The stages in the collection, in the order that they will be run.
     pub stages: Vec<Box<dyn SystemStage>>,
+    /// The stage containing the startup systems, which are run once, before the other stages.
+    startup_stage: SimpleSystemStage,
+    /// Whether or not the startup systems have been run.
+    has_started: bool,
+    /// How errors returned by systems are handled.
+    pub error_policy: ErrorPolicy,
+    /// The errors that were skipped during the most recent call to [`run()`][Self::run].
+    errors: Vec<SystemError>,
+    /// Changes to the [`SystemSets`] resource to apply at the start of the next frame.
+    system_set_changes: Vec<(Ulid, bool)>,
+    /// The index of the next stage to run, if a frame is being [stepped
+    /// through][Self::run_next_stage].
+    step_cursor: Option<usize>,
+    /// The ordering constraints of the stages that were [added][Self::add_stage] without an
+    /// explicit position.
+    stage_orderings: UlidMap<StageOrdering>,
+    /// Functions that initialize the resources that were [declared][Self::init_resource].
+    resource_inits: Vec<fn(&mut World)>,
 }
 
 impl SystemStages {
+    /// Create a [`SystemStages`] collection with the given stages.
+    pub fn new(stages: Vec<Box<dyn SystemStage>>) -> Self {
+        Self {
+            stages,
+            startup_stage: SimpleSystemStage::new(StartupStage),
+            has_started: false,
+            error_policy: default(),
+            errors: Vec::new(),
+            system_set_changes: Vec::new(),
+            step_cursor: None,
+            stage_orderings: default(),
+            resource_inits: Vec::new(),
+        }
+    }
+
+    /// Set the [`ErrorPolicy`] used to handle errors returned by systems.
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
     /// Initialize the systems in the stages agains the [`World`].
     ///
-    /// This must be called once before calling [`run()`][Self::run].
-    pub fn initialize_systems(&mut self, world: &mut World) {
+    /// This must be called once before calling [`run()`][Self::run]. The stages are
+    /// [sorted][Self::sort_stages] first.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the stages can't be sorted, or if any of the stages fail to initialize, such as
+    /// when the systems in a stage have cyclic ordering constraints.
+    pub fn initialize_systems(&mut self, world: &mut World) -> Result<(), EcsError> {
+        self.sort_stages()?;
+        world.resources.init::<SystemSets>();
+        world.resources.init::<CommandQueue>();
+        for init in &self.resource_inits {
+            init(world);
+        }
+        self.startup_stage.initialize(world)?;
         for stage in &mut self.stages {
-            stage.initialize(world);
+            stage.initialize(world)?;
         }
+
+        Ok(())
     }
 
     /// Execute the systems on the given `world`.
     ///
     /// > **Note:** You must call [`initialize_systems()`][Self::initialize_systems] once before
-    /// > calling `run()` one or more times.
-    pub fn run(&mut self, world: &World) -> SystemResult {
+    /// > calling `run()` one or more times. Systems that are added after that are initialized the
+    /// > next time their stage is run.
+    ///
+    /// The first call to `run()` will also run the [startup systems][Self::add_startup_system],
+    /// before any of the other stages. The startup systems are never run again, even if one of
+    /// them fails.
+    ///
+    /// The [`Commands`] queued by the systems in each stage are applied at the end of that stage.
+    ///
+    /// If a frame has been partially run with [`run_next_stage()`][Self::run_next_stage], `run()`
+    /// only runs the remaining stages of that frame.
+    ///
+    /// # Errors
+    ///
+    /// With [`ErrorPolicy::AbortFrame`], the first error returned by a system stops the frame and
+    /// is returned. With [`ErrorPolicy::SkipSystem`], `run()` always succeeds, and the errors of
+    /// the failing systems can be retrieved with [`errors()`][Self::errors].
+    pub fn run(&mut self, world: &mut World) -> SystemResult {
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.clear();
+
+        let result = self.run_stages(world, self.error_policy, &mut errors);
+        self.errors = errors;
+
+        result.map_err(|error| error.into())
+    }
+
+    /// Execute the systems on the given `world`, returning all of the errors that occured.
+    ///
+    /// This is the same as [`run()`][Self::run], except that with [`ErrorPolicy::AbortFrame`],
+    /// the error that aborted the frame is returned in the list along with any others.
+    pub fn run_with_errors(&mut self, world: &mut World) -> Vec<SystemError> {
+        let mut errors = Vec::new();
+        if let Err(error) = self.run_stages(world, self.error_policy, &mut errors) {
+            errors.push(error);
+        }
+
+        errors
+    }
+
+    /// Enable or disable all of the systems in the given [`SystemSet`].
+    ///
+    /// The change is applied to the [`SystemSets`] resource at the start of the next call to
+    /// [`run()`][Self::run]. Systems can also enable or disable sets by modifying the
+    /// [`SystemSets`] resource directly, which also takes effect on the next frame.
+    pub fn set_enabled<S: SystemSet>(&mut self, set: S, enabled: bool) -> &mut Self {
+        self.system_set_changes.push((set.id(), enabled));
+        self
+    }
+
+    /// Enable or disable profiling for all of the stages.
+    ///
+    /// When profiling is enabled, the execution time of every system is recorded in the
+    /// [`SystemProfile`] resource. Use [`profile_report()`][Self::profile_report] to get a report
+    /// of the slowest systems.
+    pub fn enable_profiling(&mut self, enabled: bool) -> &mut Self {
+        self.startup_stage.set_profiling(enabled);
+        for stage in &mut self.stages {
+            stage.set_profiling(enabled);
+        }
+
+        self
+    }
+
+    /// Create a report of the system execution times recorded in the [`SystemProfile`] resource,
+    /// sorted from the slowest to the fastest average execution time.
+    ///
+    /// The report will be empty unless profiling has been enabled with
+    /// [`enable_profiling()`][Self::enable_profiling].
+    pub fn profile_report(&self, world: &World) -> Vec<SystemProfileEntry> {
+        world
+            .resources
+            .try_get::<SystemProfile>()
+            .map(|profile| profile.borrow().report())
+            .unwrap_or_default()
+    }
+
+    /// Get the errors that were skipped during the most recent call to [`run()`][Self::run].
+    ///
+    /// This will always be empty unless the [`error_policy`][Self::error_policy] is
+    /// [`ErrorPolicy::SkipSystem`].
+    pub fn errors(&self) -> &[SystemError] {
+        &self.errors
+    }
+
+    /// Run all of the stages, handling errors according to the given `policy`.
+    ///
+    /// If a frame is being stepped through, only the remaining stages of that frame are run.
+    fn run_stages(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        let start = match self.step_cursor.take() {
+            Some(idx) => idx,
+            None => {
+                world.increment_change_tick();
+                world.check_change_ticks();
+                self.apply_system_sets(world);
+
+                if !self.has_started {
+                    self.has_started = true;
+                    Self::run_stage(&mut self.startup_stage, world, policy, errors)?;
+                }
+
+                0
+            }
+        };
+
+        for stage in self.stages.iter_mut().skip(start) {
+            Self::run_stage(&mut **stage, world, policy, errors)?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply the pending system set changes, and update the stages with a snapshot of the enabled
+    /// system sets, so that changes made during the frame only take effect on the next frame.
+    fn apply_system_sets(&mut self, world: &World) {
+        let sets = world.resources.get::<SystemSets>();
+        let mut sets = sets.borrow_mut();
+        for (id, enabled) in self.system_set_changes.drain(..) {
+            sets.set_id_enabled(id, enabled);
+        }
+
+        self.startup_stage.update_system_sets(&sets);
         for stage in &mut self.stages {
-            stage.run(world)?;
+            stage.update_system_sets(&sets);
+        }
+    }
+
+    /// Run a single stage if it's run criteria allows it, and apply the commands that it's
+    /// systems queued.
+    fn run_stage(
+        stage: &mut dyn SystemStage,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        match stage.should_run(world) {
+            Ok(true) => {
+                stage.run_with_policy(world, policy, errors)?;
+                world.apply_commands();
+            }
+            Ok(false) => (),
+            Err(error) => policy.handle(
+                SystemError {
+                    stage: stage.name(),
+                    system: "run criteria".into(),
+                    error,
+                },
+                errors,
+            )?,
         }
 
         Ok(())
     }
 
     /// Create a [`SystemStages`] collection, initialized with a stage for each [`CoreStage`].
+    ///
+    /// The [`time_update_system`] and the [`event_update_system`] are added to the start of
+    /// [`CoreStage::First`], in that order, and the [`removed_components_update_system`] and the
+    /// [`frame_components_clear_system`] are added to [`CoreStage::Last`], in that order.
     pub fn with_core_stages() -> Self {
-        Self {
-            stages: vec![
-                Box::new(SimpleSystemStage::new(CoreStage::First)),
-                Box::new(SimpleSystemStage::new(CoreStage::PreUpdate)),
-                Box::new(SimpleSystemStage::new(CoreStage::Update)),
-                Box::new(SimpleSystemStage::new(CoreStage::PostUpdate)),
-                Box::new(SimpleSystemStage::new(CoreStage::Last)),
-            ],
+        let mut first = SimpleSystemStage::new(CoreStage::First);
+        first.add_system(time_update_system.system());
+        first.add_system(event_update_system.system());
+        let mut last = SimpleSystemStage::new(CoreStage::Last);
+        last.add_system(removed_components_update_system.system());
+        last.add_system(frame_components_clear_system.system());
+
+        Self::new(vec![
+            Box::new(first),
+            Box::new(SimpleSystemStage::new(CoreStage::PreUpdate)),
+            Box::new(SimpleSystemStage::new(CoreStage::Update)),
+            Box::new(SimpleSystemStage::new(CoreStage::PostUpdate)),
+            Box::new(last),
+        ])
+    }
+
+    /// Add a startup system.
+    ///
+    /// Startup systems are run exactly once, on the first call to [`run()`][Self::run], before
+    /// any of the other stages. They may be ordered relative to each-other with the methods on
+    /// [`IntoSystemDescriptor`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`EcsError::StartupAlreadyRan`] if the startup systems have already been run.
+    /// Use a run criteria or a resource flag instead if you need a system to run once at a later
+    /// time.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn add_startup_system<Args, S: IntoSystemDescriptor<Args>>(
+        &mut self,
+        system: S,
+    ) -> Result<&mut Self, EcsError> {
+        if self.has_started {
+            return Err(EcsError::StartupAlreadyRan);
         }
+
+        self.startup_stage
+            .add_system_descriptor(system.descriptor());
+
+        Ok(self)
+    }
+
+    /// Declare a resource that the systems need, so that it is created with [`FromWorld`] by
+    /// [`initialize_systems()`][Self::initialize_systems] if it doesn't exist yet.
+    ///
+    /// The resources are initialized in the order that they were declared, before the systems
+    /// are initialized. See [`World::init_resource()`].
+    pub fn init_resource<T: TypedEcsData + FromWorld>(&mut self) -> &mut Self {
+        self.resource_inits.push(|world| world.init_resource::<T>());
+        self
+    }
+
+    /// Returns whether or not the startup systems have been run.
+    pub fn has_started(&self) -> bool {
+        self.has_started
     }
 
     /// Add a [`System`] to the stage with the given label.
-    pub fn add_system_to_stage<Args, S: IntoSystem<Args>, L: StageLabel>(
+    ///
+    /// The system may have ordering constraints attached with the methods on
+    /// [`IntoSystemDescriptor`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no stage with the given label. Use
+    /// [`try_add_system_to_stage()`][Self::try_add_system_to_stage] to handle the error instead.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn add_system_to_stage<Args, S: IntoSystemDescriptor<Args>, L: StageLabel>(
         &mut self,
         label: L,
         system: S,
     ) -> &mut Self {
-        let name = label.name();
-        let id = label.id();
-        let mut stage = None;
+        self.try_add_system_to_stage(label, system)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        for st in &mut self.stages {
-            if st.id() == id {
-                stage = Some(st);
-            }
+    /// Add a [`System`] to the stage with the given label.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is no stage with the given label. The error contains the names of all of
+    /// the stages that do exist.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn try_add_system_to_stage<Args, S: IntoSystemDescriptor<Args>, L: StageLabel>(
+        &mut self,
+        label: L,
+        system: S,
+    ) -> Result<&mut Self, StageNotFoundError> {
+        let idx = self.find_stage(&label)?;
+        self.stages[idx].add_system_descriptor(system.descriptor());
+
+        Ok(self)
+    }
+
+    /// Get the stage with the given label, if it exists.
+    pub fn get_stage<L: StageLabel>(&mut self, label: L) -> Option<&mut Box<dyn SystemStage>> {
+        let idx = self.stage_position(label.id())?;
+        Some(&mut self.stages[idx])
+    }
+
+    /// Insert a new stage immediately before the stage with the given label.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is no stage with the given `label`, or if there is already a stage with the
+    /// same id as the `stage` being inserted.
+    pub fn insert_stage_before<L: StageLabel>(
+        &mut self,
+        label: L,
+        stage: Box<dyn SystemStage>,
+    ) -> Result<&mut Self, EcsError> {
+        let idx = self.stage_idx(&label)?;
+        self.insert_stage_at(idx, stage)
+    }
+
+    /// Insert a new stage immediately after the stage with the given label.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is no stage with the given `label`, or if there is already a stage with the
+    /// same id as the `stage` being inserted.
+    pub fn insert_stage_after<L: StageLabel>(
+        &mut self,
+        label: L,
+        stage: Box<dyn SystemStage>,
+    ) -> Result<&mut Self, EcsError> {
+        let idx = self.stage_idx(&label)?;
+        self.insert_stage_at(idx + 1, stage)
+    }
+
+    /// Remove the stage with the given label, returning it if it existed.
+    ///
+    /// The order of the remaining stages is left unchanged.
+    pub fn remove_stage<L: StageLabel>(&mut self, label: L) -> Option<Box<dyn SystemStage>> {
+        let idx = self.stage_position(label.id())?;
+        self.stage_orderings.remove(&label.id());
+        Some(self.stages.remove(idx))
+    }
+
+    /// Replace the stage with the given label with a new stage, returning the old stage.
+    ///
+    /// The new stage will be run at the same position in the stage list as the old one.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is no stage with the given `label`, or if the new `stage` has the same id as
+    /// a different stage in the collection.
+    pub fn replace_stage<L: StageLabel>(
+        &mut self,
+        label: L,
+        stage: Box<dyn SystemStage>,
+    ) -> Result<Box<dyn SystemStage>, EcsError> {
+        let idx = self.stage_idx(&label)?;
+        self.check_replacement(idx, &*stage)?;
+
+        Ok(std::mem::replace(&mut self.stages[idx], stage))
+    }
+
+    /// Replace the stage with the given label with a new stage, moving all of the systems that
+    /// were added to the old stage into the new one.
+    ///
+    /// The old stage, now empty, is returned.
+    ///
+    /// # Errors
+    ///
+    /// Errors in the same situations as [`replace_stage()`][Self::replace_stage].
+    pub fn replace_stage_with_systems<L: StageLabel>(
+        &mut self,
+        label: L,
+        mut stage: Box<dyn SystemStage>,
+    ) -> Result<Box<dyn SystemStage>, EcsError> {
+        let idx = self.stage_idx(&label)?;
+        // The systems are only moved once the replacement is known to succeed, so that they stay
+        // in the old stage when it doesn't.
+        self.check_replacement(idx, &*stage)?;
+        for system in self.stages[idx].take_systems() {
+            stage.add_system_descriptor(system);
         }
 
-        let Some(stage) = stage else {
-            panic!("Stage with label `{}` ( {} ) doesn't exist.", name, id);
-        };
+        Ok(std::mem::replace(&mut self.stages[idx], stage))
+    }
 
-        stage.add_system(system.system());
+    /// Make sure that the stage at the given index can be replaced with the given stage, whose id
+    /// must not be used by any of the other stages.
+    fn check_replacement(&self, idx: usize, stage: &dyn SystemStage) -> Result<(), EcsError> {
+        let id = stage.id();
+        let is_duplicate = self
+            .stages
+            .iter()
+            .enumerate()
+            .any(|(i, st)| i != idx && st.id() == id);
+        if is_duplicate {
+            return Err(EcsError::StageAlreadyExists(stage.name(), id));
+        }
+        Ok(())
+    }
 
-        self
+    /// Get the index of the stage with the given label in the [`stages`][Self::stages] list.
+    fn stage_idx<L: StageLabel>(&self, label: &L) -> Result<usize, EcsError> {
+        self.find_stage(label).map_err(EcsError::StageNotFound)
+    }
+
+    /// Get the index of the stage with the given label in the [`stages`][Self::stages] list, or a
+    /// [`StageNotFoundError`] if it doesn't exist.
+    fn find_stage<L: StageLabel>(&self, label: &L) -> Result<usize, StageNotFoundError> {
+        let id = label.id();
+        self.stage_position(id).ok_or_else(|| StageNotFoundError {
+            name: label.name(),
+            id,
+            known_stages: self.stages.iter().map(|st| st.name()).collect(),
+        })
+    }
+
+    /// Get the index of the stage with the given id in the [`stages`][Self::stages] list.
+    fn stage_position(&self, id: Ulid) -> Option<usize> {
+        self.stages.iter().position(|st| st.id() == id)
+    }
+
+    /// Insert a stage at the given index, making sure that it's id is not already in use.
+    fn insert_stage_at(
+        &mut self,
+        idx: usize,
+        stage: Box<dyn SystemStage>,
+    ) -> Result<&mut Self, EcsError> {
+        let id = stage.id();
+        if self.stages.iter().any(|st| st.id() == id) {
+            return Err(EcsError::StageAlreadyExists(stage.name(), id));
+        }
+
+        self.stages.insert(idx, stage);
+
+        Ok(self)
     }
 }
 
@@ -77,19 +504,136 @@ pub trait SystemStage: Sync + Send {
     fn name(&self) -> String;
     /// Execute the systems on the given `world`.
     ///
+    /// The `world` is borrowed mutably so that [exclusive systems][System::is_exclusive] may be
+    /// run with [`System::run_mut()`]. Other systems only need shared access.
+    ///
     /// > **Note:** You must call [`initialize()`][Self::initialize] once before calling `run()` one
     /// > or more times.
-    fn run(&mut self, world: &World) -> SystemResult;
+    fn run(&mut self, world: &mut World) -> SystemResult;
     /// Initialize the contained systems for the given `world`.
     ///
     /// Must be called once before calling [`run()`][Self::run].
-    fn initialize(&mut self, world: &mut World);
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError>;
+    /// Check whether the stage should be run this frame.
+    ///
+    /// [`SystemStages::run()`] calls this before every call to [`run()`][Self::run], and skips
+    /// the stage if it returns `false`. The default implementation always returns `true`.
+    fn should_run(&mut self, _world: &World) -> anyhow::Result<bool> {
+        Ok(true)
+    }
 
     /// Add a system to this stage.
     fn add_system(&mut self, system: System);
+
+    /// Add a system, along with it's ordering constraints, to this stage.
+    ///
+    /// The default implementation ignores the ordering constraints and calls
+    /// [`add_system()`][Self::add_system].
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        self.add_system(descriptor.system);
+    }
+
+    /// Remove all of the systems from this stage and return them.
+    fn take_systems(&mut self) -> Vec<SystemDescriptor>;
+
+    /// Get the names of the systems in this stage, in the order that they will be run.
+    ///
+    /// The default implementation returns an empty list.
+    fn system_names(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    /// Create a [`StageReport`] describing this stage and it's systems, for
+    /// [`SystemStages::debug_report()`].
+    ///
+    /// The default implementation reports the [`system_names()`][Self::system_names], without any
+    /// details or nested stages.
+    fn report(&self) -> StageReport {
+        StageReport::new(self)
+    }
+
+    /// Enable or disable recording the execution time of the stage's systems in the
+    /// [`SystemProfile`] resource.
+    ///
+    /// The default implementation does nothing.
+    fn set_profiling(&mut self, _enabled: bool) {}
+
+    /// Update which of the stage's systems are enabled for the next frame, based on the
+    /// [`SystemSet`]s that they are in.
+    ///
+    /// This is called by [`SystemStages::run()`] at the start of every frame. The default
+    /// implementation does nothing.
+    fn update_system_sets(&mut self, _sets: &SystemSets) {}
+
+    /// Execute the systems on the given `world`, handling their errors according to `policy`.
+    ///
+    /// Errors from systems that are skipped are pushed to `errors`, and an error that should abort
+    /// the frame is returned.
+    ///
+    /// The default implementation calls [`run()`][Self::run], so it can't tell which system
+    /// failed, and can't continue running the stage after a failure.
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        match self.run(world) {
+            Ok(()) => Ok(()),
+            Err(error) => policy.handle(
+                SystemError {
+                    stage: self.name(),
+                    system: "<unknown>".into(),
+                    error,
+                },
+                errors,
+            ),
+        }
+    }
+}
+
+/// How [`SystemStages`] handles errors returned by systems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop running the frame, and return the error from [`SystemStages::run()`].
+    #[default]
+    AbortFrame,
+    /// Record the error and keep running the rest of the systems.
+    SkipSystem,
+    /// Panic with the error.
+    Panic,
+}
+
+impl ErrorPolicy {
+    /// Handle a system error according to this policy.
+    ///
+    /// Returns the error if the frame should be aborted, or records it in `errors` if the system
+    /// should be skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy is [`ErrorPolicy::Panic`].
+    pub fn handle(
+        self,
+        error: SystemError,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        match self {
+            ErrorPolicy::AbortFrame => Err(error),
+            ErrorPolicy::SkipSystem => {
+                errors.push(error);
+                Ok(())
+            }
+            ErrorPolicy::Panic => panic!("{}", error),
+        }
+    }
 }
 
 /// A collection of systems that will be run in order.
+///
+/// Systems are run in the order that they were added, unless they have [`SystemOrdering`]
+/// constraints, in which case the systems are sorted to satisfy the constraints when the stage is
+/// initialized.
 pub struct SimpleSystemStage {
     /// The unique identifier for the stage.
     pub id: Ulid,
@@ -99,6 +643,31 @@ pub struct SimpleSystemStage {
     ///
     /// Each system will be run in the order that they are in in this list.
     pub systems: Vec<System>,
+    /// The ordering constraints for each system, at the same index as it's system in
+    /// [`systems`][Self::systems].
+    ///
+    /// Systems without an entry in this list have no ordering constraints.
+    pub orderings: Vec<SystemOrdering>,
+    /// The ids of the [`SystemSet`]s that each system is in, at the same index as it's system in
+    /// [`systems`][Self::systems].
+    ///
+    /// Systems without an entry in this list aren't in any sets.
+    pub sets: Vec<Vec<Ulid>>,
+    /// Whether or not each system is enabled this frame, based on the system sets that it is in.
+    ///
+    /// Systems without an entry in this list are enabled.
+    enabled: Vec<bool>,
+    /// Whether or not each system has been initialized.
+    ///
+    /// Systems without an entry in this list have not been initialized.
+    initialized: Vec<bool>,
+    /// Whether or not to record the execution time of the systems in the [`SystemProfile`]
+    /// resource.
+    pub profiling: bool,
+    /// The run criteria for the stage.
+    ///
+    /// If set, the stage will only run on frames where the run criteria returns `true`.
+    pub run_criteria: Option<System<bool>>,
 }
 
 impl SimpleSystemStage {
@@ -108,8 +677,157 @@ impl SimpleSystemStage {
             id: label.id(),
             name: label.name(),
             systems: Default::default(),
+            orderings: Default::default(),
+            sets: Default::default(),
+            enabled: Default::default(),
+            initialized: Default::default(),
+            profiling: false,
+            run_criteria: None,
         }
     }
+
+    /// Set the run criteria for the stage.
+    ///
+    /// The run criteria is a system that returns a `bool`, which is run every frame before the
+    /// stage to decide whether or not the stage should run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// # #[derive(Clone, Default, TypeUlid)]
+    /// # #[ulid = "01GPST1MCRHPAK83NQFVBE5FE5"]
+    /// # struct GamePaused(bool);
+    /// let stage = SimpleSystemStage::new(CoreStage::Update)
+    ///     .with_run_criteria(|paused: Res<GamePaused>| !paused.0);
+    /// ```
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn with_run_criteria<Args, S: IntoSystem<Args, bool>>(mut self, run_criteria: S) -> Self {
+        self.run_criteria = Some(run_criteria.system());
+        self
+    }
+
+    /// Create the [`SystemError`] reported when the new systems in the stage can't be initialized.
+    pub(crate) fn initialize_error(&self, error: EcsError) -> SystemError {
+        SystemError {
+            stage: self.name.clone(),
+            system: "<initialize>".into(),
+            error: anyhow::anyhow!("{}", error),
+        }
+    }
+
+    /// Sort the systems in the stage so that they satisfy their ordering constraints.
+    ///
+    /// Systems without constraints relative to each-other keep their insertion order.
+    fn sort_systems(&mut self) -> Result<(), EcsError> {
+        let system_count = self.systems.len();
+        self.orderings.resize_with(system_count, Default::default);
+        self.sets.resize_with(system_count, Default::default);
+        self.enabled.resize(system_count, true);
+        self.initialized.resize(system_count, false);
+
+        // Collect the indexes of the systems with each label
+        let mut labeled = HashMap::<&str, Vec<usize>>::default();
+        for (i, ordering) in self.orderings.iter().enumerate() {
+            for label in &ordering.labels {
+                labeled.entry(label.as_str()).or_default().push(i);
+            }
+        }
+
+        // Build the graph of systems that must run before other systems
+        let mut dependents = vec![Vec::new(); system_count];
+        let mut dependency_counts = vec![0usize; system_count];
+        let mut add_edge = |from: usize, to: usize| {
+            if from != to && !dependents[from].contains(&to) {
+                dependents[from].push(to);
+                dependency_counts[to] += 1;
+            }
+        };
+        for (i, ordering) in self.orderings.iter().enumerate() {
+            for label in &ordering.before {
+                for &j in labeled.get(label.as_str()).into_iter().flatten() {
+                    add_edge(i, j);
+                }
+            }
+            for label in &ordering.after {
+                for &j in labeled.get(label.as_str()).into_iter().flatten() {
+                    add_edge(j, i);
+                }
+            }
+        }
+
+        // Topologically sort the systems, always picking the earliest-added system that is ready,
+        // so that unconstrained systems stay in insertion order.
+        let mut ready = std::collections::BinaryHeap::new();
+        for (i, &count) in dependency_counts.iter().enumerate() {
+            if count == 0 {
+                ready.push(std::cmp::Reverse(i));
+            }
+        }
+        let mut order = Vec::with_capacity(system_count);
+        while let Some(std::cmp::Reverse(i)) = ready.pop() {
+            order.push(i);
+            for &j in &dependents[i] {
+                dependency_counts[j] -= 1;
+                if dependency_counts[j] == 0 {
+                    ready.push(std::cmp::Reverse(j));
+                }
+            }
+        }
+
+        if order.len() != system_count {
+            let systems = dependency_counts
+                .iter()
+                .enumerate()
+                .filter(|(_, &count)| count > 0)
+                .map(|(i, _)| self.systems[i].name().to_string())
+                .collect();
+            return Err(EcsError::SystemOrderCycle(self.name.clone(), systems));
+        }
+
+        let mut systems = self.systems.drain(..).map(Some).collect::<Vec<_>>();
+        let mut orderings = self.orderings.drain(..).map(Some).collect::<Vec<_>>();
+        let mut sets = self.sets.drain(..).map(Some).collect::<Vec<_>>();
+        let enabled = std::mem::take(&mut self.enabled);
+        let initialized = std::mem::take(&mut self.initialized);
+        for i in order {
+            self.systems.push(systems[i].take().unwrap());
+            self.orderings.push(orderings[i].take().unwrap());
+            self.sets.push(sets[i].take().unwrap());
+            self.enabled.push(enabled[i]);
+            self.initialized.push(initialized[i]);
+        }
+
+        Ok(())
+    }
+
+    /// Initialize the systems that were added to the stage after it was initialized, sorting them
+    /// into place according to their ordering constraints.
+    ///
+    /// This is called automatically before the stage is run, so that systems may be added at any
+    /// time. Returns `true` if there were any new systems.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the ordering constraints of the systems form a cycle.
+    pub fn initialize_new_systems(&mut self, world: &mut World) -> Result<bool, EcsError> {
+        if self.initialized.len() == self.systems.len()
+            && self.initialized.iter().all(|&initialized| initialized)
+        {
+            return Ok(false);
+        }
+
+        self.sort_systems()?;
+        for (system, initialized) in self.systems.iter().zip(&mut self.initialized) {
+            if !*initialized {
+                system.validate_access()?;
+                system.initialize(world);
+                *initialized = true;
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 impl SystemStage for SimpleSystemStage {
@@ -121,26 +839,186 @@ impl SystemStage for SimpleSystemStage {
         self.name.clone()
     }
 
-    fn run(&mut self, world: &World) -> SystemResult {
+    fn run(&mut self, world: &mut World) -> SystemResult {
+        self.run_with_policy(world, ErrorPolicy::AbortFrame, &mut Vec::new())
+            .map_err(|error| error.into())
+    }
+
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError> {
+        self.sort_systems()?;
+
+        // Always initialize the profile, so that profiling may be enabled after initialization.
+        world.resources.init::<SystemProfile>();
+
+        if let Some(run_criteria) = &self.run_criteria {
+            run_criteria.validate_access()?;
+            run_criteria.initialize(world);
+        }
         for system in &mut self.systems {
-            system.run(world)?;
+            system.validate_access()?;
+            system.initialize(world);
         }
+        self.initialized.clear();
+        self.initialized.resize(self.systems.len(), true);
 
         Ok(())
     }
 
-    fn initialize(&mut self, world: &mut World) {
-        for system in &mut self.systems {
-            system.initialize(world);
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        if let Err(error) = self.initialize_new_systems(world) {
+            // The stage can't be run if the new systems can't be sorted.
+            return policy.handle(self.initialize_error(error), errors);
+        }
+
+        let profile = self
+            .profiling
+            .then(|| world.resources.get::<SystemProfile>());
+
+        for (i, system) in self.systems.iter_mut().enumerate() {
+            if !self.enabled.get(i).copied().unwrap_or(true) {
+                continue;
+            }
+
+            let result = match &profile {
+                Some(profile) => {
+                    let start = std::time::Instant::now();
+                    let result = system.run_mut(world);
+                    let time = start.elapsed();
+                    profile
+                        .borrow_mut()
+                        .record(&self.name, i, system.name(), time);
+                    result
+                }
+                None => system.run_mut(world),
+            };
+            // Advance the change tick after every system, so that the systems detect the changes
+            // made by the systems that ran before them, but not their own changes.
+            world.increment_change_tick();
+
+            if let Err(error) = result {
+                policy.handle(
+                    SystemError {
+                        stage: self.name.clone(),
+                        system: system.name().into(),
+                        error,
+                    },
+                    errors,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    fn update_system_sets(&mut self, sets: &SystemSets) {
+        let system_sets = &self.sets;
+        self.enabled.clear();
+        self.enabled.extend((0..self.systems.len()).map(|i| {
+            system_sets
+                .get(i)
+                .map_or(true, |ids| ids.iter().all(|&id| sets.is_id_enabled(id)))
+        }));
+    }
+
+    fn should_run(&mut self, world: &World) -> anyhow::Result<bool> {
+        match &mut self.run_criteria {
+            Some(run_criteria) => run_criteria.run(world),
+            None => Ok(true),
         }
     }
 
     fn add_system(&mut self, system: System) {
-        self.systems.push(system);
+        self.add_system_descriptor(SystemDescriptor {
+            system,
+            ordering: default(),
+            sets: default(),
+        });
+    }
+
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        // Make sure any systems pushed directly to `systems` keep their place in `orderings` and
+        // `sets`.
+        self.orderings
+            .resize_with(self.systems.len(), Default::default);
+        self.sets.resize_with(self.systems.len(), Default::default);
+
+        self.systems.push(descriptor.system);
+        self.orderings.push(descriptor.ordering);
+        self.sets.push(descriptor.sets);
+    }
+
+    fn take_systems(&mut self) -> Vec<SystemDescriptor> {
+        let mut orderings = std::mem::take(&mut self.orderings).into_iter();
+        let mut sets = std::mem::take(&mut self.sets).into_iter();
+        self.enabled.clear();
+        self.initialized.clear();
+        std::mem::take(&mut self.systems)
+            .into_iter()
+            .map(|system| SystemDescriptor {
+                system,
+                ordering: orderings.next().unwrap_or_default(),
+                sets: sets.next().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn system_names(&self) -> Vec<&str> {
+        self.systems.iter().map(|system| system.name()).collect()
+    }
+
+    fn report(&self) -> StageReport {
+        let mut report = StageReport::new(self);
+        if let Some(run_criteria) = &self.run_criteria {
+            report
+                .details
+                .push(format!("run criteria: {}", run_criteria.name()));
+        }
+
+        report
     }
 }
 
+pub use bones_ecs_macros::StageLabel;
+
 /// Trait for things that may be used to identify a system stage.
+///
+/// This can be derived for unit structs and for enums without data in their variants. The derived
+/// [`name()`][Self::name] is the name of the struct or variant, and the derived
+/// [`id()`][Self::id] is created from the full path to the struct or variant with
+/// [`stage_label_id()`].
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// #[derive(StageLabel)]
+/// enum MyStage {
+///     Physics,
+///     Network,
+/// }
+///
+/// assert_eq!(MyStage::Physics.name(), "Physics");
+/// assert_ne!(MyStage::Physics.id(), MyStage::Network.id());
+/// ```
+///
+/// Enums with data in their variants are rejected:
+///
+/// ```compile_fail
+/// # use bones_ecs::prelude::*;
+/// #[derive(StageLabel)]
+/// enum MyStage {
+///     Physics(u32),
+/// }
+/// ```
 pub trait StageLabel {
     /// Returns the human-readable name of the label, used in error messages.
     fn name(&self) -> String;
@@ -185,3 +1063,703 @@ impl StageLabel for CoreStage {
         }
     }
 }
+
+/// Create the stage label id for the label at the given `path`.
+///
+/// This is used by the [`StageLabel`] derive macro, with a `path` of
+/// `"{module_path}::{TypeName}"` for structs, and `"{module_path}::{TypeName}::{VariantName}"` for
+/// enum variants, where `module_path` is the output of [`module_path!()`], including the crate
+/// name.
+///
+/// The id is the 128 bit [FNV-1a] hash of the UTF-8 bytes of the path. This will never change
+/// between versions of this crate, so the id of a label stays the same across compiles, as long as
+/// the label isn't renamed or moved to a different module.
+///
+/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+pub const fn stage_label_id(path: &str) -> Ulid {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let bytes = path.as_bytes();
+    let mut hash = OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u128;
+        hash = hash.wrapping_mul(PRIME);
+        i += 1;
+    }
+
+    Ulid(hash)
+}
+
+/// The label of the stage that contains the [startup systems][SystemStages::add_startup_system].
+///
+/// This stage is not part of [`SystemStages::stages`], systems can only be added to it with
+/// [`SystemStages::add_startup_system()`].
+#[derive(Copy, Clone, Debug)]
+pub struct StartupStage;
+
+impl StageLabel for StartupStage {
+    fn name(&self) -> String {
+        "Startup".into()
+    }
+
+    fn id(&self) -> Ulid {
+        Ulid(2022185706062420216081436324622345542)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    /// A custom stage label for testing.
+    #[derive(Copy, Clone, Debug, StageLabel)]
+    enum TestStage {
+        Physics,
+        Network,
+    }
+
+    #[derive(StageLabel)]
+    struct UnitStage;
+
+    #[test]
+    fn derive_stage_label() {
+        assert_eq!(TestStage::Network.name(), "Network");
+        assert_eq!(UnitStage.name(), "UnitStage");
+        assert_eq!(
+            TestStage::Physics.id(),
+            stage_label_id("bones_ecs::stage::tests::TestStage::Physics")
+        );
+        assert_eq!(
+            UnitStage.id(),
+            stage_label_id("bones_ecs::stage::tests::UnitStage")
+        );
+        assert_ne!(TestStage::Physics.id(), TestStage::Network.id());
+    }
+
+    #[test]
+    fn stage_label_id_is_stable() {
+        // FNV-1a test vectors, to make sure the ids never change.
+        assert_eq!(stage_label_id("").0, 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(stage_label_id("a").0, 0xd228cb696f1a8caf78912b704e4a8964);
+    }
+
+    fn stage_ids(stages: &SystemStages) -> Vec<Ulid> {
+        stages.stages.iter().map(|st| st.id()).collect()
+    }
+
+    #[test]
+    fn insert_stage_middle() {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .insert_stage_after(
+                CoreStage::Update,
+                Box::new(SimpleSystemStage::new(TestStage::Physics)),
+            )
+            .unwrap()
+            .insert_stage_before(
+                CoreStage::PostUpdate,
+                Box::new(SimpleSystemStage::new(TestStage::Network)),
+            )
+            .unwrap();
+
+        assert_eq!(
+            stage_ids(&stages),
+            vec![
+                CoreStage::First.id(),
+                CoreStage::PreUpdate.id(),
+                CoreStage::Update.id(),
+                TestStage::Physics.id(),
+                TestStage::Network.id(),
+                CoreStage::PostUpdate.id(),
+                CoreStage::Last.id(),
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_stage_front_and_back() {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .insert_stage_before(
+                CoreStage::First,
+                Box::new(SimpleSystemStage::new(TestStage::Physics)),
+            )
+            .unwrap()
+            .insert_stage_after(
+                CoreStage::Last,
+                Box::new(SimpleSystemStage::new(TestStage::Network)),
+            )
+            .unwrap();
+
+        let ids = stage_ids(&stages);
+        assert_eq!(ids.len(), 7);
+        assert_eq!(ids[0], TestStage::Physics.id());
+        assert_eq!(ids[1], CoreStage::First.id());
+        assert_eq!(ids[5], CoreStage::Last.id());
+        assert_eq!(ids[6], TestStage::Network.id());
+    }
+
+    #[test]
+    fn insert_stage_missing_anchor() {
+        let mut stages = SystemStages::with_core_stages();
+        let result = stages.insert_stage_after(
+            TestStage::Physics,
+            Box::new(SimpleSystemStage::new(TestStage::Network)),
+        );
+
+        assert!(matches!(result, Err(EcsError::StageNotFound(..))));
+        assert_eq!(stages.stages.len(), 5);
+    }
+
+    #[test]
+    fn try_add_system_to_missing_stage() {
+        let mut stages = SystemStages::with_core_stages();
+        let Err(error) = stages.try_add_system_to_stage(TestStage::Physics, || ()) else {
+            panic!("Expected stage not found error");
+        };
+
+        assert_eq!(error.name, "Physics");
+        assert_eq!(error.id, TestStage::Physics.id());
+        assert_eq!(
+            error.known_stages,
+            vec!["First", "PreUpdate", "Update", "PostUpdate", "Last"]
+        );
+
+        assert!(stages
+            .try_add_system_to_stage(CoreStage::Update, || ())
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Physics")]
+    fn add_system_to_missing_stage_panics() {
+        SystemStages::with_core_stages().add_system_to_stage(TestStage::Physics, || ());
+    }
+
+    #[test]
+    fn get_stage() {
+        let mut stages = SystemStages::with_core_stages();
+        let stage = stages.get_stage(CoreStage::Update).unwrap();
+        assert_eq!(stage.id(), CoreStage::Update.id());
+        stage.add_system((|| ()).system());
+
+        let taken = stages.get_stage(CoreStage::Update).unwrap().take_systems();
+        assert_eq!(taken.len(), 1);
+        assert!(stages.get_stage(TestStage::Physics).is_none());
+    }
+
+    #[test]
+    fn insert_stage_duplicate_id() {
+        let mut stages = SystemStages::with_core_stages();
+        let result = stages.insert_stage_after(
+            CoreStage::Update,
+            Box::new(SimpleSystemStage::new(CoreStage::First)),
+        );
+
+        assert!(matches!(result, Err(EcsError::StageAlreadyExists(..))));
+        assert_eq!(stages.stages.len(), 5);
+    }
+
+    #[test]
+    fn remove_stage() {
+        let mut stages = SystemStages::with_core_stages();
+        let removed = stages.remove_stage(CoreStage::PreUpdate).unwrap();
+
+        assert_eq!(removed.id(), CoreStage::PreUpdate.id());
+        assert!(stages.remove_stage(CoreStage::PreUpdate).is_none());
+        assert_eq!(
+            stage_ids(&stages),
+            vec![
+                CoreStage::First.id(),
+                CoreStage::Update.id(),
+                CoreStage::PostUpdate.id(),
+                CoreStage::Last.id(),
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_stage() {
+        let mut stages = SystemStages::with_core_stages();
+        stages.add_system_to_stage(CoreStage::Update, || ());
+
+        let old = stages
+            .replace_stage(
+                CoreStage::Update,
+                Box::new(SimpleSystemStage::new(TestStage::Physics)),
+            )
+            .unwrap();
+        assert_eq!(old.id(), CoreStage::Update.id());
+        assert_eq!(stage_ids(&stages)[2], TestStage::Physics.id());
+
+        // Replacing a stage with a stage that already exists elsewhere is an error
+        let result = stages.replace_stage(
+            TestStage::Physics,
+            Box::new(SimpleSystemStage::new(CoreStage::First)),
+        );
+        assert!(matches!(result, Err(EcsError::StageAlreadyExists(..))));
+
+        // Replacing a stage with a stage with the same ID is allowed
+        stages
+            .replace_stage(
+                TestStage::Physics,
+                Box::new(SimpleSystemStage::new(TestStage::Physics)),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn replace_stage_with_systems() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages.add_system_to_stage(CoreStage::Update, |mut counter: ResMut<u32>| {
+            *counter += 1;
+        });
+
+        let mut old = stages
+            .replace_stage_with_systems(
+                CoreStage::Update,
+                Box::new(SimpleSystemStage::new(TestStage::Physics)),
+            )
+            .unwrap();
+        assert!(old.take_systems().is_empty());
+
+        stages.initialize_systems(&mut world).unwrap();
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+
+        // The systems stay in the old stage when it can't be replaced.
+        let result = stages.replace_stage_with_systems(
+            TestStage::Physics,
+            Box::new(SimpleSystemStage::new(CoreStage::First)),
+        );
+        assert!(matches!(result, Err(EcsError::StageAlreadyExists(..))));
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+    }
+
+    /// Resource used to record the order that systems run in.
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPKEYAVY5S9S0BX97G7G5MQ3"]
+    struct RunOrder(Vec<&'static str>);
+
+    #[test]
+    fn system_ordering() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut order: ResMut<RunOrder>| order.0.push("damage"))
+                    .label("damage")
+                    .after("collision"),
+            )
+            .add_system_to_stage(CoreStage::Update, |mut order: ResMut<RunOrder>| {
+                order.0.push("unordered")
+            })
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut order: ResMut<RunOrder>| order.0.push("cleanup")).after("damage"),
+            )
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut order: ResMut<RunOrder>| order.0.push("collision"))
+                    .label("collision")
+                    .before("unordered-label-that-doesnt-exist"),
+            );
+
+        stages.initialize_systems(&mut world).unwrap();
+        stages.run(&mut world).unwrap();
+
+        assert_eq!(
+            world.resources.get::<RunOrder>().borrow().0,
+            vec!["unordered", "collision", "damage", "cleanup"]
+        );
+    }
+
+    #[test]
+    fn system_ordering_cycle() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::Update, (|| ()).label("a").after("b"))
+            .add_system_to_stage(CoreStage::Update, (|| ()).label("b").after("a"))
+            .add_system_to_stage(CoreStage::Update, (|| ()).label("c"));
+
+        let result = stages.initialize_systems(&mut world);
+        let Err(EcsError::SystemOrderCycle(stage, systems)) = result else {
+            panic!("Expected system order cycle error");
+        };
+        assert_eq!(stage, "Update");
+        assert_eq!(systems.len(), 2);
+    }
+
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPSVZYNVE2B0SM58GC9VKCAK"]
+    struct GamePaused(bool);
+
+    #[test]
+    fn run_criteria() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .insert_stage_after(
+                CoreStage::Update,
+                Box::new(
+                    SimpleSystemStage::new(TestStage::Physics)
+                        .with_run_criteria(|paused: Res<GamePaused>| !paused.0),
+                ),
+            )
+            .unwrap()
+            .add_system_to_stage(TestStage::Physics, |mut counter: ResMut<u32>| *counter += 1);
+        stages.initialize_systems(&mut world).unwrap();
+
+        // The criteria initializes the resources it uses
+        assert!(world.resources.try_get::<GamePaused>().is_some());
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+
+        world.resources.get::<GamePaused>().borrow_mut().0 = true;
+        stages.run(&mut world).unwrap();
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+
+        world.resources.get::<GamePaused>().borrow_mut().0 = false;
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+    }
+
+    #[test]
+    fn run_criteria_error() {
+        let mut world = World::new();
+        let mut stages = SystemStages::new(vec![Box::new(
+            SimpleSystemStage::new(TestStage::Physics).with_run_criteria(
+                |_world: &World| -> anyhow::Result<bool> { anyhow::bail!("Criteria failed") },
+            ),
+        )]);
+        stages.initialize_systems(&mut world).unwrap();
+
+        assert!(stages.run(&mut world).is_err());
+    }
+
+    #[test]
+    fn startup_systems_run_once() {
+        #[derive(Clone, Default, TypeUlid)]
+        #[ulid = "01GPSXB5M6CC0PTPF5RT9H3WAM"]
+        struct StartupCount(u32);
+
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_startup_system(|mut count: ResMut<StartupCount>| count.0 += 1)
+            .unwrap()
+            .add_system_to_stage(CoreStage::Update, |mut count: ResMut<u32>| *count += 1);
+        stages.initialize_systems(&mut world).unwrap();
+        assert!(!stages.has_started());
+
+        for _ in 0..3 {
+            stages.run(&mut world).unwrap();
+        }
+
+        assert!(stages.has_started());
+        assert_eq!(world.resources.get::<StartupCount>().borrow().0, 1);
+        assert_eq!(*world.resources.get::<u32>().borrow(), 3);
+
+        assert!(matches!(
+            stages.add_startup_system(|| ()),
+            Err(EcsError::StartupAlreadyRan)
+        ));
+    }
+
+    fn error_policy_stages(policy: ErrorPolicy) -> SystemStages {
+        let mut stages = SystemStages::with_core_stages().with_error_policy(policy);
+        stages
+            .add_system_to_stage(CoreStage::Update, || -> SystemResult {
+                anyhow::bail!("Failed")
+            })
+            .add_system_to_stage(CoreStage::Update, |mut count: ResMut<u32>| *count += 1)
+            .add_system_to_stage(CoreStage::Last, |mut count: ResMut<u32>| *count += 1);
+
+        stages
+    }
+
+    #[test]
+    fn error_policy_abort_frame() {
+        let mut world = World::new();
+        let mut stages = error_policy_stages(ErrorPolicy::AbortFrame);
+        stages.initialize_systems(&mut world).unwrap();
+
+        assert!(stages.run(&mut world).is_err());
+        assert!(stages.errors().is_empty());
+        assert_eq!(*world.resources.get::<u32>().borrow(), 0);
+
+        let errors = stages.run_with_errors(&mut world);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].stage, "Update");
+        assert_eq!(*world.resources.get::<u32>().borrow(), 0);
+    }
+
+    #[test]
+    fn error_policy_skip_system() {
+        let mut world = World::new();
+        let mut stages = error_policy_stages(ErrorPolicy::SkipSystem);
+        stages.initialize_systems(&mut world).unwrap();
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(stages.errors().len(), 1);
+        assert_eq!(stages.errors()[0].stage, "Update");
+        assert!(stages.errors()[0].system.contains("error_policy_stages"));
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+
+        // Errors are only kept for the most recent frame
+        stages.run(&mut world).unwrap();
+        assert_eq!(stages.errors().len(), 1);
+
+        assert_eq!(stages.run_with_errors(&mut world).len(), 1);
+        assert_eq!(*world.resources.get::<u32>().borrow(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed")]
+    fn error_policy_panic() {
+        let mut world = World::new();
+        let mut stages = error_policy_stages(ErrorPolicy::Panic);
+        stages.initialize_systems(&mut world).unwrap();
+
+        let _ = stages.run(&mut world);
+    }
+
+    #[test]
+    fn profile_report() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::First, || ())
+            .add_system_to_stage(CoreStage::Update, |_a: Res<u32>| ())
+            .add_system_to_stage(CoreStage::Update, |_a: Res<u64>| ())
+            .add_startup_system(|| ())
+            .unwrap();
+        stages.initialize_systems(&mut world).unwrap();
+
+        stages.run(&mut world).unwrap();
+        assert!(stages.profile_report(&world).is_empty());
+
+        stages.enable_profiling(true);
+        stages.run(&mut world).unwrap();
+        stages.run(&mut world).unwrap();
+
+        // The `time_update_system` and `event_update_system` are also profiled, as the first
+        // systems in `First`, along with the `removed_components_update_system` and the
+        // `frame_components_clear_system` in `Last`.
+        let report = stages.profile_report(&world);
+        assert_eq!(report.len(), 7);
+        assert!(report.iter().all(|entry| entry.samples == 2));
+        assert!(report
+            .windows(2)
+            .all(|entries| entries[0].average >= entries[1].average));
+        let mut systems = report
+            .iter()
+            .map(|entry| (entry.stage.as_str(), entry.index))
+            .collect::<Vec<_>>();
+        systems.sort();
+        assert_eq!(
+            systems,
+            vec![
+                ("First", 0),
+                ("First", 1),
+                ("First", 2),
+                ("Last", 0),
+                ("Last", 1),
+                ("Update", 0),
+                ("Update", 1)
+            ]
+        );
+
+        stages.enable_profiling(false);
+        stages.run(&mut world).unwrap();
+        assert!(stages.profile_report(&world).iter().all(|e| e.samples == 2));
+    }
+
+    #[derive(Copy, Clone)]
+    struct AiSet;
+
+    impl SystemSet for AiSet {
+        fn name(&self) -> String {
+            "AiSet".into()
+        }
+
+        fn id(&self) -> Ulid {
+            Ulid(2022197881461137868757436437968740267)
+        }
+    }
+
+    #[test]
+    fn system_sets() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut count: ResMut<u32>| *count += 1).in_set(AiSet),
+            )
+            .add_system_to_stage(CoreStage::Update, |mut count: ResMut<u64>| *count += 1)
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        stages.run(&mut world).unwrap();
+        stages.set_enabled(AiSet, false);
+        assert!(world
+            .resources
+            .get::<SystemSets>()
+            .borrow()
+            .is_enabled(AiSet));
+
+        stages.run(&mut world).unwrap();
+        assert!(!world
+            .resources
+            .get::<SystemSets>()
+            .borrow()
+            .is_enabled(AiSet));
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+        assert_eq!(*world.resources.get::<u64>().borrow(), 2);
+
+        world
+            .resources
+            .get::<SystemSets>()
+            .borrow_mut()
+            .set_enabled(AiSet, true);
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+    }
+
+    #[test]
+    fn system_set_disabled_mid_frame() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::First, |mut sets: ResMut<SystemSets>| {
+                sets.set_enabled(AiSet, false)
+            })
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut count: ResMut<u32>| *count += 1).in_set(AiSet),
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        // The set is disabled during the first frame, but it still runs until the next frame.
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+    }
+
+    #[test]
+    fn exclusive_systems() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::Update, |mut order: ResMut<RunOrder>| {
+                order.0.push("before")
+            })
+            .add_system_to_stage(CoreStage::Update, |world: &mut World| {
+                world
+                    .resources
+                    .get::<RunOrder>()
+                    .borrow_mut()
+                    .0
+                    .push("exclusive");
+                world.resources.insert(7u32);
+            })
+            .add_system_to_stage(
+                CoreStage::Update,
+                |mut order: ResMut<RunOrder>, count: Res<u32>| {
+                    assert_eq!(*count, 7);
+                    order.0.push("after")
+                },
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+        stages.run(&mut world).unwrap();
+
+        assert_eq!(
+            world.resources.get::<RunOrder>().borrow().0,
+            vec!["before", "exclusive", "after"]
+        );
+    }
+
+    #[test]
+    fn late_added_systems_are_initialized() {
+        #[derive(Clone, Default, TypeUlid)]
+        #[ulid = "01GPXA0Z5K2VS3TQ8V8NQ4C3JM"]
+        struct LateResource(u32);
+
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                (|mut order: ResMut<RunOrder>| order.0.push("early")).label("early"),
+            )
+            .initialize_systems(&mut world)
+            .unwrap();
+        stages.run(&mut world).unwrap();
+
+        // The new system's resource is initialized, and it is sorted before the existing system.
+        stages.add_system_to_stage(
+            CoreStage::Update,
+            (|mut order: ResMut<RunOrder>, mut late: ResMut<LateResource>| {
+                late.0 += 1;
+                order.0.push("late")
+            })
+            .before("early"),
+        );
+        assert!(world.resources.try_get::<LateResource>().is_none());
+        stages.run(&mut world).unwrap();
+
+        assert_eq!(world.resources.get::<LateResource>().borrow().0, 1);
+        assert_eq!(
+            world.resources.get::<RunOrder>().borrow().0,
+            vec!["early", "late", "early"]
+        );
+    }
+
+    #[test]
+    fn exclusive_system_requires_mut_world() {
+        let world = World::new();
+        let mut system = (|_world: &mut World| ()).system();
+        assert!(system.is_exclusive());
+        assert!(system.run(&world).is_err());
+    }
+
+    #[test]
+    fn system_error_names() {
+        fn apply_damage() -> SystemResult {
+            anyhow::bail!("No health")
+        }
+
+        let mut world = World::new();
+        let mut stage = SimpleSystemStage::new(CoreStage::Update);
+        stage.add_system(apply_damage.system());
+        stage.add_system((|| -> SystemResult { anyhow::bail!("Closure") }).system());
+        stage.add_system(apply_damage.named("custom_name"));
+        stage.initialize(&mut world).unwrap();
+
+        let error = stage.run(&mut world).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "System `bones_ecs::stage::tests::system_error_names::apply_damage` in stage `Update` \
+            failed: No health"
+        );
+
+        let mut errors = Vec::new();
+        stage
+            .run_with_policy(&mut world, ErrorPolicy::SkipSystem, &mut errors)
+            .unwrap();
+        assert!(errors[1].system.contains("system_error_names::{{closure}}"));
+        assert_eq!(errors[2].system, "custom_name");
+    }
+}
@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Trait for resources that can report the time that has passed since the previous frame.
+///
+/// This is used by [`FixedTimestepStage`] to know how much time to accumulate every time it is run.
+pub trait DeltaTime {
+    /// The amount of time that has passed since the previous frame.
+    fn delta(&self) -> Duration;
+}
+
+/// Resource containing the interpolation alpha of the most recently run [`FixedTimestepStage`].
+///
+/// This is the fraction of a fixed step that has accumulated, but has not been simulated yet,
+/// ranging from `0.0` to `1.0`. It can be used to interpolate rendered transforms between the last
+/// two fixed updates.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut, TypeUlid)]
+#[ulid = "01GPMA7W6RM5NJ8JZ0S0VBR7G6"]
+pub struct FixedTimestepAlpha(pub f32);
+
+/// A stage that runs it's systems at a fixed rate, regardless of how often the stage itself is run.
+///
+/// Every time the stage is run, it reads the frame's delta time from the resource `T` that it was
+/// created for, and adds it to an accumulator. The systems are then run once for every full `step`
+/// in the accumulator, up to [`max_steps`][Self::max_steps] times per frame.
+///
+/// After running, the [`FixedTimestepAlpha`] resource is updated with the remaining fraction of a
+/// step.
+pub struct FixedTimestepStage {
+    /// The amount of time simulated by each run of the stage's systems.
+    pub step: Duration,
+    /// The maximum number of times the systems will be run in a single frame.
+    ///
+    /// If more steps than this have accumulated, the extra time is discarded to keep a slow frame
+    /// from causing even slower frames after it.
+    pub max_steps: usize,
+    accumulator: Duration,
+    delta_fn: fn(&World) -> Duration,
+    stage: SimpleSystemStage,
+}
+
+impl FixedTimestepStage {
+    /// The default value of [`max_steps`][Self::max_steps].
+    pub const DEFAULT_MAX_STEPS: usize = 5;
+
+    /// Create a new, empty stage, for the given label, that runs it's systems every `step`, using
+    /// the delta time from the resource `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn new<L: StageLabel, T: TypedEcsData + DeltaTime>(label: L, step: Duration) -> Self {
+        assert!(!step.is_zero(), "Fixed timestep must not be zero");
+        Self {
+            step,
+            max_steps: Self::DEFAULT_MAX_STEPS,
+            accumulator: Duration::ZERO,
+            delta_fn: |world| {
+                world
+                    .resources
+                    .try_get::<T>()
+                    .map(|time| time.borrow().delta())
+                    .unwrap_or_default()
+            },
+            stage: SimpleSystemStage::new(label),
+        }
+    }
+
+    /// Set the maximum number of steps that may be run in a single frame.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Set the run criteria for the stage.
+    ///
+    /// See [`SimpleSystemStage::with_run_criteria()`]. Time is not accumulated on frames where the
+    /// run criteria returns `false`.
+    pub fn with_run_criteria<Args, S: IntoSystem<Args, bool>>(mut self, run_criteria: S) -> Self {
+        self.stage = self.stage.with_run_criteria(run_criteria);
+        self
+    }
+
+    /// Accumulate the frame's delta time, and return the number of steps that should be run this
+    /// frame.
+    fn advance(&mut self, world: &World) -> usize {
+        self.accumulator += (self.delta_fn)(world);
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        // Drop any whole steps that we don't have time to run
+        if self.accumulator >= self.step {
+            let remainder = self.accumulator.as_nanos() % self.step.as_nanos();
+            self.accumulator = Duration::from_nanos(remainder as u64);
+        }
+
+        steps
+    }
+
+    /// Write the current interpolation alpha to the [`FixedTimestepAlpha`] resource.
+    fn update_alpha(&self, world: &World) {
+        *world.resources.get::<FixedTimestepAlpha>().borrow_mut() =
+            FixedTimestepAlpha(self.alpha());
+    }
+
+    /// Get the interpolation alpha, the fraction of a step that has been accumulated but not
+    /// simulated yet.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
+
+impl SystemStage for FixedTimestepStage {
+    fn id(&self) -> Ulid {
+        self.stage.id()
+    }
+
+    fn name(&self) -> String {
+        self.stage.name()
+    }
+
+    fn run(&mut self, world: &mut World) -> SystemResult {
+        for _ in 0..self.advance(world) {
+            self.stage.run(world)?;
+        }
+        self.update_alpha(world);
+
+        Ok(())
+    }
+
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        for _ in 0..self.advance(world) {
+            self.stage.run_with_policy(world, policy, errors)?;
+        }
+        self.update_alpha(world);
+
+        Ok(())
+    }
+
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError> {
+        world.resources.init::<FixedTimestepAlpha>();
+        self.stage.initialize(world)
+    }
+
+    fn should_run(&mut self, world: &World) -> anyhow::Result<bool> {
+        self.stage.should_run(world)
+    }
+
+    fn add_system(&mut self, system: System) {
+        self.stage.add_system(system);
+    }
+
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        self.stage.add_system_descriptor(descriptor);
+    }
+
+    fn take_systems(&mut self) -> Vec<SystemDescriptor> {
+        self.stage.take_systems()
+    }
+
+    fn system_names(&self) -> Vec<&str> {
+        self.stage.system_names()
+    }
+
+    fn report(&self) -> StageReport {
+        let mut report = self.stage.report();
+        report.details.push(format!(
+            "fixed timestep of {:?}, up to {} steps per frame",
+            self.step, self.max_steps
+        ));
+
+        report
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        self.stage.set_profiling(enabled);
+    }
+
+    fn update_system_sets(&mut self, sets: &SystemSets) {
+        self.stage.update_system_sets(sets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPMAJ3QFBFZ5R30XWW36J7Q0"]
+    struct TestTime(Duration);
+
+    impl DeltaTime for TestTime {
+        fn delta(&self) -> Duration {
+            self.0
+        }
+    }
+
+    #[derive(Copy, Clone)]
+    struct FixedUpdate;
+
+    impl StageLabel for FixedUpdate {
+        fn name(&self) -> String {
+            "FixedUpdate".into()
+        }
+
+        fn id(&self) -> Ulid {
+            Ulid(2022173091064897942134414423186556585)
+        }
+    }
+
+    /// Create a world and a fixed timestep stage with a 10ms step that counts how many times it
+    /// runs in the `u32` resource.
+    fn setup() -> (World, SystemStages) {
+        let mut world = World::new();
+        let mut stages = SystemStages::new(vec![Box::new(
+            FixedTimestepStage::new::<_, TestTime>(FixedUpdate, Duration::from_millis(10))
+                .with_max_steps(3),
+        )]);
+        stages.add_system_to_stage(FixedUpdate, |mut counter: ResMut<u32>| *counter += 1);
+        stages.initialize_systems(&mut world).unwrap();
+
+        (world, stages)
+    }
+
+    fn run_frame(world: &mut World, stages: &mut SystemStages, delta_ms: u64) -> u32 {
+        *world.resources.get::<u32>().borrow_mut() = 0;
+        world.resources.get::<TestTime>().borrow_mut().0 = Duration::from_millis(delta_ms);
+        stages.run(world).unwrap();
+        *world.resources.get::<u32>().borrow()
+    }
+
+    #[test]
+    fn fixed_timestep_zero_steps() {
+        let (mut world, mut stages) = setup();
+        world.resources.init::<TestTime>();
+
+        assert_eq!(run_frame(&mut world, &mut stages, 4), 0);
+        assert_eq!(run_frame(&mut world, &mut stages, 4), 0);
+        let alpha = world.resources.get::<FixedTimestepAlpha>();
+        assert!((alpha.borrow().0 - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn fixed_timestep_one_step() {
+        let (mut world, mut stages) = setup();
+        world.resources.init::<TestTime>();
+
+        assert_eq!(run_frame(&mut world, &mut stages, 6), 0);
+        // The time from the previous frame is accumulated
+        assert_eq!(run_frame(&mut world, &mut stages, 6), 1);
+        let alpha = world.resources.get::<FixedTimestepAlpha>();
+        assert!((alpha.borrow().0 - 0.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn fixed_timestep_many_steps() {
+        let (mut world, mut stages) = setup();
+        world.resources.init::<TestTime>();
+
+        assert_eq!(run_frame(&mut world, &mut stages, 25), 2);
+        // We are limited to 3 steps, and the extra time is discarded
+        assert_eq!(run_frame(&mut world, &mut stages, 100), 3);
+        assert_eq!(run_frame(&mut world, &mut stages, 0), 0);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::prelude::*;
+
+/// A stage that runs a whole [`SystemStages`] schedule, possibly multiple times per frame.
+///
+/// This can be used to compose sub-schedules inside of an outer frame schedule, such as a
+/// simulation schedule that is run several times in a single frame to re-simulate rollback
+/// frames.
+///
+/// The number of iterations to run each frame is either a fixed [repeat count][Self::with_repeat],
+/// or decided by a [function of the world][Self::with_iterations]. The inner schedule's errors are
+/// handled with the [`ErrorPolicy`] of the outer [`SystemStages`].
+///
+/// Systems added directly to a [`NestedStages`], such as with
+/// [`SystemStages::add_system_to_stage()`], are added to the first of the inner stages.
+pub struct NestedStages {
+    id: Ulid,
+    name: String,
+    /// The inner schedule.
+    pub stages: SystemStages,
+    iterations: Box<dyn Fn(&World) -> usize + Send + Sync>,
+}
+
+impl NestedStages {
+    /// Create a stage for the given label that runs the inner `stages` once per frame.
+    pub fn new<L: StageLabel>(label: L, stages: SystemStages) -> Self {
+        Self {
+            id: label.id(),
+            name: label.name(),
+            stages,
+            iterations: Box::new(|_| 1),
+        }
+    }
+
+    /// Run the inner stages `count` times every frame.
+    pub fn with_repeat(mut self, count: usize) -> Self {
+        self.iterations = Box::new(move |_| count);
+        self
+    }
+
+    /// Run the inner stages the number of times returned by `iterations` every frame.
+    ///
+    /// The function is called once per frame, before the inner stages are run.
+    pub fn with_iterations<F: Fn(&World) -> usize + Send + Sync + 'static>(
+        mut self,
+        iterations: F,
+    ) -> Self {
+        self.iterations = Box::new(iterations);
+        self
+    }
+}
+
+impl SystemStage for NestedStages {
+    fn id(&self) -> Ulid {
+        self.id
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn run(&mut self, world: &mut World) -> SystemResult {
+        for _ in 0..(self.iterations)(&*world) {
+            self.stages.run(world)?;
+        }
+
+        Ok(())
+    }
+
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        for _ in 0..(self.iterations)(&*world) {
+            self.stages.run_stages(world, policy, errors)?;
+        }
+
+        Ok(())
+    }
+
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError> {
+        self.stages.initialize_systems(world)
+    }
+
+    /// Add a system to the first of the inner stages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no inner stages.
+    fn add_system(&mut self, system: System) {
+        self.add_system_descriptor(SystemDescriptor {
+            system,
+            ordering: default(),
+            sets: default(),
+        });
+    }
+
+    /// Add a system to the first of the inner stages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no inner stages.
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        self.stages
+            .stages
+            .first_mut()
+            .unwrap_or_else(|| panic!("Nested stage `{}` has no inner stages", self.name))
+            .add_system_descriptor(descriptor);
+    }
+
+    /// Remove all of the systems from all of the inner stages and return them.
+    fn take_systems(&mut self) -> Vec<SystemDescriptor> {
+        self.stages
+            .stages
+            .iter_mut()
+            .flat_map(|stage| stage.take_systems())
+            .collect()
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        self.stages.enable_profiling(enabled);
+    }
+
+    fn system_names(&self) -> Vec<&str> {
+        self.stages
+            .stages
+            .iter()
+            .flat_map(|stage| stage.system_names())
+            .collect()
+    }
+
+    /// Reports the inner stages as nested stages, including the inner startup stage if it has any
+    /// systems.
+    fn report(&self) -> StageReport {
+        let inner = self.stages.debug_report();
+        let startup = Some(inner.startup).filter(|startup| !startup.systems.is_empty());
+
+        StageReport {
+            name: self.name.clone(),
+            id: self.id,
+            details: Vec::new(),
+            systems: Vec::new(),
+            stages: startup.into_iter().chain(inner.stages).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(StageLabel)]
+    struct Simulation;
+
+    #[test]
+    fn nested_repeat() {
+        let mut world = World::new();
+        let mut inner = SystemStages::with_core_stages();
+        inner.add_system_to_stage(CoreStage::Update, |mut count: ResMut<u32>| *count += 1);
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .insert_stage_after(
+                CoreStage::Update,
+                Box::new(NestedStages::new(Simulation, inner).with_repeat(3)),
+            )
+            .unwrap()
+            // Added to the first inner stage
+            .add_system_to_stage(Simulation, |mut count: ResMut<u64>| *count += 1)
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 3);
+        assert_eq!(*world.resources.get::<u64>().borrow(), 3);
+    }
+
+    #[test]
+    fn nested_iterations_and_errors() {
+        #[derive(Clone, Default, TypeUlid)]
+        #[ulid = "01GPXC4B8M8W8Z6M2TX3Q8HBS5"]
+        struct Resimulate(usize);
+
+        let mut world = World::new();
+        let mut inner = SystemStages::with_core_stages();
+        inner.add_system_to_stage(CoreStage::Update, |count: Res<u32>| -> SystemResult {
+            anyhow::ensure!(*count < 2, "Too many");
+            Ok(())
+        });
+        inner.add_system_to_stage(CoreStage::Last, |mut count: ResMut<u32>| *count += 1);
+
+        let mut stages = SystemStages::new(vec![Box::new(
+            NestedStages::new(Simulation, inner)
+                .with_iterations(|world| world.resources.get::<Resimulate>().borrow().0),
+        )])
+        .with_error_policy(ErrorPolicy::SkipSystem);
+        stages.initialize_systems(&mut world).unwrap();
+
+        world.resources.insert(Resimulate(4));
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 4);
+        // The outer error policy is used for the inner stages.
+        assert_eq!(stages.errors().len(), 2);
+        assert_eq!(stages.errors()[0].stage, "Update");
+
+        world.resources.insert(Resimulate(0));
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 4);
+    }
+}
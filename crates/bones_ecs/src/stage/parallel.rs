@@ -0,0 +1,394 @@
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+
+/// A stage that runs systems that don't conflict with each-other at the same time.
+///
+/// When the stage is initialized, the systems are grouped into batches using their
+/// [`SystemAccess`]. Every system is put in a later batch than all of the systems before it that
+/// it conflicts with, or that it has [ordering constraints][SystemOrdering] with, so conflicting
+/// systems always run in the order that they were added to the stage. This keeps the result of
+/// running the stage deterministic.
+///
+/// With the `parallel` feature, each batch is then run on the `rayon` thread pool, one batch after
+/// another. The pool's threads are shared with the rest of the app and kept alive between runs.
+/// Without the feature, which is disabled by default, and on `wasm32`, where threads aren't
+/// available, the batches are run serially instead, in the same order.
+pub struct ParallelSystemStage {
+    /// The indexes of the systems in each batch, in the order that the batches will be run.
+    batches: Vec<Vec<usize>>,
+    /// The maximum number of threads to use when running a batch.
+    threads: usize,
+    stage: SimpleSystemStage,
+}
+
+impl ParallelSystemStage {
+    /// Create a new, empty stage, for the given label.
+    pub fn new<L: StageLabel>(label: L) -> Self {
+        Self {
+            batches: Vec::new(),
+            threads: 1,
+            stage: SimpleSystemStage::new(label),
+        }
+    }
+
+    /// Set the run criteria for the stage.
+    ///
+    /// See [`SimpleSystemStage::with_run_criteria()`].
+    pub fn with_run_criteria<Args, S: IntoSystem<Args, bool>>(mut self, run_criteria: S) -> Self {
+        self.stage = self.stage.with_run_criteria(run_criteria);
+        self
+    }
+
+    /// Group the systems into batches that may run in parallel.
+    fn build_batches(&mut self) {
+        let SimpleSystemStage {
+            systems, orderings, ..
+        } = &self.stage;
+        let no_ordering = SystemOrdering::default();
+        let ordering = |i: usize| orderings.get(i).unwrap_or(&no_ordering);
+
+        let mut system_batches = Vec::<usize>::with_capacity(systems.len());
+        self.batches.clear();
+
+        for (i, system) in systems.iter().enumerate() {
+            let mut batch = 0;
+            for (j, other) in systems[..i].iter().enumerate() {
+                if !system.access().is_compatible(other.access())
+                    || is_ordered(ordering(i), ordering(j))
+                {
+                    batch = batch.max(system_batches[j] + 1);
+                }
+            }
+
+            system_batches.push(batch);
+            if batch == self.batches.len() {
+                self.batches.push(Vec::new());
+            }
+            self.batches[batch].push(i);
+        }
+    }
+
+    /// Run all of the batches, handling errors according to `policy`.
+    fn run_batches(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        match self.stage.initialize_new_systems(world) {
+            Ok(true) => self.build_batches(),
+            Ok(false) => (),
+            Err(error) => return policy.handle(self.stage.initialize_error(error), errors),
+        }
+
+        let profile = self
+            .stage
+            .profiling
+            .then(|| world.resources.get::<SystemProfile>());
+
+        for batch in &self.batches {
+            let enabled = &self.stage.enabled;
+            let batch = batch
+                .iter()
+                .copied()
+                .filter(|&i| enabled.get(i).copied().unwrap_or(true))
+                .collect::<Vec<_>>();
+            let results = run_batch(
+                &mut self.stage.systems,
+                &batch,
+                self.threads,
+                profile.is_some(),
+                world,
+            );
+            // The systems in a batch don't conflict, so they can share a change tick.
+            world.increment_change_tick();
+
+            // Handle errors in the order of the systems, so that it doesn't depend on timing.
+            for (&i, (result, time)) in batch.iter().zip(results) {
+                if let (Some(profile), Some(time)) = (&profile, time) {
+                    let system = self.stage.systems[i].name();
+                    profile
+                        .borrow_mut()
+                        .record(&self.stage.name, i, system, time);
+                }
+
+                if let Err(error) = result {
+                    policy.handle(
+                        SystemError {
+                            stage: self.stage.name.clone(),
+                            system: self.stage.systems[i].name().into(),
+                            error,
+                        },
+                        errors,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if either of the systems with the given orderings must run before the other.
+fn is_ordered(a: &SystemOrdering, b: &SystemOrdering) -> bool {
+    a.labels
+        .iter()
+        .any(|label| b.before.contains(label) || b.after.contains(label))
+        || b.labels
+            .iter()
+            .any(|label| a.before.contains(label) || a.after.contains(label))
+}
+
+/// The result of running a system, and it's execution time if it was profiled.
+type BatchResult = (SystemResult, Option<Duration>);
+
+/// Run the systems in the batch, returning their results in the same order as the batch.
+fn run_batch(
+    systems: &mut [System],
+    batch: &[usize],
+    threads: usize,
+    profiling: bool,
+    world: &mut World,
+) -> Vec<BatchResult> {
+    let mut batch_systems = systems
+        .iter_mut()
+        .enumerate()
+        .filter(|(i, _)| batch.binary_search(i).is_ok())
+        .map(|(_, system)| system)
+        .collect::<Vec<_>>();
+
+    // Exclusive systems conflict with every other system, so they are always in a batch by
+    // themselves and are run serially.
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    if batch_systems.len() > 1 && threads > 1 {
+        use rayon::prelude::*;
+
+        // The rayon thread pool is kept alive between runs, so running a batch doesn't have to
+        // spawn any threads. The results are collected in the same order as the batch.
+        let world = &*world;
+        return batch_systems
+            .par_iter_mut()
+            .map(|system| timed(profiling, || system.run(world)))
+            .collect();
+    }
+    #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+    let _ = threads;
+
+    batch_systems
+        .iter_mut()
+        .map(|system| timed(profiling, || system.run_mut(world)))
+        .collect()
+}
+
+/// Run a system, measuring it's execution time if `profiling` is enabled.
+fn timed(profiling: bool, run: impl FnOnce() -> SystemResult) -> BatchResult {
+    if profiling {
+        let start = Instant::now();
+        let result = run();
+        (result, Some(start.elapsed()))
+    } else {
+        (run(), None)
+    }
+}
+
+impl SystemStage for ParallelSystemStage {
+    fn id(&self) -> Ulid {
+        self.stage.id()
+    }
+
+    fn name(&self) -> String {
+        self.stage.name()
+    }
+
+    fn run(&mut self, world: &mut World) -> SystemResult {
+        self.run_batches(world, ErrorPolicy::AbortFrame, &mut Vec::new())
+            .map_err(|error| error.into())
+    }
+
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        self.run_batches(world, policy, errors)
+    }
+
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError> {
+        self.stage.initialize(world)?;
+        self.build_batches();
+
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        {
+            self.threads = rayon::current_num_threads();
+        }
+
+        Ok(())
+    }
+
+    fn should_run(&mut self, world: &World) -> anyhow::Result<bool> {
+        self.stage.should_run(world)
+    }
+
+    fn add_system(&mut self, system: System) {
+        self.stage.add_system(system);
+    }
+
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        self.stage.add_system_descriptor(descriptor);
+    }
+
+    fn take_systems(&mut self) -> Vec<SystemDescriptor> {
+        self.batches.clear();
+        self.stage.take_systems()
+    }
+
+    fn system_names(&self) -> Vec<&str> {
+        self.stage.system_names()
+    }
+
+    fn report(&self) -> StageReport {
+        let mut report = self.stage.report();
+        report
+            .details
+            .push(format!("parallel, with up to {} threads", self.threads));
+        for (i, batch) in self.batches.iter().enumerate() {
+            let systems = batch
+                .iter()
+                .map(|&system| self.stage.systems[system].name())
+                .collect::<Vec<_>>();
+            report
+                .details
+                .push(format!("batch {}: {}", i, systems.join(", ")));
+        }
+
+        report
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        self.stage.set_profiling(enabled);
+    }
+
+    fn update_system_sets(&mut self, sets: &SystemSets) {
+        self.stage.update_system_sets(sets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Copy, Clone)]
+    struct Parallel;
+
+    impl StageLabel for Parallel {
+        fn name(&self) -> String {
+            "Parallel".into()
+        }
+
+        fn id(&self) -> Ulid {
+            Ulid(2022190552076913351706593994941477451)
+        }
+    }
+
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPT0K1ZZ1G4Y1W1CPBW8CD4J"]
+    struct A(u32);
+
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPT0KC0P7VKA6RNXR71QJ2AM"]
+    struct B(u32);
+
+    fn batches(stage: &ParallelSystemStage) -> Vec<Vec<usize>> {
+        stage.batches.clone()
+    }
+
+    #[test]
+    fn parallel_batches() {
+        let mut world = World::new();
+        let mut stage = ParallelSystemStage::new(Parallel);
+        stage.add_system((|mut a: ResMut<A>| a.0 += 1).system());
+        stage.add_system((|mut b: ResMut<B>| b.0 += 1).system());
+        stage.add_system((|a: Res<A>, mut b: ResMut<B>| b.0 += a.0).system());
+        stage.add_system((|_a: Res<A>| ()).system());
+        stage.add_system((|_world: &World| ()).system());
+        stage.add_system((|_b: Comp<u32>| ()).system());
+        stage.initialize(&mut world).unwrap();
+
+        assert_eq!(
+            batches(&stage),
+            vec![vec![0, 1], vec![2, 3], vec![4], vec![5]]
+        );
+
+        for _ in 0..2 {
+            stage.run(&mut world).unwrap();
+        }
+        assert_eq!(world.resources.get::<A>().borrow().0, 2);
+        assert_eq!(world.resources.get::<B>().borrow().0, 5);
+    }
+
+    #[test]
+    fn parallel_ordering_constraints() {
+        let mut world = World::new();
+        let mut stage = ParallelSystemStage::new(Parallel);
+        stage.add_system_descriptor((|_a: Res<A>| ()).after("b"));
+        stage.add_system_descriptor((|_b: Res<B>| ()).label("b"));
+        stage.add_system_descriptor((|_b: Res<B>| ()).descriptor());
+        stage.initialize(&mut world).unwrap();
+
+        assert_eq!(batches(&stage), vec![vec![0, 2], vec![1]]);
+    }
+
+    #[test]
+    fn parallel_late_added_system() {
+        let mut world = World::new();
+        let mut stage = ParallelSystemStage::new(Parallel);
+        stage.add_system((|mut a: ResMut<A>| a.0 += 1).system());
+        stage.initialize(&mut world).unwrap();
+        stage.run(&mut world).unwrap();
+
+        stage.add_system((|a: Res<A>, mut b: ResMut<B>| b.0 += a.0).system());
+        stage.run(&mut world).unwrap();
+
+        assert_eq!(batches(&stage), vec![vec![0], vec![1]]);
+        assert_eq!(world.resources.get::<B>().borrow().0, 2);
+    }
+
+    #[test]
+    fn parallel_errors_in_order() {
+        let mut world = World::new();
+        let mut stage = ParallelSystemStage::new(Parallel);
+        stage.add_system((|_a: Res<A>| -> SystemResult { anyhow::bail!("first") }).system());
+        stage.add_system((|_b: Res<B>| -> SystemResult { anyhow::bail!("second") }).system());
+        stage.initialize(&mut world).unwrap();
+
+        let mut errors = Vec::new();
+        stage
+            .run_with_policy(&mut world, ErrorPolicy::SkipSystem, &mut errors)
+            .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].error.to_string(), "first");
+        assert_eq!(errors[1].error.to_string(), "second");
+
+        // The error from `run()` includes the names of the stage and the system.
+        let error = stage.run(&mut world).unwrap_err();
+        let error = error.downcast_ref::<SystemError>().unwrap();
+        assert_eq!(error.error.to_string(), "first");
+        assert_eq!(error.system, stage.system_names()[0]);
+    }
+
+    #[test]
+    fn parallel_profiling() {
+        let mut world = World::new();
+        let mut stage = ParallelSystemStage::new(Parallel);
+        stage.add_system((|_a: Res<A>| ()).system());
+        stage.add_system((|_b: Res<B>| ()).system());
+        stage.set_profiling(true);
+        stage.initialize(&mut world).unwrap();
+        stage.run(&mut world).unwrap();
+
+        let report = world.resources.get::<SystemProfile>().borrow().report();
+        assert_eq!(report.len(), 2);
+    }
+}
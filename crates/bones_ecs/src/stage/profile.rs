@@ -0,0 +1,176 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use crate::prelude::*;
+
+/// Resource containing the execution times of the systems in stages with profiling enabled.
+///
+/// Profiling is enabled with [`SystemStages::enable_profiling()`], and a report of the slowest
+/// systems can be created with [`SystemStages::profile_report()`].
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPSZ3V4W6TBR1QWYN3D6ZK8E"]
+pub struct SystemProfile {
+    /// The number of most recent samples that are kept for each system.
+    pub window: usize,
+    /// The timings of each system, by stage name, and then by the index of the system in the
+    /// stage.
+    ///
+    /// The stages are kept in a sorted map, so that systems with the same average execution time
+    /// are always reported in the same order.
+    pub stages: BTreeMap<String, Vec<SystemTimings>>,
+}
+
+impl Default for SystemProfile {
+    fn default() -> Self {
+        Self {
+            window: Self::DEFAULT_WINDOW,
+            stages: default(),
+        }
+    }
+}
+
+impl SystemProfile {
+    /// The default value of [`window`][Self::window].
+    pub const DEFAULT_WINDOW: usize = 120;
+
+    /// Record the execution time of the system at `index` in the stage named `stage`.
+    pub fn record(&mut self, stage: &str, index: usize, system: &str, time: Duration) {
+        if !self.stages.contains_key(stage) {
+            self.stages.insert(stage.into(), Vec::new());
+        }
+        let timings = self.stages.get_mut(stage).unwrap();
+        if timings.len() <= index {
+            timings.resize_with(index + 1, || SystemTimings::new(system));
+        }
+
+        let timing = &mut timings[index];
+        // The systems in the stage may have been replaced.
+        if timing.system != system {
+            *timing = SystemTimings::new(system);
+        }
+        if timing.samples.len() >= self.window {
+            timing.samples.pop_front();
+        }
+        timing.samples.push_back(time);
+    }
+
+    /// Create a report of the system timings, sorted from the slowest to the fastest average
+    /// execution time.
+    pub fn report(&self) -> Vec<SystemProfileEntry> {
+        let mut entries = self
+            .stages
+            .iter()
+            .flat_map(|(stage, timings)| {
+                timings
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, timing)| !timing.samples.is_empty())
+                    .map(move |(index, timing)| SystemProfileEntry {
+                        stage: stage.clone(),
+                        index,
+                        system: timing.system.clone(),
+                        min: timing.min(),
+                        max: timing.max(),
+                        average: timing.average(),
+                        samples: timing.samples.len(),
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| {
+            b.average
+                .cmp(&a.average)
+                .then_with(|| a.stage.cmp(&b.stage))
+                .then_with(|| a.index.cmp(&b.index))
+        });
+
+        entries
+    }
+}
+
+/// The most recent execution times of a single system.
+#[derive(Clone, Debug)]
+pub struct SystemTimings {
+    /// The name of the system.
+    pub system: String,
+    /// The most recent execution times, from oldest to newest.
+    pub samples: VecDeque<Duration>,
+}
+
+impl SystemTimings {
+    /// Create an empty set of timings for the given system.
+    pub fn new(system: &str) -> Self {
+        Self {
+            system: system.into(),
+            samples: default(),
+        }
+    }
+
+    /// The fastest of the recorded execution times.
+    pub fn min(&self) -> Duration {
+        self.samples.iter().min().copied().unwrap_or_default()
+    }
+
+    /// The slowest of the recorded execution times.
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().copied().unwrap_or_default()
+    }
+
+    /// The average of the recorded execution times.
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+}
+
+/// An entry in a [`SystemProfile::report()`].
+#[derive(Clone, Debug)]
+pub struct SystemProfileEntry {
+    /// The name of the stage that the system is in.
+    pub stage: String,
+    /// The index of the system in the stage.
+    pub index: usize,
+    /// The name of the system.
+    pub system: String,
+    /// The fastest recorded execution time.
+    pub min: Duration,
+    /// The slowest recorded execution time.
+    pub max: Duration,
+    /// The average recorded execution time.
+    pub average: Duration,
+    /// The number of samples the timings were computed from.
+    pub samples: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn profile_window() {
+        let mut profile = SystemProfile {
+            window: 3,
+            ..default()
+        };
+        for ms in [10, 1, 2, 3] {
+            profile.record("Update", 1, "a", Duration::from_millis(ms));
+        }
+        profile.record("Update", 0, "b", Duration::from_millis(5));
+
+        let report = profile.report();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].system, "b");
+        assert_eq!(report[1].system, "a");
+        assert_eq!(report[1].index, 1);
+        assert_eq!(report[1].samples, 3);
+        assert_eq!(report[1].min, Duration::from_millis(1));
+        assert_eq!(report[1].max, Duration::from_millis(3));
+        assert_eq!(report[1].average, Duration::from_millis(2));
+    }
+}
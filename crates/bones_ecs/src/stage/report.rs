@@ -0,0 +1,203 @@
+use std::fmt;
+
+use crate::prelude::*;
+
+/// A report of all of the stages in a [`SystemStages`] collection, and the systems in them.
+///
+/// Created with [`SystemStages::debug_report()`]. The [`Display`][fmt::Display] implementation
+/// lists the stages and their systems in the order that they will be run.
+#[derive(Clone, Debug)]
+pub struct ScheduleReport {
+    /// The stage containing the startup systems.
+    pub startup: StageReport,
+    /// The reports of the stages, in the order that they will be run.
+    pub stages: Vec<StageReport>,
+}
+
+/// A report of a single stage, created with [`SystemStage::report()`].
+#[derive(Clone, Debug)]
+pub struct StageReport {
+    /// The name of the stage.
+    pub name: String,
+    /// The id of the stage.
+    pub id: Ulid,
+    /// Extra details about how the stage runs it's systems, such as it's run criteria.
+    pub details: Vec<String>,
+    /// The names of the systems in the stage, in the order that they will be run.
+    pub systems: Vec<String>,
+    /// The reports of the stages nested inside of this stage.
+    pub stages: Vec<StageReport>,
+}
+
+impl StageReport {
+    /// Create a report for the given stage, with the stage's [`system_names()`] and no details or
+    /// nested stages.
+    ///
+    /// [`system_names()`]: SystemStage::system_names
+    pub fn new<S: SystemStage + ?Sized>(stage: &S) -> Self {
+        Self {
+            name: stage.name(),
+            id: stage.id(),
+            details: Vec::new(),
+            systems: stage.system_names().into_iter().map(String::from).collect(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// Write the report, indented by `indent` spaces.
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{} ( {} )",
+            "",
+            self.name,
+            self.id,
+            indent = indent
+        )?;
+        for details in &self.details {
+            writeln!(f, "{:indent$}  > {}", "", details, indent = indent)?;
+        }
+        for system in &self.systems {
+            writeln!(f, "{:indent$}  - {}", "", system, indent = indent)?;
+        }
+        for stage in &self.stages {
+            stage.fmt_indented(f, indent + 4)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for StageReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl fmt::Display for ScheduleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.startup)?;
+        for stage in &self.stages {
+            write!(f, "{}", stage)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SystemStages {
+    /// Create a report listing all of the stages, including the startup stage, and the names of
+    /// the systems in each of them, in the order that they will be run.
+    ///
+    /// The ordering constraints of systems that were added after the stages were
+    /// [initialized][Self::initialize_systems] are only applied when their stage is next run, so
+    /// the report should be created after initialization to get the correct order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// fn physics_system() {}
+    ///
+    /// let mut world = World::new();
+    /// let mut stages = SystemStages::with_core_stages();
+    /// stages.add_system_to_stage(CoreStage::Update, physics_system);
+    /// stages.initialize_systems(&mut world).unwrap();
+    ///
+    /// let report = stages.debug_report();
+    /// assert_eq!(report.stages[2].name, "Update");
+    /// assert!(report.stages[2].systems[0].ends_with("physics_system"));
+    ///
+    /// // Print the whole schedule
+    /// println!("{}", report);
+    /// ```
+    pub fn debug_report(&self) -> ScheduleReport {
+        ScheduleReport {
+            startup: self.startup_stage.report(),
+            stages: self.stages.iter().map(|stage| stage.report()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[derive(StageLabel)]
+    enum ReportStage {
+        Nested,
+        Fixed,
+    }
+
+    fn physics() {}
+    fn network() {}
+
+    #[test]
+    fn report_stages_and_systems() {
+        let mut world = World::new();
+        let mut inner =
+            SystemStages::new(vec![Box::new(SimpleSystemStage::new(CoreStage::Update))]);
+        inner.add_system_to_stage(CoreStage::Update, network);
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .insert_stage_after(
+                CoreStage::Update,
+                Box::new(NestedStages::new(ReportStage::Nested, inner)),
+            )
+            .unwrap()
+            .insert_stage_after(
+                ReportStage::Nested,
+                Box::new(FixedTimestepStage::new::<_, Time>(
+                    ReportStage::Fixed,
+                    Duration::from_millis(10),
+                )),
+            )
+            .unwrap()
+            .add_system_to_stage(ReportStage::Fixed, physics)
+            .add_system_to_stage(CoreStage::Update, network.after("physics"))
+            .add_system_to_stage(CoreStage::Update, physics.label("physics"))
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        let report = stages.debug_report();
+        assert!(report.startup.systems.is_empty());
+        let names = report
+            .stages
+            .iter()
+            .map(|stage| stage.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "First",
+                "PreUpdate",
+                "Update",
+                "Nested",
+                "Fixed",
+                "PostUpdate",
+                "Last"
+            ]
+        );
+
+        // Systems are listed in the order they will be run.
+        let update = &report.stages[2];
+        assert_eq!(update.id, CoreStage::Update.id());
+        assert!(update.systems[0].ends_with("physics"));
+        assert!(update.systems[1].ends_with("network"));
+
+        let nested = &report.stages[3];
+        assert_eq!(nested.stages.len(), 1);
+        assert!(nested.stages[0].systems[0].ends_with("network"));
+
+        let fixed = &report.stages[4];
+        assert_eq!(fixed.systems.len(), 1);
+        assert!(!fixed.details.is_empty());
+
+        let display = report.to_string();
+        assert!(display.contains("Nested"));
+        assert!(display.contains("    Update"));
+    }
+}
@@ -0,0 +1,320 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::prelude::*;
+
+/// The ordering constraints of a stage added with [`SystemStages::add_stage()`].
+#[derive(Default)]
+pub(super) struct StageOrdering {
+    /// The stages that the stage must run before.
+    before: Vec<StageRef>,
+    /// The stages that the stage must run after.
+    after: Vec<StageRef>,
+}
+
+/// A reference to a stage in an ordering constraint, keeping the name of it's label for error
+/// messages.
+struct StageRef {
+    name: String,
+    id: Ulid,
+}
+
+impl StageRef {
+    fn new<L: StageLabel>(label: L) -> Self {
+        Self {
+            name: label.name(),
+            id: label.id(),
+        }
+    }
+}
+
+impl StageLabel for StageRef {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+/// Builder returned by [`SystemStages::add_stage()`], used to add ordering constraints to the new
+/// stage.
+pub struct StageOrderingBuilder<'a> {
+    stages: &'a mut SystemStages,
+    id: Ulid,
+}
+
+impl<'a> StageOrderingBuilder<'a> {
+    /// Make the stage run after the stage with the given label.
+    pub fn after<L: StageLabel>(self, label: L) -> Self {
+        self.ordering().after.push(StageRef::new(label));
+        self
+    }
+
+    /// Make the stage run before the stage with the given label.
+    pub fn before<L: StageLabel>(self, label: L) -> Self {
+        self.ordering().before.push(StageRef::new(label));
+        self
+    }
+
+    fn ordering(&mut self) -> &mut StageOrdering {
+        self.stages.stage_orderings.entry(self.id).or_default()
+    }
+}
+
+impl SystemStages {
+    /// Add a stage that is ordered relative to the other stages with ordering constraints, instead
+    /// of at a fixed position.
+    ///
+    /// The stages are sorted to satisfy the constraints when the systems are
+    /// [initialized][Self::initialize_systems], or when [`sort_stages()`][Self::sort_stages] is
+    /// called. The stages that weren't added with `add_stage()` keep their explicit order, so
+    /// independent plugins can each add their stages without knowing about each-other.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is already a stage with the same id as the `stage` being added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// #[derive(StageLabel)]
+    /// struct PhysicsStage;
+    ///
+    /// let mut world = World::new();
+    /// let mut stages = SystemStages::with_core_stages();
+    /// stages
+    ///     .add_stage(Box::new(SimpleSystemStage::new(PhysicsStage)))
+    ///     .unwrap()
+    ///     .after(CoreStage::Update)
+    ///     .before(CoreStage::PostUpdate);
+    /// stages.initialize_systems(&mut world).unwrap();
+    ///
+    /// assert_eq!(stages.stages[3].id(), PhysicsStage.id());
+    /// ```
+    pub fn add_stage(
+        &mut self,
+        stage: Box<dyn SystemStage>,
+    ) -> Result<StageOrderingBuilder<'_>, EcsError> {
+        let id = stage.id();
+        self.insert_stage_at(self.stages.len(), stage)?;
+        self.stage_orderings.insert(id, StageOrdering::default());
+
+        Ok(StageOrderingBuilder { stages: self, id })
+    }
+
+    /// Sort the stages to satisfy the ordering constraints of the stages added with
+    /// [`add_stage()`][Self::add_stage].
+    ///
+    /// This is called automatically by [`initialize_systems()`][Self::initialize_systems]. The
+    /// other stages keep their relative order, and stages without constraints between them keep
+    /// their insertion order.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a constraint refers to a stage that doesn't exist, or if the constraints form a
+    /// cycle.
+    pub fn sort_stages(&mut self) -> Result<(), EcsError> {
+        if self.stage_orderings.is_empty() {
+            return Ok(());
+        }
+
+        let stage_count = self.stages.len();
+        let mut dependents = vec![Vec::new(); stage_count];
+        let mut dependencies = vec![Vec::new(); stage_count];
+        let mut add_edge = |from: usize, to: usize| {
+            if from != to && !dependents[from].contains(&to) {
+                dependents[from].push(to);
+                dependencies[to].push(from);
+            }
+        };
+
+        // The stages without constraints must stay in the order that they were explicitly put in.
+        let mut previous = None;
+        for (i, stage) in self.stages.iter().enumerate() {
+            if self.stage_orderings.contains_key(&stage.id()) {
+                continue;
+            }
+            if let Some(previous) = previous {
+                add_edge(previous, i);
+            }
+            previous = Some(i);
+        }
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let Some(ordering) = self.stage_orderings.get(&stage.id()) else {
+                continue;
+            };
+            for label in &ordering.before {
+                add_edge(i, self.stage_idx(label)?);
+            }
+            for label in &ordering.after {
+                add_edge(self.stage_idx(label)?, i);
+            }
+        }
+
+        // Topologically sort the stages, always picking the earliest stage that is ready, so that
+        // unconstrained stages stay in insertion order.
+        let mut dependency_counts = dependencies.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = dependency_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count == 0)
+            .map(|(i, _)| Reverse(i))
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(stage_count);
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(i);
+            for &j in &dependents[i] {
+                dependency_counts[j] -= 1;
+                if dependency_counts[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+
+        if order.len() != stage_count {
+            let cycle = find_cycle(&dependencies, &dependency_counts);
+            return Err(EcsError::StageOrderCycle(
+                cycle.iter().map(|&i| self.stages[i].name()).collect(),
+            ));
+        }
+
+        let mut stages = self.stages.drain(..).map(Some).collect::<Vec<_>>();
+        for i in order {
+            self.stages.push(stages[i].take().unwrap());
+        }
+
+        Ok(())
+    }
+}
+
+/// Find a cycle among the stages that couldn't be sorted, returning the indexes of the stages in
+/// the order that they must run in, with the first stage repeated at the end.
+///
+/// Every stage that couldn't be sorted depends on at least one other stage that couldn't be
+/// sorted, so following the dependencies backwards must eventually reach a stage twice.
+fn find_cycle(dependencies: &[Vec<usize>], dependency_counts: &[usize]) -> Vec<usize> {
+    let unsorted = |i: &usize| dependency_counts[*i] > 0;
+
+    let mut path = Vec::new();
+    let mut current = (0..dependencies.len()).find(unsorted).unwrap();
+    while !path.contains(&current) {
+        path.push(current);
+        current = dependencies[current]
+            .iter()
+            .copied()
+            .find(unsorted)
+            .unwrap();
+    }
+
+    let start = path.iter().position(|&i| i == current).unwrap();
+    let mut cycle = path.split_off(start);
+    cycle.reverse();
+    cycle.push(cycle[0]);
+
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(StageLabel)]
+    enum PluginStage {
+        Physics,
+        Network,
+        Audio,
+        Input,
+    }
+
+    fn stage_names(stages: &SystemStages) -> Vec<String> {
+        stages.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    #[test]
+    fn add_stage_with_constraints() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_stage(Box::new(SimpleSystemStage::new(PluginStage::Network)))
+            .unwrap()
+            .after(PluginStage::Physics)
+            .before(CoreStage::Last);
+        stages
+            .add_stage(Box::new(SimpleSystemStage::new(PluginStage::Physics)))
+            .unwrap()
+            .after(CoreStage::Update)
+            .before(CoreStage::PostUpdate);
+        // A stage without constraints stays at the end.
+        stages
+            .add_stage(Box::new(SimpleSystemStage::new(PluginStage::Audio)))
+            .unwrap();
+        // Explicitly positioned stages still work.
+        stages
+            .insert_stage_before(
+                CoreStage::First,
+                Box::new(SimpleSystemStage::new(PluginStage::Input)),
+            )
+            .unwrap();
+        stages.initialize_systems(&mut world).unwrap();
+
+        let expected = [
+            "Input",
+            "First",
+            "PreUpdate",
+            "Update",
+            "Physics",
+            "PostUpdate",
+            "Network",
+            "Last",
+            "Audio",
+        ];
+        assert_eq!(stage_names(&stages), expected);
+
+        // Sorting again doesn't change the order.
+        stages.sort_stages().unwrap();
+        assert_eq!(stage_names(&stages), expected);
+    }
+
+    #[test]
+    fn add_stage_duplicate() {
+        let mut stages = SystemStages::with_core_stages();
+        assert!(matches!(
+            stages.add_stage(Box::new(SimpleSystemStage::new(CoreStage::Update))),
+            Err(EcsError::StageAlreadyExists(..))
+        ));
+    }
+
+    #[test]
+    fn add_stage_missing_constraint() {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_stage(Box::new(SimpleSystemStage::new(PluginStage::Physics)))
+            .unwrap()
+            .after(PluginStage::Network);
+
+        assert!(matches!(
+            stages.sort_stages(),
+            Err(EcsError::StageNotFound(error)) if error.name == "Network"
+        ));
+    }
+
+    #[test]
+    fn add_stage_cycle() {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_stage(Box::new(SimpleSystemStage::new(PluginStage::Physics)))
+            .unwrap()
+            .after(CoreStage::PostUpdate)
+            .before(CoreStage::Update);
+
+        let error = stages.sort_stages().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Stages have cyclic ordering constraints: \
+            PostUpdate -> Physics -> Update -> PostUpdate"
+        );
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// Resource containing the current state of a [`StateStage`] state machine, along with the state
+/// transitions that haven't happened yet.
+///
+/// Transitions are queued with [`set()`][Self::set], and the [`StateStage`] for the state type
+/// performs at most one of them each frame.
+#[derive(Clone, Debug)]
+pub struct States<T> {
+    current: Option<T>,
+    queue: VecDeque<T>,
+}
+
+impl<T> Default for States<T> {
+    fn default() -> Self {
+        Self {
+            current: None,
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+/// The [`TypeUlid`] of [`States<T>`] is derived from the [`TypeUlid`] of `T`, so that every state
+/// type gets it's own resource.
+impl<T: TypeUlid> TypeUlid for States<T> {
+    const ULID: Ulid = Ulid(2022223937208839556458547004296513860 ^ T::ULID.0);
+}
+
+impl<T: Copy + Eq> States<T> {
+    /// Create a state machine that will enter the `initial` state the first time it's
+    /// [`StateStage`] is run.
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: None,
+            queue: VecDeque::from([initial]),
+        }
+    }
+
+    /// Get the current state, or `None` if no state has been entered yet.
+    pub fn current(&self) -> Option<T> {
+        self.current
+    }
+
+    /// Queue a transition to the given state.
+    ///
+    /// The transition happens the next time the [`StateStage`] runs, or on a later frame if other
+    /// transitions are already queued. Transitioning to the state that is already current does
+    /// nothing.
+    pub fn set(&mut self, state: T) {
+        self.queue.push_back(state);
+    }
+
+    /// Iterate over the state transitions that are queued, in the order that they will happen.
+    pub fn queued(&self) -> impl Iterator<Item = &T> {
+        self.queue.iter()
+    }
+}
+
+/// The systems that are run when entering, updating, or exiting a single state.
+struct StateSystems<T> {
+    state: T,
+    enter: SimpleSystemStage,
+    update: SimpleSystemStage,
+    exit: SimpleSystemStage,
+}
+
+impl<T> StateSystems<T> {
+    fn stages(&self) -> [&SimpleSystemStage; 3] {
+        [&self.enter, &self.update, &self.exit]
+    }
+
+    fn stages_mut(&mut self) -> [&mut SimpleSystemStage; 3] {
+        [&mut self.enter, &mut self.update, &mut self.exit]
+    }
+}
+
+/// Label for the inner stages of a [`StateStage`].
+struct StateHookLabel {
+    name: String,
+    id: Ulid,
+}
+
+impl StageLabel for StateHookLabel {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+/// A stage that runs systems depending on the current state in the [`States<T>`] resource.
+///
+/// Every time the stage is run, it first performs the next queued state transition, if there is
+/// one, by running the [exit systems][Self::on_exit] of the old state and then the
+/// [enter systems][Self::on_enter] of the new state. After that it runs the
+/// [update systems][Self::on_update] of the current state, followed by any systems that were
+/// added to the stage with [`add_system()`][SystemStage::add_system], which run in every state.
+///
+/// At most one transition happens per frame. If a state is [set][States::set] during an enter or
+/// exit system, the transition is queued, and happens on the next frame instead. The [`Commands`]
+/// queued by the exit and enter systems are applied before the next group of systems is run.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// #[derive(Clone, Copy, PartialEq, Eq, TypeUlid)]
+/// #[ulid = "01GPWB7YN0D2H2N9HHSF5WJX0T"]
+/// enum GameState {
+///     Menu,
+///     InGame,
+/// }
+///
+/// # #[derive(Copy, Clone)]
+/// # struct GameStateStage;
+/// # impl StageLabel for GameStateStage {
+/// #     fn name(&self) -> String { "GameState".into() }
+/// #     fn id(&self) -> Ulid { Ulid(2022224042779329432862312449612458153) }
+/// # }
+/// fn spawn_level() {}
+/// fn move_players() {}
+/// fn despawn_level() {}
+///
+/// let mut world = World::new();
+/// world.resources.insert(States::new(GameState::Menu));
+///
+/// let mut stages = SystemStages::with_core_stages();
+/// stages
+///     .insert_stage_before(
+///         CoreStage::Update,
+///         Box::new(
+///             StateStage::new(GameStateStage)
+///                 .on_enter(GameState::InGame, spawn_level)
+///                 .on_update(GameState::InGame, move_players)
+///                 .on_exit(GameState::InGame, despawn_level),
+///         ),
+///     )
+///     .unwrap();
+/// ```
+pub struct StateStage<T> {
+    id: Ulid,
+    name: String,
+    states: Vec<StateSystems<T>>,
+    stage: SimpleSystemStage,
+}
+
+impl<T: TypedEcsData + Copy + Eq> StateStage<T> {
+    /// Create a new, empty stage for the given label.
+    pub fn new<L: StageLabel>(label: L) -> Self {
+        Self {
+            id: label.id(),
+            name: label.name(),
+            states: Vec::new(),
+            stage: SimpleSystemStage::new(label),
+        }
+    }
+
+    /// Add a system that runs once whenever the given state is entered.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn on_enter<Args, S: IntoSystemDescriptor<Args>>(mut self, state: T, system: S) -> Self {
+        self.state_systems(state)
+            .enter
+            .add_system_descriptor(system.descriptor());
+        self
+    }
+
+    /// Add a system that runs every frame while the given state is current, including the frame
+    /// that the state is entered on.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn on_update<Args, S: IntoSystemDescriptor<Args>>(mut self, state: T, system: S) -> Self {
+        self.state_systems(state)
+            .update
+            .add_system_descriptor(system.descriptor());
+        self
+    }
+
+    /// Add a system that runs once whenever the given state is exited.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn on_exit<Args, S: IntoSystemDescriptor<Args>>(mut self, state: T, system: S) -> Self {
+        self.state_systems(state)
+            .exit
+            .add_system_descriptor(system.descriptor());
+        self
+    }
+
+    /// Get the systems for the given state, creating them if they don't exist yet.
+    fn state_systems(&mut self, state: T) -> &mut StateSystems<T> {
+        let idx = match self.find_state(state) {
+            Some(idx) => idx,
+            None => {
+                let idx = self.states.len();
+                let (name, id) = (&self.name, self.id);
+                let stage = |hook: &str| {
+                    SimpleSystemStage::new(StateHookLabel {
+                        name: format!("{} (state {} {})", name, idx, hook),
+                        id,
+                    })
+                };
+                self.states.push(StateSystems {
+                    state,
+                    enter: stage("enter"),
+                    update: stage("update"),
+                    exit: stage("exit"),
+                });
+                idx
+            }
+        };
+
+        &mut self.states[idx]
+    }
+
+    /// Get the index of the systems for the given state in `states`, if any have been added.
+    fn find_state(&self, state: T) -> Option<usize> {
+        self.states.iter().position(|s| s.state == state)
+    }
+
+    /// Perform the next queued state transition, if there is one, running the exit and enter
+    /// systems.
+    fn transition(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        let (current, next) = {
+            let states = world.resources.get::<States<T>>();
+            let mut states = states.borrow_mut();
+            (states.current, states.queue.pop_front())
+        };
+        let Some(next) = next else {
+            return Ok(());
+        };
+        if current == Some(next) {
+            return Ok(());
+        }
+
+        if let Some(systems) = current.and_then(|current| self.find_state(current)) {
+            let systems = &mut self.states[systems];
+            systems.exit.run_with_policy(world, policy, errors)?;
+            world.apply_commands();
+        }
+
+        world.resources.get::<States<T>>().borrow_mut().current = Some(next);
+
+        if let Some(systems) = self.find_state(next) {
+            let systems = &mut self.states[systems];
+            systems.enter.run_with_policy(world, policy, errors)?;
+            world.apply_commands();
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: TypedEcsData + Copy + Eq> SystemStage for StateStage<T> {
+    fn id(&self) -> Ulid {
+        self.id
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn run(&mut self, world: &mut World) -> SystemResult {
+        self.run_with_policy(world, ErrorPolicy::AbortFrame, &mut Vec::new())
+            .map_err(|error| error.into())
+    }
+
+    fn run_with_policy(
+        &mut self,
+        world: &mut World,
+        policy: ErrorPolicy,
+        errors: &mut Vec<SystemError>,
+    ) -> Result<(), SystemError> {
+        self.transition(world, policy, errors)?;
+
+        let current = world.resources.get::<States<T>>().borrow().current;
+        if let Some(systems) = current.and_then(|current| self.find_state(current)) {
+            let systems = &mut self.states[systems];
+            systems.update.run_with_policy(world, policy, errors)?;
+        }
+
+        self.stage.run_with_policy(world, policy, errors)
+    }
+
+    fn initialize(&mut self, world: &mut World) -> Result<(), EcsError> {
+        world.resources.init::<States<T>>();
+        for systems in &mut self.states {
+            for stage in systems.stages_mut() {
+                stage.initialize(world)?;
+            }
+        }
+        self.stage.initialize(world)
+    }
+
+    fn should_run(&mut self, world: &World) -> anyhow::Result<bool> {
+        self.stage.should_run(world)
+    }
+
+    fn add_system(&mut self, system: System) {
+        self.stage.add_system(system);
+    }
+
+    fn add_system_descriptor(&mut self, descriptor: SystemDescriptor) {
+        self.stage.add_system_descriptor(descriptor);
+    }
+
+    fn take_systems(&mut self) -> Vec<SystemDescriptor> {
+        self.stage.take_systems()
+    }
+
+    fn system_names(&self) -> Vec<&str> {
+        self.stage.system_names()
+    }
+
+    /// Reports the systems that run every frame, with the non-empty state hooks as nested stages.
+    fn report(&self) -> StageReport {
+        let mut report = self.stage.report();
+        report.stages.extend(
+            self.states
+                .iter()
+                .flat_map(|systems| systems.stages())
+                .filter(|stage| !stage.systems.is_empty())
+                .map(|stage| stage.report()),
+        );
+
+        report
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        for systems in &mut self.states {
+            for stage in systems.stages_mut() {
+                stage.set_profiling(enabled);
+            }
+        }
+        self.stage.set_profiling(enabled);
+    }
+
+    fn update_system_sets(&mut self, sets: &SystemSets) {
+        for systems in &mut self.states {
+            for stage in systems.stages_mut() {
+                stage.update_system_sets(sets);
+            }
+        }
+        self.stage.update_system_sets(sets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+    #[ulid = "01GPWBGR2XQ4S1YVAF8J7RQ4CQ"]
+    enum GameState {
+        Menu,
+        InGame,
+        Paused,
+    }
+
+    #[derive(Copy, Clone)]
+    struct GameStateStage;
+
+    impl StageLabel for GameStateStage {
+        fn name(&self) -> String {
+            "GameState".into()
+        }
+
+        fn id(&self) -> Ulid {
+            Ulid(2022224112440271505339478413155063066)
+        }
+    }
+
+    /// Resource used to record the order that the state systems run in.
+    #[derive(Clone, Default, TypeUlid)]
+    #[ulid = "01GPWBJ0E8XW0YH3W2M2S4QWZ5"]
+    struct Log(Vec<&'static str>);
+
+    fn record(message: &'static str) -> System {
+        (move |mut log: ResMut<Log>| log.0.push(message)).system()
+    }
+
+    fn setup(initial: GameState) -> (World, SystemStages) {
+        let mut world = World::new();
+        world.resources.insert(States::new(initial));
+
+        let mut stages = SystemStages::new(vec![Box::new(
+            StateStage::new(GameStateStage)
+                .on_enter(GameState::Menu, record("enter menu"))
+                .on_update(GameState::Menu, record("update menu"))
+                .on_exit(GameState::Menu, record("exit menu"))
+                .on_enter(GameState::InGame, record("enter game"))
+                .on_update(GameState::InGame, record("update game"))
+                .on_exit(GameState::InGame, record("exit game"))
+                .on_enter(
+                    GameState::InGame,
+                    |mut states: ResMut<States<GameState>>| states.set(GameState::Paused),
+                ),
+        )]);
+        stages.add_system_to_stage(GameStateStage, record("always"));
+        stages.initialize_systems(&mut world).unwrap();
+
+        (world, stages)
+    }
+
+    fn take_log(world: &World) -> Vec<&'static str> {
+        std::mem::take(&mut world.resources.get::<Log>().borrow_mut().0)
+    }
+
+    fn current(world: &World) -> Option<GameState> {
+        world
+            .resources
+            .get::<States<GameState>>()
+            .borrow()
+            .current()
+    }
+
+    #[test]
+    fn enter_update_exit() {
+        let (mut world, mut stages) = setup(GameState::Menu);
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(
+            take_log(&world),
+            vec!["enter menu", "update menu", "always"]
+        );
+        assert_eq!(current(&world), Some(GameState::Menu));
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(take_log(&world), vec!["update menu", "always"]);
+
+        // Setting the current state again doesn't transition
+        world
+            .resources
+            .get::<States<GameState>>()
+            .borrow_mut()
+            .set(GameState::Menu);
+        stages.run(&mut world).unwrap();
+        assert_eq!(take_log(&world), vec!["update menu", "always"]);
+    }
+
+    #[test]
+    fn state_set_during_enter() {
+        let (mut world, mut stages) = setup(GameState::Menu);
+        stages.run(&mut world).unwrap();
+        take_log(&world);
+
+        world
+            .resources
+            .get::<States<GameState>>()
+            .borrow_mut()
+            .set(GameState::InGame);
+        stages.run(&mut world).unwrap();
+        assert_eq!(
+            take_log(&world),
+            vec!["exit menu", "enter game", "update game", "always"]
+        );
+
+        // The transition queued by the enter system happens on the next frame. Paused doesn't
+        // have any systems, but the InGame exit systems still run.
+        assert_eq!(current(&world), Some(GameState::InGame));
+        stages.run(&mut world).unwrap();
+        assert_eq!(take_log(&world), vec!["exit game", "always"]);
+        assert_eq!(current(&world), Some(GameState::Paused));
+    }
+
+    #[test]
+    fn one_transition_per_frame() {
+        let (mut world, mut stages) = setup(GameState::Menu);
+        {
+            let states = world.resources.get::<States<GameState>>();
+            let mut states = states.borrow_mut();
+            states.set(GameState::InGame);
+            assert_eq!(
+                states.queued().copied().collect::<Vec<_>>(),
+                vec![GameState::Menu, GameState::InGame]
+            );
+        }
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(
+            take_log(&world),
+            vec!["enter menu", "update menu", "always"]
+        );
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(current(&world), Some(GameState::InGame));
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+
+/// Information about a stage that was run with [`SystemStages::run_next_stage()`].
+#[derive(Debug)]
+pub struct StageRunInfo {
+    /// The name of the stage.
+    pub name: String,
+    /// The id of the stage.
+    pub id: Ulid,
+    /// How long it took to run the stage, including checking it's run criteria and applying it's
+    /// commands.
+    pub elapsed: Duration,
+    /// The errors returned by the stage's systems.
+    ///
+    /// With [`ErrorPolicy::AbortFrame`], this contains at most one error, and the rest of the
+    /// frame is skipped if it isn't empty.
+    pub errors: Vec<SystemError>,
+}
+
+impl SystemStages {
+    /// Run the next stage of the current frame, for stepping through a frame one stage at a time,
+    /// such as from a debugger.
+    ///
+    /// The first call starts a new frame, running the [startup systems][Self::add_startup_system]
+    /// as their own step if they haven't been run yet. Each call after that runs the next stage,
+    /// until all of the stages have been run, at which point `None` is returned and the next call
+    /// will start a new frame. This means that a `while let` loop runs exactly one frame:
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// let mut world = World::new();
+    /// let mut stages = SystemStages::with_core_stages();
+    /// stages.initialize_systems(&mut world).unwrap();
+    ///
+    /// while let Some(info) = stages.run_next_stage(&mut world) {
+    ///     println!("Ran stage `{}` in {:?}", info.name, info.elapsed);
+    /// }
+    /// ```
+    ///
+    /// Errors are handled with the [`error_policy`][Self::error_policy], and the errors returned
+    /// by the stage's systems are returned in the [`StageRunInfo`] instead of being recorded in
+    /// [`errors()`][Self::errors]. An error that aborts the frame skips the rest of the stages.
+    ///
+    /// A partially stepped frame may be finished with [`run()`][Self::run], or abandoned with
+    /// [`reset_step_cursor()`][Self::reset_step_cursor].
+    pub fn run_next_stage(&mut self, world: &mut World) -> Option<StageRunInfo> {
+        let policy = self.error_policy;
+
+        let idx = match self.step_cursor {
+            Some(idx) => idx,
+            None => {
+                world.increment_change_tick();
+                world.check_change_ticks();
+                self.apply_system_sets(world);
+
+                if !self.has_started {
+                    self.has_started = true;
+                    let (info, aborted) = Self::step_stage(&mut self.startup_stage, world, policy);
+                    self.step_cursor = Some(if aborted { self.stages.len() } else { 0 });
+                    return Some(info);
+                }
+
+                0
+            }
+        };
+
+        if idx >= self.stages.len() {
+            self.step_cursor = None;
+            return None;
+        }
+
+        let (info, aborted) = Self::step_stage(&mut *self.stages[idx], world, policy);
+        self.step_cursor = Some(if aborted { self.stages.len() } else { idx + 1 });
+
+        Some(info)
+    }
+
+    /// Abandon the frame that is being stepped through with
+    /// [`run_next_stage()`][Self::run_next_stage], so that the next call to `run_next_stage()` or
+    /// [`run()`][Self::run] starts a new frame.
+    ///
+    /// The stages that were already run are not undone.
+    pub fn reset_step_cursor(&mut self) {
+        self.step_cursor = None;
+    }
+
+    /// Get the index in [`stages`][Self::stages] of the next stage that will be run by
+    /// [`run_next_stage()`][Self::run_next_stage], or `None` if no frame is being stepped
+    /// through.
+    pub fn step_cursor(&self) -> Option<usize> {
+        self.step_cursor
+    }
+
+    /// Run a single stage, timing it and collecting it's errors.
+    ///
+    /// Also returns whether the error policy aborted the frame.
+    fn step_stage(
+        stage: &mut dyn SystemStage,
+        world: &mut World,
+        policy: ErrorPolicy,
+    ) -> (StageRunInfo, bool) {
+        let mut errors = Vec::new();
+        let start = Instant::now();
+        let result = Self::run_stage(stage, world, policy, &mut errors);
+        let elapsed = start.elapsed();
+
+        let aborted = match result {
+            Ok(()) => false,
+            Err(error) => {
+                errors.push(error);
+                true
+            }
+        };
+
+        let info = StageRunInfo {
+            name: stage.name(),
+            id: stage.id(),
+            elapsed,
+            errors,
+        };
+
+        (info, aborted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn counting_stages() -> SystemStages {
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_startup_system(|mut count: ResMut<u64>| *count += 1)
+            .unwrap()
+            .add_system_to_stage(CoreStage::Update, |mut count: ResMut<u32>| *count += 1)
+            .add_system_to_stage(CoreStage::Last, |mut count: ResMut<u32>| *count += 10);
+
+        stages
+    }
+
+    #[test]
+    fn step_through_frame() {
+        let mut world = World::new();
+        let mut stages = counting_stages();
+        stages.initialize_systems(&mut world).unwrap();
+
+        let startup = stages.run_next_stage(&mut world).unwrap();
+        assert_eq!(startup.name, "Startup");
+        assert_eq!(*world.resources.get::<u64>().borrow(), 1);
+
+        let mut names = Vec::new();
+        while let Some(info) = stages.run_next_stage(&mut world) {
+            assert!(info.errors.is_empty());
+            names.push(info.name);
+            if names.len() == 3 {
+                assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+            }
+        }
+        assert_eq!(
+            names,
+            ["First", "PreUpdate", "Update", "PostUpdate", "Last"]
+        );
+        assert_eq!(*world.resources.get::<u32>().borrow(), 11);
+        assert_eq!(stages.step_cursor(), None);
+
+        // The next frame doesn't run the startup systems again.
+        let info = stages.run_next_stage(&mut world).unwrap();
+        assert_eq!(info.id, CoreStage::First.id());
+        assert_eq!(*world.resources.get::<u64>().borrow(), 1);
+    }
+
+    #[test]
+    fn run_finishes_stepped_frame() {
+        let mut world = World::new();
+        let mut stages = counting_stages();
+        stages.initialize_systems(&mut world).unwrap();
+
+        // Step through the startup systems, `First`, `PreUpdate` and `Update`.
+        for _ in 0..4 {
+            stages.run_next_stage(&mut world).unwrap();
+        }
+        assert_eq!(stages.step_cursor(), Some(3));
+        assert_eq!(*world.resources.get::<u32>().borrow(), 1);
+
+        // Only the rest of the frame is run.
+        stages.run(&mut world).unwrap();
+        assert_eq!(stages.step_cursor(), None);
+        assert_eq!(*world.resources.get::<u32>().borrow(), 11);
+
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 22);
+
+        // Abandoning a frame starts a new one.
+        stages.run_next_stage(&mut world).unwrap();
+        stages.reset_step_cursor();
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 33);
+    }
+
+    #[test]
+    fn step_error_aborts_frame() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::PreUpdate, || -> SystemResult {
+                anyhow::bail!("Failed")
+            })
+            .add_system_to_stage(CoreStage::Update, |mut count: ResMut<u32>| *count += 1);
+        stages.initialize_systems(&mut world).unwrap();
+
+        // Startup and `First`
+        stages.run_next_stage(&mut world).unwrap();
+        stages.run_next_stage(&mut world).unwrap();
+
+        let info = stages.run_next_stage(&mut world).unwrap();
+        assert_eq!(info.name, "PreUpdate");
+        assert_eq!(info.errors.len(), 1);
+        assert!(stages.errors().is_empty());
+
+        // The rest of the frame is skipped.
+        assert!(stages.run_next_stage(&mut world).is_none());
+        assert_eq!(*world.resources.get::<u32>().borrow(), 0);
+    }
+}
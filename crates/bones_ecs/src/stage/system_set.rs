@@ -0,0 +1,48 @@
+use crate::prelude::*;
+
+/// Trait for things that may be used to identify a group of systems that can be enabled and
+/// disabled together.
+///
+/// Systems are added to a set with [`in_set()`][IntoSystemDescriptor::in_set].
+pub trait SystemSet {
+    /// Returns the human-readable name of the set.
+    fn name(&self) -> String;
+    /// Returns a unique identifier for the set.
+    fn id(&self) -> Ulid;
+}
+
+/// Resource containing which [`SystemSet`]s are enabled.
+///
+/// All sets are enabled unless they have been disabled. Changes made to this resource while the
+/// [`SystemStages`] are running take effect on the next frame.
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[ulid = "01GPT6E2XG7W2C3ZD5QF9JQ5AR"]
+pub struct SystemSets {
+    disabled: UlidSet,
+}
+
+impl SystemSets {
+    /// Enable or disable the given system set.
+    pub fn set_enabled<S: SystemSet>(&mut self, set: S, enabled: bool) {
+        self.set_id_enabled(set.id(), enabled);
+    }
+
+    /// Enable or disable the system set with the given id.
+    pub fn set_id_enabled(&mut self, id: Ulid, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&id);
+        } else {
+            self.disabled.insert(id);
+        }
+    }
+
+    /// Returns whether or not the given system set is enabled.
+    pub fn is_enabled<S: SystemSet>(&self, set: S) -> bool {
+        self.is_id_enabled(set.id())
+    }
+
+    /// Returns whether or not the system set with the given id is enabled.
+    pub fn is_id_enabled(&self, id: Ulid) -> bool {
+        !self.disabled.contains(&id)
+    }
+}
@@ -1,9 +1,18 @@
 //! Implements the system API for the ECS.
 
+use std::borrow::Cow;
+
 use crate::prelude::*;
 
 /// Struct used to run a system function using the world.
-pub struct System {
+///
+/// Most systems don't have an output, but systems may return an `Out` value to the caller of
+/// [`run()`][Self::run], such as the `bool` returned by
+/// [run criteria][SimpleSystemStage::with_run_criteria].
+///
+/// Systems may also take an `Input` value, which is passed to the system function as an [`In`]
+/// argument. This is used to [pipe][IntoSystem::pipe] the output of one system into another.
+pub struct System<Out = (), Input = ()> {
     /// This should be called once to initialize the system, allowing it to intialize any resources
     /// or components in the world.
     ///
@@ -11,12 +20,45 @@ pub struct System {
     /// idempotent.
     pub initialize: Box<dyn Send + Sync + Fn(&mut World)>,
     /// This is run every time the system is executed
-    pub run: Box<dyn Send + Sync + FnMut(&World) -> SystemResult>,
+    #[allow(clippy::type_complexity)]
+    pub run: Box<dyn Send + Sync + FnMut(&World, Input) -> anyhow::Result<Out>>,
+    /// This is run instead of [`run`][Self::run] for exclusive systems, that need mutable access
+    /// to the whole [`World`].
+    ///
+    /// Exclusive systems can only be run with [`run_mut()`][Self::run_mut].
+    #[allow(clippy::type_complexity)]
+    pub run_exclusive:
+        Option<Box<dyn Send + Sync + FnMut(&mut World, Input) -> anyhow::Result<Out>>>,
     /// A best-effort name for the system, for diagnostic purposes.
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
+    /// The resources and components accessed by the system.
+    pub access: SystemAccess,
+    /// Where the system was created, which is usually where it was added to the stages.
+    ///
+    /// This is used to explain borrow conflicts between systems.
+    #[cfg(feature = "debug")]
+    pub location: &'static std::panic::Location<'static>,
 }
 
-impl System {
+impl<Out> System<Out> {
+    /// Runs the system's function using the provided [`World`]
+    ///
+    /// # Errors
+    ///
+    /// In addition to any errors returned by the system itself, this errors if the system is an
+    /// [exclusive][Self::is_exclusive] system.
+    pub fn run(&mut self, world: &World) -> anyhow::Result<Out> {
+        self.run_with(world, ())
+    }
+
+    /// Runs the system's function using the provided [`World`], allowing exclusive systems to
+    /// modify the world.
+    pub fn run_mut(&mut self, world: &mut World) -> anyhow::Result<Out> {
+        self.run_mut_with(world, ())
+    }
+}
+
+impl<Out, Input> System<Out, Input> {
     /// Initializes the resources required to run this system inside of the provided [`World`], if
     /// those resources don't already exist.
     ///
@@ -26,16 +68,337 @@ impl System {
         (self.initialize)(world)
     }
 
-    /// Runs the system's function using the provided [`World`]
-    pub fn run(&mut self, world: &World) -> anyhow::Result<()> {
-        (self.run)(world)
+    /// Runs the system's function using the provided [`World`] and `input`.
+    ///
+    /// # Errors
+    ///
+    /// In addition to any errors returned by the system itself, this errors if the system is an
+    /// [exclusive][Self::is_exclusive] system.
+    pub fn run_with(&mut self, world: &World, input: Input) -> anyhow::Result<Out> {
+        #[cfg(feature = "debug")]
+        let _running = crate::debug::RunningSystem::enter(&self.name, self.location);
+        (self.run)(world, input)
     }
 
-    /// Returns the underlying type name of the system.
+    /// Runs the system's function using the provided [`World`] and `input`, allowing exclusive
+    /// systems to modify the world.
+    pub fn run_mut_with(&mut self, world: &mut World, input: Input) -> anyhow::Result<Out> {
+        #[cfg(feature = "debug")]
+        let _running = crate::debug::RunningSystem::enter(&self.name, self.location);
+        match &mut self.run_exclusive {
+            Some(run_exclusive) => run_exclusive(world, input),
+            None => (self.run)(world, input),
+        }
+    }
+
+    /// Returns `true` if this is an exclusive system, that needs mutable access to the [`World`].
+    pub fn is_exclusive(&self) -> bool {
+        self.run_exclusive.is_some()
+    }
+
+    /// Returns the name of the system.
     ///
-    /// This is not guranteed to be stable or human-readable, but can be used for diagnostics.
-    pub fn name(&self) -> &'static str {
-        self.name
+    /// Unless it was set with [`named()`][Self::named], this is the underlying type name of the
+    /// system function, such as `my_game::damage::apply_damage`, or
+    /// `my_game::damage::setup::{{closure}}` for closures. It is not guaranteed to be stable, but
+    /// can be used for diagnostics, such as in [`SystemError`]s and the
+    /// [`SystemStages::debug_report()`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Override the name of the system, used in errors and diagnostics.
+    pub fn named(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the resources and components accessed by the system.
+    pub fn access(&self) -> &SystemAccess {
+        &self.access
+    }
+
+    /// Check that the parameters of the system don't [conflict][SystemAccess::conflicts] with
+    /// each other.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the first conflict if there are any.
+    pub fn validate_access(&self) -> Result<(), EcsError> {
+        match self.access.conflicts.first() {
+            Some(conflict) => Err(EcsError::ConflictingParams(SystemParamConflictError {
+                system: self.name.to_string(),
+                conflict: conflict.clone(),
+            })),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<Out: 'static, Input: 'static> System<Out, Input> {
+    /// Combine this system with another system that takes this system's output as it's input.
+    ///
+    /// See [`IntoSystem::pipe()`].
+    pub fn pipe<Out2: 'static>(self, other: System<Out2, Out>) -> System<Out2, Input> {
+        let System {
+            initialize: initialize_a,
+            run: mut run_a,
+            run_exclusive: mut run_exclusive_a,
+            name: name_a,
+            access: mut access,
+            #[cfg(feature = "debug")]
+            location,
+        } = self;
+        let System {
+            initialize: initialize_b,
+            run: mut run_b,
+            run_exclusive: mut run_exclusive_b,
+            name: name_b,
+            access: access_b,
+            ..
+        } = other;
+        access.extend(&access_b);
+
+        let name: Cow<'static, str> = format!("{} | {}", name_a, name_b).into();
+
+        let initialize = Box::new(move |world: &mut World| {
+            initialize_a(world);
+            initialize_b(world);
+        });
+
+        if run_exclusive_a.is_none() && run_exclusive_b.is_none() {
+            System {
+                initialize,
+                run: Box::new(move |world, input| {
+                    let output = run_a(world, input)?;
+                    run_b(world, output)
+                }),
+                run_exclusive: None,
+                name,
+                access,
+                #[cfg(feature = "debug")]
+                location,
+            }
+        } else {
+            let exclusive_name = name.clone();
+            System {
+                initialize,
+                run: Box::new(move |_, _| {
+                    anyhow::bail!(
+                        "Exclusive system `{}` must be run with mutable access to the world",
+                        exclusive_name
+                    )
+                }),
+                run_exclusive: Some(Box::new(move |world, input| {
+                    let output = match &mut run_exclusive_a {
+                        Some(run_exclusive_a) => run_exclusive_a(world, input)?,
+                        None => run_a(&*world, input)?,
+                    };
+                    match &mut run_exclusive_b {
+                        Some(run_exclusive_b) => run_exclusive_b(world, output),
+                        None => run_b(&*world, output),
+                    }
+                })),
+                name,
+                access,
+                #[cfg(feature = "debug")]
+                location,
+            }
+        }
+    }
+}
+
+/// The resources and components that a [`System`] reads and writes.
+///
+/// This is used to find out which systems may safely run at the same time, such as in the
+/// [`ParallelSystemStage`].
+#[derive(Clone, Debug, Default)]
+pub struct SystemAccess {
+    /// The [`TypeUlid`]s of resources that are read.
+    pub resource_reads: UlidSet,
+    /// The [`TypeUlid`]s of resources that are written.
+    pub resource_writes: UlidSet,
+    /// The [`TypeUlid`]s of components that are read.
+    pub component_reads: UlidSet,
+    /// The [`TypeUlid`]s of components that are written.
+    pub component_writes: UlidSet,
+    /// Whether the system may access anything in the [`World`].
+    ///
+    /// Systems with world access conflict with every other system.
+    pub world: bool,
+    /// The parameters of the system that conflict with each other.
+    ///
+    /// A system with conflicting parameters fails to borrow them whenever it is run, so these are
+    /// reported as an error by [`SystemStages::initialize_systems()`], before any system runs.
+    pub conflicts: Vec<AccessConflict>,
+}
+
+/// Two parameters of the same system that access the same resource or component, where at least
+/// one of them accesses it mutably, such as `Res<T>` and `ResMut<T>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessConflict {
+    /// The type name of the first parameter.
+    pub first: &'static str,
+    /// The type name of the second parameter.
+    pub second: &'static str,
+    /// The [`TypeUlid`] of the resource or component that both parameters access.
+    pub id: Ulid,
+    /// Whether [`id`][Self::id] is the ULID of a component type, instead of a resource type.
+    pub is_component: bool,
+}
+
+impl std::fmt::Display for AccessConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` and `{}` both access the {} ( {} ), and at least one of them mutably",
+            self.first,
+            self.second,
+            if self.is_component {
+                "component"
+            } else {
+                "resource"
+            },
+            self.id
+        )
+    }
+}
+
+impl SystemAccess {
+    /// Create a [`SystemAccess`] that may access anything in the [`World`].
+    pub fn world() -> Self {
+        Self {
+            world: true,
+            ..default()
+        }
+    }
+
+    /// Collect the access of the parameters of a system, given their type names and
+    /// [`SystemParam::access()`] functions, recording the [`conflicts`][Self::conflicts] between
+    /// them.
+    pub fn from_params(params: &[(&'static str, fn(&mut SystemAccess))]) -> Self {
+        let params = params
+            .iter()
+            .map(|&(name, access)| {
+                let mut param = SystemAccess::default();
+                access(&mut param);
+                (name, param)
+            })
+            .collect::<Vec<_>>();
+
+        let mut access = SystemAccess::default();
+        for (i, &(second, ref param)) in params.iter().enumerate() {
+            for &(first, ref earlier) in &params[..i] {
+                access.conflicts.extend(earlier.conflicting_ids(param).map(
+                    |(id, is_component)| AccessConflict {
+                        first,
+                        second,
+                        id,
+                        is_component,
+                    },
+                ));
+            }
+            access.extend(param);
+        }
+        access
+    }
+
+    /// Add everything accessed by `other` to this access.
+    pub fn extend(&mut self, other: &SystemAccess) {
+        self.resource_reads.extend(&other.resource_reads);
+        self.resource_writes.extend(&other.resource_writes);
+        self.component_reads.extend(&other.component_reads);
+        self.component_writes.extend(&other.component_writes);
+        self.world |= other.world;
+        self.conflicts.extend_from_slice(&other.conflicts);
+    }
+
+    /// Iterate over the resources and components that are written by one of the accesses, and
+    /// read or written by the other, along with whether they are components, sorted by ULID.
+    fn conflicting_ids(&self, other: &SystemAccess) -> impl Iterator<Item = (Ulid, bool)> {
+        fn conflicts(
+            a_reads: &UlidSet,
+            a_writes: &UlidSet,
+            b_reads: &UlidSet,
+            b_writes: &UlidSet,
+        ) -> Vec<Ulid> {
+            let mut ids = a_writes
+                .iter()
+                .filter(|id| b_reads.contains(id) || b_writes.contains(id))
+                .chain(b_writes.iter().filter(|id| a_reads.contains(id)))
+                .copied()
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+            ids
+        }
+
+        let resources = conflicts(
+            &self.resource_reads,
+            &self.resource_writes,
+            &other.resource_reads,
+            &other.resource_writes,
+        );
+        let components = conflicts(
+            &self.component_reads,
+            &self.component_writes,
+            &other.component_reads,
+            &other.component_writes,
+        );
+        resources
+            .into_iter()
+            .map(|id| (id, false))
+            .chain(components.into_iter().map(|id| (id, true)))
+    }
+
+    /// Returns `true` if a system with this access may run at the same time as a system with the
+    /// `other` access.
+    pub fn is_compatible(&self, other: &SystemAccess) -> bool {
+        if self.world || other.world {
+            return false;
+        }
+
+        self.resource_writes.is_disjoint(&other.resource_reads)
+            && self.resource_writes.is_disjoint(&other.resource_writes)
+            && other.resource_writes.is_disjoint(&self.resource_reads)
+            && self.component_writes.is_disjoint(&other.component_reads)
+            && self.component_writes.is_disjoint(&other.component_writes)
+            && other.component_writes.is_disjoint(&self.component_reads)
+    }
+}
+
+/// Trait for the types that may be returned by system functions.
+///
+/// This is implemented for:
+///
+/// - `()`, for systems without an output,
+/// - `bool`, for systems used as run criteria, and
+/// - [`anyhow::Result<T>`], such as [`SystemResult`], for systems that may fail.
+pub trait SystemReturn {
+    /// The output type of the [`System`] created from a function with this return type.
+    type Out;
+    /// Convert the function's return value into the system's result.
+    fn into_result(self) -> anyhow::Result<Self::Out>;
+}
+
+impl SystemReturn for () {
+    type Out = ();
+    fn into_result(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T> SystemReturn for anyhow::Result<T> {
+    type Out = T;
+    fn into_result(self) -> anyhow::Result<T> {
+        self
+    }
+}
+
+impl SystemReturn for bool {
+    type Out = bool;
+    fn into_result(self) -> anyhow::Result<bool> {
+        Ok(self)
     }
 }
 
@@ -45,53 +408,254 @@ impl System {
 ///
 /// - Have 26 or less arguments,
 /// - Where every argument implments [`SystemParam`], and
-/// - That returns either `()` or [`SystemResult`]
+/// - That return a type implementing [`SystemReturn`], such as `()` or [`SystemResult`].
 ///
 /// [`IntoSystem`] is also implemented for functions that take [`&World`][World] as an argument, and
-/// return either `()` or [`SystemResult`].
+/// return a type implementing [`SystemReturn`].
+///
+/// Functions that take [`&mut World`][World] as an argument are converted into exclusive systems,
+/// which are never run at the same time as other systems, and can make any kind of change to the
+/// world, such as despawning entities and calling [`World::maintain()`].
+///
+/// Functions that take an [`In<Input>`][In] as their first argument, followed by [`SystemParam`]s,
+/// are converted into systems that take an `Input`, which can be used as the second half of a
+/// [pipe][Self::pipe].
 ///
 /// The most common [`SystemParam`] types that you will use as arguments to a system will be:
 ///  - [`Res`] and [`ResMut`] parameters to access resources
 /// - [`Comp`] and [`CompMut`] parameters to access components
-pub trait IntoSystem<Args> {
+pub trait IntoSystem<Args, Out = (), Input = ()> {
     /// Convert into a [`System`].
-    fn system(self) -> System;
+    fn system(self) -> System<Out, Input>;
+
+    /// Combine this system with another system that takes this system's output as an [`In`]
+    /// argument, into a single [`System`].
+    ///
+    /// The combined system is named after both systems, so errors from either of them will be
+    /// reported with the combined name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// fn double(value: Res<u32>) -> anyhow::Result<u32> {
+    ///     Ok(*value * 2)
+    /// }
+    ///
+    /// fn print_value(value: In<u32>) {
+    ///     println!("The value is {}", *value);
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.run_system(double.pipe(print_value)).unwrap();
+    /// ```
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn pipe<Args2, Out2, S: IntoSystem<Args2, Out2, Out>>(self, other: S) -> System<Out2, Input>
+    where
+        Self: Sized,
+        Out: 'static,
+        Out2: 'static,
+        Input: 'static,
+    {
+        self.system().pipe(other.system())
+    }
+
+    /// Convert into a [`System`] with the given name, instead of the name of the function's type.
+    ///
+    /// The name is used in the [`SystemError`]s returned by the system, and in diagnostics such as
+    /// the [`SystemStages::debug_report()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bones_ecs::prelude::*;
+    /// let system = (|| ()).named("do_nothing");
+    /// assert_eq!(system.name(), "do_nothing");
+    /// ```
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn named(self, name: impl Into<Cow<'static, str>>) -> System<Out, Input>
+    where
+        Self: Sized,
+    {
+        self.system().named(name)
+    }
 }
 
-impl IntoSystem<System> for System {
-    fn system(self) -> System {
+impl<Out, Input> IntoSystem<System<Out, Input>, Out, Input> for System<Out, Input> {
+    fn system(self) -> System<Out, Input> {
         self
     }
 }
 
-impl<F> IntoSystem<(World, F)> for F
+impl<F, R> IntoSystem<(World, F, R), R::Out> for F
 where
-    F: FnMut(&World) + Send + Sync + 'static,
+    F: FnMut(&World) -> R + Send + Sync + 'static,
+    R: SystemReturn,
 {
-    fn system(mut self) -> System {
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn system(mut self) -> System<R::Out> {
         System {
             initialize: Box::new(|_| ()),
-            run: Box::new(move |world| {
-                self(world);
-                Ok(())
-            }),
-            name: std::any::type_name::<F>(),
+            run: Box::new(move |world, ()| self(world).into_result()),
+            run_exclusive: None,
+            name: std::any::type_name::<F>().into(),
+            access: SystemAccess::world(),
+            #[cfg(feature = "debug")]
+            location: std::panic::Location::caller(),
         }
     }
 }
-impl<F> IntoSystem<(World, F, SystemResult)> for F
+
+/// Marker used in the [`IntoSystem`] implementation for exclusive systems.
+#[doc(hidden)]
+pub struct ExclusiveSystemMarker;
+
+impl<F, R> IntoSystem<(ExclusiveSystemMarker, F, R), R::Out> for F
 where
-    F: FnMut(&World) -> SystemResult + Send + Sync + 'static,
+    F: FnMut(&mut World) -> R + Send + Sync + 'static,
+    R: SystemReturn,
 {
-    fn system(self) -> System {
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn system(mut self) -> System<R::Out> {
+        let name = std::any::type_name::<F>();
         System {
             initialize: Box::new(|_| ()),
-            run: Box::new(self),
-            name: std::any::type_name::<F>(),
+            run: Box::new(move |_, ()| {
+                anyhow::bail!(
+                    "Exclusive system `{}` must be run with mutable access to the world",
+                    name
+                )
+            }),
+            run_exclusive: Some(Box::new(move |world, ()| self(world).into_result())),
+            name: name.into(),
+            access: SystemAccess::world(),
+            #[cfg(feature = "debug")]
+            location: std::panic::Location::caller(),
+        }
+    }
+}
+
+/// [`System`] function argument containing the input of the system, such as the output of the
+/// previous system in a [pipe][IntoSystem::pipe].
+///
+/// This must be the first argument of the system function.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct In<T>(pub T);
+
+/// A [`System`] along with the [`SystemOrdering`] constraints to use when adding it to a stage.
+///
+/// Usually created by calling [`label()`][IntoSystemDescriptor::label],
+/// [`before()`][IntoSystemDescriptor::before], or [`after()`][IntoSystemDescriptor::after] on a
+/// system function.
+pub struct SystemDescriptor {
+    /// The system to run.
+    pub system: System,
+    /// The ordering constraints of the system.
+    pub ordering: SystemOrdering,
+    /// The ids of the [`SystemSet`]s that the system is in.
+    pub sets: Vec<Ulid>,
+}
+
+/// Constraints used to order systems within a single stage.
+///
+/// Labels are plain strings, and a system may be referenced by any of it's labels in the `before`
+/// and `after` constraints of other systems.
+#[derive(Clone, Debug, Default)]
+pub struct SystemOrdering {
+    /// The labels attached to the system.
+    pub labels: Vec<String>,
+    /// The labels of systems that this system must run before.
+    pub before: Vec<String>,
+    /// The labels of systems that this system must run after.
+    pub after: Vec<String>,
+}
+
+/// Converts a system into a [`SystemDescriptor`], and allows attaching ordering constraints to it.
+///
+/// This is automatically implemented for everything that implements [`IntoSystem`].
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// fn collision_detect() {}
+/// fn apply_damage() {}
+///
+/// let mut stages = SystemStages::with_core_stages();
+/// stages
+///     .add_system_to_stage(CoreStage::Update, apply_damage.after("collision_detect"))
+///     .add_system_to_stage(CoreStage::Update, collision_detect.label("collision_detect"));
+/// ```
+pub trait IntoSystemDescriptor<Args> {
+    /// Convert into a [`SystemDescriptor`].
+    fn descriptor(self) -> SystemDescriptor;
+
+    /// Attach a label to the system, that other systems may use to order themselves relative to
+    /// it.
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn label<L: Into<String>>(self, label: L) -> SystemDescriptor
+    where
+        Self: Sized,
+    {
+        let mut descriptor = self.descriptor();
+        descriptor.ordering.labels.push(label.into());
+        descriptor
+    }
+
+    /// Make the system run before the systems with the given label.
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn before<L: Into<String>>(self, label: L) -> SystemDescriptor
+    where
+        Self: Sized,
+    {
+        let mut descriptor = self.descriptor();
+        descriptor.ordering.before.push(label.into());
+        descriptor
+    }
+
+    /// Make the system run after the systems with the given label.
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn after<L: Into<String>>(self, label: L) -> SystemDescriptor
+    where
+        Self: Sized,
+    {
+        let mut descriptor = self.descriptor();
+        descriptor.ordering.after.push(label.into());
+        descriptor
+    }
+
+    /// Add the system to a [`SystemSet`], so that it can be disabled along with all of the other
+    /// systems in the set.
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn in_set<S: SystemSet>(self, set: S) -> SystemDescriptor
+    where
+        Self: Sized,
+    {
+        let mut descriptor = self.descriptor();
+        descriptor.sets.push(set.id());
+        descriptor
+    }
+}
+
+impl IntoSystemDescriptor<SystemDescriptor> for SystemDescriptor {
+    fn descriptor(self) -> SystemDescriptor {
+        self
+    }
+}
+
+impl<Args, S: IntoSystem<Args>> IntoSystemDescriptor<Args> for S {
+    #[cfg_attr(feature = "debug", track_caller)]
+    fn descriptor(self) -> SystemDescriptor {
+        SystemDescriptor {
+            system: self.system(),
+            ordering: default(),
+            sets: default(),
         }
     }
 }
 
+pub use bones_ecs_macros::SystemParam;
+
 /// Trait used to implement parameters for [`System`] functions.
 ///
 /// Functions that only take arguments implementing [`SystemParam`] automatically implment
@@ -100,6 +664,35 @@ where
 /// Implementing [`SystemParam`] manually can be useful for creating new kinds of parameters you may
 /// use in your system funciton arguments. Examples might inlclude event readers and writers or
 /// other custom ways to access the data inside a [`World`].
+///
+/// [`SystemParam`] can also be derived for structs with at most one lifetime, where every field is
+/// a [`SystemParam`], to bundle parameters that are often used together into a single parameter.
+/// The struct's access is the combined access of it's fields, so it conflicts with the same
+/// parameters that it's fields would.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, Default, TypeUlid)]
+/// # #[ulid = "01GPYAR1B5N1MX9MQBDKQ9J7GZ"]
+/// # struct Body;
+/// #[derive(SystemParam)]
+/// struct PhysicsCtx<'a> {
+///     entities: Res<'a, Entities>,
+///     bodies: CompMut<'a, Body>,
+///     time: Res<'a, Time>,
+/// }
+///
+/// fn physics_system(ctx: PhysicsCtx) {
+///     for (_entity, _body) in ctx.entities.iter_with(&ctx.bodies) {
+///         // Simulate ...
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.run_system(physics_system).unwrap();
+/// ```
 pub trait SystemParam: Sized {
     /// The intermediate state for the parameter, that may be extracted from the world.
     type State;
@@ -111,10 +704,25 @@ pub trait SystemParam: Sized {
     /// > If the type is not the same, then system functions will not be able to take it as an
     /// > argument.
     type Param<'s>;
+    /// State that is kept for as long as the system exists, and is passed to every call to
+    /// [`borrow()`][Self::borrow].
+    ///
+    /// This can be used by parameters that need to remember something in between runs of the
+    /// system, such as the position of an [`EventReader`] in the event queue. Parameters that don't
+    /// need it can use `()`.
+    type Local: Default + Send + Sync + 'static;
     /// This will be called to give the parameter a chance to initialize it's world storage.
     ///
     /// You can use this chance to init any resources or components you need in the world.
     fn initialize(world: &mut World);
+    /// Record the resources and components that this parameter accesses.
+    ///
+    /// This is used to decide which systems may run in parallel. The default implementation marks
+    /// the parameter as possibly accessing anything in the world, so that systems using it will
+    /// never run at the same time as other systems.
+    fn access(access: &mut SystemAccess) {
+        access.world = true;
+    }
     /// This is called to produce the intermediate state of the system parameter.
     ///
     /// This state will be created immediately before the system is run, and will kept alive until
@@ -123,139 +731,469 @@ pub trait SystemParam: Sized {
     /// This is used create an instance of the system parame, possibly borrowed from the
     /// intermediate parameter state.
     #[allow(clippy::needless_lifetimes)] // Explicit lifetimes help clarity in this case
-    fn borrow<'s>(state: &'s mut Self::State) -> Self::Param<'s>;
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s>;
 }
 
 /// [`SystemParam`] for getting read access to a resource.
-pub struct Res<'a, T: TypedEcsData + Default>(AtomicRef<'a, T>);
-impl<'a, T: TypedEcsData + Default> std::ops::Deref for Res<'a, T> {
+///
+/// The resource is initialized with it's [`Default`] value when the system is initialized, if it
+/// doesn't exist yet. Use an `Option<Res<T>>` parameter instead for resources that might not
+/// exist.
+pub struct Res<'a, T: TypedEcsData> {
+    value: AtomicRef<'a, T>,
+    ticks: AtomicRef<'a, ComponentTicks>,
+    last_run: Option<Tick>,
+    this_run: Tick,
+}
+impl<'a, T: TypedEcsData> std::ops::Deref for Res<'a, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.value
+    }
+}
+impl<'a, T: TypedEcsData> Res<'a, T> {
+    fn new(resource: &'a AtomicResource<T>, this_run: Tick, last_run: &mut Option<Tick>) -> Self {
+        Self {
+            value: resource.borrow(),
+            ticks: resource.borrow_ticks(),
+            last_run: last_run.replace(this_run),
+            this_run,
+        }
+    }
+
+    /// Whether the resource was inserted since the system last ran.
+    pub fn is_added(&self) -> bool {
+        self.ticks.is_added(self.last_run, self.this_run)
+    }
+
+    /// Whether the resource was inserted or mutably accessed since the system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.last_run, self.this_run)
     }
 }
 
 /// [`SystemParam`] for getting mutable access to a resource.
-pub struct ResMut<'a, T: TypedEcsData + Default>(AtomicRefMut<'a, T>);
-impl<'a, T: TypedEcsData + Default> std::ops::Deref for ResMut<'a, T> {
+///
+/// The resource is initialized with it's [`Default`] value when the system is initialized, if it
+/// doesn't exist yet. Use an `Option<ResMut<T>>` parameter instead for resources that might not
+/// exist.
+///
+/// The resource is marked as changed when it is mutably dereferenced.
+pub struct ResMut<'a, T: TypedEcsData> {
+    value: AtomicRefMut<'a, T>,
+    ticks: AtomicRefMut<'a, ComponentTicks>,
+    last_run: Option<Tick>,
+    this_run: Tick,
+}
+impl<'a, T: TypedEcsData> std::ops::Deref for ResMut<'a, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.value
     }
 }
-impl<'a, T: TypedEcsData + Default> std::ops::DerefMut for ResMut<'a, T> {
+impl<'a, T: TypedEcsData> std::ops::DerefMut for ResMut<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        self.ticks.changed = self.this_run;
+        &mut self.value
+    }
+}
+impl<'a, T: TypedEcsData> ResMut<'a, T> {
+    fn new(resource: &'a AtomicResource<T>, this_run: Tick, last_run: &mut Option<Tick>) -> Self {
+        Self {
+            value: resource.borrow_mut(),
+            ticks: resource.borrow_ticks_mut(),
+            last_run: last_run.replace(this_run),
+            this_run,
+        }
+    }
+
+    /// Whether the resource was inserted since the system last ran.
+    pub fn is_added(&self) -> bool {
+        self.ticks.is_added(self.last_run, self.this_run)
+    }
+
+    /// Whether the resource was inserted or mutably accessed since the system last ran.
+    ///
+    /// This includes the changes made by the system itself while it is running.
+    pub fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.last_run, self.this_run)
+    }
+
+    /// Mark the resource as changed, without modifying it.
+    pub fn set_changed(&mut self) {
+        self.ticks.changed = self.this_run;
+    }
+
+    /// Get mutable access to the resource without marking it as changed.
+    pub fn bypass_change_detection(&mut self) -> &mut T {
+        &mut self.value
     }
 }
 
 impl<'a, T: TypedEcsData + Default> SystemParam for Res<'a, T> {
-    type State = AtomicResource<T>;
+    type State = (AtomicResource<T>, Tick);
     type Param<'p> = Res<'p, T>;
+    type Local = Option<Tick>;
 
     fn initialize(world: &mut World) {
         world.resources.init::<T>()
     }
+    fn access(access: &mut SystemAccess) {
+        access.resource_reads.insert(T::ULID);
+    }
     fn get_state(world: &World) -> Self::State {
-        world.resources.get::<T>()
+        (world.resources.get::<T>(), world.change_tick())
     }
-    fn borrow(state: &mut Self::State) -> Self::Param<'_> {
-        Res(state.borrow())
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        Res::new(&state.0, state.1, local)
     }
 }
 
 impl<'a, T: TypedEcsData + Default> SystemParam for ResMut<'a, T> {
-    type State = AtomicResource<T>;
+    type State = (AtomicResource<T>, Tick);
     type Param<'p> = ResMut<'p, T>;
+    type Local = Option<Tick>;
 
     fn initialize(world: &mut World) {
         world.resources.init::<T>();
     }
+    fn access(access: &mut SystemAccess) {
+        access.resource_writes.insert(T::ULID);
+    }
     fn get_state(world: &World) -> Self::State {
-        world.resources.get::<T>()
+        (world.resources.get::<T>(), world.change_tick())
     }
-    fn borrow(state: &mut Self::State) -> Self::Param<'_> {
-        ResMut(state.borrow_mut())
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        ResMut::new(&state.0, state.1, local)
+    }
+}
+
+/// [`SystemParam`] for getting read access to a resource that might not exist.
+///
+/// Unlike [`Res`], this doesn't initialize the resource, and is `None` when the system is run
+/// while the resource doesn't exist.
+impl<'a, T: TypedEcsData> SystemParam for Option<Res<'a, T>> {
+    type State = (Option<AtomicResource<T>>, Tick);
+    type Param<'p> = Option<Res<'p, T>>;
+    type Local = Option<Tick>;
+
+    fn initialize(_world: &mut World) {}
+    fn access(access: &mut SystemAccess) {
+        access.resource_reads.insert(T::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        (world.resources.try_get::<T>(), world.change_tick())
+    }
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        let (resource, this_run) = state;
+        resource
+            .as_ref()
+            .map(|resource| Res::new(resource, *this_run, local))
+    }
+}
+
+/// [`SystemParam`] for getting mutable access to a resource that might not exist.
+///
+/// Unlike [`ResMut`], this doesn't initialize the resource, and is `None` when the system is run
+/// while the resource doesn't exist.
+impl<'a, T: TypedEcsData> SystemParam for Option<ResMut<'a, T>> {
+    type State = (Option<AtomicResource<T>>, Tick);
+    type Param<'p> = Option<ResMut<'p, T>>;
+    type Local = Option<Tick>;
+
+    fn initialize(_world: &mut World) {}
+    fn access(access: &mut SystemAccess) {
+        access.resource_writes.insert(T::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        (world.resources.try_get::<T>(), world.change_tick())
+    }
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        let (resource, this_run) = state;
+        resource
+            .as_ref()
+            .map(|resource| ResMut::new(resource, *this_run, local))
+    }
+}
+
+/// [`SystemParam`] for state that persists across runs of a single system.
+///
+/// The value is created with [`Default`] when the system is created, and is stored in the
+/// [`System`] itself instead of the [`World`], so every instance of a system has it's own value,
+/// even when the same function is added more than once.
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// fn count_frames(mut frames: Local<u32>) {
+///     *frames += 1;
+///     println!("This system has run {} times", *frames);
+/// }
+/// ```
+pub struct Local<'a, T: Default + Send + Sync + 'static>(&'a mut T);
+impl<'a, T: Default + Send + Sync + 'static> std::ops::Deref for Local<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+impl<'a, T: Default + Send + Sync + 'static> std::ops::DerefMut for Local<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<'a, T: Default + Send + Sync + 'static> SystemParam for Local<'a, T> {
+    type State = ();
+    type Param<'p> = Local<'p, T>;
+    type Local = T;
+
+    fn initialize(_world: &mut World) {}
+    fn access(_access: &mut SystemAccess) {}
+    fn get_state(_world: &World) -> Self::State {}
+    fn borrow<'s>(_state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        Local(local)
     }
 }
 
 /// [`SystemParam`] for getting read access to a [`ComponentStore`].
+///
+/// The [`is_changed()`][AtomicComponentStoreRef::is_changed] check and the [`changed()`] query
+/// filter detect the changes made since the system last ran.
 pub type Comp<'a, T> = AtomicComponentStoreRef<'a, T>;
 /// [`SystemParam`] for getting mutable access to a [`ComponentStore`].
+///
+/// Components are marked as changed when they are inserted, or when the [`Mut`] returned by the
+/// mutable accessors is mutably dereferenced.
 pub type CompMut<'a, T> = AtomicComponentStoreRefMut<'a, T>;
 
 impl<'a, T: TypedEcsData> SystemParam for Comp<'a, T> {
-    type State = AtomicComponentStore<T>;
+    type State = (AtomicComponentStore<T>, Tick);
     type Param<'p> = Comp<'p, T>;
+    type Local = Option<Tick>;
 
     fn initialize(world: &mut World) {
         world.components.init::<T>();
     }
+    fn access(access: &mut SystemAccess) {
+        access.component_reads.insert(T::ULID);
+    }
     fn get_state(world: &World) -> Self::State {
-        world.components.get::<T>()
+        (world.components.get::<T>(), world.change_tick())
     }
-    fn borrow(state: &mut Self::State) -> Self::Param<'_> {
-        state.borrow()
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        state.0.borrow().track_last_run(state.1, local)
     }
 }
 
 impl<'a, T: TypedEcsData> SystemParam for CompMut<'a, T> {
-    type State = AtomicComponentStore<T>;
+    type State = (AtomicComponentStore<T>, Tick);
     type Param<'p> = CompMut<'p, T>;
+    type Local = Option<Tick>;
+
+    fn initialize(world: &mut World) {
+        world.components.init::<T>();
+    }
+    fn access(access: &mut SystemAccess) {
+        access.component_writes.insert(T::ULID);
+    }
+    fn get_state(world: &World) -> Self::State {
+        (world.components.get::<T>(), world.change_tick())
+    }
+    fn borrow<'s>(state: &'s mut Self::State, local: &'s mut Self::Local) -> Self::Param<'s> {
+        state.0.borrow_mut().track_last_run(state.1, local)
+    }
+}
+
+/// [`SystemParam`] for getting the entities whose component of type `T` was removed.
+///
+/// The removed components are listed until the end of the frame that they were removed in, when
+/// the lists are cleared by the [`removed_components_update_system`]. Killed entities are listed
+/// for every component that they had, once their components are removed by
+/// [`World::maintain()`].
+///
+/// # Example
+///
+/// ```
+/// # use bones_ecs::prelude::*;
+/// # #[derive(Clone, TypeUlid)]
+/// # #[ulid = "01GQF2N3VXJ5B7QH8D1MZK6T4R"]
+/// # struct Hitbox;
+/// fn unregister_hitboxes(removed: RemovedComponents<Hitbox>) {
+///     for entity in removed.iter() {
+///         println!("Hitbox removed from {entity:?}");
+///     }
+/// }
+/// ```
+pub struct RemovedComponents<'a, T: TypedEcsData> {
+    components: Comp<'a, T>,
+}
+
+impl<'a, T: TypedEcsData> RemovedComponents<'a, T> {
+    /// Iterate over the entities whose component was removed.
+    ///
+    /// An entity is listed once for every time that it's component was removed, and may have had
+    /// the component inserted again since.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.components.removed().iter().copied()
+    }
+
+    /// Get the number of removed components.
+    pub fn len(&self) -> usize {
+        self.components.removed().len()
+    }
+
+    /// Returns `true` if no components have been removed.
+    pub fn is_empty(&self) -> bool {
+        self.components.removed().is_empty()
+    }
+}
+
+impl<'a, T: TypedEcsData> SystemParam for RemovedComponents<'a, T> {
+    type State = AtomicComponentStore<T>;
+    type Param<'p> = RemovedComponents<'p, T>;
+    type Local = ();
 
     fn initialize(world: &mut World) {
         world.components.init::<T>();
     }
+    fn access(access: &mut SystemAccess) {
+        access.component_reads.insert(T::ULID);
+    }
     fn get_state(world: &World) -> Self::State {
         world.components.get::<T>()
     }
-    fn borrow(state: &mut Self::State) -> Self::Param<'_> {
-        state.borrow_mut()
+    fn borrow<'s>(state: &'s mut Self::State, _local: &'s mut Self::Local) -> Self::Param<'s> {
+        RemovedComponents {
+            components: state.borrow(),
+        }
     }
 }
 
 macro_rules! impl_system {
-    ($($args:ident,)* $(-> $ret:ty)?) => {
+    ($($args:ident,)*) => {
         #[allow(unused_parens)]
         impl<
             F,
+            Ret: SystemReturn,
             $(
                 $args: SystemParam,
             )*
-        > IntoSystem<(F, $($args,)* $($ret)?)> for F
+        > IntoSystem<(F, $($args,)* Ret), Ret::Out> for F
         where for<'a> F: 'static + Send + Sync +
             FnMut(
                 $(
                     <$args as SystemParam>::Param<'a>,
                 )*
-            ) $(-> $ret)? +
+            ) -> Ret +
             FnMut(
                 $(
                     $args,
                 )*
-            ) $(-> $ret)?
+            ) -> Ret
         {
-            fn system(mut self) -> System {
+            #[cfg_attr(feature = "debug", track_caller)]
+            fn system(mut self) -> System<Ret::Out> {
+                #[allow(unused_mut, unused_variables)]
+                let mut locals = ($(<$args as SystemParam>::Local::default(),)*);
                 System {
-                    name: std::any::type_name::<F>(),
+                    name: std::any::type_name::<F>().into(),
+                    run_exclusive: None,
+                    #[cfg(feature = "debug")]
+                    location: std::panic::Location::caller(),
+                    access: SystemAccess::from_params(&[
+                        $(
+                            (
+                                std::any::type_name::<$args>(),
+                                <$args as SystemParam>::access as fn(&mut SystemAccess),
+                            ),
+                        )*
+                    ]),
                     initialize: Box::new(|_world| {
                         $(
                             $args::initialize(_world);
                         )*
                     }),
-                    run: Box::new(move |_world| {
+                    run: Box::new(move |_world, ()| {
+                        #[allow(non_snake_case)]
+                        let ($($args,)*) = &mut locals;
                         $(
                             #[allow(non_snake_case)]
-                            let mut $args = $args::get_state(_world);
+                            let mut $args = (<$args as SystemParam>::get_state(_world), $args);
                         )*
 
-                        let _ = self(
+                        self(
                             $(
-                                $args::borrow(&mut $args),
+                                <$args as SystemParam>::borrow(&mut $args.0, $args.1),
                             )*
-                        );
+                        )
+                        .into_result()
+                    })
+                }
+            }
+        }
+
+        #[allow(unused_parens)]
+        impl<
+            F,
+            Input: 'static,
+            Ret: SystemReturn,
+            $(
+                $args: SystemParam,
+            )*
+        > IntoSystem<(In<Input>, F, $($args,)* Ret), Ret::Out, Input> for F
+        where for<'a> F: 'static + Send + Sync +
+            FnMut(
+                In<Input>,
+                $(
+                    <$args as SystemParam>::Param<'a>,
+                )*
+            ) -> Ret +
+            FnMut(
+                In<Input>,
+                $(
+                    $args,
+                )*
+            ) -> Ret
+        {
+            #[cfg_attr(feature = "debug", track_caller)]
+            fn system(mut self) -> System<Ret::Out, Input> {
+                #[allow(unused_mut, unused_variables)]
+                let mut locals = ($(<$args as SystemParam>::Local::default(),)*);
+                System {
+                    name: std::any::type_name::<F>().into(),
+                    run_exclusive: None,
+                    #[cfg(feature = "debug")]
+                    location: std::panic::Location::caller(),
+                    access: SystemAccess::from_params(&[
+                        $(
+                            (
+                                std::any::type_name::<$args>(),
+                                <$args as SystemParam>::access as fn(&mut SystemAccess),
+                            ),
+                        )*
+                    ]),
+                    initialize: Box::new(|_world| {
+                        $(
+                            $args::initialize(_world);
+                        )*
+                    }),
+                    run: Box::new(move |_world, input| {
+                        #[allow(non_snake_case)]
+                        let ($($args,)*) = &mut locals;
+                        $(
+                            #[allow(non_snake_case)]
+                            let mut $args = (<$args as SystemParam>::get_state(_world), $args);
+                        )*
 
-                        Ok(())
+                        self(
+                            In(input),
+                            $(
+                                <$args as SystemParam>::borrow(&mut $args.0, $args.1),
+                            )*
+                        )
+                        .into_result()
                     })
                 }
             }
@@ -269,13 +1207,11 @@ macro_rules! impl_systems {
     ($head:ident, $($idents:ident,)*) => {
         // recursive call
         impl_system!($head, $($idents,)*);
-        impl_system!($head, $($idents,)* -> SystemResult);
         impl_systems!($($idents,)*);
     }
 }
 
 impl_system!();
-impl_system!(-> SystemResult);
 impl_systems!(A, B, C, D, E, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,);
 
 #[cfg(test)]
@@ -372,4 +1308,305 @@ mod tests {
         let res = world.resources.get::<A>();
         assert_eq!(*res.borrow(), A);
     }
+
+    #[test]
+    fn system_returns() {
+        let mut world = World::default();
+
+        let mut failing = (|_a: Res<u32>| -> SystemResult { anyhow::bail!("Failed") }).system();
+        failing.initialize(&mut world);
+        assert!(failing.run(&world).is_err());
+
+        let mut criteria = (|a: Res<u32>| *a == 0).system();
+        criteria.initialize(&mut world);
+        assert!(criteria.run(&world).unwrap());
+    }
+
+    #[test]
+    fn pipe_systems() {
+        let mut world = World::new();
+        world.resources.insert(3u32);
+
+        let mut piped = (|a: Res<u32>| -> anyhow::Result<u32> { Ok(*a * 2) })
+            .pipe(|doubled: In<u32>, mut b: ResMut<u64>| *b = *doubled as u64);
+        piped.initialize(&mut world);
+        piped.run(&world).unwrap();
+        assert_eq!(*world.resources.get::<u64>().borrow(), 6);
+
+        let access = piped.access();
+        assert!(access.resource_reads.contains(&u32::ULID));
+        assert!(access.resource_writes.contains(&u64::ULID));
+    }
+
+    #[test]
+    fn pipe_error_has_combined_name() {
+        fn fails() -> anyhow::Result<u32> {
+            anyhow::bail!("Failed")
+        }
+        fn never_runs(_input: In<u32>) {
+            unreachable!();
+        }
+
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(CoreStage::Update, fails.pipe(never_runs))
+            .initialize_systems(&mut world)
+            .unwrap();
+
+        let errors = stages.run_with_errors(&mut world);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].system.contains("fails"));
+        assert!(errors[0].system.contains("never_runs"));
+    }
+
+    #[test]
+    fn pipe_exclusive_system() {
+        let mut world = World::new();
+        let mut piped = (|world: &mut World| -> anyhow::Result<u32> {
+            world.resources.insert(5u32);
+            Ok(5)
+        })
+        .pipe(|input: In<u32>, value: Res<u32>| assert_eq!(*input, *value));
+
+        assert!(piped.is_exclusive());
+        assert!(piped.run(&world).is_err());
+        piped.run_mut(&mut world).unwrap();
+    }
+
+    #[test]
+    fn local_param() {
+        fn count(mut local: Local<u32>, mut total: ResMut<u32>) -> anyhow::Result<u32> {
+            *local += 1;
+            *total += 1;
+            Ok(*local)
+        }
+
+        let mut world = World::new();
+        let mut first = count.system();
+        let mut second = count.system();
+        first.initialize(&mut world);
+        second.initialize(&mut world);
+
+        // Locals don't access the world.
+        assert!(!first.access().world);
+
+        assert_eq!(first.run(&world).unwrap(), 1);
+        assert_eq!(first.run(&world).unwrap(), 2);
+        assert_eq!(first.run(&world).unwrap(), 3);
+
+        // The second instance of the system has it's own local.
+        assert_eq!(second.run(&world).unwrap(), 1);
+        assert_eq!(*world.resources.get::<u32>().borrow(), 4);
+    }
+
+    #[test]
+    fn optional_resources() {
+        #[derive(Clone, TypeUlid)]
+        #[ulid = "01GPYCJ5T0WQ3H7E1Y6D8ZQ2N4"]
+        struct Settings {
+            verbose: bool,
+        }
+
+        let mut world = World::new();
+        let mut system = (|settings: Option<Res<Settings>>,
+                           count: Option<ResMut<u32>>|
+         -> anyhow::Result<bool> {
+            if let Some(mut count) = count {
+                *count += 1;
+            }
+            Ok(settings.map_or(false, |settings| settings.verbose))
+        })
+        .system();
+
+        // Initializing the system doesn't create the resources.
+        system.initialize(&mut world);
+        assert!(world.resources.try_get::<u32>().is_none());
+        assert!(!system.run(&world).unwrap());
+
+        world.resources.insert(Settings { verbose: true });
+        world.resources.insert(1u32);
+        assert!(system.run(&world).unwrap());
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+    }
+
+    #[derive(SystemParam)]
+    struct Counter<'a> {
+        count: ResMut<'a, u32>,
+        step: Res<'a, u64>,
+    }
+
+    #[derive(SystemParam)]
+    struct NestedCounter<'a> {
+        counter: Counter<'a>,
+        _values: Comp<'a, i32>,
+    }
+
+    #[test]
+    fn derive_system_param() {
+        let mut world = World::new();
+        world.resources.insert(2u64);
+        let mut system = (|mut nested: NestedCounter| {
+            *nested.counter.count += *nested.counter.step;
+        })
+        .system();
+        system.initialize(&mut world);
+
+        system.run(&world).unwrap();
+        system.run(&world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 4);
+
+        let access = system.access();
+        assert!(access.resource_writes.contains(&u32::ULID));
+        assert!(access.resource_reads.contains(&u64::ULID));
+        assert!(access.component_reads.contains(&i32::ULID));
+        assert!(!access.world);
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn derive_system_param_conflicting_access() {
+        let mut world = World::new();
+        world
+            .run_system(|_counter: Counter, _count: Res<u32>| ())
+            .unwrap();
+    }
+
+    #[test]
+    fn conflicting_params_fail_to_initialize() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages.add_system_to_stage(
+            CoreStage::Update,
+            (|_count: Res<u32>, _count_mut: ResMut<u32>| ()).named("count_twice"),
+        );
+
+        let error = stages.initialize_systems(&mut world).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("count_twice"));
+        assert!(message.contains(std::any::type_name::<Res<u32>>()));
+        assert!(message.contains(std::any::type_name::<ResMut<u32>>()));
+        let EcsError::ConflictingParams(error) = &error else {
+            panic!("unexpected error: {error}");
+        };
+        assert_eq!(error.conflict.id, u32::ULID);
+        assert!(!error.conflict.is_component);
+    }
+
+    #[test]
+    fn conflicting_params_access() {
+        let system =
+            (|_a: CompMut<i32>, _b: Comp<u32>, _c: CompMut<i32>, _d: Res<u32>| ()).system();
+        assert_eq!(
+            system.access().conflicts,
+            [AccessConflict {
+                first: std::any::type_name::<CompMut<i32>>(),
+                second: std::any::type_name::<CompMut<i32>>(),
+                id: i32::ULID,
+                is_component: true,
+            }]
+        );
+        assert!(system.validate_access().is_err());
+
+        // Conflicts between the fields of derived parameters are found too.
+        let system = (|_counter: Counter, _values: Comp<i32>| ()).system();
+        assert!(system.validate_access().is_ok());
+        let system = (|_counter: Counter, _count: Res<u32>| ()).system();
+        assert_eq!(system.access().conflicts.len(), 1);
+        assert!(system.validate_access().is_err());
+
+        // Reading the same resource twice is fine.
+        let system = (|_a: Res<u32>, _b: Res<u32>| ()).system();
+        assert!(system.validate_access().is_ok());
+    }
+
+    #[test]
+    fn removed_components() {
+        let mut world = World::new();
+        let [a, b] = world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut comps: CompMut<u32>| -> anyhow::Result<_> {
+                    let [a, b] = [entities.create(), entities.create()];
+                    comps.insert(a, 1);
+                    comps.insert(b, 2);
+                    Ok([a, b])
+                },
+            )
+            .unwrap();
+
+        // Removing and inserting a component again in the same frame still reports the removal.
+        world
+            .run_system(move |mut comps: CompMut<u32>| {
+                assert_eq!(comps.remove(a), Some(1));
+                comps.insert(a, 3);
+            })
+            .unwrap();
+        world
+            .run_system(move |removed: RemovedComponents<u32>, comps: Comp<u32>| {
+                assert_eq!(removed.iter().collect::<Vec<_>>(), [a]);
+                assert_eq!(comps.get(a), Some(&3));
+            })
+            .unwrap();
+
+        // The components of killed entities are reported once they are removed.
+        world
+            .run_system(move |mut entities: ResMut<Entities>| entities.kill(b))
+            .unwrap();
+        world
+            .run_system(|removed: RemovedComponents<u32>| assert_eq!(removed.len(), 1))
+            .unwrap();
+        world.maintain();
+        world
+            .run_system(move |removed: RemovedComponents<u32>| {
+                assert_eq!(removed.iter().collect::<Vec<_>>(), [a, b]);
+            })
+            .unwrap();
+
+        world.run_system(removed_components_update_system).unwrap();
+        world
+            .run_system(|removed: RemovedComponents<u32>| assert!(removed.is_empty()))
+            .unwrap();
+    }
+
+    #[test]
+    fn removed_components_cleared_every_frame() {
+        let mut world = World::new();
+        world
+            .run_system(|mut entities: ResMut<Entities>, mut comps: CompMut<u64>| {
+                for i in 0..3 {
+                    comps.insert(entities.create(), i);
+                }
+            })
+            .unwrap();
+
+        let mut stages = SystemStages::with_core_stages();
+        stages
+            .add_system_to_stage(
+                CoreStage::Update,
+                |entities: Res<Entities>, mut comps: CompMut<u64>| {
+                    let with_comp = entities
+                        .iter_with(&comps)
+                        .map(|(entity, _)| entity)
+                        .collect::<Vec<_>>();
+                    for entity in with_comp.into_iter().take(2) {
+                        comps.remove(entity);
+                    }
+                },
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                |removed: RemovedComponents<u64>, mut count: ResMut<u32>| {
+                    *count += removed.len() as u32;
+                },
+            );
+        stages.initialize_systems(&mut world).unwrap();
+
+        // Two components are removed in the first frame, and the last one in the second frame.
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 2);
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 3);
+        stages.run(&mut world).unwrap();
+        assert_eq!(*world.resources.get::<u32>().borrow(), 3);
+    }
 }
@@ -0,0 +1,216 @@
+//! Resource for tracking the time that has passed between frames.
+
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+
+/// Resource containing the time that has passed since the previous frame, and since the first
+/// frame.
+///
+/// The time is advanced once every frame by the [`time_update_system`], which
+/// [`SystemStages::with_core_stages()`] adds to the start of [`CoreStage::First`]. By default the
+/// system measures the wall-clock time between updates, but the host, such as the Bevy
+/// integration or a custom runner, may feed in the delta that it measured itself with
+/// [`set_next_delta()`][Self::set_next_delta].
+///
+/// Every delta is clamped to [`max_delta`][Self::max_delta], so that a long hitch, such as the
+/// window being dragged or the game being paused in a debugger, doesn't make the simulation jump
+/// forward all at once.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use bones_ecs::prelude::*;
+/// let mut time = Time::default();
+/// time.advance_exact(Duration::from_millis(16));
+/// time.advance_exact(Duration::from_secs(10));
+///
+/// assert_eq!(time.delta(), Time::DEFAULT_MAX_DELTA);
+/// assert_eq!(time.elapsed(), Duration::from_millis(16) + Time::DEFAULT_MAX_DELTA);
+/// assert_eq!(time.frame_count(), 2);
+/// ```
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GPY3J5P8WQ5TF0JGZ7H7K6QX"]
+pub struct Time {
+    /// The maximum delta of a single frame.
+    ///
+    /// Longer deltas are clamped to this value, so [`elapsed()`][Self::elapsed] may fall behind
+    /// the wall-clock time.
+    pub max_delta: Duration,
+    delta: Duration,
+    elapsed: Duration,
+    frame_count: u64,
+    /// The delta to use for the next update, instead of measuring the wall-clock time.
+    next_delta: Option<Duration>,
+    /// The instant of the last wall-clock update.
+    last_instant: Option<Instant>,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            max_delta: Self::DEFAULT_MAX_DELTA,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame_count: 0,
+            next_delta: None,
+            last_instant: None,
+        }
+    }
+}
+
+impl Time {
+    /// The default value of [`max_delta`][Self::max_delta].
+    pub const DEFAULT_MAX_DELTA: Duration = Duration::from_millis(250);
+
+    /// Set the maximum delta of a single frame.
+    pub fn with_max_delta(mut self, max_delta: Duration) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    /// The time that passed between the previous frame and this one.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// The time that passed between the previous frame and this one, in seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// The total time that has passed over all of the frames.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The total time that has passed over all of the frames, in seconds.
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// The number of times the time has been advanced.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Advance the time by exactly `delta`, clamped to [`max_delta`][Self::max_delta].
+    pub fn advance_exact(&mut self, delta: Duration) {
+        self.delta = delta.min(self.max_delta);
+        self.elapsed += self.delta;
+        self.frame_count += 1;
+    }
+
+    /// Advance the time by the wall-clock time that has passed since the previous call to this
+    /// function.
+    ///
+    /// The first call advances the time by zero.
+    pub fn advance_with_wall_clock(&mut self) {
+        let now = Instant::now();
+        let delta = self
+            .last_instant
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_instant = Some(now);
+
+        self.advance_exact(delta);
+    }
+
+    /// Set the delta that the next [`update()`][Self::update] will advance the time by, instead
+    /// of measuring the wall-clock time.
+    ///
+    /// This is how the host feeds in the delta that it measured, and must be called before every
+    /// frame to keep the time from falling back to the wall-clock.
+    pub fn set_next_delta(&mut self, delta: Duration) {
+        self.next_delta = Some(delta);
+    }
+
+    /// Advance the time by the delta passed to [`set_next_delta()`][Self::set_next_delta], or by
+    /// the wall-clock time if it wasn't called since the previous update.
+    ///
+    /// This is called once every frame by the [`time_update_system`].
+    pub fn update(&mut self) {
+        match self.next_delta.take() {
+            Some(delta) => self.advance_exact(delta),
+            None => self.advance_with_wall_clock(),
+        }
+    }
+}
+
+impl DeltaTime for Time {
+    fn delta(&self) -> Duration {
+        self.delta
+    }
+}
+
+/// System that [updates][Time::update] the [`Time`] resource.
+///
+/// This is added to the start of [`CoreStage::First`] by [`SystemStages::with_core_stages()`].
+/// When using custom stages, it should be added to a stage that runs once every frame.
+pub fn time_update_system(mut time: ResMut<Time>) {
+    time.update();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn advance_exact() {
+        let mut time = Time::default();
+        assert_eq!(time.frame_count(), 0);
+
+        time.advance_exact(Duration::from_millis(10));
+        time.advance_exact(Duration::from_millis(20));
+        assert_eq!(time.delta(), Duration::from_millis(20));
+        assert_eq!(time.delta_seconds(), 0.02);
+        assert_eq!(time.elapsed(), Duration::from_millis(30));
+        assert_eq!(time.frame_count(), 2);
+    }
+
+    #[test]
+    fn max_delta_clamp() {
+        let mut time = Time::default().with_max_delta(Duration::from_millis(100));
+
+        time.advance_exact(Duration::from_secs(5));
+        assert_eq!(time.delta(), Duration::from_millis(100));
+        assert_eq!(time.elapsed(), Duration::from_millis(100));
+
+        time.advance_exact(Duration::from_millis(50));
+        assert_eq!(time.delta(), Duration::from_millis(50));
+        assert_eq!(time.elapsed(), Duration::from_millis(150));
+    }
+
+    #[test]
+    fn wall_clock_starts_at_zero() {
+        let mut time = Time::default();
+        time.advance_with_wall_clock();
+        assert_eq!(time.delta(), Duration::ZERO);
+
+        std::thread::sleep(Duration::from_millis(2));
+        time.advance_with_wall_clock();
+        assert!(time.delta() >= Duration::from_millis(2));
+        assert_eq!(time.frame_count(), 2);
+    }
+
+    #[test]
+    fn core_stages_update_time() {
+        let mut world = World::new();
+        let mut stages = SystemStages::with_core_stages();
+        stages.initialize_systems(&mut world).unwrap();
+
+        world
+            .resources
+            .get::<Time>()
+            .borrow_mut()
+            .set_next_delta(Duration::from_millis(16));
+        stages.run(&mut world).unwrap();
+
+        let time = world.resources.get::<Time>();
+        let time = time.borrow();
+        assert_eq!(time.delta(), Duration::from_millis(16));
+        assert_eq!(time.frame_count(), 1);
+    }
+}
@@ -5,9 +5,63 @@
 //!
 //! [`ulid`]: https://docs.rs/ulid
 
-use fxhash::FxHashMap;
+use std::any::TypeId;
+
+use fxhash::{FxHashMap, FxHashSet};
 
 pub use type_ulid::{TypeUlid, Ulid};
 
+use crate::error::{EcsError, TypeUlidCollisionError};
+
 /// Faster hash map using [`FxHashMap`] and a ULID key.
+///
+/// The hasher isn't randomized, so the iteration order only depends on the keys and the order
+/// that they were inserted in, but it is still arbitrary. Sort the keys when the order affects the
+/// state of a [`World`][crate::World].
 pub type UlidMap<T> = FxHashMap<Ulid, T>;
+
+/// Faster hash set using [`FxHashSet`] and a ULID key.
+pub type UlidSet = FxHashSet<Ulid>;
+
+/// The Rust type that a resource or component store was created for, recorded along with its
+/// [`TypeUlid`] to detect ULID collisions.
+///
+/// Types are only compared when they are registered, not when they are accessed.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RegisteredType {
+    type_id: TypeId,
+    name: &'static str,
+}
+
+impl RegisteredType {
+    /// Get the [`RegisteredType`] for the type `T`.
+    pub(crate) fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+        }
+    }
+
+    /// Returns `true` if this is the type `T`.
+    pub(crate) fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Make sure that `new`, which is being registered with the ULID `id`, is the same type as
+    /// this type, which was already registered with it.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`EcsError::TypeUlidCollision`], naming both types, if they are different.
+    pub(crate) fn check(&self, id: Ulid, new: &RegisteredType) -> Result<(), EcsError> {
+        if self.type_id == new.type_id {
+            Ok(())
+        } else {
+            Err(EcsError::TypeUlidCollision(TypeUlidCollisionError {
+                id,
+                existing: self.name,
+                new: new.name,
+            }))
+        }
+    }
+}
@@ -9,24 +9,52 @@ use crate::prelude::*;
 /// [`World`] is designed to be trivially [`Clone`]ed to allow for snapshotting the world state. The
 /// is especially useful in the context of rollback networking, which requires the ability to
 /// snapshot and restore state.
-#[derive(Clone)]
 pub struct World {
     /// Stores the world resources.
     pub resources: Resources,
     /// Stores the world components.
     pub components: ComponentStores,
+    /// The tick counter used for change detection, shared with the resources and components.
+    change_tick: TickCounter,
+    /// The tick that the change ticks were last checked in.
+    last_check_tick: Tick,
 }
 
 impl Default for World {
     fn default() -> Self {
+        let change_tick = TickCounter::default();
         let mut resources = Resources::new();
+        resources.set_tick_counter(change_tick.clone());
+        let mut components = ComponentStores::default();
+        components.set_tick_counter(change_tick.clone());
 
         // Always initialize an Entities resource
         resources.init::<Entities>();
 
         Self {
             resources,
-            components: Default::default(),
+            components,
+            change_tick,
+            last_check_tick: Tick::default(),
+        }
+    }
+}
+
+impl Clone for World {
+    fn clone(&self) -> Self {
+        // Give the clone it's own tick counter, so that advancing the tick of one world doesn't
+        // affect the other.
+        let change_tick = TickCounter::new(self.change_tick.get());
+        let mut resources = self.resources.clone();
+        resources.set_tick_counter(change_tick.clone());
+        let mut components = self.components.clone();
+        components.set_tick_counter(change_tick.clone());
+
+        Self {
+            resources,
+            components,
+            change_tick,
+            last_check_tick: self.last_check_tick,
         }
     }
 }
@@ -42,10 +70,12 @@ impl World {
     /// This should be called every game frame to cleanup entities that have been killed.
     ///
     /// This will remove the component storage for all killed entities, and allow their slots to be
-    /// re-used for any new entities.
+    /// re-used for any new entities. Entities that have been [reserved][Entities::reserve] are
+    /// created.
     pub fn maintain(&mut self) {
         let entities = self.resources.get::<Entities>();
         let mut entities = entities.borrow_mut();
+        entities.flush_reserved();
 
         for components in &mut self.components.components.values_mut() {
             let mut components = components.borrow_mut();
@@ -61,14 +91,86 @@ impl World {
         entities.clear_killed();
     }
 
-    /// Run a system once.
+    /// Kill every entity and remove all of the components, while leaving the resources untouched.
+    ///
+    /// The generations of the killed entities are advanced like with [`Entities::kill()`], so
+    /// [`Entity`] handles that are still held in resources stay dead, and don't refer to the new
+    /// entities that re-use their slots. The components are removed immediately, without being
+    /// recorded as [removed][RemovedComponents].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Entities`] or any of the component stores are borrowed.
+    pub fn clear_entities(&mut self) {
+        {
+            let entities = self.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            entities.flush_reserved();
+            let alive = entities
+                .iter_with_bitset(entities.bitset())
+                .collect::<Vec<_>>();
+            for entity in alive {
+                entities.kill(entity);
+            }
+            // The components are removed below, so there's nothing left for `maintain()` to do.
+            entities.clear_killed();
+        }
+
+        for components in self.components.components.values() {
+            components.borrow_mut().reset();
+        }
+        self.rebuild_entity_names();
+    }
+
+    /// Get the current tick used for change detection.
+    ///
+    /// Changes made outside of systems are recorded with this tick.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Advance the tick used for change detection, returning the tick from before it was
+    /// advanced.
+    ///
+    /// The returned tick is reserved for the caller, all changes made afterwards will have a newer
+    /// tick. This is called at the start of every frame by [`SystemStages::run()`], and by the
+    /// stages after every system that they run.
+    pub fn increment_change_tick(&self) -> Tick {
+        self.change_tick.increment()
+    }
+
+    /// Clamp the change ticks of all of the components and resources so that they are no older
+    /// than [`MAX_CHANGE_AGE`], if it has been at least [`CHECK_TICK_THRESHOLD`] ticks since they
+    /// were last checked.
+    ///
+    /// This is called at the start of every frame by [`SystemStages::run()`], so that old ticks
+    /// don't appear to be new after the tick counter wraps around.
+    pub fn check_change_ticks(&mut self) {
+        let tick = self.change_tick();
+        if tick.get().wrapping_sub(self.last_check_tick.get()) < CHECK_TICK_THRESHOLD {
+            return;
+        }
+
+        self.resources.untyped_mut().check_change_ticks(tick);
+        self.components.check_change_ticks(tick);
+        self.last_check_tick = tick;
+    }
+
+    /// Run a system once, returning its output.
     ///
-    /// This is good for initializing the world with setup systems.
-    pub fn run_system<R, S: IntoSystem<R>>(&mut self, system: S) -> SystemResult {
+    /// The system is converted, initialized, run, and then discarded, so this is good for
+    /// initializing the world with setup systems, running a system in response to an event such
+    /// as a UI button press, or extracting the results of a query in tests.
+    ///
+    /// Initializing a system registers the resources and components that it accesses, which costs
+    /// more than running it, so this isn't meant to be called every frame. Add systems that run
+    /// every frame to the [`SystemStages`] instead.
+    #[cfg_attr(feature = "debug", track_caller)]
+    pub fn run_system<R, Out, S: IntoSystem<R, Out>>(&mut self, system: S) -> anyhow::Result<Out> {
         let mut s = system.system();
 
         s.initialize(self);
-        s.run(self)
+        s.run_mut(self)
     }
 }
 
@@ -107,7 +209,7 @@ mod tests {
 
     /// Mutates the positions based on the velocities.
     fn pos_vel_system(entities: Res<Entities>, mut pos: CompMut<Pos>, vel: Comp<Vel>) {
-        for (_, (pos, vel)) in entities.iter_with((&mut pos, &vel)) {
+        for (_, (mut pos, vel)) in entities.iter_with((&mut pos, &vel)) {
             pos.0 += vel.0;
             pos.1 += vel.1;
         }
@@ -170,6 +272,42 @@ mod tests {
         world.run_system(test_pos_vel_1_run).unwrap();
     }
 
+    #[test]
+    fn clear_entities() {
+        #[derive(Clone, TypeUlid, Debug, Eq, PartialEq)]
+        #[ulid = "01GQMQ0X5DHY3N8K2T7CWAB9RE"]
+        struct Score(u32);
+
+        let mut world = World::new();
+        world.run_system(setup_world).unwrap();
+        world.resources.insert(Score(10));
+        let old = {
+            let entities = world.resources.get::<Entities>();
+            let entities = entities.borrow();
+            entities
+                .iter_with_bitset(entities.bitset())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(old.len(), 2);
+
+        world.clear_entities();
+        world
+            .run_system(|entities: Res<Entities>, pos: Comp<Pos>, vel: Comp<Vel>| {
+                assert_eq!(entities.iter_with((&pos, &vel)).count(), 0);
+                assert!(pos.iter().next().is_none());
+            })
+            .unwrap();
+
+        // Respawn, re-using the old entity slots.
+        world.run_system(setup_world).unwrap();
+        world.run_system(test_after_setup_state).unwrap();
+        let entities = world.resources.get::<Entities>();
+        for entity in old {
+            assert!(!entities.borrow().is_alive(entity));
+        }
+        assert_eq!(world.resources.get::<Score>().borrow().0, 10);
+    }
+
     #[test]
     fn snapshot() {
         let mut world1 = World::new();
@@ -214,6 +352,38 @@ mod tests {
         w.components.init::<B>();
     }
 
+    #[test]
+    fn type_ulid_collision_names_both_types() {
+        #[derive(Clone, Default, TypeUlid)]
+        #[ulid = "01GQSF2B7D0G3J6M9P2S5V8Y1A"]
+        struct Player;
+
+        /// Type from another crate, that accidentally has the same ULID as [`Player`].
+        #[derive(Clone, Default, TypeUlid)]
+        #[ulid = "01GQSF2B7D0G3J6M9P2S5V8Y1A"]
+        struct Projectile(u64);
+
+        let mut w = World::default();
+        w.components.init::<Player>();
+        let Err(EcsError::TypeUlidCollision(error)) = w.components.try_init::<Projectile>() else {
+            panic!("expected a collision");
+        };
+        assert_eq!(error.id, Player::ULID);
+        assert_eq!(error.existing, std::any::type_name::<Player>());
+        assert_eq!(error.new, std::any::type_name::<Projectile>());
+        let message = error.to_string();
+        assert!(message.contains(std::any::type_name::<Player>()));
+        assert!(message.contains(std::any::type_name::<Projectile>()));
+        assert!(w.components.try_get::<Projectile>().is_err());
+
+        w.resources.insert(Projectile(1));
+        let Err(EcsError::TypeUlidCollision(error)) = w.resources.try_insert(Player) else {
+            panic!("expected a collision");
+        };
+        assert_eq!(error.existing, std::any::type_name::<Projectile>());
+        assert_eq!(error.new, std::any::type_name::<Player>());
+    }
+
     #[test]
     fn world_is_send() {
         send(World::new())
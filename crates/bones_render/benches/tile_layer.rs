@@ -0,0 +1,122 @@
+//! Compares generating a 1024x1024 tile layer one tile at a time with [`TileLayer::set`] to
+//! generating it with the bulk editing methods, and editing a sparse 4096x4096 map stored in a
+//! [`TileLayer`] to editing it in a [`ChunkedTileLayer`].
+
+use bones_render::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const SIZE: u32 = 1024;
+
+/// Generate a map with a ground of dirt covered by a layer of grass, and a sky of empty tiles.
+fn bench_generate(c: &mut Criterion) {
+    let mut entities = Entities::default();
+    let (grass, dirt) = (entities.create(), entities.create());
+    let ground = SIZE / 2;
+    let new_layer = || TileLayer::new(UVec2::splat(SIZE), Vec2::splat(16.0), default());
+
+    let mut group = c.benchmark_group("generate 1024x1024 map");
+    group.bench_function("set", |b| {
+        b.iter(|| {
+            let mut layer = new_layer();
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let tile = match y {
+                        y if y < ground => None,
+                        y if y == ground => Some(grass),
+                        _ => Some(dirt),
+                    };
+                    layer.set(UVec2::new(x, y), tile);
+                }
+            }
+            black_box(layer)
+        })
+    });
+    group.bench_function("fill_rect", |b| {
+        b.iter(|| {
+            let mut layer = new_layer();
+            layer.fill_rect(
+                UVec2::new(0, ground),
+                UVec2::new(SIZE, ground + 1),
+                Some(grass),
+            );
+            layer.fill_rect(UVec2::new(0, ground + 1), UVec2::splat(SIZE), Some(dirt));
+            black_box(layer)
+        })
+    });
+    group.bench_function("row_mut", |b| {
+        b.iter(|| {
+            let mut layer = new_layer();
+            for y in ground..SIZE {
+                let tile = if y == ground { grass } else { dirt };
+                layer.row_mut(y).unwrap().fill(Some(tile));
+            }
+            black_box(layer)
+        })
+    });
+    group.finish();
+}
+
+/// Scatter a thousand tiles over a 4096x4096 map, like the items of a large, mostly empty level.
+fn bench_sparse(c: &mut Criterion) {
+    const SPARSE_SIZE: u32 = 4096;
+    let mut entities = Entities::default();
+    let item = entities.create();
+    // A simple linear congruential generator, so that the positions are the same every run.
+    let positions = (0..1000u32)
+        .scan(12345u32, |seed, _| {
+            *seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            Some(UVec2::new(
+                *seed % SPARSE_SIZE,
+                (*seed / SPARSE_SIZE) % SPARSE_SIZE,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let mut dense = TileLayer::new(UVec2::splat(SPARSE_SIZE), Vec2::splat(16.0), default());
+    let mut chunked =
+        ChunkedTileLayer::new(UVec2::splat(SPARSE_SIZE), Vec2::splat(16.0), default());
+    for &pos in &positions {
+        dense.set(pos, Some(item));
+        chunked.set(pos, Some(item));
+    }
+    let slot_size = std::mem::size_of::<Option<Entity>>();
+    println!(
+        "Memory of the tiles of a sparse {SPARSE_SIZE}x{SPARSE_SIZE} map: {} bytes dense, {} bytes \
+        in {} chunks",
+        dense.tiles.len() * slot_size,
+        chunked.chunk_count() * (TILE_CHUNK_SIZE * TILE_CHUNK_SIZE) as usize * slot_size,
+        chunked.chunk_count(),
+    );
+
+    let mut group = c.benchmark_group("edit sparse 4096x4096 map");
+    group.bench_function("TileLayer", |b| {
+        b.iter(|| {
+            for &pos in &positions {
+                dense.set(pos, None);
+                dense.set(pos, Some(item));
+            }
+        })
+    });
+    group.bench_function("ChunkedTileLayer", |b| {
+        b.iter(|| {
+            for &pos in &positions {
+                chunked.set(pos, None);
+                chunked.set(pos, Some(item));
+            }
+        })
+    });
+    group.bench_function("create ChunkedTileLayer", |b| {
+        b.iter(|| {
+            let mut layer =
+                ChunkedTileLayer::new(UVec2::splat(SPARSE_SIZE), Vec2::splat(16.0), default());
+            for &pos in &positions {
+                layer.set(pos, Some(item));
+            }
+            black_box(layer)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_generate, bench_sparse);
+criterion_main!(benches);
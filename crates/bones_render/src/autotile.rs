@@ -0,0 +1,497 @@
+//! Auto-tiling rules for tile layers.
+
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// Rules that choose the tile of each slot of a terrain from the slots around it, so that the
+/// edges and the corners of the terrain line up after it has been edited.
+///
+/// The rules have a set of [`AutotileTerrain`]s, by their IDs. Each terrain is drawn with its own
+/// tiles, and a slot of a [`TileLayer`] is part of a terrain if its tile is one of the tiles of the
+/// terrain. After editing a layer, such as by removing the tiles that have been dug out, or setting
+/// a slot to any tile of the terrain to add to it, [`TileLayer::autotile_region()`] picks the
+/// right tiles for the edited slots and their neighbors:
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// const DIRT: u16 = 0;
+///
+/// fn dig(
+///     layer: &mut TileLayer,
+///     pos: UVec2,
+///     rules: &AutotileRules,
+///     entities: &mut Entities,
+///     tiles: &mut CompMut<Tile>,
+/// ) {
+///     layer.set(pos, None);
+///     layer.autotile_region(pos, pos + 1, rules, DIRT, entities, tiles);
+/// }
+/// ```
+///
+/// With the `serde` feature, the rules can be loaded from an asset file, so that they can be
+/// tweaked without recompiling the game:
+///
+/// ```yaml
+/// terrains:
+///   0:
+///     layout: blob47
+///     first_tile: 0
+///   1:
+///     layout: edges16
+///     first_tile: 48
+///     # Use another tile for the slots that have no neighbors.
+///     tiles:
+///       0: 64
+/// ```
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[ulid = "01GR0B7K4N9XQ2TD5WM8HC3FJA"]
+pub struct AutotileRules {
+    /// The terrains, by their IDs.
+    pub terrains: HashMap<u16, AutotileTerrain>,
+}
+
+/// The tiles of a terrain of the [`AutotileRules`].
+///
+/// The tiles are in the order of the masks of the [`layout`][Self::layout], starting at the
+/// [`first_tile`][Self::first_tile] of the atlas, unless the [`tiles`][Self::tiles] say
+/// otherwise.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct AutotileTerrain {
+    /// The layout of the tiles of the terrain.
+    pub layout: AutotileLayout,
+    /// The index in the atlas of the tile for the first mask of the layout.
+    pub first_tile: usize,
+    /// The indexes in the atlas of the tiles for some of the masks, which replace the tiles that
+    /// the layout has for them.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tiles: HashMap<u8, usize>,
+}
+
+/// The layout of the tiles of an [`AutotileTerrain`], which says which neighbors of a slot are
+/// taken into account, and how many tiles the terrain has.
+///
+/// The neighbors of a slot make up its mask, where each neighbor that is part of the same terrain
+/// sets one bit. The directions are in the space of the layer, where `+y` is up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[repr(u8)]
+pub enum AutotileLayout {
+    /// 16 tiles for a 4-bit mask of the sides, with the bits of [`AutotileMask::SIDES`].
+    ///
+    /// The tiles are in the order of the masks, from `0` to `15`.
+    #[default]
+    Edges16,
+    /// 47 tiles for an 8-bit mask of the sides and the corners, with the bits of the
+    /// [`AutotileMask`] constants, such as [`AutotileMask::NORTH_EAST`].
+    ///
+    /// A corner only counts when both of the sides next to it are part of the terrain, because it
+    /// doesn't change the tile otherwise, which leaves 47 of the 256 masks. The tiles are in the
+    /// order of those masks, from `0` to `255`.
+    Blob47,
+}
+
+/// The bits of the neighbor masks of the [`AutotileLayout`]s.
+pub struct AutotileMask;
+
+impl AutotileMask {
+    /// The neighbor above.
+    pub const NORTH: u8 = 1 << 0;
+    /// The neighbor above and to the right.
+    pub const NORTH_EAST: u8 = 1 << 1;
+    /// The neighbor to the right.
+    pub const EAST: u8 = 1 << 2;
+    /// The neighbor below and to the right.
+    pub const SOUTH_EAST: u8 = 1 << 3;
+    /// The neighbor below.
+    pub const SOUTH: u8 = 1 << 4;
+    /// The neighbor below and to the left.
+    pub const SOUTH_WEST: u8 = 1 << 5;
+    /// The neighbor to the left.
+    pub const WEST: u8 = 1 << 6;
+    /// The neighbor above and to the left.
+    pub const NORTH_WEST: u8 = 1 << 7;
+
+    /// The bits of the north, east, south, and west sides in the 4-bit masks of the
+    /// [`AutotileLayout::Edges16`] layout, which are different from the bits of the 8-bit masks.
+    ///
+    /// They're `1` for north, `2` for east, `4` for south, and `8` for west.
+    pub const SIDES: [u8; 4] = [1, 2, 4, 8];
+
+    /// The offsets of the neighbors, in the order of the bits of the 8-bit masks.
+    const OFFSETS: [IVec2; 8] = [
+        IVec2::new(0, 1),
+        IVec2::new(1, 1),
+        IVec2::new(1, 0),
+        IVec2::new(1, -1),
+        IVec2::new(0, -1),
+        IVec2::new(-1, -1),
+        IVec2::new(-1, 0),
+        IVec2::new(-1, 1),
+    ];
+}
+
+impl AutotileLayout {
+    /// Get the number of tiles in the layout.
+    pub fn tile_count(self) -> usize {
+        match self {
+            AutotileLayout::Edges16 => 16,
+            AutotileLayout::Blob47 => 47,
+        }
+    }
+
+    /// Get the mask of the slot at `pos`, where `is_terrain` says whether a slot is part of the
+    /// same terrain.
+    pub fn mask(self, pos: IVec2, is_terrain: impl Fn(IVec2) -> bool) -> u8 {
+        let full: u8 = AutotileMask::OFFSETS
+            .iter()
+            .enumerate()
+            .filter(|(_, offset)| is_terrain(pos + **offset))
+            .fold(0, |mask, (bit, _)| mask | 1 << bit);
+        match self {
+            AutotileLayout::Edges16 => AutotileMask::SIDES
+                .iter()
+                .zip([
+                    AutotileMask::NORTH,
+                    AutotileMask::EAST,
+                    AutotileMask::SOUTH,
+                    AutotileMask::WEST,
+                ])
+                .filter(|(_, side)| full & side != 0)
+                .fold(0, |mask, (bit, _)| mask | bit),
+            AutotileLayout::Blob47 => reduce_blob_mask(full),
+        }
+    }
+
+    /// Get the position of the mask in the tiles of the layout, or `None` if the layout never
+    /// makes the mask.
+    pub fn tile_offset(self, mask: u8) -> Option<usize> {
+        match self {
+            AutotileLayout::Edges16 => (mask < 16).then_some(mask as usize),
+            AutotileLayout::Blob47 => (reduce_blob_mask(mask) == mask)
+                .then(|| (0..mask).filter(|&x| reduce_blob_mask(x) == x).count()),
+        }
+    }
+}
+
+/// Remove the corners that don't have both of the sides next to them from an 8-bit mask.
+fn reduce_blob_mask(mask: u8) -> u8 {
+    let corners = [
+        (
+            AutotileMask::NORTH_EAST,
+            AutotileMask::NORTH | AutotileMask::EAST,
+        ),
+        (
+            AutotileMask::SOUTH_EAST,
+            AutotileMask::SOUTH | AutotileMask::EAST,
+        ),
+        (
+            AutotileMask::SOUTH_WEST,
+            AutotileMask::SOUTH | AutotileMask::WEST,
+        ),
+        (
+            AutotileMask::NORTH_WEST,
+            AutotileMask::NORTH | AutotileMask::WEST,
+        ),
+    ];
+    corners
+        .into_iter()
+        .filter(|(_, sides)| mask & sides != *sides)
+        .fold(mask, |mask, (corner, _)| mask & !corner)
+}
+
+impl AutotileTerrain {
+    /// Get the index in the atlas of the tile for the given mask.
+    ///
+    /// Masks that the layout never makes get the tile for a slot without neighbors.
+    pub fn tile(&self, mask: u8) -> usize {
+        if let Some(tile) = self.tiles.get(&mask) {
+            return *tile;
+        }
+        match self.layout.tile_offset(mask) {
+            Some(offset) => self.first_tile + offset,
+            None => self.tile(0),
+        }
+    }
+
+    /// Returns `true` if the tile with the given index in the atlas is one of the tiles of the
+    /// terrain.
+    pub fn contains(&self, idx: usize) -> bool {
+        (self.first_tile..self.first_tile + self.layout.tile_count()).contains(&idx)
+            || self.tiles.values().any(|tile| *tile == idx)
+    }
+}
+
+impl TileLayer {
+    /// Choose the tiles of the slots of a terrain in the rectangle from `min` to `max`, including
+    /// `min` but not `max`, and of the slots around it, from the [`AutotileRules`].
+    ///
+    /// The slots that have a tile of the terrain with the given ID get the tile for their
+    /// neighbors, and the other slots are left alone. The slots outside of the layer count as part
+    /// of the terrain, so that the terrain doesn't have edges at the sides of the layer. Nothing
+    /// changes if the rules don't have the terrain.
+    ///
+    /// The slots share the tile entities that the rectangle already has, or new entities that are
+    /// created for the tiles that it doesn't have. The tile entities that aren't used by the slots
+    /// anymore are left alone, because other slots may still use them.
+    pub fn autotile_region(
+        &mut self,
+        min: UVec2,
+        max: UVec2,
+        rules: &AutotileRules,
+        terrain_id: u16,
+        entities: &mut Entities,
+        tiles: &mut CompMut<Tile>,
+    ) {
+        let Some(terrain) = rules.terrains.get(&terrain_id) else {
+            return;
+        };
+        // The neighbors of the rectangle may have to change too.
+        let grid_size = self.grid_size.as_ivec2();
+        let min = (min.as_ivec2() - 1).max(IVec2::ZERO);
+        let max = (max.as_ivec2() + 1).min(grid_size);
+        let in_layer = |pos: IVec2| pos.cmpge(IVec2::ZERO).all() && pos.cmplt(grid_size).all();
+        let tile = |pos: IVec2| {
+            let entity = self.get(pos.as_uvec2())?;
+            Some((entity, tiles.get(entity)?.clone()))
+        };
+        let is_terrain = |pos: IVec2| {
+            !in_layer(pos) || tile(pos).map_or(false, |(_, tile)| terrain.contains(tile.idx))
+        };
+
+        let mut tile_entities = HashMap::new();
+        let mut changes = Vec::new();
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let pos = IVec2::new(x, y);
+                let Some((entity, current)) = tile(pos) else {
+                    continue;
+                };
+                let is_plain =
+                    !current.flip_x && !current.flip_y && current.rotation == TileRotation::None;
+                if is_plain {
+                    tile_entities.insert(current.idx, entity);
+                }
+                if !terrain.contains(current.idx) {
+                    continue;
+                }
+                let idx = terrain.tile(terrain.layout.mask(pos, &is_terrain));
+                if !is_plain || current.idx != idx {
+                    changes.push((pos.as_uvec2(), idx));
+                }
+            }
+        }
+
+        for (pos, idx) in changes {
+            let entity = *tile_entities.entry(idx).or_insert_with(|| {
+                let entity = entities.create();
+                tiles.insert(entity, Tile { idx, ..default() });
+                entity
+            });
+            self.set(pos, Some(entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    const N: u8 = AutotileMask::NORTH;
+    const NE: u8 = AutotileMask::NORTH_EAST;
+    const E: u8 = AutotileMask::EAST;
+    const SE: u8 = AutotileMask::SOUTH_EAST;
+    const S: u8 = AutotileMask::SOUTH;
+    const W: u8 = AutotileMask::WEST;
+
+    /// Get the mask of a slot in a map, where `#` is part of the terrain, with the first row at the
+    /// top.
+    fn mask(layout: AutotileLayout, map: &[&str], pos: IVec2) -> u8 {
+        let height = map.len() as i32;
+        layout.mask(pos, |pos| {
+            let row = height - 1 - pos.y;
+            (0..height).contains(&row)
+                && map[row as usize].as_bytes().get(pos.x as usize) == Some(&b'#')
+        })
+    }
+
+    #[test]
+    fn diagonal_neighbors() {
+        // The center only touches the terrain at its corners.
+        let map = ["#.#", ".#.", "#.#"];
+        let center = IVec2::new(1, 1);
+        assert_eq!(mask(AutotileLayout::Edges16, &map, center), 0);
+        assert_eq!(mask(AutotileLayout::Blob47, &map, center), 0);
+
+        // A corner counts once both sides next to it are part of the terrain.
+        let map = ["##.", "##.", "..."];
+        assert_eq!(mask(AutotileLayout::Blob47, &map, center), N | W | 1 << 7);
+        let map = [".##", ".#.", "..."];
+        assert_eq!(mask(AutotileLayout::Blob47, &map, center), N);
+        assert_eq!(mask(AutotileLayout::Edges16, &map, center), 1);
+
+        // A full square.
+        let map = ["###", "###", "###"];
+        assert_eq!(mask(AutotileLayout::Blob47, &map, center), 255);
+        assert_eq!(mask(AutotileLayout::Edges16, &map, center), 15);
+        // The 4-bit mask uses its own bits.
+        let map = ["...", ".##", ".##"];
+        assert_eq!(mask(AutotileLayout::Edges16, &map, center), 2 | 4);
+        assert_eq!(mask(AutotileLayout::Blob47, &map, center), E | S | SE);
+        assert_eq!(AutotileLayout::Edges16.tile_offset(2 | 4), Some(6));
+    }
+
+    #[test]
+    fn blob_layout() {
+        let offsets = (0..=255)
+            .filter_map(|mask| AutotileLayout::Blob47.tile_offset(mask))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, (0..47).collect::<Vec<_>>());
+        assert_eq!(AutotileLayout::Blob47.tile_offset(0), Some(0));
+        assert_eq!(AutotileLayout::Blob47.tile_offset(255), Some(46));
+        // A corner without its sides is never made.
+        assert_eq!(AutotileLayout::Blob47.tile_offset(NE), None);
+        assert_eq!(AutotileLayout::Blob47.tile_offset(N | NE | E), Some(4));
+
+        let terrain = AutotileTerrain {
+            layout: AutotileLayout::Blob47,
+            first_tile: 100,
+            tiles: [(0, 7)].into_iter().collect(),
+        };
+        assert_eq!(terrain.tile(N | NE | E), 104);
+        assert_eq!(terrain.tile(0), 7);
+        // Masks that the layout doesn't make get the tile without neighbors.
+        assert_eq!(terrain.tile(NE), 7);
+        assert!(terrain.contains(7) && terrain.contains(146));
+        assert!(!terrain.contains(99) && !terrain.contains(147));
+    }
+
+    #[test]
+    fn autotile_region() {
+        let mut world = World::new();
+        let (dirt, stone) = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            (entities.create(), entities.create())
+        };
+        let rules = AutotileRules {
+            terrains: [(
+                0,
+                AutotileTerrain {
+                    layout: AutotileLayout::Edges16,
+                    first_tile: 0,
+                    tiles: default(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        world
+            .run_system(
+                move |mut entities: ResMut<Entities>, mut tiles: CompMut<Tile>| {
+                    tiles.insert(
+                        dirt,
+                        Tile {
+                            idx: 15,
+                            ..default()
+                        },
+                    );
+                    tiles.insert(
+                        stone,
+                        Tile {
+                            idx: 20,
+                            ..default()
+                        },
+                    );
+
+                    // A 3x3 square of dirt in the middle of a 5x5 layer, with a stone in the
+                    // corner.
+                    let mut layer = TileLayer::new(UVec2::new(5, 5), Vec2::ONE, default());
+                    layer.fill_rect(UVec2::new(1, 1), UVec2::new(4, 4), Some(dirt));
+                    layer.set(UVec2::new(4, 4), Some(stone));
+                    let idx = |layer: &TileLayer, tiles: &CompMut<Tile>, x, y| {
+                        layer
+                            .get(UVec2::new(x, y))
+                            .map(|e| tiles.get(e).unwrap().idx)
+                    };
+
+                    layer.autotile_region(
+                        UVec2::new(1, 1),
+                        UVec2::new(4, 4),
+                        &rules,
+                        0,
+                        &mut entities,
+                        &mut tiles,
+                    );
+                    // The bottom-left corner has neighbors to the north and the east.
+                    assert_eq!(idx(&layer, &tiles, 1, 1), Some(1 | 2));
+                    assert_eq!(idx(&layer, &tiles, 2, 1), Some(1 | 2 | 8));
+                    assert_eq!(idx(&layer, &tiles, 2, 2), Some(15));
+                    assert_eq!(idx(&layer, &tiles, 3, 3), Some(4 | 8));
+                    // The stone, which only touches the dirt at its corner, isn't changed.
+                    assert_eq!(idx(&layer, &tiles, 4, 4), Some(20));
+                    // The slots with the same tile share an entity.
+                    assert_eq!(layer.get(UVec2::new(2, 2)), Some(dirt));
+
+                    // Digging out the middle turns the square into a ring, and only touches the
+                    // slots around it.
+                    layer.set(UVec2::new(2, 2), None);
+                    let before = layer.tiles.clone();
+                    layer.autotile_region(
+                        UVec2::new(2, 2),
+                        UVec2::new(3, 3),
+                        &rules,
+                        0,
+                        &mut entities,
+                        &mut tiles,
+                    );
+                    assert_eq!(idx(&layer, &tiles, 2, 2), None);
+                    assert_eq!(idx(&layer, &tiles, 2, 1), Some(2 | 8));
+                    assert_eq!(idx(&layer, &tiles, 1, 2), Some(1 | 4));
+                    // The corners of the ring only have side neighbors, so they don't change.
+                    assert_eq!(idx(&layer, &tiles, 1, 1), Some(1 | 2));
+                    assert_eq!(layer.tiles[0..5], before[0..5]);
+
+                    // Slots that aren't part of the terrain are left alone, and a layer without
+                    // the terrain doesn't change at all.
+                    let before = layer.tiles.clone();
+                    layer.autotile_region(
+                        UVec2::ZERO,
+                        UVec2::splat(5),
+                        &rules,
+                        1,
+                        &mut entities,
+                        &mut tiles,
+                    );
+                    assert_eq!(layer.tiles, before);
+                },
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_rules() {
+        let rules: AutotileRules = serde_yaml::from_str(
+            "
+terrains:
+  0:
+    layout: blob47
+    first_tile: 0
+  1:
+    layout: edges16
+    first_tile: 48
+    tiles:
+      0: 64
+",
+        )
+        .unwrap();
+        assert_eq!(rules.terrains[&0].layout, AutotileLayout::Blob47);
+        assert_eq!(rules.terrains[&1].tile(0), 64);
+        assert_eq!(rules.terrains[&1].tile(15), 63);
+    }
+}
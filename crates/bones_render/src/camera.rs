@@ -5,28 +5,1593 @@ use crate::prelude::*;
 /// Makes an entity behave like a camera.
 ///
 /// The entity must also have a [`Transform`] component for the camera to render anything.
+///
+/// There may be more than one camera. The [`active`][Self::active] cameras are rendered in
+/// ascending order of [`priority`][Self::priority], each one over the output of the cameras before
+/// it, so a camera for the HUD can be rendered over the camera that follows the player. Cameras
+/// that are rendered over other cameras usually don't [`clear`][Self::clear] the window.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn spawn_cameras(
+///     mut entities: ResMut<Entities>,
+///     mut cameras: CompMut<Camera>,
+///     mut transforms: CompMut<Transform>,
+/// ) {
+///     let world_camera = entities.create();
+///     cameras.insert(world_camera, Camera::default());
+///     transforms.insert(world_camera, Transform::default());
+///
+///     // The HUD is rendered over the world, and doesn't move with the world camera.
+///     let hud_camera = entities.create();
+///     cameras.insert(
+///         hud_camera,
+///         Camera {
+///             priority: 1,
+///             clear: CameraClear::None,
+///             ..default()
+///         },
+///     );
+///     transforms.insert(hud_camera, Transform::default());
+/// }
+/// # let mut world = World::new();
+/// # world.run_system(spawn_cameras).unwrap();
+/// ```
 #[derive(Clone, Copy, Debug, TypeUlid)]
 #[ulid = "01GNR2978NRN7PH5XWBXP3KMD7"]
 #[repr(C)]
 pub struct Camera {
-    /// The height of the camera in in-game pixels.
-    ///
-    /// The width of the camera will be determined from the window aspect ratio.
-    pub height: f32,
+    /// How much of the world the camera shows, in in-game pixels.
+    pub size: CameraSize,
     /// Whether or not the camera is enabled and rendering.
+    ///
+    /// Inactive cameras keep their settings, so they can be enabled again later without
+    /// re-creating them.
     pub active: bool,
+    /// The order that the camera is rendered in, relative to the other cameras.
+    ///
+    /// Cameras with a higher priority are rendered over cameras with a lower priority. Cameras with
+    /// the same priority are rendered in order of their entities.
+    pub priority: i32,
+    /// The rectangle of the window that the camera renders to, or `None` to render to the whole
+    /// window.
+    ///
+    /// The [`size`][Self::size] of the camera is determined from the aspect ratio of the viewport
+    /// instead of the window, if it is set.
+    pub viewport: Option<Viewport>,
+    /// How the window is cleared before the camera renders to it.
+    ///
+    /// The whole window is cleared, even if the camera has a [`viewport`][Self::viewport], so
+    /// only the first camera that is rendered should usually clear it. This is what the default,
+    /// [`CameraClear::Inherit`], does.
+    pub clear: CameraClear,
 }
 
 impl Default for Camera {
     fn default() -> Self {
         Self {
-            height: 400.0,
+            size: CameraSize::default(),
             active: true,
+            priority: 0,
+            viewport: None,
+            clear: CameraClear::Inherit,
+        }
+    }
+}
+
+impl Camera {
+    /// Get the position and size of the rectangle that the camera renders to, in physical pixels,
+    /// in a window with the given size.
+    ///
+    /// This is the whole window if the camera doesn't have a [`viewport`][Self::viewport], and the
+    /// viewport [clamped][Viewport::clamp_to_window] to the window if it does. Returns `None` if the
+    /// window has no area, such as when it's minimized.
+    pub fn viewport_rect(&self, window_size: UVec2) -> Option<(UVec2, UVec2)> {
+        let viewport = match self.viewport {
+            Some(viewport) => viewport.clamp_to_window(window_size)?,
+            None if window_size.x == 0 || window_size.y == 0 => return None,
+            None => Viewport::new(UVec2::ZERO, window_size),
+        };
+        Some((viewport.position, viewport.size))
+    }
+
+    /// Get the width and height of the area of the world that the camera shows, in in-game pixels,
+    /// in a window with the given size.
+    ///
+    /// Returns `None` if the window has no area.
+    pub fn visible_size(&self, window_size: UVec2) -> Option<Vec2> {
+        let (_, size) = self.viewport_rect(window_size)?;
+        Some(self.size.visible_size(size.as_vec2()))
+    }
+
+    /// Get the rectangle of the world that the camera with the given transform shows, in a window
+    /// with the given size.
+    ///
+    /// When the camera is rotated, this is the smallest rectangle that contains everything that it
+    /// shows. Returns `None` if the window has no area.
+    pub fn visible_rect(&self, transform: &Transform, window_size: UVec2) -> Option<Rect> {
+        let (position, size) = self.viewport_rect(window_size)?;
+        let (min, max) = (position.as_vec2(), (position + size).as_vec2());
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+            .map(|corner| self.screen_to_world(transform, window_size, corner));
+        let [first, rest @ ..] = corners;
+        let first = first?;
+        rest.into_iter()
+            .try_fold(Rect::from_corners(first, first), |rect, corner| {
+                let corner = corner?;
+                Some(Rect::from_corners(
+                    rect.min.min(corner),
+                    rect.max.max(corner),
+                ))
+            })
+    }
+
+    /// Convert a position on the screen to a position in the world, for a camera with the given
+    /// transform.
+    ///
+    /// The screen position is in physical pixels from the top-left corner of a window with the
+    /// given size, and takes the [`viewport`][Self::viewport] of the camera into account, so
+    /// positions outside of the viewport are converted to world positions outside of the camera's
+    /// view. Returns `None` if the window has no area.
+    pub fn screen_to_world(
+        &self,
+        transform: &Transform,
+        window_size: UVec2,
+        screen_position: Vec2,
+    ) -> Option<Vec2> {
+        let (position, size) = self.viewport_rect(window_size)?;
+        let (position, size) = (position.as_vec2(), size.as_vec2());
+
+        // The position relative to the center of the viewport, from -1 to 1, with y up.
+        let normalized = (screen_position - position) / size * 2.0 - Vec2::ONE;
+        let local = normalized * Vec2::new(1.0, -1.0) * self.size.visible_size(size) / 2.0;
+        let world = transform_matrix(transform).transform_point3(local.extend(0.0));
+        Some(world.truncate())
+    }
+
+    /// Convert a position in the world to a position on the screen, for a camera with the given
+    /// transform.
+    ///
+    /// This is the inverse of [`screen_to_world()`][Self::screen_to_world]. Returns `None` if the
+    /// window has no area, or if the transform of the camera has a scale of zero.
+    pub fn world_to_screen(
+        &self,
+        transform: &Transform,
+        window_size: UVec2,
+        world_position: Vec2,
+    ) -> Option<Vec2> {
+        let (position, size) = self.viewport_rect(window_size)?;
+        let (position, size) = (position.as_vec2(), size.as_vec2());
+
+        let matrix = transform_matrix(transform);
+        if matrix.determinant() == 0.0 {
+            return None;
+        }
+        let world = world_position.extend(transform.translation.z);
+        let local = matrix.inverse().transform_point3(world).truncate();
+        let normalized = local / (self.size.visible_size(size) / 2.0) * Vec2::new(1.0, -1.0);
+        Some((normalized + Vec2::ONE) / 2.0 * size + position)
+    }
+}
+
+/// How much of the world a [`Camera`] shows, in in-game pixels.
+///
+/// Except for the fixed sizes, the camera shows more of the world in one direction when the aspect
+/// ratio of the window doesn't match. To show exactly the same area on every window instead, use
+/// [`AutoMin`][Self::AutoMin] with a [letterboxed][Viewport::letterbox] viewport, and the rest of
+/// the window is filled with the [`ClearColor`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, u8)]
+pub enum CameraSize {
+    /// Show exactly the given height, and determine the width from the aspect ratio.
+    FixedHeight(f32),
+    /// Show exactly the given width, and determine the height from the aspect ratio.
+    FixedWidth(f32),
+    /// Show at least the given width and height, and more in one direction if the aspect ratio
+    /// doesn't match.
+    AutoMin {
+        /// The minimum width to show.
+        width: f32,
+        /// The minimum height to show.
+        height: f32,
+    },
+    /// Show at most the given width and height, and less in one direction if the aspect ratio
+    /// doesn't match.
+    AutoMax {
+        /// The maximum width to show.
+        width: f32,
+        /// The maximum height to show.
+        height: f32,
+    },
+}
+
+impl Default for CameraSize {
+    fn default() -> Self {
+        Self::FixedHeight(400.0)
+    }
+}
+
+impl CameraSize {
+    /// Get the width and height of the area of the world that is shown in a viewport of the given
+    /// size.
+    pub fn visible_size(&self, viewport_size: Vec2) -> Vec2 {
+        let aspect_ratio = viewport_size.x / viewport_size.y;
+        match *self {
+            CameraSize::FixedHeight(height) => Vec2::new(height * aspect_ratio, height),
+            CameraSize::FixedWidth(width) => Vec2::new(width, width / aspect_ratio),
+            CameraSize::AutoMin { width, height } => {
+                if aspect_ratio > width / height {
+                    Vec2::new(height * aspect_ratio, height)
+                } else {
+                    Vec2::new(width, width / aspect_ratio)
+                }
+            }
+            CameraSize::AutoMax { width, height } => {
+                if aspect_ratio > width / height {
+                    Vec2::new(width, width / aspect_ratio)
+                } else {
+                    Vec2::new(height * aspect_ratio, height)
+                }
+            }
+        }
+    }
+
+    /// Linearly interpolate between this size and another size.
+    ///
+    /// Sizes of different kinds can't be interpolated, so the size switches to `other` once `t`
+    /// reaches `1.0` instead.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        match (*self, *other) {
+            (CameraSize::FixedHeight(a), CameraSize::FixedHeight(b)) => {
+                CameraSize::FixedHeight(lerp(a, b))
+            }
+            (CameraSize::FixedWidth(a), CameraSize::FixedWidth(b)) => {
+                CameraSize::FixedWidth(lerp(a, b))
+            }
+            (
+                CameraSize::AutoMin { width, height },
+                CameraSize::AutoMin {
+                    width: other_width,
+                    height: other_height,
+                },
+            ) => CameraSize::AutoMin {
+                width: lerp(width, other_width),
+                height: lerp(height, other_height),
+            },
+            (
+                CameraSize::AutoMax { width, height },
+                CameraSize::AutoMax {
+                    width: other_width,
+                    height: other_height,
+                },
+            ) => CameraSize::AutoMax {
+                width: lerp(width, other_width),
+                height: lerp(height, other_height),
+            },
+            _ if t >= 1.0 => *other,
+            _ => *self,
+        }
+    }
+}
+
+/// System parameter for converting between screen and world positions with the cameras, such as
+/// to find where the cursor is in the world.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn aim_system(camera_helper: CameraHelper) {
+///     if let Some(target) = camera_helper.cursor_world_pos() {
+///         /* aim at the target... */
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct CameraHelper<'a> {
+    /// The [`Window`] resource.
+    pub window: Res<'a, Window>,
+    /// The [`ActiveCameras`] resource.
+    pub active_cameras: Res<'a, ActiveCameras>,
+    /// The [`Entities`] resource.
+    pub entities: Res<'a, Entities>,
+    /// The [`Camera`] components.
+    pub cameras: Comp<'a, Camera>,
+    /// The [`Transform`] components.
+    pub transforms: Comp<'a, Transform>,
+}
+
+impl<'a> CameraHelper<'a> {
+    /// Get the camera that renders at the given screen position, in physical pixels from the
+    /// top-left corner of the window, along with its transform.
+    ///
+    /// This is the active camera with the highest priority whose viewport contains the position,
+    /// so it's the camera that rendered what is visible at the position.
+    pub fn camera_at(&self, screen_position: Vec2) -> Option<(Entity, &Camera, &Transform)> {
+        self.entities
+            .iter_with((&self.cameras, &self.transforms))
+            .filter(|&(entity, (camera, _))| {
+                let Some((position, size)) = camera.viewport_rect(self.window.size) else {
+                    return false;
+                };
+                let (position, size) = (position.as_vec2(), size.as_vec2());
+                self.is_active(entity, camera)
+                    && screen_position.cmpge(position).all()
+                    && screen_position.cmplt(position + size).all()
+            })
+            // Cameras with the same priority are rendered in order of their entities, so the last
+            // one is rendered over the others.
+            .max_by_key(|(_, (camera, _))| camera.priority)
+            .map(|(entity, (camera, transform))| (entity, camera, transform))
+    }
+
+    /// Get the primary camera, along with its transform.
+    ///
+    /// This is the [primary camera][ActiveCameras::primary] of the [`ActiveCameras`], or the active
+    /// camera with the lowest priority, which is rendered first, if the list is empty.
+    pub fn primary_camera(&self) -> Option<(Entity, &Camera, &Transform)> {
+        let mut cameras = self.entities.iter_with((&self.cameras, &self.transforms));
+        let (entity, (camera, transform)) = match self.active_cameras.primary() {
+            Some(primary) => cameras.find(|&(entity, _)| entity == primary)?,
+            None => cameras
+                .filter(|(_, (camera, _))| camera.active)
+                .min_by_key(|(_, (camera, _))| camera.priority)?,
+        };
+        Some((entity, camera, transform))
+    }
+
+    /// Convert a screen position, in physical pixels from the top-left corner of the window, to a
+    /// position in the world, with the [camera at the position][Self::camera_at].
+    pub fn screen_to_world(&self, screen_position: Vec2) -> Option<Vec2> {
+        let (_, camera, transform) = self.camera_at(screen_position)?;
+        camera.screen_to_world(transform, self.window.size, screen_position)
+    }
+
+    /// Get the position of the cursor in the world, if it's over the window.
+    pub fn cursor_world_pos(&self) -> Option<Vec2> {
+        self.screen_to_world(self.window.cursor_position?)
+    }
+
+    /// Returns `true` if the camera is rendered, which is the case if it is active and in the
+    /// [`ActiveCameras`], if the list isn't empty.
+    fn is_active(&self, entity: Entity, camera: &Camera) -> bool {
+        camera.active
+            && (self.active_cameras.cameras().is_empty() || self.active_cameras.contains(entity))
+    }
+}
+
+/// Get the matrix that transforms from the local space of the transform to world space.
+fn transform_matrix(transform: &Transform) -> Mat4 {
+    Mat4::from_scale_rotation_translation(
+        transform.scale,
+        transform.rotation,
+        transform.translation,
+    )
+}
+
+/// A rectangle of the window that a [`Camera`] renders to, such as one half of the window for
+/// split-screen multiplayer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Viewport {
+    /// The position of the top-left corner of the viewport, in physical pixels from the top-left
+    /// corner of the window.
+    pub position: UVec2,
+    /// The size of the viewport in physical pixels.
+    pub size: UVec2,
+    /// The minimum depth to render, from `0.0` to `1.0`.
+    pub depth_min: f32,
+    /// The maximum depth to render, from `0.0` to `1.0`.
+    pub depth_max: f32,
+}
+
+impl Viewport {
+    /// Create a viewport with the given position and size in physical pixels, that renders the
+    /// full depth range.
+    pub fn new(position: UVec2, size: UVec2) -> Self {
+        Self {
+            position,
+            size,
+            depth_min: 0.0,
+            depth_max: 1.0,
+        }
+    }
+
+    /// Create the largest viewport with the given aspect ratio that fits in a window with the
+    /// given size in physical pixels, centered in the window.
+    ///
+    /// The rest of the window is left as bars on two sides of the viewport, which are filled with
+    /// the [`ClearColor`]. The viewport must be updated when the window is resized. Returns `None`
+    /// if the window has no area.
+    pub fn letterbox(window_size: UVec2, aspect_ratio: f32) -> Option<Self> {
+        if window_size.x == 0 || window_size.y == 0 {
+            return None;
         }
+        let window = window_size.as_vec2();
+        let size = if window.x / window.y > aspect_ratio {
+            Vec2::new(window.y * aspect_ratio, window.y)
+        } else {
+            Vec2::new(window.x, window.x / aspect_ratio)
+        };
+        let size = size.round().as_uvec2().clamp(UVec2::ONE, window_size);
+        Some(Self::new((window_size - size) / 2, size))
+    }
+
+    /// Clamp the viewport so that it fits in a window with the given size in physical pixels,
+    /// such as after the window was made smaller.
+    ///
+    /// The clamped viewport is always at least one pixel wide and high. Returns `None` if the
+    /// window has no area, such as when it's minimized.
+    pub fn clamp_to_window(&self, window_size: UVec2) -> Option<Self> {
+        if window_size.x == 0 || window_size.y == 0 {
+            return None;
+        }
+        let position = self.position.min(window_size - UVec2::ONE);
+        let size = self.size.min(window_size - position).max(UVec2::ONE);
+        Some(Self {
+            position,
+            size,
+            ..*self
+        })
+    }
+}
+
+/// Shakes the [`Camera`] of an entity, such as after an explosion or a hit.
+///
+/// The shake is driven by trauma, from `0.0` to `1.0`, which is added by gameplay code with
+/// [`add_trauma()`][Self::add_trauma] and decays over time. The [`camera_shake_system`] computes the
+/// offset of the camera from the trauma, which is applied to the camera's [`Transform`] when it is
+/// rendered, so the shake never moves the actual position of the camera.
+///
+/// The shake is sampled from smooth noise that only depends on the [`seed`][Self::seed] and the
+/// time that the camera has been shaking for, which is stored in the component, so the shake is the
+/// same when a frame is re-simulated after a rollback.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn hit_system(mut shakes: CompMut<CameraShake>) {
+///     /* something was hit... */
+///     for mut shake in shakes.iter_mut() {
+///         shake.add_trauma(0.5);
+///     }
+/// }
+/// # let mut stages = SystemStages::with_core_stages();
+/// stages
+///     .add_system_to_stage(CoreStage::Update, hit_system)
+///     .add_system_to_stage(CoreStage::PostUpdate, camera_shake_system);
+/// ```
+#[derive(Clone, Copy, Debug, TypeUlid)]
+#[ulid = "01GQSQ8E2Y7WKXJ4C1N6B3VZRT"]
+#[repr(C)]
+pub struct CameraShake {
+    /// The intensity of the shake, from `0.0` to `1.0`.
+    trauma: f32,
+    /// The amount of trauma that decays every second.
+    pub decay_rate: f32,
+    /// The maximum offset of the camera, in in-game pixels, at full trauma.
+    pub max_offset: Vec2,
+    /// The maximum rotation of the camera, in radians, at full trauma.
+    pub max_rotation: f32,
+    /// How fast the camera shakes, in noise samples per second.
+    pub frequency: f32,
+    /// The seed of the noise that the shake is sampled from.
+    pub seed: u32,
+    /// The time in seconds that the noise is sampled at.
+    time: f32,
+    /// The current offset of the camera.
+    offset: Vec2,
+    /// The current rotation of the camera.
+    rotation: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay_rate: 1.0,
+            max_offset: Vec2::splat(16.0),
+            max_rotation: 0.1,
+            frequency: 15.0,
+            seed: 0,
+            time: 0.0,
+            offset: Vec2::ZERO,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl CameraShake {
+    /// Create a [`CameraShake`] with the given maximum offset in in-game pixels, maximum rotation
+    /// in radians, and trauma decay rate per second.
+    pub fn new(max_offset: Vec2, max_rotation: f32, decay_rate: f32) -> Self {
+        Self {
+            max_offset,
+            max_rotation,
+            decay_rate,
+            ..default()
+        }
+    }
+
+    /// Add trauma to the shake, capping it at `1.0`.
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /// Get the current trauma, from `0.0` to `1.0`.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Get the current offset of the camera, in in-game pixels.
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+
+    /// Get the current rotation of the camera, in radians.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Apply the current offset and rotation to the transform of the camera.
+    pub fn apply(&self, transform: Transform) -> Transform {
+        Transform {
+            translation: transform.translation + self.offset.extend(0.0),
+            rotation: transform.rotation * Quat::from_rotation_z(self.rotation),
+            ..transform
+        }
+    }
+
+    /// Advance the shake by the given number of seconds, decaying the trauma and computing the
+    /// new offset and rotation.
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.trauma = (self.trauma - self.decay_rate * delta_seconds).max(0.0);
+        if self.trauma == 0.0 {
+            self.time = 0.0;
+            self.offset = Vec2::ZERO;
+            self.rotation = 0.0;
+            return;
+        }
+
+        self.time += delta_seconds;
+        // Squaring the trauma makes small amounts of trauma shake the camera much less than large
+        // amounts, which feels better than a linear shake.
+        let shake = self.trauma * self.trauma;
+        let time = self.time * self.frequency;
+        self.offset = Vec2::new(
+            noise(self.seed, time),
+            noise(self.seed.wrapping_add(1), time),
+        ) * self.max_offset
+            * shake;
+        self.rotation = noise(self.seed.wrapping_add(2), time) * self.max_rotation * shake;
+    }
+}
+
+/// System that updates the [`CameraShake`] components.
+///
+/// This isn't added to the [`SystemStages`] by default. Games that use camera shake should add
+/// it to [`CoreStage::PostUpdate`], so that the trauma added during the update is applied in the
+/// same frame.
+pub fn camera_shake_system(time: Res<Time>, mut shakes: CompMut<CameraShake>) {
+    let delta_seconds = time.delta_seconds();
+    for mut shake in shakes.iter_mut() {
+        shake.update(delta_seconds);
+    }
+}
+
+/// Sample smooth noise from `-1.0` to `1.0`, that only depends on the seed and the time.
+fn noise(seed: u32, time: f32) -> f32 {
+    let floor = time.floor();
+    let t = time - floor;
+    let a = noise_value(seed, floor as i32);
+    let b = noise_value(seed, (floor as i32).wrapping_add(1));
+    // Smoothstep between the values, so the noise doesn't have sharp corners.
+    a + (b - a) * t * t * (3.0 - 2.0 * t)
+}
+
+/// Get the random value from `-1.0` to `1.0` at an integer point of the noise.
+fn noise_value(seed: u32, point: i32) -> f32 {
+    let mut x = (point as u32).wrapping_mul(0x9e37_79b9) ^ seed.wrapping_mul(0x85eb_ca6b);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Makes a [`Camera`] follow a target entity.
+///
+/// The camera doesn't move while the target is inside the [`deadzone`][Self::deadzone] around the
+/// center of the camera. When the target leaves it, the camera moves to bring the target back
+/// to the edge of the deadzone, smoothly and at most at the [`max_speed`][Self::max_speed].
+///
+/// The camera is moved by the [`camera_follow_system`].
+#[derive(Clone, Copy, Debug, TypeUlid)]
+#[ulid = "01GQSW4H7M2B9KD5XR3CZ8NVJT"]
+#[repr(C)]
+pub struct CameraFollow {
+    /// The entity to follow, which must have a [`Transform`].
+    ///
+    /// The camera stops moving if the target is killed.
+    pub target: Entity,
+    /// How fast the camera catches up with the target.
+    ///
+    /// Every second, the distance that the camera still has to move shrinks by a factor of
+    /// `e^smoothing`. Use [`f32::INFINITY`] to follow the target without smoothing.
+    pub smoothing: f32,
+    /// The width and height of the area around the center of the camera that the target can move
+    /// in without the camera moving, in in-game pixels.
+    pub deadzone: Vec2,
+    /// The maximum speed of the camera, in in-game pixels per second.
+    pub max_speed: Option<f32>,
+}
+
+impl CameraFollow {
+    /// Create a [`CameraFollow`] that follows the target with the default smoothing, without a
+    /// deadzone or a maximum speed.
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            smoothing: 5.0,
+            deadzone: Vec2::ZERO,
+            max_speed: None,
+        }
+    }
+
+    /// Get the position of the camera after following the target for the given number of seconds.
+    pub fn follow(&self, camera: Vec2, target: Vec2, delta_seconds: f32) -> Vec2 {
+        if delta_seconds <= 0.0 {
+            return camera;
+        }
+
+        let half_deadzone = self.deadzone / 2.0;
+        let offset = target - camera;
+        let outside_deadzone = offset - offset.clamp(-half_deadzone, half_deadzone);
+
+        let mut movement = outside_deadzone * (1.0 - (-self.smoothing * delta_seconds).exp());
+        if let Some(max_speed) = self.max_speed {
+            movement = movement.clamp_length_max(max_speed * delta_seconds);
+        }
+        camera + movement
     }
 }
 
+/// System that moves the cameras with a [`CameraFollow`] component towards their targets.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], after the targets have moved. The smoothing depends on the delta
+/// time, so games that re-simulate frames after a rollback must advance the [`Time`] by a fixed
+/// step, such as with [`Time::advance_exact()`], for the camera to move the same way.
+pub fn camera_follow_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    follows: Comp<CameraFollow>,
+    mut transforms: CompMut<Transform>,
+) {
+    let delta_seconds = time.delta_seconds();
+    for (camera, follow) in entities.iter_with(&follows) {
+        if !entities.is_alive(follow.target) {
+            continue;
+        }
+        let Some(target) = transforms.get(follow.target) else {
+            continue;
+        };
+        let target = target.translation.truncate();
+        let Some(mut transform) = transforms.get_mut(camera) else {
+            continue;
+        };
+        let position = follow.follow(transform.translation.truncate(), target, delta_seconds);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+/// Keeps a [`Camera`] from showing anything outside of a rectangle of the world, such as the area
+/// of a tilemap.
+///
+/// The camera is moved so that the area that it shows stays inside the bounds. If the bounds are
+/// smaller than that area in a direction, the camera is centered on the bounds in that direction
+/// instead. The rotation of the camera isn't taken into account.
+///
+/// The bounds are applied by the [`camera_bounds_system`], and again by the renderer after the
+/// [`CameraShake`] is applied.
+#[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+#[ulid = "01GQSX9B4N7JRD2KW6TQ3HZM8C"]
+#[repr(C)]
+pub struct CameraBounds {
+    /// The bottom-left corner of the bounds, in in-game pixels.
+    pub min: Vec2,
+    /// The top-right corner of the bounds, in in-game pixels.
+    pub max: Vec2,
+}
+
+impl CameraBounds {
+    /// Clamp the position of a camera that shows an area with the given size, so that the area
+    /// stays inside the bounds.
+    pub fn clamp(&self, position: Vec2, visible_size: Vec2) -> Vec2 {
+        let half_size = visible_size / 2.0;
+        let clamp_axis = |position: f32, min: f32, max: f32, half_size: f32| {
+            if max - min <= half_size * 2.0 {
+                (min + max) / 2.0
+            } else {
+                position.clamp(min + half_size, max - half_size)
+            }
+        };
+        Vec2::new(
+            clamp_axis(position.x, self.min.x, self.max.x, half_size.x),
+            clamp_axis(position.y, self.min.y, self.max.y, half_size.y),
+        )
+    }
+
+    /// Clamp the transform of a camera in a window with the given size, taking the
+    /// [`size`][Camera::size] and the viewport of the camera, and the scale of the transform, into
+    /// account.
+    ///
+    /// The transform isn't changed if the window has no area.
+    pub fn clamp_transform(
+        &self,
+        camera: &Camera,
+        window_size: UVec2,
+        transform: Transform,
+    ) -> Transform {
+        let Some(visible_size) = camera.visible_size(window_size) else {
+            return transform;
+        };
+        let visible_size = visible_size * transform.scale.truncate().abs();
+        let position = self.clamp(transform.translation.truncate(), visible_size);
+        Transform {
+            translation: position.extend(transform.translation.z),
+            ..transform
+        }
+    }
+}
+
+/// System that moves the cameras with a [`CameraBounds`] component back inside of their bounds.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], after the [`camera_follow_system`], if they use it.
+pub fn camera_bounds_system(
+    window: Res<Window>,
+    entities: Res<Entities>,
+    cameras: Comp<Camera>,
+    bounds: Comp<CameraBounds>,
+    mut transforms: CompMut<Transform>,
+) {
+    for (entity, (camera, bounds)) in entities.iter_with((&cameras, &bounds)) {
+        let Some(mut transform) = transforms.get_mut(entity) else {
+            continue;
+        };
+        let clamped = bounds.clamp_transform(camera, window.size, *transform);
+        if clamped.translation != transform.translation {
+            transform.translation = clamped.translation;
+        }
+    }
+}
+
+/// Animates the [`size`][Camera::size] of a [`Camera`], such as to zoom in smoothly on a boss.
+///
+/// The tween is advanced by the [`camera_zoom_tween_system`], which removes it once it's
+/// finished. Inserting a new tween while another one is in progress starts the new tween from the
+/// size that the camera has been zoomed to, if its [`start`][Self::start] is `None`, so the camera
+/// doesn't jump.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn boss_intro(camera: Entity, mut tweens: CompMut<CameraZoomTween>) {
+///     // Zoom in over two seconds, hold for three, and zoom back out.
+///     tweens.insert(
+///         camera,
+///         CameraZoomTween {
+///             hold_and_reverse: Some(3.0),
+///             ..CameraZoomTween::new(CameraSize::FixedHeight(200.0), 2.0, Ease::QuadInOut)
+///         },
+///     );
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, TypeUlid)]
+#[ulid = "01GQT4M8V2CX7HJ5RD9NKB3WQF"]
+#[repr(C)]
+pub struct CameraZoomTween {
+    /// The size to start from, or `None` to start from the size of the camera when the tween
+    /// starts.
+    pub start: Option<CameraSize>,
+    /// The size to zoom to.
+    pub end: CameraSize,
+    /// The number of seconds that it takes to zoom from the start to the end.
+    pub duration: f32,
+    /// The easing function used to zoom.
+    pub ease: Ease,
+    /// The number of seconds to hold the end size for, before zooming back to the start, or `None`
+    /// to finish at the end size.
+    pub hold_and_reverse: Option<f32>,
+    /// The number of seconds since the tween started.
+    elapsed: f32,
+}
+
+impl CameraZoomTween {
+    /// Create a tween that zooms from the current size of the camera to the end size.
+    pub fn new(end: CameraSize, duration: f32, ease: Ease) -> Self {
+        Self {
+            start: None,
+            end,
+            duration,
+            ease,
+            hold_and_reverse: None,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Get the number of seconds since the tween started.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Get the number of seconds that the whole tween takes, including holding and reversing.
+    pub fn total_duration(&self) -> f32 {
+        match self.hold_and_reverse {
+            Some(hold) => self.duration * 2.0 + hold,
+            None => self.duration,
+        }
+    }
+
+    /// Returns `true` if the tween is finished.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.total_duration()
+    }
+
+    /// Get the linear progress from the start to the end size, between `0.0` and `1.0`, which
+    /// goes back to `0.0` when the tween is reversed.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return if self.hold_and_reverse.is_some() && self.is_finished() {
+                0.0
+            } else {
+                1.0
+            };
+        }
+        let progress = match self.hold_and_reverse {
+            Some(hold) if self.elapsed > self.duration + hold => {
+                1.0 - (self.elapsed - self.duration - hold) / self.duration
+            }
+            _ => self.elapsed / self.duration,
+        };
+        progress.clamp(0.0, 1.0)
+    }
+
+    /// Advance the tween by the given number of seconds, and get the new size of a camera that
+    /// currently has the given size.
+    pub fn update(&mut self, size: CameraSize, delta_seconds: f32) -> CameraSize {
+        let start = *self.start.get_or_insert(size);
+        self.elapsed += delta_seconds;
+        start.lerp(&self.end, self.ease.ease(self.progress()))
+    }
+}
+
+/// System that advances the [`CameraZoomTween`]s, updates the sizes of their cameras, and removes
+/// the tweens that are finished.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn camera_zoom_tween_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut tweens: CompMut<CameraZoomTween>,
+    mut cameras: CompMut<Camera>,
+) {
+    let delta_seconds = time.delta_seconds();
+    let mut finished = Vec::new();
+    for (entity, (mut tween, mut camera)) in entities.iter_with((&mut tweens, &mut cameras)) {
+        camera.size = tween.update(camera.size, delta_seconds);
+        if tween.is_finished() {
+            finished.push(entity);
+        }
+    }
+    for entity in finished {
+        tweens.remove(entity);
+    }
+}
+
+/// Resource that lists the cameras that are rendered, starting with the primary camera.
+///
+/// The primary camera is "the" camera, for systems that need a single camera, such as
+/// [`CameraHelper::primary_camera()`]. The other cameras are usually overlays, such as a camera
+/// for the HUD.
+///
+/// While the list is empty, the [`active`][Camera::active] flags of the cameras are left alone.
+/// Otherwise the [`active_cameras_system`] activates the cameras in the list and deactivates all
+/// of the others, so cutting to another camera is as simple as making it the primary camera.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// #[derive(Clone, Copy, TypeUlid)]
+/// #[ulid = "01GQT2K6D9R4WB8XN3JM7FCZVH"]
+/// struct CutsceneCamera;
+///
+/// /// Cut between the cutscene cameras every three seconds.
+/// fn cutscene_system(
+///     time: Res<Time>,
+///     mut timer: Local<f32>,
+///     entities: Res<Entities>,
+///     cutscene_cameras: Comp<CutsceneCamera>,
+///     mut active_cameras: ResMut<ActiveCameras>,
+/// ) {
+///     *timer += time.delta_seconds();
+///     if *timer < 3.0 {
+///         return;
+///     }
+///     *timer = 0.0;
+///
+///     let primary = active_cameras.primary();
+///     let next = entities
+///         .iter_with(&cutscene_cameras)
+///         .map(|(entity, _)| entity)
+///         .find(|&entity| Some(entity) != primary);
+///     if let Some(next) = next {
+///         active_cameras.set_primary(next);
+///     }
+/// }
+/// # let mut world = World::new();
+/// # world.run_system(cutscene_system).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, TypeUlid)]
+#[ulid = "01GQT1YH3C8M5QZ2KD7WRB9XNE"]
+pub struct ActiveCameras {
+    cameras: Vec<Entity>,
+}
+
+impl ActiveCameras {
+    /// Get the primary camera, if there are any active cameras.
+    pub fn primary(&self) -> Option<Entity> {
+        self.cameras.first().copied()
+    }
+
+    /// Get the active cameras, starting with the primary camera.
+    pub fn cameras(&self) -> &[Entity] {
+        &self.cameras
+    }
+
+    /// Returns `true` if the camera is in the list.
+    pub fn contains(&self, camera: Entity) -> bool {
+        self.cameras.contains(&camera)
+    }
+
+    /// Make the camera the primary camera, replacing the previous primary camera.
+    ///
+    /// The other cameras in the list stay active.
+    pub fn set_primary(&mut self, camera: Entity) {
+        self.cameras.retain(|&x| x != camera);
+        match self.cameras.first_mut() {
+            Some(primary) => *primary = camera,
+            None => self.cameras.push(camera),
+        }
+    }
+
+    /// Add a camera to the end of the list, if it isn't in it already.
+    pub fn push(&mut self, camera: Entity) {
+        if !self.contains(camera) {
+            self.cameras.push(camera);
+        }
+    }
+
+    /// Remove a camera from the list, returning `true` if it was in it.
+    pub fn remove(&mut self, camera: Entity) -> bool {
+        let len = self.cameras.len();
+        self.cameras.retain(|&x| x != camera);
+        self.cameras.len() != len
+    }
+
+    /// Remove all of the cameras from the list, leaving the [`active`][Camera::active] flags of the
+    /// cameras alone.
+    pub fn clear(&mut self) {
+        self.cameras.clear();
+    }
+}
+
+/// System that updates the [`active`][Camera::active] flags of the cameras from the
+/// [`ActiveCameras`] resource.
+///
+/// Cameras that have been killed, or that no longer have a [`Camera`] component, are removed from
+/// the list. If that leaves the list empty, another camera is made the primary camera, and a
+/// warning is logged.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn active_cameras_system(
+    entities: Res<Entities>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    mut cameras: CompMut<Camera>,
+) {
+    if active_cameras.cameras.is_empty() {
+        return;
+    }
+
+    let len = active_cameras.cameras.len();
+    active_cameras
+        .cameras
+        .retain(|&camera| entities.is_alive(camera) && cameras.contains(camera));
+    if active_cameras.cameras.is_empty() {
+        let Some((fallback, _)) = entities.iter_with(&cameras).next() else {
+            tracing::warn!("The active cameras were removed, and there are no cameras left");
+            return;
+        };
+        tracing::warn!(
+            ?fallback,
+            "The active cameras were removed, falling back to another camera"
+        );
+        active_cameras.cameras.push(fallback);
+    } else if active_cameras.cameras.len() != len {
+        tracing::warn!(
+            primary = ?active_cameras.primary(),
+            "Some of the active cameras were removed"
+        );
+    }
+
+    for (entity, mut camera) in entities.iter_with(&mut cameras) {
+        let active = active_cameras.contains(entity);
+        if camera.active != active {
+            camera.active = active;
+        }
+    }
+}
+
+/// How a [`Camera`] clears the window before rendering to it.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[repr(C, u8)]
+pub enum CameraClear {
+    /// Clear the window with the [`ClearColor`] resource if this is the first active camera that
+    /// is rendered, and don't clear it otherwise.
+    #[default]
+    Inherit,
+    /// Clear the window with the given color.
+    Color([f32; 4]),
+    /// Don't clear the window, and render over what the cameras before this one rendered.
+    None,
+}
+
 /// Resource for controlling the clear color.
+///
+/// This is the color that the window is cleared with by cameras that
+/// [inherit][CameraClear::Inherit] it.
 #[derive(Deref, DerefMut, Clone, Copy, TypeUlid, Default)]
 #[ulid = "01GP4XRQYRPQNX4J22E513975M"]
 pub struct ClearColor(pub [f32; 4]);
+
+#[cfg(test)]
+mod tests {
+    use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+    use crate::prelude::*;
+
+    #[track_caller]
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn screen_to_world() {
+        let camera = Camera::default();
+        let window_size = UVec2::new(800, 400);
+        let transform = Transform::from_translation(Vec3::new(100.0, 50.0, 0.0));
+
+        let world = |screen: Vec2| camera.screen_to_world(&transform, window_size, screen);
+        assert_near(
+            world(Vec2::new(400.0, 200.0)).unwrap(),
+            Vec2::new(100.0, 50.0),
+        );
+        assert_near(world(Vec2::ZERO).unwrap(), Vec2::new(-300.0, 250.0));
+        assert_near(
+            world(Vec2::new(800.0, 400.0)).unwrap(),
+            Vec2::new(500.0, -150.0),
+        );
+        assert_eq!(
+            camera.screen_to_world(&transform, UVec2::ZERO, Vec2::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn rotated_and_zoomed_camera() {
+        let camera = Camera::default();
+        let window_size = UVec2::new(800, 400);
+        let transform = Transform {
+            translation: Vec3::new(100.0, 50.0, 0.0),
+            rotation: Quat::from_rotation_z(FRAC_PI_2),
+            scale: Vec3::splat(2.0),
+        };
+
+        // The right edge of the screen is above the camera, twice as far as without the zoom.
+        let world = camera.screen_to_world(&transform, window_size, Vec2::new(800.0, 200.0));
+        assert_near(world.unwrap(), Vec2::new(100.0, 850.0));
+
+        let screen = camera.world_to_screen(&transform, window_size, Vec2::new(100.0, 850.0));
+        assert_near(screen.unwrap(), Vec2::new(800.0, 200.0));
+
+        let transform = Transform::from_scale(Vec3::ZERO);
+        assert_eq!(
+            camera.world_to_screen(&transform, window_size, Vec2::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn visible_rect() {
+        let camera = Camera::default();
+        let window_size = UVec2::new(800, 400);
+        let transform = Transform::from_translation(Vec3::new(100.0, 50.0, 0.0));
+        let rect = camera.visible_rect(&transform, window_size).unwrap();
+        assert_near(rect.min, Vec2::new(-300.0, -150.0));
+        assert_near(rect.max, Vec2::new(500.0, 250.0));
+
+        // The rectangle of a rotated camera contains all of its view.
+        let transform = Transform {
+            rotation: Quat::from_rotation_z(FRAC_PI_2),
+            scale: Vec3::splat(2.0),
+            ..transform
+        };
+        let rect = camera.visible_rect(&transform, window_size).unwrap();
+        assert_near(rect.min, Vec2::new(-300.0, -750.0));
+        assert_near(rect.max, Vec2::new(500.0, 850.0));
+
+        assert_eq!(camera.visible_rect(&transform, UVec2::ZERO), None);
+    }
+
+    #[test]
+    fn viewport() {
+        // The camera renders to the top-right quarter of the window.
+        let camera = Camera {
+            size: CameraSize::FixedHeight(300.0),
+            viewport: Some(Viewport::new(UVec2::new(400, 0), UVec2::new(400, 300))),
+            ..default()
+        };
+        let window_size = UVec2::new(800, 600);
+        let transform = Transform {
+            translation: Vec3::new(-50.0, 20.0, 0.0),
+            rotation: Quat::from_rotation_z(0.3),
+            ..default()
+        };
+        assert_near(
+            camera.visible_size(window_size).unwrap(),
+            Vec2::new(400.0, 300.0),
+        );
+
+        let world = camera.screen_to_world(&transform, window_size, Vec2::new(600.0, 150.0));
+        assert_near(world.unwrap(), Vec2::new(-50.0, 20.0));
+
+        for screen in [
+            Vec2::new(400.0, 0.0),
+            Vec2::new(750.0, 20.0),
+            Vec2::new(100.0, 500.0),
+        ] {
+            let world = camera.screen_to_world(&transform, window_size, screen);
+            let back = camera.world_to_screen(&transform, window_size, world.unwrap());
+            assert_near(back.unwrap(), screen);
+        }
+
+        // The viewport is clamped when the window is made smaller than it.
+        let window_size = UVec2::new(500, 200);
+        assert_eq!(
+            camera.viewport_rect(window_size),
+            Some((UVec2::new(400, 0), UVec2::new(100, 200)))
+        );
+        assert_near(
+            camera.visible_size(window_size).unwrap(),
+            Vec2::new(150.0, 300.0),
+        );
+    }
+
+    #[test]
+    fn camera_sizes() {
+        let viewport_size = Vec2::new(800.0, 300.0);
+        let visible = |size: CameraSize| size.visible_size(viewport_size);
+        assert_near(
+            visible(CameraSize::FixedHeight(150.0)),
+            Vec2::new(400.0, 150.0),
+        );
+        assert_near(
+            visible(CameraSize::FixedWidth(400.0)),
+            Vec2::new(400.0, 150.0),
+        );
+        assert_near(
+            visible(CameraSize::AutoMin {
+                width: 400.0,
+                height: 300.0,
+            }),
+            Vec2::new(800.0, 300.0),
+        );
+        assert_near(
+            visible(CameraSize::AutoMax {
+                width: 400.0,
+                height: 300.0,
+            }),
+            Vec2::new(400.0, 150.0),
+        );
+
+        assert_eq!(
+            Viewport::letterbox(UVec2::new(800, 300), 4.0 / 3.0),
+            Some(Viewport::new(UVec2::new(200, 0), UVec2::new(400, 300)))
+        );
+        assert_eq!(Viewport::letterbox(UVec2::new(0, 300), 4.0 / 3.0), None);
+    }
+
+    #[test]
+    fn camera_helper() {
+        let mut world = World::new();
+        world.resources.insert(Window {
+            size: UVec2::new(800, 400),
+            cursor_position: Some(Vec2::new(200.0, 100.0)),
+        });
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut cameras: CompMut<Camera>,
+                 mut transforms: CompMut<Transform>| {
+                    // The minimap in the top-left corner is rendered over the world camera.
+                    let minimap = entities.create();
+                    cameras.insert(
+                        minimap,
+                        Camera {
+                            size: CameraSize::FixedHeight(1000.0),
+                            priority: 1,
+                            clear: CameraClear::None,
+                            viewport: Some(Viewport::new(UVec2::ZERO, UVec2::new(400, 200))),
+                            ..default()
+                        },
+                    );
+                    transforms.insert(minimap, Transform::default());
+
+                    let world_camera = entities.create();
+                    cameras.insert(world_camera, Camera::default());
+                    transforms.insert(world_camera, Transform::default());
+                },
+            )
+            .unwrap();
+
+        let positions = |world: &mut World| {
+            world
+                .run_system(|camera_helper: CameraHelper| {
+                    (
+                        camera_helper.cursor_world_pos(),
+                        camera_helper.screen_to_world(Vec2::new(600.0, 300.0)),
+                    )
+                })
+                .unwrap()
+        };
+        let (cursor, outside_minimap) = positions(&mut world);
+        assert_near(cursor.unwrap(), Vec2::ZERO);
+        assert_near(outside_minimap.unwrap(), Vec2::new(200.0, -100.0));
+
+        // Inactive cameras are skipped.
+        world
+            .run_system(|mut cameras: CompMut<Camera>| {
+                for mut camera in cameras.iter_mut() {
+                    if camera.priority == 1 {
+                        camera.active = false;
+                    }
+                }
+            })
+            .unwrap();
+        let (cursor, _) = positions(&mut world);
+        assert_near(cursor.unwrap(), Vec2::new(-200.0, 100.0));
+    }
+
+    #[test]
+    fn zoom_tween() {
+        let mut tween = CameraZoomTween {
+            hold_and_reverse: Some(1.0),
+            ..CameraZoomTween::new(CameraSize::FixedHeight(200.0), 2.0, Ease::Linear)
+        };
+        let mut size = CameraSize::FixedHeight(400.0);
+        let mut sizes = Vec::new();
+        while !tween.is_finished() {
+            size = tween.update(size, 0.5);
+            sizes.push(size);
+        }
+        let heights = [
+            350.0, 300.0, 250.0, 200.0, 200.0, 200.0, 250.0, 300.0, 350.0, 400.0,
+        ];
+        assert_eq!(sizes, heights.map(CameraSize::FixedHeight));
+
+        // Sizes of different kinds switch at the end.
+        let mut tween = CameraZoomTween::new(CameraSize::FixedWidth(100.0), 1.0, Ease::QuadIn);
+        let size = CameraSize::FixedHeight(400.0);
+        assert_eq!(tween.update(size, 0.5), size);
+        assert_eq!(tween.update(size, 0.5), CameraSize::FixedWidth(100.0));
+    }
+
+    #[test]
+    fn zoom_tween_system() {
+        let mut world = World::new();
+        let camera = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut cameras: CompMut<Camera>,
+                 mut tweens: CompMut<CameraZoomTween>| {
+                    let camera = entities.create();
+                    cameras.insert(camera, Camera::default());
+                    tweens.insert(
+                        camera,
+                        CameraZoomTween::new(CameraSize::FixedHeight(200.0), 1.0, Ease::Linear),
+                    );
+                    camera
+                },
+            )
+            .unwrap();
+        let step = move |world: &mut World| {
+            world
+                .resources
+                .get::<Time>()
+                .borrow_mut()
+                .advance_exact(Duration::from_secs_f32(0.5));
+            world.run_system(camera_zoom_tween_system).unwrap();
+            world
+                .run_system(
+                    move |cameras: Comp<Camera>, tweens: Comp<CameraZoomTween>| {
+                        (cameras.get(camera).unwrap().size, tweens.contains(camera))
+                    },
+                )
+                .unwrap()
+        };
+        world.resources.init::<Time>();
+        assert_eq!(step(&mut world), (CameraSize::FixedHeight(300.0), true));
+
+        // Interrupting the tween starts the new one from the current size.
+        world
+            .run_system(move |mut tweens: CompMut<CameraZoomTween>| {
+                tweens.insert(
+                    camera,
+                    CameraZoomTween::new(CameraSize::FixedHeight(500.0), 1.0, Ease::Linear),
+                );
+            })
+            .unwrap();
+        assert_eq!(step(&mut world), (CameraSize::FixedHeight(400.0), true));
+        assert_eq!(step(&mut world), (CameraSize::FixedHeight(500.0), false));
+    }
+
+    #[test]
+    fn active_cameras() {
+        let mut world = World::new();
+        let [first, second, hud] = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut cameras: CompMut<Camera>,
+                 mut transforms: CompMut<Transform>| {
+                    [(); 3].map(|_| {
+                        let camera = entities.create();
+                        cameras.insert(camera, Camera::default());
+                        transforms.insert(camera, Transform::default());
+                        camera
+                    })
+                },
+            )
+            .unwrap();
+        let active = |world: &mut World| {
+            world.run_system(active_cameras_system).unwrap();
+            world
+                .run_system(move |camera_helper: CameraHelper| {
+                    let primary = camera_helper.primary_camera().map(|(entity, ..)| entity);
+                    let active = [first, second, hud]
+                        .map(|entity| camera_helper.cameras.get(entity).unwrap().active);
+                    (primary, active)
+                })
+                .unwrap()
+        };
+
+        // The cameras are left alone while the list is empty.
+        assert_eq!(active(&mut world), (Some(first), [true; 3]));
+
+        world
+            .resources
+            .get::<ActiveCameras>()
+            .borrow_mut()
+            .push(hud);
+        world
+            .resources
+            .get::<ActiveCameras>()
+            .borrow_mut()
+            .set_primary(second);
+        assert_eq!(
+            world.resources.get::<ActiveCameras>().borrow().cameras(),
+            &[second, hud]
+        );
+        assert_eq!(active(&mut world), (Some(second), [false, true, true]));
+
+        // Cut to the first camera.
+        world
+            .resources
+            .get::<ActiveCameras>()
+            .borrow_mut()
+            .set_primary(first);
+        assert_eq!(active(&mut world), (Some(first), [true, false, true]));
+
+        // Killing the active cameras falls back to the remaining camera.
+        {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            entities.kill(first);
+            entities.kill(hud);
+        }
+        world.run_system(active_cameras_system).unwrap();
+        assert_eq!(
+            world.resources.get::<ActiveCameras>().borrow().cameras(),
+            &[second]
+        );
+    }
+
+    #[test]
+    fn camera_follow() {
+        let follow = CameraFollow {
+            deadzone: Vec2::new(100.0, 50.0),
+            ..CameraFollow::new(Entities::default().create())
+        };
+        // The camera doesn't move while the target is in the deadzone.
+        assert_eq!(
+            follow.follow(Vec2::ZERO, Vec2::new(40.0, -20.0), 0.1),
+            Vec2::ZERO
+        );
+
+        // The camera moves towards the edge of the deadzone, with smoothing.
+        let moved = follow.follow(Vec2::ZERO, Vec2::new(150.0, 0.0), 0.1);
+        let expected = 100.0 * (1.0 - (-0.5f32).exp());
+        assert_near(moved, Vec2::new(expected, 0.0));
+
+        let instant = CameraFollow {
+            smoothing: f32::INFINITY,
+            ..follow
+        };
+        assert_near(
+            instant.follow(Vec2::ZERO, Vec2::new(150.0, 0.0), 0.1),
+            Vec2::new(100.0, 0.0),
+        );
+        assert_eq!(
+            instant.follow(Vec2::ZERO, Vec2::new(150.0, 0.0), 0.0),
+            Vec2::ZERO
+        );
+
+        let slow = CameraFollow {
+            max_speed: Some(20.0),
+            ..instant
+        };
+        assert_near(
+            slow.follow(Vec2::ZERO, Vec2::new(150.0, 0.0), 0.1),
+            Vec2::new(2.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn follow_system() {
+        let mut world = World::new();
+        let mut time = Time::default();
+        time.advance_exact(std::time::Duration::from_millis(100));
+        world.resources.insert(time);
+
+        let (camera, player) = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut follows: CompMut<CameraFollow>,
+                 mut transforms: CompMut<Transform>| {
+                    let player = entities.create();
+                    transforms.insert(
+                        player,
+                        Transform::from_translation(Vec3::new(50.0, 0.0, 0.0)),
+                    );
+                    let camera = entities.create();
+                    transforms.insert(
+                        camera,
+                        Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
+                    );
+                    follows.insert(
+                        camera,
+                        CameraFollow {
+                            smoothing: f32::INFINITY,
+                            ..CameraFollow::new(player)
+                        },
+                    );
+                    (camera, player)
+                },
+            )
+            .unwrap();
+
+        let position = |world: &World| {
+            world
+                .components
+                .get::<Transform>()
+                .borrow()
+                .get(camera)
+                .unwrap()
+                .translation
+        };
+        world.run_system(camera_follow_system).unwrap();
+        assert_eq!(position(&world), Vec3::new(50.0, 0.0, 10.0));
+
+        // Nothing happens when the target is dead.
+        world
+            .run_system(
+                |mut entities: ResMut<Entities>, mut transforms: CompMut<Transform>| {
+                    transforms.get_mut(player).unwrap().translation.x = 80.0;
+                    entities.kill(player);
+                },
+            )
+            .unwrap();
+        world.run_system(camera_follow_system).unwrap();
+        assert_eq!(position(&world), Vec3::new(50.0, 0.0, 10.0));
+    }
+
+    #[test]
+    fn camera_bounds() {
+        let bounds = CameraBounds {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(1000.0, 300.0),
+        };
+        let visible_size = Vec2::new(400.0, 200.0);
+
+        assert_eq!(
+            bounds.clamp(Vec2::new(500.0, 150.0), visible_size),
+            Vec2::new(500.0, 150.0)
+        );
+        assert_eq!(
+            bounds.clamp(Vec2::new(-100.0, 500.0), visible_size),
+            Vec2::new(200.0, 200.0)
+        );
+        assert_eq!(
+            bounds.clamp(Vec2::new(2000.0, -500.0), visible_size),
+            Vec2::new(800.0, 100.0)
+        );
+
+        // A map that is narrower than the screen is centered horizontally.
+        let narrow = CameraBounds {
+            min: Vec2::new(100.0, 0.0),
+            max: Vec2::new(300.0, 1000.0),
+        };
+        assert_eq!(
+            narrow.clamp(Vec2::new(0.0, 0.0), visible_size),
+            Vec2::new(200.0, 100.0)
+        );
+        assert_eq!(
+            narrow.clamp(Vec2::new(900.0, 500.0), visible_size),
+            Vec2::new(200.0, 500.0)
+        );
+    }
+
+    #[test]
+    fn bounds_system() {
+        let mut world = World::new();
+        world.resources.insert(Window {
+            size: UVec2::new(800, 400),
+            cursor_position: None,
+        });
+        let camera = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut cameras: CompMut<Camera>,
+                 mut bounds: CompMut<CameraBounds>,
+                 mut transforms: CompMut<Transform>| {
+                    let camera = entities.create();
+                    // The camera shows 800x400 in-game pixels, zoomed out to 1600x800.
+                    cameras.insert(camera, Camera::default());
+                    transforms.insert(
+                        camera,
+                        Transform {
+                            translation: Vec3::new(0.0, 0.0, 10.0),
+                            scale: Vec3::splat(2.0),
+                            ..default()
+                        },
+                    );
+                    // The map is narrower than the screen.
+                    bounds.insert(
+                        camera,
+                        CameraBounds {
+                            min: Vec2::new(0.0, 0.0),
+                            max: Vec2::new(1000.0, 2000.0),
+                        },
+                    );
+                    camera
+                },
+            )
+            .unwrap();
+
+        world.run_system(camera_bounds_system).unwrap();
+        let transforms = world.components.get::<Transform>();
+        assert_eq!(
+            transforms.borrow().get(camera).unwrap().translation,
+            Vec3::new(500.0, 400.0, 10.0)
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! Chunked tile map rendering components, for very large maps.
+
+use std::{collections::HashMap, time::Duration};
+
+use crate::{prelude::*, tilemap::animated_idx};
+
+/// The width and height of the chunks of a [`ChunkedTileLayer`], in tiles.
+pub const TILE_CHUNK_SIZE: u32 = 32;
+
+/// A tilemap layer component that stores its tiles in chunks of [`TILE_CHUNK_SIZE`] by
+/// [`TILE_CHUNK_SIZE`] tiles, for very large maps that are mostly empty.
+///
+/// A [`TileLayer`] allocates a slot for every tile of the layer, even when it's empty. The chunks
+/// of a chunked layer are only allocated once a tile is set in them, and are dropped again when
+/// all of their tiles are cleared. Renderers only draw the chunks that can be seen by a camera.
+///
+/// It has the same [`get()`][Self::get] and [`set()`][Self::set] methods as a [`TileLayer`], and
+/// the same [metadata][TileLayer::meta] and [tints][TileLayer::tints] for each tile slot, and can
+/// be converted to and from one with [`From`]. A chunk is only dropped when none of its slots have
+/// a tile, metadata, or a tint.
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GQXA5M3K8RTD2WB7HN4CJ9ZF"]
+pub struct ChunkedTileLayer {
+    /// The size of the layer in tiles.
+    pub grid_size: UVec2,
+    /// The size of each tile in the layer.
+    pub tile_size: Vec2,
+    /// The texture atlas to use for the layer
+    pub atlas: Handle<Atlas>,
+    /// The animations of the tiles of the layer, like the [`animations`][TileLayer::animations]
+    /// of a [`TileLayer`].
+    pub animations: HashMap<usize, TileAnimation>,
+    /// How long the tile animations of the layer have been playing for.
+    pub animation_time: Duration,
+    /// The chunks that have tiles in them, by the position of the chunk in chunks.
+    chunks: HashMap<UVec2, TileChunk>,
+}
+
+/// A chunk of a [`ChunkedTileLayer`].
+#[derive(Clone, Debug)]
+struct TileChunk {
+    /// The tile slots of the chunk, in rows from the top-left of the chunk.
+    tiles: Box<[Option<Entity>]>,
+    /// The metadata of the tile slots of the chunk.
+    meta: Box<[u16]>,
+    /// The tints of the tile slots of the chunk, or empty if none of them have been tinted.
+    tints: Vec<Color>,
+    /// The number of slots that have a tile, metadata, or a tint.
+    len: usize,
+}
+
+impl TileChunk {
+    fn new() -> Self {
+        let slot_count = (TILE_CHUNK_SIZE * TILE_CHUNK_SIZE) as usize;
+        Self {
+            tiles: vec![None; slot_count].into_boxed_slice(),
+            meta: vec![0; slot_count].into_boxed_slice(),
+            tints: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns `true` if the slot with the given index has a tile, metadata, or a tint.
+    fn is_used(&self, idx: usize) -> bool {
+        self.tiles[idx].is_some() || self.meta[idx] != 0 || self.tint(idx) != Color::WHITE
+    }
+
+    /// Get the tint of the slot with the given index.
+    fn tint(&self, idx: usize) -> Color {
+        self.tints.get(idx).copied().unwrap_or(Color::WHITE)
+    }
+
+    /// Set the tint of the slot with the given index, storing the tints of the chunk once a slot
+    /// is tinted.
+    fn set_tint(&mut self, idx: usize, tint: Color) {
+        if self.tints.is_empty() {
+            if tint == Color::WHITE {
+                return;
+            }
+            self.tints = vec![Color::WHITE; self.tiles.len()];
+        }
+        self.tints[idx] = tint;
+    }
+}
+
+impl ChunkedTileLayer {
+    /// Create a new, empty chunked tile layer.
+    pub fn new(grid_size: UVec2, tile_size: Vec2, atlas: Handle<Atlas>) -> Self {
+        Self {
+            grid_size,
+            tile_size,
+            atlas,
+            animations: HashMap::new(),
+            animation_time: Duration::ZERO,
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Get the position of the chunk that a tile is in, and the index of the tile in the chunk.
+    #[inline]
+    fn chunk_idx(pos: UVec2) -> (UVec2, usize) {
+        let chunk = pos / TILE_CHUNK_SIZE;
+        let local = pos % TILE_CHUNK_SIZE;
+        (chunk, (local.y * TILE_CHUNK_SIZE + local.x) as usize)
+    }
+
+    /// Get the position of the slot with the given index in a chunk, relative to the chunk.
+    #[inline]
+    fn slot_pos(idx: usize) -> UVec2 {
+        let idx = idx as u32;
+        UVec2::new(idx % TILE_CHUNK_SIZE, idx / TILE_CHUNK_SIZE)
+    }
+
+    /// Get's the tile at the given position in the layer, indexed with the top-left of the layer
+    /// being (0, 0).
+    pub fn get(&self, pos: UVec2) -> Option<Entity> {
+        if !pos.cmplt(self.grid_size).all() {
+            return None;
+        }
+        let (chunk, idx) = Self::chunk_idx(pos);
+        self.chunks.get(&chunk)?.tiles[idx]
+    }
+
+    /// Set the tile at the given position, to a certain entity.
+    ///
+    /// The chunk of the tile is allocated if it doesn't exist yet, and dropped if the tile was the
+    /// last one in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of range, like [`TileLayer::set()`].
+    pub fn set(&mut self, pos: UVec2, entity: Option<Entity>) {
+        self.edit_slot(pos, entity.is_some(), |chunk, idx| {
+            chunk.tiles[idx] = entity
+        });
+    }
+
+    /// Get the metadata of the tile slot at the given position, or `0` if it is out of range, like
+    /// [`TileLayer::get_meta()`].
+    pub fn get_meta(&self, pos: UVec2) -> u16 {
+        if !pos.cmplt(self.grid_size).all() {
+            return 0;
+        }
+        let (chunk, idx) = Self::chunk_idx(pos);
+        self.chunks.get(&chunk).map_or(0, |chunk| chunk.meta[idx])
+    }
+
+    /// Set the metadata of the tile slot at the given position, like [`TileLayer::set_meta()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of range.
+    pub fn set_meta(&mut self, pos: UVec2, meta: u16) {
+        self.edit_slot(pos, meta != 0, |chunk, idx| chunk.meta[idx] = meta);
+    }
+
+    /// Get the tint of the tile slot at the given position, which is [white][Color::WHITE] if the
+    /// slot hasn't been tinted or is out of range, like [`TileLayer::get_tint()`].
+    pub fn get_tint(&self, pos: UVec2) -> Color {
+        if !pos.cmplt(self.grid_size).all() {
+            return Color::WHITE;
+        }
+        let (chunk, idx) = Self::chunk_idx(pos);
+        self.chunks
+            .get(&chunk)
+            .map_or(Color::WHITE, |chunk| chunk.tint(idx))
+    }
+
+    /// Set the tint of the tile slot at the given position, like [`TileLayer::set_tint()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of range.
+    pub fn set_tint(&mut self, pos: UVec2, tint: Color) {
+        self.edit_slot(pos, tint != Color::WHITE, |chunk, idx| {
+            chunk.set_tint(idx, tint)
+        });
+    }
+
+    /// Edit the slot at the given position, allocating its chunk if the slot will be used, and
+    /// dropping the chunk if none of its slots are used anymore.
+    fn edit_slot(&mut self, pos: UVec2, used: bool, edit: impl FnOnce(&mut TileChunk, usize)) {
+        if !pos.cmplt(self.grid_size).all() {
+            panic!(
+                "Tile pos out of range of tile size: pos {:?} size {:?}",
+                pos, self.grid_size
+            );
+        }
+        let (chunk_pos, idx) = Self::chunk_idx(pos);
+        // Clearing a slot in a chunk that doesn't exist doesn't change anything.
+        if !used && !self.chunks.contains_key(&chunk_pos) {
+            return;
+        }
+        let chunk = self.chunks.entry(chunk_pos).or_insert_with(TileChunk::new);
+        let was_used = chunk.is_used(idx);
+        edit(chunk, idx);
+        match (was_used, chunk.is_used(idx)) {
+            (false, true) => chunk.len += 1,
+            (true, false) => chunk.len -= 1,
+            _ => (),
+        }
+        if chunk.len == 0 {
+            self.chunks.remove(&chunk_pos);
+        }
+    }
+
+    /// Resize the layer to the given number of tiles, like [`TileLayer::resize()`].
+    pub fn resize(&mut self, grid_size: UVec2) {
+        let old_size = self.grid_size;
+        self.grid_size = grid_size;
+        if grid_size.cmpge(old_size).all() {
+            return;
+        }
+        // Clear the slots that are outside of the new size, so that they are empty if the layer
+        // grows again.
+        let outside = self
+            .chunks
+            .iter()
+            .filter(|(chunk_pos, _)| !((**chunk_pos + 1) * TILE_CHUNK_SIZE).cmple(grid_size).all())
+            .flat_map(|(&chunk_pos, chunk)| {
+                let origin = chunk_pos * TILE_CHUNK_SIZE;
+                (0..chunk.tiles.len())
+                    .filter(move |&idx| chunk.is_used(idx))
+                    .map(move |idx| origin + Self::slot_pos(idx))
+            })
+            .filter(|pos| !pos.cmplt(grid_size).all())
+            .collect::<Vec<_>>();
+        for pos in outside {
+            let (chunk_pos, idx) = Self::chunk_idx(pos);
+            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+                chunk.tiles[idx] = None;
+                chunk.meta[idx] = 0;
+                chunk.set_tint(idx, Color::WHITE);
+                chunk.len -= 1;
+                if chunk.len == 0 {
+                    self.chunks.remove(&chunk_pos);
+                }
+            }
+        }
+    }
+
+    /// Get the number of chunks that have been allocated, because they have tiles in them.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Get the index in the atlas of the image that a tile with the given index is drawn with,
+    /// like [`TileLayer::animated_idx()`].
+    #[inline]
+    pub fn animated_idx(&self, idx: usize) -> usize {
+        animated_idx(&self.animations, self.animation_time, idx)
+    }
+
+    /// Iterate over the positions and the entities of all of the tiles of the layer.
+    ///
+    /// The tiles are in no particular order.
+    pub fn tiles(&self) -> impl Iterator<Item = (UVec2, Entity)> + '_ {
+        self.tiles_in_rect(UVec2::ZERO, self.grid_size)
+    }
+
+    /// Iterate over the positions and the entities of the tiles in the rectangle from `min` to
+    /// `max`, including `min` but not `max`.
+    ///
+    /// Only the chunks that overlap the rectangle are visited. The tiles are in no particular
+    /// order.
+    pub fn tiles_in_rect(
+        &self,
+        min: UVec2,
+        max: UVec2,
+    ) -> impl Iterator<Item = (UVec2, Entity)> + '_ {
+        self.slots_in_rect(min, max)
+            .filter_map(|(pos, chunk, idx)| Some((pos, chunk.tiles[idx]?)))
+    }
+
+    /// Iterate over the positions and the metadata of the tile slots in the rectangle from `min`
+    /// to `max`, including `min` but not `max`, that have metadata, like
+    /// [`TileLayer::iter_meta_in_rect()`].
+    ///
+    /// Only the chunks that overlap the rectangle are visited. The slots are in no particular
+    /// order.
+    pub fn iter_meta_in_rect(
+        &self,
+        min: UVec2,
+        max: UVec2,
+    ) -> impl Iterator<Item = (UVec2, u16)> + '_ {
+        self.slots_in_rect(min, max)
+            .map(|(pos, chunk, idx)| (pos, chunk.meta[idx]))
+            .filter(|(_, meta)| *meta != 0)
+    }
+
+    /// Iterate over the positions, the entities, and the tints of the tiles in the rectangle from
+    /// `min` to `max`, including `min` but not `max`, such as to render them.
+    ///
+    /// Like [`tiles_in_rect()`][Self::tiles_in_rect], only the chunks that overlap the rectangle
+    /// are visited.
+    pub fn tinted_tiles_in_rect(
+        &self,
+        min: UVec2,
+        max: UVec2,
+    ) -> impl Iterator<Item = (UVec2, Entity, Color)> + '_ {
+        self.slots_in_rect(min, max)
+            .filter_map(|(pos, chunk, idx)| Some((pos, chunk.tiles[idx]?, chunk.tint(idx))))
+    }
+
+    /// Iterate over the positions, the chunks, and the indices in the chunks of the used slots in
+    /// the rectangle from `min` to `max`.
+    fn slots_in_rect(
+        &self,
+        min: UVec2,
+        max: UVec2,
+    ) -> impl Iterator<Item = (UVec2, &TileChunk, usize)> + '_ {
+        let max = max.min(self.grid_size);
+        let (chunk_min, chunk_max) = if min.cmplt(max).all() {
+            (
+                min / TILE_CHUNK_SIZE,
+                (max + UVec2::splat(TILE_CHUNK_SIZE - 1)) / TILE_CHUNK_SIZE,
+            )
+        } else {
+            (UVec2::ZERO, UVec2::ZERO)
+        };
+
+        // Look the chunks up from the rectangle when it's smaller than the number of chunks, such
+        // as for the view of a camera, and filter the chunks otherwise.
+        let chunk_area = chunk_max - chunk_min;
+        let chunks: Vec<(UVec2, &TileChunk)> =
+            if (chunk_area.x as usize) * (chunk_area.y as usize) < self.chunks.len() {
+                (chunk_min.y..chunk_max.y)
+                    .flat_map(|y| (chunk_min.x..chunk_max.x).map(move |x| UVec2::new(x, y)))
+                    .filter_map(|pos| Some((pos, self.chunks.get(&pos)?)))
+                    .collect()
+            } else {
+                self.chunks
+                    .iter()
+                    .filter(|(pos, _)| pos.cmpge(chunk_min).all() && pos.cmplt(chunk_max).all())
+                    .map(|(pos, chunk)| (*pos, chunk))
+                    .collect()
+            };
+
+        chunks.into_iter().flat_map(move |(chunk_pos, chunk)| {
+            let origin = chunk_pos * TILE_CHUNK_SIZE;
+            (0..chunk.tiles.len()).filter_map(move |idx| {
+                let pos = origin + Self::slot_pos(idx);
+                (chunk.is_used(idx) && pos.cmpge(min).all() && pos.cmplt(max).all())
+                    .then_some((pos, chunk, idx))
+            })
+        })
+    }
+
+    /// Get the rectangle of tiles, from `min` to `max`, that overlaps the given rectangle of the
+    /// world, for the layer with the given transform.
+    ///
+    /// The tile at (x, y) is drawn from `(x, y) * tile_size` to `(x + 1, y + 1) * tile_size`,
+    /// relative to the position of the layer. The rotation of the layer isn't taken into account.
+    pub fn tile_rect(&self, transform: &Transform, world_rect: Rect) -> (UVec2, UVec2) {
+        let scale = transform.scale.truncate() * self.tile_size;
+        let origin = transform.translation.truncate();
+        let local = Rect::from_corners(
+            (world_rect.min - origin) / scale,
+            (world_rect.max - origin) / scale,
+        );
+        let grid_size = self.grid_size.as_vec2();
+        (
+            local.min.floor().clamp(Vec2::ZERO, grid_size).as_uvec2(),
+            local.max.ceil().clamp(Vec2::ZERO, grid_size).as_uvec2(),
+        )
+    }
+}
+
+impl From<TileLayer> for ChunkedTileLayer {
+    fn from(layer: TileLayer) -> Self {
+        let mut chunked = Self::new(layer.grid_size, layer.tile_size, layer.atlas);
+        chunked.animations = layer.animations;
+        chunked.animation_time = layer.animation_time;
+        let width = layer.grid_size.x.max(1);
+        let tints = layer.tints;
+        let slots = layer.tiles.into_iter().zip(layer.meta).enumerate();
+        for (idx, (entity, meta)) in slots {
+            let idx = idx as u32;
+            let pos = UVec2::new(idx % width, idx / width);
+            if entity.is_some() {
+                chunked.set(pos, entity);
+            }
+            if meta != 0 {
+                chunked.set_meta(pos, meta);
+            }
+            if let Some(&tint) = tints.get(idx as usize) {
+                chunked.set_tint(pos, tint);
+            }
+        }
+        chunked
+    }
+}
+
+impl From<ChunkedTileLayer> for TileLayer {
+    fn from(chunked: ChunkedTileLayer) -> Self {
+        let mut layer = TileLayer::new(chunked.grid_size, chunked.tile_size, chunked.atlas.clone());
+        layer.set_many(chunked.tiles().map(|(pos, entity)| (pos, Some(entity))));
+        for (pos, meta) in chunked.iter_meta_in_rect(UVec2::ZERO, chunked.grid_size) {
+            layer.set_meta(pos, meta);
+        }
+        for (&chunk_pos, chunk) in &chunked.chunks {
+            let origin = chunk_pos * TILE_CHUNK_SIZE;
+            for (idx, &tint) in chunk.tints.iter().enumerate() {
+                if tint != Color::WHITE {
+                    layer.set_tint(origin + ChunkedTileLayer::slot_pos(idx), tint);
+                }
+            }
+        }
+        layer.animations = chunked.animations;
+        layer.animation_time = chunked.animation_time;
+        layer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn chunks_are_allocated_lazily() {
+        let mut entities = Entities::default();
+        let (grass, dirt) = (entities.create(), entities.create());
+        let mut layer = ChunkedTileLayer::new(UVec2::splat(4096), Vec2::splat(16.0), default());
+        assert_eq!(layer.chunk_count(), 0);
+
+        layer.set(UVec2::new(0, 0), Some(grass));
+        layer.set(UVec2::new(31, 31), Some(dirt));
+        layer.set(UVec2::new(4000, 100), Some(grass));
+        assert_eq!(layer.chunk_count(), 2);
+        assert_eq!(layer.get(UVec2::new(31, 31)), Some(dirt));
+        assert_eq!(layer.get(UVec2::new(4000, 100)), Some(grass));
+        assert_eq!(layer.get(UVec2::new(4000, 101)), None);
+        assert_eq!(layer.get(UVec2::new(5000, 0)), None);
+
+        // Clearing the last tile of a chunk drops it.
+        layer.set(UVec2::new(4000, 100), None);
+        layer.set(UVec2::new(2000, 2000), None);
+        assert_eq!(layer.chunk_count(), 1);
+        layer.set(UVec2::new(0, 0), Some(dirt));
+        layer.set(UVec2::new(0, 0), None);
+        assert_eq!(layer.chunk_count(), 1);
+        layer.set(UVec2::new(31, 31), None);
+        assert_eq!(layer.chunk_count(), 0);
+    }
+
+    #[test]
+    fn chunk_meta() {
+        const SOLID: u16 = 1;
+        let mut entities = Entities::default();
+        let mut layer = ChunkedTileLayer::new(UVec2::splat(256), Vec2::splat(16.0), default());
+
+        // Metadata keeps a chunk alive without a tile.
+        layer.set_meta(UVec2::new(40, 40), SOLID);
+        assert_eq!(layer.chunk_count(), 1);
+        let tile = entities.create();
+        layer.set(UVec2::new(40, 40), Some(tile));
+        layer.set(UVec2::new(40, 40), None);
+        assert_eq!(layer.chunk_count(), 1);
+        assert_eq!(layer.get_meta(UVec2::new(40, 40)), SOLID);
+        assert_eq!(layer.get_meta(UVec2::new(41, 40)), 0);
+        assert_eq!(
+            layer
+                .iter_meta_in_rect(UVec2::ZERO, UVec2::splat(64))
+                .collect::<Vec<_>>(),
+            [(UVec2::new(40, 40), SOLID)]
+        );
+        layer.set_meta(UVec2::new(40, 40), 0);
+        assert_eq!(layer.chunk_count(), 0);
+
+        // Shrinking the layer clears the slots outside of it.
+        layer.set_meta(UVec2::new(10, 10), SOLID);
+        layer.set(UVec2::new(100, 10), Some(tile));
+        layer.resize(UVec2::splat(50));
+        assert_eq!(layer.chunk_count(), 1);
+        layer.resize(UVec2::splat(256));
+        assert_eq!(layer.get(UVec2::new(100, 10)), None);
+        assert_eq!(layer.get_meta(UVec2::new(10, 10)), SOLID);
+    }
+
+    #[test]
+    fn chunk_tints() {
+        let mut entities = Entities::default();
+        let tile = entities.create();
+        let mut layer = ChunkedTileLayer::new(UVec2::splat(256), Vec2::splat(16.0), default());
+
+        // White tints don't allocate a chunk, but other tints keep a chunk alive without a tile.
+        layer.set_tint(UVec2::new(40, 40), Color::WHITE);
+        assert_eq!(layer.chunk_count(), 0);
+        layer.set_tint(UVec2::new(40, 40), Color::RED);
+        assert_eq!(layer.chunk_count(), 1);
+        assert_eq!(layer.get_tint(UVec2::new(40, 40)), Color::RED);
+        assert_eq!(layer.get_tint(UVec2::new(41, 40)), Color::WHITE);
+        assert_eq!(layer.get_tint(UVec2::new(300, 40)), Color::WHITE);
+
+        layer.set(UVec2::new(40, 40), Some(tile));
+        layer.set(UVec2::new(41, 40), Some(tile));
+        assert_eq!(
+            layer
+                .tinted_tiles_in_rect(UVec2::new(40, 40), UVec2::new(41, 41))
+                .collect::<Vec<_>>(),
+            [(UVec2::new(40, 40), tile, Color::RED)]
+        );
+        layer.set(UVec2::new(40, 40), None);
+        layer.set(UVec2::new(41, 40), None);
+        assert_eq!(layer.chunk_count(), 1);
+        layer.set_tint(UVec2::new(40, 40), Color::WHITE);
+        assert_eq!(layer.chunk_count(), 0);
+
+        // Shrinking the layer clears the tints outside of it.
+        layer.set_tint(UVec2::new(100, 10), Color::BLUE);
+        layer.resize(UVec2::splat(50));
+        assert_eq!(layer.chunk_count(), 0);
+        layer.resize(UVec2::splat(256));
+        assert_eq!(layer.get_tint(UVec2::new(100, 10)), Color::WHITE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_out_of_range() {
+        let mut layer = ChunkedTileLayer::new(UVec2::splat(40), Vec2::splat(16.0), default());
+        layer.set(UVec2::new(40, 0), None);
+    }
+
+    #[test]
+    fn tiles_in_rect() {
+        let mut entities = Entities::default();
+        let mut layer = ChunkedTileLayer::new(UVec2::splat(256), Vec2::splat(16.0), default());
+        for x in [10, 40, 70, 200] {
+            layer.set(UVec2::new(x, x), Some(entities.create()));
+        }
+
+        let mut tiles = layer
+            .tiles_in_rect(UVec2::new(20, 20), UVec2::new(71, 71))
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        tiles.sort_by_key(|pos| pos.x);
+        assert_eq!(tiles, [UVec2::splat(40), UVec2::splat(70)]);
+        assert_eq!(layer.tiles().count(), 4);
+        assert_eq!(
+            layer
+                .tiles_in_rect(UVec2::new(300, 0), UVec2::new(400, 400))
+                .count(),
+            0
+        );
+
+        // A camera showing 100x50 pixels around (200, 100), over a layer at (-16, 0) with 16x16
+        // tiles.
+        let transform = Transform::from_translation(Vec3::new(-16.0, 0.0, 0.0));
+        let view = Rect::new(150.0, 75.0, 250.0, 125.0);
+        assert_eq!(
+            layer.tile_rect(&transform, view),
+            (UVec2::new(10, 4), UVec2::new(17, 8))
+        );
+        let view = Rect::new(-100.0, -100.0, 0.0, 0.0);
+        assert_eq!(
+            layer.tile_rect(&transform, view),
+            (UVec2::ZERO, UVec2::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn convert_dense_layers() {
+        let mut entities = Entities::default();
+        let (grass, water) = (entities.create(), entities.create());
+        let mut dense = TileLayer::new(UVec2::new(100, 40), Vec2::splat(16.0), default());
+        dense.set(UVec2::new(3, 2), Some(grass));
+        dense.set(UVec2::new(99, 39), Some(water));
+        dense.animations.insert(1, TileAnimation::new(1..3, 2.0));
+        dense.set_meta(UVec2::new(50, 20), 3);
+        dense.set_tint(UVec2::new(3, 2), Color::RED);
+        dense.set_tint(UVec2::new(70, 10), Color::BLUE);
+
+        let chunked = ChunkedTileLayer::from(dense.clone());
+        assert_eq!(chunked.chunk_count(), 4);
+        assert_eq!(chunked.get_tint(UVec2::new(3, 2)), Color::RED);
+        assert_eq!(chunked.get_tint(UVec2::new(70, 10)), Color::BLUE);
+        assert_eq!(chunked.get_tint(UVec2::new(99, 39)), Color::WHITE);
+        assert_eq!(chunked.get_meta(UVec2::new(50, 20)), 3);
+        assert_eq!(chunked.get(UVec2::new(3, 2)), Some(grass));
+        assert_eq!(chunked.get(UVec2::new(99, 39)), Some(water));
+        assert_eq!(chunked.animations, dense.animations);
+
+        let converted = TileLayer::from(chunked);
+        assert_eq!(converted.tiles, dense.tiles);
+        assert_eq!(converted.meta, dense.meta);
+        assert_eq!(converted.tints, dense.tints);
+        assert_eq!(converted.grid_size, dense.grid_size);
+    }
+}
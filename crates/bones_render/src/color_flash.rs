@@ -0,0 +1,347 @@
+//! Color flash component.
+
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// Animates the [`color`][Sprite::color] of a [`Sprite`] or [`AtlasSprite`], such as to flash a
+/// character white when it's hit, or to fade out a corpse.
+///
+/// The color goes from [`start`][Self::start] to [`end`][Self::end] over the
+/// [`duration`][Self::duration], and then the flash ends as its [`on_complete`][Self::on_complete]
+/// says. More flashes can be [queued][Self::queue] to play after it.
+///
+/// The flash remembers the color that the sprite had before it started, so that it can be
+/// restored. Inserting a new flash while another one is playing would make it remember the
+/// flashing color instead, so [`interrupt()`][Self::interrupt] the flash that is playing:
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn hit_flash(player: Entity, mut flashes: CompMut<ColorFlash>) {
+///     // Turn white for a tenth of a second.
+///     let flash = ColorFlash::flash(Color::WHITE, 0.1);
+///     if let Some(mut current) = flashes.get_mut(player) {
+///         current.interrupt(flash);
+///     } else {
+///         flashes.insert(player, flash);
+///     }
+/// }
+///
+/// fn fade_out(corpse: Entity, mut flashes: CompMut<ColorFlash>) {
+///     flashes.insert(
+///         corpse,
+///         ColorFlash {
+///             on_complete: ColorFlashEnd::Hold,
+///             ..ColorFlash::tint(Color::WHITE.with_a(0.0), 1.0, Ease::QuadIn)
+///         },
+///     );
+/// }
+/// ```
+///
+/// The flash only depends on the delta time, so it plays the same way every time when the
+/// [`Time`] is advanced by a fixed step.
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GQW9D4S7K2MB8XF5RZN3HCJT"]
+pub struct ColorFlash {
+    /// The color that the flash starts at, or `None` to start at the color of the sprite.
+    pub start: Option<Color>,
+    /// The color that the flash ends at.
+    pub end: Color,
+    /// The number of seconds that the flash takes.
+    pub duration: f32,
+    /// The easing function used to go from the start to the end color.
+    pub ease: Ease,
+    /// What happens to the sprite once the flash is finished.
+    pub on_complete: ColorFlashEnd,
+    /// The color that the sprite had before the first flash started.
+    original: Option<Color>,
+    /// The number of seconds since the flash started.
+    elapsed: f32,
+    /// The flashes that are played after this one.
+    queued: VecDeque<ColorFlash>,
+}
+
+/// What happens to a sprite once its [`ColorFlash`] is finished.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ColorFlashEnd {
+    /// Restore the color that the sprite had before the flash, and remove the [`ColorFlash`].
+    #[default]
+    Restore,
+    /// Remove the [`ColorFlash`], and leave the sprite at the end color.
+    Remove,
+    /// Keep the [`ColorFlash`], and hold the sprite at the end color until it is removed.
+    Hold,
+}
+
+impl ColorFlash {
+    /// Create a flash that changes the color of the sprite to `color` for `duration` seconds, and
+    /// then restores it.
+    pub fn flash(color: Color, duration: f32) -> Self {
+        Self {
+            start: Some(color),
+            ..Self::tint(color, duration, Ease::Linear)
+        }
+    }
+
+    /// Create a flash that tints the sprite from its current color to `color` over `duration`
+    /// seconds, and then restores its color.
+    pub fn tint(color: Color, duration: f32, ease: Ease) -> Self {
+        Self {
+            start: None,
+            end: color,
+            duration,
+            ease,
+            on_complete: ColorFlashEnd::Restore,
+            original: None,
+            elapsed: 0.0,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Queue another flash to play after this one, and get the flash back.
+    pub fn then(mut self, flash: ColorFlash) -> Self {
+        self.queue(flash);
+        self
+    }
+
+    /// Queue another flash to play after this one and the flashes that are already queued.
+    ///
+    /// The queued flash plays right after the previous one ends as its
+    /// [`on_complete`][Self::on_complete] says, except that the [`ColorFlash`] isn't removed
+    /// until the last flash is finished.
+    pub fn queue(&mut self, flash: ColorFlash) {
+        self.queued.push_back(flash);
+    }
+
+    /// Replace this flash and the queued flashes with another flash, which starts right away.
+    ///
+    /// The color that the sprite had before this flash started is still the one that is restored.
+    pub fn interrupt(&mut self, flash: ColorFlash) {
+        let original = self.original;
+        *self = flash;
+        self.original = original;
+    }
+
+    /// Get the color that the sprite had before the first flash started, if it has started.
+    pub fn original(&self) -> Option<Color> {
+        self.original
+    }
+
+    /// Get the number of seconds since the flash started.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Returns `true` if the flash and all of the queued flashes are finished.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration && self.queued.is_empty()
+    }
+
+    /// Advance the flash by the given number of seconds, and get the new color of a sprite that
+    /// currently has the given color.
+    ///
+    /// When the flash ends, the rest of the time is used to advance the next queued flash.
+    pub fn update(&mut self, color: Color, delta_seconds: f32) -> Color {
+        let original = *self.original.get_or_insert(color);
+        let mut color = color;
+        let mut delta_seconds = delta_seconds;
+        loop {
+            let start = *self.start.get_or_insert(color);
+            self.elapsed += delta_seconds;
+            let progress = if self.duration > 0.0 {
+                self.elapsed / self.duration
+            } else {
+                1.0
+            };
+            color = start.lerp(&self.end, self.ease.ease(progress));
+            if self.elapsed < self.duration {
+                return color;
+            }
+
+            if self.on_complete == ColorFlashEnd::Restore {
+                color = original;
+            }
+            let Some(mut next) = self.queued.pop_front() else {
+                return color;
+            };
+            delta_seconds = self.elapsed - self.duration;
+            next.original = Some(original);
+            next.queued = std::mem::take(&mut self.queued);
+            *self = next;
+        }
+    }
+}
+
+/// System that advances the [`ColorFlash`]es, updates the colors of their sprites, and removes the
+/// flashes that are finished.
+///
+/// The flashes of entities that have neither a [`Sprite`] nor an [`AtlasSprite`], such as when the
+/// sprite has been removed in the middle of the flash, are left alone.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn color_flash_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut flashes: CompMut<ColorFlash>,
+    mut sprites: CompMut<Sprite>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+) {
+    let delta_seconds = time.delta_seconds();
+    let mut finished = Vec::new();
+    for (entity, mut flash) in entities.iter_with(&mut flashes) {
+        if let Some(mut sprite) = sprites.get_mut(entity) {
+            sprite.color = flash.update(sprite.color, delta_seconds);
+        } else if let Some(mut atlas_sprite) = atlas_sprites.get_mut(entity) {
+            atlas_sprite.color = flash.update(atlas_sprite.color, delta_seconds);
+        } else {
+            continue;
+        }
+        if flash.is_finished() && flash.on_complete != ColorFlashEnd::Hold {
+            finished.push(entity);
+        }
+    }
+    for entity in finished {
+        flashes.remove(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+
+    #[test]
+    fn flash_restores_color() {
+        let mut flash = ColorFlash::flash(Color::WHITE, 0.5);
+        assert_eq!(flash.update(RED, 0.25), Color::WHITE);
+        assert_eq!(flash.original(), Some(RED));
+        assert!(!flash.is_finished());
+        assert_eq!(flash.update(Color::WHITE, 0.25), RED);
+        assert!(flash.is_finished());
+    }
+
+    #[test]
+    fn tint_eases_to_end_color() {
+        let mut fade = ColorFlash {
+            on_complete: ColorFlashEnd::Hold,
+            ..ColorFlash::tint(Color::BLACK, 1.0, Ease::Linear)
+        };
+        let color = fade.update(Color::WHITE, 0.25);
+        assert_eq!(color, Color::rgb(0.75, 0.75, 0.75));
+        let color = fade.update(color, 1.0);
+        assert_eq!(color, Color::BLACK);
+        assert!(fade.is_finished());
+        // The end color is held.
+        assert_eq!(fade.update(color, 1.0), Color::BLACK);
+    }
+
+    #[test]
+    fn queued_flashes_restore_original_color() {
+        let mut flash = ColorFlash::flash(Color::WHITE, 0.25)
+            .then(ColorFlash::flash(Color::BLACK, 0.25))
+            .then(ColorFlash::flash(Color::WHITE, 0.25));
+
+        let mut color = RED;
+        let mut colors = Vec::new();
+        for _ in 0..3 {
+            color = flash.update(color, 0.125);
+            colors.push(color);
+            color = flash.update(color, 0.125);
+        }
+        assert_eq!(colors, [Color::WHITE, Color::BLACK, Color::WHITE]);
+        assert_eq!(color, RED);
+        assert!(flash.is_finished());
+
+        // The time left over from a flash is used by the next one.
+        let mut flash = ColorFlash::flash(Color::WHITE, 0.25).then(ColorFlash {
+            on_complete: ColorFlashEnd::Hold,
+            ..ColorFlash::tint(Color::BLACK, 0.5, Ease::Linear)
+        });
+        flash.update(RED, 0.5);
+        assert_eq!(flash.elapsed(), 0.25);
+
+        // Interrupting a flash keeps the original color.
+        let mut flash = ColorFlash::flash(Color::WHITE, 0.25);
+        let color = flash.update(RED, 0.125);
+        flash.interrupt(ColorFlash::flash(Color::BLACK, 0.25));
+        let color = flash.update(color, 0.125);
+        assert_eq!(color, Color::BLACK);
+        assert_eq!(flash.update(color, 0.125), RED);
+    }
+
+    #[test]
+    fn flash_sprites() {
+        let mut world = World::new();
+        world.resources.init::<Time>();
+        let (player, corpse) = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            (entities.create(), entities.create())
+        };
+        world
+            .run_system(
+                move |mut sprites: CompMut<Sprite>,
+                      mut atlas_sprites: CompMut<AtlasSprite>,
+                      mut flashes: CompMut<ColorFlash>| {
+                    sprites.insert(
+                        player,
+                        Sprite {
+                            color: RED,
+                            ..default()
+                        },
+                    );
+                    flashes.insert(player, ColorFlash::flash(Color::WHITE, 0.5));
+                    atlas_sprites.insert(corpse, AtlasSprite::default());
+                    flashes.insert(
+                        corpse,
+                        ColorFlash {
+                            on_complete: ColorFlashEnd::Remove,
+                            ..ColorFlash::tint(Color::NONE, 0.5, Ease::Linear)
+                        },
+                    );
+                },
+            )
+            .unwrap();
+
+        let step = |world: &mut World| {
+            world
+                .resources
+                .get::<Time>()
+                .borrow_mut()
+                .advance_exact(Duration::from_secs_f32(0.25));
+            world.run_system(color_flash_system).unwrap();
+        };
+        step(&mut world);
+        world
+            .run_system(
+                move |sprites: Comp<Sprite>, atlas_sprites: Comp<AtlasSprite>| {
+                    assert_eq!(sprites.get(player).unwrap().color, Color::WHITE);
+                    assert_eq!(
+                        atlas_sprites.get(corpse).unwrap().color,
+                        Color::rgba(0.5, 0.5, 0.5, 0.5)
+                    );
+                },
+            )
+            .unwrap();
+
+        // Removing the sprite in the middle of the flash doesn't stop the other flashes.
+        world
+            .components
+            .get::<AtlasSprite>()
+            .borrow_mut()
+            .remove(corpse);
+        step(&mut world);
+        world
+            .run_system(move |sprites: Comp<Sprite>, flashes: Comp<ColorFlash>| {
+                assert_eq!(sprites.get(player).unwrap().color, RED);
+                assert!(flashes.get(player).is_none());
+                assert!(flashes.get(corpse).is_some());
+            })
+            .unwrap();
+    }
+}
@@ -1,4 +1,8 @@
-//! Useful data types such as [`Key`].
+//! Useful data types such as [`Key`], [`Color`], [`Rect`], and [`Ease`].
+
+use std::f32::consts::PI;
+
+use glam::Vec2;
 
 /// A small ascii byte array stored on the stack and used similarly to a string to represent things
 /// like animation keys, etc, without requring a heap allocation.
@@ -67,6 +71,230 @@ impl<const N: usize> Key<N> {
     }
 }
 
+/// An RGBA color, with sRGB components between `0.0` and `1.0`.
+///
+/// When it is deserialized, the color may be a hex string, such as `"#ff0000"` or `"#ff000080"`,
+/// or an array of three or four components, such as `[1.0, 0.0, 0.0, 0.5]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Color {
+    /// The red component.
+    pub r: f32,
+    /// The green component.
+    pub g: f32,
+    /// The blue component.
+    pub b: f32,
+    /// The alpha component, where `0.0` is fully transparent and `1.0` is opaque.
+    pub a: f32,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl Color {
+    /// Opaque white.
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    /// Opaque black.
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    /// Opaque red.
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    /// Opaque green.
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    /// Opaque blue.
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    /// Fully transparent.
+    pub const NONE: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+
+    /// Create a color from its components.
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create an opaque color from its components.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    /// Create a color from a hex string with six or eight digits, with or without a leading `#`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a valid hex color.
+    pub fn hex(hex: &str) -> Result<Self, ColorError> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.is_ascii() || !(hex.len() == 6 || hex.len() == 8) {
+            return Err(ColorError::InvalidHex);
+        }
+        let mut components = [255; 4];
+        for (i, component) in components.iter_mut().take(hex.len() / 2).enumerate() {
+            *component = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| ColorError::InvalidHex)?;
+        }
+        let [r, g, b, a] = components.map(|x| x as f32 / 255.0);
+        Ok(Self::rgba(r, g, b, a))
+    }
+
+    /// Get a copy of the color with a different alpha.
+    pub const fn with_a(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Linearly interpolate between this color and another color, component by component.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Self::rgba(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+            lerp(self.a, other.a),
+        )
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+/// An error that may be caused when creating a [`Color`].
+#[derive(Copy, Clone, Debug)]
+pub enum ColorError {
+    /// The hex string isn't a valid color.
+    InvalidHex,
+}
+impl std::fmt::Display for ColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorError::InvalidHex => write!(f, "Invalid hex color."),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+/// An axis-aligned rectangle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[repr(C)]
+pub struct Rect {
+    /// The corner of the rectangle with the smallest coordinates.
+    pub min: Vec2,
+    /// The corner of the rectangle with the largest coordinates.
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// Create a rectangle from the coordinates of two opposite corners.
+    pub fn new(x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        Self::from_corners(Vec2::new(x0, y0), Vec2::new(x1, y1))
+    }
+
+    /// Create a rectangle from two opposite corners.
+    pub fn from_corners(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Get the width and height of the rectangle.
+    pub fn size(&self) -> Vec2 {
+        (self.max - self.min).max(Vec2::ZERO)
+    }
+
+    /// Returns `true` if the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.max.cmple(self.min).any()
+    }
+
+    /// Get the part of the rectangle that overlaps another rectangle.
+    ///
+    /// If the rectangles don't overlap, the result is [empty][Self::is_empty].
+    pub fn intersect(&self, other: &Self) -> Self {
+        let min = self.min.max(other.min);
+        Self {
+            min,
+            max: self.max.min(other.max).max(min),
+        }
+    }
+
+    /// Returns `true` if the point is inside of the rectangle.
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+}
+
+/// An easing function, for animating values smoothly instead of at a constant speed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Ease {
+    /// Move at a constant speed.
+    #[default]
+    Linear,
+    /// Start slowly, and speed up quadratically.
+    QuadIn,
+    /// Start fast, and slow down quadratically.
+    QuadOut,
+    /// Speed up quadratically, then slow down.
+    QuadInOut,
+    /// Start slowly, and speed up cubically.
+    CubicIn,
+    /// Start fast, and slow down cubically.
+    CubicOut,
+    /// Speed up cubically, then slow down.
+    CubicInOut,
+    /// Start slowly, and speed up along a sine curve.
+    SineIn,
+    /// Start fast, and slow down along a sine curve.
+    SineOut,
+    /// Speed up, then slow down along a sine curve.
+    SineInOut,
+}
+
+impl Ease {
+    /// Get the eased progress for the linear progress `t`, which is clamped between `0.0` and
+    /// `1.0`.
+    ///
+    /// The eased progress is `0.0` at the start and `1.0` at the end, for every easing function.
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Ease::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(2) / 2.0
+                }
+            }
+            Ease::CubicIn => t.powi(3),
+            Ease::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Ease::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t.powi(3)
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+            Ease::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Ease::SineOut => (t * PI / 2.0).sin(),
+            Ease::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+        }
+    }
+}
+
 #[cfg(feature = "serde")]
 mod serde_impl {
     use super::*;
@@ -91,6 +319,52 @@ mod serde_impl {
         }
     }
 
+    impl<'de> Deserialize<'de> for Color {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(ColorVisitor)
+        }
+    }
+
+    struct ColorVisitor;
+    impl<'de> Visitor<'de> for ColorVisitor {
+        type Value = Color;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(
+                formatter,
+                "A hex color string, or an array of three or four color components."
+            )
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Color::hex(v).map_err(|e| E::custom(e.to_string()))
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut components = [1.0; 4];
+            for (i, component) in components.iter_mut().enumerate() {
+                match seq.next_element()? {
+                    Some(value) => *component = value,
+                    None if i == 3 => break,
+                    None => return Err(serde::de::Error::invalid_length(i, &self)),
+                }
+            }
+            if seq.next_element::<f32>()?.is_some() {
+                return Err(serde::de::Error::invalid_length(5, &self));
+            }
+            Ok(components.into())
+        }
+    }
+
     struct KeyVisitor<const N: usize>;
     impl<'de, const N: usize> Visitor<'de> for KeyVisitor<N> {
         type Value = Key<N>;
@@ -107,3 +381,73 @@ mod serde_impl {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn hex_color() {
+        assert_eq!(Color::hex("#ff0000").unwrap(), Color::RED);
+        assert_eq!(Color::hex("00ff00").unwrap(), Color::GREEN);
+        assert_eq!(Color::hex("#0000ff00").unwrap(), Color::BLUE.with_a(0.0));
+        assert!(Color::hex("#ff00").is_err());
+        assert!(Color::hex("#gg0000").is_err());
+        assert!(Color::hex("#ff00é").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_color() {
+        let colors: Vec<Color> =
+            serde_yaml::from_str("['#ff0000', [0.0, 1.0, 0.0], [0.0, 0.0, 1.0, 0.5]]").unwrap();
+        assert_eq!(colors, [Color::RED, Color::GREEN, Color::BLUE.with_a(0.5)]);
+        assert!(serde_yaml::from_str::<Color>("[1.0, 0.0]").is_err());
+        assert!(serde_yaml::from_str::<Color>("[1.0, 0.0, 0.0, 1.0, 1.0]").is_err());
+    }
+
+    #[test]
+    fn rect() {
+        let rect = Rect::new(10.0, 20.0, 0.0, 0.0);
+        assert_eq!(rect.min, Vec2::ZERO);
+        assert_eq!(rect.size(), Vec2::new(10.0, 20.0));
+        assert!(rect.contains(Vec2::new(10.0, 5.0)));
+        assert!(!rect.contains(Vec2::new(10.5, 5.0)));
+
+        let overlap = rect.intersect(&Rect::new(5.0, 15.0, 50.0, 50.0));
+        assert_eq!(overlap, Rect::new(5.0, 15.0, 10.0, 20.0));
+        let outside = rect.intersect(&Rect::new(20.0, 20.0, 30.0, 30.0));
+        assert!(outside.is_empty());
+        assert_eq!(outside.size(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn ease() {
+        let eases = [
+            Ease::Linear,
+            Ease::QuadIn,
+            Ease::QuadOut,
+            Ease::QuadInOut,
+            Ease::CubicIn,
+            Ease::CubicOut,
+            Ease::CubicInOut,
+            Ease::SineIn,
+            Ease::SineOut,
+            Ease::SineInOut,
+        ];
+        for ease in eases {
+            assert!(ease.ease(0.0).abs() < 1e-6, "{ease:?}");
+            assert!((ease.ease(1.0) - 1.0).abs() < 1e-6, "{ease:?}");
+            assert_eq!(ease.ease(-1.0), ease.ease(0.0), "{ease:?}");
+            assert_eq!(ease.ease(2.0), ease.ease(1.0), "{ease:?}");
+            // Every easing function moves forward.
+            let samples = (0..=10)
+                .map(|i| ease.ease(i as f32 / 10.0))
+                .collect::<Vec<_>>();
+            assert!(samples.windows(2).all(|x| x[0] <= x[1]), "{ease:?}");
+        }
+        assert!(Ease::QuadIn.ease(0.5) < 0.5);
+        assert!(Ease::QuadOut.ease(0.5) > 0.5);
+        assert!((Ease::CubicInOut.ease(0.5) - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,570 @@
+//! Hexagonal tile grids.
+//!
+//! The math is done with axial coordinates, where a hex is an [`IVec2`] of its `q` and `r`
+//! coordinates, along two of the three axes of the grid. The [`TileLayer`] helpers convert the
+//! positions of the tiles of a [hexagonal][TileProjection::Hexagonal] layer to and from axial
+//! coordinates, as its [`HexGrid`] says.
+
+use crate::prelude::*;
+
+/// The layout of a [`Hexagonal`][TileProjection::Hexagonal] tile layer.
+///
+/// The [`tile_size`][TileLayer::tile_size] of the layer is the size of the rectangle around each
+/// hex, so a regular pointy-top hex with sides of length `s` is `sqrt(3) * s` wide and `2 * s`
+/// tall. The hexes of other sizes are stretched to fit their rectangle, and fit together all the
+/// same.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HexGrid {
+    /// Which way the hexes are pointed.
+    pub orientation: HexOrientation,
+    /// How the positions of the tiles of the layer map to hexes.
+    pub coords: HexCoords,
+}
+
+/// Which way the hexes of a [`HexGrid`] are pointed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HexOrientation {
+    /// The hexes have a corner at the top, and are in rows that go right.
+    ///
+    /// The `q` axis goes right, and the `r` axis goes up and to the right.
+    #[default]
+    PointyTop,
+    /// The hexes have a side at the top, and are in columns that go up.
+    ///
+    /// The `q` axis goes right and up, and the `r` axis goes up.
+    FlatTop,
+}
+
+/// How the positions of the tiles of a [`HexGrid`] map to hexes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HexCoords {
+    /// The tiles are in a rectangle of rows of hexes, for pointy-top hexes, or of columns, for
+    /// flat-top hexes, and every other row or column is shifted by half a hex to fit between its
+    /// neighbors.
+    ///
+    /// The rows are shifted to the right, and the columns are shifted up.
+    Offset(TileStagger),
+    /// The positions of the tiles are the axial coordinates of their hexes, so the tiles are in a
+    /// rhombus.
+    Axial,
+}
+
+impl Default for HexCoords {
+    fn default() -> Self {
+        HexCoords::Offset(default())
+    }
+}
+
+/// The offsets from a hex to its six neighbors, counter-clockwise from the one along `+q`.
+pub const HEX_DIRECTIONS: [IVec2; 6] = [
+    IVec2::new(1, 0),
+    IVec2::new(1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+];
+
+/// Get the six neighbors of a hex, in the order of the [`HEX_DIRECTIONS`].
+pub fn hex_neighbors(hex: IVec2) -> [IVec2; 6] {
+    HEX_DIRECTIONS.map(|direction| hex + direction)
+}
+
+/// Get the number of steps between two hexes.
+pub fn hex_distance(a: IVec2, b: IVec2) -> u32 {
+    let d = a - b;
+    (d.x.unsigned_abs() + d.y.unsigned_abs() + (d.x + d.y).unsigned_abs()) / 2
+}
+
+/// Get the hexes that are `radius` steps away from `center`, going around the ring
+/// counter-clockwise.
+///
+/// The ring of a radius of `0` is only the `center`, and the other rings have `6 * radius` hexes.
+pub fn hex_ring(center: IVec2, radius: u32) -> Vec<IVec2> {
+    if radius == 0 {
+        return vec![center];
+    }
+    let mut ring = Vec::with_capacity(6 * radius as usize);
+    let mut hex = center + HEX_DIRECTIONS[4] * radius as i32;
+    for direction in HEX_DIRECTIONS {
+        for _ in 0..radius {
+            ring.push(hex);
+            hex += direction;
+        }
+    }
+    ring
+}
+
+/// Get the hexes on the line from `a` to `b`, including both of them, with each hex a step away
+/// from the one before it.
+///
+/// When the line goes along the edge between two hexes, it always takes the hexes on the same side
+/// of the edge.
+pub fn hex_line(a: IVec2, b: IVec2) -> Vec<IVec2> {
+    let steps = hex_distance(a, b);
+    // Nudge the line off of the edges of the hexes, so that the hexes on it are never ties.
+    let start = a.as_vec2() + Vec2::new(1e-4, 2e-4);
+    let end = b.as_vec2() + Vec2::new(1e-4, 2e-4);
+    (0..=steps)
+        .map(|step| {
+            if steps == 0 {
+                return a;
+            }
+            hex_round(start.lerp(end, step as f32 / steps as f32))
+        })
+        .collect()
+}
+
+/// Get the hex that has the given fractional axial coordinates.
+pub fn hex_round(hex: Vec2) -> IVec2 {
+    // Round the three cube coordinates, and fix up the one that was rounded the most, so that
+    // they still add up to zero.
+    let s = -hex.x - hex.y;
+    let (mut q, mut r, s_rounded) = (hex.x.round(), hex.y.round(), s.round());
+    let (dq, dr, ds) = ((q - hex.x).abs(), (r - hex.y).abs(), (s_rounded - s).abs());
+    if dq > dr && dq > ds {
+        q = -r - s_rounded;
+    } else if dr > ds {
+        r = -q - s_rounded;
+    }
+    IVec2::new(q as i32, r as i32)
+}
+
+/// Get how far the axial coordinate along the rows or columns of an offset grid is from the
+/// position in them, for the row or column that is `across` them.
+fn offset_shift(stagger: TileStagger, across: i32) -> i32 {
+    match stagger {
+        TileStagger::Odd => (across - (across & 1)) / 2,
+        TileStagger::Even => (across + (across & 1)) / 2,
+    }
+}
+
+impl HexGrid {
+    /// Get the hex of the tile at `pos`.
+    pub fn to_axial(self, pos: UVec2) -> IVec2 {
+        let pos = pos.as_ivec2();
+        match self.coords {
+            HexCoords::Axial => pos,
+            HexCoords::Offset(stagger) => {
+                // The hexes are offset along the rows or the columns.
+                let (along, across) = match self.orientation {
+                    HexOrientation::PointyTop => (pos.x, pos.y),
+                    HexOrientation::FlatTop => (pos.y, pos.x),
+                };
+                let along = along - offset_shift(stagger, across);
+                match self.orientation {
+                    HexOrientation::PointyTop => IVec2::new(along, across),
+                    HexOrientation::FlatTop => IVec2::new(across, along),
+                }
+            }
+        }
+    }
+
+    /// Get the position of the tile of a hex, or `None` if the position would be negative.
+    pub fn from_axial(self, hex: IVec2) -> Option<UVec2> {
+        let pos = match self.coords {
+            HexCoords::Axial => hex,
+            HexCoords::Offset(stagger) => {
+                let (along, across) = match self.orientation {
+                    HexOrientation::PointyTop => (hex.x, hex.y),
+                    HexOrientation::FlatTop => (hex.y, hex.x),
+                };
+                let along = along + offset_shift(stagger, across);
+                match self.orientation {
+                    HexOrientation::PointyTop => IVec2::new(along, across),
+                    HexOrientation::FlatTop => IVec2::new(across, along),
+                }
+            }
+        };
+        pos.cmpge(IVec2::ZERO).all().then(|| pos.as_uvec2())
+    }
+
+    /// Get the center of the hex with the given tile size, relative to the bottom-left corner of
+    /// the layer.
+    pub fn hex_center(self, hex: IVec2, tile_size: Vec2) -> Vec2 {
+        let hex = hex.as_vec2();
+        let center = match self.orientation {
+            HexOrientation::PointyTop => Vec2::new(hex.x + hex.y / 2.0, hex.y * 0.75),
+            HexOrientation::FlatTop => Vec2::new(hex.x * 0.75, hex.y + hex.x / 2.0),
+        };
+        (center + 0.5 + self.origin()) * tile_size
+    }
+
+    /// Get the hex that has the given point, relative to the bottom-left corner of the layer, for
+    /// the given tile size.
+    ///
+    /// The point is in the hex whose center is the closest to it, after the grid is stretched
+    /// back into regular hexes, so every point is in exactly one hex.
+    pub fn hex_at(self, point: Vec2, tile_size: Vec2) -> IVec2 {
+        let point = point / tile_size - 0.5 - self.origin();
+        let hex = match self.orientation {
+            HexOrientation::PointyTop => {
+                let r = point.y / 0.75;
+                Vec2::new(point.x - r / 2.0, r)
+            }
+            HexOrientation::FlatTop => {
+                let q = point.x / 0.75;
+                Vec2::new(q, point.y - q / 2.0)
+            }
+        };
+        hex_round(hex)
+    }
+
+    /// Get the depth of the tile at `pos` in a layer with the given size, like
+    /// [`TileProjection::depth()`], so that the hexes that are lower down are in front.
+    pub fn depth(self, pos: UVec2, grid_size: UVec2) -> f32 {
+        let (pos, grid_size) = (pos.as_vec2(), grid_size.as_vec2());
+        match (self.orientation, self.coords) {
+            // The rows of pointy-top hexes are all level.
+            (HexOrientation::PointyTop, _) => (grid_size.y - 1.0 - pos.y) / grid_size.y,
+            // The shifted columns are half a hex further up.
+            (HexOrientation::FlatTop, HexCoords::Offset(stagger)) => {
+                let shift = if stagger.is_shifted(pos.x as u32) {
+                    1.0
+                } else {
+                    0.0
+                };
+                (2.0 * (grid_size.y - 1.0 - pos.y) + 1.0 - shift) / (2.0 * grid_size.y)
+            }
+            // Each column is half a hex further up than the one before it.
+            (HexOrientation::FlatTop, HexCoords::Axial) => {
+                let top = 2.0 * (grid_size.y - 1.0) + grid_size.x - 1.0;
+                (top - 2.0 * pos.y - pos.x) / (top + 1.0)
+            }
+        }
+    }
+
+    /// Get the offset of the hexes of the grid, in tiles, that moves the cells of the layer up
+    /// and to the right of its bottom-left corner.
+    fn origin(self) -> Vec2 {
+        match (self.orientation, self.coords) {
+            (HexOrientation::PointyTop, HexCoords::Offset(TileStagger::Even)) => Vec2::X * 0.5,
+            (HexOrientation::FlatTop, HexCoords::Offset(TileStagger::Even)) => Vec2::Y * 0.5,
+            _ => Vec2::ZERO,
+        }
+    }
+}
+
+impl TileLayer {
+    /// Get the [`HexGrid`] of the layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`projection`][Self::projection] of the layer isn't
+    /// [`Hexagonal`][TileProjection::Hexagonal].
+    pub fn hex_grid(&self) -> HexGrid {
+        match self.projection {
+            TileProjection::Hexagonal(grid) => grid,
+            projection => {
+                panic!("Tile layer has a {projection:?} projection instead of a hex grid")
+            }
+        }
+    }
+
+    /// Get the positions of the tiles of the layer that are next to the one at `pos`, in the order
+    /// of the [`HEX_DIRECTIONS`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer doesn't have a [`hex_grid()`][Self::hex_grid].
+    pub fn hex_neighbors(&self, pos: UVec2) -> Vec<UVec2> {
+        let grid = self.hex_grid();
+        self.hex_tiles(grid, hex_neighbors(grid.to_axial(pos)))
+    }
+
+    /// Get the number of steps between the tiles of the layer at `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer doesn't have a [`hex_grid()`][Self::hex_grid].
+    pub fn hex_distance(&self, a: UVec2, b: UVec2) -> u32 {
+        let grid = self.hex_grid();
+        hex_distance(grid.to_axial(a), grid.to_axial(b))
+    }
+
+    /// Get the positions of the tiles of the layer that are `radius` steps away from the one at
+    /// `center`, like [`hex_ring()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer doesn't have a [`hex_grid()`][Self::hex_grid].
+    pub fn hex_ring(&self, center: UVec2, radius: u32) -> Vec<UVec2> {
+        let grid = self.hex_grid();
+        self.hex_tiles(grid, hex_ring(grid.to_axial(center), radius))
+    }
+
+    /// Get the positions of the tiles of the layer on the line from `a` to `b`, like
+    /// [`hex_line()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer doesn't have a [`hex_grid()`][Self::hex_grid].
+    pub fn hex_line(&self, a: UVec2, b: UVec2) -> Vec<UVec2> {
+        let grid = self.hex_grid();
+        self.hex_tiles(grid, hex_line(grid.to_axial(a), grid.to_axial(b)))
+    }
+
+    /// Get the positions of the hexes that are in the layer.
+    fn hex_tiles(&self, grid: HexGrid, hexes: impl IntoIterator<Item = IVec2>) -> Vec<UVec2> {
+        hexes
+            .into_iter()
+            .filter_map(|hex| grid.from_axial(hex))
+            .filter(|pos| pos.cmplt(self.grid_size).all())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    /// Every layout of hex grid.
+    const GRIDS: [HexGrid; 6] = [
+        HexGrid {
+            orientation: HexOrientation::PointyTop,
+            coords: HexCoords::Offset(TileStagger::Odd),
+        },
+        HexGrid {
+            orientation: HexOrientation::PointyTop,
+            coords: HexCoords::Offset(TileStagger::Even),
+        },
+        HexGrid {
+            orientation: HexOrientation::PointyTop,
+            coords: HexCoords::Axial,
+        },
+        HexGrid {
+            orientation: HexOrientation::FlatTop,
+            coords: HexCoords::Offset(TileStagger::Odd),
+        },
+        HexGrid {
+            orientation: HexOrientation::FlatTop,
+            coords: HexCoords::Offset(TileStagger::Even),
+        },
+        HexGrid {
+            orientation: HexOrientation::FlatTop,
+            coords: HexCoords::Axial,
+        },
+    ];
+
+    /// A size of hexes that isn't regular, to make sure that stretched hexes work too.
+    const TILE_SIZE: Vec2 = Vec2::new(30.0, 34.0);
+
+    fn hexes() -> impl Iterator<Item = IVec2> {
+        (-6..=6).flat_map(|q| (-6..=6).map(move |r| IVec2::new(q, r)))
+    }
+
+    #[test]
+    fn distance_identities() {
+        for a in hexes() {
+            assert_eq!(hex_distance(a, a), 0);
+            for neighbor in hex_neighbors(a) {
+                assert_eq!(hex_distance(a, neighbor), 1);
+            }
+            for b in hexes() {
+                let distance = hex_distance(a, b);
+                assert_eq!(distance, hex_distance(b, a));
+                // The distance doesn't change when both hexes are moved.
+                assert_eq!(
+                    distance,
+                    hex_distance(a + IVec2::new(3, -7), b + IVec2::new(3, -7))
+                );
+                // The triangle inequality.
+                let c = IVec2::new(2, -1);
+                assert!(distance <= hex_distance(a, c) + hex_distance(c, b));
+            }
+        }
+    }
+
+    #[test]
+    fn rings() {
+        let center = IVec2::new(2, -3);
+        assert_eq!(hex_ring(center, 0), vec![center]);
+        let mut area = 1;
+        for radius in 1..6 {
+            let ring = hex_ring(center, radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            for (i, hex) in ring.iter().enumerate() {
+                assert_eq!(hex_distance(center, *hex), radius);
+                // The ring goes around from each hex to the next one.
+                assert_eq!(hex_distance(*hex, ring[(i + 1) % ring.len()]), 1);
+                assert!(!ring[..i].contains(hex));
+            }
+            area += ring.len();
+            // The hexes within a radius are `3 * radius * (radius + 1) + 1`.
+            let r = radius as usize;
+            assert_eq!(area, 3 * r * (r + 1) + 1);
+        }
+    }
+
+    #[test]
+    fn lines() {
+        for a in hexes() {
+            for b in hexes() {
+                let line = hex_line(a, b);
+                assert_eq!(line.len(), hex_distance(a, b) as usize + 1);
+                assert_eq!(line.first(), Some(&a));
+                assert_eq!(line.last(), Some(&b));
+                for step in line.windows(2) {
+                    assert_eq!(hex_distance(step[0], step[1]), 1);
+                }
+            }
+        }
+        assert_eq!(
+            hex_line(IVec2::ZERO, IVec2::new(3, 0)),
+            (0..=3).map(|q| IVec2::new(q, 0)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn axial_round_trip() {
+        for grid in GRIDS {
+            for x in 0..8 {
+                for y in 0..8 {
+                    let pos = UVec2::new(x, y);
+                    assert_eq!(grid.from_axial(grid.to_axial(pos)), Some(pos), "{grid:?}");
+                }
+            }
+        }
+        for hex in hexes() {
+            let grid = HexGrid::default();
+            if let Some(pos) = grid.from_axial(hex) {
+                assert_eq!(grid.to_axial(pos), hex);
+            }
+        }
+    }
+
+    #[test]
+    fn offset_layouts() {
+        // The odd rows of pointy-top hexes are shifted right.
+        let grid = GRIDS[0];
+        let center = |x, y| grid.hex_center(grid.to_axial(UVec2::new(x, y)), TILE_SIZE);
+        assert_eq!(center(0, 0), Vec2::new(15.0, 17.0));
+        assert_eq!(center(1, 0), Vec2::new(45.0, 17.0));
+        assert_eq!(center(0, 1), Vec2::new(30.0, 42.5));
+        assert_eq!(center(0, 2), Vec2::new(15.0, 68.0));
+
+        // The even columns of flat-top hexes are shifted up.
+        let grid = GRIDS[4];
+        let center = |x, y| grid.hex_center(grid.to_axial(UVec2::new(x, y)), TILE_SIZE);
+        assert_eq!(center(0, 0), Vec2::new(15.0, 34.0));
+        assert_eq!(center(1, 0), Vec2::new(37.5, 17.0));
+        assert_eq!(center(2, 0), Vec2::new(60.0, 34.0));
+        assert_eq!(center(0, 1), Vec2::new(15.0, 68.0));
+
+        // The neighbors of a tile in an offset grid depend on whether its row is shifted.
+        let mut layer = TileLayer::new(UVec2::new(4, 4), TILE_SIZE, default());
+        layer.projection = TileProjection::Hexagonal(GRIDS[0]);
+        let mut neighbors = layer.hex_neighbors(UVec2::new(1, 1));
+        neighbors.sort_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(
+            neighbors,
+            [(1, 0), (2, 0), (0, 1), (2, 1), (1, 2), (2, 2)].map(|(x, y)| UVec2::new(x, y))
+        );
+        // The neighbors outside of the layer are left out.
+        assert_eq!(layer.hex_neighbors(UVec2::ZERO).len(), 2);
+        assert_eq!(layer.hex_distance(UVec2::new(0, 0), UVec2::new(3, 3)), 5);
+        assert_eq!(layer.hex_ring(UVec2::new(1, 1), 1).len(), 6);
+        assert_eq!(layer.hex_line(UVec2::new(0, 0), UVec2::new(3, 0)).len(), 4);
+    }
+
+    #[test]
+    fn picking() {
+        // The corners of a hex, relative to its center, for each orientation.
+        let corners = |orientation| {
+            (0..6).map(move |i| {
+                let angle = (i as f32 * 60.0).to_radians();
+                match orientation {
+                    HexOrientation::PointyTop => {
+                        let angle = angle + 30f32.to_radians();
+                        Vec2::new(angle.cos() / 3f32.sqrt(), angle.sin() / 2.0) * TILE_SIZE
+                    }
+                    HexOrientation::FlatTop => {
+                        Vec2::new(angle.cos() / 2.0, angle.sin() / 3f32.sqrt()) * TILE_SIZE
+                    }
+                }
+            })
+        };
+
+        for grid in GRIDS {
+            let grid_size = UVec2::new(5, 4);
+            let projection = TileProjection::Hexagonal(grid);
+            for x in 0..grid_size.x {
+                for y in 0..grid_size.y {
+                    let pos = UVec2::new(x, y);
+                    let hex = grid.to_axial(pos);
+                    let center = projection.tile_center(pos, grid_size, TILE_SIZE);
+                    assert_eq!(grid.hex_at(center, TILE_SIZE), hex);
+
+                    // Points just inside of the corners are in the hex.
+                    for corner in corners(grid.orientation) {
+                        let point = center + corner * 0.99;
+                        assert_eq!(grid.hex_at(point, TILE_SIZE), hex, "{grid:?} {pos}");
+                    }
+
+                    // The middle of the edge between two hexes is half way between their
+                    // centers, so the points just on either side of it are in each of them.
+                    for neighbor in hex_neighbors(hex) {
+                        let neighbor_center = grid.hex_center(neighbor, TILE_SIZE);
+                        let near = center.lerp(neighbor_center, 0.499);
+                        let far = center.lerp(neighbor_center, 0.501);
+                        assert_eq!(grid.hex_at(near, TILE_SIZE), hex, "{grid:?} {pos}");
+                        assert_eq!(grid.hex_at(far, TILE_SIZE), neighbor, "{grid:?} {pos}");
+                        let edge = grid.hex_at(center.lerp(neighbor_center, 0.5), TILE_SIZE);
+                        assert!(edge == hex || edge == neighbor);
+                    }
+                }
+            }
+
+            // All of the cells are above and to the right of the bottom-left corner.
+            for x in 0..grid_size.x {
+                for y in 0..grid_size.y {
+                    let center = projection.tile_center(UVec2::new(x, y), grid_size, TILE_SIZE);
+                    for corner in corners(grid.orientation) {
+                        assert!(
+                            (center + corner).cmpge(Vec2::splat(-1e-3)).all(),
+                            "{grid:?}"
+                        );
+                    }
+                }
+            }
+            assert_eq!(
+                projection.tile_at(Vec2::splat(-1.0), grid_size, TILE_SIZE),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn layer_picking() {
+        let mut layer = TileLayer::new(UVec2::new(3, 3), TILE_SIZE, default());
+        layer.projection = TileProjection::Hexagonal(HexGrid::default());
+        let transform = Transform::from_translation(Vec3::new(-100.0, 20.0, 0.0));
+        for x in 0..3 {
+            for y in 0..3 {
+                let pos = UVec2::new(x, y);
+                let world = layer.tile_to_world(&transform, pos);
+                assert_eq!(layer.world_to_tile(&transform, world), Some(pos));
+            }
+        }
+        // The right side of the bottom row is outside of the layer.
+        let corner = layer.tile_to_world(&transform, UVec2::new(2, 0));
+        assert_eq!(
+            layer.world_to_tile(&transform, corner + Vec2::new(20.0, 0.0)),
+            None
+        );
+
+        // The rows further up are further back.
+        assert!(layer.tile_depth(UVec2::new(0, 0)) > layer.tile_depth(UVec2::new(2, 1)));
+        assert!(layer.tile_depth(UVec2::new(2, 2)) >= 0.0);
+        assert!(layer.tile_depth(UVec2::new(0, 0)) < 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "instead of a hex grid")]
+    fn square_layers_have_no_hex_grid() {
+        let layer = TileLayer::new(UVec2::new(3, 3), TILE_SIZE, default());
+        layer.hex_grid();
+    }
+}
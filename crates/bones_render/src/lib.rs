@@ -5,17 +5,33 @@
 #![cfg_attr(doc, allow(unknown_lints))]
 #![deny(rustdoc::all)]
 
+pub mod autotile;
 pub mod camera;
+pub mod chunked_tilemap;
+pub mod color_flash;
 pub mod datatypes;
+pub mod hex;
+pub mod parallax;
+pub mod pixel_snap;
+pub mod render_layers;
 pub mod sprite;
+pub mod static_sprite;
+pub mod tile_projection;
 pub mod tilemap;
 pub mod transform;
+pub mod visibility;
+pub mod window;
+pub mod y_sort;
 
 /// The prelude
 pub mod prelude {
     pub use {bones_asset::prelude::*, bones_ecs::prelude::*, glam::*, type_ulid::TypeUlid};
 
-    pub use crate::{camera::*, datatypes::*, sprite::*, tilemap::*, transform::*};
+    pub use crate::{
+        autotile::*, camera::*, chunked_tilemap::*, color_flash::*, datatypes::*, hex::*,
+        parallax::*, pixel_snap::*, render_layers::*, sprite::*, static_sprite::*,
+        tile_projection::*, tilemap::*, transform::*, visibility::*, window::*, y_sort::*,
+    };
 }
 
 #[cfg(feature = "bevy")]
@@ -0,0 +1,265 @@
+//! Parallax background component.
+
+use crate::prelude::*;
+
+/// Moves an entity along with the primary camera, by a fraction of the camera's movement, so that
+/// background layers look further away than the world.
+///
+/// A [`factor`][Self::factor] of `0.0` doesn't move the entity at all, and a factor of `1.0` moves
+/// it as much as the camera, so that it stays in place on the screen, like the sky. The
+/// [`parallax_system`] computes the offset of the entity, which is the camera's translation times
+/// the factor, and the offset is applied to the entity's [`Transform`] when it is rendered, so the
+/// parallax never moves the actual position of the entity.
+///
+/// With [`repeat_x`][Self::repeat_x] or [`repeat_y`][Self::repeat_y], the entity also jumps by its
+/// size whenever the camera has moved past it, so a [repeated][Sprite::repeat] sprite that is one
+/// repetition larger than the screen scrolls forever. Wrapping needs the sprite's
+/// [`custom_size`][Sprite::custom_size], and the size of one repetition is used for repeated
+/// sprites. Sprites without a custom size don't wrap.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// /// Spawn mountains, hills, and trees that are further and further away.
+/// fn spawn_backgrounds(
+///     mut entities: ResMut<Entities>,
+///     mut sprites: CompMut<Sprite>,
+///     mut transforms: CompMut<Transform>,
+///     mut parallaxes: CompMut<Parallax>,
+/// ) {
+///     for (image, factor, z) in [
+///         ("trees.png", 0.2, -10.0),
+///         ("hills.png", 0.5, -20.0),
+///         ("mountains.png", 0.8, -30.0),
+///     ] {
+///         // The images are 320x180 pixels, and are repeated enough to cover a 640 pixel wide
+///         // screen while the camera scrolls.
+///         let layer = entities.create();
+///         sprites.insert(
+///             layer,
+///             Sprite {
+///                 image: Handle::new(image, None),
+///                 custom_size: Some(Vec2::new(320.0 * 3.0, 180.0)),
+///                 repeat: Some(Vec2::new(3.0, 1.0)),
+///                 ..default()
+///             },
+///         );
+///         transforms.insert(layer, Transform::from_translation(Vec3::new(0.0, 0.0, z)));
+///         parallaxes.insert(
+///             layer,
+///             Parallax {
+///                 repeat_x: true,
+///                 ..Parallax::new(Vec2::new(factor, 0.0))
+///             },
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, TypeUlid)]
+#[ulid = "01GQWBR6Y3T8KD2NF7XM5HC9QP"]
+#[repr(C)]
+pub struct Parallax {
+    /// The fraction of the camera's movement that the entity moves by, along each axis.
+    pub factor: Vec2,
+    /// Whether the entity wraps around horizontally to follow the camera.
+    pub repeat_x: bool,
+    /// Whether the entity wraps around vertically to follow the camera.
+    pub repeat_y: bool,
+    /// The offset that is applied to the transform of the entity when it is rendered.
+    offset: Vec2,
+}
+
+impl Parallax {
+    /// Create a parallax that moves by the given fraction of the camera's movement, without
+    /// wrapping.
+    pub fn new(factor: Vec2) -> Self {
+        Self {
+            factor,
+            ..default()
+        }
+    }
+
+    /// Get the offset that is applied to the transform of the entity when it is rendered.
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+
+    /// Apply the current offset to the transform of the entity.
+    pub fn apply(&self, transform: Transform) -> Transform {
+        Transform {
+            translation: transform.translation + self.offset.extend(0.0),
+            ..transform
+        }
+    }
+
+    /// Compute the offset of the entity with the given transform, for the camera at the given
+    /// translation.
+    ///
+    /// The `size` is the size that the entity wraps around by, if it has one.
+    pub fn update(&mut self, transform: &Transform, camera: Vec2, size: Option<Vec2>) {
+        let position = transform.translation.truncate();
+        let mut offset = camera * self.factor;
+        if let Some(size) = size {
+            // Move the entity by whole sizes, to the closest position to the camera.
+            let wrap = |repeat: bool, distance: f32, size: f32| {
+                if repeat && size > 0.0 {
+                    (distance / size).round() * size
+                } else {
+                    0.0
+                }
+            };
+            let distance = camera - (position + offset);
+            offset += Vec2::new(
+                wrap(self.repeat_x, distance.x, size.x),
+                wrap(self.repeat_y, distance.y, size.y),
+            );
+        }
+        self.offset = offset;
+    }
+}
+
+/// System that updates the offsets of the entities with a [`Parallax`], to move them along with
+/// the primary camera.
+///
+/// The primary camera is the [primary camera][ActiveCameras::primary] of the [`ActiveCameras`],
+/// or the active camera with the lowest priority if the list is empty, like for
+/// [`CameraHelper::primary_camera()`].
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], after the camera has been moved for the frame, if they use it.
+pub fn parallax_system(
+    entities: Res<Entities>,
+    active_cameras: Res<ActiveCameras>,
+    cameras: Comp<Camera>,
+    sprites: Comp<Sprite>,
+    atlas_sprites: Comp<AtlasSprite>,
+    mut parallaxes: CompMut<Parallax>,
+    transforms: Comp<Transform>,
+) {
+    let camera = {
+        let mut camera_transforms = entities.iter_with((&cameras, &transforms));
+        let camera = match active_cameras.primary() {
+            Some(primary) => camera_transforms.find(|&(entity, _)| entity == primary),
+            None => camera_transforms
+                .filter(|(_, (camera, _))| camera.active)
+                .min_by_key(|(_, (camera, _))| camera.priority),
+        };
+        camera.map(|(_, (_, transform))| transform.translation.truncate())
+    };
+    let Some(camera) = camera else {
+        return;
+    };
+
+    for (entity, (mut parallax, transform)) in entities.iter_with((&mut parallaxes, &transforms)) {
+        let size = if let Some(sprite) = sprites.get(entity) {
+            sprite
+                .custom_size
+                .map(|size| size / sprite.repeat.unwrap_or(Vec2::ONE))
+        } else {
+            atlas_sprites.get(entity).and_then(|x| x.custom_size)
+        };
+        parallax.update(transform, camera, size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn parallax_follows_camera() {
+        let mut parallax = Parallax::new(Vec2::new(0.5, 0.25));
+        let mut transform = Transform::from_translation(Vec3::new(10.0, 0.0, -5.0));
+
+        parallax.update(&transform, Vec2::new(100.0, 40.0), None);
+        assert_eq!(
+            parallax.apply(transform).translation,
+            Vec3::new(60.0, 10.0, -5.0)
+        );
+
+        // The entity can still be moved, and the offset isn't applied twice.
+        transform.translation.x += 5.0;
+        parallax.update(&transform, Vec2::new(200.0, 40.0), None);
+        assert_eq!(
+            parallax.apply(transform).translation,
+            Vec3::new(115.0, 10.0, -5.0)
+        );
+        assert_eq!(parallax.offset(), Vec2::new(100.0, 10.0));
+        // The transform itself is never moved.
+        assert_eq!(transform.translation, Vec3::new(15.0, 0.0, -5.0));
+    }
+
+    #[test]
+    fn parallax_wraps() {
+        let mut parallax = Parallax {
+            repeat_x: true,
+            ..Parallax::new(Vec2::new(0.5, 0.0))
+        };
+        let transform = Transform::default();
+        let size = Some(Vec2::new(64.0, 32.0));
+
+        // The camera is less than half a size away, so the entity doesn't wrap yet.
+        parallax.update(&transform, Vec2::new(50.0, 100.0), size);
+        assert_eq!(parallax.offset().x, 25.0);
+        // The camera is 60 units past the entity, so it jumps a whole size forward.
+        parallax.update(&transform, Vec2::new(120.0, 100.0), size);
+        assert_eq!(parallax.offset().x, 60.0 + 64.0);
+        // It doesn't wrap vertically.
+        assert_eq!(parallax.offset().y, 0.0);
+        // Going back unwraps the entity.
+        parallax.update(&transform, Vec2::ZERO, size);
+        assert_eq!(parallax.offset(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn move_layers() {
+        let mut world = World::new();
+        let (camera, layer) = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            (entities.create(), entities.create())
+        };
+        world
+            .run_system(
+                move |mut cameras: CompMut<Camera>,
+                      mut transforms: CompMut<Transform>,
+                      mut sprites: CompMut<Sprite>,
+                      mut parallaxes: CompMut<Parallax>| {
+                    cameras.insert(camera, Camera::default());
+                    transforms.insert(
+                        camera,
+                        Transform::from_translation(Vec3::new(100.0, 0.0, 0.0)),
+                    );
+                    sprites.insert(
+                        layer,
+                        Sprite {
+                            custom_size: Some(Vec2::new(90.0, 10.0)),
+                            repeat: Some(Vec2::new(3.0, 1.0)),
+                            ..default()
+                        },
+                    );
+                    transforms.insert(layer, Transform::default());
+                    parallaxes.insert(
+                        layer,
+                        Parallax {
+                            repeat_x: true,
+                            ..Parallax::new(Vec2::new(0.5, 0.0))
+                        },
+                    );
+                },
+            )
+            .unwrap();
+
+        world.run_system(parallax_system).unwrap();
+        world
+            .run_system(
+                move |transforms: Comp<Transform>, parallaxes: Comp<Parallax>| {
+                    // The camera is 50 units past the layer, which wraps forward by two
+                    // repetitions of 30 units to get as close to the camera as it can.
+                    assert_eq!(parallaxes.get(layer).unwrap().offset().x, 110.0);
+                    // Only the rendered position of the layer moves.
+                    assert_eq!(transforms.get(layer).unwrap().translation.x, 0.0);
+                },
+            )
+            .unwrap();
+    }
+}
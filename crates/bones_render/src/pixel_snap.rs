@@ -0,0 +1,152 @@
+//! Pixel snapping resource.
+
+use crate::prelude::*;
+
+/// Resource that snaps the rendered positions of cameras and sprites to pixel grids, to keep pixel
+/// art from shimmering when the camera moves by fractions of a pixel.
+///
+/// The positions are only snapped when they are rendered, so the [`Transform`]s used by gameplay
+/// keep their exact values.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn toggle_pixel_snap_system(mut pixel_snap: ResMut<PixelSnap>) {
+///     /* when the toggle key is pressed... */
+///     pixel_snap.cameras = !pixel_snap.cameras;
+///     pixel_snap.sprites = !pixel_snap.sprites;
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, TypeUlid)]
+#[ulid = "01GQSYD3F8W2KN5TB7XJ4RCV9M"]
+#[repr(C)]
+pub struct PixelSnap {
+    /// Snap the positions of cameras to the pixels of the screen.
+    ///
+    /// The size of a screen pixel in the world depends on the [`size`][Camera::size] of the
+    /// camera, the size of the window, and the scale of the camera's transform, so cameras are
+    /// snapped correctly when they are zoomed, or when the window has a non-integer scale factor.
+    pub cameras: bool,
+    /// Snap the positions of [`Sprite`]s and [`AtlasSprite`]s to the texel grid, which has
+    /// [`texels_per_unit`][Self::texels_per_unit] texels per in-game pixel.
+    pub sprites: bool,
+    /// The number of sprite texels per in-game pixel.
+    pub texels_per_unit: f32,
+}
+
+impl Default for PixelSnap {
+    fn default() -> Self {
+        Self {
+            cameras: false,
+            sprites: false,
+            texels_per_unit: 1.0,
+        }
+    }
+}
+
+impl PixelSnap {
+    /// Snap the transform of a camera to the screen pixels of a window with the given size, if
+    /// [`cameras`][Self::cameras] is enabled.
+    pub fn snap_camera(
+        &self,
+        camera: &Camera,
+        window_size: UVec2,
+        transform: Transform,
+    ) -> Transform {
+        if !self.cameras {
+            return transform;
+        }
+        let (Some((_, viewport_size)), Some(visible_size)) = (
+            camera.viewport_rect(window_size),
+            camera.visible_size(window_size),
+        ) else {
+            return transform;
+        };
+        let visible_size = visible_size * transform.scale.truncate().abs();
+        let pixels_per_unit = viewport_size.as_vec2() / visible_size;
+        if !pixels_per_unit.is_finite() {
+            return transform;
+        }
+        let position = transform.translation.truncate();
+        Transform {
+            translation: snap(position, pixels_per_unit).extend(transform.translation.z),
+            ..transform
+        }
+    }
+
+    /// Snap the transform of a sprite to the texel grid, if [`sprites`][Self::sprites] is enabled.
+    pub fn snap_sprite(&self, transform: Transform) -> Transform {
+        if !self.sprites || !(self.texels_per_unit > 0.0) {
+            return transform;
+        }
+        let position = transform.translation.truncate();
+        Transform {
+            translation: snap(position, Vec2::splat(self.texels_per_unit))
+                .extend(transform.translation.z),
+            ..transform
+        }
+    }
+}
+
+/// Round the position to the nearest point on a grid with the given number of cells per unit.
+fn snap(position: Vec2, cells_per_unit: Vec2) -> Vec2 {
+    (position * cells_per_unit).round() / cells_per_unit
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn snap_camera() {
+        let pixel_snap = PixelSnap {
+            cameras: true,
+            ..default()
+        };
+        // The camera shows 200x100 in-game pixels in an 800x400 window, so every in-game pixel is
+        // 4 screen pixels.
+        let camera = Camera {
+            size: CameraSize::FixedHeight(100.0),
+            ..default()
+        };
+        let window_size = UVec2::new(800, 400);
+        let transform = Transform::from_translation(Vec3::new(10.1, -3.9, 5.0));
+        let snapped = pixel_snap.snap_camera(&camera, window_size, transform);
+        assert_eq!(snapped.translation, Vec3::new(10.0, -4.0, 5.0));
+
+        // Zoomed out by two, every in-game pixel is 2 screen pixels.
+        let transform = Transform {
+            translation: Vec3::new(10.1, -3.9, 5.0),
+            scale: Vec3::splat(2.0),
+            ..default()
+        };
+        let snapped = pixel_snap.snap_camera(&camera, window_size, transform);
+        assert_eq!(snapped.translation, Vec3::new(10.0, -4.0, 5.0));
+        let transform = Transform {
+            translation: Vec3::new(10.3, 0.0, 0.0),
+            ..transform
+        };
+        let snapped = pixel_snap.snap_camera(&camera, window_size, transform);
+        assert_eq!(snapped.translation, Vec3::new(10.5, 0.0, 0.0));
+
+        // Nothing is snapped when snapping is disabled, or the window has no area.
+        let disabled = PixelSnap::default();
+        let snapped = disabled.snap_camera(&camera, window_size, transform);
+        assert_eq!(snapped.translation, transform.translation);
+        let snapped = pixel_snap.snap_camera(&camera, UVec2::ZERO, transform);
+        assert_eq!(snapped.translation, transform.translation);
+    }
+
+    #[test]
+    fn snap_sprite() {
+        let pixel_snap = PixelSnap {
+            sprites: true,
+            texels_per_unit: 2.0,
+            ..default()
+        };
+        let transform = Transform::from_translation(Vec3::new(1.3, 2.8, 1.0));
+        assert_eq!(
+            pixel_snap.snap_sprite(transform).translation,
+            Vec3::new(1.5, 3.0, 1.0)
+        );
+    }
+}
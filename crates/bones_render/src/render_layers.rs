@@ -0,0 +1,153 @@
+//! Render layers component.
+
+use crate::prelude::*;
+
+/// The render layers of an entity, which select the cameras that render it.
+///
+/// May be added to [`Sprite`], [`AtlasSprite`], [`TileLayer`], and [`Camera`] entities. A camera
+/// only renders the entities that share at least one layer with it, so a minimap camera can
+/// render the map without the HUD, and a HUD camera can render only the HUD. Entities without a
+/// [`RenderLayers`] component are on layer `0`.
+///
+/// There are [`TOTAL_LAYERS`][Self::TOTAL_LAYERS] layers, numbered from `0`.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// const HUD_LAYER: u8 = 1;
+///
+/// fn spawn_hud_camera(
+///     mut entities: ResMut<Entities>,
+///     mut cameras: CompMut<Camera>,
+///     mut transforms: CompMut<Transform>,
+///     mut render_layers: CompMut<RenderLayers>,
+/// ) {
+///     let hud_camera = entities.create();
+///     cameras.insert(
+///         hud_camera,
+///         Camera {
+///             priority: 1,
+///             clear: CameraClear::None,
+///             ..default()
+///         },
+///     );
+///     transforms.insert(hud_camera, Transform::default());
+///     render_layers.insert(hud_camera, RenderLayers::single(HUD_LAYER));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TypeUlid)]
+#[ulid = "01GQST6V9D3KB8XW2ZQ5H7RMCJ"]
+#[repr(transparent)]
+pub struct RenderLayers(u32);
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::single(0)
+    }
+}
+
+impl RenderLayers {
+    /// The number of render layers.
+    pub const TOTAL_LAYERS: u8 = 32;
+
+    /// Create a [`RenderLayers`] with only the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer isn't less than [`TOTAL_LAYERS`][Self::TOTAL_LAYERS].
+    pub const fn single(layer: u8) -> Self {
+        Self::none().with(layer)
+    }
+
+    /// Create a [`RenderLayers`] with every layer.
+    pub const fn all() -> Self {
+        Self(u32::MAX)
+    }
+
+    /// Create a [`RenderLayers`] without any layers, which isn't rendered by any camera.
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// Add the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer isn't less than [`TOTAL_LAYERS`][Self::TOTAL_LAYERS].
+    #[must_use]
+    pub const fn with(self, layer: u8) -> Self {
+        Self(self.0 | Self::bit(layer))
+    }
+
+    /// Remove the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer isn't less than [`TOTAL_LAYERS`][Self::TOTAL_LAYERS].
+    #[must_use]
+    pub const fn without(self, layer: u8) -> Self {
+        Self(self.0 & !Self::bit(layer))
+    }
+
+    /// Returns `true` if the given layer is one of the layers.
+    pub fn contains(&self, layer: u8) -> bool {
+        layer < Self::TOTAL_LAYERS && self.0 & Self::bit(layer) != 0
+    }
+
+    /// Returns `true` if the layers share at least one layer with `other`, such as when a camera
+    /// renders an entity.
+    pub fn intersects(&self, other: &RenderLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Iterate over the layers, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..Self::TOTAL_LAYERS).filter(|&layer| self.contains(layer))
+    }
+
+    /// Get the layers as a bitmask, where bit `n` is set if layer `n` is one of the layers.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Get the bit of the given layer.
+    const fn bit(layer: u8) -> u32 {
+        assert!(
+            layer < Self::TOTAL_LAYERS,
+            "Render layers must be less than `RenderLayers::TOTAL_LAYERS`"
+        );
+        1 << layer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn layers() {
+        let map = RenderLayers::single(2);
+        let hud = RenderLayers::single(1);
+        let minimap_camera = RenderLayers::default().with(2);
+        let hud_camera = RenderLayers::all().without(0).without(2);
+
+        assert!(minimap_camera.intersects(&map));
+        assert!(!minimap_camera.intersects(&hud));
+        assert!(hud_camera.intersects(&hud));
+        assert!(!hud_camera.intersects(&map));
+        assert!(!RenderLayers::none().intersects(&RenderLayers::all()));
+
+        assert_eq!(minimap_camera.iter().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(minimap_camera.bits(), 0b101);
+        assert!(!map.contains(RenderLayers::TOTAL_LAYERS));
+        assert_eq!(
+            RenderLayers::all().iter().count(),
+            RenderLayers::TOTAL_LAYERS as usize
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn layer_out_of_range() {
+        RenderLayers::single(RenderLayers::TOTAL_LAYERS);
+    }
+}
@@ -1,5 +1,7 @@
 //! Sprite rendering components.
 
+use std::collections::HashMap;
+
 use crate::prelude::*;
 
 /// Image asset type, contains no data, but [`Handle<Image>`] is still useful because it uniquely
@@ -8,29 +10,321 @@ use crate::prelude::*;
 #[ulid = "01GNJGPQ8TKA234G1EA510BD96"]
 pub struct Image;
 
-/// An atlas image asset type, contains no data, but [`Handle<Atlas>`] is still useful becaause it
-/// uniquely represents an atlas asset that may be rendered outside of the core.
-#[derive(Copy, Clone, TypeUlid, Debug)]
+/// An atlas asset type, which describes the frames of a sprite sheet image.
+///
+/// [`Handle<Atlas>`] uniquely represents an atlas asset that may be rendered outside of the core.
+///
+/// Atlases can be built from a [grid][Self::from_grid] of equally sized frames, or from a list of
+/// [frames][Self::from_frames] of any size, such as the sheets packed by tools like TexturePacker.
+///
+/// Packers often trim the transparent borders off of the frames to save space. The
+/// [`trims`][Self::trims] of an atlas remember where the trimmed frames were in the original
+/// images, so that the sprites can still be drawn where they would have been, and an animation
+/// doesn't jitter as its frames are trimmed by different amounts.
+#[derive(Clone, TypeUlid, Debug, Default)]
 #[ulid = "01GNYXD7FVC46C7A3273HMEBRA"]
-pub struct Atlas;
+pub struct Atlas {
+    /// The sprite sheet image.
+    pub image: Handle<Image>,
+    /// The size of the image, in pixels.
+    pub size: Vec2,
+    /// The rectangles of the frames in the image, in pixels from the top-left corner of the image.
+    pub frames: Vec<Rect>,
+    /// How each of the [`frames`][Self::frames] was trimmed, or `None` for the frames that weren't
+    /// trimmed.
+    ///
+    /// This may be shorter than the list of frames, or empty if none of the frames were trimmed.
+    pub trims: Vec<Option<AtlasTrim>>,
+}
+
+/// Where a trimmed frame of an [`Atlas`] was in its original, untrimmed image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[repr(C)]
+pub struct AtlasTrim {
+    /// The size of the original image, in pixels.
+    pub source_size: Vec2,
+    /// The position of the top-left corner of the trimmed frame in the original image, in pixels
+    /// from the top-left corner of the original image.
+    pub offset: Vec2,
+}
+
+impl AtlasTrim {
+    /// Get the point of the trimmed frame of the given size that is at the `anchor` point of the
+    /// original image.
+    ///
+    /// Both points go from `(-0.5, -0.5)` for the bottom-left corner to `(0.5, 0.5)` for the
+    /// top-right corner, like [`Anchor::as_vec()`], so the point of the trimmed frame may be
+    /// outside of that range when the anchor has been trimmed off.
+    pub fn anchor(&self, frame_size: Vec2, anchor: Vec2) -> Vec2 {
+        // The anchor in pixels from the top-left corner of the trimmed frame.
+        let pixel = (Vec2::new(anchor.x, -anchor.y) + 0.5) * self.source_size - self.offset;
+        let point = pixel / frame_size - 0.5;
+        Vec2::new(point.x, -point.y)
+    }
+}
+
+impl Atlas {
+    /// Create an atlas from a grid of frames of the same size, in rows from left to right.
+    ///
+    /// The `padding` is the space between two frames, and the `offset` is the space between the
+    /// top-left corner of the image and the first frame.
+    pub fn from_grid(
+        image: Handle<Image>,
+        tile_size: Vec2,
+        columns: usize,
+        rows: usize,
+        padding: Vec2,
+        offset: Vec2,
+    ) -> Self {
+        let cell_size = tile_size + padding;
+        let frames = (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let min = offset + cell_size * Vec2::new(x as f32, y as f32);
+                Rect {
+                    min,
+                    max: min + tile_size,
+                }
+            })
+            .collect::<Vec<_>>();
+        let grid_size = Vec2::new(columns as f32, rows as f32);
+        let size = (offset + cell_size * grid_size - padding).max(Vec2::ZERO);
+        Self {
+            image,
+            size,
+            frames,
+            trims: Vec::new(),
+        }
+    }
+
+    /// Create an atlas from the rectangles of its frames, in pixels from the top-left corner of the
+    /// image.
+    ///
+    /// The size of the image is the smallest size that fits all of the frames.
+    pub fn from_frames(image: Handle<Image>, frames: Vec<Rect>) -> Self {
+        let size = frames
+            .iter()
+            .fold(Vec2::ZERO, |size, frame| size.max(frame.max));
+        Self {
+            image,
+            size,
+            frames,
+            trims: Vec::new(),
+        }
+    }
+
+    /// Set the [`trims`][Self::trims] of the frames of the atlas, and get the atlas back.
+    pub fn with_trims(mut self, trims: Vec<Option<AtlasTrim>>) -> Self {
+        self.trims = trims;
+        self
+    }
+
+    /// Get the number of frames in the atlas.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Get the number of frames in the atlas, the same as [`len()`][Self::len].
+    ///
+    /// The valid [`AtlasSprite::index`]es of the atlas are `0..frame_count()`.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if the atlas has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get the rectangle of the frame with the given index, in pixels, or `None` if the index is
+    /// out of range.
+    pub fn frame(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).copied()
+    }
+
+    /// Get how the frame with the given index was trimmed, or `None` if it wasn't trimmed or the
+    /// index is out of range.
+    pub fn trim(&self, index: usize) -> Option<AtlasTrim> {
+        self.trims.get(index).copied().flatten()
+    }
+
+    /// Get the size of the original image of the frame with the given index, before it was
+    /// trimmed, or `None` if the index is out of range.
+    pub fn source_size(&self, index: usize) -> Option<Vec2> {
+        let frame = self.frame(index)?;
+        Some(match self.trim(index) {
+            Some(trim) => trim.source_size,
+            None => frame.size(),
+        })
+    }
+
+    /// Get the rectangle of the frame with the given index in texture coordinates, from `0.0` to
+    /// `1.0`, or `None` if the index is out of range.
+    pub fn uv_rect(&self, index: usize) -> Option<Rect> {
+        let frame = self.frame(index)?;
+        Some(Rect {
+            min: frame.min / self.size,
+            max: frame.max / self.size,
+        })
+    }
+}
 
 /// A 2D sprite component
-#[derive(Clone, TypeUlid, Debug)]
+///
+/// When it is deserialized, the fields that are missing are set to their default values.
+#[derive(Clone, TypeUlid, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[ulid = "01GNJXPWZKS6BHJEG1SX5B93DA"]
 pub struct Sprite {
     /// The sprite image handle.
     pub image: Handle<Image>,
-    /// Whether or not the flip the sprite horizontally.
+    /// The rectangle of the image to draw, in pixels from the top-left corner of the image, or
+    /// `None` to draw the whole image.
+    ///
+    /// The sprite is drawn at the size of the rectangle, and is flipped inside of it. A rectangle
+    /// that goes outside of the image is clamped to the image.
+    pub rect: Option<Rect>,
+    /// Whether or not to flip the sprite horizontally.
+    ///
+    /// Unlike a negative scale, this doesn't change the [`Transform`], so it doesn't affect
+    /// children or collisions.
     pub flip_x: bool,
-    /// Whether or not the flip the sprite vertically.
+    /// Whether or not to flip the sprite vertically.
+    ///
+    /// Unlike a negative scale, this doesn't change the [`Transform`].
     pub flip_y: bool,
+    /// The color that the image is multiplied with, such as to tint the sprite red when it's hit,
+    /// or to fade it out with the alpha.
+    ///
+    /// [`Color::WHITE`] by default, which doesn't change the image.
+    pub color: Color,
+    /// The point of the sprite that is placed at the position of the [`Transform`], and that the
+    /// sprite is rotated, scaled, and flipped around.
+    pub anchor: Anchor,
+    /// The z that the sprite is drawn at, or `None` to use the z of its [`Transform`].
+    ///
+    /// This only changes the order that the sprite is drawn in, so UI can be drawn above the world
+    /// without moving it, and it isn't undone by the [`y_sort_system`]. See [`SpriteSortKey`].
+    pub z_index: Option<f32>,
+    /// The size that the sprite is drawn at, in in-game pixels, or `None` to draw it at its
+    /// natural [`size()`][Self::size].
+    ///
+    /// Unlike the scale of the [`Transform`], this doesn't affect children or collisions. The
+    /// image, or its repetitions, are stretched to fit the size, and the sprite is still anchored
+    /// and flipped within it.
+    pub custom_size: Option<Vec2>,
+    /// The number of times that the image is repeated along each axis, or `None` to draw it once.
+    ///
+    /// The image, or its [`rect`][Self::rect] if it has one, is repeated side by side, so the
+    /// sprite is drawn at the size of the image times the repeat counts. A fractional count cuts
+    /// off the last repetition. See [`tiles()`][Self::tiles].
+    pub repeat: Option<Vec2>,
+}
+
+impl Sprite {
+    /// Get the z that the sprite is drawn at, which is its [`z_index`][Self::z_index] if it has
+    /// one, or the z of its transform.
+    pub fn draw_z(&self, transform: &Transform) -> f32 {
+        self.z_index.unwrap_or(transform.translation.z)
+    }
+
+    /// Get the quads that the sprite is drawn with when it is [repeated][Self::repeat], for an
+    /// image of the given size in pixels.
+    ///
+    /// Each quad draws one repetition of the image, or of the part of the [`rect`][Self::rect]
+    /// that is inside of the image. The positions are relative to the position of the
+    /// [`Transform`], and take the [`anchor`][Self::anchor] and flipping into account.
+    ///
+    /// ```
+    /// # use bones_render::prelude::*;
+    /// /// Spawn a brick wall background that is 50 units wide.
+    /// fn spawn_background(
+    ///     mut entities: ResMut<Entities>,
+    ///     mut sprites: CompMut<Sprite>,
+    ///     mut transforms: CompMut<Transform>,
+    /// ) {
+    ///     // The bricks image is 16x16 pixels.
+    ///     let background = entities.create();
+    ///     sprites.insert(
+    ///         background,
+    ///         Sprite {
+    ///             image: Handle::new("bricks.png", None),
+    ///             repeat: Some(Vec2::new(50.0 / 16.0, 2.0)),
+    ///             anchor: Anchor::BottomLeft,
+    ///             ..default()
+    ///         },
+    ///     );
+    ///     transforms.insert(background, Transform::default());
+    /// }
+    /// ```
+    pub fn tiles(&self, image_size: Vec2) -> Vec<NinePatchSlice> {
+        let region = self.region(image_size);
+        let tile_size = region.size();
+        let natural_size = tile_size * self.repeat.unwrap_or(Vec2::ONE).max(Vec2::ZERO);
+        let size = self.size(image_size);
+        // The tiles are stretched to fit the custom size.
+        let scale = size / natural_size;
+        let offset = -self.anchor.flipped(self.flip_x, self.flip_y) * size;
+
+        // A nine-patch without borders is tiled the same way.
+        let tiled = NinePatchSprite {
+            size: natural_size,
+            mode: NinePatchMode::Tile,
+            ..default()
+        };
+        tiled
+            .slices(tile_size)
+            .into_iter()
+            .map(|mut tile| {
+                tile.rect_min += region.min;
+                tile.rect_max += region.min;
+                tile.position *= scale;
+                tile.size *= scale;
+                // Flipping mirrors the tiles around the center of the sprite.
+                if self.flip_x {
+                    tile.position.x = -tile.position.x;
+                }
+                if self.flip_y {
+                    tile.position.y = -tile.position.y;
+                }
+                tile.position += offset;
+                tile
+            })
+            .collect()
+    }
+
+    /// Get the size that the sprite is drawn at, for an image of the given size in pixels.
+    ///
+    /// This is the [`custom_size`][Self::custom_size] if it has one, or else the size of its
+    /// [`rect`][Self::rect], or of the image, times the [`repeat`][Self::repeat] counts.
+    pub fn size(&self, image_size: Vec2) -> Vec2 {
+        self.custom_size.unwrap_or_else(|| {
+            self.region(image_size).size() * self.repeat.unwrap_or(Vec2::ONE).max(Vec2::ZERO)
+        })
+    }
+
+    /// Get the part of the image that is drawn, which is the [`rect`][Self::rect] clamped to the
+    /// image, or the whole image.
+    fn region(&self, image_size: Vec2) -> Rect {
+        let image = Rect {
+            min: Vec2::ZERO,
+            max: image_size,
+        };
+        self.rect.map_or(image, |rect| rect.intersect(&image))
+    }
 }
 
 /// An animated sprite component.
 ///
 /// Represents one or more [`Atlas`]s stacked on top of each other, and possibly animated through a
 /// range of frames out of the atlas.
+///
+/// When it is deserialized, the fields that are missing are set to their default values.
 #[derive(Debug, Default, Clone, TypeUlid)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[ulid = "01GNYXFHC6T3NS061GMVFBXFYE"]
 pub struct AtlasSprite {
     /// This is the current index in the animation, with an `idx` of `0` meaning that the index in
@@ -40,8 +334,1390 @@ pub struct AtlasSprite {
     pub index: usize,
     /// The atlas handle.
     pub atlas: Handle<Atlas>,
-    /// Whether or not the flip the sprite horizontally.
+    /// Whether or not to flip the sprite horizontally.
+    ///
+    /// Unlike a negative scale, this doesn't change the [`Transform`], so it doesn't affect
+    /// children or collisions.
     pub flip_x: bool,
-    /// Whether or not the flip the sprite vertically.
+    /// Whether or not to flip the sprite vertically.
+    ///
+    /// Unlike a negative scale, this doesn't change the [`Transform`].
     pub flip_y: bool,
+    /// The color that the image is multiplied with, such as to tint the sprite red when it's hit,
+    /// or to fade it out with the alpha.
+    ///
+    /// [`Color::WHITE`] by default, which doesn't change the image.
+    pub color: Color,
+    /// The point of the sprite that is placed at the position of the [`Transform`], and that the
+    /// sprite is rotated, scaled, and flipped around.
+    pub anchor: Anchor,
+    /// The z that the sprite is drawn at, or `None` to use the z of its [`Transform`].
+    ///
+    /// This only changes the order that the sprite is drawn in, so UI can be drawn above the world
+    /// without moving it, and it isn't undone by the [`y_sort_system`]. See [`SpriteSortKey`].
+    pub z_index: Option<f32>,
+    /// The size that the sprite is drawn at, in in-game pixels, or `None` to draw it at the size
+    /// of its frame of the atlas.
+    ///
+    /// Unlike the scale of the [`Transform`], this doesn't affect children or collisions. The
+    /// sprite is still anchored and flipped within its size.
+    pub custom_size: Option<Vec2>,
+}
+
+impl AtlasSprite {
+    /// Set the [`index`][Self::index] of the sprite, or return an error without changing it if the
+    /// index is out of range for the atlas.
+    ///
+    /// Setting the index directly is fine too, but an index that is out of range is only clamped
+    /// by the renderer.
+    pub fn set_index_checked(
+        &mut self,
+        index: usize,
+        atlas: &Atlas,
+    ) -> Result<(), AtlasIndexError> {
+        let frame_count = atlas.frame_count();
+        if index >= frame_count {
+            return Err(AtlasIndexError { index, frame_count });
+        }
+        self.index = index;
+        Ok(())
+    }
+
+    /// Get the size that the sprite is drawn at, which is the [`custom_size`][Self::custom_size] if
+    /// it has one, or the size of its frame of the atlas, before it was [trimmed][Atlas::trims].
+    ///
+    /// Returns `None` if the sprite doesn't have a custom size and the index is out of range.
+    pub fn size(&self, atlas: &Atlas) -> Option<Vec2> {
+        self.custom_size.or_else(|| atlas.source_size(self.index))
+    }
+
+    /// Get the quad that the frame of the sprite is drawn on, or `None` if the index is out of
+    /// range.
+    ///
+    /// When the frame was [trimmed][Atlas::trims], the quad only covers the part of the sprite that
+    /// is left, and its anchor is moved so that the frame is drawn where it was in the original
+    /// image. The anchor of the quad has already been [flipped][Anchor::flipped].
+    pub fn quad(&self, atlas: &Atlas) -> Option<AtlasSpriteQuad> {
+        let frame_size = atlas.frame(self.index)?.size();
+        let anchor = self.anchor.as_vec();
+        let (size, anchor) = match atlas.trim(self.index) {
+            Some(trim) => {
+                let scale = self
+                    .custom_size
+                    .map_or(Vec2::ONE, |size| size / trim.source_size);
+                (frame_size * scale, trim.anchor(frame_size, anchor))
+            }
+            None => (self.custom_size.unwrap_or(frame_size), anchor),
+        };
+        Some(AtlasSpriteQuad {
+            size,
+            anchor: Anchor::Custom(anchor).flipped(self.flip_x, self.flip_y),
+        })
+    }
+
+    /// Get the z that the sprite is drawn at, which is its [`z_index`][Self::z_index] if it has
+    /// one, or the z of its transform.
+    pub fn draw_z(&self, transform: &Transform) -> f32 {
+        self.z_index.unwrap_or(transform.translation.z)
+    }
+}
+
+/// The quad that the frame of an [`AtlasSprite`] is drawn on, from [`AtlasSprite::quad()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AtlasSpriteQuad {
+    /// The size of the quad, in in-game pixels.
+    pub size: Vec2,
+    /// The point of the quad that is placed at the position of the [`Transform`], from
+    /// `(-0.5, -0.5)` for the bottom-left corner to `(0.5, 0.5)` for the top-right corner.
+    pub anchor: Vec2,
+}
+
+/// An error returned by [`AtlasSprite::set_index_checked()`] when the index is out of range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AtlasIndexError {
+    /// The index that was requested.
+    pub index: usize,
+    /// The number of frames in the atlas.
+    pub frame_count: usize,
+}
+
+impl std::fmt::Display for AtlasIndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Atlas index {} is out of range for an atlas with {} frames.",
+            self.index, self.frame_count
+        )
+    }
+}
+
+impl std::error::Error for AtlasIndexError {}
+
+/// A sprite that is drawn in nine slices, so that it can be resized without stretching its
+/// corners, such as for UI panels and speech bubbles.
+///
+/// The corners of the image are drawn at their original size, the edges are stretched or
+/// [tiled][NinePatchMode::Tile] along one axis, and the center is stretched or tiled along both.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// /// Open a dialog panel by growing it to its full width.
+/// fn open_dialog_system(
+///     time: Res<Time>,
+///     entities: Res<Entities>,
+///     mut panels: CompMut<NinePatchSprite>,
+/// ) {
+///     for (_, mut panel) in entities.iter_with(&mut panels) {
+///         if panel.size.x < 300.0 {
+///             panel.size.x = (panel.size.x + 600.0 * time.delta_seconds()).min(300.0);
+///         }
+///     }
+/// }
+///
+/// fn spawn_dialog(
+///     mut entities: ResMut<Entities>,
+///     mut panels: CompMut<NinePatchSprite>,
+///     mut transforms: CompMut<Transform>,
+/// ) {
+///     let dialog = entities.create();
+///     panels.insert(
+///         dialog,
+///         NinePatchSprite {
+///             image: Handle::new("ui/panel.png", None),
+///             borders: NinePatchBorders::all(8.0),
+///             size: Vec2::new(16.0, 100.0),
+///             ..default()
+///         },
+///     );
+///     transforms.insert(dialog, Transform::default());
+/// }
+/// ```
+#[derive(Clone, Debug, TypeUlid)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[ulid = "01GQT9P4E7N3WC5KX2HB8DRJ6M"]
+pub struct NinePatchSprite {
+    /// The sprite image handle.
+    pub image: Handle<Image>,
+    /// The sizes of the borders of the image, in pixels of the image.
+    pub borders: NinePatchBorders,
+    /// The size that the sprite is drawn at, in in-game pixels.
+    ///
+    /// If the size is smaller than the borders, the borders are shrunk to fit.
+    pub size: Vec2,
+    /// Whether or not to draw the center of the image.
+    pub fill_center: bool,
+    /// How the edges and center of the image are resized.
+    pub mode: NinePatchMode,
+    /// The color that the image is multiplied with.
+    pub color: Color,
+}
+
+impl Default for NinePatchSprite {
+    fn default() -> Self {
+        Self {
+            image: default(),
+            borders: default(),
+            size: Vec2::ZERO,
+            fill_center: true,
+            mode: default(),
+            color: default(),
+        }
+    }
+}
+
+/// The sizes of the borders of a [`NinePatchSprite`], in pixels of the image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[repr(C)]
+pub struct NinePatchBorders {
+    /// The size of the left border.
+    pub left: f32,
+    /// The size of the right border.
+    pub right: f32,
+    /// The size of the top border.
+    pub top: f32,
+    /// The size of the bottom border.
+    pub bottom: f32,
+}
+
+impl NinePatchBorders {
+    /// Create borders that all have the same size.
+    pub fn all(size: f32) -> Self {
+        Self {
+            left: size,
+            right: size,
+            top: size,
+            bottom: size,
+        }
+    }
+}
+
+/// How the edges and center of a [`NinePatchSprite`] are resized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[repr(u8)]
+pub enum NinePatchMode {
+    /// Stretch the edges and center to fill the sprite.
+    #[default]
+    Stretch,
+    /// Repeat the edges and center to fill the sprite, cutting off the last repetition.
+    Tile,
+}
+
+/// One of the quads that a [`NinePatchSprite`] is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NinePatchSlice {
+    /// The top-left corner of the part of the image drawn by the quad, in pixels of the image.
+    pub rect_min: Vec2,
+    /// The bottom-right corner of the part of the image drawn by the quad, in pixels of the image.
+    pub rect_max: Vec2,
+    /// The position of the center of the quad relative to the center of the sprite, in in-game
+    /// pixels.
+    pub position: Vec2,
+    /// The size of the quad, in in-game pixels.
+    pub size: Vec2,
+}
+
+impl NinePatchSprite {
+    /// Get the quads that the sprite is drawn with, for an image of the given size in pixels.
+    ///
+    /// Borders that don't fit in the image or in the [`size`][Self::size] of the sprite are shrunk
+    /// proportionally, so the quads are never inverted.
+    pub fn slices(&self, image_size: Vec2) -> Vec<NinePatchSlice> {
+        let size = self.size.max(Vec2::ZERO);
+        let borders = &self.borders;
+        let columns =
+            nine_patch_segments(image_size.x, size.x, borders.left, borders.right, self.mode);
+        let rows =
+            nine_patch_segments(image_size.y, size.y, borders.top, borders.bottom, self.mode);
+
+        let mut slices = Vec::with_capacity(columns.len() * rows.len());
+        for row in &rows {
+            for column in &columns {
+                if row.middle && column.middle && !self.fill_center {
+                    continue;
+                }
+                let slice_size = Vec2::new(column.dst.1 - column.dst.0, row.dst.1 - row.dst.0);
+                if slice_size.x <= 0.0 || slice_size.y <= 0.0 {
+                    continue;
+                }
+                slices.push(NinePatchSlice {
+                    rect_min: Vec2::new(column.src.0, row.src.0),
+                    rect_max: Vec2::new(column.src.1, row.src.1),
+                    // The rows are measured from the top, but the world's Y axis points up.
+                    position: Vec2::new(
+                        -size.x / 2.0 + (column.dst.0 + column.dst.1) / 2.0,
+                        size.y / 2.0 - (row.dst.0 + row.dst.1) / 2.0,
+                    ),
+                    size: slice_size,
+                });
+            }
+        }
+        slices
+    }
+}
+
+/// A segment of a [`NinePatchSprite`] along one axis.
+struct NinePatchSegment {
+    /// The start and end of the segment in the image.
+    src: (f32, f32),
+    /// The start and end of the segment in the sprite.
+    dst: (f32, f32),
+    /// Whether the segment is between the borders.
+    middle: bool,
+}
+
+/// Split one axis of a [`NinePatchSprite`] into the start border, the middle segments, and the
+/// end border.
+fn nine_patch_segments(
+    image_len: f32,
+    len: f32,
+    start: f32,
+    end: f32,
+    mode: NinePatchMode,
+) -> Vec<NinePatchSegment> {
+    let fit = |start: f32, end: f32, len: f32| {
+        let (start, end) = (start.max(0.0), end.max(0.0));
+        if start + end > len && start + end > 0.0 {
+            let scale = len / (start + end);
+            (start * scale, end * scale)
+        } else {
+            (start, end)
+        }
+    };
+    let (src_start, src_end) = fit(start, end, image_len);
+    let (dst_start, dst_end) = fit(src_start, src_end, len);
+    let src_middle = (src_start, image_len - src_end);
+    let dst_middle = (dst_start, len - dst_end);
+
+    let mut segments = vec![NinePatchSegment {
+        src: (0.0, src_start),
+        dst: (0.0, dst_start),
+        middle: false,
+    }];
+    let src_middle_len = src_middle.1 - src_middle.0;
+    match mode {
+        NinePatchMode::Tile if src_middle_len > 0.0 => {
+            let mut position = dst_middle.0;
+            while position < dst_middle.1 {
+                let tile_len = src_middle_len.min(dst_middle.1 - position);
+                segments.push(NinePatchSegment {
+                    src: (src_middle.0, src_middle.0 + tile_len),
+                    dst: (position, position + tile_len),
+                    middle: true,
+                });
+                position += tile_len;
+            }
+        }
+        _ => segments.push(NinePatchSegment {
+            src: src_middle,
+            dst: dst_middle,
+            middle: true,
+        }),
+    }
+    segments.push(NinePatchSegment {
+        src: (image_len - src_end, image_len),
+        dst: (len - dst_end, len),
+        middle: false,
+    });
+    segments
+}
+
+/// The point of a [`Sprite`] or [`AtlasSprite`] that is placed at the position of its
+/// [`Transform`].
+///
+/// The anchor is the pivot of the sprite, so the sprite is rotated and scaled around it. Flipping
+/// the sprite mirrors it around the anchor too, so a character anchored at its feet stays on the
+/// same spot when it turns around.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[repr(C, u8)]
+pub enum Anchor {
+    /// The center of the sprite.
+    #[default]
+    Center,
+    /// The bottom-left corner of the sprite.
+    BottomLeft,
+    /// The center of the bottom edge of the sprite.
+    BottomCenter,
+    /// The bottom-right corner of the sprite.
+    BottomRight,
+    /// The center of the left edge of the sprite.
+    CenterLeft,
+    /// The center of the right edge of the sprite.
+    CenterRight,
+    /// The top-left corner of the sprite.
+    TopLeft,
+    /// The center of the top edge of the sprite.
+    TopCenter,
+    /// The top-right corner of the sprite.
+    TopRight,
+    /// A custom point of the sprite, from `(-0.5, -0.5)` for the bottom-left corner to
+    /// `(0.5, 0.5)` for the top-right corner.
+    Custom(Vec2),
+}
+
+impl Anchor {
+    /// Get the point of the sprite, from `(-0.5, -0.5)` for the bottom-left corner to `(0.5, 0.5)`
+    /// for the top-right corner.
+    pub fn as_vec(&self) -> Vec2 {
+        match *self {
+            Anchor::Center => Vec2::ZERO,
+            Anchor::BottomLeft => Vec2::new(-0.5, -0.5),
+            Anchor::BottomCenter => Vec2::new(0.0, -0.5),
+            Anchor::BottomRight => Vec2::new(0.5, -0.5),
+            Anchor::CenterLeft => Vec2::new(-0.5, 0.0),
+            Anchor::CenterRight => Vec2::new(0.5, 0.0),
+            Anchor::TopLeft => Vec2::new(-0.5, 0.5),
+            Anchor::TopCenter => Vec2::new(0.0, 0.5),
+            Anchor::TopRight => Vec2::new(0.5, 0.5),
+            Anchor::Custom(point) => point,
+        }
+    }
+
+    /// Get the point of the image that the anchor is at, after the sprite has been flipped.
+    ///
+    /// Flipping mirrors the image, so the anchor of the flipped image is mirrored too.
+    pub fn flipped(&self, flip_x: bool, flip_y: bool) -> Vec2 {
+        let point = self.as_vec();
+        Vec2::new(
+            if flip_x { -point.x } else { point.x },
+            if flip_y { -point.y } else { point.y },
+        )
+    }
+}
+
+/// The key that sprites are sorted by to decide the order that they are drawn in.
+///
+/// Sprites are drawn in ascending order of:
+///
+/// 1. The [`priority`][Camera::priority] of the camera that draws them, because each camera draws
+///    over the output of the cameras before it.
+/// 2. Their draw z, which is their `z_index` if they have one, or the z of their [`Transform`]. See
+///    [`Sprite::draw_z()`].
+/// 3. The index of their entity, so that sprites with the same z are always drawn in the same
+///    order.
+///
+/// [`RenderLayers`] aren't part of the key: they only select the cameras that draw a sprite, so a
+/// sprite on a HUD layer is drawn over the world when the HUD camera has a higher priority.
+///
+/// > **Note:** The bevy renderer sorts sprites with the same z by their image instead of their
+/// > entity, so sprites that overlap should be given different z's.
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteSortKey {
+    /// The priority of the camera that draws the sprite.
+    pub camera_priority: i32,
+    /// The z that the sprite is drawn at.
+    pub z: f32,
+    /// The entity of the sprite.
+    pub entity: Entity,
+}
+
+impl SpriteSortKey {
+    /// Get the sort key of a sprite that is drawn by `camera` at the given z.
+    pub fn new(camera: &Camera, z: f32, entity: Entity) -> Self {
+        Self {
+            camera_priority: camera.priority,
+            z,
+            entity,
+        }
+    }
+}
+
+impl PartialEq for SpriteSortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for SpriteSortKey {}
+
+impl PartialOrd for SpriteSortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SpriteSortKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.camera_priority
+            .cmp(&other.camera_priority)
+            .then(self.z.total_cmp(&other.z))
+            .then(self.entity.index().cmp(&other.entity.index()))
+    }
+}
+
+/// Animates an [`AtlasSprite`] by changing its [`index`][AtlasSprite::index] at a fixed rate.
+///
+/// The animation is advanced by the [`atlas_animation_system`]. It only depends on the delta time,
+/// so it plays the same way every time when the [`Time`] is advanced by a fixed step.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn play_death_animation(player: Entity, mut animations: CompMut<AtlasAnimation>) {
+///     animations.insert(
+///         player,
+///         AtlasAnimation {
+///             mode: AnimationMode::Once,
+///             ..AtlasAnimation::new(8..14, 12.0)
+///         },
+///     );
+/// }
+///
+/// fn despawn_dead_players(
+///     mut entities: ResMut<Entities>,
+///     animations: Comp<AtlasAnimation>,
+/// ) {
+///     let finished = entities
+///         .iter_with(&animations)
+///         .filter(|(_, animation)| animation.finished())
+///         .map(|(entity, _)| entity)
+///         .collect::<Vec<_>>();
+///     for entity in finished {
+///         entities.kill(entity);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, TypeUlid)]
+#[ulid = "01GQT7B5W9K2DJ4XM8RH3NZC6V"]
+pub struct AtlasAnimation {
+    /// The indices in the atlas of the frames of the animation, in the order that they are played.
+    pub frames: Vec<usize>,
+    /// The number of frames played per second.
+    ///
+    /// This may be changed while the animation is playing, and takes effect from the current
+    /// frame on.
+    pub fps: f32,
+    /// What to do when the animation reaches its last frame.
+    pub mode: AnimationMode,
+    /// Whether or not the animation is playing.
+    pub playing: bool,
+    /// The playback state of the animation.
+    state: AnimationState,
+}
+
+/// What an [`AtlasAnimation`] or [`AnimationClip`] does when it reaches its last frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[repr(u8)]
+pub enum AnimationMode {
+    /// Start again from the first frame.
+    #[default]
+    Loop,
+    /// Stop at the last frame, and [finish][AtlasAnimation::finished].
+    Once,
+    /// Play backwards to the first frame, and then forwards again.
+    PingPong,
+}
+
+impl AtlasAnimation {
+    /// Create an animation that plays the given frames in a loop.
+    pub fn new(frames: impl IntoIterator<Item = usize>, fps: f32) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+            fps,
+            mode: AnimationMode::Loop,
+            playing: true,
+            state: default(),
+        }
+    }
+
+    /// Get the position of the current frame in the [`frames`][Self::frames].
+    pub fn frame(&self) -> usize {
+        self.state.frame
+    }
+
+    /// Get the index in the atlas of the current frame, or `None` if there are no frames.
+    pub fn index(&self) -> Option<usize> {
+        self.frames.get(self.state.frame).copied()
+    }
+
+    /// Returns `true` if the animation has reached its last frame in [`AnimationMode::Once`].
+    pub fn finished(&self) -> bool {
+        self.state.finished
+    }
+
+    /// Start the animation again from the first frame.
+    pub fn restart(&mut self) {
+        self.state = default();
+    }
+
+    /// Advance the animation by the given number of seconds, if it's playing.
+    pub fn update(&mut self, delta_seconds: f32) {
+        if self.playing {
+            self.state
+                .update(self.frames.len(), self.fps, self.mode, delta_seconds);
+        }
+    }
+}
+
+/// The playback state of an [`AtlasAnimation`] or [`AnimationBank`].
+#[derive(Clone, Copy, Debug, Default)]
+struct AnimationState {
+    /// The position of the current frame in the list of frames.
+    frame: usize,
+    /// The number of seconds that the current frame has been shown for.
+    frame_time: f32,
+    /// Whether the animation is playing backwards, in [`AnimationMode::PingPong`].
+    reversed: bool,
+    /// Whether the animation has finished, in [`AnimationMode::Once`].
+    finished: bool,
+}
+
+impl AnimationState {
+    /// Advance an animation with the given number of frames by the given number of seconds.
+    fn update(&mut self, frame_count: usize, fps: f32, mode: AnimationMode, delta_seconds: f32) {
+        if self.finished || fps <= 0.0 || frame_count == 0 {
+            return;
+        }
+        let frame_duration = 1.0 / fps;
+        self.frame_time += delta_seconds;
+        while self.frame_time >= frame_duration && !self.finished {
+            self.frame_time -= frame_duration;
+            self.next_frame(frame_count - 1, mode);
+        }
+    }
+
+    /// Move to the next frame according to the mode.
+    fn next_frame(&mut self, last: usize, mode: AnimationMode) {
+        match mode {
+            AnimationMode::Loop => {
+                self.frame = if self.frame >= last {
+                    0
+                } else {
+                    self.frame + 1
+                };
+            }
+            AnimationMode::Once => {
+                if self.frame >= last {
+                    self.frame = last;
+                    self.finished = true;
+                } else {
+                    self.frame += 1;
+                }
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return;
+                }
+                if self.frame >= last {
+                    self.reversed = true;
+                } else if self.frame == 0 {
+                    self.reversed = false;
+                }
+                if self.reversed {
+                    self.frame = self.frame.min(last) - 1;
+                } else {
+                    self.frame += 1;
+                }
+            }
+        }
+    }
+}
+
+/// System that advances the [`AtlasAnimation`]s and updates the indices of their [`AtlasSprite`]s.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn atlas_animation_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut animations: CompMut<AtlasAnimation>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+) {
+    let delta_seconds = time.delta_seconds();
+    for (_, (mut animation, mut atlas_sprite)) in
+        entities.iter_with((&mut animations, &mut atlas_sprites))
+    {
+        animation.update(delta_seconds);
+        if let Some(index) = animation.index() {
+            if atlas_sprite.index != index {
+                atlas_sprite.index = index;
+            }
+        }
+    }
+}
+
+/// A library of named animations for an [`AtlasSprite`], such as `idle`, `run`, and `jump`.
+///
+/// The [`current`][Self::current] clip is played by the [`animation_bank_system`]. Changing the
+/// current clip starts the new clip from its first frame, as soon as the system runs.
+///
+/// When it is deserialized, the current clip defaults to `idle`, so a bank can be written next to
+/// the atlas that it animates:
+///
+/// ```yaml
+/// clips:
+///   idle:
+///     frames: [0, 1, 2, 3]
+///     fps: 6
+///   jump:
+///     frames: [8, 9, 10]
+///     fps: 12
+///     mode: once
+/// ```
+#[derive(Clone, Debug, TypeUlid)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[ulid = "01GQTFS7K2D9WM4XH6RB3NJC8Z"]
+pub struct AnimationBank {
+    /// The clips of the bank, by name.
+    pub clips: HashMap<Key, AnimationClip>,
+    /// The name of the clip that is played.
+    pub current: Key,
+    /// Whether or not the current clip is playing.
+    pub playing: bool,
+    /// The name of the clip that the playback state is for.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state_clip: Option<Key>,
+    /// The playback state of the current clip.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: AnimationState,
+}
+
+impl Default for AnimationBank {
+    fn default() -> Self {
+        Self {
+            clips: default(),
+            current: Key::new("idle").unwrap(),
+            playing: true,
+            state_clip: None,
+            state: default(),
+        }
+    }
+}
+
+/// A named animation in an [`AnimationBank`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct AnimationClip {
+    /// The indices in the atlas of the frames of the clip, in the order that they are played.
+    pub frames: Vec<usize>,
+    /// The number of frames played per second.
+    pub fps: f32,
+    /// What to do when the clip reaches its last frame.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: AnimationMode,
+}
+
+impl AnimationBank {
+    /// Add a clip to the bank, replacing the clip with the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name isn't a valid [`Key`].
+    pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.clips.insert(Key::new(name).unwrap(), clip);
+        self
+    }
+
+    /// Play the clip with the given name, starting it from its first frame if it isn't already
+    /// the current clip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name isn't a valid [`Key`].
+    pub fn play(&mut self, name: &str) {
+        self.current = Key::new(name).unwrap();
+        self.playing = true;
+    }
+
+    /// Get the current clip, if the bank has a clip with its name.
+    pub fn current_clip(&self) -> Option<&AnimationClip> {
+        self.clips.get(&self.current)
+    }
+
+    /// Get the index in the atlas of the current frame, or `None` if there is no current clip.
+    pub fn index(&self) -> Option<usize> {
+        let frame = self.sync_state().frame;
+        self.current_clip()?.frames.get(frame).copied()
+    }
+
+    /// Returns `true` if the current clip has reached its last frame in [`AnimationMode::Once`].
+    pub fn finished(&self) -> bool {
+        self.sync_state().finished
+    }
+
+    /// Start the current clip again from its first frame.
+    pub fn restart(&mut self) {
+        self.state = default();
+        self.state_clip = Some(self.current);
+    }
+
+    /// Advance the current clip by the given number of seconds, if it's playing.
+    ///
+    /// If the current clip has changed since the last update, the new clip is started from its
+    /// first frame instead.
+    pub fn update(&mut self, delta_seconds: f32) {
+        if self.state_clip != Some(self.current) {
+            self.restart();
+            return;
+        }
+        let Some(clip) = self.clips.get(&self.current) else {
+            return;
+        };
+        if self.playing {
+            self.state
+                .update(clip.frames.len(), clip.fps, clip.mode, delta_seconds);
+        }
+    }
+
+    /// Get the playback state for the current clip, which is the start of the clip if it has
+    /// changed since the last update.
+    fn sync_state(&self) -> AnimationState {
+        if self.state_clip == Some(self.current) {
+            self.state
+        } else {
+            default()
+        }
+    }
+}
+
+/// System that plays the current clips of the [`AnimationBank`]s, and updates the indices of their
+/// [`AtlasSprite`]s.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn animation_bank_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut banks: CompMut<AnimationBank>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+) {
+    let delta_seconds = time.delta_seconds();
+    for (_, (mut bank, mut atlas_sprite)) in entities.iter_with((&mut banks, &mut atlas_sprites)) {
+        bank.update(delta_seconds);
+        if let Some(index) = bank.index() {
+            if atlas_sprite.index != index {
+                atlas_sprite.index = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    /// Play the animation for the given number of frames at 10 fps, and get the atlas indices.
+    fn play(animation: &mut AtlasAnimation, frames: usize) -> Vec<usize> {
+        (0..frames)
+            .map(|_| {
+                animation.update(0.1);
+                animation.index().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn atlas_grid() {
+        let atlas = Atlas::from_grid(
+            default(),
+            Vec2::new(16.0, 8.0),
+            3,
+            2,
+            Vec2::new(2.0, 1.0),
+            Vec2::new(4.0, 4.0),
+        );
+        assert_eq!(atlas.len(), 6);
+        assert_eq!(
+            atlas.size,
+            Vec2::new(4.0 + 16.0 * 3.0 + 2.0 * 2.0, 4.0 + 8.0 * 2.0 + 1.0)
+        );
+        assert_eq!(atlas.frame(0), Some(Rect::new(4.0, 4.0, 20.0, 12.0)));
+        // The second frame of the second row.
+        assert_eq!(atlas.frame(4), Some(Rect::new(22.0, 13.0, 38.0, 21.0)));
+        assert_eq!(atlas.frame(6), None);
+
+        let uv = atlas.uv_rect(5).unwrap();
+        assert_eq!(uv.max, Vec2::ONE);
+        assert_eq!(atlas.uv_rect(6), None);
+
+        let tight = Atlas::from_grid(default(), Vec2::splat(8.0), 4, 4, Vec2::ZERO, Vec2::ZERO);
+        assert_eq!(tight.size, Vec2::splat(32.0));
+        assert_eq!(tight.uv_rect(5), Some(Rect::new(0.25, 0.25, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn atlas_index_checked() {
+        let atlas = Atlas::from_grid(default(), Vec2::splat(8.0), 4, 1, Vec2::ZERO, Vec2::ZERO);
+        assert_eq!(atlas.frame_count(), 4);
+
+        let mut sprite = AtlasSprite::default();
+        assert!(sprite.set_index_checked(3, &atlas).is_ok());
+        assert_eq!(sprite.index, 3);
+        assert_eq!(
+            sprite.set_index_checked(4, &atlas),
+            Err(AtlasIndexError {
+                index: 4,
+                frame_count: 4
+            })
+        );
+        assert_eq!(
+            sprite.set_index_checked(5, &atlas),
+            Err(AtlasIndexError {
+                index: 5,
+                frame_count: 4
+            })
+        );
+        // The index isn't changed by an error.
+        assert_eq!(sprite.index, 3);
+    }
+
+    #[test]
+    fn atlas_frames() {
+        let atlas = Atlas::from_frames(
+            default(),
+            vec![
+                Rect::new(0.0, 0.0, 30.0, 10.0),
+                Rect::new(0.0, 10.0, 12.0, 40.0),
+            ],
+        );
+        assert_eq!(atlas.size, Vec2::new(30.0, 40.0));
+        assert_eq!(atlas.uv_rect(1), Some(Rect::new(0.0, 0.25, 0.4, 1.0)));
+    }
+
+    #[test]
+    fn trimmed_atlas_frames() {
+        // Three 32x32 frames of a character standing on the bottom edge, with the transparent
+        // borders trimmed off of the first two by different amounts.
+        let atlas = Atlas::from_frames(
+            default(),
+            vec![
+                Rect::new(0.0, 0.0, 16.0, 24.0),
+                Rect::new(16.0, 0.0, 32.0, 16.0),
+                Rect::new(32.0, 0.0, 64.0, 32.0),
+            ],
+        )
+        .with_trims(vec![
+            Some(AtlasTrim {
+                source_size: Vec2::splat(32.0),
+                offset: Vec2::new(8.0, 8.0),
+            }),
+            Some(AtlasTrim {
+                source_size: Vec2::splat(32.0),
+                offset: Vec2::new(12.0, 16.0),
+            }),
+        ]);
+        assert_eq!(atlas.trim(2), None);
+        assert_eq!(atlas.source_size(1), Some(Vec2::splat(32.0)));
+        assert_eq!(atlas.source_size(2), Some(Vec2::splat(32.0)));
+
+        let mut sprite = AtlasSprite {
+            anchor: Anchor::BottomCenter,
+            ..default()
+        };
+        assert_eq!(sprite.size(&atlas), Some(Vec2::splat(32.0)));
+        // The bottom center of the original image is at (16, 32), which is (8, 24) in the first
+        // trimmed frame, and (4, 16) in the second one.
+        assert_eq!(
+            sprite.quad(&atlas),
+            Some(AtlasSpriteQuad {
+                size: Vec2::new(16.0, 24.0),
+                anchor: Vec2::new(0.0, -0.5),
+            })
+        );
+        sprite.index = 1;
+        assert_eq!(
+            sprite.quad(&atlas),
+            Some(AtlasSpriteQuad {
+                size: Vec2::new(16.0, 16.0),
+                anchor: Vec2::new(-0.25, -0.5),
+            })
+        );
+
+        // Flipping mirrors the frame around the anchor, and the custom size scales the original
+        // image.
+        sprite.flip_x = true;
+        sprite.custom_size = Some(Vec2::splat(64.0));
+        assert_eq!(
+            sprite.quad(&atlas),
+            Some(AtlasSpriteQuad {
+                size: Vec2::new(32.0, 32.0),
+                anchor: Vec2::new(0.25, -0.5),
+            })
+        );
+
+        sprite.index = 2;
+        assert_eq!(
+            sprite.quad(&atlas),
+            Some(AtlasSpriteQuad {
+                size: Vec2::splat(64.0),
+                anchor: Vec2::new(0.0, -0.5),
+            })
+        );
+        sprite.index = 3;
+        assert_eq!(sprite.quad(&atlas), None);
+    }
+
+    #[test]
+    fn nine_patch_slices() {
+        let panel = NinePatchSprite {
+            borders: NinePatchBorders {
+                left: 2.0,
+                right: 4.0,
+                top: 1.0,
+                bottom: 3.0,
+            },
+            size: Vec2::new(100.0, 50.0),
+            ..default()
+        };
+        let slices = panel.slices(Vec2::new(16.0, 8.0));
+        assert_eq!(slices.len(), 9);
+        // The top-left corner isn't resized.
+        assert_eq!(
+            slices[0],
+            NinePatchSlice {
+                rect_min: Vec2::ZERO,
+                rect_max: Vec2::new(2.0, 1.0),
+                position: Vec2::new(-49.0, 24.5),
+                size: Vec2::new(2.0, 1.0),
+            }
+        );
+        // The center is stretched over the rest of the sprite.
+        assert_eq!(
+            slices[4],
+            NinePatchSlice {
+                rect_min: Vec2::new(2.0, 1.0),
+                rect_max: Vec2::new(12.0, 5.0),
+                position: Vec2::new(-1.0, 1.0),
+                size: Vec2::new(94.0, 46.0),
+            }
+        );
+        // The bottom-right corner isn't resized.
+        assert_eq!(slices[8].size, Vec2::new(4.0, 3.0));
+        assert_eq!(slices[8].position, Vec2::new(48.0, -23.5));
+
+        let hollow = NinePatchSprite {
+            fill_center: false,
+            ..panel.clone()
+        };
+        assert_eq!(hollow.slices(Vec2::new(16.0, 8.0)).len(), 8);
+    }
+
+    #[test]
+    fn nine_patch_clamps_borders() {
+        let panel = NinePatchSprite {
+            borders: NinePatchBorders::all(8.0),
+            size: Vec2::new(8.0, 32.0),
+            ..default()
+        };
+        let slices = panel.slices(Vec2::new(32.0, 32.0));
+        // The left and right borders are shrunk to fit, and there is no room for the middle.
+        assert_eq!(slices.len(), 6);
+        for slice in &slices {
+            assert!(slice.size.x > 0.0 && slice.size.y > 0.0);
+            assert_eq!(slice.size.x, 4.0);
+            assert!(slice.rect_min.cmple(slice.rect_max).all());
+        }
+    }
+
+    #[test]
+    fn nine_patch_tiles() {
+        let panel = NinePatchSprite {
+            borders: NinePatchBorders::all(4.0),
+            size: Vec2::new(28.0, 16.0),
+            mode: NinePatchMode::Tile,
+            ..default()
+        };
+        // The middle of the image is 8 pixels wide, so it's repeated 2.5 times horizontally and
+        // once vertically.
+        let slices = panel.slices(Vec2::new(16.0, 16.0));
+        assert_eq!(slices.len(), 5 * 3);
+        let last_tile = slices[3];
+        assert_eq!(last_tile.size, Vec2::new(4.0, 4.0));
+        assert_eq!(last_tile.rect_min, Vec2::new(4.0, 0.0));
+        assert_eq!(last_tile.rect_max, Vec2::new(8.0, 4.0));
+    }
+
+    #[test]
+    fn repeated_sprite_tiles() {
+        let bricks = Sprite {
+            repeat: Some(Vec2::new(2.5, 1.0)),
+            anchor: Anchor::BottomLeft,
+            ..default()
+        };
+        let tiles = bricks.tiles(Vec2::new(16.0, 8.0));
+        assert_eq!(tiles.len(), 3);
+        assert_eq!(
+            tiles[0],
+            NinePatchSlice {
+                rect_min: Vec2::ZERO,
+                rect_max: Vec2::new(16.0, 8.0),
+                position: Vec2::new(8.0, 4.0),
+                size: Vec2::new(16.0, 8.0),
+            }
+        );
+        // The last repetition is cut off.
+        assert_eq!(tiles[2].rect_max, Vec2::new(8.0, 8.0));
+        assert_eq!(tiles[2].position, Vec2::new(36.0, 4.0));
+        assert_eq!(tiles[2].size, Vec2::new(8.0, 8.0));
+
+        // Flipping mirrors the tiles around the anchor.
+        let flipped = Sprite {
+            flip_x: true,
+            ..bricks.clone()
+        };
+        let tiles = flipped.tiles(Vec2::new(16.0, 8.0));
+        assert_eq!(tiles[0].position, Vec2::new(-8.0, 4.0));
+        assert_eq!(tiles[2].position, Vec2::new(-36.0, 4.0));
+
+        // The rectangle is repeated, after it has been clamped to the image.
+        let rect = Sprite {
+            rect: Some(Rect::new(8.0, 0.0, 24.0, 4.0)),
+            repeat: Some(Vec2::new(1.0, 2.0)),
+            ..default()
+        };
+        let tiles = rect.tiles(Vec2::new(16.0, 8.0));
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[1].rect_min, Vec2::new(8.0, 0.0));
+        assert_eq!(tiles[1].rect_max, Vec2::new(16.0, 4.0));
+        assert_eq!(tiles[1].position, Vec2::new(0.0, -2.0));
+    }
+
+    #[test]
+    fn custom_size() {
+        let image_size = Vec2::new(16.0, 16.0);
+        let sprite = Sprite::default();
+        assert_eq!(sprite.size(image_size), image_size);
+        let rect = Sprite {
+            rect: Some(Rect::new(0.0, 0.0, 8.0, 4.0)),
+            ..default()
+        };
+        assert_eq!(rect.size(image_size), Vec2::new(8.0, 4.0));
+        let health_bar = Sprite {
+            custom_size: Some(Vec2::new(64.0, 8.0)),
+            ..rect.clone()
+        };
+        assert_eq!(health_bar.size(image_size), Vec2::new(64.0, 8.0));
+
+        // The tiles of a repeated sprite are stretched to fit.
+        let repeated = Sprite {
+            repeat: Some(Vec2::new(2.0, 1.0)),
+            anchor: Anchor::CenterLeft,
+            ..health_bar
+        };
+        let tiles = repeated.tiles(image_size);
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[1].size, Vec2::new(32.0, 8.0));
+        assert_eq!(tiles[1].position, Vec2::new(48.0, 0.0));
+        assert_eq!(tiles[1].rect_max, Vec2::new(8.0, 4.0));
+
+        let atlas = Atlas::from_grid(
+            default(),
+            Vec2::new(16.0, 8.0),
+            2,
+            1,
+            Vec2::ZERO,
+            Vec2::ZERO,
+        );
+        let atlas_sprite = AtlasSprite {
+            index: 1,
+            ..default()
+        };
+        assert_eq!(atlas_sprite.size(&atlas), Some(Vec2::new(16.0, 8.0)));
+        let stretched = AtlasSprite {
+            custom_size: Some(Vec2::splat(32.0)),
+            ..atlas_sprite.clone()
+        };
+        assert_eq!(stretched.size(&atlas), Some(Vec2::splat(32.0)));
+        let out_of_range = AtlasSprite {
+            index: 2,
+            ..atlas_sprite
+        };
+        assert_eq!(out_of_range.size(&atlas), None);
+    }
+
+    #[test]
+    fn z_index_overrides_transform() {
+        let transform = Transform::from_translation(Vec3::new(0.0, 0.0, 5.0));
+        let sprite = Sprite::default();
+        assert_eq!(sprite.draw_z(&transform), 5.0);
+        let hud = Sprite {
+            z_index: Some(100.0),
+            ..default()
+        };
+        assert_eq!(hud.draw_z(&transform), 100.0);
+        let atlas = AtlasSprite {
+            z_index: Some(-1.0),
+            ..default()
+        };
+        assert_eq!(atlas.draw_z(&transform), -1.0);
+    }
+
+    #[test]
+    fn sprite_sort_key_is_stable() {
+        let mut entities = Entities::default();
+        let sprites = (0..6).map(|_| entities.create()).collect::<Vec<_>>();
+        let world_camera = Camera::default();
+        let hud_camera = Camera {
+            priority: 1,
+            ..default()
+        };
+        let keys = [
+            SpriteSortKey::new(&hud_camera, -10.0, sprites[0]),
+            SpriteSortKey::new(&world_camera, 1.0, sprites[1]),
+            SpriteSortKey::new(&world_camera, 1.0, sprites[2]),
+            SpriteSortKey::new(&world_camera, 0.0, sprites[3]),
+            SpriteSortKey::new(&world_camera, 1.0, sprites[4]),
+            SpriteSortKey::new(&hud_camera, -10.0, sprites[5]),
+        ];
+        let order = |keys: &mut [SpriteSortKey]| {
+            keys.sort_unstable();
+            keys.iter().map(|x| x.entity).collect::<Vec<_>>()
+        };
+        let expected = [3, 1, 2, 4, 0, 5].map(|i| sprites[i]).to_vec();
+        assert_eq!(order(&mut keys.clone()), expected);
+
+        // The order doesn't depend on the order that the sprites are sorted from.
+        let mut reversed = keys;
+        reversed.reverse();
+        assert_eq!(order(&mut reversed), expected);
+    }
+
+    #[test]
+    fn flipped_anchor() {
+        assert_eq!(
+            Anchor::BottomCenter.flipped(true, false),
+            Vec2::new(0.0, -0.5)
+        );
+        assert_eq!(
+            Anchor::BottomLeft.flipped(true, false),
+            Vec2::new(0.5, -0.5)
+        );
+        assert_eq!(
+            Anchor::Custom(Vec2::new(0.25, 0.125)).flipped(true, true),
+            Vec2::new(-0.25, -0.125)
+        );
+        assert_eq!(Anchor::TopRight.flipped(false, false), Vec2::new(0.5, 0.5));
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let mut animation = AtlasAnimation::new(4..7, 10.0);
+        assert_eq!(animation.index(), Some(4));
+        assert_eq!(play(&mut animation, 5), [5, 6, 4, 5, 6]);
+        assert!(!animation.finished());
+    }
+
+    #[test]
+    fn once_stops_at_the_end() {
+        let mut animation = AtlasAnimation {
+            mode: AnimationMode::Once,
+            ..AtlasAnimation::new([2, 0, 1], 10.0)
+        };
+        assert_eq!(play(&mut animation, 2), [0, 1]);
+        assert!(!animation.finished());
+        assert_eq!(play(&mut animation, 2), [1, 1]);
+        assert!(animation.finished());
+
+        animation.restart();
+        assert_eq!(animation.index(), Some(2));
+        assert!(!animation.finished());
+    }
+
+    #[test]
+    fn ping_pong_flips_direction() {
+        let mut animation = AtlasAnimation {
+            mode: AnimationMode::PingPong,
+            ..AtlasAnimation::new(0..3, 10.0)
+        };
+        assert_eq!(play(&mut animation, 7), [1, 2, 1, 0, 1, 2, 1]);
+
+        // An animation with one frame stays on it.
+        let mut animation = AtlasAnimation {
+            mode: AnimationMode::PingPong,
+            ..AtlasAnimation::new([5], 10.0)
+        };
+        assert_eq!(play(&mut animation, 3), [5, 5, 5]);
+    }
+
+    #[test]
+    fn fps_changes_and_pauses() {
+        let mut animation = AtlasAnimation::new(0..10, 4.0);
+        // Part of a frame carries over to the next update.
+        animation.update(0.125);
+        animation.update(0.1875);
+        assert_eq!(animation.index(), Some(1));
+
+        animation.fps = 16.0;
+        animation.update(0.1875);
+        assert_eq!(animation.index(), Some(5));
+
+        animation.playing = false;
+        animation.update(1.0);
+        assert_eq!(animation.index(), Some(4));
+    }
+
+    #[test]
+    fn animation_bank() {
+        let mut bank = AnimationBank::default()
+            .with_clip(
+                "idle",
+                AnimationClip {
+                    frames: vec![0, 1],
+                    fps: 10.0,
+                    mode: AnimationMode::Loop,
+                },
+            )
+            .with_clip(
+                "jump",
+                AnimationClip {
+                    frames: vec![5, 6, 7],
+                    fps: 10.0,
+                    mode: AnimationMode::Once,
+                },
+            );
+        let play = |bank: &mut AnimationBank, frames: usize| {
+            (0..frames)
+                .map(|_| {
+                    bank.update(0.1);
+                    bank.index().unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The first update starts the clip.
+        assert_eq!(play(&mut bank, 4), [0, 1, 0, 1]);
+
+        // Switching clips takes effect right away, from the first frame.
+        bank.play("jump");
+        assert_eq!(bank.index(), Some(5));
+        assert!(!bank.finished());
+        assert_eq!(play(&mut bank, 4), [5, 6, 7, 7]);
+        assert!(bank.finished());
+
+        // Playing the same clip again doesn't restart it.
+        bank.play("jump");
+        assert!(bank.finished());
+        bank.restart();
+        assert_eq!(play(&mut bank, 1), [6]);
+
+        bank.play("missing");
+        assert_eq!(bank.index(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_animation_bank() {
+        let bank: AnimationBank = serde_yaml::from_str(
+            "
+            clips:
+              idle:
+                frames: [0, 1, 2, 3]
+                fps: 6
+              jump:
+                frames: [8, 9, 10]
+                fps: 12
+                mode: once
+            ",
+        )
+        .unwrap();
+        assert_eq!(bank.current, Key::new("idle").unwrap());
+        assert_eq!(bank.index(), Some(0));
+        assert_eq!(
+            bank.clips[&Key::new("jump").unwrap()],
+            AnimationClip {
+                frames: vec![8, 9, 10],
+                fps: 12.0,
+                mode: AnimationMode::Once,
+            }
+        );
+    }
+
+    #[test]
+    fn animation_system() {
+        let mut world = World::new();
+        let entity = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut animations: CompMut<AtlasAnimation>,
+                 mut atlas_sprites: CompMut<AtlasSprite>| {
+                    let entity = entities.create();
+                    animations.insert(entity, AtlasAnimation::new(3..6, 4.0));
+                    atlas_sprites.insert(entity, AtlasSprite::default());
+                    entity
+                },
+            )
+            .unwrap();
+
+        world.resources.init::<Time>();
+        let mut indices = Vec::new();
+        for _ in 0..4 {
+            world
+                .resources
+                .get::<Time>()
+                .borrow_mut()
+                .advance_exact(Duration::from_millis(250));
+            world.run_system(atlas_animation_system).unwrap();
+            let atlas_sprites = world.components.get::<AtlasSprite>();
+            indices.push(atlas_sprites.borrow().get(entity).unwrap().index);
+        }
+        assert_eq!(indices, [4, 5, 3, 4]);
+    }
 }
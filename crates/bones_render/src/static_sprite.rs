@@ -0,0 +1,89 @@
+//! Static sprite hint component.
+
+use crate::prelude::*;
+
+/// Marks a [`Sprite`] that never moves or changes, such as the decorations of a level.
+///
+/// Renderers may cache the render data of static sprites, and only sync it again when one of the
+/// components that they are drawn with changes or is removed. Removing the marker makes the sprite
+/// dynamic again. Mutably accessing the components of a static sprite counts as a change, even if
+/// nothing was written, so static sprites should not be iterated over mutably every frame.
+///
+/// Only [`Sprite`]s can be static for now. The marker is ignored on other renderables, and on
+/// [repeated][Sprite::repeat] sprites.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn spawn_decoration(
+///     mut entities: ResMut<Entities>,
+///     mut sprites: CompMut<Sprite>,
+///     mut transforms: CompMut<Transform>,
+///     mut statics: CompMut<Static>,
+/// ) {
+///     let grass = entities.create();
+///     sprites.insert(
+///         grass,
+///         Sprite {
+///             image: Handle::new("grass.png", None),
+///             ..default()
+///         },
+///     );
+///     transforms.insert(grass, Transform::from_translation(Vec3::new(40.0, 0.0, 0.0)));
+///     statics.insert(grass, Static);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, TypeUlid)]
+#[ulid = "01GQW3K8N5T2RZC7XM4HJ9BVDE"]
+#[repr(C)]
+pub struct Static;
+
+impl Static {
+    /// Returns `true` if the render data of a static sprite has to be synced again, because one of
+    /// the components that it is drawn with was added or changed since `last_sync`.
+    ///
+    /// The `ticks` are the change ticks of the components of the sprite, or `None` for components
+    /// that it doesn't have. Every sprite has to be synced when it has never been synced before,
+    /// and `last_sync` is `None`.
+    pub fn needs_sync(
+        ticks: impl IntoIterator<Item = Option<ComponentTicks>>,
+        last_sync: Option<Tick>,
+        this_sync: Tick,
+    ) -> bool {
+        ticks
+            .into_iter()
+            .flatten()
+            .any(|ticks| ticks.is_changed(last_sync, this_sync))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn needs_sync() {
+        let synced = ComponentTicks::new(Tick::new(5));
+        let changed = ComponentTicks::new(Tick::new(12));
+        let last_sync = Some(Tick::new(10));
+        let this_sync = Tick::new(20);
+
+        assert!(!Static::needs_sync(
+            [Some(synced), None],
+            last_sync,
+            this_sync
+        ));
+        assert!(Static::needs_sync(
+            [Some(synced), Some(changed)],
+            last_sync,
+            this_sync
+        ));
+        // A sprite that has never been synced needs to be synced.
+        assert!(Static::needs_sync([Some(synced)], None, this_sync));
+        // The changes made while the sprite was being synced have already been synced.
+        assert!(!Static::needs_sync(
+            [Some(ComponentTicks::new(Tick::new(10)))],
+            last_sync,
+            this_sync
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+//! Projections of tile layers.
+
+use crate::prelude::*;
+
+/// How the grid of a [`TileLayer`] is laid out in the world.
+///
+/// The projection changes where the tiles are drawn, and which tile is at a position of the world,
+/// with [`TileLayer::tile_to_world()`] and [`TileLayer::world_to_tile()`]. The
+/// [`tile_size`][TileLayer::tile_size] is the size of a cell of the grid, which is the size of the
+/// diamond of an isometric tile. The images of the tiles may be taller than the grid, such as for
+/// walls, in which case they're drawn up from the bottom corner of their diamond.
+///
+/// Isometric tiles overlap, so they're drawn from the back to the front, by their
+/// [`depth`][TileLayer::tile_depth], and so are hexes, in case their images are taller than them.
+///
+/// [`ChunkedTileLayer`]s are always orthogonal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TileProjection {
+    /// A grid of squares, with `+x` going right and `+y` going up.
+    #[default]
+    Orthogonal,
+    /// A diamond of isometric tiles, with `+x` going down and to the right, and `+y` going up and
+    /// to the right.
+    ///
+    /// The tile at `(0, 0)` is the left corner of the diamond, and the tile at
+    /// `(grid_size.x - 1, 0)` is its bottom corner.
+    IsometricDiamond,
+    /// Rows of isometric tiles, that fit together in a rectangle, with `+x` going right and `+y`
+    /// going up by half a tile.
+    ///
+    /// Every other row is shifted to the right by half a tile, so that it fits between the rows
+    /// below and above it.
+    IsometricStaggered(TileStagger),
+    /// A grid of hexes, laid out as the [`HexGrid`] says.
+    ///
+    /// See the [`hex`][crate::hex] module for the helpers that work with the hexes.
+    Hexagonal(HexGrid),
+}
+
+/// The rows of an [`IsometricStaggered`][TileProjection::IsometricStaggered] tile layer that are
+/// shifted to the right by half a tile, or the rows or columns of a [`HexGrid`] with
+/// [`Offset`][HexCoords::Offset] coordinates that are shifted by half a hex.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TileStagger {
+    /// The rows with an odd `y`, or the columns with an odd `x`, are shifted.
+    #[default]
+    Odd,
+    /// The rows with an even `y`, or the columns with an even `x`, are shifted.
+    Even,
+}
+
+impl TileStagger {
+    /// Returns `true` if the row or column at the given `y` or `x` is shifted.
+    pub fn is_shifted(self, y: u32) -> bool {
+        match self {
+            TileStagger::Odd => y % 2 == 1,
+            TileStagger::Even => y % 2 == 0,
+        }
+    }
+}
+
+impl TileProjection {
+    /// Get the center of the cell of the tile at `pos`, relative to the bottom-left corner of a
+    /// layer with the given size.
+    ///
+    /// All of the cells of the layer are above and to the right of its bottom-left corner.
+    pub fn tile_center(self, pos: UVec2, grid_size: UVec2, tile_size: Vec2) -> Vec2 {
+        let pos = pos.as_vec2();
+        match self {
+            TileProjection::Orthogonal => (pos + 0.5) * tile_size,
+            TileProjection::IsometricDiamond => Vec2::new(
+                (pos.x + pos.y + 1.0) * tile_size.x / 2.0,
+                (pos.y - pos.x + grid_size.x as f32) * tile_size.y / 2.0,
+            ),
+            TileProjection::IsometricStaggered(stagger) => {
+                let shift = if stagger.is_shifted(pos.y as u32) {
+                    0.5
+                } else {
+                    0.0
+                };
+                Vec2::new(
+                    (pos.x + 0.5 + shift) * tile_size.x,
+                    (pos.y + 1.0) * tile_size.y / 2.0,
+                )
+            }
+            TileProjection::Hexagonal(grid) => {
+                grid.hex_center(grid.to_axial(pos.as_uvec2()), tile_size)
+            }
+        }
+    }
+
+    /// Get the tile whose cell has the given point, relative to the bottom-left corner of a layer
+    /// with the given size, or `None` if it's outside of the layer.
+    pub fn tile_at(self, point: Vec2, grid_size: UVec2, tile_size: Vec2) -> Option<UVec2> {
+        let pos = match self {
+            TileProjection::Orthogonal => (point / tile_size).floor(),
+            TileProjection::IsometricDiamond => {
+                // Rotate the diamonds into squares, in units of half a tile.
+                let u = point.x / (tile_size.x / 2.0);
+                let v = point.y / (tile_size.y / 2.0) - grid_size.x as f32;
+                Vec2::new((u - v) / 2.0, (u + v) / 2.0).floor()
+            }
+            TileProjection::IsometricStaggered(stagger) => {
+                // The point is in the diamond of the row of half tiles that it's in, or of the row
+                // below, and each row only has one diamond that could have it.
+                let row = (point.y / (tile_size.y / 2.0)).floor();
+                [row, row - 1.0]
+                    .into_iter()
+                    .filter(|y| *y >= 0.0)
+                    .map(|y| {
+                        let shift = if stagger.is_shifted(y as u32) {
+                            0.5
+                        } else {
+                            0.0
+                        };
+                        let x = (point.x / tile_size.x - shift).floor();
+                        let center = Vec2::new(
+                            (x + 0.5 + shift) * tile_size.x,
+                            (y + 1.0) * tile_size.y / 2.0,
+                        );
+                        (Vec2::new(x, y), center)
+                    })
+                    .find(|(_, center)| {
+                        let offset = ((point - *center) / (tile_size / 2.0)).abs();
+                        offset.x + offset.y <= 1.0
+                    })?
+                    .0
+            }
+            TileProjection::Hexagonal(grid) => {
+                return grid
+                    .from_axial(grid.hex_at(point, tile_size))
+                    .filter(|pos| pos.cmplt(grid_size).all());
+            }
+        };
+        (pos.cmpge(Vec2::ZERO).all() && pos.cmplt(grid_size.as_vec2()).all())
+            .then(|| pos.as_uvec2())
+    }
+
+    /// Get the depth of the tile at `pos` in a layer with the given size, from `0.0` for the
+    /// tiles at the back to less than `1.0` for the tiles at the front.
+    ///
+    /// Tiles with a higher depth are drawn over the tiles with a lower depth, and the tiles of
+    /// orthogonal layers all have a depth of `0.0`.
+    pub fn depth(self, pos: UVec2, grid_size: UVec2) -> f32 {
+        let (pos, grid_size) = (pos.as_vec2(), grid_size.as_vec2());
+        match self {
+            TileProjection::Orthogonal => 0.0,
+            TileProjection::IsometricDiamond => {
+                (pos.x - pos.y + grid_size.y - 1.0) / (grid_size.x + grid_size.y - 1.0)
+            }
+            TileProjection::IsometricStaggered(_) => (grid_size.y - 1.0 - pos.y) / grid_size.y,
+            TileProjection::Hexagonal(grid) => grid.depth(pos.as_uvec2(), grid_size.as_uvec2()),
+        }
+    }
+}
+
+impl TileLayer {
+    /// Get the position in the world of the center of the cell of the tile at `pos`, for the
+    /// layer with the given transform, as its [`projection`][Self::projection] says.
+    ///
+    /// The rotation of the layer isn't taken into account.
+    pub fn tile_to_world(&self, transform: &Transform, pos: UVec2) -> Vec2 {
+        let center = self
+            .projection
+            .tile_center(pos, self.grid_size, self.tile_size);
+        transform.translation.truncate() + center * transform.scale.truncate()
+    }
+
+    /// Get the tile whose cell has the given position in the world, for the layer with the given
+    /// transform, or `None` if it's outside of the layer.
+    ///
+    /// The rotation of the layer isn't taken into account.
+    pub fn world_to_tile(&self, transform: &Transform, point: Vec2) -> Option<UVec2> {
+        let local = (point - transform.translation.truncate()) / transform.scale.truncate();
+        self.projection
+            .tile_at(local, self.grid_size, self.tile_size)
+    }
+
+    /// Get the depth of the tile at `pos`, which renderers add to the z of the layer to draw the
+    /// tiles in the right order, as its [`projection`][Self::projection] says.
+    ///
+    /// See [`TileProjection::depth()`].
+    pub fn tile_depth(&self, pos: UVec2) -> f32 {
+        self.projection.depth(pos, self.grid_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    const TILE_SIZE: Vec2 = Vec2::new(32.0, 16.0);
+
+    #[test]
+    fn orthogonal_tiles() {
+        let layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+        assert_eq!(layer.projection, TileProjection::Orthogonal);
+        let transform = Transform::from_translation(Vec3::new(100.0, 50.0, 0.0));
+        assert_eq!(
+            layer.tile_to_world(&transform, UVec2::new(1, 2)),
+            Vec2::new(124.0, 90.0)
+        );
+        assert_eq!(
+            layer.world_to_tile(&transform, Vec2::new(124.0, 90.0)),
+            Some(UVec2::new(1, 2))
+        );
+        assert_eq!(
+            layer.world_to_tile(&transform, Vec2::new(116.0, 66.0)),
+            Some(UVec2::new(1, 1))
+        );
+        assert_eq!(layer.world_to_tile(&transform, Vec2::new(99.0, 60.0)), None);
+        assert_eq!(
+            layer.world_to_tile(&transform, Vec2::new(164.0, 60.0)),
+            None
+        );
+        assert_eq!(layer.tile_depth(UVec2::new(3, 2)), 0.0);
+    }
+
+    #[test]
+    fn isometric_diamond_tiles() {
+        let projection = TileProjection::IsometricDiamond;
+        let grid_size = UVec2::new(3, 2);
+        let center = |x, y| projection.tile_center(UVec2::new(x, y), grid_size, TILE_SIZE);
+        // The left, bottom, right, and top corners of the diamond.
+        assert_eq!(center(0, 0), Vec2::new(16.0, 24.0));
+        assert_eq!(center(2, 0), Vec2::new(48.0, 8.0));
+        assert_eq!(center(2, 1), Vec2::new(64.0, 16.0));
+        assert_eq!(center(0, 1), Vec2::new(32.0, 32.0));
+
+        for x in 0..3 {
+            for y in 0..2 {
+                let pos = UVec2::new(x, y);
+                let c = center(x, y);
+                for offset in [
+                    Vec2::ZERO,
+                    Vec2::new(15.0, 0.0),
+                    Vec2::new(-15.0, 0.0),
+                    Vec2::new(0.0, 7.5),
+                    Vec2::new(0.0, -7.5),
+                    Vec2::new(7.0, 3.0),
+                ] {
+                    assert_eq!(
+                        projection.tile_at(c + offset, grid_size, TILE_SIZE),
+                        Some(pos)
+                    );
+                }
+            }
+        }
+        // Outside of the diamond, but inside of the rectangle around it.
+        assert_eq!(
+            projection.tile_at(Vec2::new(2.0, 2.0), grid_size, TILE_SIZE),
+            None
+        );
+
+        // The tiles are drawn from the top to the bottom of the screen.
+        let depth = |x, y| projection.depth(UVec2::new(x, y), grid_size);
+        assert_eq!(depth(0, 1), 0.0);
+        assert!(depth(0, 0) > depth(0, 1));
+        assert!(depth(1, 1) == depth(0, 0));
+        assert!(depth(2, 0) > depth(1, 0));
+        assert!(depth(2, 0) < 1.0);
+    }
+
+    #[test]
+    fn isometric_staggered_tiles() {
+        let projection = TileProjection::IsometricStaggered(TileStagger::Odd);
+        let grid_size = UVec2::new(2, 3);
+        let center = |x, y| projection.tile_center(UVec2::new(x, y), grid_size, TILE_SIZE);
+        assert_eq!(center(0, 0), Vec2::new(16.0, 8.0));
+        assert_eq!(center(0, 1), Vec2::new(32.0, 16.0));
+        assert_eq!(center(1, 2), Vec2::new(48.0, 24.0));
+
+        for x in 0..2 {
+            for y in 0..3 {
+                let pos = UVec2::new(x, y);
+                let c = center(x, y);
+                for offset in [
+                    Vec2::ZERO,
+                    Vec2::new(15.0, 0.0),
+                    Vec2::new(-15.0, 0.0),
+                    Vec2::new(0.0, 7.5),
+                    Vec2::new(0.0, -7.5),
+                ] {
+                    assert_eq!(
+                        projection.tile_at(c + offset, grid_size, TILE_SIZE),
+                        Some(pos)
+                    );
+                }
+            }
+        }
+        // The point between the corners of the two bottom tiles is in the shifted row above them.
+        assert_eq!(
+            projection.tile_at(Vec2::new(32.0, 8.0), grid_size, TILE_SIZE),
+            Some(UVec2::new(0, 1))
+        );
+        // The left side of the shifted rows isn't part of the layer.
+        assert_eq!(
+            projection.tile_at(Vec2::new(2.0, 16.0), grid_size, TILE_SIZE),
+            None
+        );
+
+        let stagger = TileProjection::IsometricStaggered(TileStagger::Even);
+        assert_eq!(
+            stagger.tile_center(UVec2::ZERO, grid_size, TILE_SIZE),
+            Vec2::new(32.0, 8.0)
+        );
+        assert!(
+            projection.depth(UVec2::new(0, 0), grid_size)
+                > projection.depth(UVec2::new(1, 1), grid_size)
+        );
+    }
+}
@@ -1,23 +1,194 @@
 //! Tile map rendering components.
 
+use std::{collections::HashMap, ops::Range, time::Duration};
+
 use crate::prelude::*;
 
 /// A tilemap layer component.
+///
+/// Tiles such as water, lava, and torches can be animated with the
+/// [`animations`][Self::animations] of the layer. Every tile of the layer that shows the first
+/// frame of an animation plays it, in sync with the other tiles of the layer, without changing
+/// the [`Tile`]s themselves:
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn animate_water(entities: Res<Entities>, mut tile_layers: CompMut<TileLayer>) {
+///     for (_, mut layer) in entities.iter_with(&mut tile_layers) {
+///         // The tiles that show the water tile 12 cycle through the tiles 12 to 15.
+///         layer
+///             .animations
+///             .insert(12, TileAnimation::new(12..16, 4.0));
+///     }
+/// }
+/// ```
+///
+/// The clock of the layer is advanced by the [`tile_animation_system`].
+///
+/// Every tile slot also has a [`meta`][Self::meta] value, for the game logic that needs to know
+/// what is at a position, such as whether it's solid, a ladder, or spikes. It's up to the game
+/// what the values mean, such as a set of bit flags, but `0` means that the slot has no metadata.
+/// The metadata is kept along with the tiles when the layer is [resized][Self::resize], and is
+/// cloned along with the rest of the layer when the world is.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// const SOLID: u16 = 1 << 0;
+/// const SPIKES: u16 = 1 << 1;
+///
+/// /// Returns `true` if the player touching the given tiles is hurt.
+/// fn is_hurt(layer: &TileLayer, min: UVec2, max: UVec2) -> bool {
+///     layer
+///         .iter_meta_in_rect(min, max)
+///         .any(|(_, meta)| meta & SPIKES != 0)
+/// }
+/// ```
+///
+/// The tile slots can also be tinted, such as to darken the tiles in the shadows, or to blend
+/// biomes together, without making more tileset art. The [`tints`][Self::tints] multiply the
+/// colors of the tiles' images, and belong to the slots instead of to the tiles, so that the tile
+/// entities can still be shared, and the animated tiles keep their tint on every frame. Like the
+/// metadata, the tints are kept when the layer is resized, and are cloned with the world.
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// /// Darken the tiles in a cave, and the tiles near its entrance a bit less.
+/// fn darken_cave(layer: &mut TileLayer, min: UVec2, max: UVec2) {
+///     layer.fill_rect_tint(min, max, Color::rgb(0.4, 0.4, 0.5));
+///     layer.fill_rect_tint(min, UVec2::new(min.x + 1, max.y), Color::rgb(0.7, 0.7, 0.75));
+/// }
+/// ```
 #[derive(Clone, Debug, TypeUlid)]
 #[ulid = "01GNF7SRDRN4K8HPW32JAHKMX1"]
 pub struct TileLayer {
     /// The vector of tile slots in this layer.
     pub tiles: Vec<Option<Entity>>,
+    /// The metadata of the tile slots in this layer, in the same order as the
+    /// [`tiles`][Self::tiles].
+    pub meta: Vec<u16>,
     /// The size of the layer in tiles.
     pub grid_size: UVec2,
     /// The size of each tile in the layer.
     pub tile_size: Vec2,
+    /// How the grid of the layer is laid out in the world.
+    pub projection: TileProjection,
     /// The texture atlas to use for the layer
     pub atlas: Handle<Atlas>,
+    /// The animations of the tiles of the layer, by the index in the atlas of the tile that they
+    /// replace.
+    pub animations: HashMap<usize, TileAnimation>,
+    /// How long the tile animations of the layer have been playing for.
+    ///
+    /// This is a [`Duration`] so that it adds up exactly the same way every time the [`Time`] is
+    /// advanced by a fixed step, and the animations stay deterministic.
+    pub animation_time: Duration,
+    /// The tints of the tile slots in this layer, in the same order as the
+    /// [`tiles`][Self::tiles], or empty if none of the slots have been tinted.
+    ///
+    /// The tints are only stored once a slot is tinted, so the layers that aren't tinted don't
+    /// cost anything more, and their tiles are drawn [white][Color::WHITE].
+    pub tints: Vec<Color>,
+}
+
+/// An animation of the tiles of a [`TileLayer`] that loops through a list of frames.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct TileAnimation {
+    /// The indices in the atlas of the frames of the animation, in the order that they are played.
+    pub frames: Vec<usize>,
+    /// The number of frames played per second.
+    pub fps: f32,
+}
+
+/// Get the index in the atlas of the image that a tile with the given index is drawn with, in a
+/// layer with the given animations and animation time.
+#[inline]
+pub(crate) fn animated_idx(
+    animations: &HashMap<usize, TileAnimation>,
+    animation_time: Duration,
+    idx: usize,
+) -> usize {
+    if animations.is_empty() {
+        return idx;
+    }
+    animations
+        .get(&idx)
+        .and_then(|animation| animation.index(animation_time))
+        .unwrap_or(idx)
+}
+
+impl TileAnimation {
+    /// Create an animation that plays the given frames in a loop.
+    pub fn new(frames: impl IntoIterator<Item = usize>, fps: f32) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+            fps,
+        }
+    }
+
+    /// Get the index in the atlas of the frame that is shown when the animation has been playing
+    /// for the given time, or `None` if there are no frames.
+    pub fn index(&self, time: Duration) -> Option<usize> {
+        let frame = (time.as_secs_f64() * self.fps as f64).max(0.0) as u64;
+        let frame = frame.checked_rem(self.frames.len() as u64)?;
+        self.frames.get(frame as usize).copied()
+    }
 }
 
 /// A tilemap tile component.
-#[derive(Clone, Debug, TypeUlid, Default)]
+///
+/// The tile image can be flipped and rotated, so that the same tileset art can be reused for
+/// walls and corners facing every direction. The image is flipped first, and then rotated
+/// clockwise, so these are all eight of the orientations of a tile:
+///
+/// ```
+/// # use bones_render::prelude::*;
+/// fn spawn_orientations(
+///     mut entities: ResMut<Entities>,
+///     mut tiles: CompMut<Tile>,
+///     mut tile_layers: CompMut<TileLayer>,
+///     mut transforms: CompMut<Transform>,
+/// ) {
+///     let mut layer = TileLayer::new(
+///         UVec2::new(8, 1),
+///         Vec2::splat(16.0),
+///         Handle::new("arrows.atlas.yaml", None),
+///     );
+///     let rotations = [
+///         TileRotation::None,
+///         TileRotation::Clockwise90,
+///         TileRotation::Clockwise180,
+///         TileRotation::Clockwise270,
+///     ];
+///     for (x, (flip_x, rotation)) in [false, true]
+///         .into_iter()
+///         .flat_map(|flip_x| rotations.map(|rotation| (flip_x, rotation)))
+///         .enumerate()
+///     {
+///         let tile = entities.create();
+///         tiles.insert(
+///             tile,
+///             Tile {
+///                 idx: 0,
+///                 flip_x,
+///                 rotation,
+///                 ..default()
+///             },
+///         );
+///         layer.set(UVec2::new(x as u32, 0), Some(tile));
+///     }
+///     let layer_entity = entities.create();
+///     tile_layers.insert(layer_entity, layer);
+///     transforms.insert(layer_entity, default());
+/// }
+/// ```
+///
+/// Flipping the tile both horizontally and vertically is the same as rotating it by 180 degrees.
+///
+/// When it is deserialized, the fields that are missing are set to their default values.
+#[derive(Clone, Debug, TypeUlid, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[ulid = "01GNZHDZV61TFPEE4GDJY4SRAM"]
 pub struct Tile {
     /// The tile index in the tilemap texture.
@@ -26,6 +197,161 @@ pub struct Tile {
     pub flip_x: bool,
     /// Whether or not to flip tile vertically.
     pub flip_y: bool,
+    /// How far the tile is rotated clockwise, after it has been flipped.
+    pub rotation: TileRotation,
+}
+
+/// The clockwise rotation of a [`Tile`], in steps of 90 degrees.
+///
+/// It is (de)serialized as the number of degrees, `0`, `90`, `180`, or `270`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u16", into = "u16")
+)]
+#[repr(u8)]
+pub enum TileRotation {
+    /// The tile isn't rotated.
+    #[default]
+    None,
+    /// The tile is rotated by 90 degrees clockwise.
+    Clockwise90,
+    /// The tile is rotated by 180 degrees.
+    Clockwise180,
+    /// The tile is rotated by 270 degrees clockwise, or 90 degrees counter-clockwise.
+    Clockwise270,
+}
+
+impl TileRotation {
+    /// Get the rotation by the given number of degrees clockwise, or `None` if it isn't `0`, `90`,
+    /// `180`, or `270`.
+    pub fn from_degrees(degrees: u16) -> Option<Self> {
+        Some(match degrees {
+            0 => Self::None,
+            90 => Self::Clockwise90,
+            180 => Self::Clockwise180,
+            270 => Self::Clockwise270,
+            _ => return None,
+        })
+    }
+
+    /// Get the number of degrees that the rotation turns the tile clockwise.
+    pub fn degrees(self) -> u16 {
+        match self {
+            Self::None => 0,
+            Self::Clockwise90 => 90,
+            Self::Clockwise180 => 180,
+            Self::Clockwise270 => 270,
+        }
+    }
+}
+
+impl From<TileRotation> for u16 {
+    fn from(rotation: TileRotation) -> Self {
+        rotation.degrees()
+    }
+}
+
+impl TryFrom<u16> for TileRotation {
+    type Error = TileRotationError;
+
+    fn try_from(degrees: u16) -> Result<Self, Self::Error> {
+        Self::from_degrees(degrees).ok_or(TileRotationError { degrees })
+    }
+}
+
+/// An error returned when converting a number of degrees that isn't a multiple of 90 degrees
+/// from `0` to `270` into a [`TileRotation`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileRotationError {
+    /// The number of degrees that was converted.
+    pub degrees: u16,
+}
+
+impl std::fmt::Display for TileRotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Tile rotation of {} degrees isn't one of 0, 90, 180, or 270 degrees.",
+            self.degrees
+        )
+    }
+}
+
+impl std::error::Error for TileRotationError {}
+
+/// The flip flags of a tile in a Tiled map, which are stored in the highest bits of its global tile
+/// ID.
+///
+/// Tiled transposes the tile image first if it is flipped diagonally, and then flips it
+/// horizontally and vertically. Use [`Tile::set_tiled_flips()`] to convert them to the flips and
+/// the rotation of a [`Tile`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TiledFlips {
+    /// Whether the tile is flipped horizontally.
+    pub horizontal: bool,
+    /// Whether the tile is flipped vertically.
+    pub vertical: bool,
+    /// Whether the tile is flipped diagonally, from the top-left to the bottom-right corner.
+    pub diagonal: bool,
+}
+
+impl TiledFlips {
+    const HORIZONTAL_BIT: u32 = 1 << 31;
+    const VERTICAL_BIT: u32 = 1 << 30;
+    const DIAGONAL_BIT: u32 = 1 << 29;
+    /// The bit that rotates hexagonal tiles by 120 degrees, which isn't supported.
+    const HEXAGONAL_BIT: u32 = 1 << 28;
+
+    /// Split a Tiled global tile ID into the ID without the flag bits, and the flips.
+    pub fn from_gid(gid: u32) -> (u32, Self) {
+        let flags = Self::HORIZONTAL_BIT | Self::VERTICAL_BIT | Self::DIAGONAL_BIT;
+        let flips = Self {
+            horizontal: gid & Self::HORIZONTAL_BIT != 0,
+            vertical: gid & Self::VERTICAL_BIT != 0,
+            diagonal: gid & Self::DIAGONAL_BIT != 0,
+        };
+        (gid & !(flags | Self::HEXAGONAL_BIT), flips)
+    }
+}
+
+impl Tile {
+    /// Set the flips and the rotation of the tile to the orientation of a tile in a Tiled map.
+    pub fn set_tiled_flips(&mut self, flips: TiledFlips) {
+        let TiledFlips {
+            horizontal,
+            vertical,
+            diagonal,
+        } = flips;
+        if diagonal {
+            // Transposing the image is the same as flipping it vertically and rotating it
+            // clockwise, and the other flips are applied to the rotated image.
+            self.flip_x = vertical;
+            self.flip_y = !horizontal;
+            self.rotation = TileRotation::Clockwise90;
+        } else {
+            self.flip_x = horizontal;
+            self.flip_y = vertical;
+            self.rotation = TileRotation::None;
+        }
+    }
+
+    /// Get the flips of a tile in a Tiled map that has the same orientation as the tile.
+    pub fn tiled_flips(&self) -> TiledFlips {
+        let (flip_x, flip_y) = (self.flip_x, self.flip_y);
+        let (horizontal, vertical, diagonal) = match self.rotation {
+            TileRotation::None => (flip_x, flip_y, false),
+            TileRotation::Clockwise90 => (!flip_y, flip_x, true),
+            TileRotation::Clockwise180 => (!flip_x, !flip_y, false),
+            TileRotation::Clockwise270 => (flip_y, !flip_x, true),
+        };
+        TiledFlips {
+            horizontal,
+            vertical,
+            diagonal,
+        }
+    }
 }
 
 impl TileLayer {
@@ -38,12 +364,26 @@ impl TileLayer {
         }
         Self {
             tiles,
+            meta: vec![0; tile_count],
             grid_size,
             tile_size,
+            projection: TileProjection::Orthogonal,
             atlas,
+            animations: HashMap::new(),
+            animation_time: Duration::ZERO,
+            tints: Vec::new(),
         }
     }
 
+    /// Get the index in the atlas of the image that a tile with the given index is drawn with,
+    /// which is the current frame of its [animation][Self::animations] if it has one.
+    ///
+    /// Renderers should use this instead of the [`idx`][Tile::idx] of the tile.
+    #[inline]
+    pub fn animated_idx(&self, idx: usize) -> usize {
+        animated_idx(&self.animations, self.animation_time, idx)
+    }
+
     #[inline]
     fn idx(&self, pos: UVec2) -> usize {
         (self.grid_size.x * pos.y + pos.x) as usize
@@ -66,4 +406,608 @@ impl TileLayer {
             )
         }) = entity;
     }
+
+    /// Set the tiles at many positions at once, such as when generating a map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the positions is out of range, like [`set()`][Self::set].
+    pub fn set_many(&mut self, tiles: impl IntoIterator<Item = (UVec2, Option<Entity>)>) {
+        for (pos, entity) in tiles {
+            let idx = self.idx(pos);
+            match self.tiles.get_mut(idx) {
+                Some(slot) if pos.x < self.grid_size.x => *slot = entity,
+                _ => panic!(
+                    "Tile pos out of range of tile size: pos {:?} size {:?}",
+                    pos, self.grid_size
+                ),
+            }
+        }
+    }
+
+    /// Set every tile in the rectangle from `min` to `max` to the same entity, including `min`
+    /// but not `max`.
+    ///
+    /// The parts of the rectangle that are outside of the layer are left out. Many slots can hold
+    /// the same tile entity, which is cheaper than creating an entity for every one of them.
+    pub fn fill_rect(&mut self, min: UVec2, max: UVec2, entity: Option<Entity>) {
+        for row in self.rows_in_rect(min, max) {
+            self.tiles[row].fill(entity);
+        }
+    }
+
+    /// Get the metadata of the tile slot at the given position, or `0` if it is out of range.
+    pub fn get_meta(&self, pos: UVec2) -> u16 {
+        if !pos.cmplt(self.grid_size).all() {
+            return 0;
+        }
+        self.meta[self.idx(pos)]
+    }
+
+    /// Set the metadata of the tile slot at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of range, like [`set()`][Self::set].
+    pub fn set_meta(&mut self, pos: UVec2, meta: u16) {
+        if !pos.cmplt(self.grid_size).all() {
+            panic!(
+                "Tile pos out of range of tile size: pos {:?} size {:?}",
+                pos, self.grid_size
+            );
+        }
+        let idx = self.idx(pos);
+        self.meta[idx] = meta;
+    }
+
+    /// Set the metadata of every tile slot in the rectangle from `min` to `max`, including `min`
+    /// but not `max`, like [`fill_rect()`][Self::fill_rect].
+    pub fn fill_meta_rect(&mut self, min: UVec2, max: UVec2, meta: u16) {
+        for row in self.rows_in_rect(min, max) {
+            self.meta[row].fill(meta);
+        }
+    }
+
+    /// Get the tint of the tile slot at the given position, which is [white][Color::WHITE] if the
+    /// slot hasn't been tinted or is out of range.
+    pub fn get_tint(&self, pos: UVec2) -> Color {
+        if !pos.cmplt(self.grid_size).all() {
+            return Color::WHITE;
+        }
+        self.tints
+            .get(self.idx(pos))
+            .copied()
+            .unwrap_or(Color::WHITE)
+    }
+
+    /// Set the tint of the tile slot at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of range, like [`set()`][Self::set].
+    pub fn set_tint(&mut self, pos: UVec2, tint: Color) {
+        if !pos.cmplt(self.grid_size).all() {
+            panic!(
+                "Tile pos out of range of tile size: pos {:?} size {:?}",
+                pos, self.grid_size
+            );
+        }
+        let idx = self.idx(pos);
+        if self.store_tints(tint) {
+            self.tints[idx] = tint;
+        }
+    }
+
+    /// Set the tint of every tile slot in the rectangle from `min` to `max`, including `min` but
+    /// not `max`, like [`fill_rect()`][Self::fill_rect].
+    pub fn fill_rect_tint(&mut self, min: UVec2, max: UVec2, tint: Color) {
+        if !self.store_tints(tint) {
+            return;
+        }
+        for row in self.rows_in_rect(min, max) {
+            self.tints[row].fill(tint);
+        }
+    }
+
+    /// Remove the tints of all of the tile slots.
+    pub fn clear_tints(&mut self) {
+        self.tints = Vec::new();
+    }
+
+    /// Make sure that there is a tint for every slot, before slots are set to the given tint, and
+    /// get whether they need to be set, which they don't if the tint is white and the layer isn't
+    /// tinted.
+    fn store_tints(&mut self, tint: Color) -> bool {
+        if self.tints.is_empty() {
+            if tint == Color::WHITE {
+                return false;
+            }
+            self.tints = vec![Color::WHITE; self.tiles.len()];
+        }
+        true
+    }
+
+    /// Iterate over the positions and the metadata of the tile slots in the rectangle from `min`
+    /// to `max`, including `min` but not `max`, that have metadata, such as to find the solid
+    /// tiles that a player overlaps.
+    ///
+    /// The parts of the rectangle that are outside of the layer are left out.
+    pub fn iter_meta_in_rect(
+        &self,
+        min: UVec2,
+        max: UVec2,
+    ) -> impl Iterator<Item = (UVec2, u16)> + '_ {
+        let y_range = min.y..max.y.min(self.grid_size.y);
+        self.rows_in_rect(min, max)
+            .zip(y_range)
+            .flat_map(move |(row, y)| {
+                self.meta[row]
+                    .iter()
+                    .zip(min.x..)
+                    .filter(|(meta, _)| **meta != 0)
+                    .map(move |(meta, x)| (UVec2::new(x, y), *meta))
+            })
+    }
+
+    /// Resize the layer to the given number of tiles.
+    ///
+    /// The tiles, the metadata, and the tints keep their positions from the top-left of the layer.
+    /// The tiles that are outside of the new size are removed, and the new tile slots are empty.
+    pub fn resize(&mut self, grid_size: UVec2) {
+        let mut resized = TileLayer::new(grid_size, self.tile_size, self.atlas.clone());
+        let common = self.grid_size.min(grid_size);
+        for y in 0..common.y {
+            let (old, new) = (self.idx(UVec2::new(0, y)), resized.idx(UVec2::new(0, y)));
+            let (old, new) = (old..old + common.x as usize, new..new + common.x as usize);
+            resized.tiles[new.clone()].copy_from_slice(&self.tiles[old.clone()]);
+            resized.meta[new.clone()].copy_from_slice(&self.meta[old.clone()]);
+            if !self.tints.is_empty() {
+                resized.tints.resize(resized.tiles.len(), Color::WHITE);
+                resized.tints[new].copy_from_slice(&self.tints[old]);
+            }
+        }
+        self.tiles = resized.tiles;
+        self.meta = resized.meta;
+        self.tints = resized.tints;
+        self.grid_size = grid_size;
+    }
+
+    /// Get the ranges of the [`tiles`][Self::tiles] of each row of the rectangle from `min` to
+    /// `max`, with the parts of the rectangle that are outside of the layer left out.
+    fn rows_in_rect(&self, min: UVec2, max: UVec2) -> impl Iterator<Item = Range<usize>> {
+        let max = max.min(self.grid_size);
+        // A rectangle that is entirely outside of the layer has no tiles in it.
+        let min = min.min(max);
+        let width = self.grid_size.x as usize;
+        let columns = min.x as usize..max.x as usize;
+        (min.y..max.y).map(move |y| {
+            let row = y as usize * width;
+            row + columns.start..row + columns.end
+        })
+    }
+
+    /// Get the tiles of the row at the given `y`, from left to right, or `None` if it is out of
+    /// range.
+    pub fn row(&self, y: u32) -> Option<&[Option<Entity>]> {
+        if y >= self.grid_size.y {
+            return None;
+        }
+        let start = self.idx(UVec2::new(0, y));
+        Some(&self.tiles[start..start + self.grid_size.x as usize])
+    }
+
+    /// Get the tiles of the row at the given `y` mutably, from left to right, or `None` if it is
+    /// out of range.
+    pub fn row_mut(&mut self, y: u32) -> Option<&mut [Option<Entity>]> {
+        if y >= self.grid_size.y {
+            return None;
+        }
+        let start = self.idx(UVec2::new(0, y));
+        Some(&mut self.tiles[start..start + self.grid_size.x as usize])
+    }
+
+    /// Iterate over the tiles of the column at the given `x`, from top to bottom.
+    ///
+    /// The iterator is empty if the column is out of range.
+    pub fn col(&self, x: u32) -> impl Iterator<Item = &Option<Entity>> {
+        let (skip, step) = self.col_range(x);
+        self.tiles.iter().skip(skip).step_by(step)
+    }
+
+    /// Iterate mutably over the tiles of the column at the given `x`, from top to bottom.
+    ///
+    /// The iterator is empty if the column is out of range.
+    pub fn col_mut(&mut self, x: u32) -> impl Iterator<Item = &mut Option<Entity>> {
+        let (skip, step) = self.col_range(x);
+        self.tiles.iter_mut().skip(skip).step_by(step)
+    }
+
+    /// Get the number of tiles to skip and the step to iterate over the column at the given `x`.
+    fn col_range(&self, x: u32) -> (usize, usize) {
+        let skip = if x < self.grid_size.x {
+            x as usize
+        } else {
+            self.tiles.len()
+        };
+        (skip, self.grid_size.x.max(1) as usize)
+    }
+}
+
+/// System that advances the animation clocks of the [`TileLayer`]s and [`ChunkedTileLayer`]s that
+/// have [`animations`][TileLayer::animations].
+///
+/// The tiles themselves aren't changed, so this is cheap however many tiles the layers have.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], if they use it.
+pub fn tile_animation_system(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut tile_layers: CompMut<TileLayer>,
+    mut chunked_tile_layers: CompMut<ChunkedTileLayer>,
+) {
+    let delta = time.delta();
+    for (_, mut layer) in entities.iter_with(&mut tile_layers) {
+        if !layer.animations.is_empty() {
+            layer.animation_time += delta;
+        }
+    }
+    for (_, mut layer) in entities.iter_with(&mut chunked_tile_layers) {
+        if !layer.animations.is_empty() {
+            layer.animation_time += delta;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn tiled_flips_round_trip() {
+        let rotations = [
+            TileRotation::None,
+            TileRotation::Clockwise90,
+            TileRotation::Clockwise180,
+            TileRotation::Clockwise270,
+        ];
+        let mut orientations = Vec::new();
+        for flip_x in [false, true] {
+            for flip_y in [false, true] {
+                for rotation in rotations {
+                    let tile = Tile {
+                        flip_x,
+                        flip_y,
+                        rotation,
+                        ..default()
+                    };
+                    let flips = tile.tiled_flips();
+                    let mut converted = Tile::default();
+                    converted.set_tiled_flips(flips);
+                    assert_eq!(converted.tiled_flips(), flips);
+                    if !orientations.contains(&flips) {
+                        orientations.push(flips);
+                    }
+                }
+            }
+        }
+        // Every orientation of a tile can be represented both ways.
+        assert_eq!(orientations.len(), 8);
+
+        // Flipping diagonally transposes the image, which is flipping it vertically and rotating
+        // it clockwise.
+        let mut tile = Tile::default();
+        tile.set_tiled_flips(TiledFlips {
+            diagonal: true,
+            ..default()
+        });
+        assert_eq!(
+            tile,
+            Tile {
+                flip_y: true,
+                rotation: TileRotation::Clockwise90,
+                ..default()
+            }
+        );
+        // Rotating by 180 degrees is flipping both ways.
+        let upside_down = Tile {
+            rotation: TileRotation::Clockwise180,
+            ..default()
+        };
+        assert_eq!(
+            upside_down.tiled_flips(),
+            TiledFlips {
+                horizontal: true,
+                vertical: true,
+                diagonal: false,
+            }
+        );
+    }
+
+    #[test]
+    fn tile_animations() {
+        let water = TileAnimation::new(12..16, 4.0);
+        assert_eq!(water.index(Duration::ZERO), Some(12));
+        assert_eq!(water.index(Duration::from_millis(260)), Some(13));
+        // The animation loops.
+        assert_eq!(water.index(Duration::from_millis(1100)), Some(12));
+        assert_eq!(
+            TileAnimation::new([], 4.0).index(Duration::from_secs(1)),
+            None
+        );
+
+        let mut layer = TileLayer::new(UVec2::new(2, 2), Vec2::splat(16.0), default());
+        layer.animations.insert(12, water);
+        layer.animation_time = Duration::from_millis(500);
+        assert_eq!(layer.animated_idx(12), 14);
+        // The other tiles aren't animated.
+        assert_eq!(layer.animated_idx(13), 13);
+    }
+
+    #[test]
+    fn animate_layers() {
+        let mut world = World::new();
+        world.resources.init::<Time>();
+        let (animated, still) = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            (entities.create(), entities.create())
+        };
+        world
+            .run_system(move |mut tile_layers: CompMut<TileLayer>| {
+                let mut layer = TileLayer::new(UVec2::ONE, Vec2::splat(16.0), default());
+                tile_layers.insert(still, layer.clone());
+                layer
+                    .animations
+                    .insert(0, TileAnimation::new([0, 1, 2], 10.0));
+                tile_layers.insert(animated, layer);
+            })
+            .unwrap();
+
+        // Advancing the time by a fixed step adds up to exactly the same time.
+        for _ in 0..50 {
+            world
+                .resources
+                .get::<Time>()
+                .borrow_mut()
+                .advance_exact(Duration::from_millis(20));
+            world.run_system(tile_animation_system).unwrap();
+        }
+        world
+            .run_system(move |tile_layers: Comp<TileLayer>| {
+                let layer = tile_layers.get(animated).unwrap();
+                assert_eq!(layer.animation_time, Duration::from_secs(1));
+                assert_eq!(layer.animated_idx(0), 1);
+                assert_eq!(
+                    tile_layers.get(still).unwrap().animation_time,
+                    Duration::ZERO
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn bulk_tile_edits() {
+        let mut entities = Entities::default();
+        let (grass, dirt) = (entities.create(), entities.create());
+        let mut layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+
+        layer.set_many([
+            (UVec2::new(0, 0), Some(grass)),
+            (UVec2::new(3, 2), Some(dirt)),
+        ]);
+        assert_eq!(layer.get(UVec2::new(0, 0)), Some(grass));
+        assert_eq!(layer.get(UVec2::new(3, 2)), Some(dirt));
+
+        // The part of the rectangle that is out of range is left out.
+        layer.fill_rect(UVec2::new(2, 1), UVec2::new(10, 10), Some(grass));
+        assert_eq!(layer.row(0).unwrap(), [Some(grass), None, None, None]);
+        assert_eq!(
+            layer.row(1).unwrap(),
+            [None, None, Some(grass), Some(grass)]
+        );
+        assert_eq!(
+            layer.row(2).unwrap(),
+            [None, None, Some(grass), Some(grass)]
+        );
+        assert!(layer.row(3).is_none());
+        layer.fill_rect(UVec2::new(5, 0), UVec2::new(10, 10), Some(dirt));
+        layer.fill_rect(UVec2::new(1, 1), UVec2::new(1, 3), Some(dirt));
+        assert_eq!(
+            layer.row(1).unwrap(),
+            [None, None, Some(grass), Some(grass)]
+        );
+
+        layer.row_mut(0).unwrap().fill(Some(dirt));
+        for tile in layer.col_mut(3) {
+            *tile = None;
+        }
+        assert_eq!(
+            layer.row(0).unwrap(),
+            [Some(dirt), Some(dirt), Some(dirt), None]
+        );
+        assert_eq!(
+            layer.col(2).copied().collect::<Vec<_>>(),
+            [Some(dirt), Some(grass), Some(grass)]
+        );
+        assert_eq!(layer.col(4).count(), 0);
+    }
+
+    #[test]
+    fn edit_rect_outside_of_layer() {
+        let mut entities = Entities::default();
+        let grass = entities.create();
+        let mut layer = TileLayer::new(UVec2::new(4, 4), Vec2::splat(16.0), default());
+
+        // Rectangles that are entirely to the right of or below the layer don't change anything.
+        for (min, max) in [
+            (UVec2::new(6, 3), UVec2::new(8, 4)),
+            (UVec2::new(1, 5), UVec2::new(3, 7)),
+            (UVec2::new(5, 5), UVec2::new(6, 6)),
+        ] {
+            layer.fill_rect(min, max, Some(grass));
+            layer.fill_meta_rect(min, max, 1);
+            layer.fill_rect_tint(min, max, Color::RED);
+            assert_eq!(layer.iter_meta_in_rect(min, max).count(), 0);
+        }
+        assert!(layer.tiles.iter().all(|tile| tile.is_none()));
+        assert!(layer.meta.iter().all(|&meta| meta == 0));
+        assert!(layer.tints.iter().all(|&tint| tint == Color::WHITE));
+    }
+
+    #[test]
+    fn tile_meta() {
+        const SOLID: u16 = 1;
+        const LADDER: u16 = 2;
+        let mut layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+
+        layer.fill_meta_rect(UVec2::new(0, 2), UVec2::new(10, 3), SOLID);
+        layer.set_meta(UVec2::new(1, 1), LADDER);
+        assert_eq!(layer.get_meta(UVec2::new(3, 2)), SOLID);
+        assert_eq!(layer.get_meta(UVec2::new(1, 1)), LADDER);
+        assert_eq!(layer.get_meta(UVec2::new(4, 1)), 0);
+
+        let meta = layer
+            .iter_meta_in_rect(UVec2::new(1, 1), UVec2::new(3, 5))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            meta,
+            [
+                (UVec2::new(1, 1), LADDER),
+                (UVec2::new(1, 2), SOLID),
+                (UVec2::new(2, 2), SOLID),
+            ]
+        );
+
+        // The metadata keeps its position when the layer is resized.
+        let mut entities = Entities::default();
+        let crate_tile = entities.create();
+        layer.set(UVec2::new(1, 1), Some(crate_tile));
+        layer.resize(UVec2::new(2, 4));
+        assert_eq!(layer.tiles.len(), 8);
+        assert_eq!(layer.meta.len(), 8);
+        assert_eq!(layer.get(UVec2::new(1, 1)), Some(crate_tile));
+        assert_eq!(layer.get_meta(UVec2::new(1, 1)), LADDER);
+        assert_eq!(layer.get_meta(UVec2::new(1, 2)), SOLID);
+        assert_eq!(layer.get_meta(UVec2::new(1, 3)), 0);
+    }
+
+    #[test]
+    fn tile_tints() {
+        let shadow = Color::rgb(0.5, 0.5, 0.5);
+        let sand = Color::rgb(1.0, 0.9, 0.6);
+        let mut layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+
+        // The tints aren't stored until a slot is tinted.
+        assert_eq!(layer.get_tint(UVec2::new(1, 1)), Color::WHITE);
+        layer.set_tint(UVec2::new(1, 1), Color::WHITE);
+        layer.fill_rect_tint(UVec2::ZERO, UVec2::new(4, 3), Color::WHITE);
+        assert!(layer.tints.is_empty());
+
+        layer.fill_rect_tint(UVec2::new(2, 0), UVec2::new(10, 2), shadow);
+        layer.set_tint(UVec2::new(0, 2), sand);
+        assert_eq!(layer.tints.len(), 12);
+        assert_eq!(layer.get_tint(UVec2::new(3, 1)), shadow);
+        assert_eq!(layer.get_tint(UVec2::new(1, 1)), Color::WHITE);
+        assert_eq!(layer.get_tint(UVec2::new(0, 2)), sand);
+        assert_eq!(layer.get_tint(UVec2::new(4, 1)), Color::WHITE);
+
+        // The tints belong to the slots, so the frames of an animated tile keep the tint.
+        layer.animations.insert(12, TileAnimation::new(12..16, 4.0));
+        for millis in [0, 300, 600] {
+            layer.animation_time = Duration::from_millis(millis);
+            assert_eq!(layer.get_tint(UVec2::new(2, 0)), shadow);
+        }
+
+        // The tints keep their positions when the layer is resized.
+        layer.resize(UVec2::new(3, 4));
+        assert_eq!(layer.tints.len(), 12);
+        assert_eq!(layer.get_tint(UVec2::new(2, 1)), shadow);
+        assert_eq!(layer.get_tint(UVec2::new(0, 2)), sand);
+        assert_eq!(layer.get_tint(UVec2::new(0, 3)), Color::WHITE);
+
+        layer.clear_tints();
+        assert!(layer.tints.is_empty());
+        assert_eq!(layer.get_tint(UVec2::new(2, 1)), Color::WHITE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_tint_out_of_range() {
+        let mut layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+        layer.set_tint(UVec2::new(0, 3), Color::RED);
+    }
+
+    #[test]
+    fn clone_tile_meta_and_tints_with_world() {
+        let mut world = World::new();
+        let entity = {
+            let entities = world.resources.get::<Entities>();
+            let mut entities = entities.borrow_mut();
+            entities.create()
+        };
+        world
+            .run_system(move |mut tile_layers: CompMut<TileLayer>| {
+                let mut layer = TileLayer::new(UVec2::splat(2), Vec2::splat(16.0), default());
+                layer.set_meta(UVec2::new(1, 0), 7);
+                layer.set_tint(UVec2::new(0, 1), Color::BLUE);
+                tile_layers.insert(entity, layer);
+            })
+            .unwrap();
+
+        let snapshot = world.clone();
+        world
+            .run_system(move |mut tile_layers: CompMut<TileLayer>| {
+                let layer = tile_layers.get_mut(entity).unwrap();
+                layer.set_meta(UVec2::new(1, 0), 0);
+                layer.clear_tints();
+            })
+            .unwrap();
+        let tile_layers = snapshot.components.get::<TileLayer>();
+        let tile_layers = tile_layers.borrow();
+        let layer = tile_layers.get(entity).unwrap();
+        assert_eq!(layer.get_meta(UVec2::new(1, 0)), 7);
+        assert_eq!(layer.get_tint(UVec2::new(0, 1)), Color::BLUE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_many_out_of_range() {
+        let mut layer = TileLayer::new(UVec2::new(4, 3), Vec2::splat(16.0), default());
+        layer.set_many([(UVec2::new(4, 0), None)]);
+    }
+
+    #[test]
+    fn tiled_gid_flips() {
+        let (id, flips) = TiledFlips::from_gid(0xa000_0007);
+        assert_eq!(id, 7);
+        assert_eq!(
+            flips,
+            TiledFlips {
+                horizontal: true,
+                vertical: false,
+                diagonal: true,
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tile_serde_round_trip() {
+        let tile = Tile {
+            idx: 3,
+            flip_x: true,
+            flip_y: false,
+            rotation: TileRotation::Clockwise270,
+        };
+        let yaml = serde_yaml::to_string(&tile).unwrap();
+        assert_eq!(serde_yaml::from_str::<Tile>(&yaml).unwrap(), tile);
+
+        let tile: Tile = serde_yaml::from_str("idx: 2\nrotation: 90").unwrap();
+        assert_eq!(tile.rotation, TileRotation::Clockwise90);
+        assert!(!tile.flip_x);
+        assert!(serde_yaml::from_str::<Tile>("rotation: 45").is_err());
+    }
 }
@@ -0,0 +1,43 @@
+//! Visibility component.
+
+use crate::prelude::*;
+
+/// Hides an entity without removing its renderable components.
+///
+/// Entities without a [`Visibility`] component are visible. Hiding and showing an entity only
+/// changes this component, so it's cheap to do every frame, such as to make a character blink
+/// after it has been hit.
+///
+/// This is respected by the [`Sprite`], [`AtlasSprite`], [`NinePatchSprite`], and [`TileLayer`]
+/// components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeUlid)]
+#[ulid = "01GQTBX2R6H9MD4KJ7WN3QZF5C"]
+#[repr(C)]
+pub struct Visibility {
+    /// Whether or not the entity is rendered.
+    pub visible: bool,
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self::VISIBLE
+    }
+}
+
+impl Visibility {
+    /// A visible entity.
+    pub const VISIBLE: Self = Self { visible: true };
+    /// A hidden entity.
+    pub const HIDDEN: Self = Self { visible: false };
+
+    /// Returns `true` if an entity with the given [`Visibility`] component, or without one, is
+    /// visible.
+    pub fn is_visible(visibility: Option<&Self>) -> bool {
+        visibility.map_or(true, |x| x.visible)
+    }
+
+    /// Toggle the visibility.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
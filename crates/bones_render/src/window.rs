@@ -0,0 +1,17 @@
+//! Window resource.
+
+use crate::prelude::*;
+
+/// Resource with information about the window that the game is rendered to.
+///
+/// This is updated by the renderer at the start of every frame.
+#[derive(Clone, Copy, Debug, Default, TypeUlid)]
+#[ulid = "01GQSR3N8K2HDW5Y7M4CJ6XBTV"]
+#[repr(C)]
+pub struct Window {
+    /// The size of the window in physical pixels.
+    pub size: UVec2,
+    /// The position of the cursor in physical pixels from the top-left corner of the window, or
+    /// `None` if the cursor isn't over the window.
+    pub cursor_position: Option<Vec2>,
+}
@@ -0,0 +1,127 @@
+//! Y-sorting for top-down games.
+
+use crate::prelude::*;
+
+/// Sorts an entity by its Y position, so that entities lower on the screen are rendered in front
+/// of the entities above them, such as in a top-down game.
+///
+/// The [`y_sort_system`] sets the Z position of the [`Transform`] of every entity with a [`YSort`]
+/// component to a value in the [`YSortBand`]. Entities without a [`YSort`] component keep their Z
+/// position, so sprites for the UI can be rendered in front of the sorted entities by placing
+/// them above the band.
+#[derive(Clone, Copy, Debug, Default, PartialEq, TypeUlid)]
+#[ulid = "01GQTDH8N5C3XW7KB2RJ9MVF4Q"]
+#[repr(C)]
+pub struct YSort {
+    /// The offset that is added to the Y position of the entity to get the Y position that it is
+    /// sorted by, such as to sort a character by its feet instead of its center.
+    pub offset: f32,
+}
+
+/// Resource with the range of Z positions that [`YSort`] entities are placed in.
+///
+/// The entities are spread evenly between [`min`][Self::min] and [`max`][Self::max], without
+/// reaching either of them, so other entities can be placed at the edges of the band.
+#[derive(Clone, Copy, Debug, PartialEq, TypeUlid)]
+#[ulid = "01GQTDJ3W8F6KM2RZ5XB9HC7NT"]
+#[repr(C)]
+pub struct YSortBand {
+    /// The Z position of the back of the band.
+    pub min: f32,
+    /// The Z position of the front of the band.
+    pub max: f32,
+}
+
+impl Default for YSortBand {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 100.0,
+        }
+    }
+}
+
+/// System that sets the Z positions of the [`YSort`] entities, so that entities with a lower Y
+/// position are rendered in front of entities with a higher one.
+///
+/// Entities with the same Y position are sorted by their entities, so the order is the same every
+/// time the system runs.
+///
+/// This isn't added to the [`SystemStages`] by default. Games should add it to
+/// [`CoreStage::PostUpdate`], after the entities have moved.
+pub fn y_sort_system(
+    mut sorted: Local<Vec<(f32, Entity)>>,
+    band: Res<YSortBand>,
+    entities: Res<Entities>,
+    y_sorts: Comp<YSort>,
+    mut transforms: CompMut<Transform>,
+) {
+    sorted.clear();
+    sorted.extend(
+        entities
+            .iter_with((&y_sorts, &transforms))
+            .map(|(entity, (y_sort, transform))| (transform.translation.y + y_sort.offset, entity)),
+    );
+    // The entities at the top are at the back of the band.
+    sorted.sort_unstable_by(|a, b| b.0.total_cmp(&a.0).then(a.1.index().cmp(&b.1.index())));
+
+    let step = (band.max - band.min) / (sorted.len() + 1) as f32;
+    for (i, &(_, entity)) in sorted.iter().enumerate() {
+        let z = band.min + step * (i + 1) as f32;
+        let mut transform = transforms.get_mut(entity).unwrap();
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn y_sort() {
+        let mut world = World::new();
+        world.resources.insert(YSortBand {
+            min: 10.0,
+            max: 20.0,
+        });
+        let [high, low, feet, tie, ui] = world
+            .run_system(
+                |mut entities: ResMut<Entities>,
+                 mut y_sorts: CompMut<YSort>,
+                 mut transforms: CompMut<Transform>| {
+                    let mut spawn = |y: f32, y_sort: Option<YSort>| {
+                        let entity = entities.create();
+                        transforms
+                            .insert(entity, Transform::from_translation(Vec3::new(0.0, y, 50.0)));
+                        if let Some(y_sort) = y_sort {
+                            y_sorts.insert(entity, y_sort);
+                        }
+                        entity
+                    };
+                    [
+                        spawn(100.0, Some(YSort::default())),
+                        spawn(-100.0, Some(YSort::default())),
+                        // Sorted by its feet, below the entity with the same Y position.
+                        spawn(100.0, Some(YSort { offset: -20.0 })),
+                        spawn(-100.0, Some(YSort::default())),
+                        spawn(0.0, None),
+                    ]
+                },
+            )
+            .unwrap();
+
+        world.run_system(y_sort_system).unwrap();
+        let transforms = world.components.get::<Transform>();
+        let transforms = transforms.borrow();
+        let z = |entity| transforms.get(entity).unwrap().translation.z;
+        assert_eq!(z(high), 12.0);
+        assert_eq!(z(feet), 14.0);
+        // Entities at the same Y position are sorted by their entities.
+        assert_eq!(z(low), 16.0);
+        assert_eq!(z(tie), 18.0);
+        // Entities that aren't sorted keep their Z position.
+        assert_eq!(z(ui), 50.0);
+    }
+}
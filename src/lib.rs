@@ -10,6 +10,10 @@ pub use {
 pub mod prelude {
     pub use crate::{asset::prelude::*, ecs::prelude::*, input::prelude::*, render::prelude::*};
 
+    // Both the ECS and the input crate have a `Time` resource, prefer the one that is updated by
+    // the core stages.
+    pub use crate::ecs::prelude::Time;
+
     #[cfg(feature = "bevy")]
     pub use crate::bevy_utils::*;
 }